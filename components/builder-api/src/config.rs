@@ -14,10 +14,15 @@
 
 //! Configuration for a Habitat Builder-API service
 
+use std::collections::BTreeMap;
 use std::net;
 
-use hab_net::config::{GitHubOAuth, RouteAddrs};
+use hab_net::config::{GITHUB_DEFAULT_SCOPES, GITHUB_RENAMED_KEYS, GITHUB_WEB_URL, GitHubApps,
+                      GitHubCfg, GitHubClientOptions, GitHubOAuth, GitLabCfg, HttpCfg, OAuth,
+                      RouteAddrs, StubAuthCfg, migrate_keys};
 use hab_core::config::{ConfigFile, ParseInto};
+use hab_net::oauth::audit::{DEFAULT_AUDIT_LOG_KEEP, DEFAULT_AUDIT_LOG_MAX_BYTES};
+use hab_net::session::token::SessionTokenOptions;
 use depot;
 use toml;
 
@@ -44,13 +49,79 @@ pub struct Config {
     /// List of net addresses for routing servers to connect to
     pub routers: Vec<net::SocketAddrV4>,
     /// URL to GitHub API
-    pub github_url: String,
+    pub github_api_url: String,
+    /// URL to the GitHub web frontend which hosts the OAuth endpoints
+    pub github_web_url: String,
+    /// OAuth scopes which must be granted to a GitHub access token
+    pub github_scopes: Vec<String>,
     /// Client identifier used for GitHub API requests
     pub github_client_id: String,
     /// Client secret used for GitHub API requests
     pub github_client_secret: String,
+    /// Path to a file containing the client secret used for GitHub API requests
+    pub github_client_secret_file: Option<String>,
+    /// Retry, timeout, and caching behaviour of the GitHub client
+    pub github_options: GitHubClientOptions,
+    /// GitHub organization whose members, or whose `github_admin_team` members, are admins
+    pub github_admin_org: Option<String>,
+    /// GitHub team within `github_admin_org` whose members are admins
+    pub github_admin_team: Option<String>,
+    /// GitHub team within `github_admin_org` whose members may run builds
+    pub github_builder_team: Option<String>,
+    /// GitHub team within `github_admin_org` whose members may create origins
+    pub github_origin_creator_team: Option<String>,
+    /// GitHub organization a user must be a member of to log in
+    pub github_required_org: Option<String>,
+    /// Access token, granted `read:org`, which lists the teams of `github_admin_org`
+    pub github_service_token: Option<String>,
+    /// GitHub logins, compared case-insensitively, of users who are operators
+    pub github_admin_logins: Vec<String>,
+    /// GitHub ids of users who are operators
+    pub github_admin_ids: Vec<u64>,
+    /// Make GitHub Enterprise site administrators operators
+    pub github_honor_site_admin: bool,
+    /// GitHub logins, compared case-insensitively, of users who may log in
+    pub github_allow_logins: Vec<String>,
+    /// GitHub logins, compared case-insensitively, of users who may never log in
+    pub github_deny_logins: Vec<String>,
+    /// GitHub organizations whose members may log in
+    pub github_allow_orgs: Vec<String>,
+    /// Only let members of `github_allow_orgs` log in
+    pub github_require_org_membership: bool,
+    /// Create sessions, flagged `PARTIAL_PROFILE`, for users whose emails or memberships couldn't
+    /// be fetched because GitHub is having trouble. If not set such logins fail.
+    pub github_allow_partial_sessions: bool,
+    /// Additional GitHub OAuth applications, keyed by name, for frontends which require their own
+    /// callback URL
+    pub github_apps: BTreeMap<String, GitHubCfg>,
+    /// Proxy, TLS, and timeout settings of outbound HTTP requests
+    pub http: HttpCfg,
+    /// Stub identity provider used in place of GitHub. If not set GitHub authentication is used.
+    pub stub_auth: Option<StubAuthCfg>,
+    /// GitLab instance users log in with instead of GitHub. If not set GitHub authentication is
+    /// used.
+    pub gitlab: Option<GitLabCfg>,
     /// Path to UI files to host over HTTP. If not set the UI will be disabled.
     pub ui_root: Option<String>,
+    /// Deployment salt used to derive pseudonymous analytics identifiers. If not set no analytics
+    /// events are recorded.
+    pub analytics_salt: Option<String>,
+    /// File login attempts are recorded in, one line of JSON each. If not set no audit trail is
+    /// kept.
+    pub audit_log: Option<String>,
+    /// Size in bytes past which the audit log is rotated
+    pub audit_log_max_bytes: u64,
+    /// Rotated audit logs to keep
+    pub audit_log_keep: usize,
+    /// Keys internal session tokens are signed with. Tokens are minted with the first and
+    /// verified with any, see `hab_net::session::token`. If not set no tokens are minted and
+    /// every request is authenticated with the session service.
+    pub session_keys: Vec<String>,
+    /// Lifetime of internal session tokens
+    pub session_token: SessionTokenOptions,
+    /// Configuration file the service was started with. If set, changes to its `[cfg.github]`
+    /// section are applied to the running GitHub clients, see `GitHubCfgWatcher`.
+    pub config_file: Option<String>,
 }
 
 impl Config {
@@ -67,10 +138,39 @@ impl Default for Config {
             http_addr: net::SocketAddrV4::new(net::Ipv4Addr::new(0, 0, 0, 0), 9636),
             routers: vec![net::SocketAddrV4::new(net::Ipv4Addr::new(127, 0, 0, 1), 5562)],
             depot: depot::Config::default(),
-            github_url: GITHUB_URL.to_string(),
+            github_api_url: GITHUB_URL.to_string(),
+            github_web_url: GITHUB_WEB_URL.to_string(),
+            github_scopes: GITHUB_DEFAULT_SCOPES.iter().map(|s| s.to_string()).collect(),
             github_client_id: DEV_GITHUB_CLIENT_ID.to_string(),
             github_client_secret: DEV_GITHUB_CLIENT_SECRET.to_string(),
+            github_client_secret_file: None,
+            github_options: GitHubClientOptions::default(),
+            github_admin_org: None,
+            github_admin_team: None,
+            github_builder_team: None,
+            github_origin_creator_team: None,
+            github_required_org: None,
+            github_service_token: None,
+            github_admin_logins: vec![],
+            github_admin_ids: vec![],
+            github_honor_site_admin: false,
+            github_allow_logins: vec![],
+            github_deny_logins: vec![],
+            github_allow_orgs: vec![],
+            github_require_org_membership: false,
+            github_allow_partial_sessions: false,
+            github_apps: BTreeMap::new(),
+            http: HttpCfg::default(),
+            stub_auth: None,
+            gitlab: None,
             ui_root: None,
+            analytics_salt: None,
+            audit_log: None,
+            audit_log_max_bytes: DEFAULT_AUDIT_LOG_MAX_BYTES,
+            audit_log_keep: DEFAULT_AUDIT_LOG_KEEP,
+            session_keys: vec![],
+            session_token: SessionTokenOptions::default(),
+            config_file: None,
         }
     }
 }
@@ -79,6 +179,7 @@ impl ConfigFile for Config {
     type Error = Error;
 
     fn from_toml(toml: toml::Value) -> Result<Self> {
+        let toml = try!(migrate_keys(&toml, "cfg.github", GITHUB_RENAMED_KEYS));
         let mut cfg = Config::default();
         let mut pkg_path = String::new();
         if try!(toml.parse_into("pkg.svc_static_path", &mut pkg_path)) {
@@ -88,17 +189,73 @@ impl ConfigFile for Config {
         try!(toml.parse_into("cfg.router_addrs", &mut cfg.routers));
         try!(toml.parse_into("pkg.svc_data_path", &mut cfg.depot.path));
         try!(toml.parse_into("cfg.depot.datastore_addr", &mut cfg.depot.datastore_addr));
-        try!(toml.parse_into("cfg.github.url", &mut cfg.github_url));
-        try!(toml.parse_into("cfg.github.url", &mut cfg.depot.github_url));
-        if !try!(toml.parse_into("cfg.github.client_id", &mut cfg.github_client_id)) {
+        if let Some(http) = toml.lookup("cfg.http") {
+            cfg.http = try!(HttpCfg::from_toml(http));
+            cfg.depot.http = cfg.http.clone();
+        }
+        if let Some(auth) = toml.lookup("cfg.auth") {
+            cfg.stub_auth = try!(StubAuthCfg::from_toml(auth));
+            cfg.depot.stub_auth = cfg.stub_auth.clone();
+            cfg.gitlab = try!(GitLabCfg::from_toml(auth));
+        }
+        let github_auth = cfg.stub_auth.is_none() && cfg.gitlab.is_none();
+        if let Some(github) = toml.lookup("cfg.github") {
+            // The default application is found like a named one, so its profile applies too
+            let endpoints = try!(GitHubCfg::endpoints_from_toml(github));
+            cfg.github_api_url = endpoints.api_url;
+            cfg.github_web_url = endpoints.web_url;
+            cfg.github_scopes = endpoints.scopes;
+        }
+        cfg.depot.github_api_url = cfg.github_api_url.clone();
+        if !try!(toml.parse_into("cfg.github.client_id", &mut cfg.github_client_id)) &&
+           github_auth {
             return Err(Error::RequiredConfigField("github.client_id"));
         }
         try!(toml.parse_into("cfg.github.client_id", &mut cfg.depot.github_client_id));
-        if !try!(toml.parse_into("cfg.github.client_secret", &mut cfg.github_client_secret)) {
+        try!(toml.parse_into("cfg.github.client_secret_file",
+                             &mut cfg.github_client_secret_file));
+        try!(toml.parse_into("cfg.github.client_secret_file",
+                             &mut cfg.depot.github_client_secret_file));
+        if !try!(toml.parse_into("cfg.github.client_secret", &mut cfg.github_client_secret)) &&
+           cfg.github_client_secret_file.is_none() && github_auth {
             return Err(Error::RequiredConfigField("github.client_secret"));
         }
         try!(toml.parse_into("cfg.github.client_secret",
                              &mut cfg.depot.github_client_secret));
+        try!(toml.parse_into("cfg.github.admin_org", &mut cfg.github_admin_org));
+        try!(toml.parse_into("cfg.github.admin_team", &mut cfg.github_admin_team));
+        try!(toml.parse_into("cfg.github.builder_team", &mut cfg.github_builder_team));
+        try!(toml.parse_into("cfg.github.origin_creator_team",
+                             &mut cfg.github_origin_creator_team));
+        try!(toml.parse_into("cfg.github.required_org", &mut cfg.github_required_org));
+        try!(toml.parse_into("cfg.github.service_token", &mut cfg.github_service_token));
+        cfg.github_admin_logins = try!(str_list(toml, "github.admin_logins"));
+        try!(toml.parse_into("cfg.github.admin_ids", &mut cfg.github_admin_ids));
+        try!(toml.parse_into("cfg.github.honor_site_admin",
+                             &mut cfg.github_honor_site_admin));
+        cfg.github_allow_logins = try!(str_list(toml, "github.allow_logins"));
+        cfg.github_deny_logins = try!(str_list(toml, "github.deny_logins"));
+        cfg.github_allow_orgs = try!(str_list(toml, "github.allow_orgs"));
+        try!(toml.parse_into("cfg.github.require_org_membership",
+                             &mut cfg.github_require_org_membership));
+        try!(toml.parse_into("cfg.github.allow_partial_sessions",
+                             &mut cfg.github_allow_partial_sessions));
+        if let Some(github) = toml.lookup("cfg.github") {
+            cfg.github_options = try!(GitHubClientOptions::from_toml(github));
+            cfg.depot.github_options = cfg.github_options.clone();
+        }
+        try!(toml.parse_into("cfg.analytics.salt", &mut cfg.analytics_salt));
+        try!(toml.parse_into("cfg.audit.path", &mut cfg.audit_log));
+        try!(toml.parse_into("cfg.audit.max_bytes", &mut cfg.audit_log_max_bytes));
+        try!(toml.parse_into("cfg.audit.keep", &mut cfg.audit_log_keep));
+        cfg.session_keys = try!(str_list(toml, "session.keys"));
+        try!(toml.parse_into("cfg.session.ttl", &mut cfg.session_token.ttl));
+        try!(toml.parse_into("cfg.session.refresh_after",
+                             &mut cfg.session_token.refresh_after));
+        try!(toml.parse_into("cfg.session.clock_skew", &mut cfg.session_token.clock_skew));
+        if let Some(apps) = toml.lookup("cfg.github.apps") {
+            cfg.github_apps = try!(GitHubCfg::apps_from_toml(apps));
+        }
         Ok(cfg)
     }
 }
@@ -110,8 +267,8 @@ impl RouteAddrs for Config {
 }
 
 impl GitHubOAuth for Config {
-    fn github_url(&self) -> &str {
-        &self.github_url
+    fn github_api_url(&self) -> &str {
+        &self.github_api_url
     }
 
     fn github_client_id(&self) -> &str {
@@ -121,4 +278,134 @@ impl GitHubOAuth for Config {
     fn github_client_secret(&self) -> &str {
         &self.github_client_secret
     }
+
+    fn github_client_secret_file(&self) -> Option<&str> {
+        self.github_client_secret_file.as_ref().map(|f| f.as_str())
+    }
+
+    fn github_web_url(&self) -> &str {
+        &self.github_web_url
+    }
+
+    fn github_scopes(&self) -> Vec<String> {
+        self.github_scopes.clone()
+    }
+
+    fn github_admin_org(&self) -> Option<&str> {
+        self.github_admin_org.as_ref().map(|o| o.as_str())
+    }
+
+    fn github_admin_team(&self) -> Option<&str> {
+        self.github_admin_team.as_ref().map(|t| t.as_str())
+    }
+
+    fn github_builder_team(&self) -> Option<&str> {
+        self.github_builder_team.as_ref().map(|t| t.as_str())
+    }
+
+    fn github_origin_creator_team(&self) -> Option<&str> {
+        self.github_origin_creator_team.as_ref().map(|t| t.as_str())
+    }
+
+    fn github_required_org(&self) -> Option<&str> {
+        self.github_required_org.as_ref().map(|o| o.as_str())
+    }
+
+    fn github_admin_logins(&self) -> Vec<String> {
+        self.github_admin_logins.clone()
+    }
+
+    fn github_admin_ids(&self) -> Vec<u64> {
+        self.github_admin_ids.clone()
+    }
+
+    fn github_honor_site_admin(&self) -> bool {
+        self.github_honor_site_admin
+    }
+
+    fn github_allow_logins(&self) -> Vec<String> {
+        self.github_allow_logins.clone()
+    }
+
+    fn github_deny_logins(&self) -> Vec<String> {
+        self.github_deny_logins.clone()
+    }
+
+    fn github_allow_orgs(&self) -> Vec<String> {
+        self.github_allow_orgs.clone()
+    }
+
+    fn github_require_org_membership(&self) -> bool {
+        self.github_require_org_membership
+    }
+
+    fn github_service_token(&self) -> Option<&str> {
+        self.github_service_token.as_ref().map(|t| t.as_str())
+    }
+
+    fn github_http(&self) -> HttpCfg {
+        self.http.clone()
+    }
+}
+
+impl OAuth for Config {
+    fn oauth_provider(&self) -> &str {
+        self.gitlab.as_ref().map_or("github", |gitlab| gitlab.oauth_provider())
+    }
+
+    fn oauth_url(&self) -> &str {
+        self.gitlab.as_ref().map_or(self.github_api_url.as_str(), |gitlab| gitlab.oauth_url())
+    }
+
+    fn oauth_client_id(&self) -> &str {
+        self.gitlab
+            .as_ref()
+            .map_or(self.github_client_id.as_str(), |gitlab| gitlab.oauth_client_id())
+    }
+
+    fn oauth_client_secret(&self) -> &str {
+        self.gitlab
+            .as_ref()
+            .map_or(self.github_client_secret.as_str(), |gitlab| gitlab.oauth_client_secret())
+    }
+
+    fn oauth_redirect_uri(&self) -> Option<&str> {
+        self.gitlab.as_ref().and_then(|gitlab| gitlab.oauth_redirect_uri())
+    }
+
+    fn oauth_http(&self) -> HttpCfg {
+        self.http.clone()
+    }
+}
+
+impl GitHubApps for Config {
+    fn github_apps(&self) -> &BTreeMap<String, GitHubCfg> {
+        &self.github_apps
+    }
+
+    fn github_client_options(&self) -> &GitHubClientOptions {
+        &self.github_options
+    }
+}
+
+/// Parse the list of strings found at `cfg.<field>`. Unlike `admin_logins` an entry which isn't a
+/// string is an error, since dropping it could, for example, let in a user an access list was
+/// meant to keep out.
+fn str_list(toml: &toml::Value, field: &'static str) -> Result<Vec<String>> {
+    let value = match toml.lookup(&format!("cfg.{}", field)) {
+        Some(value) => value,
+        None => return Ok(vec![]),
+    };
+    let entries = match value.as_slice() {
+        Some(entries) => entries,
+        None => return Err(Error::RequiredConfigField(field)),
+    };
+    let mut list = vec![];
+    for entry in entries.iter() {
+        match entry.as_str() {
+            Some(entry) => list.push(entry.to_string()),
+            None => return Err(Error::RequiredConfigField(field)),
+        }
+    }
+    Ok(list)
 }
@@ -18,6 +18,7 @@ use std::io;
 use std::result;
 
 use hab_core;
+use hab_net;
 use depot;
 use hyper;
 use protobuf;
@@ -29,6 +30,7 @@ pub enum Error {
     BadPort(String),
     Depot(depot::Error),
     HabitatCore(hab_core::Error),
+    HabitatNet(hab_net::Error),
     HyperError(hyper::error::Error),
     HTTP(hyper::status::StatusCode),
     IO(io::Error),
@@ -46,6 +48,7 @@ impl fmt::Display for Error {
             Error::BadPort(ref e) => format!("{} is an invalid port. Valid range 1-65535.", e),
             Error::Depot(ref e) => format!("{}", e),
             Error::HabitatCore(ref e) => format!("{}", e),
+            Error::HabitatNet(ref e) => format!("{}", e),
             Error::HyperError(ref e) => format!("{}", e),
             Error::HTTP(ref e) => format!("{}", e),
             Error::IO(ref e) => format!("{}", e),
@@ -66,6 +69,7 @@ impl error::Error for Error {
             Error::BadPort(_) => "Received an invalid port or a number outside of the valid range.",
             Error::Depot(ref err) => err.description(),
             Error::HabitatCore(ref err) => err.description(),
+            Error::HabitatNet(ref err) => err.description(),
             Error::HyperError(ref err) => err.description(),
             Error::HTTP(_) => "Non-200 HTTP response.",
             Error::IO(ref err) => err.description(),
@@ -83,6 +87,12 @@ impl From<hab_core::Error> for Error {
     }
 }
 
+impl From<hab_net::Error> for Error {
+    fn from(err: hab_net::Error) -> Error {
+        Error::HabitatNet(err)
+    }
+}
+
 impl From<depot::Error> for Error {
     fn from(err: depot::Error) -> Error {
         Error::Depot(err)
@@ -15,133 +15,383 @@
 //! A collection of handlers for the HTTP server's router
 
 use std::result;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use hab_net;
+use hab_net::analytics::{self, AnalyticsId};
 use hab_net::routing::Broker;
-use hab_net::oauth::github::GitHubClient;
+use hab_net::oauth::LoginOutcome;
+use hab_net::oauth::audit::Auditor;
+use hab_net::oauth::github::GitHubClients;
+use hab_net::oauth::health::Health;
+use hab_net::oauth::identity::Identity;
+use hab_net::oauth::policy::TeamPolicies;
+use hab_net::session::token::SessionClaims;
 use iron::prelude::*;
 use iron::status;
 use iron::headers::{Authorization, Bearer};
 use protobuf;
 use protocol::jobsrv::{Job, JobCreate, JobGet};
-use protocol::sessionsrv::{OAuthProvider, Session, SessionCreate, SessionGet};
+use protocol::sessionsrv::{self, Session, SessionCreate, SessionDelete, SessionGet,
+                           SessionValidated};
 use protocol::vault::*;
 use protocol::net::{self, NetError, ErrCode};
 use router::Router;
-use rustc_serialize::json::{self, ToJson};
+use rustc_serialize::json::{self, Json, ToJson};
+use urlencoded::UrlEncodedQuery;
 
+use super::{OAuthClientIds, OAuthClients, RenewedSessionToken, SessionTokens,
+            SESSION_TOKEN_HEADER};
 use super::super::server::ZMQ_CONTEXT;
 
+/// Returns the session of the request. A valid internal session token whose privileges are
+/// fresh is trusted as it is; otherwise the session of the request's bearer token is looked up,
+/// and its token checked again with GitHub if it is stale, see `refresh_session`. A session token
+/// which wasn't trusted is then renewed with the looked up session, see `renew_session_token`.
 pub fn authenticate(req: &mut Request) -> result::Result<Session, Response> {
-    match req.headers.get::<Authorization<Bearer>>() {
-        Some(&Authorization(Bearer { ref token })) => {
+    let claims = token_claims(req);
+    if let Some(session) = claims.as_ref().and_then(|claims| token_session(req, claims)) {
+        return Ok(session);
+    }
+    let token = match req.headers.get::<Authorization<Bearer>>() {
+        Some(&Authorization(Bearer { ref token })) => token.to_string(),
+        _ => return Err(Response::with(status::Unauthorized)),
+    };
+    let mut conn = Broker::connect(&**ZMQ_CONTEXT).unwrap();
+    let mut request = SessionGet::new();
+    request.set_token(token);
+    if let Some(ids) = req.extensions.get::<OAuthClientIds>() {
+        request.set_client_ids(protobuf::RepeatedField::from_vec((**ids).clone()));
+    }
+    conn.route(&request).unwrap();
+    match conn.recv() {
+        Ok(rep) => {
+            match rep.get_message_id() {
+                "Session" => {
+                    let session = protobuf::parse_from_bytes(rep.get_body()).unwrap();
+                    let session = try!(refresh_session(req, session));
+                    if let Some(ref claims) = claims {
+                        renew_session_token(req, claims, &session);
+                    }
+                    Ok(session)
+                }
+                "NetError" => {
+                    let err: NetError = protobuf::parse_from_bytes(rep.get_body()).unwrap();
+                    Err(render_net_error(&err))
+                }
+
+                _ => unreachable!("unexpected msg: {:?}", rep),
+            }
+        }
+        Err(e) => {
+            error!("session get, err={:?}", e);
+            Err(Response::with(status::InternalServerError))
+        }
+    }
+}
+
+/// Checks the access token of a looked up session again if it hasn't been validated recently, see
+/// `GitHubClients::refresh_session`, and stores the outcome with the session. A session whose
+/// token was revoked, or issued to an OAuth application which is no longer configured, is
+/// expired. If GitHub can't be asked the session is trusted as it is until it can.
+fn refresh_session(req: &Request, session: Session) -> result::Result<Session, Response> {
+    let clients = match req.extensions.get::<OAuthClients>() {
+        Some(clients) => clients.clone(),
+        None => return Ok(session),
+    };
+    match clients.refresh_session(&session) {
+        Ok(None) => Ok(session),
+        Ok(Some(record)) => {
             let mut conn = Broker::connect(&**ZMQ_CONTEXT).unwrap();
-            let mut request = SessionGet::new();
-            request.set_token(token.to_string());
-            conn.route(&request).unwrap();
+            conn.route(&SessionValidated::from(record)).unwrap();
             match conn.recv() {
                 Ok(rep) => {
                     match rep.get_message_id() {
-                        "Session" => {
-                            let session = protobuf::parse_from_bytes(rep.get_body()).unwrap();
-                            Ok(session)
-                        }
+                        "Session" => Ok(protobuf::parse_from_bytes(rep.get_body()).unwrap()),
                         "NetError" => {
                             let err: NetError = protobuf::parse_from_bytes(rep.get_body()).unwrap();
                             Err(render_net_error(&err))
                         }
-
                         _ => unreachable!("unexpected msg: {:?}", rep),
                     }
                 }
                 Err(e) => {
-                    error!("session get, err={:?}", e);
-                    Err(Response::with(status::InternalServerError))
+                    error!("session validated, err={:?}", e);
+                    Ok(session)
                 }
             }
         }
-        _ => Err(Response::with(status::Unauthorized)),
+        Err(e @ hab_net::Error::TokenRevoked) |
+        Err(e @ hab_net::Error::TokenClientMismatch(_)) => {
+            info!("expiring session of {}, err={}", session.get_name(), e);
+            let mut conn = Broker::connect(&**ZMQ_CONTEXT).unwrap();
+            let mut request = SessionDelete::new();
+            request.set_token(session.get_token().to_string());
+            conn.route(&request).unwrap();
+            if let Err(e) = conn.recv() {
+                error!("session delete, err={:?}", e);
+            }
+            Err(render_net_error(&net::err(ErrCode::SESSION_EXPIRED, "rg:auth:10")))
+        }
+        Err(e) => {
+            warn!("token of {} couldn't be checked again, keeping the session, err={}",
+                  session.get_name(),
+                  e);
+            Ok(session)
+        }
     }
 }
 
-pub fn session_create(req: &mut Request, github: &GitHubClient) -> IronResult<Response> {
+/// Returns the claims of the request's internal session token, or `None` if it carries none, or
+/// one which is invalid or has expired.
+fn token_claims(req: &Request) -> Option<SessionClaims> {
+    let signer = match req.extensions.get::<SessionTokens>() {
+        Some(signer) => signer.clone(),
+        None => return None,
+    };
+    let token = match req.headers.get_raw(SESSION_TOKEN_HEADER) {
+        Some(values) if values.len() == 1 => String::from_utf8_lossy(&values[0]).into_owned(),
+        _ => return None,
+    };
+    match signer.verify(&token) {
+        Ok(claims) => Some(claims),
+        Err(e) => {
+            debug!("session token not accepted, err={}", e);
+            None
+        }
+    }
+}
+
+/// Returns the session described by the claims of the request's internal session token, or
+/// `None` if the token was revoked, needs its privileges refreshed, or wasn't minted for the
+/// request's bearer token.
+fn token_session(req: &Request, claims: &SessionClaims) -> Option<Session> {
+    let signer = match req.extensions.get::<SessionTokens>() {
+        Some(signer) => signer.clone(),
+        None => return None,
+    };
+    if signer.is_revoked(claims) {
+        debug!("session token of {} was revoked", claims.login);
+        return None;
+    }
+    if signer.needs_refresh(claims) {
+        debug!("session token of {} needs its privileges refreshed", claims.login);
+        return None;
+    }
+    match req.headers.get::<Authorization<Bearer>>() {
+        Some(&Authorization(Bearer { ref token })) => claims.session(token),
+        _ => None,
+    }
+}
+
+/// Hands out a renewed session token with the response, see `RenewedSessionToken`, if the
+/// request's session token was revoked or needed its privileges refreshed and its session was
+/// looked up instead. Revoked tokens are renewed too, as the session they were minted for
+/// outlived the logout of another session of the account.
+fn renew_session_token(req: &mut Request, claims: &SessionClaims, session: &Session) {
+    let signer = match req.extensions.get::<SessionTokens>() {
+        Some(signer) => signer.clone(),
+        None => return,
+    };
+    if !signer.is_revoked(claims) && !signer.needs_refresh(claims) {
+        return;
+    }
+    if let Some(token) = signer.renew(claims, session) {
+        req.extensions.insert::<RenewedSessionToken>(token);
+    }
+}
+
+pub fn session_create(req: &mut Request,
+                      clients: &GitHubClients,
+                      policies: &TeamPolicies,
+                      auditor: &Auditor,
+                      allow_partial: bool,
+                      analytics_salt: Option<&str>)
+                      -> IronResult<Response> {
+    let app = extract_query_value("app", req);
+    let app = app.as_ref().map(|a| a.as_str());
+    let (github, policy) = match (clients.get(app), policies.get(app)) {
+        (Ok(github), Ok(policy)) => (github, policy),
+        (Err(e), _) | (_, Err(e)) => {
+            debug!("github client select, err={:?}", e);
+            return Ok(Response::with(status::BadRequest));
+        }
+    };
     let params = req.extensions.get::<Router>().unwrap();
     let code = match params.find("code") {
         Some(code) => code,
         _ => return Ok(Response::with(status::BadRequest)),
     };
-    match github.authenticate(code) {
-        Ok(token) => {
-            match github.user(&token) {
-                Ok(user) => {
-                    // Select primary email. If no primary email can be found, use any email. If no email
-                    // is associated with account return an access denied error.
-                    let email = match github.emails(&token) {
-                        Ok(ref emails) => {
-                            emails.iter().find(|e| e.primary).unwrap_or(&emails[0]).email.clone()
-                        }
-                        Err(_) => {
-                            let err = net::err(ErrCode::ACCESS_DENIED, "rg:auth:0");
-                            return Ok(render_net_error(&err));
-                        }
-                    };
-                    let mut conn = Broker::connect(&**ZMQ_CONTEXT).unwrap();
-                    let mut request = SessionCreate::new();
-                    request.set_token(token);
-                    request.set_extern_id(user.id);
-                    request.set_email(email);
-                    request.set_name(user.login);
-                    request.set_provider(OAuthProvider::GitHub);
-                    conn.route(&request).unwrap();
-                    match conn.recv() {
-                        Ok(rep) => {
-                            match rep.get_message_id() {
-                                "Session" => {
-                                    let token: Session = protobuf::parse_from_bytes(rep.get_body())
-                                        .unwrap();
-                                    let encoded = json::encode(&token.to_json()).unwrap();
-                                    Ok(Response::with((status::Ok, encoded)))
-                                }
-                                "NetError" => {
-                                    let err: NetError = protobuf::parse_from_bytes(rep.get_body())
-                                        .unwrap();
-                                    Ok(render_net_error(&err))
-                                }
-                                _ => unreachable!("unexpected msg: {:?}", rep),
-                            }
-                        }
-                        Err(e) => {
-                            error!("{:?}", e);
-                            Ok(Response::with(status::ServiceUnavailable))
-                        }
-                    }
-                }
-                Err(e @ hab_net::Error::JsonDecode(_)) => {
-                    debug!("github user get, err={:?}", e);
-                    let err = net::err(ErrCode::BAD_REMOTE_REPLY, "rg:auth:1");
-                    Ok(render_net_error(&err))
-                }
-                Err(e) => {
-                    debug!("github user get, err={:?}", e);
-                    let err = net::err(ErrCode::BUG, "rg:auth:2");
-                    Ok(render_net_error(&err))
-                }
+    let login = match github.login_audited(code, policy, auditor) {
+        Ok(LoginOutcome::Complete(login)) => login,
+        Ok(LoginOutcome::Partial(partial)) => {
+            for &(section, ref e) in partial.errors.iter() {
+                warn!("github login of {} is missing {:?}, err={}",
+                      partial.login.user.login,
+                      section,
+                      e);
             }
+            if !allow_partial {
+                let err = net::err(ErrCode::BAD_REMOTE_REPLY, "rg:auth:5");
+                return Ok(render_net_error(&err));
+            }
+            partial.login
+        }
+        Err(hab_net::Error::AccessDenied(reason)) => {
+            let err = net::err(ErrCode::ACCESS_DENIED, format!("rg:auth:6:{}", reason.code()));
+            return Ok(render_net_error(&err));
+        }
+        Err(e @ hab_net::Error::OrgAccessDenied(_)) => {
+            debug!("github memberships, err={:?}", e);
+            let err = net::err(ErrCode::ACCESS_DENIED, "rg:auth:7");
+            return Ok(render_net_error(&err));
         }
         Err(hab_net::Error::Auth(e)) => {
             debug!("github authentication, err={:?}", e);
             let err = net::err(ErrCode::REMOTE_REJECTED, e.error);
-            Ok(render_net_error(&err))
+            return Ok(render_net_error(&err));
         }
-        Err(e @ hab_net::Error::JsonDecode(_)) => {
-            debug!("github authentication, err={:?}", e);
+        Err(e @ hab_net::Error::JsonDecode(_)) |
+        Err(e @ hab_net::Error::InvalidLinkHeader(_)) |
+        Err(e @ hab_net::Error::HTTP(_)) => {
+            debug!("github login, err={:?}", e);
+            let err = net::err(ErrCode::BAD_REMOTE_REPLY, "rg:auth:1");
+            return Ok(render_net_error(&err));
+        }
+        Err(hab_net::Error::GitHubRateLimited { reset }) => {
+            warn!("github login rate limited, reset={}", reset);
+            return Ok(render_rate_limited(reset));
+        }
+        Err(hab_net::Error::GitHubTimeout(url)) => {
+            warn!("github login timed out, url={}", url);
+            return Ok(render_gateway_timeout());
+        }
+        // Such as the memberships of an org-gated login, which can't be left out
+        Err(ref e) if hab_net::oauth::github::is_transient_error(e) => {
+            warn!("github login, err={}", e);
             let err = net::err(ErrCode::BAD_REMOTE_REPLY, "rg:auth:1");
-            Ok(render_net_error(&err))
+            return Ok(render_net_error(&err));
+        }
+        Err(e @ hab_net::Error::GitHubAPI(_)) => {
+            debug!("github privileges, err={:?}", e);
+            let err = net::err(ErrCode::ACCESS_DENIED, "rg:auth:3");
+            return Ok(render_net_error(&err));
         }
         Err(e) => {
-            error!("github authentication, err={:?}", e);
+            error!("github login, err={:?}", e);
             let err = net::err(ErrCode::BUG, "rg:auth:0");
-            Ok(render_net_error(&err))
+            return Ok(render_net_error(&err));
+        }
+    };
+    let flags = login.flags;
+    if !flags.contains(sessionsrv::MEMBER) {
+        let err = net::err(ErrCode::ACCESS_DENIED, "rg:auth:4");
+        return Ok(render_net_error(&err));
+    }
+    let mut account = login.account;
+    let analytics_id = analytics_salt.and_then(|salt| account.analytics_id(salt));
+    let mut identity = Identity::from(&account);
+    let signer = req.extensions.get::<SessionTokens>().map(|signer| signer.clone());
+    let mut conn = Broker::connect(&**ZMQ_CONTEXT).unwrap();
+    let mut request = SessionCreate::from(login.record);
+    request.set_extern_id(account.get_extern_id());
+    request.set_avatar_url(account.take_avatar_url());
+    request.set_display_name(account.take_display_name());
+    request.set_email_verified(account.get_email_verified());
+    if account.has_email() {
+        request.set_email(account.take_email());
+    }
+    request.set_name(account.take_name());
+    request.set_provider(account.get_provider());
+    request.set_provider_host(account.take_provider_host());
+    request.set_feature_flags(flags);
+    conn.route(&request).unwrap();
+    match conn.recv() {
+        Ok(rep) => {
+            match rep.get_message_id() {
+                "Session" => {
+                    let token: Session = protobuf::parse_from_bytes(rep.get_body()).unwrap();
+                    if let Some(ref id) = analytics_id {
+                        analytics::login_event(id);
+                    }
+                    let mut body = token.to_json();
+                    if let (Some(signer), &mut Json::Object(ref mut fields)) = (signer, &mut body) {
+                        identity.account_id = Some(token.get_id());
+                        let session_token = signer.mint(&identity,
+                                                        &token.get_feature_flags(),
+                                                        token.get_token());
+                        fields.insert("session_token".to_string(), Json::String(session_token));
+                    }
+                    let encoded = json::encode(&body).unwrap();
+                    Ok(Response::with((status::Ok, encoded)))
+                }
+                "NetError" => {
+                    let err: NetError = protobuf::parse_from_bytes(rep.get_body()).unwrap();
+                    Ok(render_net_error(&err))
+                }
+                _ => unreachable!("unexpected msg: {:?}", rep),
+            }
+        }
+        Err(e) => {
+            error!("{:?}", e);
+            Ok(Response::with(status::ServiceUnavailable))
+        }
+    }
+}
+
+/// Log out of the session identified by the request's bearer token. The GitHub token is revoked
+/// first, but the session is expired even if revocation fails. The session tokens of the account
+/// minted so far are revoked too, see `SessionSigner::revoke`.
+pub fn session_delete(req: &mut Request, clients: &GitHubClients) -> IronResult<Response> {
+    let token = match req.headers.get::<Authorization<Bearer>>() {
+        Some(&Authorization(Bearer { ref token })) => token.to_string(),
+        _ => return Ok(Response::with(status::Unauthorized)),
+    };
+    let signer = req.extensions.get::<SessionTokens>().map(|signer| signer.clone());
+    // A session token names its account even if the session service no longer knows the session
+    if let (Some(signer), Some(claims)) = (signer.as_ref(), token_claims(req)) {
+        if let Some(id) = claims.account_id {
+            signer.revoke(id);
+        }
+    }
+    let app = extract_query_value("app", req);
+    let github = match clients.get(app.as_ref().map(|a| a.as_str())) {
+        Ok(github) => github,
+        Err(e) => {
+            debug!("github client select, err={:?}", e);
+            return Ok(Response::with(status::BadRequest));
+        }
+    };
+    if let Err(e) = github.logout(&token) {
+        warn!("github token revocation failed, expiring session anyway, err={}", e);
+    }
+    let mut conn = Broker::connect(&**ZMQ_CONTEXT).unwrap();
+    let mut request = SessionDelete::new();
+    request.set_token(token);
+    conn.route(&request).unwrap();
+    match conn.recv() {
+        Ok(rep) => {
+            match rep.get_message_id() {
+                "Session" => {
+                    let session: Session = protobuf::parse_from_bytes(rep.get_body()).unwrap();
+                    if let Some(ref signer) = signer {
+                        signer.revoke(session.get_id());
+                    }
+                    Ok(Response::with(status::NoContent))
+                }
+                "NetError" => {
+                    let err: NetError = protobuf::parse_from_bytes(rep.get_body()).unwrap();
+                    match err.get_code() {
+                        ErrCode::SESSION_EXPIRED => Ok(Response::with(status::NoContent)),
+                        _ => Ok(render_net_error(&err)),
+                    }
+                }
+                _ => unreachable!("unexpected msg: {:?}", rep),
+            }
+        }
+        Err(e) => {
+            error!("{:?}", e);
+            Ok(Response::with(status::ServiceUnavailable))
         }
     }
 }
@@ -221,6 +471,38 @@ pub fn status(_req: &mut Request) -> IronResult<Response> {
     Ok(Response::with(status::Ok))
 }
 
+/// Endpoint reporting whether logins through GitHub can be completed, see
+/// `GitHubClients::health`.
+///
+/// Returns a status 200 with the health and its reason while GitHub is healthy or degraded, and a
+/// status 503 while it is unhealthy.
+pub fn github_status(_req: &mut Request, github: &GitHubClients) -> IronResult<Response> {
+    let health = github.health();
+    let code = match health.health {
+        Health::Unhealthy => status::ServiceUnavailable,
+        Health::Healthy | Health::Degraded => status::Ok,
+    };
+    let encoded = json::encode(&health.to_json()).unwrap();
+    Ok(Response::with((code, encoded)))
+}
+
+fn extract_query_value(key: &str, req: &mut Request) -> Option<String> {
+    match req.get_ref::<UrlEncodedQuery>() {
+        Ok(map) => {
+            for (k, v) in map.iter() {
+                if key == *k {
+                    if v.len() < 1 {
+                        return None;
+                    }
+                    return Some(v[0].clone());
+                }
+            }
+            None
+        }
+        Err(_) => None,
+    }
+}
+
 /// Return an IronResult containing the body of a NetError and the appropriate HTTP response status
 /// for the corresponding NetError.
 ///
@@ -235,6 +517,7 @@ pub fn status(_req: &mut Request) -> IronResult<Response> {
 fn render_net_error(err: &NetError) -> Response {
     let encoded = json::encode(&err.to_json()).unwrap();
     let status = match err.get_code() {
+        ErrCode::ACCESS_DENIED => status::Forbidden,
         ErrCode::ENTITY_NOT_FOUND => status::NotFound,
         ErrCode::ENTITY_CONFLICT => status::Conflict,
         ErrCode::NO_SHARD => status::ServiceUnavailable,
@@ -246,6 +529,26 @@ fn render_net_error(err: &NetError) -> Response {
     Response::with((status, encoded))
 }
 
+/// Answers with 429 and how long to wait before trying again, when GitHub's rate limit resets
+/// at `reset` seconds since the epoch.
+fn render_rate_limited(reset: u64) -> Response {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let err = net::err(ErrCode::ACCESS_DENIED, "rg:auth:8");
+    let encoded = json::encode(&err.to_json()).unwrap();
+    let mut rep = Response::with((status::TooManyRequests, encoded));
+    rep.headers.set_raw("Retry-After", vec![reset.saturating_sub(now).to_string().into_bytes()]);
+    rep
+}
+
+/// Answers with 504 when GitHub didn't answer a request of the login in time. The code is
+/// `TIMEOUT` like the router's own timeouts, which `render_net_error` answers with 408, but here
+/// it's the upstream which was slow, not the client.
+fn render_gateway_timeout() -> Response {
+    let err = net::err(ErrCode::TIMEOUT, "rg:auth:9");
+    let encoded = json::encode(&err.to_json()).unwrap();
+    Response::with((status::GatewayTimeout, encoded))
+}
+
 pub fn list_account_invitations(req: &mut Request) -> IronResult<Response> {
     debug!("list_account_invitations");
     let session = match authenticate(req) {
@@ -20,12 +20,20 @@ use std::sync::{mpsc, Arc};
 use std::thread::{self, JoinHandle};
 
 use depot;
-use hab_net::oauth::github::GitHubClient;
+use hab_core::util::shutdown::Shutdown;
+use hab_net::config::{GitHubApps, GitHubOAuth, OAuth};
+use hab_net::oauth::audit::{self, Auditor, FileSink};
+use hab_net::oauth::OAuthProvider;
+use hab_net::oauth::github::GitHubClients;
+use hab_net::oauth::policy::TeamPolicies;
+use hab_net::oauth::watcher::GitHubCfgWatcher;
+use hab_net::session::token::SessionSigner;
 use iron::prelude::*;
-use iron::AfterMiddleware;
+use iron::{AfterMiddleware, BeforeMiddleware};
 use iron::headers;
 use iron::method::Method;
 use iron::Protocol;
+use iron::typemap;
 use mount::Mount;
 use staticfile::Static;
 use unicase::UniCase;
@@ -39,13 +47,62 @@ use self::handlers::*;
 // See: http://172.16.2.131:9633/iron/prelude/struct.Iron.html#method.http
 const HTTP_THREAD_COUNT: usize = 128;
 
-/// Create a new `iron::Chain` containing a Router and it's required middleware
-pub fn router(config: Arc<Config>) -> Result<Chain> {
-    let github = GitHubClient::new(&*config);
+/// Header carrying the internal session token of a request, see `SessionTokens`
+pub const SESSION_TOKEN_HEADER: &'static str = "X-Habitat-Session";
+
+/// Create a new `iron::Chain` containing a Router and it's required middleware. Changes to the
+/// GitHub configuration are watched for until `shutdown` is shut down.
+pub fn router(config: Arc<Config>, shutdown: &Shutdown) -> Result<Chain> {
+    let github = match config.stub_auth {
+        Some(ref stub) => {
+            warn!("GitHub authentication is disabled by `insecure_stub_auth`. Every login \
+                   presenting the shared secret is granted access as {}.",
+                  stub.login);
+            GitHubClients::stub(stub.clone())
+        }
+        None if config.oauth_provider() == "gitlab" => try!(GitHubClients::gitlab(&*config)),
+        None => try!(GitHubClients::new(&*config)),
+    };
+    let policies = TeamPolicies::new(&*config);
+    validate_policies(&*config, &github, &policies);
+    let client_ids = OAuthClientIds(Arc::new(github.client_ids()));
+    let allow_partial = config.github_allow_partial_sessions;
+    let analytics_salt = config.analytics_salt.clone();
+    let audit_sink: Arc<audit::AuditSink> = match config.audit_log {
+        Some(ref path) => {
+            Arc::new(try!(FileSink::new(path, config.audit_log_max_bytes, config.audit_log_keep)))
+        }
+        None => audit::noop(),
+    };
+    let auditor = Auditor::new(audit_sink, analytics_salt.clone());
+    let session_tokens = if config.session_keys.is_empty() {
+        None
+    } else {
+        let signer = try!(SessionSigner::new(&config.session_keys, config.session_token));
+        Some(SessionTokens(Arc::new(signer)))
+    };
+    let github = Arc::new(github);
+    if let Some(ref path) = config.config_file {
+        if config.stub_auth.is_none() && config.oauth_provider() != "gitlab" {
+            try!(GitHubCfgWatcher::new(path.clone(), github.clone()).run(shutdown));
+        }
+    }
+    let clients = OAuthClients(github.clone());
+    let logout_github = github.clone();
+    let status_github = github.clone();
 
     let router = router!(
         get "/status" => move |r: &mut Request| status(r),
-        get "/authenticate/:code" => move |r: &mut Request| session_create(r, &github),
+        get "/status/github" => move |r: &mut Request| github_status(r, &status_github),
+        get "/authenticate/:code" => move |r: &mut Request| {
+            session_create(r,
+                           &github,
+                           &policies,
+                           &auditor,
+                           allow_partial,
+                           analytics_salt.as_ref().map(|s| s.as_str()))
+        },
+        delete "/authenticate" => move |r: &mut Request| session_delete(r, &logout_github),
 
         post "/jobs" => move |r: &mut Request| job_create(r),
         get "/jobs/:id" => move |r: &mut Request| job_show(r),
@@ -56,10 +113,30 @@ pub fn router(config: Arc<Config>) -> Result<Chain> {
 
     );
     let mut chain = Chain::new(router);
+    chain.link_before(client_ids);
+    chain.link_before(clients);
+    if let Some(session_tokens) = session_tokens {
+        chain.link_before(session_tokens);
+        chain.link_after(RenewedSessionToken);
+    }
     chain.link_after(Cors);
     Ok(chain)
 }
 
+/// Check the team and access policies of every OAuth application against its client, logging a
+/// warning for every problem found. The teams of an application are listed with its
+/// `service_token`.
+fn validate_policies(config: &Config, github: &GitHubClients, policies: &TeamPolicies) {
+    policies.default().validate(github.default(), config.github_service_token());
+    github.default().access_policy().validate();
+    for (name, app) in config.github_apps().iter() {
+        if let (Ok(client), Ok(policy)) = (github.get(Some(name)), policies.get(Some(name))) {
+            policy.validate(client, app.github_service_token());
+            client.access_policy().validate();
+        }
+    }
+}
+
 /// Create a new HTTP listener and run it in a separate thread. This function will block the calling
 /// thread until the new listener has successfully started.
 ///
@@ -71,7 +148,7 @@ pub fn router(config: Arc<Config>) -> Result<Chain> {
 /// # Panics
 ///
 /// * Listener crashed during startup
-pub fn run(config: Arc<Config>) -> Result<JoinHandle<()>> {
+pub fn run(config: Arc<Config>, shutdown: &Shutdown) -> Result<JoinHandle<()>> {
     let (tx, rx) = mpsc::sync_channel(1);
 
     let addr = config.http_addr.clone();
@@ -84,7 +161,7 @@ pub fn run(config: Arc<Config>) -> Result<JoinHandle<()>> {
         debug!("Mounting UI at filepath {}", path);
         mount.mount("/", Static::new(path));
     }
-    let chain = try!(router(config));
+    let chain = try!(router(config, shutdown));
     mount.mount("/v1", chain).mount("/v1/depot", depot_chain);
 
     let handle = thread::Builder::new()
@@ -102,15 +179,83 @@ pub fn run(config: Arc<Config>) -> Result<JoinHandle<()>> {
     }
 }
 
+/// Client ids of every configured OAuth application. A session whose token was issued to any
+/// other application, for example before the credentials were rotated, is invalidated the next
+/// time it is used.
+pub struct OAuthClientIds(Arc<Vec<String>>);
+
+impl typemap::Key for OAuthClientIds {
+    type Value = Arc<Vec<String>>;
+}
+
+impl BeforeMiddleware for OAuthClientIds {
+    fn before(&self, req: &mut Request) -> IronResult<()> {
+        req.extensions.insert::<OAuthClientIds>(self.0.clone());
+        Ok(())
+    }
+}
+
+/// The registry of OAuth applications, which `authenticate` checks the tokens of looked up
+/// sessions with.
+pub struct OAuthClients(Arc<GitHubClients>);
+
+impl typemap::Key for OAuthClients {
+    type Value = Arc<GitHubClients>;
+}
+
+impl BeforeMiddleware for OAuthClients {
+    fn before(&self, req: &mut Request) -> IronResult<()> {
+        req.extensions.insert::<OAuthClients>(self.0.clone());
+        Ok(())
+    }
+}
+
+/// Signer of the internal session tokens handed out with sessions, which `authenticate` accepts
+/// in the `X-Habitat-Session` header in place of a session lookup.
+pub struct SessionTokens(Arc<SessionSigner>);
+
+impl typemap::Key for SessionTokens {
+    type Value = Arc<SessionSigner>;
+}
+
+impl BeforeMiddleware for SessionTokens {
+    fn before(&self, req: &mut Request) -> IronResult<()> {
+        req.extensions.insert::<SessionTokens>(self.0.clone());
+        Ok(())
+    }
+}
+
+/// A session token `authenticate` renewed, which is handed out in the `X-Habitat-Session` header
+/// of the response so the client can replace the one it sent.
+pub struct RenewedSessionToken;
+
+impl typemap::Key for RenewedSessionToken {
+    type Value = String;
+}
+
+impl AfterMiddleware for RenewedSessionToken {
+    fn after(&self, req: &mut Request, mut res: Response) -> IronResult<Response> {
+        if let Some(token) = req.extensions.get::<RenewedSessionToken>() {
+            res.headers.set_raw(SESSION_TOKEN_HEADER, vec![token.clone().into_bytes()]);
+        }
+        Ok(res)
+    }
+}
+
 struct Cors;
 
 impl AfterMiddleware for Cors {
     fn after(&self, _req: &mut Request, mut res: Response) -> IronResult<Response> {
         res.headers.set(headers::AccessControlAllowOrigin::Any);
         res.headers
-            .set(headers::AccessControlAllowHeaders(vec![UniCase("authorization".to_owned())]));
+            .set(headers::AccessControlAllowHeaders(vec![UniCase("authorization".to_owned()),
+                                                         UniCase("x-habitat-session"
+                                                             .to_owned())]));
+        res.headers
+            .set(headers::AccessControlAllowMethods(vec![Method::Put, Method::Delete]));
         res.headers
-            .set(headers::AccessControlAllowMethods(vec![Method::Put]));
+            .set(headers::AccessControlExposeHeaders(vec![UniCase("x-habitat-session"
+                                                          .to_owned())]));
         Ok(res)
     }
 }
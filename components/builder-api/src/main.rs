@@ -64,8 +64,20 @@ fn config_from_args(matches: &clap::ArgMatches) -> Result<Config> {
     let cmd = matches.subcommand_name().unwrap();
     let args = matches.subcommand_matches(cmd).unwrap();
     let mut config = match args.value_of("config") {
-        Some(cfg_path) => try!(Config::from_file(cfg_path)),
-        None => Config::from_file(CFG_DEFAULT_PATH).unwrap_or(Config::default()),
+        Some(cfg_path) => {
+            let mut config = try!(Config::from_file(cfg_path));
+            config.config_file = Some(cfg_path.to_string());
+            config
+        }
+        None => {
+            match Config::from_file(CFG_DEFAULT_PATH) {
+                Ok(mut config) => {
+                    config.config_file = Some(CFG_DEFAULT_PATH.to_string());
+                    config
+                }
+                Err(_) => Config::default(),
+            }
+        }
     };
     if let Some(port) = args.value_of("port") {
         if u16::from_str(port).map(|p| config.set_port(p)).is_err() {
@@ -16,6 +16,7 @@
 
 use std::sync::Arc;
 
+use hab_core::util::shutdown::Shutdown;
 use hab_net::config::RouteAddrs;
 use hab_net::routing::Broker;
 use hab_net::server::{NetIdent, ServerContext};
@@ -34,12 +35,16 @@ lazy_static! {
 /// The main server for the Builder-API application. This should be run on the main thread.
 pub struct Server {
     pub config: Arc<Config>,
+    shutdown: Shutdown,
 }
 
 impl Server {
     /// Create a new `Server`
     pub fn new(config: Config) -> Self {
-        Server { config: Arc::new(config) }
+        Server {
+            config: Arc::new(config),
+            shutdown: Shutdown::new(),
+        }
     }
 
     /// Runs the main server and starts and manages all supporting threads. This function will
@@ -52,11 +57,12 @@ impl Server {
         let cfg1 = self.config.clone();
         let ctx1 = ZMQ_CONTEXT.clone();
         let broker = Broker::run(Self::net_ident(), ctx1, self.config.route_addrs());
-        let http = try!(http::run(cfg1));
+        let http = try!(http::run(cfg1, &self.shutdown));
 
         println!("Builder API listening on {}", &self.config.http_addr);
         http.join().unwrap();
         broker.join().unwrap();
+        self.shutdown.shutdown();
         Ok(())
     }
 }
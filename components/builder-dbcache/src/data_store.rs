@@ -137,6 +137,13 @@ pub trait ExpiringSet: Bucket {
                          Self::expiry()));
         Ok(())
     }
+
+    /// Remove the record with the given ID from the data set.
+    fn delete(&self, id: &<Self::Record as Persistable>::Key) -> Result<()> {
+        let conn = try!(self.pool().get());
+        try!(conn.del::<String, ()>(Self::key(id)));
+        Ok(())
+    }
 }
 
 /// A specialized data set for reading and writing entities with a unique and sequential
@@ -15,7 +15,7 @@
 use std::net;
 
 use hab_core::config::{ConfigFile, ParseInto};
-use hab_net::config::{GitHubOAuth, RouteAddrs};
+use hab_net::config::{GitHubClientOptions, GitHubOAuth, HttpCfg, RouteAddrs, StubAuthCfg};
 use redis;
 use toml;
 
@@ -39,11 +39,19 @@ pub struct Config {
     /// List of net addresses for routing servers to connect to
     pub routers: Vec<net::SocketAddrV4>,
     /// URL to GitHub API
-    pub github_url: String,
+    pub github_api_url: String,
     /// Client identifier used for GitHub API requests
     pub github_client_id: String,
     /// Client secret used for GitHub API requests
     pub github_client_secret: String,
+    /// Path to a file containing the client secret used for GitHub API requests
+    pub github_client_secret_file: Option<String>,
+    /// Retry, timeout, and caching behaviour of the GitHub client
+    pub github_options: GitHubClientOptions,
+    /// Proxy, TLS, and timeout settings of outbound HTTP requests
+    pub http: HttpCfg,
+    /// Stub identity provider used in place of GitHub. If not set GitHub authentication is used.
+    pub stub_auth: Option<StubAuthCfg>,
     /// allows you to upload packages and public keys without auth
     pub insecure: bool,
 }
@@ -68,9 +76,13 @@ impl Default for Config {
             listen_addr: net::SocketAddrV4::new(net::Ipv4Addr::new(0, 0, 0, 0), 9632),
             datastore_addr: net::SocketAddrV4::new(net::Ipv4Addr::new(127, 0, 0, 1), 6379),
             routers: vec![net::SocketAddrV4::new(net::Ipv4Addr::new(127, 0, 0, 1), 5562)],
-            github_url: GITHUB_URL.to_string(),
+            github_api_url: GITHUB_URL.to_string(),
             github_client_id: DEV_GITHUB_CLIENT_ID.to_string(),
             github_client_secret: DEV_GITHUB_CLIENT_SECRET.to_string(),
+            github_client_secret_file: None,
+            github_options: GitHubClientOptions::default(),
+            http: HttpCfg::default(),
+            stub_auth: None,
             insecure: false,
         }
     }
@@ -92,8 +104,8 @@ impl RouteAddrs for Config {
 }
 
 impl GitHubOAuth for Config {
-    fn github_url(&self) -> &str {
-        &self.github_url
+    fn github_api_url(&self) -> &str {
+        &self.github_api_url
     }
 
     fn github_client_id(&self) -> &str {
@@ -103,4 +115,12 @@ impl GitHubOAuth for Config {
     fn github_client_secret(&self) -> &str {
         &self.github_client_secret
     }
+
+    fn github_client_secret_file(&self) -> Option<&str> {
+        self.github_client_secret_file.as_ref().map(|f| f.as_str())
+    }
+
+    fn github_http(&self) -> HttpCfg {
+        self.http.clone()
+    }
 }
@@ -60,7 +60,9 @@ use std::path::{Path, PathBuf};
 use crypto::sha2::Sha256;
 use crypto::digest::Digest;
 use hab_core::package::{Identifiable, PackageArchive};
+use hab_net::oauth::OAuthProvider;
 use hab_net::oauth::github::GitHubClient;
+use hab_net::oauth::stub::StubProvider;
 use hab_net::server::{NetIdent, ServerContext};
 use data_store::DataStore;
 
@@ -68,13 +70,16 @@ pub struct Depot {
     pub config: Config,
     pub datastore: DataStore,
     context: Arc<Box<ServerContext>>,
-    github: GitHubClient,
+    github: Box<OAuthProvider>,
 }
 
 impl Depot {
     pub fn new(config: Config, ctx: Arc<Box<ServerContext>>) -> Result<Arc<Depot>> {
         let datastore = try!(DataStore::open(&config));
-        let github = GitHubClient::new(&config);
+        let github: Box<OAuthProvider> = match config.stub_auth {
+            Some(ref stub) => Box::new(StubProvider::new(stub.clone())),
+            None => Box::new(try!(GitHubClient::new(&config, config.github_options.clone()))),
+        };
         Ok(Arc::new(Depot {
             config: config,
             datastore: datastore,
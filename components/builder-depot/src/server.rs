@@ -38,7 +38,7 @@ use mount::Mount;
 use protobuf;
 use protocol::depotsrv;
 use protocol::net::{self, NetError, ErrCode};
-use protocol::sessionsrv::{Account, AccountGet, OAuthProvider, Session, SessionCreate, SessionGet};
+use protocol::sessionsrv::{Account, AccountGet, Session, SessionCreate, SessionGet};
 use protocol::vault::*;
 use router::{Params, Router};
 use rustc_serialize::json::{self, ToJson};
@@ -80,26 +80,23 @@ fn render_net_error(err: &NetError) -> Response {
 }
 
 pub fn session_create(depot: &Depot, token: &str) -> result::Result<Session, Response> {
-    match depot.github.user(&token) {
-        Ok(user) => {
-            // Select primary email. If no primary email can be found, use any email. If no email
-            // is associated with account return an access denied error.
-            let email = match depot.github.emails(&token) {
-                Ok(ref emails) => {
-                    emails.iter().find(|e| e.primary).unwrap_or(&emails[0]).email.clone()
-                }
-                Err(_) => {
-                    let err = net::err(ErrCode::ACCESS_DENIED, "dp:auth:0");
-                    return Err(render_net_error(&err));
-                }
-            };
+    match depot.github.identity(&token) {
+        Ok(identity) => {
             let mut conn = Broker::connect(&depot.context).unwrap();
             let mut request = SessionCreate::new();
             request.set_token(token.to_string());
-            request.set_extern_id(user.id);
-            request.set_email(email);
-            request.set_name(user.login);
-            request.set_provider(OAuthProvider::GitHub);
+            request.set_extern_id(identity.external_id);
+            request.set_avatar_url(identity.avatar_url);
+            if let Some(display_name) = identity.name {
+                request.set_display_name(display_name);
+            }
+            request.set_email_verified(identity.email_verified);
+            if let Some(email) = identity.email {
+                request.set_email(email);
+            }
+            request.set_name(identity.login);
+            request.set_provider(identity.provider);
+            request.set_provider_host(identity.provider_host);
             conn.route(&request).unwrap();
             match conn.recv() {
                 Ok(rep) => {
@@ -22,6 +22,12 @@ pub struct Account {
     id: ::std::option::Option<u64>,
     email: ::protobuf::SingularField<::std::string::String>,
     name: ::protobuf::SingularField<::std::string::String>,
+    extern_id: ::std::option::Option<u64>,
+    avatar_url: ::protobuf::SingularField<::std::string::String>,
+    display_name: ::protobuf::SingularField<::std::string::String>,
+    email_verified: ::std::option::Option<bool>,
+    provider: ::std::option::Option<OAuthProvider>,
+    provider_host: ::protobuf::SingularField<::std::string::String>,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::std::cell::Cell<u32>,
@@ -46,6 +52,12 @@ impl Account {
                     id: ::std::option::Option::None,
                     email: ::protobuf::SingularField::none(),
                     name: ::protobuf::SingularField::none(),
+                    extern_id: ::std::option::Option::None,
+                    avatar_url: ::protobuf::SingularField::none(),
+                    display_name: ::protobuf::SingularField::none(),
+                    email_verified: ::std::option::Option::None,
+                    provider: ::std::option::Option::None,
+                    provider_host: ::protobuf::SingularField::none(),
                     unknown_fields: ::protobuf::UnknownFields::new(),
                     cached_size: ::std::cell::Cell::new(0),
                 }
@@ -72,7 +84,7 @@ impl Account {
         self.id.unwrap_or(0)
     }
 
-    // required string email = 2;
+    // optional string email = 2;
 
     pub fn clear_email(&mut self) {
         self.email.clear();
@@ -143,6 +155,171 @@ impl Account {
             None => "",
         }
     }
+
+    // optional uint64 extern_id = 4;
+
+    pub fn clear_extern_id(&mut self) {
+        self.extern_id = ::std::option::Option::None;
+    }
+
+    pub fn has_extern_id(&self) -> bool {
+        self.extern_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_extern_id(&mut self, v: u64) {
+        self.extern_id = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_extern_id(&self) -> u64 {
+        self.extern_id.unwrap_or(0)
+    }
+
+    // optional string avatar_url = 5;
+
+    pub fn clear_avatar_url(&mut self) {
+        self.avatar_url.clear();
+    }
+
+    pub fn has_avatar_url(&self) -> bool {
+        self.avatar_url.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_avatar_url(&mut self, v: ::std::string::String) {
+        self.avatar_url = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_avatar_url(&mut self) -> &mut ::std::string::String {
+        if self.avatar_url.is_none() {
+            self.avatar_url.set_default();
+        };
+        self.avatar_url.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_avatar_url(&mut self) -> ::std::string::String {
+        self.avatar_url.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_avatar_url(&self) -> &str {
+        match self.avatar_url.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // optional string display_name = 6;
+
+    pub fn clear_display_name(&mut self) {
+        self.display_name.clear();
+    }
+
+    pub fn has_display_name(&self) -> bool {
+        self.display_name.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_display_name(&mut self, v: ::std::string::String) {
+        self.display_name = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_display_name(&mut self) -> &mut ::std::string::String {
+        if self.display_name.is_none() {
+            self.display_name.set_default();
+        };
+        self.display_name.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_display_name(&mut self) -> ::std::string::String {
+        self.display_name.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_display_name(&self) -> &str {
+        match self.display_name.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // optional bool email_verified = 7;
+
+    pub fn clear_email_verified(&mut self) {
+        self.email_verified = ::std::option::Option::None;
+    }
+
+    pub fn has_email_verified(&self) -> bool {
+        self.email_verified.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_email_verified(&mut self, v: bool) {
+        self.email_verified = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_email_verified(&self) -> bool {
+        self.email_verified.unwrap_or(false)
+    }
+
+    // optional .sessionsrv.OAuthProvider provider = 8;
+
+    pub fn clear_provider(&mut self) {
+        self.provider = ::std::option::Option::None;
+    }
+
+    pub fn has_provider(&self) -> bool {
+        self.provider.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_provider(&mut self, v: OAuthProvider) {
+        self.provider = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_provider(&self) -> OAuthProvider {
+        self.provider.unwrap_or(OAuthProvider::GitHub)
+    }
+
+    // optional string provider_host = 9;
+
+    pub fn clear_provider_host(&mut self) {
+        self.provider_host.clear();
+    }
+
+    pub fn has_provider_host(&self) -> bool {
+        self.provider_host.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_provider_host(&mut self, v: ::std::string::String) {
+        self.provider_host = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_provider_host(&mut self) -> &mut ::std::string::String {
+        if self.provider_host.is_none() {
+            self.provider_host.set_default();
+        };
+        self.provider_host.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_provider_host(&mut self) -> ::std::string::String {
+        self.provider_host.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_provider_host(&self) -> &str {
+        match self.provider_host.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
 }
 
 impl ::protobuf::Message for Account {
@@ -150,9 +327,6 @@ impl ::protobuf::Message for Account {
         if self.id.is_none() {
             return false;
         };
-        if self.email.is_none() {
-            return false;
-        };
         if self.name.is_none() {
             return false;
         };
@@ -176,6 +350,36 @@ impl ::protobuf::Message for Account {
                 3 => {
                     try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.name));
                 },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.extern_id = ::std::option::Option::Some(tmp);
+                },
+                5 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.avatar_url));
+                },
+                6 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.display_name));
+                },
+                7 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_bool());
+                    self.email_verified = ::std::option::Option::Some(tmp);
+                },
+                8 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_enum());
+                    self.provider = ::std::option::Option::Some(tmp);
+                },
+                9 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.provider_host));
+                },
                 _ => {
                     try!(::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields()));
                 },
@@ -197,6 +401,24 @@ impl ::protobuf::Message for Account {
         for value in self.name.iter() {
             my_size += ::protobuf::rt::string_size(3, &value);
         };
+        for value in self.extern_id.iter() {
+            my_size += ::protobuf::rt::value_size(4, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.avatar_url.iter() {
+            my_size += ::protobuf::rt::string_size(5, &value);
+        };
+        for value in self.display_name.iter() {
+            my_size += ::protobuf::rt::string_size(6, &value);
+        };
+        if self.email_verified.is_some() {
+            my_size += 2;
+        };
+        for value in self.provider.iter() {
+            my_size += ::protobuf::rt::enum_size(8, *value);
+        };
+        for value in self.provider_host.iter() {
+            my_size += ::protobuf::rt::string_size(9, &value);
+        };
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -212,6 +434,24 @@ impl ::protobuf::Message for Account {
         if let Some(v) = self.name.as_ref() {
             try!(os.write_string(3, &v));
         };
+        if let Some(v) = self.extern_id {
+            try!(os.write_uint64(4, v));
+        };
+        if let Some(v) = self.avatar_url.as_ref() {
+            try!(os.write_string(5, &v));
+        };
+        if let Some(v) = self.display_name.as_ref() {
+            try!(os.write_string(6, &v));
+        };
+        if let Some(v) = self.email_verified {
+            try!(os.write_bool(7, v));
+        };
+        if let Some(v) = self.provider {
+            try!(os.write_enum(8, v.value()));
+        };
+        if let Some(v) = self.provider_host.as_ref() {
+            try!(os.write_string(9, &v));
+        };
         try!(os.write_unknown_fields(self.get_unknown_fields()));
         ::std::result::Result::Ok(())
     }
@@ -269,6 +509,36 @@ impl ::protobuf::MessageStatic for Account {
                     Account::has_name,
                     Account::get_name,
                 ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "extern_id",
+                    Account::has_extern_id,
+                    Account::get_extern_id,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "avatar_url",
+                    Account::has_avatar_url,
+                    Account::get_avatar_url,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "display_name",
+                    Account::has_display_name,
+                    Account::get_display_name,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_bool_accessor(
+                    "email_verified",
+                    Account::has_email_verified,
+                    Account::get_email_verified,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_enum_accessor(
+                    "provider",
+                    Account::has_provider,
+                    Account::get_provider,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "provider_host",
+                    Account::has_provider_host,
+                    Account::get_provider_host,
+                ));
                 ::protobuf::reflect::MessageDescriptor::new::<Account>(
                     "Account",
                     fields,
@@ -284,6 +554,12 @@ impl ::protobuf::Clear for Account {
         self.clear_id();
         self.clear_email();
         self.clear_name();
+        self.clear_extern_id();
+        self.clear_avatar_url();
+        self.clear_display_name();
+        self.clear_email_verified();
+        self.clear_provider();
+        self.clear_provider_host();
         self.unknown_fields.clear();
     }
 }
@@ -293,6 +569,12 @@ impl ::std::cmp::PartialEq for Account {
         self.id == other.id &&
         self.email == other.email &&
         self.name == other.name &&
+        self.extern_id == other.extern_id &&
+        self.avatar_url == other.avatar_url &&
+        self.display_name == other.display_name &&
+        self.email_verified == other.email_verified &&
+        self.provider == other.provider &&
+        self.provider_host == other.provider_host &&
         self.unknown_fields == other.unknown_fields
     }
 }
@@ -496,6 +778,13 @@ pub struct Session {
     email: ::protobuf::SingularField<::std::string::String>,
     name: ::protobuf::SingularField<::std::string::String>,
     token: ::protobuf::SingularField<::std::string::String>,
+    flags: ::std::option::Option<u32>,
+    avatar_url: ::protobuf::SingularField<::std::string::String>,
+    display_name: ::protobuf::SingularField<::std::string::String>,
+    client_id: ::protobuf::SingularField<::std::string::String>,
+    scopes: ::protobuf::RepeatedField<::std::string::String>,
+    issued_at: ::std::option::Option<u64>,
+    last_validated_at: ::std::option::Option<u64>,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::std::cell::Cell<u32>,
@@ -521,6 +810,13 @@ impl Session {
                     email: ::protobuf::SingularField::none(),
                     name: ::protobuf::SingularField::none(),
                     token: ::protobuf::SingularField::none(),
+                    flags: ::std::option::Option::None,
+                    avatar_url: ::protobuf::SingularField::none(),
+                    display_name: ::protobuf::SingularField::none(),
+                    client_id: ::protobuf::SingularField::none(),
+                    scopes: ::protobuf::RepeatedField::new(),
+                    issued_at: ::std::option::Option::None,
+                    last_validated_at: ::std::option::Option::None,
                     unknown_fields: ::protobuf::UnknownFields::new(),
                     cached_size: ::std::cell::Cell::new(0),
                 }
@@ -547,7 +843,7 @@ impl Session {
         self.id.unwrap_or(0)
     }
 
-    // required string email = 2;
+    // optional string email = 2;
 
     pub fn clear_email(&mut self) {
         self.email.clear();
@@ -654,101 +950,363 @@ impl Session {
             None => "",
         }
     }
-}
 
-impl ::protobuf::Message for Session {
-    fn is_initialized(&self) -> bool {
-        if self.id.is_none() {
-            return false;
-        };
-        if self.email.is_none() {
-            return false;
-        };
-        if self.name.is_none() {
-            return false;
-        };
-        if self.token.is_none() {
-            return false;
-        };
-        true
+    // optional uint32 flags = 5;
+
+    pub fn clear_flags(&mut self) {
+        self.flags = ::std::option::Option::None;
     }
 
-    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
-        while !try!(is.eof()) {
-            let (field_number, wire_type) = try!(is.read_tag_unpack());
-            match field_number {
-                1 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
-                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
-                    };
-                    let tmp = try!(is.read_uint64());
-                    self.id = ::std::option::Option::Some(tmp);
-                },
-                2 => {
-                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.email));
-                },
-                3 => {
-                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.name));
-                },
-                4 => {
-                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.token));
-                },
-                _ => {
-                    try!(::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields()));
-                },
-            };
-        }
-        ::std::result::Result::Ok(())
+    pub fn has_flags(&self) -> bool {
+        self.flags.is_some()
     }
 
-    // Compute sizes of nested messages
-    #[allow(unused_variables)]
-    fn compute_size(&self) -> u32 {
-        let mut my_size = 0;
-        for value in self.id.iter() {
-            my_size += ::protobuf::rt::value_size(1, *value, ::protobuf::wire_format::WireTypeVarint);
-        };
-        for value in self.email.iter() {
-            my_size += ::protobuf::rt::string_size(2, &value);
-        };
-        for value in self.name.iter() {
-            my_size += ::protobuf::rt::string_size(3, &value);
-        };
-        for value in self.token.iter() {
-            my_size += ::protobuf::rt::string_size(4, &value);
-        };
-        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
-        self.cached_size.set(my_size);
-        my_size
+    // Param is passed by value, moved
+    pub fn set_flags(&mut self, v: u32) {
+        self.flags = ::std::option::Option::Some(v);
     }
 
-    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
-        if let Some(v) = self.id {
-            try!(os.write_uint64(1, v));
-        };
-        if let Some(v) = self.email.as_ref() {
-            try!(os.write_string(2, &v));
-        };
-        if let Some(v) = self.name.as_ref() {
-            try!(os.write_string(3, &v));
-        };
-        if let Some(v) = self.token.as_ref() {
-            try!(os.write_string(4, &v));
-        };
-        try!(os.write_unknown_fields(self.get_unknown_fields()));
-        ::std::result::Result::Ok(())
+    pub fn get_flags(&self) -> u32 {
+        self.flags.unwrap_or(0)
     }
 
-    fn get_cached_size(&self) -> u32 {
-        self.cached_size.get()
+    // optional string avatar_url = 6;
+
+    pub fn clear_avatar_url(&mut self) {
+        self.avatar_url.clear();
     }
 
-    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
-        &self.unknown_fields
+    pub fn has_avatar_url(&self) -> bool {
+        self.avatar_url.is_some()
     }
 
-    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
-        &mut self.unknown_fields
+    // Param is passed by value, moved
+    pub fn set_avatar_url(&mut self, v: ::std::string::String) {
+        self.avatar_url = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_avatar_url(&mut self) -> &mut ::std::string::String {
+        if self.avatar_url.is_none() {
+            self.avatar_url.set_default();
+        };
+        self.avatar_url.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_avatar_url(&mut self) -> ::std::string::String {
+        self.avatar_url.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_avatar_url(&self) -> &str {
+        match self.avatar_url.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // optional string display_name = 7;
+
+    pub fn clear_display_name(&mut self) {
+        self.display_name.clear();
+    }
+
+    pub fn has_display_name(&self) -> bool {
+        self.display_name.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_display_name(&mut self, v: ::std::string::String) {
+        self.display_name = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_display_name(&mut self) -> &mut ::std::string::String {
+        if self.display_name.is_none() {
+            self.display_name.set_default();
+        };
+        self.display_name.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_display_name(&mut self) -> ::std::string::String {
+        self.display_name.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_display_name(&self) -> &str {
+        match self.display_name.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // optional string client_id = 8;
+
+    pub fn clear_client_id(&mut self) {
+        self.client_id.clear();
+    }
+
+    pub fn has_client_id(&self) -> bool {
+        self.client_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_client_id(&mut self, v: ::std::string::String) {
+        self.client_id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_client_id(&mut self) -> &mut ::std::string::String {
+        if self.client_id.is_none() {
+            self.client_id.set_default();
+        };
+        self.client_id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_client_id(&mut self) -> ::std::string::String {
+        self.client_id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_client_id(&self) -> &str {
+        match self.client_id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // repeated string scopes = 9;
+
+    pub fn clear_scopes(&mut self) {
+        self.scopes.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_scopes(&mut self, v: ::protobuf::RepeatedField<::std::string::String>) {
+        self.scopes = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_scopes(&mut self) -> &mut ::protobuf::RepeatedField<::std::string::String> {
+        &mut self.scopes
+    }
+
+    // Take field
+    pub fn take_scopes(&mut self) -> ::protobuf::RepeatedField<::std::string::String> {
+        ::std::mem::replace(&mut self.scopes, ::protobuf::RepeatedField::new())
+    }
+
+    pub fn get_scopes(&self) -> &[::std::string::String] {
+        &self.scopes
+    }
+
+    // optional uint64 issued_at = 10;
+
+    pub fn clear_issued_at(&mut self) {
+        self.issued_at = ::std::option::Option::None;
+    }
+
+    pub fn has_issued_at(&self) -> bool {
+        self.issued_at.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_issued_at(&mut self, v: u64) {
+        self.issued_at = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_issued_at(&self) -> u64 {
+        self.issued_at.unwrap_or(0)
+    }
+
+    // optional uint64 last_validated_at = 11;
+
+    pub fn clear_last_validated_at(&mut self) {
+        self.last_validated_at = ::std::option::Option::None;
+    }
+
+    pub fn has_last_validated_at(&self) -> bool {
+        self.last_validated_at.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_validated_at(&mut self, v: u64) {
+        self.last_validated_at = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_last_validated_at(&self) -> u64 {
+        self.last_validated_at.unwrap_or(0)
+    }
+}
+
+impl ::protobuf::Message for Session {
+    fn is_initialized(&self) -> bool {
+        if self.id.is_none() {
+            return false;
+        };
+        if self.name.is_none() {
+            return false;
+        };
+        if self.token.is_none() {
+            return false;
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !try!(is.eof()) {
+            let (field_number, wire_type) = try!(is.read_tag_unpack());
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.id = ::std::option::Option::Some(tmp);
+                },
+                2 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.email));
+                },
+                3 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.name));
+                },
+                4 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.token));
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint32());
+                    self.flags = ::std::option::Option::Some(tmp);
+                },
+                6 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.avatar_url));
+                },
+                7 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.display_name));
+                },
+                8 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.client_id));
+                },
+                9 => {
+                    try!(::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.scopes));
+                },
+                10 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.issued_at = ::std::option::Option::Some(tmp);
+                },
+                11 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.last_validated_at = ::std::option::Option::Some(tmp);
+                },
+                _ => {
+                    try!(::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields()));
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in self.id.iter() {
+            my_size += ::protobuf::rt::value_size(1, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.email.iter() {
+            my_size += ::protobuf::rt::string_size(2, &value);
+        };
+        for value in self.name.iter() {
+            my_size += ::protobuf::rt::string_size(3, &value);
+        };
+        for value in self.token.iter() {
+            my_size += ::protobuf::rt::string_size(4, &value);
+        };
+        for value in self.flags.iter() {
+            my_size += ::protobuf::rt::value_size(5, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.avatar_url.iter() {
+            my_size += ::protobuf::rt::string_size(6, &value);
+        };
+        for value in self.display_name.iter() {
+            my_size += ::protobuf::rt::string_size(7, &value);
+        };
+        for value in self.client_id.iter() {
+            my_size += ::protobuf::rt::string_size(8, &value);
+        };
+        for value in self.scopes.iter() {
+            my_size += ::protobuf::rt::string_size(9, &value);
+        };
+        for value in self.issued_at.iter() {
+            my_size += ::protobuf::rt::value_size(10, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.last_validated_at.iter() {
+            my_size += ::protobuf::rt::value_size(11, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.id {
+            try!(os.write_uint64(1, v));
+        };
+        if let Some(v) = self.email.as_ref() {
+            try!(os.write_string(2, &v));
+        };
+        if let Some(v) = self.name.as_ref() {
+            try!(os.write_string(3, &v));
+        };
+        if let Some(v) = self.token.as_ref() {
+            try!(os.write_string(4, &v));
+        };
+        if let Some(v) = self.flags {
+            try!(os.write_uint32(5, v));
+        };
+        if let Some(v) = self.avatar_url.as_ref() {
+            try!(os.write_string(6, &v));
+        };
+        if let Some(v) = self.display_name.as_ref() {
+            try!(os.write_string(7, &v));
+        };
+        if let Some(v) = self.client_id.as_ref() {
+            try!(os.write_string(8, &v));
+        };
+        for v in self.scopes.iter() {
+            try!(os.write_string(9, &v));
+        };
+        if let Some(v) = self.issued_at {
+            try!(os.write_uint64(10, v));
+        };
+        if let Some(v) = self.last_validated_at {
+            try!(os.write_uint64(11, v));
+        };
+        try!(os.write_unknown_fields(self.get_unknown_fields()));
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
     }
 
     fn type_id(&self) -> ::std::any::TypeId {
@@ -769,7 +1327,1242 @@ impl ::protobuf::MessageStatic for Session {
         Session::new()
     }
 
-    fn descriptor_static(_: ::std::option::Option<Session>) -> &'static ::protobuf::reflect::MessageDescriptor {
+    fn descriptor_static(_: ::std::option::Option<Session>) -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "id",
+                    Session::has_id,
+                    Session::get_id,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "email",
+                    Session::has_email,
+                    Session::get_email,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "name",
+                    Session::has_name,
+                    Session::get_name,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "token",
+                    Session::has_token,
+                    Session::get_token,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u32_accessor(
+                    "flags",
+                    Session::has_flags,
+                    Session::get_flags,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "avatar_url",
+                    Session::has_avatar_url,
+                    Session::get_avatar_url,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "display_name",
+                    Session::has_display_name,
+                    Session::get_display_name,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "client_id",
+                    Session::has_client_id,
+                    Session::get_client_id,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_repeated_string_accessor(
+                    "scopes",
+                    Session::get_scopes,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "issued_at",
+                    Session::has_issued_at,
+                    Session::get_issued_at,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "last_validated_at",
+                    Session::has_last_validated_at,
+                    Session::get_last_validated_at,
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<Session>(
+                    "Session",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+}
+
+impl ::protobuf::Clear for Session {
+    fn clear(&mut self) {
+        self.clear_id();
+        self.clear_email();
+        self.clear_name();
+        self.clear_token();
+        self.clear_flags();
+        self.clear_avatar_url();
+        self.clear_display_name();
+        self.clear_client_id();
+        self.clear_scopes();
+        self.clear_issued_at();
+        self.clear_last_validated_at();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::cmp::PartialEq for Session {
+    fn eq(&self, other: &Session) -> bool {
+        self.id == other.id &&
+        self.email == other.email &&
+        self.name == other.name &&
+        self.token == other.token &&
+        self.flags == other.flags &&
+        self.avatar_url == other.avatar_url &&
+        self.display_name == other.display_name &&
+        self.client_id == other.client_id &&
+        self.scopes == other.scopes &&
+        self.issued_at == other.issued_at &&
+        self.last_validated_at == other.last_validated_at &&
+        self.unknown_fields == other.unknown_fields
+    }
+}
+
+impl ::std::fmt::Debug for Session {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+#[derive(Clone,Default)]
+pub struct SessionToken {
+    // message fields
+    token: ::protobuf::SingularField<::std::string::String>,
+    owner_id: ::std::option::Option<u64>,
+    flags: ::std::option::Option<u32>,
+    client_id: ::protobuf::SingularField<::std::string::String>,
+    scopes: ::protobuf::RepeatedField<::std::string::String>,
+    issued_at: ::std::option::Option<u64>,
+    last_validated_at: ::std::option::Option<u64>,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::std::cell::Cell<u32>,
+}
+
+// see codegen.rs for the explanation why impl Sync explicitly
+unsafe impl ::std::marker::Sync for SessionToken {}
+
+impl SessionToken {
+    pub fn new() -> SessionToken {
+        ::std::default::Default::default()
+    }
+
+    pub fn default_instance() -> &'static SessionToken {
+        static mut instance: ::protobuf::lazy::Lazy<SessionToken> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const SessionToken,
+        };
+        unsafe {
+            instance.get(|| {
+                SessionToken {
+                    token: ::protobuf::SingularField::none(),
+                    owner_id: ::std::option::Option::None,
+                    flags: ::std::option::Option::None,
+                    client_id: ::protobuf::SingularField::none(),
+                    scopes: ::protobuf::RepeatedField::new(),
+                    issued_at: ::std::option::Option::None,
+                    last_validated_at: ::std::option::Option::None,
+                    unknown_fields: ::protobuf::UnknownFields::new(),
+                    cached_size: ::std::cell::Cell::new(0),
+                }
+            })
+        }
+    }
+
+    // required string token = 1;
+
+    pub fn clear_token(&mut self) {
+        self.token.clear();
+    }
+
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_token(&mut self, v: ::std::string::String) {
+        self.token = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_token(&mut self) -> &mut ::std::string::String {
+        if self.token.is_none() {
+            self.token.set_default();
+        };
+        self.token.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_token(&mut self) -> ::std::string::String {
+        self.token.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_token(&self) -> &str {
+        match self.token.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // required uint64 owner_id = 2;
+
+    pub fn clear_owner_id(&mut self) {
+        self.owner_id = ::std::option::Option::None;
+    }
+
+    pub fn has_owner_id(&self) -> bool {
+        self.owner_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_owner_id(&mut self, v: u64) {
+        self.owner_id = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_owner_id(&self) -> u64 {
+        self.owner_id.unwrap_or(0)
+    }
+
+    // optional uint32 flags = 3;
+
+    pub fn clear_flags(&mut self) {
+        self.flags = ::std::option::Option::None;
+    }
+
+    pub fn has_flags(&self) -> bool {
+        self.flags.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_flags(&mut self, v: u32) {
+        self.flags = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_flags(&self) -> u32 {
+        self.flags.unwrap_or(0)
+    }
+
+    // optional string client_id = 4;
+
+    pub fn clear_client_id(&mut self) {
+        self.client_id.clear();
+    }
+
+    pub fn has_client_id(&self) -> bool {
+        self.client_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_client_id(&mut self, v: ::std::string::String) {
+        self.client_id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_client_id(&mut self) -> &mut ::std::string::String {
+        if self.client_id.is_none() {
+            self.client_id.set_default();
+        };
+        self.client_id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_client_id(&mut self) -> ::std::string::String {
+        self.client_id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_client_id(&self) -> &str {
+        match self.client_id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // repeated string scopes = 5;
+
+    pub fn clear_scopes(&mut self) {
+        self.scopes.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_scopes(&mut self, v: ::protobuf::RepeatedField<::std::string::String>) {
+        self.scopes = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_scopes(&mut self) -> &mut ::protobuf::RepeatedField<::std::string::String> {
+        &mut self.scopes
+    }
+
+    // Take field
+    pub fn take_scopes(&mut self) -> ::protobuf::RepeatedField<::std::string::String> {
+        ::std::mem::replace(&mut self.scopes, ::protobuf::RepeatedField::new())
+    }
+
+    pub fn get_scopes(&self) -> &[::std::string::String] {
+        &self.scopes
+    }
+
+    // optional uint64 issued_at = 6;
+
+    pub fn clear_issued_at(&mut self) {
+        self.issued_at = ::std::option::Option::None;
+    }
+
+    pub fn has_issued_at(&self) -> bool {
+        self.issued_at.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_issued_at(&mut self, v: u64) {
+        self.issued_at = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_issued_at(&self) -> u64 {
+        self.issued_at.unwrap_or(0)
+    }
+
+    // optional uint64 last_validated_at = 7;
+
+    pub fn clear_last_validated_at(&mut self) {
+        self.last_validated_at = ::std::option::Option::None;
+    }
+
+    pub fn has_last_validated_at(&self) -> bool {
+        self.last_validated_at.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_validated_at(&mut self, v: u64) {
+        self.last_validated_at = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_last_validated_at(&self) -> u64 {
+        self.last_validated_at.unwrap_or(0)
+    }
+}
+
+impl ::protobuf::Message for SessionToken {
+    fn is_initialized(&self) -> bool {
+        if self.token.is_none() {
+            return false;
+        };
+        if self.owner_id.is_none() {
+            return false;
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !try!(is.eof()) {
+            let (field_number, wire_type) = try!(is.read_tag_unpack());
+            match field_number {
+                1 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.token));
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.owner_id = ::std::option::Option::Some(tmp);
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint32());
+                    self.flags = ::std::option::Option::Some(tmp);
+                },
+                4 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.client_id));
+                },
+                5 => {
+                    try!(::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.scopes));
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.issued_at = ::std::option::Option::Some(tmp);
+                },
+                7 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.last_validated_at = ::std::option::Option::Some(tmp);
+                },
+                _ => {
+                    try!(::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields()));
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in self.token.iter() {
+            my_size += ::protobuf::rt::string_size(1, &value);
+        };
+        for value in self.owner_id.iter() {
+            my_size += ::protobuf::rt::value_size(2, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.flags.iter() {
+            my_size += ::protobuf::rt::value_size(3, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.client_id.iter() {
+            my_size += ::protobuf::rt::string_size(4, &value);
+        };
+        for value in self.scopes.iter() {
+            my_size += ::protobuf::rt::string_size(5, &value);
+        };
+        for value in self.issued_at.iter() {
+            my_size += ::protobuf::rt::value_size(6, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.last_validated_at.iter() {
+            my_size += ::protobuf::rt::value_size(7, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.token.as_ref() {
+            try!(os.write_string(1, &v));
+        };
+        if let Some(v) = self.owner_id {
+            try!(os.write_uint64(2, v));
+        };
+        if let Some(v) = self.flags {
+            try!(os.write_uint32(3, v));
+        };
+        if let Some(v) = self.client_id.as_ref() {
+            try!(os.write_string(4, &v));
+        };
+        for v in self.scopes.iter() {
+            try!(os.write_string(5, &v));
+        };
+        if let Some(v) = self.issued_at {
+            try!(os.write_uint64(6, v));
+        };
+        if let Some(v) = self.last_validated_at {
+            try!(os.write_uint64(7, v));
+        };
+        try!(os.write_unknown_fields(self.get_unknown_fields()));
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn type_id(&self) -> ::std::any::TypeId {
+        ::std::any::TypeId::of::<SessionToken>()
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        ::protobuf::MessageStatic::descriptor_static(None::<Self>)
+    }
+}
+
+impl ::protobuf::MessageStatic for SessionToken {
+    fn new() -> SessionToken {
+        SessionToken::new()
+    }
+
+    fn descriptor_static(_: ::std::option::Option<SessionToken>) -> &'static ::protobuf::reflect::MessageDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "token",
+                    SessionToken::has_token,
+                    SessionToken::get_token,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "owner_id",
+                    SessionToken::has_owner_id,
+                    SessionToken::get_owner_id,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u32_accessor(
+                    "flags",
+                    SessionToken::has_flags,
+                    SessionToken::get_flags,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "client_id",
+                    SessionToken::has_client_id,
+                    SessionToken::get_client_id,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_repeated_string_accessor(
+                    "scopes",
+                    SessionToken::get_scopes,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "issued_at",
+                    SessionToken::has_issued_at,
+                    SessionToken::get_issued_at,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "last_validated_at",
+                    SessionToken::has_last_validated_at,
+                    SessionToken::get_last_validated_at,
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<SessionToken>(
+                    "SessionToken",
+                    fields,
+                    file_descriptor_proto()
+                )
+            })
+        }
+    }
+}
+
+impl ::protobuf::Clear for SessionToken {
+    fn clear(&mut self) {
+        self.clear_token();
+        self.clear_owner_id();
+        self.clear_flags();
+        self.clear_client_id();
+        self.clear_scopes();
+        self.clear_issued_at();
+        self.clear_last_validated_at();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::cmp::PartialEq for SessionToken {
+    fn eq(&self, other: &SessionToken) -> bool {
+        self.token == other.token &&
+        self.owner_id == other.owner_id &&
+        self.flags == other.flags &&
+        self.client_id == other.client_id &&
+        self.scopes == other.scopes &&
+        self.issued_at == other.issued_at &&
+        self.last_validated_at == other.last_validated_at &&
+        self.unknown_fields == other.unknown_fields
+    }
+}
+
+impl ::std::fmt::Debug for SessionToken {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+#[derive(Clone,Default)]
+pub struct SessionCreate {
+    // message fields
+    token: ::protobuf::SingularField<::std::string::String>,
+    extern_id: ::std::option::Option<u64>,
+    email: ::protobuf::SingularField<::std::string::String>,
+    name: ::protobuf::SingularField<::std::string::String>,
+    provider: ::std::option::Option<OAuthProvider>,
+    flags: ::std::option::Option<u32>,
+    avatar_url: ::protobuf::SingularField<::std::string::String>,
+    display_name: ::protobuf::SingularField<::std::string::String>,
+    client_id: ::protobuf::SingularField<::std::string::String>,
+    scopes: ::protobuf::RepeatedField<::std::string::String>,
+    issued_at: ::std::option::Option<u64>,
+    last_validated_at: ::std::option::Option<u64>,
+    email_verified: ::std::option::Option<bool>,
+    provider_host: ::protobuf::SingularField<::std::string::String>,
+    // special fields
+    unknown_fields: ::protobuf::UnknownFields,
+    cached_size: ::std::cell::Cell<u32>,
+}
+
+// see codegen.rs for the explanation why impl Sync explicitly
+unsafe impl ::std::marker::Sync for SessionCreate {}
+
+impl SessionCreate {
+    pub fn new() -> SessionCreate {
+        ::std::default::Default::default()
+    }
+
+    pub fn default_instance() -> &'static SessionCreate {
+        static mut instance: ::protobuf::lazy::Lazy<SessionCreate> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const SessionCreate,
+        };
+        unsafe {
+            instance.get(|| {
+                SessionCreate {
+                    token: ::protobuf::SingularField::none(),
+                    extern_id: ::std::option::Option::None,
+                    email: ::protobuf::SingularField::none(),
+                    name: ::protobuf::SingularField::none(),
+                    provider: ::std::option::Option::None,
+                    flags: ::std::option::Option::None,
+                    avatar_url: ::protobuf::SingularField::none(),
+                    display_name: ::protobuf::SingularField::none(),
+                    client_id: ::protobuf::SingularField::none(),
+                    scopes: ::protobuf::RepeatedField::new(),
+                    issued_at: ::std::option::Option::None,
+                    last_validated_at: ::std::option::Option::None,
+                    email_verified: ::std::option::Option::None,
+                    provider_host: ::protobuf::SingularField::none(),
+                    unknown_fields: ::protobuf::UnknownFields::new(),
+                    cached_size: ::std::cell::Cell::new(0),
+                }
+            })
+        }
+    }
+
+    // required string token = 1;
+
+    pub fn clear_token(&mut self) {
+        self.token.clear();
+    }
+
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_token(&mut self, v: ::std::string::String) {
+        self.token = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_token(&mut self) -> &mut ::std::string::String {
+        if self.token.is_none() {
+            self.token.set_default();
+        };
+        self.token.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_token(&mut self) -> ::std::string::String {
+        self.token.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_token(&self) -> &str {
+        match self.token.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // required uint64 extern_id = 2;
+
+    pub fn clear_extern_id(&mut self) {
+        self.extern_id = ::std::option::Option::None;
+    }
+
+    pub fn has_extern_id(&self) -> bool {
+        self.extern_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_extern_id(&mut self, v: u64) {
+        self.extern_id = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_extern_id(&self) -> u64 {
+        self.extern_id.unwrap_or(0)
+    }
+
+    // optional string email = 3;
+
+    pub fn clear_email(&mut self) {
+        self.email.clear();
+    }
+
+    pub fn has_email(&self) -> bool {
+        self.email.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_email(&mut self, v: ::std::string::String) {
+        self.email = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_email(&mut self) -> &mut ::std::string::String {
+        if self.email.is_none() {
+            self.email.set_default();
+        };
+        self.email.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_email(&mut self) -> ::std::string::String {
+        self.email.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_email(&self) -> &str {
+        match self.email.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // required string name = 4;
+
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    pub fn has_name(&self) -> bool {
+        self.name.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        if self.name.is_none() {
+            self.name.set_default();
+        };
+        self.name.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        self.name.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self.name.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // required .sessionsrv.OAuthProvider provider = 5;
+
+    pub fn clear_provider(&mut self) {
+        self.provider = ::std::option::Option::None;
+    }
+
+    pub fn has_provider(&self) -> bool {
+        self.provider.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_provider(&mut self, v: OAuthProvider) {
+        self.provider = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_provider(&self) -> OAuthProvider {
+        self.provider.unwrap_or(OAuthProvider::GitHub)
+    }
+
+    // optional uint32 flags = 6;
+
+    pub fn clear_flags(&mut self) {
+        self.flags = ::std::option::Option::None;
+    }
+
+    pub fn has_flags(&self) -> bool {
+        self.flags.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_flags(&mut self, v: u32) {
+        self.flags = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_flags(&self) -> u32 {
+        self.flags.unwrap_or(0)
+    }
+
+    // optional string avatar_url = 7;
+
+    pub fn clear_avatar_url(&mut self) {
+        self.avatar_url.clear();
+    }
+
+    pub fn has_avatar_url(&self) -> bool {
+        self.avatar_url.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_avatar_url(&mut self, v: ::std::string::String) {
+        self.avatar_url = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_avatar_url(&mut self) -> &mut ::std::string::String {
+        if self.avatar_url.is_none() {
+            self.avatar_url.set_default();
+        };
+        self.avatar_url.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_avatar_url(&mut self) -> ::std::string::String {
+        self.avatar_url.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_avatar_url(&self) -> &str {
+        match self.avatar_url.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // optional string display_name = 8;
+
+    pub fn clear_display_name(&mut self) {
+        self.display_name.clear();
+    }
+
+    pub fn has_display_name(&self) -> bool {
+        self.display_name.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_display_name(&mut self, v: ::std::string::String) {
+        self.display_name = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_display_name(&mut self) -> &mut ::std::string::String {
+        if self.display_name.is_none() {
+            self.display_name.set_default();
+        };
+        self.display_name.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_display_name(&mut self) -> ::std::string::String {
+        self.display_name.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_display_name(&self) -> &str {
+        match self.display_name.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // optional string client_id = 9;
+
+    pub fn clear_client_id(&mut self) {
+        self.client_id.clear();
+    }
+
+    pub fn has_client_id(&self) -> bool {
+        self.client_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_client_id(&mut self, v: ::std::string::String) {
+        self.client_id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_client_id(&mut self) -> &mut ::std::string::String {
+        if self.client_id.is_none() {
+            self.client_id.set_default();
+        };
+        self.client_id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_client_id(&mut self) -> ::std::string::String {
+        self.client_id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_client_id(&self) -> &str {
+        match self.client_id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // repeated string scopes = 10;
+
+    pub fn clear_scopes(&mut self) {
+        self.scopes.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_scopes(&mut self, v: ::protobuf::RepeatedField<::std::string::String>) {
+        self.scopes = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_scopes(&mut self) -> &mut ::protobuf::RepeatedField<::std::string::String> {
+        &mut self.scopes
+    }
+
+    // Take field
+    pub fn take_scopes(&mut self) -> ::protobuf::RepeatedField<::std::string::String> {
+        ::std::mem::replace(&mut self.scopes, ::protobuf::RepeatedField::new())
+    }
+
+    pub fn get_scopes(&self) -> &[::std::string::String] {
+        &self.scopes
+    }
+
+    // optional uint64 issued_at = 11;
+
+    pub fn clear_issued_at(&mut self) {
+        self.issued_at = ::std::option::Option::None;
+    }
+
+    pub fn has_issued_at(&self) -> bool {
+        self.issued_at.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_issued_at(&mut self, v: u64) {
+        self.issued_at = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_issued_at(&self) -> u64 {
+        self.issued_at.unwrap_or(0)
+    }
+
+    // optional uint64 last_validated_at = 12;
+
+    pub fn clear_last_validated_at(&mut self) {
+        self.last_validated_at = ::std::option::Option::None;
+    }
+
+    pub fn has_last_validated_at(&self) -> bool {
+        self.last_validated_at.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_validated_at(&mut self, v: u64) {
+        self.last_validated_at = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_last_validated_at(&self) -> u64 {
+        self.last_validated_at.unwrap_or(0)
+    }
+
+    // optional bool email_verified = 13;
+
+    pub fn clear_email_verified(&mut self) {
+        self.email_verified = ::std::option::Option::None;
+    }
+
+    pub fn has_email_verified(&self) -> bool {
+        self.email_verified.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_email_verified(&mut self, v: bool) {
+        self.email_verified = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_email_verified(&self) -> bool {
+        self.email_verified.unwrap_or(false)
+    }
+
+    // optional string provider_host = 14;
+
+    pub fn clear_provider_host(&mut self) {
+        self.provider_host.clear();
+    }
+
+    pub fn has_provider_host(&self) -> bool {
+        self.provider_host.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_provider_host(&mut self, v: ::std::string::String) {
+        self.provider_host = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_provider_host(&mut self) -> &mut ::std::string::String {
+        if self.provider_host.is_none() {
+            self.provider_host.set_default();
+        };
+        self.provider_host.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_provider_host(&mut self) -> ::std::string::String {
+        self.provider_host.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_provider_host(&self) -> &str {
+        match self.provider_host.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+}
+
+impl ::protobuf::Message for SessionCreate {
+    fn is_initialized(&self) -> bool {
+        if self.token.is_none() {
+            return false;
+        };
+        if self.extern_id.is_none() {
+            return false;
+        };
+        if self.name.is_none() {
+            return false;
+        };
+        if self.provider.is_none() {
+            return false;
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+        while !try!(is.eof()) {
+            let (field_number, wire_type) = try!(is.read_tag_unpack());
+            match field_number {
+                1 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.token));
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.extern_id = ::std::option::Option::Some(tmp);
+                },
+                3 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.email));
+                },
+                4 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.name));
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_enum());
+                    self.provider = ::std::option::Option::Some(tmp);
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint32());
+                    self.flags = ::std::option::Option::Some(tmp);
+                },
+                7 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.avatar_url));
+                },
+                8 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.display_name));
+                },
+                9 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.client_id));
+                },
+                10 => {
+                    try!(::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.scopes));
+                },
+                11 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.issued_at = ::std::option::Option::Some(tmp);
+                },
+                12 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.last_validated_at = ::std::option::Option::Some(tmp);
+                },
+                13 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_bool());
+                    self.email_verified = ::std::option::Option::Some(tmp);
+                },
+                14 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.provider_host));
+                },
+                _ => {
+                    try!(::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields()));
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in self.token.iter() {
+            my_size += ::protobuf::rt::string_size(1, &value);
+        };
+        for value in self.extern_id.iter() {
+            my_size += ::protobuf::rt::value_size(2, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.email.iter() {
+            my_size += ::protobuf::rt::string_size(3, &value);
+        };
+        for value in self.name.iter() {
+            my_size += ::protobuf::rt::string_size(4, &value);
+        };
+        for value in self.provider.iter() {
+            my_size += ::protobuf::rt::enum_size(5, *value);
+        };
+        for value in self.flags.iter() {
+            my_size += ::protobuf::rt::value_size(6, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.avatar_url.iter() {
+            my_size += ::protobuf::rt::string_size(7, &value);
+        };
+        for value in self.display_name.iter() {
+            my_size += ::protobuf::rt::string_size(8, &value);
+        };
+        for value in self.client_id.iter() {
+            my_size += ::protobuf::rt::string_size(9, &value);
+        };
+        for value in self.scopes.iter() {
+            my_size += ::protobuf::rt::string_size(10, &value);
+        };
+        for value in self.issued_at.iter() {
+            my_size += ::protobuf::rt::value_size(11, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.last_validated_at.iter() {
+            my_size += ::protobuf::rt::value_size(12, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        if self.email_verified.is_some() {
+            my_size += 2;
+        };
+        for value in self.provider_host.iter() {
+            my_size += ::protobuf::rt::string_size(14, &value);
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.token.as_ref() {
+            try!(os.write_string(1, &v));
+        };
+        if let Some(v) = self.extern_id {
+            try!(os.write_uint64(2, v));
+        };
+        if let Some(v) = self.email.as_ref() {
+            try!(os.write_string(3, &v));
+        };
+        if let Some(v) = self.name.as_ref() {
+            try!(os.write_string(4, &v));
+        };
+        if let Some(v) = self.provider {
+            try!(os.write_enum(5, v.value()));
+        };
+        if let Some(v) = self.flags {
+            try!(os.write_uint32(6, v));
+        };
+        if let Some(v) = self.avatar_url.as_ref() {
+            try!(os.write_string(7, &v));
+        };
+        if let Some(v) = self.display_name.as_ref() {
+            try!(os.write_string(8, &v));
+        };
+        if let Some(v) = self.client_id.as_ref() {
+            try!(os.write_string(9, &v));
+        };
+        for v in self.scopes.iter() {
+            try!(os.write_string(10, &v));
+        };
+        if let Some(v) = self.issued_at {
+            try!(os.write_uint64(11, v));
+        };
+        if let Some(v) = self.last_validated_at {
+            try!(os.write_uint64(12, v));
+        };
+        if let Some(v) = self.email_verified {
+            try!(os.write_bool(13, v));
+        };
+        if let Some(v) = self.provider_host.as_ref() {
+            try!(os.write_string(14, &v));
+        };
+        try!(os.write_unknown_fields(self.get_unknown_fields()));
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn type_id(&self) -> ::std::any::TypeId {
+        ::std::any::TypeId::of::<SessionCreate>()
+    }
+
+    fn as_any(&self) -> &::std::any::Any {
+        self as &::std::any::Any
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        ::protobuf::MessageStatic::descriptor_static(None::<Self>)
+    }
+}
+
+impl ::protobuf::MessageStatic for SessionCreate {
+    fn new() -> SessionCreate {
+        SessionCreate::new()
+    }
+
+    fn descriptor_static(_: ::std::option::Option<SessionCreate>) -> &'static ::protobuf::reflect::MessageDescriptor {
         static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
             ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
@@ -777,28 +2570,77 @@ impl ::protobuf::MessageStatic for Session {
         unsafe {
             descriptor.get(|| {
                 let mut fields = ::std::vec::Vec::new();
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "token",
+                    SessionCreate::has_token,
+                    SessionCreate::get_token,
+                ));
                 fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
-                    "id",
-                    Session::has_id,
-                    Session::get_id,
+                    "extern_id",
+                    SessionCreate::has_extern_id,
+                    SessionCreate::get_extern_id,
                 ));
                 fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
                     "email",
-                    Session::has_email,
-                    Session::get_email,
+                    SessionCreate::has_email,
+                    SessionCreate::get_email,
                 ));
                 fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
                     "name",
-                    Session::has_name,
-                    Session::get_name,
+                    SessionCreate::has_name,
+                    SessionCreate::get_name,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_enum_accessor(
+                    "provider",
+                    SessionCreate::has_provider,
+                    SessionCreate::get_provider,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u32_accessor(
+                    "flags",
+                    SessionCreate::has_flags,
+                    SessionCreate::get_flags,
                 ));
                 fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
-                    "token",
-                    Session::has_token,
-                    Session::get_token,
+                    "avatar_url",
+                    SessionCreate::has_avatar_url,
+                    SessionCreate::get_avatar_url,
                 ));
-                ::protobuf::reflect::MessageDescriptor::new::<Session>(
-                    "Session",
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "display_name",
+                    SessionCreate::has_display_name,
+                    SessionCreate::get_display_name,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "client_id",
+                    SessionCreate::has_client_id,
+                    SessionCreate::get_client_id,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_repeated_string_accessor(
+                    "scopes",
+                    SessionCreate::get_scopes,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "issued_at",
+                    SessionCreate::has_issued_at,
+                    SessionCreate::get_issued_at,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "last_validated_at",
+                    SessionCreate::has_last_validated_at,
+                    SessionCreate::get_last_validated_at,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_bool_accessor(
+                    "email_verified",
+                    SessionCreate::has_email_verified,
+                    SessionCreate::get_email_verified,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "provider_host",
+                    SessionCreate::has_provider_host,
+                    SessionCreate::get_provider_host,
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<SessionCreate>(
+                    "SessionCreate",
                     fields,
                     file_descriptor_proto()
                 )
@@ -807,60 +2649,80 @@ impl ::protobuf::MessageStatic for Session {
     }
 }
 
-impl ::protobuf::Clear for Session {
+impl ::protobuf::Clear for SessionCreate {
     fn clear(&mut self) {
-        self.clear_id();
+        self.clear_token();
+        self.clear_extern_id();
         self.clear_email();
         self.clear_name();
-        self.clear_token();
+        self.clear_provider();
+        self.clear_flags();
+        self.clear_avatar_url();
+        self.clear_display_name();
+        self.clear_client_id();
+        self.clear_scopes();
+        self.clear_issued_at();
+        self.clear_last_validated_at();
+        self.clear_email_verified();
+        self.clear_provider_host();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::cmp::PartialEq for Session {
-    fn eq(&self, other: &Session) -> bool {
-        self.id == other.id &&
+impl ::std::cmp::PartialEq for SessionCreate {
+    fn eq(&self, other: &SessionCreate) -> bool {
+        self.token == other.token &&
+        self.extern_id == other.extern_id &&
         self.email == other.email &&
         self.name == other.name &&
-        self.token == other.token &&
+        self.provider == other.provider &&
+        self.flags == other.flags &&
+        self.avatar_url == other.avatar_url &&
+        self.display_name == other.display_name &&
+        self.client_id == other.client_id &&
+        self.scopes == other.scopes &&
+        self.issued_at == other.issued_at &&
+        self.last_validated_at == other.last_validated_at &&
+        self.email_verified == other.email_verified &&
+        self.provider_host == other.provider_host &&
         self.unknown_fields == other.unknown_fields
     }
 }
 
-impl ::std::fmt::Debug for Session {
+impl ::std::fmt::Debug for SessionCreate {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
 #[derive(Clone,Default)]
-pub struct SessionToken {
+pub struct SessionGet {
     // message fields
     token: ::protobuf::SingularField<::std::string::String>,
-    owner_id: ::std::option::Option<u64>,
+    client_ids: ::protobuf::RepeatedField<::std::string::String>,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::std::cell::Cell<u32>,
 }
 
 // see codegen.rs for the explanation why impl Sync explicitly
-unsafe impl ::std::marker::Sync for SessionToken {}
+unsafe impl ::std::marker::Sync for SessionGet {}
 
-impl SessionToken {
-    pub fn new() -> SessionToken {
+impl SessionGet {
+    pub fn new() -> SessionGet {
         ::std::default::Default::default()
     }
 
-    pub fn default_instance() -> &'static SessionToken {
-        static mut instance: ::protobuf::lazy::Lazy<SessionToken> = ::protobuf::lazy::Lazy {
+    pub fn default_instance() -> &'static SessionGet {
+        static mut instance: ::protobuf::lazy::Lazy<SessionGet> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const SessionToken,
+            ptr: 0 as *const SessionGet,
         };
         unsafe {
             instance.get(|| {
-                SessionToken {
+                SessionGet {
                     token: ::protobuf::SingularField::none(),
-                    owner_id: ::std::option::Option::None,
+                    client_ids: ::protobuf::RepeatedField::new(),
                     unknown_fields: ::protobuf::UnknownFields::new(),
                     cached_size: ::std::cell::Cell::new(0),
                 }
@@ -904,34 +2766,37 @@ impl SessionToken {
         }
     }
 
-    // required uint64 owner_id = 2;
+    // repeated string client_ids = 2;
 
-    pub fn clear_owner_id(&mut self) {
-        self.owner_id = ::std::option::Option::None;
+    pub fn clear_client_ids(&mut self) {
+        self.client_ids.clear();
     }
 
-    pub fn has_owner_id(&self) -> bool {
-        self.owner_id.is_some()
+    // Param is passed by value, moved
+    pub fn set_client_ids(&mut self, v: ::protobuf::RepeatedField<::std::string::String>) {
+        self.client_ids = v;
     }
 
-    // Param is passed by value, moved
-    pub fn set_owner_id(&mut self, v: u64) {
-        self.owner_id = ::std::option::Option::Some(v);
+    // Mutable pointer to the field.
+    pub fn mut_client_ids(&mut self) -> &mut ::protobuf::RepeatedField<::std::string::String> {
+        &mut self.client_ids
     }
 
-    pub fn get_owner_id(&self) -> u64 {
-        self.owner_id.unwrap_or(0)
+    // Take field
+    pub fn take_client_ids(&mut self) -> ::protobuf::RepeatedField<::std::string::String> {
+        ::std::mem::replace(&mut self.client_ids, ::protobuf::RepeatedField::new())
+    }
+
+    pub fn get_client_ids(&self) -> &[::std::string::String] {
+        &self.client_ids
     }
 }
 
-impl ::protobuf::Message for SessionToken {
+impl ::protobuf::Message for SessionGet {
     fn is_initialized(&self) -> bool {
         if self.token.is_none() {
             return false;
         };
-        if self.owner_id.is_none() {
-            return false;
-        };
         true
     }
 
@@ -943,11 +2808,7 @@ impl ::protobuf::Message for SessionToken {
                     try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.token));
                 },
                 2 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
-                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
-                    };
-                    let tmp = try!(is.read_uint64());
-                    self.owner_id = ::std::option::Option::Some(tmp);
+                    try!(::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.client_ids));
                 },
                 _ => {
                     try!(::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields()));
@@ -964,8 +2825,8 @@ impl ::protobuf::Message for SessionToken {
         for value in self.token.iter() {
             my_size += ::protobuf::rt::string_size(1, &value);
         };
-        for value in self.owner_id.iter() {
-            my_size += ::protobuf::rt::value_size(2, *value, ::protobuf::wire_format::WireTypeVarint);
+        for value in self.client_ids.iter() {
+            my_size += ::protobuf::rt::string_size(2, &value);
         };
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
@@ -976,8 +2837,8 @@ impl ::protobuf::Message for SessionToken {
         if let Some(v) = self.token.as_ref() {
             try!(os.write_string(1, &v));
         };
-        if let Some(v) = self.owner_id {
-            try!(os.write_uint64(2, v));
+        for v in self.client_ids.iter() {
+            try!(os.write_string(2, &v));
         };
         try!(os.write_unknown_fields(self.get_unknown_fields()));
         ::std::result::Result::Ok(())
@@ -996,7 +2857,7 @@ impl ::protobuf::Message for SessionToken {
     }
 
     fn type_id(&self) -> ::std::any::TypeId {
-        ::std::any::TypeId::of::<SessionToken>()
+        ::std::any::TypeId::of::<SessionGet>()
     }
 
     fn as_any(&self) -> &::std::any::Any {
@@ -1008,12 +2869,12 @@ impl ::protobuf::Message for SessionToken {
     }
 }
 
-impl ::protobuf::MessageStatic for SessionToken {
-    fn new() -> SessionToken {
-        SessionToken::new()
+impl ::protobuf::MessageStatic for SessionGet {
+    fn new() -> SessionGet {
+        SessionGet::new()
     }
 
-    fn descriptor_static(_: ::std::option::Option<SessionToken>) -> &'static ::protobuf::reflect::MessageDescriptor {
+    fn descriptor_static(_: ::std::option::Option<SessionGet>) -> &'static ::protobuf::reflect::MessageDescriptor {
         static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
             ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
@@ -1023,16 +2884,15 @@ impl ::protobuf::MessageStatic for SessionToken {
                 let mut fields = ::std::vec::Vec::new();
                 fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
                     "token",
-                    SessionToken::has_token,
-                    SessionToken::get_token,
+                    SessionGet::has_token,
+                    SessionGet::get_token,
                 ));
-                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
-                    "owner_id",
-                    SessionToken::has_owner_id,
-                    SessionToken::get_owner_id,
+                fields.push(::protobuf::reflect::accessor::make_repeated_string_accessor(
+                    "client_ids",
+                    SessionGet::get_client_ids,
                 ));
-                ::protobuf::reflect::MessageDescriptor::new::<SessionToken>(
-                    "SessionToken",
+                ::protobuf::reflect::MessageDescriptor::new::<SessionGet>(
+                    "SessionGet",
                     fields,
                     file_descriptor_proto()
                 )
@@ -1041,62 +2901,54 @@ impl ::protobuf::MessageStatic for SessionToken {
     }
 }
 
-impl ::protobuf::Clear for SessionToken {
+impl ::protobuf::Clear for SessionGet {
     fn clear(&mut self) {
         self.clear_token();
-        self.clear_owner_id();
+        self.clear_client_ids();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::cmp::PartialEq for SessionToken {
-    fn eq(&self, other: &SessionToken) -> bool {
+impl ::std::cmp::PartialEq for SessionGet {
+    fn eq(&self, other: &SessionGet) -> bool {
         self.token == other.token &&
-        self.owner_id == other.owner_id &&
+        self.client_ids == other.client_ids &&
         self.unknown_fields == other.unknown_fields
     }
 }
 
-impl ::std::fmt::Debug for SessionToken {
+impl ::std::fmt::Debug for SessionGet {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
 #[derive(Clone,Default)]
-pub struct SessionCreate {
+pub struct SessionDelete {
     // message fields
     token: ::protobuf::SingularField<::std::string::String>,
-    extern_id: ::std::option::Option<u64>,
-    email: ::protobuf::SingularField<::std::string::String>,
-    name: ::protobuf::SingularField<::std::string::String>,
-    provider: ::std::option::Option<OAuthProvider>,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::std::cell::Cell<u32>,
 }
 
 // see codegen.rs for the explanation why impl Sync explicitly
-unsafe impl ::std::marker::Sync for SessionCreate {}
+unsafe impl ::std::marker::Sync for SessionDelete {}
 
-impl SessionCreate {
-    pub fn new() -> SessionCreate {
+impl SessionDelete {
+    pub fn new() -> SessionDelete {
         ::std::default::Default::default()
     }
 
-    pub fn default_instance() -> &'static SessionCreate {
-        static mut instance: ::protobuf::lazy::Lazy<SessionCreate> = ::protobuf::lazy::Lazy {
+    pub fn default_instance() -> &'static SessionDelete {
+        static mut instance: ::protobuf::lazy::Lazy<SessionDelete> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const SessionCreate,
+            ptr: 0 as *const SessionDelete,
         };
         unsafe {
             instance.get(|| {
-                SessionCreate {
+                SessionDelete {
                     token: ::protobuf::SingularField::none(),
-                    extern_id: ::std::option::Option::None,
-                    email: ::protobuf::SingularField::none(),
-                    name: ::protobuf::SingularField::none(),
-                    provider: ::std::option::Option::None,
                     unknown_fields: ::protobuf::UnknownFields::new(),
                     cached_size: ::std::cell::Cell::new(0),
                 }
@@ -1105,169 +2957,47 @@ impl SessionCreate {
     }
 
     // required string token = 1;
-
-    pub fn clear_token(&mut self) {
-        self.token.clear();
-    }
-
-    pub fn has_token(&self) -> bool {
-        self.token.is_some()
-    }
-
-    // Param is passed by value, moved
-    pub fn set_token(&mut self, v: ::std::string::String) {
-        self.token = ::protobuf::SingularField::some(v);
-    }
-
-    // Mutable pointer to the field.
-    // If field is not initialized, it is initialized with default value first.
-    pub fn mut_token(&mut self) -> &mut ::std::string::String {
-        if self.token.is_none() {
-            self.token.set_default();
-        };
-        self.token.as_mut().unwrap()
-    }
-
-    // Take field
-    pub fn take_token(&mut self) -> ::std::string::String {
-        self.token.take().unwrap_or_else(|| ::std::string::String::new())
-    }
-
-    pub fn get_token(&self) -> &str {
-        match self.token.as_ref() {
-            Some(v) => &v,
-            None => "",
-        }
-    }
-
-    // required uint64 extern_id = 2;
-
-    pub fn clear_extern_id(&mut self) {
-        self.extern_id = ::std::option::Option::None;
-    }
-
-    pub fn has_extern_id(&self) -> bool {
-        self.extern_id.is_some()
-    }
-
-    // Param is passed by value, moved
-    pub fn set_extern_id(&mut self, v: u64) {
-        self.extern_id = ::std::option::Option::Some(v);
-    }
-
-    pub fn get_extern_id(&self) -> u64 {
-        self.extern_id.unwrap_or(0)
-    }
-
-    // required string email = 3;
-
-    pub fn clear_email(&mut self) {
-        self.email.clear();
-    }
-
-    pub fn has_email(&self) -> bool {
-        self.email.is_some()
-    }
-
-    // Param is passed by value, moved
-    pub fn set_email(&mut self, v: ::std::string::String) {
-        self.email = ::protobuf::SingularField::some(v);
-    }
-
-    // Mutable pointer to the field.
-    // If field is not initialized, it is initialized with default value first.
-    pub fn mut_email(&mut self) -> &mut ::std::string::String {
-        if self.email.is_none() {
-            self.email.set_default();
-        };
-        self.email.as_mut().unwrap()
-    }
-
-    // Take field
-    pub fn take_email(&mut self) -> ::std::string::String {
-        self.email.take().unwrap_or_else(|| ::std::string::String::new())
-    }
-
-    pub fn get_email(&self) -> &str {
-        match self.email.as_ref() {
-            Some(v) => &v,
-            None => "",
-        }
-    }
-
-    // required string name = 4;
-
-    pub fn clear_name(&mut self) {
-        self.name.clear();
+
+    pub fn clear_token(&mut self) {
+        self.token.clear();
     }
 
-    pub fn has_name(&self) -> bool {
-        self.name.is_some()
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
     }
 
     // Param is passed by value, moved
-    pub fn set_name(&mut self, v: ::std::string::String) {
-        self.name = ::protobuf::SingularField::some(v);
+    pub fn set_token(&mut self, v: ::std::string::String) {
+        self.token = ::protobuf::SingularField::some(v);
     }
 
     // Mutable pointer to the field.
     // If field is not initialized, it is initialized with default value first.
-    pub fn mut_name(&mut self) -> &mut ::std::string::String {
-        if self.name.is_none() {
-            self.name.set_default();
+    pub fn mut_token(&mut self) -> &mut ::std::string::String {
+        if self.token.is_none() {
+            self.token.set_default();
         };
-        self.name.as_mut().unwrap()
+        self.token.as_mut().unwrap()
     }
 
     // Take field
-    pub fn take_name(&mut self) -> ::std::string::String {
-        self.name.take().unwrap_or_else(|| ::std::string::String::new())
+    pub fn take_token(&mut self) -> ::std::string::String {
+        self.token.take().unwrap_or_else(|| ::std::string::String::new())
     }
 
-    pub fn get_name(&self) -> &str {
-        match self.name.as_ref() {
+    pub fn get_token(&self) -> &str {
+        match self.token.as_ref() {
             Some(v) => &v,
             None => "",
         }
     }
-
-    // required .sessionsrv.OAuthProvider provider = 5;
-
-    pub fn clear_provider(&mut self) {
-        self.provider = ::std::option::Option::None;
-    }
-
-    pub fn has_provider(&self) -> bool {
-        self.provider.is_some()
-    }
-
-    // Param is passed by value, moved
-    pub fn set_provider(&mut self, v: OAuthProvider) {
-        self.provider = ::std::option::Option::Some(v);
-    }
-
-    pub fn get_provider(&self) -> OAuthProvider {
-        self.provider.unwrap_or(OAuthProvider::GitHub)
-    }
 }
 
-impl ::protobuf::Message for SessionCreate {
+impl ::protobuf::Message for SessionDelete {
     fn is_initialized(&self) -> bool {
         if self.token.is_none() {
             return false;
         };
-        if self.extern_id.is_none() {
-            return false;
-        };
-        if self.email.is_none() {
-            return false;
-        };
-        if self.name.is_none() {
-            return false;
-        };
-        if self.provider.is_none() {
-            return false;
-        };
         true
     }
 
@@ -1278,26 +3008,6 @@ impl ::protobuf::Message for SessionCreate {
                 1 => {
                     try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.token));
                 },
-                2 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
-                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
-                    };
-                    let tmp = try!(is.read_uint64());
-                    self.extern_id = ::std::option::Option::Some(tmp);
-                },
-                3 => {
-                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.email));
-                },
-                4 => {
-                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.name));
-                },
-                5 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
-                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
-                    };
-                    let tmp = try!(is.read_enum());
-                    self.provider = ::std::option::Option::Some(tmp);
-                },
                 _ => {
                     try!(::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields()));
                 },
@@ -1313,18 +3023,6 @@ impl ::protobuf::Message for SessionCreate {
         for value in self.token.iter() {
             my_size += ::protobuf::rt::string_size(1, &value);
         };
-        for value in self.extern_id.iter() {
-            my_size += ::protobuf::rt::value_size(2, *value, ::protobuf::wire_format::WireTypeVarint);
-        };
-        for value in self.email.iter() {
-            my_size += ::protobuf::rt::string_size(3, &value);
-        };
-        for value in self.name.iter() {
-            my_size += ::protobuf::rt::string_size(4, &value);
-        };
-        for value in self.provider.iter() {
-            my_size += ::protobuf::rt::enum_size(5, *value);
-        };
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -1334,18 +3032,6 @@ impl ::protobuf::Message for SessionCreate {
         if let Some(v) = self.token.as_ref() {
             try!(os.write_string(1, &v));
         };
-        if let Some(v) = self.extern_id {
-            try!(os.write_uint64(2, v));
-        };
-        if let Some(v) = self.email.as_ref() {
-            try!(os.write_string(3, &v));
-        };
-        if let Some(v) = self.name.as_ref() {
-            try!(os.write_string(4, &v));
-        };
-        if let Some(v) = self.provider {
-            try!(os.write_enum(5, v.value()));
-        };
         try!(os.write_unknown_fields(self.get_unknown_fields()));
         ::std::result::Result::Ok(())
     }
@@ -1363,7 +3049,7 @@ impl ::protobuf::Message for SessionCreate {
     }
 
     fn type_id(&self) -> ::std::any::TypeId {
-        ::std::any::TypeId::of::<SessionCreate>()
+        ::std::any::TypeId::of::<SessionDelete>()
     }
 
     fn as_any(&self) -> &::std::any::Any {
@@ -1375,12 +3061,12 @@ impl ::protobuf::Message for SessionCreate {
     }
 }
 
-impl ::protobuf::MessageStatic for SessionCreate {
-    fn new() -> SessionCreate {
-        SessionCreate::new()
+impl ::protobuf::MessageStatic for SessionDelete {
+    fn new() -> SessionDelete {
+        SessionDelete::new()
     }
 
-    fn descriptor_static(_: ::std::option::Option<SessionCreate>) -> &'static ::protobuf::reflect::MessageDescriptor {
+    fn descriptor_static(_: ::std::option::Option<SessionDelete>) -> &'static ::protobuf::reflect::MessageDescriptor {
         static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
             ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
@@ -1390,31 +3076,11 @@ impl ::protobuf::MessageStatic for SessionCreate {
                 let mut fields = ::std::vec::Vec::new();
                 fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
                     "token",
-                    SessionCreate::has_token,
-                    SessionCreate::get_token,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
-                    "extern_id",
-                    SessionCreate::has_extern_id,
-                    SessionCreate::get_extern_id,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
-                    "email",
-                    SessionCreate::has_email,
-                    SessionCreate::get_email,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
-                    "name",
-                    SessionCreate::has_name,
-                    SessionCreate::get_name,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_enum_accessor(
-                    "provider",
-                    SessionCreate::has_provider,
-                    SessionCreate::get_provider,
+                    SessionDelete::has_token,
+                    SessionDelete::get_token,
                 ));
-                ::protobuf::reflect::MessageDescriptor::new::<SessionCreate>(
-                    "SessionCreate",
+                ::protobuf::reflect::MessageDescriptor::new::<SessionDelete>(
+                    "SessionDelete",
                     fields,
                     file_descriptor_proto()
                 )
@@ -1423,60 +3089,60 @@ impl ::protobuf::MessageStatic for SessionCreate {
     }
 }
 
-impl ::protobuf::Clear for SessionCreate {
+impl ::protobuf::Clear for SessionDelete {
     fn clear(&mut self) {
         self.clear_token();
-        self.clear_extern_id();
-        self.clear_email();
-        self.clear_name();
-        self.clear_provider();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::cmp::PartialEq for SessionCreate {
-    fn eq(&self, other: &SessionCreate) -> bool {
+impl ::std::cmp::PartialEq for SessionDelete {
+    fn eq(&self, other: &SessionDelete) -> bool {
         self.token == other.token &&
-        self.extern_id == other.extern_id &&
-        self.email == other.email &&
-        self.name == other.name &&
-        self.provider == other.provider &&
         self.unknown_fields == other.unknown_fields
     }
 }
 
-impl ::std::fmt::Debug for SessionCreate {
+impl ::std::fmt::Debug for SessionDelete {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
 #[derive(Clone,Default)]
-pub struct SessionGet {
+pub struct SessionValidated {
     // message fields
     token: ::protobuf::SingularField<::std::string::String>,
+    client_id: ::protobuf::SingularField<::std::string::String>,
+    scopes: ::protobuf::RepeatedField<::std::string::String>,
+    issued_at: ::std::option::Option<u64>,
+    last_validated_at: ::std::option::Option<u64>,
     // special fields
     unknown_fields: ::protobuf::UnknownFields,
     cached_size: ::std::cell::Cell<u32>,
 }
 
 // see codegen.rs for the explanation why impl Sync explicitly
-unsafe impl ::std::marker::Sync for SessionGet {}
+unsafe impl ::std::marker::Sync for SessionValidated {}
 
-impl SessionGet {
-    pub fn new() -> SessionGet {
+impl SessionValidated {
+    pub fn new() -> SessionValidated {
         ::std::default::Default::default()
     }
 
-    pub fn default_instance() -> &'static SessionGet {
-        static mut instance: ::protobuf::lazy::Lazy<SessionGet> = ::protobuf::lazy::Lazy {
+    pub fn default_instance() -> &'static SessionValidated {
+        static mut instance: ::protobuf::lazy::Lazy<SessionValidated> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const SessionGet,
+            ptr: 0 as *const SessionValidated,
         };
         unsafe {
             instance.get(|| {
-                SessionGet {
+                SessionValidated {
                     token: ::protobuf::SingularField::none(),
+                    client_id: ::protobuf::SingularField::none(),
+                    scopes: ::protobuf::RepeatedField::new(),
+                    issued_at: ::std::option::Option::None,
+                    last_validated_at: ::std::option::Option::None,
                     unknown_fields: ::protobuf::UnknownFields::new(),
                     cached_size: ::std::cell::Cell::new(0),
                 }
@@ -1519,9 +3185,108 @@ impl SessionGet {
             None => "",
         }
     }
+
+    // optional string client_id = 2;
+
+    pub fn clear_client_id(&mut self) {
+        self.client_id.clear();
+    }
+
+    pub fn has_client_id(&self) -> bool {
+        self.client_id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_client_id(&mut self, v: ::std::string::String) {
+        self.client_id = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_client_id(&mut self) -> &mut ::std::string::String {
+        if self.client_id.is_none() {
+            self.client_id.set_default();
+        };
+        self.client_id.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_client_id(&mut self) -> ::std::string::String {
+        self.client_id.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+
+    pub fn get_client_id(&self) -> &str {
+        match self.client_id.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+
+    // repeated string scopes = 3;
+
+    pub fn clear_scopes(&mut self) {
+        self.scopes.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_scopes(&mut self, v: ::protobuf::RepeatedField<::std::string::String>) {
+        self.scopes = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_scopes(&mut self) -> &mut ::protobuf::RepeatedField<::std::string::String> {
+        &mut self.scopes
+    }
+
+    // Take field
+    pub fn take_scopes(&mut self) -> ::protobuf::RepeatedField<::std::string::String> {
+        ::std::mem::replace(&mut self.scopes, ::protobuf::RepeatedField::new())
+    }
+
+    pub fn get_scopes(&self) -> &[::std::string::String] {
+        &self.scopes
+    }
+
+    // optional uint64 issued_at = 4;
+
+    pub fn clear_issued_at(&mut self) {
+        self.issued_at = ::std::option::Option::None;
+    }
+
+    pub fn has_issued_at(&self) -> bool {
+        self.issued_at.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_issued_at(&mut self, v: u64) {
+        self.issued_at = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_issued_at(&self) -> u64 {
+        self.issued_at.unwrap_or(0)
+    }
+
+    // optional uint64 last_validated_at = 5;
+
+    pub fn clear_last_validated_at(&mut self) {
+        self.last_validated_at = ::std::option::Option::None;
+    }
+
+    pub fn has_last_validated_at(&self) -> bool {
+        self.last_validated_at.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_last_validated_at(&mut self, v: u64) {
+        self.last_validated_at = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_last_validated_at(&self) -> u64 {
+        self.last_validated_at.unwrap_or(0)
+    }
 }
 
-impl ::protobuf::Message for SessionGet {
+impl ::protobuf::Message for SessionValidated {
     fn is_initialized(&self) -> bool {
         if self.token.is_none() {
             return false;
@@ -1536,6 +3301,26 @@ impl ::protobuf::Message for SessionGet {
                 1 => {
                     try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.token));
                 },
+                2 => {
+                    try!(::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.client_id));
+                },
+                3 => {
+                    try!(::protobuf::rt::read_repeated_string_into(wire_type, is, &mut self.scopes));
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.issued_at = ::std::option::Option::Some(tmp);
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    };
+                    let tmp = try!(is.read_uint64());
+                    self.last_validated_at = ::std::option::Option::Some(tmp);
+                },
                 _ => {
                     try!(::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields()));
                 },
@@ -1551,6 +3336,18 @@ impl ::protobuf::Message for SessionGet {
         for value in self.token.iter() {
             my_size += ::protobuf::rt::string_size(1, &value);
         };
+        for value in self.client_id.iter() {
+            my_size += ::protobuf::rt::string_size(2, &value);
+        };
+        for value in self.scopes.iter() {
+            my_size += ::protobuf::rt::string_size(3, &value);
+        };
+        for value in self.issued_at.iter() {
+            my_size += ::protobuf::rt::value_size(4, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
+        for value in self.last_validated_at.iter() {
+            my_size += ::protobuf::rt::value_size(5, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -1560,6 +3357,18 @@ impl ::protobuf::Message for SessionGet {
         if let Some(v) = self.token.as_ref() {
             try!(os.write_string(1, &v));
         };
+        if let Some(v) = self.client_id.as_ref() {
+            try!(os.write_string(2, &v));
+        };
+        for v in self.scopes.iter() {
+            try!(os.write_string(3, &v));
+        };
+        if let Some(v) = self.issued_at {
+            try!(os.write_uint64(4, v));
+        };
+        if let Some(v) = self.last_validated_at {
+            try!(os.write_uint64(5, v));
+        };
         try!(os.write_unknown_fields(self.get_unknown_fields()));
         ::std::result::Result::Ok(())
     }
@@ -1577,7 +3386,7 @@ impl ::protobuf::Message for SessionGet {
     }
 
     fn type_id(&self) -> ::std::any::TypeId {
-        ::std::any::TypeId::of::<SessionGet>()
+        ::std::any::TypeId::of::<SessionValidated>()
     }
 
     fn as_any(&self) -> &::std::any::Any {
@@ -1589,12 +3398,12 @@ impl ::protobuf::Message for SessionGet {
     }
 }
 
-impl ::protobuf::MessageStatic for SessionGet {
-    fn new() -> SessionGet {
-        SessionGet::new()
+impl ::protobuf::MessageStatic for SessionValidated {
+    fn new() -> SessionValidated {
+        SessionValidated::new()
     }
 
-    fn descriptor_static(_: ::std::option::Option<SessionGet>) -> &'static ::protobuf::reflect::MessageDescriptor {
+    fn descriptor_static(_: ::std::option::Option<SessionValidated>) -> &'static ::protobuf::reflect::MessageDescriptor {
         static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
             lock: ::protobuf::lazy::ONCE_INIT,
             ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
@@ -1604,11 +3413,30 @@ impl ::protobuf::MessageStatic for SessionGet {
                 let mut fields = ::std::vec::Vec::new();
                 fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
                     "token",
-                    SessionGet::has_token,
-                    SessionGet::get_token,
+                    SessionValidated::has_token,
+                    SessionValidated::get_token,
                 ));
-                ::protobuf::reflect::MessageDescriptor::new::<SessionGet>(
-                    "SessionGet",
+                fields.push(::protobuf::reflect::accessor::make_singular_string_accessor(
+                    "client_id",
+                    SessionValidated::has_client_id,
+                    SessionValidated::get_client_id,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_repeated_string_accessor(
+                    "scopes",
+                    SessionValidated::get_scopes,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "issued_at",
+                    SessionValidated::has_issued_at,
+                    SessionValidated::get_issued_at,
+                ));
+                fields.push(::protobuf::reflect::accessor::make_singular_u64_accessor(
+                    "last_validated_at",
+                    SessionValidated::has_last_validated_at,
+                    SessionValidated::get_last_validated_at,
+                ));
+                ::protobuf::reflect::MessageDescriptor::new::<SessionValidated>(
+                    "SessionValidated",
                     fields,
                     file_descriptor_proto()
                 )
@@ -1617,21 +3445,29 @@ impl ::protobuf::MessageStatic for SessionGet {
     }
 }
 
-impl ::protobuf::Clear for SessionGet {
+impl ::protobuf::Clear for SessionValidated {
     fn clear(&mut self) {
         self.clear_token();
+        self.clear_client_id();
+        self.clear_scopes();
+        self.clear_issued_at();
+        self.clear_last_validated_at();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::cmp::PartialEq for SessionGet {
-    fn eq(&self, other: &SessionGet) -> bool {
+impl ::std::cmp::PartialEq for SessionValidated {
+    fn eq(&self, other: &SessionValidated) -> bool {
         self.token == other.token &&
+        self.client_id == other.client_id &&
+        self.scopes == other.scopes &&
+        self.issued_at == other.issued_at &&
+        self.last_validated_at == other.last_validated_at &&
         self.unknown_fields == other.unknown_fields
     }
 }
 
-impl ::std::fmt::Debug for SessionGet {
+impl ::std::fmt::Debug for SessionValidated {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
@@ -1640,6 +3476,8 @@ impl ::std::fmt::Debug for SessionGet {
 #[derive(Clone,PartialEq,Eq,Debug,Hash)]
 pub enum OAuthProvider {
     GitHub = 0,
+    GitLab = 1,
+    Stub = 2,
 }
 
 impl ::protobuf::ProtobufEnum for OAuthProvider {
@@ -1650,6 +3488,8 @@ impl ::protobuf::ProtobufEnum for OAuthProvider {
     fn from_i32(value: i32) -> ::std::option::Option<OAuthProvider> {
         match value {
             0 => ::std::option::Option::Some(OAuthProvider::GitHub),
+            1 => ::std::option::Option::Some(OAuthProvider::GitLab),
+            2 => ::std::option::Option::Some(OAuthProvider::Stub),
             _ => ::std::option::Option::None
         }
     }
@@ -1657,6 +3497,8 @@ impl ::protobuf::ProtobufEnum for OAuthProvider {
     fn values() -> &'static [Self] {
         static values: &'static [OAuthProvider] = &[
             OAuthProvider::GitHub,
+            OAuthProvider::GitLab,
+            OAuthProvider::Stub,
         ];
         values
     }
@@ -1680,116 +3522,372 @@ impl ::std::marker::Copy for OAuthProvider {
 static file_descriptor_proto_data: &'static [u8] = &[
     0x0a, 0x1a, 0x70, 0x72, 0x6f, 0x74, 0x6f, 0x63, 0x6f, 0x6c, 0x73, 0x2f, 0x73, 0x65, 0x73, 0x73,
     0x69, 0x6f, 0x6e, 0x73, 0x72, 0x76, 0x2e, 0x70, 0x72, 0x6f, 0x74, 0x6f, 0x12, 0x0a, 0x73, 0x65,
-    0x73, 0x73, 0x69, 0x6f, 0x6e, 0x73, 0x72, 0x76, 0x22, 0x32, 0x0a, 0x07, 0x41, 0x63, 0x63, 0x6f,
-    0x75, 0x6e, 0x74, 0x12, 0x0a, 0x0a, 0x02, 0x69, 0x64, 0x18, 0x01, 0x20, 0x02, 0x28, 0x04, 0x12,
-    0x0d, 0x0a, 0x05, 0x65, 0x6d, 0x61, 0x69, 0x6c, 0x18, 0x02, 0x20, 0x02, 0x28, 0x09, 0x12, 0x0c,
-    0x0a, 0x04, 0x6e, 0x61, 0x6d, 0x65, 0x18, 0x03, 0x20, 0x02, 0x28, 0x09, 0x22, 0x1a, 0x0a, 0x0a,
-    0x41, 0x63, 0x63, 0x6f, 0x75, 0x6e, 0x74, 0x47, 0x65, 0x74, 0x12, 0x0c, 0x0a, 0x04, 0x6e, 0x61,
-    0x6d, 0x65, 0x18, 0x01, 0x20, 0x02, 0x28, 0x09, 0x22, 0x41, 0x0a, 0x07, 0x53, 0x65, 0x73, 0x73,
-    0x69, 0x6f, 0x6e, 0x12, 0x0a, 0x0a, 0x02, 0x69, 0x64, 0x18, 0x01, 0x20, 0x02, 0x28, 0x04, 0x12,
-    0x0d, 0x0a, 0x05, 0x65, 0x6d, 0x61, 0x69, 0x6c, 0x18, 0x02, 0x20, 0x02, 0x28, 0x09, 0x12, 0x0c,
-    0x0a, 0x04, 0x6e, 0x61, 0x6d, 0x65, 0x18, 0x03, 0x20, 0x02, 0x28, 0x09, 0x12, 0x0d, 0x0a, 0x05,
-    0x74, 0x6f, 0x6b, 0x65, 0x6e, 0x18, 0x04, 0x20, 0x02, 0x28, 0x09, 0x22, 0x2f, 0x0a, 0x0c, 0x53,
-    0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x54, 0x6f, 0x6b, 0x65, 0x6e, 0x12, 0x0d, 0x0a, 0x05, 0x74,
-    0x6f, 0x6b, 0x65, 0x6e, 0x18, 0x01, 0x20, 0x02, 0x28, 0x09, 0x12, 0x10, 0x0a, 0x08, 0x6f, 0x77,
-    0x6e, 0x65, 0x72, 0x5f, 0x69, 0x64, 0x18, 0x02, 0x20, 0x02, 0x28, 0x04, 0x22, 0x7b, 0x0a, 0x0d,
-    0x53, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x43, 0x72, 0x65, 0x61, 0x74, 0x65, 0x12, 0x0d, 0x0a,
-    0x05, 0x74, 0x6f, 0x6b, 0x65, 0x6e, 0x18, 0x01, 0x20, 0x02, 0x28, 0x09, 0x12, 0x11, 0x0a, 0x09,
-    0x65, 0x78, 0x74, 0x65, 0x72, 0x6e, 0x5f, 0x69, 0x64, 0x18, 0x02, 0x20, 0x02, 0x28, 0x04, 0x12,
-    0x0d, 0x0a, 0x05, 0x65, 0x6d, 0x61, 0x69, 0x6c, 0x18, 0x03, 0x20, 0x02, 0x28, 0x09, 0x12, 0x0c,
-    0x0a, 0x04, 0x6e, 0x61, 0x6d, 0x65, 0x18, 0x04, 0x20, 0x02, 0x28, 0x09, 0x12, 0x2b, 0x0a, 0x08,
-    0x70, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x18, 0x05, 0x20, 0x02, 0x28, 0x0e, 0x32, 0x19,
-    0x2e, 0x73, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x73, 0x72, 0x76, 0x2e, 0x4f, 0x41, 0x75, 0x74,
-    0x68, 0x50, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x22, 0x1b, 0x0a, 0x0a, 0x53, 0x65, 0x73,
+    0x73, 0x73, 0x69, 0x6f, 0x6e, 0x73, 0x72, 0x76, 0x22, 0xcb, 0x01, 0x0a, 0x07, 0x41, 0x63, 0x63,
+    0x6f, 0x75, 0x6e, 0x74, 0x12, 0x0a, 0x0a, 0x02, 0x69, 0x64, 0x18, 0x01, 0x20, 0x02, 0x28, 0x04,
+    0x12, 0x0d, 0x0a, 0x05, 0x65, 0x6d, 0x61, 0x69, 0x6c, 0x18, 0x02, 0x20, 0x01, 0x28, 0x09, 0x12,
+    0x0c, 0x0a, 0x04, 0x6e, 0x61, 0x6d, 0x65, 0x18, 0x03, 0x20, 0x02, 0x28, 0x09, 0x12, 0x11, 0x0a,
+    0x09, 0x65, 0x78, 0x74, 0x65, 0x72, 0x6e, 0x5f, 0x69, 0x64, 0x18, 0x04, 0x20, 0x01, 0x28, 0x04,
+    0x12, 0x12, 0x0a, 0x0a, 0x61, 0x76, 0x61, 0x74, 0x61, 0x72, 0x5f, 0x75, 0x72, 0x6c, 0x18, 0x05,
+    0x20, 0x01, 0x28, 0x09, 0x12, 0x14, 0x0a, 0x0c, 0x64, 0x69, 0x73, 0x70, 0x6c, 0x61, 0x79, 0x5f,
+    0x6e, 0x61, 0x6d, 0x65, 0x18, 0x06, 0x20, 0x01, 0x28, 0x09, 0x12, 0x16, 0x0a, 0x0e, 0x65, 0x6d,
+    0x61, 0x69, 0x6c, 0x5f, 0x76, 0x65, 0x72, 0x69, 0x66, 0x69, 0x65, 0x64, 0x18, 0x07, 0x20, 0x01,
+    0x28, 0x08, 0x12, 0x2b, 0x0a, 0x08, 0x70, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x18, 0x08,
+    0x20, 0x01, 0x28, 0x0e, 0x32, 0x19, 0x2e, 0x73, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x73, 0x72,
+    0x76, 0x2e, 0x4f, 0x41, 0x75, 0x74, 0x68, 0x50, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x12,
+    0x15, 0x0a, 0x0d, 0x70, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x5f, 0x68, 0x6f, 0x73, 0x74,
+    0x18, 0x09, 0x20, 0x01, 0x28, 0x09, 0x22, 0x1a, 0x0a, 0x0a, 0x41, 0x63, 0x63, 0x6f, 0x75, 0x6e,
+    0x74, 0x47, 0x65, 0x74, 0x12, 0x0c, 0x0a, 0x04, 0x6e, 0x61, 0x6d, 0x65, 0x18, 0x01, 0x20, 0x02,
+    0x28, 0x09, 0x22, 0xcb, 0x01, 0x0a, 0x07, 0x53, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x12, 0x0a,
+    0x0a, 0x02, 0x69, 0x64, 0x18, 0x01, 0x20, 0x02, 0x28, 0x04, 0x12, 0x0d, 0x0a, 0x05, 0x65, 0x6d,
+    0x61, 0x69, 0x6c, 0x18, 0x02, 0x20, 0x01, 0x28, 0x09, 0x12, 0x0c, 0x0a, 0x04, 0x6e, 0x61, 0x6d,
+    0x65, 0x18, 0x03, 0x20, 0x02, 0x28, 0x09, 0x12, 0x0d, 0x0a, 0x05, 0x74, 0x6f, 0x6b, 0x65, 0x6e,
+    0x18, 0x04, 0x20, 0x02, 0x28, 0x09, 0x12, 0x0d, 0x0a, 0x05, 0x66, 0x6c, 0x61, 0x67, 0x73, 0x18,
+    0x05, 0x20, 0x01, 0x28, 0x0d, 0x12, 0x12, 0x0a, 0x0a, 0x61, 0x76, 0x61, 0x74, 0x61, 0x72, 0x5f,
+    0x75, 0x72, 0x6c, 0x18, 0x06, 0x20, 0x01, 0x28, 0x09, 0x12, 0x14, 0x0a, 0x0c, 0x64, 0x69, 0x73,
+    0x70, 0x6c, 0x61, 0x79, 0x5f, 0x6e, 0x61, 0x6d, 0x65, 0x18, 0x07, 0x20, 0x01, 0x28, 0x09, 0x12,
+    0x11, 0x0a, 0x09, 0x63, 0x6c, 0x69, 0x65, 0x6e, 0x74, 0x5f, 0x69, 0x64, 0x18, 0x08, 0x20, 0x01,
+    0x28, 0x09, 0x12, 0x0e, 0x0a, 0x06, 0x73, 0x63, 0x6f, 0x70, 0x65, 0x73, 0x18, 0x09, 0x20, 0x03,
+    0x28, 0x09, 0x12, 0x11, 0x0a, 0x09, 0x69, 0x73, 0x73, 0x75, 0x65, 0x64, 0x5f, 0x61, 0x74, 0x18,
+    0x0a, 0x20, 0x01, 0x28, 0x04, 0x12, 0x19, 0x0a, 0x11, 0x6c, 0x61, 0x73, 0x74, 0x5f, 0x76, 0x61,
+    0x6c, 0x69, 0x64, 0x61, 0x74, 0x65, 0x64, 0x5f, 0x61, 0x74, 0x18, 0x0b, 0x20, 0x01, 0x28, 0x04,
+    0x22, 0x8f, 0x01, 0x0a, 0x0c, 0x53, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x54, 0x6f, 0x6b, 0x65,
+    0x6e, 0x12, 0x0d, 0x0a, 0x05, 0x74, 0x6f, 0x6b, 0x65, 0x6e, 0x18, 0x01, 0x20, 0x02, 0x28, 0x09,
+    0x12, 0x10, 0x0a, 0x08, 0x6f, 0x77, 0x6e, 0x65, 0x72, 0x5f, 0x69, 0x64, 0x18, 0x02, 0x20, 0x02,
+    0x28, 0x04, 0x12, 0x0d, 0x0a, 0x05, 0x66, 0x6c, 0x61, 0x67, 0x73, 0x18, 0x03, 0x20, 0x01, 0x28,
+    0x0d, 0x12, 0x11, 0x0a, 0x09, 0x63, 0x6c, 0x69, 0x65, 0x6e, 0x74, 0x5f, 0x69, 0x64, 0x18, 0x04,
+    0x20, 0x01, 0x28, 0x09, 0x12, 0x0e, 0x0a, 0x06, 0x73, 0x63, 0x6f, 0x70, 0x65, 0x73, 0x18, 0x05,
+    0x20, 0x03, 0x28, 0x09, 0x12, 0x11, 0x0a, 0x09, 0x69, 0x73, 0x73, 0x75, 0x65, 0x64, 0x5f, 0x61,
+    0x74, 0x18, 0x06, 0x20, 0x01, 0x28, 0x04, 0x12, 0x19, 0x0a, 0x11, 0x6c, 0x61, 0x73, 0x74, 0x5f,
+    0x76, 0x61, 0x6c, 0x69, 0x64, 0x61, 0x74, 0x65, 0x64, 0x5f, 0x61, 0x74, 0x18, 0x07, 0x20, 0x01,
+    0x28, 0x04, 0x22, 0xb4, 0x02, 0x0a, 0x0d, 0x53, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x43, 0x72,
+    0x65, 0x61, 0x74, 0x65, 0x12, 0x0d, 0x0a, 0x05, 0x74, 0x6f, 0x6b, 0x65, 0x6e, 0x18, 0x01, 0x20,
+    0x02, 0x28, 0x09, 0x12, 0x11, 0x0a, 0x09, 0x65, 0x78, 0x74, 0x65, 0x72, 0x6e, 0x5f, 0x69, 0x64,
+    0x18, 0x02, 0x20, 0x02, 0x28, 0x04, 0x12, 0x0d, 0x0a, 0x05, 0x65, 0x6d, 0x61, 0x69, 0x6c, 0x18,
+    0x03, 0x20, 0x01, 0x28, 0x09, 0x12, 0x0c, 0x0a, 0x04, 0x6e, 0x61, 0x6d, 0x65, 0x18, 0x04, 0x20,
+    0x02, 0x28, 0x09, 0x12, 0x2b, 0x0a, 0x08, 0x70, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x18,
+    0x05, 0x20, 0x02, 0x28, 0x0e, 0x32, 0x19, 0x2e, 0x73, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x73,
+    0x72, 0x76, 0x2e, 0x4f, 0x41, 0x75, 0x74, 0x68, 0x50, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72,
+    0x12, 0x0d, 0x0a, 0x05, 0x66, 0x6c, 0x61, 0x67, 0x73, 0x18, 0x06, 0x20, 0x01, 0x28, 0x0d, 0x12,
+    0x12, 0x0a, 0x0a, 0x61, 0x76, 0x61, 0x74, 0x61, 0x72, 0x5f, 0x75, 0x72, 0x6c, 0x18, 0x07, 0x20,
+    0x01, 0x28, 0x09, 0x12, 0x14, 0x0a, 0x0c, 0x64, 0x69, 0x73, 0x70, 0x6c, 0x61, 0x79, 0x5f, 0x6e,
+    0x61, 0x6d, 0x65, 0x18, 0x08, 0x20, 0x01, 0x28, 0x09, 0x12, 0x11, 0x0a, 0x09, 0x63, 0x6c, 0x69,
+    0x65, 0x6e, 0x74, 0x5f, 0x69, 0x64, 0x18, 0x09, 0x20, 0x01, 0x28, 0x09, 0x12, 0x0e, 0x0a, 0x06,
+    0x73, 0x63, 0x6f, 0x70, 0x65, 0x73, 0x18, 0x0a, 0x20, 0x03, 0x28, 0x09, 0x12, 0x11, 0x0a, 0x09,
+    0x69, 0x73, 0x73, 0x75, 0x65, 0x64, 0x5f, 0x61, 0x74, 0x18, 0x0b, 0x20, 0x01, 0x28, 0x04, 0x12,
+    0x19, 0x0a, 0x11, 0x6c, 0x61, 0x73, 0x74, 0x5f, 0x76, 0x61, 0x6c, 0x69, 0x64, 0x61, 0x74, 0x65,
+    0x64, 0x5f, 0x61, 0x74, 0x18, 0x0c, 0x20, 0x01, 0x28, 0x04, 0x12, 0x16, 0x0a, 0x0e, 0x65, 0x6d,
+    0x61, 0x69, 0x6c, 0x5f, 0x76, 0x65, 0x72, 0x69, 0x66, 0x69, 0x65, 0x64, 0x18, 0x0d, 0x20, 0x01,
+    0x28, 0x08, 0x12, 0x15, 0x0a, 0x0d, 0x70, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x5f, 0x68,
+    0x6f, 0x73, 0x74, 0x18, 0x0e, 0x20, 0x01, 0x28, 0x09, 0x22, 0x2f, 0x0a, 0x0a, 0x53, 0x65, 0x73,
     0x73, 0x69, 0x6f, 0x6e, 0x47, 0x65, 0x74, 0x12, 0x0d, 0x0a, 0x05, 0x74, 0x6f, 0x6b, 0x65, 0x6e,
-    0x18, 0x01, 0x20, 0x02, 0x28, 0x09, 0x2a, 0x1b, 0x0a, 0x0d, 0x4f, 0x41, 0x75, 0x74, 0x68, 0x50,
-    0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x12, 0x0a, 0x0a, 0x06, 0x47, 0x69, 0x74, 0x48, 0x75,
-    0x62, 0x10, 0x00, 0x4a, 0xd4, 0x0a, 0x0a, 0x06, 0x12, 0x04, 0x00, 0x00, 0x27, 0x01, 0x0a, 0x08,
-    0x0a, 0x01, 0x02, 0x12, 0x03, 0x00, 0x08, 0x12, 0x0a, 0x0a, 0x0a, 0x02, 0x05, 0x00, 0x12, 0x04,
-    0x02, 0x00, 0x04, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x05, 0x00, 0x01, 0x12, 0x03, 0x02, 0x05, 0x12,
-    0x0a, 0x0b, 0x0a, 0x04, 0x05, 0x00, 0x02, 0x00, 0x12, 0x03, 0x03, 0x02, 0x0d, 0x0a, 0x0c, 0x0a,
-    0x05, 0x05, 0x00, 0x02, 0x00, 0x01, 0x12, 0x03, 0x03, 0x02, 0x08, 0x0a, 0x0c, 0x0a, 0x05, 0x05,
-    0x00, 0x02, 0x00, 0x02, 0x12, 0x03, 0x03, 0x0b, 0x0c, 0x0a, 0x0a, 0x0a, 0x02, 0x04, 0x00, 0x12,
-    0x04, 0x06, 0x00, 0x0a, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x00, 0x01, 0x12, 0x03, 0x06, 0x08,
-    0x0f, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x00, 0x12, 0x03, 0x07, 0x02, 0x19, 0x0a, 0x0c,
-    0x0a, 0x05, 0x04, 0x00, 0x02, 0x00, 0x04, 0x12, 0x03, 0x07, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05,
-    0x04, 0x00, 0x02, 0x00, 0x05, 0x12, 0x03, 0x07, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00,
-    0x02, 0x00, 0x01, 0x12, 0x03, 0x07, 0x12, 0x14, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x00,
-    0x03, 0x12, 0x03, 0x07, 0x17, 0x18, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x01, 0x12, 0x03,
-    0x08, 0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x01, 0x04, 0x12, 0x03, 0x08, 0x02,
-    0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x01, 0x05, 0x12, 0x03, 0x08, 0x0b, 0x11, 0x0a,
-    0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x01, 0x01, 0x12, 0x03, 0x08, 0x12, 0x17, 0x0a, 0x0c, 0x0a,
-    0x05, 0x04, 0x00, 0x02, 0x01, 0x03, 0x12, 0x03, 0x08, 0x1a, 0x1b, 0x0a, 0x0b, 0x0a, 0x04, 0x04,
-    0x00, 0x02, 0x02, 0x12, 0x03, 0x09, 0x02, 0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02,
-    0x04, 0x12, 0x03, 0x09, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02, 0x05, 0x12,
-    0x03, 0x09, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02, 0x01, 0x12, 0x03, 0x09,
-    0x12, 0x16, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02, 0x03, 0x12, 0x03, 0x09, 0x19, 0x1a,
-    0x0a, 0x2b, 0x0a, 0x02, 0x04, 0x01, 0x12, 0x04, 0x0d, 0x00, 0x0f, 0x01, 0x1a, 0x1f, 0x20, 0x67,
-    0x65, 0x74, 0x20, 0x61, 0x6e, 0x20, 0x61, 0x63, 0x63, 0x6f, 0x75, 0x6e, 0x74, 0x20, 0x62, 0x79,
-    0x20, 0x47, 0x48, 0x20, 0x75, 0x73, 0x65, 0x72, 0x6e, 0x61, 0x6d, 0x65, 0x0a, 0x0a, 0x0a, 0x0a,
-    0x03, 0x04, 0x01, 0x01, 0x12, 0x03, 0x0d, 0x08, 0x12, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x01, 0x02,
-    0x00, 0x12, 0x03, 0x0e, 0x02, 0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x01, 0x02, 0x00, 0x04, 0x12,
-    0x03, 0x0e, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x01, 0x02, 0x00, 0x05, 0x12, 0x03, 0x0e,
-    0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x01, 0x02, 0x00, 0x01, 0x12, 0x03, 0x0e, 0x12, 0x16,
-    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x01, 0x02, 0x00, 0x03, 0x12, 0x03, 0x0e, 0x19, 0x1a, 0x0a, 0x0a,
-    0x0a, 0x02, 0x04, 0x02, 0x12, 0x04, 0x11, 0x00, 0x16, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x02,
-    0x01, 0x12, 0x03, 0x11, 0x08, 0x0f, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x02, 0x02, 0x00, 0x12, 0x03,
-    0x12, 0x02, 0x19, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x00, 0x04, 0x12, 0x03, 0x12, 0x02,
-    0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x00, 0x05, 0x12, 0x03, 0x12, 0x0b, 0x11, 0x0a,
-    0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x00, 0x01, 0x12, 0x03, 0x12, 0x12, 0x14, 0x0a, 0x0c, 0x0a,
-    0x05, 0x04, 0x02, 0x02, 0x00, 0x03, 0x12, 0x03, 0x12, 0x17, 0x18, 0x0a, 0x0b, 0x0a, 0x04, 0x04,
-    0x02, 0x02, 0x01, 0x12, 0x03, 0x13, 0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x01,
-    0x04, 0x12, 0x03, 0x13, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x01, 0x05, 0x12,
-    0x03, 0x13, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x01, 0x01, 0x12, 0x03, 0x13,
-    0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x01, 0x03, 0x12, 0x03, 0x13, 0x1a, 0x1b,
-    0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x02, 0x02, 0x02, 0x12, 0x03, 0x14, 0x02, 0x1b, 0x0a, 0x0c, 0x0a,
-    0x05, 0x04, 0x02, 0x02, 0x02, 0x04, 0x12, 0x03, 0x14, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
-    0x02, 0x02, 0x02, 0x05, 0x12, 0x03, 0x14, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02,
-    0x02, 0x01, 0x12, 0x03, 0x14, 0x12, 0x16, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x02, 0x03,
-    0x12, 0x03, 0x14, 0x19, 0x1a, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x02, 0x02, 0x03, 0x12, 0x03, 0x15,
-    0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x03, 0x04, 0x12, 0x03, 0x15, 0x02, 0x0a,
-    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x03, 0x05, 0x12, 0x03, 0x15, 0x0b, 0x11, 0x0a, 0x0c,
-    0x0a, 0x05, 0x04, 0x02, 0x02, 0x03, 0x01, 0x12, 0x03, 0x15, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05,
-    0x04, 0x02, 0x02, 0x03, 0x03, 0x12, 0x03, 0x15, 0x1a, 0x1b, 0x0a, 0x0a, 0x0a, 0x02, 0x04, 0x03,
-    0x12, 0x04, 0x18, 0x00, 0x1b, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x03, 0x01, 0x12, 0x03, 0x18,
-    0x08, 0x14, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x03, 0x02, 0x00, 0x12, 0x03, 0x19, 0x02, 0x1c, 0x0a,
-    0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x00, 0x04, 0x12, 0x03, 0x19, 0x02, 0x0a, 0x0a, 0x0c, 0x0a,
-    0x05, 0x04, 0x03, 0x02, 0x00, 0x05, 0x12, 0x03, 0x19, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
-    0x03, 0x02, 0x00, 0x01, 0x12, 0x03, 0x19, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02,
-    0x00, 0x03, 0x12, 0x03, 0x19, 0x1a, 0x1b, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x03, 0x02, 0x01, 0x12,
-    0x03, 0x1a, 0x02, 0x1f, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x01, 0x04, 0x12, 0x03, 0x1a,
-    0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x01, 0x05, 0x12, 0x03, 0x1a, 0x0b, 0x11,
-    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x01, 0x01, 0x12, 0x03, 0x1a, 0x12, 0x1a, 0x0a, 0x0c,
-    0x0a, 0x05, 0x04, 0x03, 0x02, 0x01, 0x03, 0x12, 0x03, 0x1a, 0x1d, 0x1e, 0x0a, 0x0a, 0x0a, 0x02,
-    0x04, 0x04, 0x12, 0x04, 0x1d, 0x00, 0x23, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x04, 0x01, 0x12,
-    0x03, 0x1d, 0x08, 0x15, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x00, 0x12, 0x03, 0x1e, 0x02,
-    0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x00, 0x04, 0x12, 0x03, 0x1e, 0x02, 0x0a, 0x0a,
-    0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x00, 0x05, 0x12, 0x03, 0x1e, 0x0b, 0x11, 0x0a, 0x0c, 0x0a,
-    0x05, 0x04, 0x04, 0x02, 0x00, 0x01, 0x12, 0x03, 0x1e, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
-    0x04, 0x02, 0x00, 0x03, 0x12, 0x03, 0x1e, 0x1a, 0x1b, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02,
-    0x01, 0x12, 0x03, 0x1f, 0x02, 0x20, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x01, 0x04, 0x12,
-    0x03, 0x1f, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x01, 0x05, 0x12, 0x03, 0x1f,
-    0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x01, 0x01, 0x12, 0x03, 0x1f, 0x12, 0x1b,
-    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x01, 0x03, 0x12, 0x03, 0x1f, 0x1e, 0x1f, 0x0a, 0x0b,
-    0x0a, 0x04, 0x04, 0x04, 0x02, 0x02, 0x12, 0x03, 0x20, 0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
-    0x04, 0x02, 0x02, 0x04, 0x12, 0x03, 0x20, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02,
-    0x02, 0x05, 0x12, 0x03, 0x20, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x02, 0x01,
-    0x12, 0x03, 0x20, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x02, 0x03, 0x12, 0x03,
-    0x20, 0x1a, 0x1b, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x03, 0x12, 0x03, 0x21, 0x02, 0x1b,
-    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x03, 0x04, 0x12, 0x03, 0x21, 0x02, 0x0a, 0x0a, 0x0c,
-    0x0a, 0x05, 0x04, 0x04, 0x02, 0x03, 0x05, 0x12, 0x03, 0x21, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05,
-    0x04, 0x04, 0x02, 0x03, 0x01, 0x12, 0x03, 0x21, 0x12, 0x16, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04,
-    0x02, 0x03, 0x03, 0x12, 0x03, 0x21, 0x19, 0x1a, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x04,
-    0x12, 0x03, 0x22, 0x02, 0x26, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x04, 0x04, 0x12, 0x03,
-    0x22, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x04, 0x06, 0x12, 0x03, 0x22, 0x0b,
-    0x18, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x04, 0x01, 0x12, 0x03, 0x22, 0x19, 0x21, 0x0a,
-    0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x04, 0x03, 0x12, 0x03, 0x22, 0x24, 0x25, 0x0a, 0x0a, 0x0a,
-    0x02, 0x04, 0x05, 0x12, 0x04, 0x25, 0x00, 0x27, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x05, 0x01,
-    0x12, 0x03, 0x25, 0x08, 0x12, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x05, 0x02, 0x00, 0x12, 0x03, 0x26,
-    0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x05, 0x02, 0x00, 0x04, 0x12, 0x03, 0x26, 0x02, 0x0a,
-    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x05, 0x02, 0x00, 0x05, 0x12, 0x03, 0x26, 0x0b, 0x11, 0x0a, 0x0c,
-    0x0a, 0x05, 0x04, 0x05, 0x02, 0x00, 0x01, 0x12, 0x03, 0x26, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05,
-    0x04, 0x05, 0x02, 0x00, 0x03, 0x12, 0x03, 0x26, 0x1a, 0x1b,
+    0x18, 0x01, 0x20, 0x02, 0x28, 0x09, 0x12, 0x12, 0x0a, 0x0a, 0x63, 0x6c, 0x69, 0x65, 0x6e, 0x74,
+    0x5f, 0x69, 0x64, 0x73, 0x18, 0x02, 0x20, 0x03, 0x28, 0x09, 0x22, 0x1e, 0x0a, 0x0d, 0x53, 0x65,
+    0x73, 0x73, 0x69, 0x6f, 0x6e, 0x44, 0x65, 0x6c, 0x65, 0x74, 0x65, 0x12, 0x0d, 0x0a, 0x05, 0x74,
+    0x6f, 0x6b, 0x65, 0x6e, 0x18, 0x01, 0x20, 0x02, 0x28, 0x09, 0x22, 0x72, 0x0a, 0x10, 0x53, 0x65,
+    0x73, 0x73, 0x69, 0x6f, 0x6e, 0x56, 0x61, 0x6c, 0x69, 0x64, 0x61, 0x74, 0x65, 0x64, 0x12, 0x0d,
+    0x0a, 0x05, 0x74, 0x6f, 0x6b, 0x65, 0x6e, 0x18, 0x01, 0x20, 0x02, 0x28, 0x09, 0x12, 0x11, 0x0a,
+    0x09, 0x63, 0x6c, 0x69, 0x65, 0x6e, 0x74, 0x5f, 0x69, 0x64, 0x18, 0x02, 0x20, 0x01, 0x28, 0x09,
+    0x12, 0x0e, 0x0a, 0x06, 0x73, 0x63, 0x6f, 0x70, 0x65, 0x73, 0x18, 0x03, 0x20, 0x03, 0x28, 0x09,
+    0x12, 0x11, 0x0a, 0x09, 0x69, 0x73, 0x73, 0x75, 0x65, 0x64, 0x5f, 0x61, 0x74, 0x18, 0x04, 0x20,
+    0x01, 0x28, 0x04, 0x12, 0x19, 0x0a, 0x11, 0x6c, 0x61, 0x73, 0x74, 0x5f, 0x76, 0x61, 0x6c, 0x69,
+    0x64, 0x61, 0x74, 0x65, 0x64, 0x5f, 0x61, 0x74, 0x18, 0x05, 0x20, 0x01, 0x28, 0x04, 0x2a, 0x31,
+    0x0a, 0x0d, 0x4f, 0x41, 0x75, 0x74, 0x68, 0x50, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x12,
+    0x0a, 0x0a, 0x06, 0x47, 0x69, 0x74, 0x48, 0x75, 0x62, 0x10, 0x00, 0x12, 0x0a, 0x0a, 0x06, 0x47,
+    0x69, 0x74, 0x4c, 0x61, 0x62, 0x10, 0x01, 0x12, 0x08, 0x0a, 0x04, 0x53, 0x74, 0x75, 0x62, 0x10,
+    0x02, 0x4a, 0xd1, 0x24, 0x0a, 0x06, 0x12, 0x04, 0x00, 0x00, 0x5d, 0x01, 0x0a, 0x08, 0x0a, 0x01,
+    0x02, 0x12, 0x03, 0x00, 0x08, 0x12, 0x0a, 0x0a, 0x0a, 0x02, 0x05, 0x00, 0x12, 0x04, 0x02, 0x00,
+    0x06, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x05, 0x00, 0x01, 0x12, 0x03, 0x02, 0x05, 0x12, 0x0a, 0x0b,
+    0x0a, 0x04, 0x05, 0x00, 0x02, 0x00, 0x12, 0x03, 0x03, 0x02, 0x0d, 0x0a, 0x0c, 0x0a, 0x05, 0x05,
+    0x00, 0x02, 0x00, 0x01, 0x12, 0x03, 0x03, 0x02, 0x08, 0x0a, 0x0c, 0x0a, 0x05, 0x05, 0x00, 0x02,
+    0x00, 0x02, 0x12, 0x03, 0x03, 0x0b, 0x0c, 0x0a, 0x0b, 0x0a, 0x04, 0x05, 0x00, 0x02, 0x01, 0x12,
+    0x03, 0x04, 0x02, 0x0d, 0x0a, 0x0c, 0x0a, 0x05, 0x05, 0x00, 0x02, 0x01, 0x01, 0x12, 0x03, 0x04,
+    0x02, 0x08, 0x0a, 0x0c, 0x0a, 0x05, 0x05, 0x00, 0x02, 0x01, 0x02, 0x12, 0x03, 0x04, 0x0b, 0x0c,
+    0x0a, 0x0b, 0x0a, 0x04, 0x05, 0x00, 0x02, 0x02, 0x12, 0x03, 0x05, 0x02, 0x0b, 0x0a, 0x0c, 0x0a,
+    0x05, 0x05, 0x00, 0x02, 0x02, 0x01, 0x12, 0x03, 0x05, 0x02, 0x06, 0x0a, 0x0c, 0x0a, 0x05, 0x05,
+    0x00, 0x02, 0x02, 0x02, 0x12, 0x03, 0x05, 0x09, 0x0a, 0x0a, 0x0a, 0x0a, 0x02, 0x04, 0x00, 0x12,
+    0x04, 0x08, 0x00, 0x16, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x00, 0x01, 0x12, 0x03, 0x08, 0x08,
+    0x0f, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x00, 0x12, 0x03, 0x09, 0x02, 0x19, 0x0a, 0x0c,
+    0x0a, 0x05, 0x04, 0x00, 0x02, 0x00, 0x04, 0x12, 0x03, 0x09, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x00, 0x02, 0x00, 0x05, 0x12, 0x03, 0x09, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00,
+    0x02, 0x00, 0x01, 0x12, 0x03, 0x09, 0x12, 0x14, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x00,
+    0x03, 0x12, 0x03, 0x09, 0x17, 0x18, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x01, 0x12, 0x03,
+    0x0a, 0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x01, 0x04, 0x12, 0x03, 0x0a, 0x02,
+    0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x01, 0x05, 0x12, 0x03, 0x0a, 0x0b, 0x11, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x01, 0x01, 0x12, 0x03, 0x0a, 0x12, 0x17, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x00, 0x02, 0x01, 0x03, 0x12, 0x03, 0x0a, 0x1a, 0x1b, 0x0a, 0x0b, 0x0a, 0x04, 0x04,
+    0x00, 0x02, 0x02, 0x12, 0x03, 0x0b, 0x02, 0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02,
+    0x04, 0x12, 0x03, 0x0b, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02, 0x05, 0x12,
+    0x03, 0x0b, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02, 0x01, 0x12, 0x03, 0x0b,
+    0x12, 0x16, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x02, 0x03, 0x12, 0x03, 0x0b, 0x19, 0x1a,
+    0x0a, 0x55, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x03, 0x12, 0x03, 0x0d, 0x02, 0x20, 0x1a, 0x48, 0x20,
+    0x69, 0x64, 0x65, 0x6e, 0x74, 0x69, 0x66, 0x69, 0x65, 0x72, 0x20, 0x6f, 0x66, 0x20, 0x74, 0x68,
+    0x65, 0x20, 0x61, 0x63, 0x63, 0x6f, 0x75, 0x6e, 0x74, 0x20, 0x61, 0x74, 0x20, 0x69, 0x74, 0x73,
+    0x20, 0x4f, 0x41, 0x75, 0x74, 0x68, 0x20, 0x70, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x2c,
+    0x20, 0x73, 0x74, 0x61, 0x62, 0x6c, 0x65, 0x20, 0x61, 0x63, 0x72, 0x6f, 0x73, 0x73, 0x20, 0x72,
+    0x65, 0x6e, 0x61, 0x6d, 0x65, 0x73, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x03, 0x04,
+    0x12, 0x03, 0x0d, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x03, 0x05, 0x12, 0x03,
+    0x0d, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x03, 0x01, 0x12, 0x03, 0x0d, 0x12,
+    0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x03, 0x03, 0x12, 0x03, 0x0d, 0x1e, 0x1f, 0x0a,
+    0x0b, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x04, 0x12, 0x03, 0x0e, 0x02, 0x21, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x00, 0x02, 0x04, 0x04, 0x12, 0x03, 0x0e, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00,
+    0x02, 0x04, 0x05, 0x12, 0x03, 0x0e, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x04,
+    0x01, 0x12, 0x03, 0x0e, 0x12, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x04, 0x03, 0x12,
+    0x03, 0x0e, 0x1f, 0x20, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x05, 0x12, 0x03, 0x0f, 0x02,
+    0x23, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x05, 0x04, 0x12, 0x03, 0x0f, 0x02, 0x0a, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x05, 0x05, 0x12, 0x03, 0x0f, 0x0b, 0x11, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x00, 0x02, 0x05, 0x01, 0x12, 0x03, 0x0f, 0x12, 0x1e, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x00, 0x02, 0x05, 0x03, 0x12, 0x03, 0x0f, 0x21, 0x22, 0x0a, 0x52, 0x0a, 0x04, 0x04, 0x00, 0x02,
+    0x06, 0x12, 0x03, 0x11, 0x02, 0x23, 0x1a, 0x45, 0x20, 0x74, 0x72, 0x75, 0x65, 0x20, 0x69, 0x66,
+    0x20, 0x74, 0x68, 0x65, 0x20, 0x4f, 0x41, 0x75, 0x74, 0x68, 0x20, 0x70, 0x72, 0x6f, 0x76, 0x69,
+    0x64, 0x65, 0x72, 0x20, 0x68, 0x61, 0x73, 0x20, 0x76, 0x65, 0x72, 0x69, 0x66, 0x69, 0x65, 0x64,
+    0x20, 0x74, 0x68, 0x65, 0x20, 0x61, 0x63, 0x63, 0x6f, 0x75, 0x6e, 0x74, 0x27, 0x73, 0x20, 0x65,
+    0x6d, 0x61, 0x69, 0x6c, 0x20, 0x61, 0x64, 0x64, 0x72, 0x65, 0x73, 0x73, 0x0a, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x00, 0x02, 0x06, 0x04, 0x12, 0x03, 0x11, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x00, 0x02, 0x06, 0x05, 0x12, 0x03, 0x11, 0x0b, 0x0f, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02,
+    0x06, 0x01, 0x12, 0x03, 0x11, 0x10, 0x1e, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x06, 0x03,
+    0x12, 0x03, 0x11, 0x21, 0x22, 0x0a, 0xa2, 0x01, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x07, 0x12, 0x03,
+    0x14, 0x02, 0x26, 0x1a, 0x94, 0x01, 0x20, 0x69, 0x64, 0x65, 0x6e, 0x74, 0x69, 0x74, 0x79, 0x20,
+    0x70, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x20, 0x61, 0x6e, 0x64, 0x20, 0x68, 0x6f, 0x73,
+    0x74, 0x2c, 0x20, 0x73, 0x75, 0x63, 0x68, 0x20, 0x61, 0x73, 0x20, 0x61, 0x20, 0x47, 0x69, 0x74,
+    0x48, 0x75, 0x62, 0x20, 0x45, 0x6e, 0x74, 0x65, 0x72, 0x70, 0x72, 0x69, 0x73, 0x65, 0x20, 0x61,
+    0x70, 0x70, 0x6c, 0x69, 0x61, 0x6e, 0x63, 0x65, 0x2c, 0x20, 0x74, 0x68, 0x65, 0x20, 0x61, 0x63,
+    0x63, 0x6f, 0x75, 0x6e, 0x74, 0x20, 0x63, 0x61, 0x6d, 0x65, 0x20, 0x66, 0x72, 0x6f, 0x6d, 0x2e,
+    0x0a, 0x20, 0x65, 0x78, 0x74, 0x65, 0x72, 0x6e, 0x5f, 0x69, 0x64, 0x20, 0x69, 0x73, 0x20, 0x6f,
+    0x6e, 0x6c, 0x79, 0x20, 0x75, 0x6e, 0x69, 0x71, 0x75, 0x65, 0x20, 0x66, 0x6f, 0x72, 0x20, 0x61,
+    0x20, 0x67, 0x69, 0x76, 0x65, 0x6e, 0x20, 0x70, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x20,
+    0x61, 0x6e, 0x64, 0x20, 0x68, 0x6f, 0x73, 0x74, 0x2e, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00,
+    0x02, 0x07, 0x04, 0x12, 0x03, 0x14, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x07,
+    0x06, 0x12, 0x03, 0x14, 0x0b, 0x18, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x07, 0x01, 0x12,
+    0x03, 0x14, 0x19, 0x21, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x07, 0x03, 0x12, 0x03, 0x14,
+    0x24, 0x25, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x00, 0x02, 0x08, 0x12, 0x03, 0x15, 0x02, 0x24, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02, 0x08, 0x04, 0x12, 0x03, 0x15, 0x02, 0x0a, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x00, 0x02, 0x08, 0x05, 0x12, 0x03, 0x15, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x00, 0x02, 0x08, 0x01, 0x12, 0x03, 0x15, 0x12, 0x1f, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x00, 0x02,
+    0x08, 0x03, 0x12, 0x03, 0x15, 0x22, 0x23, 0x0a, 0x2b, 0x0a, 0x02, 0x04, 0x01, 0x12, 0x04, 0x19,
+    0x00, 0x1b, 0x01, 0x1a, 0x1f, 0x20, 0x67, 0x65, 0x74, 0x20, 0x61, 0x6e, 0x20, 0x61, 0x63, 0x63,
+    0x6f, 0x75, 0x6e, 0x74, 0x20, 0x62, 0x79, 0x20, 0x47, 0x48, 0x20, 0x75, 0x73, 0x65, 0x72, 0x6e,
+    0x61, 0x6d, 0x65, 0x0a, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x01, 0x01, 0x12, 0x03, 0x19, 0x08, 0x12,
+    0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x01, 0x02, 0x00, 0x12, 0x03, 0x1a, 0x02, 0x1b, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x01, 0x02, 0x00, 0x04, 0x12, 0x03, 0x1a, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x01, 0x02, 0x00, 0x05, 0x12, 0x03, 0x1a, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x01, 0x02,
+    0x00, 0x01, 0x12, 0x03, 0x1a, 0x12, 0x16, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x01, 0x02, 0x00, 0x03,
+    0x12, 0x03, 0x1a, 0x19, 0x1a, 0x0a, 0x0a, 0x0a, 0x02, 0x04, 0x02, 0x12, 0x04, 0x1d, 0x00, 0x2a,
+    0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x02, 0x01, 0x12, 0x03, 0x1d, 0x08, 0x0f, 0x0a, 0x0b, 0x0a,
+    0x04, 0x04, 0x02, 0x02, 0x00, 0x12, 0x03, 0x1e, 0x02, 0x19, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02,
+    0x02, 0x00, 0x04, 0x12, 0x03, 0x1e, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x00,
+    0x05, 0x12, 0x03, 0x1e, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x00, 0x01, 0x12,
+    0x03, 0x1e, 0x12, 0x14, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x00, 0x03, 0x12, 0x03, 0x1e,
+    0x17, 0x18, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x02, 0x02, 0x01, 0x12, 0x03, 0x1f, 0x02, 0x1c, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x01, 0x04, 0x12, 0x03, 0x1f, 0x02, 0x0a, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x02, 0x02, 0x01, 0x05, 0x12, 0x03, 0x1f, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x02, 0x02, 0x01, 0x01, 0x12, 0x03, 0x1f, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02,
+    0x01, 0x03, 0x12, 0x03, 0x1f, 0x1a, 0x1b, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x02, 0x02, 0x02, 0x12,
+    0x03, 0x20, 0x02, 0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x02, 0x04, 0x12, 0x03, 0x20,
+    0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x02, 0x05, 0x12, 0x03, 0x20, 0x0b, 0x11,
+    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x02, 0x01, 0x12, 0x03, 0x20, 0x12, 0x16, 0x0a, 0x0c,
+    0x0a, 0x05, 0x04, 0x02, 0x02, 0x02, 0x03, 0x12, 0x03, 0x20, 0x19, 0x1a, 0x0a, 0x0b, 0x0a, 0x04,
+    0x04, 0x02, 0x02, 0x03, 0x12, 0x03, 0x21, 0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02,
+    0x03, 0x04, 0x12, 0x03, 0x21, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x03, 0x05,
+    0x12, 0x03, 0x21, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x03, 0x01, 0x12, 0x03,
+    0x21, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x03, 0x03, 0x12, 0x03, 0x21, 0x1a,
+    0x1b, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x02, 0x02, 0x04, 0x12, 0x03, 0x22, 0x02, 0x1c, 0x0a, 0x0c,
+    0x0a, 0x05, 0x04, 0x02, 0x02, 0x04, 0x04, 0x12, 0x03, 0x22, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x02, 0x02, 0x04, 0x05, 0x12, 0x03, 0x22, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02,
+    0x02, 0x04, 0x01, 0x12, 0x03, 0x22, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x04,
+    0x03, 0x12, 0x03, 0x22, 0x1a, 0x1b, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x02, 0x02, 0x05, 0x12, 0x03,
+    0x23, 0x02, 0x21, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x05, 0x04, 0x12, 0x03, 0x23, 0x02,
+    0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x05, 0x05, 0x12, 0x03, 0x23, 0x0b, 0x11, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x05, 0x01, 0x12, 0x03, 0x23, 0x12, 0x1c, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x02, 0x02, 0x05, 0x03, 0x12, 0x03, 0x23, 0x1f, 0x20, 0x0a, 0x0b, 0x0a, 0x04, 0x04,
+    0x02, 0x02, 0x06, 0x12, 0x03, 0x24, 0x02, 0x23, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x06,
+    0x04, 0x12, 0x03, 0x24, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x06, 0x05, 0x12,
+    0x03, 0x24, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x06, 0x01, 0x12, 0x03, 0x24,
+    0x12, 0x1e, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x06, 0x03, 0x12, 0x03, 0x24, 0x21, 0x22,
+    0x0a, 0x67, 0x0a, 0x04, 0x04, 0x02, 0x02, 0x07, 0x12, 0x03, 0x26, 0x02, 0x20, 0x1a, 0x5a, 0x20,
+    0x6d, 0x65, 0x74, 0x61, 0x64, 0x61, 0x74, 0x61, 0x20, 0x6f, 0x66, 0x20, 0x74, 0x68, 0x65, 0x20,
+    0x73, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x27, 0x73, 0x20, 0x61, 0x63, 0x63, 0x65, 0x73, 0x73,
+    0x20, 0x74, 0x6f, 0x6b, 0x65, 0x6e, 0x2c, 0x20, 0x73, 0x65, 0x65, 0x20, 0x53, 0x65, 0x73, 0x73,
+    0x69, 0x6f, 0x6e, 0x54, 0x6f, 0x6b, 0x65, 0x6e, 0x2c, 0x20, 0x73, 0x6f, 0x20, 0x74, 0x68, 0x65,
+    0x20, 0x67, 0x61, 0x74, 0x65, 0x77, 0x61, 0x79, 0x20, 0x63, 0x61, 0x6e, 0x20, 0x72, 0x65, 0x2d,
+    0x63, 0x68, 0x65, 0x63, 0x6b, 0x20, 0x69, 0x74, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02,
+    0x07, 0x04, 0x12, 0x03, 0x26, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x07, 0x05,
+    0x12, 0x03, 0x26, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x07, 0x01, 0x12, 0x03,
+    0x26, 0x12, 0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x07, 0x03, 0x12, 0x03, 0x26, 0x1e,
+    0x1f, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x02, 0x02, 0x08, 0x12, 0x03, 0x27, 0x02, 0x1d, 0x0a, 0x0c,
+    0x0a, 0x05, 0x04, 0x02, 0x02, 0x08, 0x04, 0x12, 0x03, 0x27, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x02, 0x02, 0x08, 0x05, 0x12, 0x03, 0x27, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02,
+    0x02, 0x08, 0x01, 0x12, 0x03, 0x27, 0x12, 0x18, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x08,
+    0x03, 0x12, 0x03, 0x27, 0x1b, 0x1c, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x02, 0x02, 0x09, 0x12, 0x03,
+    0x28, 0x02, 0x21, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x09, 0x04, 0x12, 0x03, 0x28, 0x02,
+    0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x09, 0x05, 0x12, 0x03, 0x28, 0x0b, 0x11, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x09, 0x01, 0x12, 0x03, 0x28, 0x12, 0x1b, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x02, 0x02, 0x09, 0x03, 0x12, 0x03, 0x28, 0x1e, 0x20, 0x0a, 0x0b, 0x0a, 0x04, 0x04,
+    0x02, 0x02, 0x0a, 0x12, 0x03, 0x29, 0x02, 0x29, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x0a,
+    0x04, 0x12, 0x03, 0x29, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x0a, 0x05, 0x12,
+    0x03, 0x29, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x0a, 0x01, 0x12, 0x03, 0x29,
+    0x12, 0x23, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x02, 0x02, 0x0a, 0x03, 0x12, 0x03, 0x29, 0x26, 0x28,
+    0x0a, 0x0a, 0x0a, 0x02, 0x04, 0x03, 0x12, 0x04, 0x2c, 0x00, 0x36, 0x01, 0x0a, 0x0a, 0x0a, 0x03,
+    0x04, 0x03, 0x01, 0x12, 0x03, 0x2c, 0x08, 0x14, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x03, 0x02, 0x00,
+    0x12, 0x03, 0x2d, 0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x00, 0x04, 0x12, 0x03,
+    0x2d, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x00, 0x05, 0x12, 0x03, 0x2d, 0x0b,
+    0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x00, 0x01, 0x12, 0x03, 0x2d, 0x12, 0x17, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x00, 0x03, 0x12, 0x03, 0x2d, 0x1a, 0x1b, 0x0a, 0x0b, 0x0a,
+    0x04, 0x04, 0x03, 0x02, 0x01, 0x12, 0x03, 0x2e, 0x02, 0x1f, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03,
+    0x02, 0x01, 0x04, 0x12, 0x03, 0x2e, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x01,
+    0x05, 0x12, 0x03, 0x2e, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x01, 0x01, 0x12,
+    0x03, 0x2e, 0x12, 0x1a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x01, 0x03, 0x12, 0x03, 0x2e,
+    0x1d, 0x1e, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x03, 0x02, 0x02, 0x12, 0x03, 0x2f, 0x02, 0x1c, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x02, 0x04, 0x12, 0x03, 0x2f, 0x02, 0x0a, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x03, 0x02, 0x02, 0x05, 0x12, 0x03, 0x2f, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x03, 0x02, 0x02, 0x01, 0x12, 0x03, 0x2f, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02,
+    0x02, 0x03, 0x12, 0x03, 0x2f, 0x1a, 0x1b, 0x0a, 0x49, 0x0a, 0x04, 0x04, 0x03, 0x02, 0x03, 0x12,
+    0x03, 0x31, 0x02, 0x20, 0x1a, 0x3c, 0x20, 0x63, 0x6c, 0x69, 0x65, 0x6e, 0x74, 0x20, 0x69, 0x64,
+    0x20, 0x6f, 0x66, 0x20, 0x74, 0x68, 0x65, 0x20, 0x4f, 0x41, 0x75, 0x74, 0x68, 0x20, 0x61, 0x70,
+    0x70, 0x6c, 0x69, 0x63, 0x61, 0x74, 0x69, 0x6f, 0x6e, 0x20, 0x74, 0x68, 0x65, 0x20, 0x74, 0x6f,
+    0x6b, 0x65, 0x6e, 0x20, 0x77, 0x61, 0x73, 0x20, 0x69, 0x73, 0x73, 0x75, 0x65, 0x64, 0x20, 0x74,
+    0x6f, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x03, 0x04, 0x12, 0x03, 0x31, 0x02, 0x0a,
+    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x03, 0x05, 0x12, 0x03, 0x31, 0x0b, 0x11, 0x0a, 0x0c,
+    0x0a, 0x05, 0x04, 0x03, 0x02, 0x03, 0x01, 0x12, 0x03, 0x31, 0x12, 0x1b, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x03, 0x02, 0x03, 0x03, 0x12, 0x03, 0x31, 0x1e, 0x1f, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x03,
+    0x02, 0x04, 0x12, 0x03, 0x32, 0x02, 0x1d, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x04, 0x04,
+    0x12, 0x03, 0x32, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x04, 0x05, 0x12, 0x03,
+    0x32, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x04, 0x01, 0x12, 0x03, 0x32, 0x12,
+    0x18, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x04, 0x03, 0x12, 0x03, 0x32, 0x1b, 0x1c, 0x0a,
+    0x2b, 0x0a, 0x04, 0x04, 0x03, 0x02, 0x05, 0x12, 0x03, 0x34, 0x02, 0x20, 0x1a, 0x1e, 0x20, 0x73,
+    0x65, 0x63, 0x6f, 0x6e, 0x64, 0x73, 0x20, 0x73, 0x69, 0x6e, 0x63, 0x65, 0x20, 0x74, 0x68, 0x65,
+    0x20, 0x75, 0x6e, 0x69, 0x78, 0x20, 0x65, 0x70, 0x6f, 0x63, 0x68, 0x0a, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x03, 0x02, 0x05, 0x04, 0x12, 0x03, 0x34, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03,
+    0x02, 0x05, 0x05, 0x12, 0x03, 0x34, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x05,
+    0x01, 0x12, 0x03, 0x34, 0x12, 0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x05, 0x03, 0x12,
+    0x03, 0x34, 0x1e, 0x1f, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x03, 0x02, 0x06, 0x12, 0x03, 0x35, 0x02,
+    0x28, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x06, 0x04, 0x12, 0x03, 0x35, 0x02, 0x0a, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x03, 0x02, 0x06, 0x05, 0x12, 0x03, 0x35, 0x0b, 0x11, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x03, 0x02, 0x06, 0x01, 0x12, 0x03, 0x35, 0x12, 0x23, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x03, 0x02, 0x06, 0x03, 0x12, 0x03, 0x35, 0x26, 0x27, 0x0a, 0x0a, 0x0a, 0x02, 0x04, 0x04, 0x12,
+    0x04, 0x38, 0x00, 0x47, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x04, 0x01, 0x12, 0x03, 0x38, 0x08,
+    0x15, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x00, 0x12, 0x03, 0x39, 0x02, 0x1c, 0x0a, 0x0c,
+    0x0a, 0x05, 0x04, 0x04, 0x02, 0x00, 0x04, 0x12, 0x03, 0x39, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x04, 0x02, 0x00, 0x05, 0x12, 0x03, 0x39, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04,
+    0x02, 0x00, 0x01, 0x12, 0x03, 0x39, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x00,
+    0x03, 0x12, 0x03, 0x39, 0x1a, 0x1b, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x01, 0x12, 0x03,
+    0x3a, 0x02, 0x20, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x01, 0x04, 0x12, 0x03, 0x3a, 0x02,
+    0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x01, 0x05, 0x12, 0x03, 0x3a, 0x0b, 0x11, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x01, 0x01, 0x12, 0x03, 0x3a, 0x12, 0x1b, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x04, 0x02, 0x01, 0x03, 0x12, 0x03, 0x3a, 0x1e, 0x1f, 0x0a, 0x0b, 0x0a, 0x04, 0x04,
+    0x04, 0x02, 0x02, 0x12, 0x03, 0x3b, 0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x02,
+    0x04, 0x12, 0x03, 0x3b, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x02, 0x05, 0x12,
+    0x03, 0x3b, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x02, 0x01, 0x12, 0x03, 0x3b,
+    0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x02, 0x03, 0x12, 0x03, 0x3b, 0x1a, 0x1b,
+    0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x03, 0x12, 0x03, 0x3c, 0x02, 0x1b, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x04, 0x02, 0x03, 0x04, 0x12, 0x03, 0x3c, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x04, 0x02, 0x03, 0x05, 0x12, 0x03, 0x3c, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02,
+    0x03, 0x01, 0x12, 0x03, 0x3c, 0x12, 0x16, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x03, 0x03,
+    0x12, 0x03, 0x3c, 0x19, 0x1a, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x04, 0x12, 0x03, 0x3d,
+    0x02, 0x26, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x04, 0x04, 0x12, 0x03, 0x3d, 0x02, 0x0a,
+    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x04, 0x06, 0x12, 0x03, 0x3d, 0x0b, 0x18, 0x0a, 0x0c,
+    0x0a, 0x05, 0x04, 0x04, 0x02, 0x04, 0x01, 0x12, 0x03, 0x3d, 0x19, 0x21, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x04, 0x02, 0x04, 0x03, 0x12, 0x03, 0x3d, 0x24, 0x25, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04,
+    0x02, 0x05, 0x12, 0x03, 0x3e, 0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x05, 0x04,
+    0x12, 0x03, 0x3e, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x05, 0x05, 0x12, 0x03,
+    0x3e, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x05, 0x01, 0x12, 0x03, 0x3e, 0x12,
+    0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x05, 0x03, 0x12, 0x03, 0x3e, 0x1a, 0x1b, 0x0a,
+    0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x06, 0x12, 0x03, 0x3f, 0x02, 0x21, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x04, 0x02, 0x06, 0x04, 0x12, 0x03, 0x3f, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04,
+    0x02, 0x06, 0x05, 0x12, 0x03, 0x3f, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x06,
+    0x01, 0x12, 0x03, 0x3f, 0x12, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x06, 0x03, 0x12,
+    0x03, 0x3f, 0x1f, 0x20, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x07, 0x12, 0x03, 0x40, 0x02,
+    0x23, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x07, 0x04, 0x12, 0x03, 0x40, 0x02, 0x0a, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x07, 0x05, 0x12, 0x03, 0x40, 0x0b, 0x11, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x04, 0x02, 0x07, 0x01, 0x12, 0x03, 0x40, 0x12, 0x1e, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x04, 0x02, 0x07, 0x03, 0x12, 0x03, 0x40, 0x21, 0x22, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02,
+    0x08, 0x12, 0x03, 0x41, 0x02, 0x20, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x08, 0x04, 0x12,
+    0x03, 0x41, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x08, 0x05, 0x12, 0x03, 0x41,
+    0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x08, 0x01, 0x12, 0x03, 0x41, 0x12, 0x1b,
+    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x08, 0x03, 0x12, 0x03, 0x41, 0x1e, 0x1f, 0x0a, 0x0b,
+    0x0a, 0x04, 0x04, 0x04, 0x02, 0x09, 0x12, 0x03, 0x42, 0x02, 0x1e, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x04, 0x02, 0x09, 0x04, 0x12, 0x03, 0x42, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02,
+    0x09, 0x05, 0x12, 0x03, 0x42, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x09, 0x01,
+    0x12, 0x03, 0x42, 0x12, 0x18, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x09, 0x03, 0x12, 0x03,
+    0x42, 0x1b, 0x1d, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x0a, 0x12, 0x03, 0x43, 0x02, 0x21,
+    0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x0a, 0x04, 0x12, 0x03, 0x43, 0x02, 0x0a, 0x0a, 0x0c,
+    0x0a, 0x05, 0x04, 0x04, 0x02, 0x0a, 0x05, 0x12, 0x03, 0x43, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x04, 0x02, 0x0a, 0x01, 0x12, 0x03, 0x43, 0x12, 0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04,
+    0x02, 0x0a, 0x03, 0x12, 0x03, 0x43, 0x1e, 0x20, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x0b,
+    0x12, 0x03, 0x44, 0x02, 0x29, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x0b, 0x04, 0x12, 0x03,
+    0x44, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x0b, 0x05, 0x12, 0x03, 0x44, 0x0b,
+    0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x0b, 0x01, 0x12, 0x03, 0x44, 0x12, 0x23, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x0b, 0x03, 0x12, 0x03, 0x44, 0x26, 0x28, 0x0a, 0x0b, 0x0a,
+    0x04, 0x04, 0x04, 0x02, 0x0c, 0x12, 0x03, 0x45, 0x02, 0x24, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04,
+    0x02, 0x0c, 0x04, 0x12, 0x03, 0x45, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x0c,
+    0x05, 0x12, 0x03, 0x45, 0x0b, 0x0f, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x0c, 0x01, 0x12,
+    0x03, 0x45, 0x10, 0x1e, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x0c, 0x03, 0x12, 0x03, 0x45,
+    0x21, 0x23, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x04, 0x02, 0x0d, 0x12, 0x03, 0x46, 0x02, 0x25, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02, 0x0d, 0x04, 0x12, 0x03, 0x46, 0x02, 0x0a, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x04, 0x02, 0x0d, 0x05, 0x12, 0x03, 0x46, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x04, 0x02, 0x0d, 0x01, 0x12, 0x03, 0x46, 0x12, 0x1f, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x04, 0x02,
+    0x0d, 0x03, 0x12, 0x03, 0x46, 0x22, 0x24, 0x0a, 0x0a, 0x0a, 0x02, 0x04, 0x05, 0x12, 0x04, 0x49,
+    0x00, 0x4e, 0x01, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x05, 0x01, 0x12, 0x03, 0x49, 0x08, 0x12, 0x0a,
+    0x0b, 0x0a, 0x04, 0x04, 0x05, 0x02, 0x00, 0x12, 0x03, 0x4a, 0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x05, 0x02, 0x00, 0x04, 0x12, 0x03, 0x4a, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x05,
+    0x02, 0x00, 0x05, 0x12, 0x03, 0x4a, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x05, 0x02, 0x00,
+    0x01, 0x12, 0x03, 0x4a, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x05, 0x02, 0x00, 0x03, 0x12,
+    0x03, 0x4a, 0x1a, 0x1b, 0x0a, 0xc1, 0x01, 0x0a, 0x04, 0x04, 0x05, 0x02, 0x01, 0x12, 0x03, 0x4d,
+    0x02, 0x21, 0x1a, 0xb3, 0x01, 0x20, 0x63, 0x6c, 0x69, 0x65, 0x6e, 0x74, 0x20, 0x69, 0x64, 0x73,
+    0x20, 0x6f, 0x66, 0x20, 0x74, 0x68, 0x65, 0x20, 0x4f, 0x41, 0x75, 0x74, 0x68, 0x20, 0x61, 0x70,
+    0x70, 0x6c, 0x69, 0x63, 0x61, 0x74, 0x69, 0x6f, 0x6e, 0x73, 0x20, 0x74, 0x68, 0x65, 0x20, 0x63,
+    0x61, 0x6c, 0x6c, 0x65, 0x72, 0x20, 0x61, 0x63, 0x63, 0x65, 0x70, 0x74, 0x73, 0x20, 0x74, 0x6f,
+    0x6b, 0x65, 0x6e, 0x73, 0x20, 0x66, 0x72, 0x6f, 0x6d, 0x2e, 0x20, 0x41, 0x20, 0x73, 0x65, 0x73,
+    0x73, 0x69, 0x6f, 0x6e, 0x20, 0x77, 0x68, 0x6f, 0x73, 0x65, 0x20, 0x74, 0x6f, 0x6b, 0x65, 0x6e,
+    0x0a, 0x20, 0x77, 0x61, 0x73, 0x20, 0x69, 0x73, 0x73, 0x75, 0x65, 0x64, 0x20, 0x74, 0x6f, 0x20,
+    0x61, 0x6e, 0x79, 0x20, 0x6f, 0x74, 0x68, 0x65, 0x72, 0x20, 0x61, 0x70, 0x70, 0x6c, 0x69, 0x63,
+    0x61, 0x74, 0x69, 0x6f, 0x6e, 0x20, 0x69, 0x73, 0x20, 0x69, 0x6e, 0x76, 0x61, 0x6c, 0x69, 0x64,
+    0x61, 0x74, 0x65, 0x64, 0x2e, 0x20, 0x45, 0x76, 0x65, 0x72, 0x79, 0x20, 0x74, 0x6f, 0x6b, 0x65,
+    0x6e, 0x20, 0x69, 0x73, 0x20, 0x61, 0x63, 0x63, 0x65, 0x70, 0x74, 0x65, 0x64, 0x20, 0x69, 0x66,
+    0x20, 0x65, 0x6d, 0x70, 0x74, 0x79, 0x2e, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x05, 0x02, 0x01,
+    0x04, 0x12, 0x03, 0x4d, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x05, 0x02, 0x01, 0x05, 0x12,
+    0x03, 0x4d, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x05, 0x02, 0x01, 0x01, 0x12, 0x03, 0x4d,
+    0x12, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x05, 0x02, 0x01, 0x03, 0x12, 0x03, 0x4d, 0x1f, 0x20,
+    0x0a, 0x43, 0x0a, 0x02, 0x04, 0x06, 0x12, 0x04, 0x51, 0x00, 0x53, 0x01, 0x1a, 0x37, 0x20, 0x65,
+    0x78, 0x70, 0x69, 0x72, 0x65, 0x20, 0x61, 0x20, 0x73, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x2c,
+    0x20, 0x72, 0x65, 0x70, 0x6c, 0x69, 0x65, 0x64, 0x20, 0x74, 0x6f, 0x20, 0x77, 0x69, 0x74, 0x68,
+    0x20, 0x74, 0x68, 0x65, 0x20, 0x65, 0x78, 0x70, 0x69, 0x72, 0x65, 0x64, 0x20, 0x53, 0x65, 0x73,
+    0x73, 0x69, 0x6f, 0x6e, 0x0a, 0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x06, 0x01, 0x12, 0x03, 0x51, 0x08,
+    0x15, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x06, 0x02, 0x00, 0x12, 0x03, 0x52, 0x02, 0x1c, 0x0a, 0x0c,
+    0x0a, 0x05, 0x04, 0x06, 0x02, 0x00, 0x04, 0x12, 0x03, 0x52, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x06, 0x02, 0x00, 0x05, 0x12, 0x03, 0x52, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x06,
+    0x02, 0x00, 0x01, 0x12, 0x03, 0x52, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x06, 0x02, 0x00,
+    0x03, 0x12, 0x03, 0x52, 0x1a, 0x1b, 0x0a, 0x87, 0x01, 0x0a, 0x02, 0x04, 0x07, 0x12, 0x04, 0x57,
+    0x00, 0x5d, 0x01, 0x1a, 0x7b, 0x20, 0x72, 0x65, 0x63, 0x6f, 0x72, 0x64, 0x20, 0x74, 0x68, 0x61,
+    0x74, 0x20, 0x74, 0x68, 0x65, 0x20, 0x61, 0x63, 0x63, 0x65, 0x73, 0x73, 0x20, 0x74, 0x6f, 0x6b,
+    0x65, 0x6e, 0x20, 0x6f, 0x66, 0x20, 0x61, 0x20, 0x73, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x20,
+    0x77, 0x61, 0x73, 0x20, 0x63, 0x68, 0x65, 0x63, 0x6b, 0x65, 0x64, 0x20, 0x61, 0x67, 0x61, 0x69,
+    0x6e, 0x20, 0x77, 0x69, 0x74, 0x68, 0x20, 0x69, 0x74, 0x73, 0x20, 0x4f, 0x41, 0x75, 0x74, 0x68,
+    0x20, 0x70, 0x72, 0x6f, 0x76, 0x69, 0x64, 0x65, 0x72, 0x2c, 0x20, 0x72, 0x65, 0x70, 0x6c, 0x69,
+    0x65, 0x64, 0x0a, 0x20, 0x74, 0x6f, 0x20, 0x77, 0x69, 0x74, 0x68, 0x20, 0x74, 0x68, 0x65, 0x20,
+    0x75, 0x70, 0x64, 0x61, 0x74, 0x65, 0x64, 0x20, 0x53, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x0a,
+    0x0a, 0x0a, 0x0a, 0x03, 0x04, 0x07, 0x01, 0x12, 0x03, 0x57, 0x08, 0x18, 0x0a, 0x0b, 0x0a, 0x04,
+    0x04, 0x07, 0x02, 0x00, 0x12, 0x03, 0x58, 0x02, 0x1c, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02,
+    0x00, 0x04, 0x12, 0x03, 0x58, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x00, 0x05,
+    0x12, 0x03, 0x58, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x00, 0x01, 0x12, 0x03,
+    0x58, 0x12, 0x17, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x00, 0x03, 0x12, 0x03, 0x58, 0x1a,
+    0x1b, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x07, 0x02, 0x01, 0x12, 0x03, 0x59, 0x02, 0x20, 0x0a, 0x0c,
+    0x0a, 0x05, 0x04, 0x07, 0x02, 0x01, 0x04, 0x12, 0x03, 0x59, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05,
+    0x04, 0x07, 0x02, 0x01, 0x05, 0x12, 0x03, 0x59, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07,
+    0x02, 0x01, 0x01, 0x12, 0x03, 0x59, 0x12, 0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x01,
+    0x03, 0x12, 0x03, 0x59, 0x1e, 0x1f, 0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x07, 0x02, 0x02, 0x12, 0x03,
+    0x5a, 0x02, 0x1d, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x02, 0x04, 0x12, 0x03, 0x5a, 0x02,
+    0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x02, 0x05, 0x12, 0x03, 0x5a, 0x0b, 0x11, 0x0a,
+    0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x02, 0x01, 0x12, 0x03, 0x5a, 0x12, 0x18, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x07, 0x02, 0x02, 0x03, 0x12, 0x03, 0x5a, 0x1b, 0x1c, 0x0a, 0x0b, 0x0a, 0x04, 0x04,
+    0x07, 0x02, 0x03, 0x12, 0x03, 0x5b, 0x02, 0x20, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x03,
+    0x04, 0x12, 0x03, 0x5b, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x03, 0x05, 0x12,
+    0x03, 0x5b, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x03, 0x01, 0x12, 0x03, 0x5b,
+    0x12, 0x1b, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x03, 0x03, 0x12, 0x03, 0x5b, 0x1e, 0x1f,
+    0x0a, 0x0b, 0x0a, 0x04, 0x04, 0x07, 0x02, 0x04, 0x12, 0x03, 0x5c, 0x02, 0x28, 0x0a, 0x0c, 0x0a,
+    0x05, 0x04, 0x07, 0x02, 0x04, 0x04, 0x12, 0x03, 0x5c, 0x02, 0x0a, 0x0a, 0x0c, 0x0a, 0x05, 0x04,
+    0x07, 0x02, 0x04, 0x05, 0x12, 0x03, 0x5c, 0x0b, 0x11, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02,
+    0x04, 0x01, 0x12, 0x03, 0x5c, 0x12, 0x23, 0x0a, 0x0c, 0x0a, 0x05, 0x04, 0x07, 0x02, 0x04, 0x03,
+    0x12, 0x03, 0x5c, 0x26, 0x27,
 ];
 
 static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {
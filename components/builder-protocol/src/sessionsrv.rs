@@ -20,6 +20,44 @@ use message::{Persistable, Routable};
 
 pub use message::sessionsrv::*;
 
+bitflags! {
+    /// Privileges of a session's owner, carried in the `flags` field of `Session`, `SessionToken`,
+    /// and `SessionCreate`.
+    pub flags FeatureFlags: u32 {
+        const ADMIN           = 0b00000001,
+        const MEMBER          = 0b00000010,
+        const BUILDER         = 0b00000100,
+        const ORIGIN_CREATOR  = 0b00001000,
+        const OPERATOR        = 0b00010000,
+        /// Some of the owner's profile couldn't be fetched when the session was created, so the
+        /// other flags may grant less than the owner is entitled to. A session carrying this
+        /// flag should have its privileges evaluated again once the identity provider recovers.
+        const PARTIAL_PROFILE = 0b00100000,
+    }
+}
+
+impl Session {
+    /// Privileges of the session's owner.
+    pub fn get_feature_flags(&self) -> FeatureFlags {
+        FeatureFlags::from_bits_truncate(self.get_flags())
+    }
+
+    pub fn set_feature_flags(&mut self, flags: FeatureFlags) {
+        self.set_flags(flags.bits())
+    }
+}
+
+impl SessionCreate {
+    /// Privileges to grant the owner of the created session.
+    pub fn get_feature_flags(&self) -> FeatureFlags {
+        FeatureFlags::from_bits_truncate(self.get_flags())
+    }
+
+    pub fn set_feature_flags(&mut self, flags: FeatureFlags) {
+        self.set_flags(flags.bits())
+    }
+}
+
 impl Routable for SessionCreate {
     type H = u64;
 
@@ -38,6 +76,29 @@ impl Routable for SessionGet {
     }
 }
 
+impl Account {
+    /// True if the account has an email address and its OAuth provider verified it.
+    pub fn has_verified_email(&self) -> bool {
+        self.has_email() && self.get_email_verified()
+    }
+}
+
+impl Routable for SessionDelete {
+    type H = String;
+
+    fn route_key(&self) -> Option<Self::H> {
+        None
+    }
+}
+
+impl Routable for SessionValidated {
+    type H = String;
+
+    fn route_key(&self) -> Option<Self::H> {
+        None
+    }
+}
+
 impl Persistable for Account {
     type Key = u64;
 
@@ -54,8 +115,12 @@ impl Into<Session> for Account {
     fn into(self) -> Session {
         let mut session = Session::new();
         session.set_id(self.get_id());
-        session.set_email(self.get_email().to_owned());
+        if self.has_email() {
+            session.set_email(self.get_email().to_owned());
+        }
         session.set_name(self.get_name().to_owned());
+        session.set_avatar_url(self.get_avatar_url().to_owned());
+        session.set_display_name(self.get_display_name().to_owned());
         session
     }
 }
@@ -66,6 +131,8 @@ impl ToJson for Account {
         m.insert("id".to_string(), self.get_id().to_string().to_json());
         m.insert("name".to_string(), self.get_name().to_json());
         m.insert("email".to_string(), self.get_email().to_json());
+        m.insert("avatar_url".to_string(), self.get_avatar_url().to_json());
+        m.insert("display_name".to_string(), self.get_display_name().to_json());
         Json::Object(m)
     }
 }
@@ -97,6 +164,24 @@ impl ToJson for Session {
         m.insert("email".to_string(), self.get_email().to_json());
         m.insert("name".to_string(), self.get_name().to_json());
         m.insert("id".to_string(), self.get_id().to_json());
+        m.insert("flags".to_string(), self.get_flags().to_json());
+        m.insert("avatar_url".to_string(), self.get_avatar_url().to_json());
+        m.insert("display_name".to_string(), self.get_display_name().to_json());
         Json::Object(m)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_verified_email() {
+        let mut account = Account::new();
+        assert!(!account.has_verified_email());
+        account.set_email("octocat@example.com".to_string());
+        assert!(!account.has_verified_email());
+        account.set_email_verified(true);
+        assert!(account.has_verified_email());
+    }
+}
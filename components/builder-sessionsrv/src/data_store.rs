@@ -12,10 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::ops::Deref;
 use std::sync::Arc;
 
 use dbcache::{self, data_store, Bucket, ConnectionPool, ExpiringSet, IndexSet, InstaSet};
+use hab_net::config::GITHUB_HOST;
+use protobuf::Message;
 use protocol::sessionsrv;
+use redis::{self, Commands, PipelineCommands};
 
 use config::Config;
 use error::Result;
@@ -45,6 +49,7 @@ impl data_store::Pool for DataStore {
 pub struct AccountTable {
     pool: Arc<ConnectionPool>,
     github: GitHub2AccountIdx,
+    externals: Extern2AccountIdx,
     user_to_account: GitHubUser2AccountIdx,
 }
 
@@ -52,40 +57,117 @@ impl AccountTable {
     pub fn new(pool: Arc<ConnectionPool>) -> Self {
         let pool1 = pool.clone();
         let pool2 = pool.clone();
+        let pool3 = pool.clone();
         let directory = GitHub2AccountIdx::new(pool1);
         let user_to_account = GitHubUser2AccountIdx::new(pool2);
+        let externals = Extern2AccountIdx::new(pool3);
         AccountTable {
             pool: pool,
             github: directory,
+            externals: externals,
             user_to_account: user_to_account,
         }
     }
 
     pub fn find_or_create(&self, req: &sessionsrv::SessionCreate) -> Result<sessionsrv::Account> {
-        let id = match req.get_provider() {
-            sessionsrv::OAuthProvider::GitHub => self.github.find(&req.get_extern_id()).ok(),
+        let key = extern_key(req);
+        let id = match self.externals.find(&key).ok() {
+            Some(id) => Some(id),
+            None if is_legacy_github(req) => {
+                // Accounts created before provenance was recorded are only indexed by their
+                // GitHub id.
+                let id = self.github.find(&req.get_extern_id()).ok();
+                if let Some(id) = id {
+                    try!(self.externals.write(&key, id));
+                }
+                id
+            }
+            None => None,
         };
         if let Some(ref id) = id {
-            let account = try!(self.find(id));
+            // Accounts are matched by the provider's identifier so a user who renamed their
+            // account at the provider keeps the same account here.
+            let mut account = try!(self.find(id));
+            let previous_name = account.get_name().to_string();
+            if !refresh_account(&mut account, req) {
+                return Ok(account);
+            }
+            if previous_name != account.get_name() {
+                info!("account renamed, id={}, from={}, to={}",
+                      account.get_id(),
+                      previous_name,
+                      account.get_name());
+                try!(self.rename(&account, &previous_name));
+            } else {
+                try!(self.update(&account));
+            }
             Ok(account)
         } else {
             let mut account = sessionsrv::Account::new();
-            account.set_email(req.get_email().to_string());
-            account.set_name(req.get_name().to_string());
+            refresh_account(&mut account, req);
             // JW TODO: make these two database calls transactional
             try!(self.write(&mut account));
-            try!(self.github.write(&req.get_extern_id(), account.get_id()));
+            try!(self.externals.write(&key, account.get_id()));
             // TODO: route a message to the appropriate sessionsrv, and
             // that sessionsrv will write to the db
-            try!(self.user_to_account.write(&req.get_name().to_string(), account.get_id()));
+            if !try!(self.claim_name(&account)) {
+                warn!("username taken by another account, id={}, name={}",
+                      account.get_id(),
+                      account.get_name());
+            }
             Ok(account)
         }
     }
 
+    /// Maps the account's name to it unless the name already maps to another account, which
+    /// keeps a user of another provider with the same login from taking over the mapping. Returns
+    /// false if the name is taken.
+    fn claim_name(&self, account: &sessionsrv::Account) -> Result<bool> {
+        let conn = try!(self.pool.get().map_err(dbcache::Error::from));
+        let names = GitHubUser2AccountIdx::prefix();
+        let claimed: bool = try!(conn.hset_nx(names, account.get_name(), account.get_id())
+            .map_err(dbcache::Error::from));
+        if claimed {
+            return Ok(true);
+        }
+        let owner: u64 = try!(conn.hget(names, account.get_name()).map_err(dbcache::Error::from));
+        Ok(owner == account.get_id())
+    }
+
     pub fn find_by_username(&self, username: &str) -> dbcache::Result<sessionsrv::Account> {
         let account_id = try!(self.user_to_account.find(&username.to_string()));
         self.find(&account_id)
     }
+
+    /// Stores an account which was renamed at its provider, and moves its username mapping from
+    /// the previous name to the new one in the same transaction, so the previous name can be
+    /// taken by another user. Names which already map to another account are left alone, so the
+    /// account loses its mapping if the new name is taken.
+    fn rename(&self, account: &sessionsrv::Account, previous_name: &str) -> Result<()> {
+        let conn = try!(self.pool.get().map_err(dbcache::Error::from));
+        let names = GitHubUser2AccountIdx::prefix();
+        let taken = try!(redis::transaction(conn.deref(), &[names], |txn| {
+            let previous_owner: Option<u64> = try!(conn.hget(names, previous_name));
+            let owner: Option<u64> = try!(conn.hget(names, account.get_name()));
+            let taken = owner.map_or(false, |owner| owner != account.get_id());
+            txn.set(Self::key(&account.get_id()),
+                     account.write_to_bytes().unwrap())
+                .ignore();
+            if !taken {
+                txn.hset(names, account.get_name(), account.get_id()).ignore();
+            }
+            if previous_owner == Some(account.get_id()) {
+                txn.hdel(names, previous_name).ignore();
+            }
+            txn.query(conn.deref()).map(|result: Option<()>| result.map(|_| taken))
+        }).map_err(dbcache::Error::from));
+        if taken {
+            warn!("username taken by another account, id={}, name={}",
+                  account.get_id(),
+                  account.get_name());
+        }
+        Ok(())
+    }
 }
 
 impl Bucket for AccountTable {
@@ -159,6 +241,31 @@ impl IndexSet for GitHub2AccountIdx {
     type Value = u64;
 }
 
+/// maps provider:host:extern_id -> Account.id's
+struct Extern2AccountIdx {
+    pool: Arc<ConnectionPool>,
+}
+
+impl Extern2AccountIdx {
+    pub fn new(pool: Arc<ConnectionPool>) -> Self {
+        Extern2AccountIdx { pool: pool }
+    }
+}
+
+impl Bucket for Extern2AccountIdx {
+    fn prefix() -> &'static str {
+        "extern2account"
+    }
+
+    fn pool(&self) -> &ConnectionPool {
+        &self.pool
+    }
+}
+
+impl IndexSet for Extern2AccountIdx {
+    type Key = String;
+    type Value = u64;
+}
 
 /// maps github usernames -> Account.id's
 struct GitHubUser2AccountIdx {
@@ -185,3 +292,250 @@ impl IndexSet for GitHubUser2AccountIdx {
     type Key = String;
     type Value = u64;
 }
+
+/// Returns the key identifying the requesting user across every provider and host. External ids
+/// are only unique within one provider instance, so they never match an account from another.
+fn extern_key(req: &sessionsrv::SessionCreate) -> String {
+    let provider = match req.get_provider() {
+        sessionsrv::OAuthProvider::GitHub => "github",
+        sessionsrv::OAuthProvider::GitLab => "gitlab",
+        sessionsrv::OAuthProvider::Stub => "stub",
+    };
+    format!("{}:{}:{}",
+            provider,
+            provider_host(req),
+            req.get_extern_id())
+}
+
+/// Returns the normalized provider host of a request. Requests which don't name a host come from
+/// public GitHub.
+fn provider_host(req: &sessionsrv::SessionCreate) -> String {
+    if req.has_provider_host() && !req.get_provider_host().is_empty() {
+        req.get_provider_host().to_lowercase()
+    } else {
+        GITHUB_HOST.to_string()
+    }
+}
+
+/// True if the request comes from public GitHub, the only provider accounts were created for
+/// before provenance was recorded.
+fn is_legacy_github(req: &sessionsrv::SessionCreate) -> bool {
+    req.get_provider() == sessionsrv::OAuthProvider::GitHub && provider_host(req) == GITHUB_HOST
+}
+
+/// Copy the details the OAuth provider reported in a session request onto an account. An email
+/// is only replaced when the request carries one, but its verified flag always follows the
+/// request so an address which is no longer verified stops counting as verified. Returns true if
+/// the account changed.
+fn refresh_account(account: &mut sessionsrv::Account, req: &sessionsrv::SessionCreate) -> bool {
+    let mut changed = false;
+    if account.get_name() != req.get_name() {
+        account.set_name(req.get_name().to_string());
+        changed = true;
+    }
+    if req.has_email() && (!account.has_email() || account.get_email() != req.get_email()) {
+        account.set_email(req.get_email().to_string());
+        changed = true;
+    }
+    let verified = req.has_email() && req.get_email_verified();
+    if account.get_email_verified() != verified {
+        account.set_email_verified(verified);
+        changed = true;
+    }
+    if !account.has_extern_id() || account.get_extern_id() != req.get_extern_id() {
+        account.set_extern_id(req.get_extern_id());
+        changed = true;
+    }
+    if account.get_avatar_url() != req.get_avatar_url() {
+        account.set_avatar_url(req.get_avatar_url().to_string());
+        changed = true;
+    }
+    if !account.has_provider() || account.get_provider() != req.get_provider() {
+        account.set_provider(req.get_provider());
+        changed = true;
+    }
+    let host = provider_host(req);
+    if account.get_provider_host() != host {
+        account.set_provider_host(host);
+        changed = true;
+    }
+    if account.get_display_name() != req.get_display_name() {
+        account.set_display_name(req.get_display_name().to_string());
+        changed = true;
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::sessionsrv;
+
+    use super::{extern_key, is_legacy_github, refresh_account};
+    #[cfg(feature = "functional")]
+    use super::DataStore;
+    #[cfg(feature = "functional")]
+    use config::Config;
+    #[cfg(feature = "functional")]
+    use dbcache::data_store::Pool;
+
+    fn verified(name: &str, email: &str, verified: bool) -> sessionsrv::SessionCreate {
+        let mut req = request(name, Some(email));
+        req.set_email_verified(verified);
+        req
+    }
+
+    fn request(name: &str, email: Option<&str>) -> sessionsrv::SessionCreate {
+        let mut req = sessionsrv::SessionCreate::new();
+        req.set_token("token".to_string());
+        req.set_extern_id(583231);
+        req.set_name(name.to_string());
+        if let Some(email) = email {
+            req.set_email(email.to_string());
+        }
+        req.set_avatar_url("https://avatars.example.com/583231".to_string());
+        req.set_provider(sessionsrv::OAuthProvider::GitHub);
+        req
+    }
+
+    fn existing() -> sessionsrv::Account {
+        let mut account = sessionsrv::Account::new();
+        account.set_id(42);
+        refresh_account(&mut account, &request("octocat", Some("octocat@example.com")));
+        account
+    }
+
+    #[test]
+    fn new_user() {
+        let mut account = sessionsrv::Account::new();
+        assert!(refresh_account(&mut account, &request("octocat", None)));
+        assert_eq!(account.get_name(), "octocat");
+        assert_eq!(account.get_extern_id(), 583231);
+        assert_eq!(account.get_avatar_url(), "https://avatars.example.com/583231");
+        assert!(!account.has_email());
+    }
+
+    #[test]
+    fn renamed_user_keeps_account() {
+        let mut account = existing();
+        assert!(refresh_account(&mut account, &request("hubot", Some("octocat@example.com"))));
+        assert_eq!(account.get_id(), 42);
+        assert_eq!(account.get_name(), "hubot");
+        assert_eq!(account.get_email(), "octocat@example.com");
+    }
+
+    #[test]
+    fn changed_email() {
+        let mut account = existing();
+        assert!(refresh_account(&mut account, &request("octocat", Some("new@example.com"))));
+        assert_eq!(account.get_id(), 42);
+        assert_eq!(account.get_email(), "new@example.com");
+    }
+
+    #[test]
+    fn hidden_email_keeps_previous() {
+        let mut account = existing();
+        assert!(!refresh_account(&mut account, &request("octocat", None)));
+        assert_eq!(account.get_email(), "octocat@example.com");
+    }
+
+    #[test]
+    fn email_loses_verification() {
+        let mut account = existing();
+        assert!(refresh_account(&mut account, &verified("octocat", "octocat@example.com", true)));
+        assert!(account.has_verified_email());
+        assert!(!refresh_account(&mut account, &verified("octocat", "octocat@example.com", true)));
+        assert!(refresh_account(&mut account, &verified("octocat", "octocat@example.com", false)));
+        assert_eq!(account.get_email(), "octocat@example.com");
+        assert!(!account.has_verified_email());
+    }
+
+    #[test]
+    fn colliding_ids_from_different_providers_stay_distinct() {
+        let github = request("octocat", None);
+        let mut gitlab = request("octocat", None);
+        gitlab.set_provider(sessionsrv::OAuthProvider::GitLab);
+        gitlab.set_provider_host("gitlab.com".to_string());
+        let mut enterprise = request("octocat", None);
+        enterprise.set_provider_host("GHE.example.com".to_string());
+        assert_eq!(extern_key(&github), "github:github.com:583231");
+        assert_eq!(extern_key(&gitlab), "gitlab:gitlab.com:583231");
+        assert_eq!(extern_key(&enterprise), "github:ghe.example.com:583231");
+
+        let mut from_github = sessionsrv::Account::new();
+        refresh_account(&mut from_github, &github);
+        let mut from_gitlab = sessionsrv::Account::new();
+        refresh_account(&mut from_gitlab, &gitlab);
+        assert_eq!(from_github.get_provider(), sessionsrv::OAuthProvider::GitHub);
+        assert_eq!(from_gitlab.get_provider(), sessionsrv::OAuthProvider::GitLab);
+        assert_eq!(from_gitlab.get_provider_host(), "gitlab.com");
+        assert!(from_github != from_gitlab);
+    }
+
+    /// Needs a Redis server listening on the default datastore address.
+    #[cfg(feature = "functional")]
+    #[test]
+    #[ignore]
+    fn renamed_users_give_up_their_previous_name() {
+        let datastore = DataStore::start(&Config::default());
+        let extern_id = ::time::precise_time_ns();
+        let (before, after) = (format!("before-{}", extern_id), format!("after-{}", extern_id));
+        let mut req = request(&before, None);
+        req.set_extern_id(extern_id);
+        let created = datastore.accounts.find_or_create(&req).unwrap();
+        assert_eq!(datastore.accounts.find_by_username(&before).unwrap().get_id(),
+                   created.get_id());
+
+        req.set_name(after.clone());
+        let renamed = datastore.accounts.find_or_create(&req).unwrap();
+        assert_eq!(renamed.get_id(), created.get_id());
+        assert_eq!(datastore.accounts.find_by_username(&after).unwrap().get_name(), after);
+        assert!(datastore.accounts.find_by_username(&before).is_err());
+    }
+
+    /// Needs a Redis server listening on the default datastore address.
+    #[cfg(feature = "functional")]
+    #[test]
+    #[ignore]
+    fn colliding_logins_from_different_providers_keep_the_first_mapping() {
+        let datastore = DataStore::start(&Config::default());
+        let extern_id = ::time::precise_time_ns();
+        let (login, other) = (format!("login-{}", extern_id), format!("other-{}", extern_id));
+        let mut github = request(&login, None);
+        github.set_extern_id(extern_id);
+        let mut gitlab = github.clone();
+        gitlab.set_provider(sessionsrv::OAuthProvider::GitLab);
+        gitlab.set_provider_host("gitlab.com".to_string());
+        let from_github = datastore.accounts.find_or_create(&github).unwrap();
+        let from_gitlab = datastore.accounts.find_or_create(&gitlab).unwrap();
+        assert!(from_github.get_id() != from_gitlab.get_id());
+        assert_eq!(datastore.accounts.find_by_username(&login).unwrap().get_id(),
+                   from_github.get_id());
+
+        gitlab.set_name(other.clone());
+        datastore.accounts.find_or_create(&gitlab).unwrap();
+        gitlab.set_name(login.clone());
+        datastore.accounts.find_or_create(&gitlab).unwrap();
+        assert_eq!(datastore.accounts.find_by_username(&login).unwrap().get_id(),
+                   from_github.get_id());
+        assert!(datastore.accounts.find_by_username(&other).is_err());
+    }
+
+    #[test]
+    fn stub_logins_are_not_github_logins() {
+        let github = request("admin", None);
+        let mut stub = request("admin", None);
+        stub.set_provider(sessionsrv::OAuthProvider::Stub);
+        stub.set_provider_host("stub".to_string());
+        assert_eq!(extern_key(&github), "github:github.com:583231");
+        assert_eq!(extern_key(&stub), "stub:stub:583231");
+        assert!(!is_legacy_github(&stub));
+
+        let mut from_github = sessionsrv::Account::new();
+        refresh_account(&mut from_github, &github);
+        let mut from_stub = sessionsrv::Account::new();
+        refresh_account(&mut from_stub, &stub);
+        assert_eq!(from_stub.get_provider(), sessionsrv::OAuthProvider::Stub);
+        assert_eq!(from_stub.get_provider_host(), "stub");
+        assert!(from_github != from_stub);
+    }
+}
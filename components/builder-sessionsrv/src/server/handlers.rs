@@ -57,12 +57,22 @@ pub fn session_create(req: &mut Envelope,
     let mut session_token = proto::SessionToken::new();
     session_token.set_owner_id(account.get_id());
     session_token.set_token(msg.take_token());
+    session_token.set_flags(msg.get_flags());
+    session_token.set_client_id(msg.take_client_id());
+    session_token.set_scopes(msg.take_scopes());
+    session_token.set_issued_at(msg.get_issued_at());
+    session_token.set_last_validated_at(msg.get_last_validated_at());
     try!(state.datastore.sessions.write(&mut session_token));
     let mut session = proto::Session::new();
     session.set_token(session_token.take_token());
     session.set_id(session_token.get_owner_id());
-    session.set_email(account.take_email());
+    if account.has_email() {
+        session.set_email(account.take_email());
+    }
     session.set_name(account.take_name());
+    session.set_avatar_url(account.take_avatar_url());
+    session.set_display_name(account.take_display_name());
+    session.set_flags(session_token.get_flags());
     try!(req.reply_complete(sock, &session));
     Ok(())
 }
@@ -72,23 +82,107 @@ pub fn session_get(req: &mut Envelope,
                    state: &mut ServerState)
                    -> Result<()> {
     let msg: proto::SessionGet = try!(req.parse_msg());
+    match state.datastore.sessions.find(&msg.get_token().to_string()) {
+        Ok(ref token) if !accepts_client(&msg, token) => {
+            // The token was issued to an OAuth application which is no longer configured, for
+            // example before its credentials were rotated.
+            info!("invalidating session issued to a previous OAuth application, owner={}, \
+                   client_id={}",
+                  token.get_owner_id(),
+                  token.get_client_id());
+            try!(state.datastore.sessions.delete(&token.get_token().to_string()));
+            let err = net::err(ErrCode::SESSION_EXPIRED, "ss:auth:6");
+            try!(req.reply_complete(sock, &err));
+        }
+        Ok(token) => {
+            let session = token_session(state, token);
+            try!(req.reply_complete(sock, &session));
+        }
+        Err(dbcache::Error::EntityNotFound) => {
+            let err = net::err(ErrCode::SESSION_EXPIRED, "ss:auth:4");
+            try!(req.reply_complete(sock, &err));
+        }
+        Err(e) => {
+            error!("datastore error, err={:?}", e);
+            let err = net::err(ErrCode::INTERNAL, "ss:auth:5");
+            try!(req.reply_complete(sock, &err));
+        }
+    }
+    Ok(())
+}
+
+pub fn session_validated(req: &mut Envelope,
+                         sock: &mut zmq::Socket,
+                         state: &mut ServerState)
+                         -> Result<()> {
+    let mut msg: proto::SessionValidated = try!(req.parse_msg());
     match state.datastore.sessions.find(&msg.get_token().to_string()) {
         Ok(mut token) => {
+            token.set_client_id(msg.take_client_id());
+            token.set_scopes(msg.take_scopes());
+            token.set_issued_at(msg.get_issued_at());
+            token.set_last_validated_at(msg.get_last_validated_at());
+            try!(state.datastore.sessions.write(&token));
+            let session = token_session(state, token);
+            try!(req.reply_complete(sock, &session));
+        }
+        Err(dbcache::Error::EntityNotFound) => {
+            let err = net::err(ErrCode::SESSION_EXPIRED, "ss:session_validated:0");
+            try!(req.reply_complete(sock, &err));
+        }
+        Err(e) => {
+            error!("datastore error, err={:?}", e);
+            let err = net::err(ErrCode::INTERNAL, "ss:session_validated:1");
+            try!(req.reply_complete(sock, &err));
+        }
+    }
+    Ok(())
+}
+
+pub fn session_delete(req: &mut Envelope,
+                      sock: &mut zmq::Socket,
+                      state: &mut ServerState)
+                      -> Result<()> {
+    let msg: proto::SessionDelete = try!(req.parse_msg());
+    match state.datastore.sessions.find(&msg.get_token().to_string()) {
+        Ok(mut token) => {
+            try!(state.datastore.sessions.delete(&token.get_token().to_string()));
             let account: proto::Account =
                 state.datastore.accounts.find(&token.get_owner_id()).unwrap();
             let mut session: proto::Session = account.into();
             session.set_token(token.take_token());
+            session.set_flags(token.get_flags());
             try!(req.reply_complete(sock, &session));
         }
         Err(dbcache::Error::EntityNotFound) => {
-            let err = net::err(ErrCode::SESSION_EXPIRED, "ss:auth:4");
+            let err = net::err(ErrCode::SESSION_EXPIRED, "ss:session_delete:0");
             try!(req.reply_complete(sock, &err));
         }
         Err(e) => {
             error!("datastore error, err={:?}", e);
-            let err = net::err(ErrCode::INTERNAL, "ss:auth:5");
+            let err = net::err(ErrCode::INTERNAL, "ss:session_delete:1");
             try!(req.reply_complete(sock, &err));
         }
     }
     Ok(())
 }
+
+/// Returns the session of a stored token, with its owner's account and the token's metadata.
+fn token_session(state: &ServerState, mut token: proto::SessionToken) -> proto::Session {
+    let account: proto::Account = state.datastore.accounts.find(&token.get_owner_id()).unwrap();
+    let mut session: proto::Session = account.into();
+    session.set_token(token.take_token());
+    session.set_flags(token.get_flags());
+    session.set_client_id(token.take_client_id());
+    session.set_scopes(token.take_scopes());
+    session.set_issued_at(token.get_issued_at());
+    session.set_last_validated_at(token.get_last_validated_at());
+    session
+}
+
+/// True if the token of a session was issued to an OAuth application the request accepts. Tokens
+/// created before the application was recorded are always accepted.
+fn accepts_client(msg: &proto::SessionGet, token: &proto::SessionToken) -> bool {
+    msg.get_client_ids().is_empty() || !token.has_client_id() ||
+    msg.get_client_ids().iter().any(|id| id == token.get_client_id())
+}
@@ -73,6 +73,8 @@ impl Dispatcher for Worker {
             "AccountGet" => handlers::account_get(message, sock, state),
             "SessionCreate" => handlers::session_create(message, sock, state),
             "SessionGet" => handlers::session_get(message, sock, state),
+            "SessionDelete" => handlers::session_delete(message, sock, state),
+            "SessionValidated" => handlers::session_validated(message, sock, state),
             _ => panic!("unhandled message"),
         }
     }
@@ -135,6 +135,21 @@ impl ParseInto<String> for toml::Value {
     }
 }
 
+impl ParseInto<bool> for toml::Value {
+    fn parse_into(&self, field: &'static str, out: &mut bool) -> Result<bool> {
+        if let Some(val) = self.lookup(field) {
+            if let Some(v) = val.as_bool() {
+                *out = v;
+                Ok(true)
+            } else {
+                Err(Error::ConfigInvalidString(field))
+            }
+        } else {
+            Ok(false)
+        }
+    }
+}
+
 impl ParseInto<Option<String>> for toml::Value {
     fn parse_into(&self, field: &'static str, out: &mut Option<String>) -> Result<bool> {
         if let Some(val) = self.lookup(field) {
@@ -19,11 +19,13 @@ use std::num;
 use std::result;
 use std::str;
 use std::string;
+use std::time::Duration;
 
 use libarchive;
 use regex;
 
 use package::{self, Identifiable};
+use util::redact;
 
 pub type Result<T> = result::Result<T, Error>;
 
@@ -32,6 +34,8 @@ pub type Result<T> = result::Result<T, Error>;
 pub enum Error {
     /// Occurs when a `habitat_core::package::PackageArchive` is being read.
     ArchiveError(libarchive::error::ArchiveError),
+    /// Occurs when an ARP probe for an address can't be sent or its answers can't be read.
+    ArpProbeFailed(String),
     /// An invalid path to a keyfile was given.
     BadKeyPath(String),
     /// Error reading raw contents of configuration file.
@@ -46,36 +50,101 @@ pub enum Error {
     ConfigInvalidSocketAddrV4(&'static str),
     /// Expected a string for configuration field value.
     ConfigInvalidString(&'static str),
+    /// Occurs when the processors of this host can't be counted.
+    CpuInfoFailed(String),
     /// Crypto library error
     CryptoError(String),
+    /// Occurs when the space on the filesystem holding a path can't be determined.
+    DiskUsageFailed(String),
+    /// Occurs when the entropy available to the kernel's random number generator can't be
+    /// determined.
+    EntropyFailed(String),
+    /// Occurs when a fact provider is registered for a namespace of `[sys]` another provider
+    /// already has.
+    FactNamespaceTaken(String),
     /// Occurs when a file that should exist does not or could not be read.
     FileNotFound(String),
+    /// Occurs when the host name of this host can't be determined.
+    HostnameFailed(String),
+    /// Occurs when the network interfaces of this host can't be listed.
+    InterfaceEnumeration(io::Error),
+    /// Occurs when no strategy discovers an IP address of this host. Carries each strategy's
+    /// failure reason.
+    IpDiscoveryFailed(Vec<String>),
+    /// Occurs when a file identifying this host or its boot, such as `/etc/machine-id`, holds
+    /// something other than an id. Carries the file and its contents.
+    InvalidHostId(String, String),
+    /// Occurs when an IP selector string cannot be successfully parsed.
+    InvalidIpSelector(String),
     /// Occurs when a package identifier string cannot be successfully parsed.
     InvalidPackageIdent(String),
     /// Occurs when a service group string cannot be successfully parsed.
     InvalidServiceGroup(String),
+    /// Occurs when a URL has no host to connect to, or can't be parsed at all.
+    InvalidUrl(String),
     /// Occurs when making lower level IO calls.
     IO(io::Error),
+    /// Occurs when a service would listen on an address which isn't an address of this host.
+    ListenAddrUnavailable(String, String),
+    /// Occurs when the load average of this host can't be determined.
+    LoadAvgFailed(String),
+    /// Occurs when the memory of this host can't be determined.
+    MemInfoFailed(String),
     /// Occurs when a package metadata file cannot be opened, read, or parsed.
     MetaFileMalformed(package::MetaFile),
     /// Occurs when a particular package metadata file is not found.
     MetaFileNotFound(package::MetaFile),
     /// When an IO error while accessing a MetaFile.
     MetaFileIO(io::Error),
+    /// Occurs when the mount holding a path can't be determined.
+    MountInfoFailed(String),
+    /// Occurs when no address of this host matches an IP selector. Carries the selector and a
+    /// listing of the host's interfaces.
+    NoMatchingIp(String, String),
     /// Occurs when we can't find an outbound IP address
     NoOutboundAddr,
+    /// Occurs when no release file names the operating system of this host.
+    OsReleaseFailed(String),
     /// Occurs when a suitable installed pacakge cannot be found.
     PackageNotFound(package::PackageIdent),
     /// When an error occurs parsing an integer.
     ParseIntError(num::ParseIntError),
+    /// Occurs when the largest packets reaching a target can't be probed for.
+    PathMtuProbeFailed(String),
     /// Occurs when setting ownership or permissions on a file or directory fails.
     PermissionFailed(String),
+    /// Occurs when a file of `/proc` can't be parsed. Carries the file and what was wrong with it.
+    ProcParse { path: String, reason: String },
     /// When an error occurs parsing or compiling a regular expression.
     RegexParse(regex::Error),
+    /// Occurs when the resource limits of this process can't be determined.
+    RlimitsFailed(String),
     /// When an error occurs converting a `String` from a UTF-8 byte vector.
     StringFromUtf8Error(string::FromUtf8Error),
+    /// Occurs when a command run to discover a fact about this host fails. Carries the command
+    /// line, how the command exited and the start of what it wrote.
+    SysCommandFailed {
+        cmd: String,
+        status: String,
+        stdout: String,
+        stderr: String,
+    },
+    /// Occurs when a command run to discover a fact about this host doesn't finish in time and is
+    /// killed. Carries the command line, how long it ran and the start of what it wrote until
+    /// then.
+    SysCommandTimeout {
+        cmd: String,
+        timeout: Duration,
+        stdout: String,
+        stderr: String,
+    },
+    /// Occurs when a fact about this host failed to be discovered when the cached snapshot of
+    /// them was taken. Carries the original failure.
+    SysInfoFailed(String),
     /// Occurs when a `uname` libc call returns an error.
     UnameFailed(String),
+    /// Occurs when the time since this host booted can't be determined.
+    UptimeFailed(String),
     /// When an error occurs attempting to interpret a sequence of u8 as a string.
     Utf8Error(str::Utf8Error),
 }
@@ -84,6 +153,7 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match *self {
             Error::ArchiveError(ref err) => format!("{}", err),
+            Error::ArpProbeFailed(ref e) => format!("ARP probe failed: {}", e),
             Error::BadKeyPath(ref e) => {
                 format!("Invalid keypath: {}. Specify an absolute path to a file on disk.",
                         e)
@@ -108,8 +178,30 @@ impl fmt::Display for Error {
             Error::ConfigInvalidString(ref f) => {
                 format!("Invalid string value in config, field={}.", f)
             }
+            Error::CpuInfoFailed(ref e) => format!("Failed to count the CPUs of this host: {}", e),
             Error::CryptoError(ref e) => format!("Crypto error: {}", e),
+            Error::DiskUsageFailed(ref e) => format!("{}", e),
+            Error::EntropyFailed(ref e) => format!("Failed to read the available entropy: {}", e),
+            Error::FactNamespaceTaken(ref e) => {
+                format!("Another fact provider already renders sys.{}", e)
+            }
             Error::FileNotFound(ref e) => format!("File not found at: {}", e),
+            Error::HostnameFailed(ref e) => format!("Failed to discover this hosts name: {}", e),
+            Error::InterfaceEnumeration(ref e) => {
+                format!("Failed to list the network interfaces of this host: {}", e)
+            }
+            Error::IpDiscoveryFailed(ref reasons) => {
+                format!("Failed to discover an IP address of this host: {}",
+                        reasons.join("; "))
+            }
+            Error::InvalidHostId(ref path, ref contents) => {
+                format!("{} holds {:?}, expected 32 hex digits or a UUID", path, contents)
+            }
+            Error::InvalidIpSelector(ref e) => {
+                format!("Invalid IP selector: {:?}. A valid selector is an interface name \
+                         (example: eth1), a CIDR (example: 10.0.0.0/8), or default",
+                        e)
+            }
             Error::InvalidPackageIdent(ref e) => {
                 format!("Invalid package identifier: {:?}. A valid identifier is in the form \
                          origin/name (example: acme/redis)",
@@ -120,13 +212,28 @@ impl fmt::Display for Error {
                          service.group (example: redis.production)",
                         e)
             }
+            Error::InvalidUrl(ref e) => format!("Invalid URL: {}", e),
             Error::IO(ref err) => format!("{}", err),
+            Error::ListenAddrUnavailable(ref addr, ref interfaces) => {
+                format!("Can't listen on {}, which isn't an address of this host. Interfaces: {}",
+                        addr,
+                        interfaces)
+            }
+            Error::LoadAvgFailed(ref e) => format!("{}", e),
+            Error::MemInfoFailed(ref e) => format!("Failed to read the memory of this host: {}", e),
             Error::MetaFileMalformed(ref e) => {
                 format!("MetaFile: {:?}, didn't contain a valid UTF-8 string", e)
             }
             Error::MetaFileNotFound(ref e) => format!("Couldn't read MetaFile: {}, not found", e),
             Error::MetaFileIO(ref e) => format!("IO error while accessing MetaFile: {:?}", e),
+            Error::MountInfoFailed(ref e) => format!("{}", e),
+            Error::NoMatchingIp(ref selector, ref interfaces) => {
+                format!("No IP address of this host matches {}. Available interfaces: {}",
+                        selector,
+                        interfaces)
+            }
             Error::NoOutboundAddr => format!("Failed to discover this hosts outbound IP address"),
+            Error::OsReleaseFailed(ref e) => format!("{}", e),
             Error::PackageNotFound(ref pkg) => {
                 if pkg.fully_qualified() {
                     format!("Cannot find package: {}", pkg)
@@ -135,13 +242,37 @@ impl fmt::Display for Error {
                 }
             }
             Error::ParseIntError(ref e) => format!("{}", e),
+            Error::PathMtuProbeFailed(ref e) => format!("Path MTU probe failed: {}", e),
             Error::PermissionFailed(ref e) => format!("{}", e),
+            Error::ProcParse { ref path, ref reason } => {
+                format!("Failed to parse {}: {}", path, reason)
+            }
             Error::RegexParse(ref e) => format!("{}", e),
+            Error::RlimitsFailed(ref e) => {
+                format!("Failed to read the resource limits of this process: {}", e)
+            }
             Error::StringFromUtf8Error(ref e) => format!("{}", e),
+            Error::SysCommandFailed { ref cmd, ref status, ref stdout, ref stderr } => {
+                format!("{} failed with {}, stdout: {:?}, stderr: {:?}",
+                        cmd,
+                        status,
+                        stdout.trim(),
+                        stderr.trim())
+            }
+            Error::SysCommandTimeout { ref cmd, ref timeout, ref stdout, ref stderr } => {
+                format!("{} didn't finish within {}ms and was killed, stdout: {:?}, stderr: {:?}",
+                        cmd,
+                        timeout.as_secs() * 1000 + (timeout.subsec_nanos() / 1_000_000) as u64,
+                        stdout.trim(),
+                        stderr.trim())
+            }
+            Error::SysInfoFailed(ref e) => format!("{}", e),
             Error::UnameFailed(ref e) => format!("{}", e),
+            Error::UptimeFailed(ref e) => format!("{}", e),
             Error::Utf8Error(ref e) => format!("{}", e),
         };
-        write!(f, "{}", msg)
+        // Errors end up in logs, and the output of a failed command may hold secrets
+        write!(f, "{}", redact::redact_text(&msg))
     }
 }
 
@@ -149,6 +280,7 @@ impl error::Error for Error {
     fn description(&self) -> &str {
         match *self {
             Error::ArchiveError(ref err) => err.description(),
+            Error::ArpProbeFailed(_) => "Failed to probe the local network for an address",
             Error::BadKeyPath(_) => "An absolute path to a file on disk is required",
             Error::ConfigFileIO(_) => "Unable to read the raw contents of a configuration file",
             Error::ConfigFileSyntax(_) => "Error parsing contents of configuration file",
@@ -164,25 +296,52 @@ impl error::Error for Error {
             Error::ConfigInvalidString(_) => {
                 "Invalid string value encountered while parsing a configuration file"
             }
+            Error::CpuInfoFailed(_) => "Failed to count the CPUs of this host",
             Error::CryptoError(_) => "Crypto error",
+            Error::DiskUsageFailed(_) => "Failed to determine the space on a filesystem",
+            Error::EntropyFailed(_) => "Failed to read the available entropy",
+            Error::FactNamespaceTaken(_) => "A fact provider's namespace is already taken",
             Error::FileNotFound(_) => "File not found",
+            Error::HostnameFailed(_) => "Failed to discover the host name",
+            Error::InterfaceEnumeration(_) => "Failed to list the network interfaces",
+            Error::IpDiscoveryFailed(_) => "Failed to discover an IP address",
+            Error::InvalidHostId(_, _) => "Host id files must hold 32 hex digits or a UUID",
+            Error::InvalidIpSelector(_) => {
+                "IP selectors must be an interface name, a CIDR, or default (example: 10.0.0.0/8)"
+            }
             Error::InvalidPackageIdent(_) => {
                 "Package identifiers must be in origin/name format (example: acme/redis)"
             }
             Error::InvalidServiceGroup(_) => {
                 "Service group strings must be in service.group format (example: redis.production)"
             }
+            Error::InvalidUrl(_) => "URLs must have a scheme and a host",
             Error::IO(ref err) => err.description(),
+            Error::ListenAddrUnavailable(_, _) => "The address to listen on isn't one of this host",
+            Error::LoadAvgFailed(_) => "Failed to determine the load average of this host",
+            Error::MemInfoFailed(_) => "Failed to read the memory of this host",
             Error::MetaFileMalformed(_) => "MetaFile didn't contain a valid UTF-8 string",
             Error::MetaFileNotFound(_) => "Failed to read an archive's metafile",
             Error::MetaFileIO(_) => "MetaFile could not be read or written to",
+            Error::MountInfoFailed(_) => "Failed to determine the mount holding a path",
+            Error::NoMatchingIp(_, _) => "No IP address of this host matches the IP selector",
             Error::NoOutboundAddr => "Failed to discover the outbound IP address",
+            Error::OsReleaseFailed(_) => "Failed to determine the operating system release",
             Error::PackageNotFound(_) => "Cannot find a package",
             Error::ParseIntError(_) => "Failed to parse an integer from a string!",
+            Error::PathMtuProbeFailed(_) => "Failed to probe the largest packets reaching a target",
             Error::PermissionFailed(_) => "Failed to set permissions",
+            Error::ProcParse { .. } => "Failed to parse a file of /proc",
             Error::RegexParse(_) => "Failed to parse a regular expression",
+            Error::RlimitsFailed(_) => "Failed to read the resource limits of this process",
             Error::StringFromUtf8Error(_) => "Failed to convert a string from a Vec<u8> as UTF-8",
+            Error::SysCommandFailed { .. } => "A command discovering a fact about this host failed",
+            Error::SysCommandTimeout { .. } => {
+                "A command discovering a fact about this host didn't finish in time"
+            }
+            Error::SysInfoFailed(_) => "Failed to discover a fact about this host",
             Error::UnameFailed(_) => "uname failed",
+            Error::UptimeFailed(_) => "Failed to determine the time since this host booted",
             Error::Utf8Error(_) => "Failed to interpret a sequence of bytes as a string",
         }
     }
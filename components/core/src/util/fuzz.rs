@@ -0,0 +1,210 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Short runs of the fuzz targets of `components/fuzz` for the ignored tests, which exercise the
+//! targets a little without the fuzzer toolchain.
+//!
+//! Each target lists its seeds in `components/fuzz/seeds/<target>`, one path of a recorded
+//! fixture relative to `components` per line. `fuzz/seed-corpus.sh` copies them into the corpus
+//! cargo-fuzz starts from, and `smoke` runs the target on each of them, on whatever the fuzzer
+//! has added to the corpus, and on mutations of them. The mutations come from a fixed seed, so a
+//! failing run fails the same way every time.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+/// Mutated inputs a smoke run tries after the seeds
+pub const SMOKE_MUTATIONS: usize = 5000;
+
+/// Bytes the parsers split on or treat specially, which mutations insert
+const INTERESTING_BYTES: &'static [u8] = b"\n\r\t :=-,/%[]{}\"'\\#()\x00\xff";
+
+/// Numbers at the edges of the integer types, which mutations swap digits for
+const INTERESTING_NUMBERS: &'static [&'static str] = &["0",
+                                                       "-1",
+                                                       "255",
+                                                       "4294967295",
+                                                       "4294967296",
+                                                       "18446744073709551615",
+                                                       "18446744073709551616",
+                                                       "99999999999999999999999999"];
+
+/// Returns the `components` directory the fuzz targets, their seeds and the fixtures are in.
+pub fn components_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().expect("core has a parent").to_path_buf()
+}
+
+/// Returns the seeds of `target` followed by the inputs in its cargo-fuzz corpus, if it has one.
+///
+/// # Panics
+///
+/// * The target has no seeds, or one of them can't be read
+pub fn seeds(target: &str) -> Vec<Vec<u8>> {
+    let components = components_dir();
+    let list = read(&components.join("fuzz").join("seeds").join(target));
+    let mut seeds: Vec<Vec<u8>> = String::from_utf8_lossy(&list)
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|fixture| read(&components.join(fixture)))
+        .collect();
+    assert!(!seeds.is_empty(), "fuzz target {} has no seeds", target);
+    if let Ok(entries) = fs::read_dir(components.join("fuzz").join("corpus").join(target)) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if entry.path().is_file() {
+                seeds.push(read(&entry.path()));
+            }
+        }
+    }
+    seeds
+}
+
+/// Runs `run`, the body of the fuzz target `target`, on its seeds and on `SMOKE_MUTATIONS`
+/// mutations of them.
+///
+/// # Panics
+///
+/// * `run` panics on any of the inputs, with the input in the message
+pub fn smoke<F: Fn(&[u8])>(target: &str, run: F) {
+    let seeds = seeds(target);
+    let mut rng = XorShift(0x9e3779b97f4a7c15);
+    for i in 0..seeds.len() + SMOKE_MUTATIONS {
+        let input = if i < seeds.len() {
+            seeds[i].clone()
+        } else {
+            mutate(&mut rng, &seeds)
+        };
+        if panic::catch_unwind(AssertUnwindSafe(|| run(&input))).is_err() {
+            panic!("fuzz target {} panicked on {:?}",
+                   target,
+                   String::from_utf8_lossy(&input));
+        }
+    }
+}
+
+fn read(path: &Path) -> Vec<u8> {
+    let mut bytes = vec![];
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .unwrap_or_else(|e| panic!("can't read {}: {}", path.display(), e));
+    bytes
+}
+
+/// Returns one of the seeds with one to four mutations applied.
+fn mutate(rng: &mut XorShift, seeds: &[Vec<u8>]) -> Vec<u8> {
+    let mut input = seeds[rng.below(seeds.len())].clone();
+    for _ in 0..1 + rng.below(4) {
+        let len = input.len();
+        match rng.below(7) {
+            0 if len > 0 => {
+                let at = rng.below(len);
+                input[at] ^= 1 << rng.below(8);
+            }
+            1 => {
+                let at = rng.below(len + 1);
+                input.insert(at, INTERESTING_BYTES[rng.below(INTERESTING_BYTES.len())]);
+            }
+            2 if len > 0 => {
+                let at = rng.below(len);
+                let end = at + 1 + rng.below(len - at);
+                input.drain(at..end);
+            }
+            3 if len > 0 => {
+                let at = rng.below(len);
+                let end = at + 1 + rng.below(len - at);
+                let copy: Vec<u8> = input[at..end].to_vec();
+                let to = rng.below(len + 1);
+                for (i, b) in copy.into_iter().enumerate() {
+                    input.insert(to + i, b);
+                }
+            }
+            4 => {
+                let other = &seeds[rng.below(seeds.len())];
+                let at = rng.below(len + 1);
+                let from = rng.below(other.len() + 1);
+                input.truncate(at);
+                input.extend_from_slice(&other[from..]);
+            }
+            5 => input = swap_number(rng, input),
+            _ => {
+                let at = rng.below(len + 1);
+                input.truncate(at);
+            }
+        }
+    }
+    input
+}
+
+/// Replaces the digits of a number in `input` with one of `INTERESTING_NUMBERS`.
+fn swap_number(rng: &mut XorShift, input: Vec<u8>) -> Vec<u8> {
+    let digits: Vec<usize> = (0..input.len())
+        .filter(|i| (input[*i] as char).is_digit(10))
+        .collect();
+    if digits.is_empty() {
+        return input;
+    }
+    let start = digits[rng.below(digits.len())];
+    let mut end = start;
+    while end < input.len() && (input[end] as char).is_digit(10) {
+        end += 1;
+    }
+    let mut swapped = input[..start].to_vec();
+    swapped.extend_from_slice(INTERESTING_NUMBERS[rng.below(INTERESTING_NUMBERS.len())]
+        .as_bytes());
+    swapped.extend_from_slice(&input[end..]);
+    swapped
+}
+
+/// Marsaglia's xorshift, which is all the randomness mutating needs.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number below `n`, or 0 if `n` is 0.
+    fn below(&mut self, n: usize) -> usize {
+        if n == 0 { 0 } else { (self.next() % n as u64) as usize }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{XorShift, mutate};
+
+    #[test]
+    fn mutations_are_repeatable() {
+        let seeds = vec![b"cpu 1 2 3 4\n".to_vec(), b"MemTotal: 1 kB\n".to_vec()];
+        let mut first = XorShift(1);
+        let mut second = XorShift(1);
+        for _ in 0..100 {
+            assert_eq!(mutate(&mut first, &seeds), mutate(&mut second, &seeds));
+        }
+    }
+
+    #[test]
+    fn empty_seeds_can_be_mutated() {
+        let seeds = vec![vec![]];
+        let mut rng = XorShift(7);
+        for _ in 0..100 {
+            mutate(&mut rng, &seeds);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sending counters, timings and gauges somewhere outside the process.
+//!
+//! Whatever measures something, such as the GitHub client or the sys metrics sampler, is handed
+//! a `MetricsSink` when it is constructed and reports to it under the keys of `keys`. The sink
+//! decides where the metrics go: `NoopSink`, the default, drops them, and `StatsdSink` sends
+//! them to a statsd daemon over UDP.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The keys metrics are reported under. Dashboards and alerts are built on them, so a key is
+/// never renamed; a metric which changes meaning gets a new key instead.
+pub mod keys {
+    /// Requests sent to the GitHub API, counted when they are sent
+    pub const GITHUB_REQUESTS: &'static str = "github.requests";
+    /// Requests to the GitHub API which failed on the way or with a server error
+    pub const GITHUB_REQUEST_FAILURES: &'static str = "github.requests.failed";
+    /// Time from sending a request to the GitHub API to having its response or failure
+    pub const GITHUB_REQUEST_TIME: &'static str = "github.request_time";
+    /// Requests to the GitHub API refused because the circuit breaker of their host is open
+    pub const GITHUB_BREAKER_REJECTIONS: &'static str = "github.breaker.rejected";
+    /// Responses served from the client's cache without asking GitHub
+    pub const GITHUB_CACHE_HITS: &'static str = "github.cache.hit";
+    /// Cached responses GitHub confirmed were unchanged
+    pub const GITHUB_CACHE_REVALIDATIONS: &'static str = "github.cache.revalidated";
+    /// Requests GitHub reported it will still accept in the current rate limit window
+    pub const GITHUB_RATE_LIMIT_REMAINING: &'static str = "github.rate_limit.remaining";
+
+    /// Load average over the last minute, see `sys::MetricsSample`
+    pub const SYS_LOAD_ONE: &'static str = "sys.load.one";
+    /// Bytes of memory which can be allocated without swapping
+    pub const SYS_MEM_AVAILABLE_BYTES: &'static str = "sys.mem.available_bytes";
+    /// Bytes unprivileged users may still write on the filesystem holding Habitat's files
+    pub const SYS_DISK_AVAILABLE_BYTES: &'static str = "sys.disk.available_bytes";
+    /// Samples of the sys metrics which failed entirely
+    pub const SYS_SAMPLE_FAILURES: &'static str = "sys.samples.failed";
+}
+
+/// Where metrics are reported. Reporting never fails and never blocks for long, so it can be
+/// done on any path; a sink which can't deliver a metric drops it.
+pub trait MetricsSink: Send + Sync {
+    /// Adds `n` to the counter `key`.
+    fn incr(&self, key: &str, n: u64);
+
+    /// Records that something measured under `key` took `d`.
+    fn timing(&self, key: &str, d: Duration);
+
+    /// Sets the gauge `key` to `v`.
+    fn gauge(&self, key: &str, v: f64);
+}
+
+/// A sink which drops every metric, used when none is configured.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopSink;
+
+impl MetricsSink for NoopSink {
+    fn incr(&self, _key: &str, _n: u64) {}
+
+    fn timing(&self, _key: &str, _d: Duration) {}
+
+    fn gauge(&self, _key: &str, _v: f64) {}
+}
+
+/// Returns a shared `NoopSink`.
+pub fn noop() -> Arc<MetricsSink> {
+    Arc::new(NoopSink)
+}
+
+/// A sink sending each metric as a datagram of the plain text statsd protocol, such as
+/// `hab.github.requests:1|c`. Sends never block, and a datagram which can't be sent, because
+/// the socket's buffer is full or nothing listens at the address, is dropped.
+#[derive(Debug)]
+pub struct StatsdSink {
+    socket: UdpSocket,
+    prefix: String,
+}
+
+impl StatsdSink {
+    /// Returns a sink sending to the statsd daemon at `addr`, such as `127.0.0.1:8125`, with
+    /// every key prefixed by `prefix`, such as `hab.`. The address is resolved once, here.
+    ///
+    /// # Errors
+    ///
+    /// * The address doesn't resolve
+    /// * The socket could not be bound or made non-blocking
+    pub fn new<A: ToSocketAddrs>(addr: A, prefix: &str) -> io::Result<Self> {
+        let addr = match try!(addr.to_socket_addrs()).next() {
+            Some(addr) => addr,
+            None => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "statsd address resolves to nothing"))
+            }
+        };
+        let local = match addr {
+            SocketAddr::V4(_) => "0.0.0.0:0",
+            SocketAddr::V6(_) => "[::]:0",
+        };
+        let socket = try!(UdpSocket::bind(local));
+        try!(socket.set_nonblocking(true));
+        try!(socket.connect(addr));
+        Ok(StatsdSink {
+            socket: socket,
+            prefix: prefix.to_string(),
+        })
+    }
+
+    fn send(&self, key: &str, value: &str, kind: &str) {
+        let datagram = format_datagram(&self.prefix, key, value, kind);
+        let _ = self.socket.send(datagram.as_bytes());
+    }
+}
+
+impl MetricsSink for StatsdSink {
+    fn incr(&self, key: &str, n: u64) {
+        self.send(key, &n.to_string(), "c");
+    }
+
+    fn timing(&self, key: &str, d: Duration) {
+        let millis = d.as_secs() * 1000 + d.subsec_nanos() as u64 / 1_000_000;
+        self.send(key, &millis.to_string(), "ms");
+    }
+
+    /// statsd reads a signed gauge as a change of the gauge rather than its value, so a
+    /// negative value is sent as 0.
+    fn gauge(&self, key: &str, v: f64) {
+        let v = if v < 0.0 || v.is_nan() { 0.0 } else { v };
+        self.send(key, &v.to_string(), "g");
+    }
+}
+
+/// Returns the datagram reporting a metric. The characters the protocol uses as separators,
+/// and whitespace, are replaced with `_` in the key.
+fn format_datagram(prefix: &str, key: &str, value: &str, kind: &str) -> String {
+    let key: String = prefix.chars()
+        .chain(key.chars())
+        .map(|c| match c {
+            ':' | '|' | '@' => '_',
+            c if c.is_whitespace() => '_',
+            c => c,
+        })
+        .collect();
+    format!("{}:{}|{}", key, value, kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::UdpSocket;
+    use std::time::Duration;
+
+    use super::*;
+    use super::format_datagram;
+
+    /// Returns a socket listening on a port of its own, as the statsd daemon.
+    fn daemon() -> UdpSocket {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+        socket
+    }
+
+    fn recv(socket: &UdpSocket) -> String {
+        let mut buf = [0; 512];
+        let (len, _) = socket.recv_from(&mut buf).unwrap();
+        String::from_utf8(buf[..len].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn metrics_are_sent_as_statsd_datagrams() {
+        let daemon = daemon();
+        let sink = StatsdSink::new(daemon.local_addr().unwrap(), "hab.").unwrap();
+        sink.incr(keys::GITHUB_REQUESTS, 1);
+        assert_eq!(recv(&daemon), "hab.github.requests:1|c");
+        sink.timing(keys::GITHUB_REQUEST_TIME, Duration::from_millis(1250));
+        assert_eq!(recv(&daemon), "hab.github.request_time:1250|ms");
+        sink.gauge(keys::SYS_LOAD_ONE, 0.5);
+        assert_eq!(recv(&daemon), "hab.sys.load.one:0.5|g");
+        sink.gauge(keys::SYS_MEM_AVAILABLE_BYTES, 2147483648.0);
+        assert_eq!(recv(&daemon), "hab.sys.mem.available_bytes:2147483648|g");
+        sink.gauge(keys::SYS_LOAD_ONE, -1.0);
+        assert_eq!(recv(&daemon), "hab.sys.load.one:0|g");
+    }
+
+    #[test]
+    fn separators_in_keys_are_replaced() {
+        assert_eq!(format_datagram("my app:", "a|b@c", "1", "c"), "my_app_a_b_c:1|c");
+    }
+
+    #[test]
+    fn undeliverable_metrics_are_dropped() {
+        // Nothing listens on the port once the socket holding it is gone
+        let addr = daemon().local_addr().unwrap();
+        let sink = StatsdSink::new(addr, "").unwrap();
+        for _ in 0..100 {
+            sink.incr(keys::GITHUB_REQUESTS, 1);
+        }
+    }
+
+    #[test]
+    fn noop_sink_takes_anything() {
+        let sink = noop();
+        sink.incr(keys::GITHUB_REQUESTS, 1);
+        sink.timing(keys::GITHUB_REQUEST_TIME, Duration::from_secs(1));
+        sink.gauge(keys::SYS_LOAD_ONE, 1.0);
+    }
+}
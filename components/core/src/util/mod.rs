@@ -12,5 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod fuzz;
+pub mod metrics;
 pub mod perm;
+pub mod redact;
+pub mod render;
+pub mod retry;
+pub mod shutdown;
 pub mod sys;
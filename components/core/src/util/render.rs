@@ -0,0 +1,117 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reports written for people and for the tools wrapping Habitat, such as the hab CLI or a
+//! support bundle, which read them without scraping log lines.
+//!
+//! A `Render` report has a plain text form, lined up in columns and wrapped to the width of a
+//! terminal, and a TOML form holding the same facts. Every report says when it was made and how
+//! long it took: timestamps are RFC 3339 strings in UTC, and durations are integers of
+//! milliseconds under keys ending in `_ms`.
+
+use std::time::Duration;
+
+use time;
+use toml;
+
+use util::sys;
+
+/// A report with a text and a TOML form.
+pub trait Render {
+    /// Returns the report as plain text, its lines wrapped to `width` columns, or not wrapped at
+    /// all if `width` is 0.
+    fn to_text(&self, width: usize) -> String;
+
+    /// Returns the report as a TOML table, which `toml::encode_str` writes and `toml::Parser`
+    /// reads back unchanged.
+    fn to_toml(&self) -> toml::Table;
+}
+
+/// Returns a line wrapped at spaces into lines of at most `width` columns, or unchanged if `width`
+/// is 0. The first `indent` columns, such as those lining other columns up, are never broken, and
+/// every line after the first is indented by as many spaces. A word too long for a line of its
+/// own is left whole.
+pub fn wrap(line: &str, indent: usize, width: usize) -> String {
+    if width == 0 || line.chars().count() <= width {
+        return line.to_string();
+    }
+    let mut lines = vec![];
+    let mut current: String = line.chars().take(indent).collect();
+    let mut len = current.chars().count();
+    let mut words = 0;
+    let rest: String = line.chars().skip(indent).collect();
+    for word in rest.split(' ').filter(|word| !word.is_empty()) {
+        let word_len = word.chars().count();
+        if words > 0 && len + 1 + word_len > width {
+            lines.push(current);
+            current = format!("{:1$}", "", indent);
+            len = indent;
+            words = 0;
+        }
+        if words > 0 {
+            current.push(' ');
+            len += 1;
+        }
+        current.push_str(word);
+        len += word_len;
+        words += 1;
+    }
+    lines.push(current);
+    lines.join("\n")
+}
+
+/// Returns a time as the TOML string of its RFC 3339 timestamp in UTC.
+pub fn timestamp(at: time::Timespec) -> toml::Value {
+    toml::Value::String(sys::rfc3339_utc(at))
+}
+
+/// Returns a duration as a TOML integer of milliseconds.
+pub fn duration_ms(duration: Duration) -> toml::Value {
+    toml::Value::Integer(millis(duration) as i64)
+}
+
+/// Returns a duration in whole milliseconds.
+pub fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + (duration.subsec_nanos() / 1_000_000) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use time;
+
+    use super::*;
+
+    #[test]
+    fn lines_wrap_after_their_indent() {
+        let line = "  disk_space  warn  5% of the data filesystem free, 512 MB of 10240 MB";
+        assert_eq!(wrap(line, 20, 0), line);
+        assert_eq!(wrap(line, 20, 100), line);
+        assert_eq!(wrap(line, 20, 48),
+                   ["  disk_space  warn  5% of the data filesystem",
+                    "                    free, 512 MB of 10240 MB"]
+                       .join("\n"));
+        // Columns aren't broken, and long words aren't either
+        assert_eq!(wrap("  a  https://depot.example.com/v1/depot/pkgs", 5, 12),
+                   "  a  https://depot.example.com/v1/depot/pkgs");
+    }
+
+    #[test]
+    fn times_and_durations_are_toml_values() {
+        assert_eq!(timestamp(time::Timespec::new(1476350400, 0)).as_str(),
+                   Some("2016-10-13T09:20:00Z"));
+        assert_eq!(duration_ms(Duration::new(2, 345_678_901)).as_integer(), Some(2345));
+    }
+}
@@ -0,0 +1,423 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Running operations which fail transiently again, such as requests to the GitHub API or the
+//! probes of a cloud's metadata service.
+//!
+//! `retry` runs an operation until it succeeds, its error is classified as permanent, the
+//! retries of its `RetryPolicy` are used up, or waiting for the next retry would take it past
+//! the policy's cap on elapsed time. Waiting goes through a `Sleeper`, which tests replace with a
+//! `FakeSleeper` that only moves a clock forward, so they run instantly.
+
+use std::cell::RefCell;
+use std::cmp;
+use std::error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Sets jitter drawn by calls in flight at the same time apart
+static JITTER_CALLS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Whether an operation which failed with an error is worth running again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Retryable {
+    /// The error may go away on its own, such as a dropped connection
+    Transient,
+    /// Running the operation again fails the same way, such as bad credentials
+    Permanent,
+}
+
+/// How long to wait before each retry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backoff {
+    /// The same delay before every retry
+    Fixed(Duration),
+    /// A delay doubling from `base` on each retry, up to `max`
+    Exponential { base: Duration, max: Duration },
+    /// A delay drawn evenly between half of and the whole of the `Exponential` one, so
+    /// callers which failed together don't retry together
+    ExponentialJitter { base: Duration, max: Duration },
+}
+
+impl Backoff {
+    /// Returns the longest delay before the retry following the given attempt, counting from
+    /// 1. Only `ExponentialJitter` waits less.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match *self {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { base, max } |
+            Backoff::ExponentialJitter { base, max } => {
+                let factor = 1u64.checked_shl(attempt.saturating_sub(1))
+                    .unwrap_or(u64::max_value());
+                from_nanos(cmp::min(nanos(base).saturating_mul(factor), nanos(max)))
+            }
+        }
+    }
+
+    fn draw(&self, attempt: u32, rng: &mut XorShift) -> Duration {
+        let delay = self.delay(attempt);
+        match *self {
+            Backoff::ExponentialJitter { .. } => {
+                let half = nanos(delay) / 2;
+                from_nanos(half + rng.next() % (nanos(delay) - half + 1))
+            }
+            _ => delay,
+        }
+    }
+}
+
+fn nanos(d: Duration) -> u64 {
+    d.as_secs().saturating_mul(1_000_000_000).saturating_add(d.subsec_nanos() as u64)
+}
+
+fn from_nanos(nanos: u64) -> Duration {
+    Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+}
+
+/// How often, and for how long, a failing operation is run again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub backoff: Backoff,
+    /// Number of times a failed operation is run again, so it runs at most once more
+    pub max_retries: u32,
+    /// Time from the start of the first attempt past which no retry is waited for, `None` for
+    /// no limit
+    pub max_elapsed: Option<Duration>,
+}
+
+impl RetryPolicy {
+    pub fn new(backoff: Backoff, max_retries: u32) -> Self {
+        RetryPolicy {
+            backoff: backoff,
+            max_retries: max_retries,
+            max_elapsed: None,
+        }
+    }
+
+    /// Returns a policy which runs an operation once.
+    pub fn none() -> Self {
+        Self::new(Backoff::Fixed(Duration::from_secs(0)), 0)
+    }
+
+    /// Returns the policy with its elapsed time capped to `max_elapsed`.
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+}
+
+/// Why `retry` gave up, with the last error of the operation.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RetryError<E> {
+    /// The operation failed with an error classified as permanent
+    Permanent(E),
+    /// The operation still failed after the given number of attempts, which is all its policy
+    /// allows or all that fit in its elapsed time
+    Exhausted { error: E, attempts: u32 },
+}
+
+impl<E> RetryError<E> {
+    /// Returns the last error of the operation.
+    pub fn into_inner(self) -> E {
+        match self {
+            RetryError::Permanent(error) |
+            RetryError::Exhausted { error, .. } => error,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RetryError::Permanent(ref error) => write!(f, "{}", error),
+            RetryError::Exhausted { ref error, attempts } => {
+                write!(f, "{}, after {} attempts", error, attempts)
+            }
+        }
+    }
+}
+
+impl<E: error::Error> error::Error for RetryError<E> {
+    fn description(&self) -> &str {
+        match *self {
+            RetryError::Permanent(ref error) |
+            RetryError::Exhausted { ref error, .. } => error.description(),
+        }
+    }
+}
+
+/// What `retry` tells the time with and waits on.
+pub trait Sleeper {
+    fn now(&self) -> Instant;
+
+    fn sleep(&self, delay: Duration);
+}
+
+/// A sleeper on the clock, which blocks the calling thread.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadSleeper;
+
+impl Sleeper for ThreadSleeper {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, delay: Duration) {
+        thread::sleep(delay);
+    }
+}
+
+/// A sleeper which returns at once, moving a clock of its own forward by the delay instead. It
+/// records every delay it was asked to wait.
+#[derive(Debug)]
+pub struct FakeSleeper {
+    start: Instant,
+    slept: RefCell<Vec<Duration>>,
+}
+
+impl FakeSleeper {
+    pub fn new() -> Self {
+        FakeSleeper {
+            start: Instant::now(),
+            slept: RefCell::new(vec![]),
+        }
+    }
+
+    /// Returns the delays waited so far, in order.
+    pub fn slept(&self) -> Vec<Duration> {
+        self.slept.borrow().clone()
+    }
+}
+
+impl Sleeper for FakeSleeper {
+    fn now(&self) -> Instant {
+        self.slept.borrow().iter().fold(self.start, |now, delay| now + *delay)
+    }
+
+    fn sleep(&self, delay: Duration) {
+        self.slept.borrow_mut().push(delay);
+    }
+}
+
+/// Runs `op` until it succeeds or `retry_with` gives up, waiting on the calling thread.
+pub fn retry<F, T, E, C>(policy: &RetryPolicy, classify: C, op: F) -> Result<T, RetryError<E>>
+    where F: FnMut(u32) -> Result<T, E>,
+          C: Fn(&E) -> Retryable
+{
+    retry_with(policy, &ThreadSleeper, classify, op)
+}
+
+/// Runs `op`, which is handed the number of the attempt counting from 1, and returns its first
+/// success. An error `classify` finds permanent is returned at once; a transient one is retried
+/// after the policy's delay, unless it allows no more retries or the delay would end past its
+/// elapsed time.
+///
+/// # Errors
+///
+/// * `RetryError::Permanent` with an error `classify` finds permanent
+/// * `RetryError::Exhausted` with the last error once no more retries are allowed
+pub fn retry_with<S, F, T, E, C>(policy: &RetryPolicy,
+                                 sleeper: &S,
+                                 classify: C,
+                                 mut op: F)
+                                 -> Result<T, RetryError<E>>
+    where S: Sleeper,
+          F: FnMut(u32) -> Result<T, E>,
+          C: Fn(&E) -> Retryable
+{
+    let started = sleeper.now();
+    let mut rng = XorShift::seeded();
+    let mut attempt = 1;
+    loop {
+        let error = match op(attempt) {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        if classify(&error) == Retryable::Permanent {
+            return Err(RetryError::Permanent(error));
+        }
+        let exhausted = RetryError::Exhausted {
+            error: error,
+            attempts: attempt,
+        };
+        if attempt > policy.max_retries {
+            return Err(exhausted);
+        }
+        let delay = policy.backoff.draw(attempt, &mut rng);
+        if let Some(max_elapsed) = policy.max_elapsed {
+            if sleeper.now().duration_since(started) + delay > max_elapsed {
+                return Err(exhausted);
+            }
+        }
+        sleeper.sleep(delay);
+        attempt += 1;
+    }
+}
+
+/// Marsaglia's xorshift, which is all the randomness jitter needs.
+struct XorShift(u64);
+
+impl XorShift {
+    /// Returns a generator seeded from the clock and the number of calls before, which is never
+    /// seeded with 0.
+    fn seeded() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs() ^ since.subsec_nanos() as u64)
+            .unwrap_or(0);
+        let calls = JITTER_CALLS.fetch_add(1, Ordering::Relaxed) as u64;
+        XorShift((nanos ^ calls.wrapping_mul(0x9e3779b97f4a7c15)) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn transient(_: &&'static str) -> Retryable {
+        Retryable::Transient
+    }
+
+    /// Runs an operation failing `failures` times under `policy`, and returns the outcome and
+    /// the delays waited.
+    fn run(policy: &RetryPolicy,
+           failures: u32)
+           -> (Result<u32, RetryError<&'static str>>, Vec<Duration>) {
+        let sleeper = FakeSleeper::new();
+        let result = retry_with(policy, &sleeper, transient, |attempt| if attempt > failures {
+            Ok(attempt)
+        } else {
+            Err("down")
+        });
+        (result, sleeper.slept())
+    }
+
+    #[test]
+    fn fixed_delays_are_waited_until_success() {
+        let policy = RetryPolicy::new(Backoff::Fixed(ms(10)), 5);
+        assert_eq!(run(&policy, 3), (Ok(4), vec![ms(10), ms(10), ms(10)]));
+    }
+
+    #[test]
+    fn exponential_delays_double_up_to_the_max() {
+        let backoff = Backoff::Exponential {
+            base: ms(100),
+            max: ms(500),
+        };
+        let (result, slept) = run(&RetryPolicy::new(backoff, 10), 5);
+        assert_eq!(result, Ok(6));
+        assert_eq!(slept, vec![ms(100), ms(200), ms(400), ms(500), ms(500)]);
+        assert_eq!(backoff.delay(200), ms(500));
+    }
+
+    #[test]
+    fn retries_are_exhausted() {
+        let policy = RetryPolicy::new(Backoff::Fixed(ms(1)), 2);
+        let (result, slept) = run(&policy, 10);
+        assert_eq!(result,
+                   Err(RetryError::Exhausted {
+                       error: "down",
+                       attempts: 3,
+                   }));
+        assert_eq!(slept.len(), 2);
+        assert_eq!(run(&RetryPolicy::none(), 10).0.unwrap_err().into_inner(), "down");
+    }
+
+    #[test]
+    fn permanent_errors_short_circuit() {
+        let sleeper = FakeSleeper::new();
+        let attempts = Cell::new(0);
+        let result: Result<(), _> = retry_with(&RetryPolicy::new(Backoff::Fixed(ms(1)), 5),
+                                               &sleeper,
+                                               |e: &&str| if *e == "denied" {
+                                                   Retryable::Permanent
+                                               } else {
+                                                   Retryable::Transient
+                                               },
+                                               |attempt| {
+            attempts.set(attempt);
+            Err(if attempt == 2 { "denied" } else { "down" })
+        });
+        assert_eq!(result, Err(RetryError::Permanent("denied")));
+        assert_eq!(attempts.get(), 2);
+        assert_eq!(sleeper.slept(), vec![ms(1)]);
+    }
+
+    #[test]
+    fn elapsed_time_is_capped() {
+        // The third delay would end at 700ms, past the cap
+        let backoff = Backoff::Exponential {
+            base: ms(100),
+            max: ms(10_000),
+        };
+        let policy = RetryPolicy::new(backoff, 10).with_max_elapsed(ms(500));
+        let (result, slept) = run(&policy, 10);
+        assert_eq!(result,
+                   Err(RetryError::Exhausted {
+                       error: "down",
+                       attempts: 3,
+                   }));
+        assert_eq!(slept, vec![ms(100), ms(200)]);
+    }
+
+    #[test]
+    fn jitter_stays_within_half_and_all_of_the_delay() {
+        let backoff = Backoff::ExponentialJitter {
+            base: ms(100),
+            max: ms(1_000),
+        };
+        let mut drawn = vec![];
+        for _ in 0..50 {
+            let (_, slept) = run(&RetryPolicy::new(backoff, 6), 6);
+            for (i, delay) in slept.into_iter().enumerate() {
+                let ceiling = backoff.delay(i as u32 + 1);
+                assert!(delay >= ceiling / 2 && delay <= ceiling,
+                        "retry {} waited {:?}, not within half and all of {:?}",
+                        i + 1,
+                        delay,
+                        ceiling);
+                drawn.push(delay);
+            }
+        }
+        drawn.sort();
+        drawn.dedup();
+        assert!(drawn.len() > 100, "jitter drew only {} delays", drawn.len());
+    }
+
+    #[test]
+    fn errors_are_displayed_with_their_attempts() {
+        let err: RetryError<&str> = RetryError::Exhausted {
+            error: "down",
+            attempts: 3,
+        };
+        assert_eq!(err.to_string(), "down, after 3 attempts");
+        assert_eq!(RetryError::Permanent("denied").to_string(), "denied");
+    }
+}
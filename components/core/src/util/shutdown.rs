@@ -0,0 +1,195 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Stopping the background threads of a process together.
+//!
+//! A `Shutdown` is handed to each background thread, which sleeps with `Shutdown::sleep` so it
+//! wakes as soon as `shutdown` is called rather than at the end of its interval, and returns
+//! once it has. Threads started with `Shutdown::spawn` are counted, so whoever shuts them down
+//! can wait for them with `join`.
+
+use std::io;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Handle shared by the background threads to stop and whoever stops them. Clones share the
+/// same state.
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    stopping: bool,
+    running: usize,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Shutdown::default()
+    }
+
+    /// True once `shutdown` was called.
+    pub fn is_shutdown(&self) -> bool {
+        self.state().stopping
+    }
+
+    /// Tells every thread sharing this handle to stop, waking those which are sleeping.
+    pub fn shutdown(&self) {
+        self.state().stopping = true;
+        self.inner.changed.notify_all();
+    }
+
+    /// Sleeps for `interval`, returning early with `true` once `shutdown` is called.
+    pub fn sleep(&self, interval: Duration) -> bool {
+        let deadline = Instant::now() + interval;
+        let mut state = self.state();
+        loop {
+            if state.stopping {
+                return true;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            state = self.inner.changed.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+
+    /// Starts a thread named `name` running `f` with a clone of this handle, which `join` waits
+    /// for.
+    ///
+    /// # Errors
+    ///
+    /// * The thread could not be started
+    pub fn spawn<F>(&self, name: &str, f: F) -> io::Result<()>
+        where F: FnOnce(Shutdown) + Send + 'static
+    {
+        self.state().running += 1;
+        let shutdown = self.clone();
+        let spawned = thread::Builder::new().name(name.to_string()).spawn(move || {
+            let _running = Running(shutdown.clone());
+            f(shutdown)
+        });
+        match spawned {
+            Ok(handle) => {
+                self.state().handles.push(handle);
+                Ok(())
+            }
+            Err(e) => {
+                self.state().running -= 1;
+                Err(e)
+            }
+        }
+    }
+
+    /// Waits up to `timeout` for the threads started with `spawn` to return, and returns whether
+    /// they all did. Threads still running are left to finish on their own. Doesn't call
+    /// `shutdown`.
+    pub fn join(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state();
+        while state.running > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = self.inner.changed.wait_timeout(state, deadline - now).unwrap().0;
+        }
+        if state.running > 0 {
+            return false;
+        }
+        let handles: Vec<JoinHandle<()>> = state.handles.drain(..).collect();
+        drop(state);
+        for handle in handles {
+            // A thread which panicked has returned as far as stopping it is concerned
+            let _ = handle.join();
+        }
+        true
+    }
+
+    fn state(&self) -> MutexGuard<State> {
+        self.inner.state.lock().expect("Shutdown lock poisoned")
+    }
+}
+
+/// Counts a thread started with `Shutdown::spawn` as running until it returns or panics.
+struct Running(Shutdown);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        // The lock is never held while calling out, so it can't be poisoned by the thread's panic
+        if let Ok(mut state) = self.0.inner.state.lock() {
+            state.running -= 1;
+        }
+        self.0.inner.changed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    #[test]
+    fn sleep_wakes_on_shutdown() {
+        let shutdown = Shutdown::new();
+        let sleeper = shutdown.clone();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || tx.send(sleeper.sleep(Duration::from_secs(600))).unwrap());
+        thread::sleep(Duration::from_millis(10));
+        shutdown.shutdown();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(10)), Ok(true));
+    }
+
+    #[test]
+    fn sleep_runs_out_without_shutdown() {
+        assert!(!Shutdown::new().sleep(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn join_waits_for_spawned_threads() {
+        let shutdown = Shutdown::new();
+        for i in 0..4 {
+            shutdown.spawn(&format!("worker-{}", i), |shutdown| {
+                    while !shutdown.sleep(Duration::from_secs(600)) {}
+                })
+                .unwrap();
+        }
+        assert!(!shutdown.join(Duration::from_millis(10)));
+        let started = Instant::now();
+        shutdown.shutdown();
+        assert!(shutdown.join(Duration::from_secs(10)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn panicked_threads_are_joined() {
+        let shutdown = Shutdown::new();
+        shutdown.spawn("panics", |_| panic!("on purpose")).unwrap();
+        assert!(shutdown.join(Duration::from_secs(10)));
+    }
+}
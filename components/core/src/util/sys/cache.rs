@@ -0,0 +1,999 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A cached snapshot of the facts about this host.
+//!
+//! The snapshot is detected the first time it is asked for and again once it is older than the
+//! max age, or when `refresh` is called. Only one caller detects at a time; callers asking while
+//! a detection is running wait for it and share its result.
+//!
+//! Taking a snapshot discovers nothing by itself: each fact is discovered the first time it is
+//! asked of the snapshot and kept for its later callers, so rendering only the cheap facts, see
+//! `FactSelection::Core`, never waits on the network.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::net::IpAddr;
+use std::result;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use time;
+
+use env as henv;
+use error::{Error, Result};
+use util::redact;
+use super::{CpuInfo, DiskUsage, DnsConfig, Gateway, HostIps, Interface, IpCandidate,
+            IpPreference, IpSelection, IpSelector, LoadAvg, MemInfo, OsRelease, Virt, Uname,
+            boot_id, boot_time_at, cpu_info, data_path, default_gateway, disk_usage, dns_config,
+            entropy_available, hostname, interfaces, ip_selection, loadavg, machine_id, mem_info,
+            netns_id, os_release, qualify_hostname, reverse_lookup, select_ip, uname, uptime,
+            virtualization};
+
+/// Environment variable setting the max age of the snapshot in seconds
+pub const MAX_AGE_ENVVAR: &'static str = "HAB_SYS_MAX_AGE";
+
+/// Max age of the snapshot in seconds if `MAX_AGE_ENVVAR` isn't set
+const DEFAULT_MAX_AGE_SECS: u64 = 60;
+
+/// Seconds detection waits for the reverse lookup of the advertised address
+const PTR_LOOKUP_TIMEOUT_SECS: u64 = 1;
+
+lazy_static! {
+    static ref CACHE: Cache = Cache::new(Box::new(SysInfo::detect), max_age_from_env());
+}
+
+/// How long a group of facts takes to discover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FactCost {
+    /// Read from a file or asked of the kernel
+    Cheap,
+    /// Asked of the network, such as a DNS server or a cloud's metadata service, or of a
+    /// filesystem which may be remote or hung
+    Expensive,
+}
+
+/// The groups the facts rendered in the `[sys]` table are discovered in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FactGroup {
+    /// The advertised addresses and how they were chosen
+    Addresses,
+    Hostname,
+    /// Fully qualified domain name, which asks the resolver
+    Fqdn,
+    /// Machine and boot ids
+    HostIds,
+    /// Name the PTR record of the advertised address names
+    ReverseDns,
+    /// Addresses the host names of this host resolve to, which `SysInfo` doesn't hold
+    Resolution,
+    /// Default route and resolver configuration
+    Network,
+    Interfaces,
+    Cpu,
+    Memory,
+    /// Space and inodes of the filesystem holding Habitat's files
+    Disk,
+    /// Kernel, distribution, uptime and virtualization
+    Os,
+    Load,
+    Entropy,
+    /// Instance metadata of the cloud this host runs in, which `SysInfo` doesn't hold
+    Cloud,
+    /// Address this host is seen from on the internet, which `SysInfo` doesn't hold
+    PublicIp,
+    /// The supervisor's own process, which `SysInfo` doesn't hold
+    Process,
+    /// Limits of the supervisor's cgroup, which `SysInfo` doesn't hold
+    Limits,
+    /// The clock of this host, which `SysInfo` doesn't hold
+    Time,
+    /// Environment variables named by `ENV_FACTS_ENVVAR`, which `SysInfo` doesn't hold
+    Env,
+    /// Space of every real filesystem mounted, which `SysInfo` doesn't hold
+    Filesystems,
+    /// Hosts recently seen on the links of this one, which `SysInfo` doesn't hold
+    Neighbors,
+    /// Security modules confining processes, which `SysInfo` doesn't hold
+    Security,
+    /// Skew of the clock against a reference, which `SysInfo` doesn't hold
+    ClockSkew,
+}
+
+/// Every group of facts, in order.
+pub const FACT_GROUPS: &'static [FactGroup] = &[FactGroup::Addresses,
+                                                FactGroup::Hostname,
+                                                FactGroup::Fqdn,
+                                                FactGroup::HostIds,
+                                                FactGroup::ReverseDns,
+                                                FactGroup::Resolution,
+                                                FactGroup::Network,
+                                                FactGroup::Interfaces,
+                                                FactGroup::Cpu,
+                                                FactGroup::Memory,
+                                                FactGroup::Disk,
+                                                FactGroup::Os,
+                                                FactGroup::Load,
+                                                FactGroup::Entropy,
+                                                FactGroup::Cloud,
+                                                FactGroup::PublicIp,
+                                                FactGroup::Process,
+                                                FactGroup::Limits,
+                                                FactGroup::Time,
+                                                FactGroup::Env,
+                                                FactGroup::Filesystems,
+                                                FactGroup::Neighbors,
+                                                FactGroup::Security,
+                                                FactGroup::ClockSkew];
+
+impl FactGroup {
+    pub fn cost(&self) -> FactCost {
+        match *self {
+            FactGroup::Fqdn |
+            FactGroup::ReverseDns |
+            FactGroup::Resolution |
+            FactGroup::Disk |
+            FactGroup::Filesystems |
+            FactGroup::Cloud |
+            FactGroup::PublicIp |
+            FactGroup::ClockSkew => FactCost::Expensive,
+            _ => FactCost::Cheap,
+        }
+    }
+
+    /// Returns the name of the group, in snake case, which keys it where the groups are
+    /// rendered.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            FactGroup::Addresses => "addresses",
+            FactGroup::Hostname => "hostname",
+            FactGroup::Fqdn => "fqdn",
+            FactGroup::HostIds => "host_ids",
+            FactGroup::ReverseDns => "reverse_dns",
+            FactGroup::Resolution => "resolution",
+            FactGroup::Network => "network",
+            FactGroup::Interfaces => "interfaces",
+            FactGroup::Cpu => "cpu",
+            FactGroup::Memory => "memory",
+            FactGroup::Disk => "disk",
+            FactGroup::Os => "os",
+            FactGroup::Load => "load",
+            FactGroup::Entropy => "entropy",
+            FactGroup::Cloud => "cloud",
+            FactGroup::PublicIp => "public_ip",
+            FactGroup::Process => "process",
+            FactGroup::Limits => "limits",
+            FactGroup::Time => "time",
+            FactGroup::Env => "env",
+            FactGroup::Filesystems => "filesystems",
+            FactGroup::Neighbors => "neighbors",
+            FactGroup::Security => "security",
+            FactGroup::ClockSkew => "clock_skew",
+        }
+    }
+}
+
+/// Which groups of facts to discover and render.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FactSelection {
+    All,
+    /// The groups which are cheap to discover, see `FactCost`
+    Core,
+    Keys(BTreeSet<FactGroup>),
+}
+
+impl FactSelection {
+    pub fn includes(&self, group: FactGroup) -> bool {
+        match *self {
+            FactSelection::All => true,
+            FactSelection::Core => group.cost() == FactCost::Cheap,
+            FactSelection::Keys(ref groups) => groups.contains(&group),
+        }
+    }
+}
+
+/// Discovers the facts `SysInfo` holds, each of which it asks for at most once, the first time
+/// the fact is asked of it.
+pub trait FactSource: Send + Sync {
+    fn ip_selection(&self) -> IpSelection;
+    fn hostname(&self) -> Result<String>;
+    /// Returns the fully qualified domain name of the host named `hostname`.
+    fn fqdn(&self, hostname: &str) -> String;
+    fn machine_id(&self) -> Result<Option<String>>;
+    fn boot_id(&self) -> Result<Option<String>>;
+    /// Returns the name the PTR record of `ip` names.
+    fn reverse_lookup(&self, ip: IpAddr) -> Result<Option<String>>;
+    fn gateway(&self) -> Result<Option<Gateway>>;
+    fn dns_config(&self) -> Result<DnsConfig>;
+    fn netns_id(&self) -> Result<String>;
+    fn interfaces(&self) -> Result<Vec<Interface>>;
+    fn cpu_info(&self) -> Result<CpuInfo>;
+    fn mem_info(&self) -> Result<MemInfo>;
+    fn data_disk_usage(&self) -> Result<DiskUsage>;
+    fn uname(&self) -> Result<Uname>;
+    fn os_release(&self) -> Result<OsRelease>;
+    fn uptime(&self) -> Result<Duration>;
+    fn loadavg(&self) -> Result<LoadAvg>;
+    fn virtualization(&self) -> Result<Virt>;
+    fn entropy_available(&self) -> Result<u32>;
+}
+
+/// This host, whose facts are discovered by the functions of `sys`.
+pub struct HostFacts;
+
+impl FactSource for HostFacts {
+    fn ip_selection(&self) -> IpSelection {
+        ip_selection()
+    }
+
+    fn hostname(&self) -> Result<String> {
+        hostname()
+    }
+
+    fn fqdn(&self, hostname: &str) -> String {
+        qualify_hostname(hostname)
+    }
+
+    fn machine_id(&self) -> Result<Option<String>> {
+        machine_id()
+    }
+
+    fn boot_id(&self) -> Result<Option<String>> {
+        boot_id()
+    }
+
+    fn reverse_lookup(&self, ip: IpAddr) -> Result<Option<String>> {
+        reverse_lookup(ip, Duration::from_secs(PTR_LOOKUP_TIMEOUT_SECS))
+    }
+
+    fn gateway(&self) -> Result<Option<Gateway>> {
+        default_gateway()
+    }
+
+    fn dns_config(&self) -> Result<DnsConfig> {
+        dns_config()
+    }
+
+    fn netns_id(&self) -> Result<String> {
+        netns_id()
+    }
+
+    fn interfaces(&self) -> Result<Vec<Interface>> {
+        interfaces()
+    }
+
+    fn cpu_info(&self) -> Result<CpuInfo> {
+        cpu_info()
+    }
+
+    fn mem_info(&self) -> Result<MemInfo> {
+        mem_info()
+    }
+
+    fn data_disk_usage(&self) -> Result<DiskUsage> {
+        disk_usage(&data_path())
+    }
+
+    fn uname(&self) -> Result<Uname> {
+        uname()
+    }
+
+    fn os_release(&self) -> Result<OsRelease> {
+        os_release()
+    }
+
+    fn uptime(&self) -> Result<Duration> {
+        uptime()
+    }
+
+    fn loadavg(&self) -> Result<LoadAvg> {
+        loadavg()
+    }
+
+    fn virtualization(&self) -> Result<Virt> {
+        virtualization()
+    }
+
+    fn entropy_available(&self) -> Result<u32> {
+        entropy_available()
+    }
+}
+
+/// A fact discovered the first time it is asked for. A failure is kept as its string.
+struct Lazy<T> {
+    value: Mutex<Option<result::Result<T, String>>>,
+}
+
+impl<T: Clone> Lazy<T> {
+    fn new() -> Self {
+        Lazy { value: Mutex::new(None) }
+    }
+
+    fn get<F: FnOnce() -> Result<T>>(&self, discover: F) -> Result<T> {
+        let mut value = self.value.lock().expect("Sys info fact lock poisoned");
+        if value.is_none() {
+            *value = Some(discover().map_err(|e| e.to_string()));
+        }
+        match *value {
+            Some(ref value) => value.clone().map_err(Error::SysInfoFailed),
+            None => unreachable!(),
+        }
+    }
+
+    fn is_discovered(&self) -> bool {
+        self.value.lock().expect("Sys info fact lock poisoned").is_some()
+    }
+}
+
+/// Uptime as of the instant it was measured.
+#[derive(Clone)]
+struct Uptime {
+    uptime: Duration,
+    measured_at: Instant,
+    boot_time: time::Timespec,
+}
+
+/// The facts about this host, each discovered the first time it is asked for and kept for as
+/// long as the snapshot is. A fact which failed to be discovered keeps its failure, which its
+/// accessor returns as `Error::SysInfoFailed`.
+pub struct SysInfo {
+    source: Box<FactSource>,
+    ip_selection: Lazy<IpSelection>,
+    hostname: Lazy<String>,
+    fqdn: Lazy<String>,
+    machine_id: Lazy<Option<String>>,
+    boot_id: Lazy<Option<String>>,
+    ptr_hostname: Lazy<Option<String>>,
+    gateway: Lazy<Option<Gateway>>,
+    dns: Lazy<DnsConfig>,
+    netns: Lazy<String>,
+    interfaces: Lazy<Vec<Interface>>,
+    cpu: Lazy<CpuInfo>,
+    mem: Lazy<MemInfo>,
+    data_disk: Lazy<DiskUsage>,
+    uname: Lazy<Uname>,
+    os_release: Lazy<OsRelease>,
+    uptime: Lazy<Uptime>,
+    loadavg: Lazy<LoadAvg>,
+    virt: Lazy<Virt>,
+    entropy: Lazy<u32>,
+    detected_at: Instant,
+}
+
+impl fmt::Debug for SysInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SysInfo")
+            .field("discovered", &self.discovered())
+            .field("age", &self.age())
+            .finish()
+    }
+}
+
+impl SysInfo {
+    /// Returns a snapshot of this host, whose facts are discovered as they are asked for.
+    pub fn detect() -> SysInfo {
+        SysInfo::from_source(Box::new(HostFacts))
+    }
+
+    /// Returns a snapshot whose facts are discovered by `source` as they are asked for.
+    pub fn from_source(source: Box<FactSource>) -> SysInfo {
+        SysInfo {
+            source: source,
+            ip_selection: Lazy::new(),
+            hostname: Lazy::new(),
+            fqdn: Lazy::new(),
+            machine_id: Lazy::new(),
+            boot_id: Lazy::new(),
+            ptr_hostname: Lazy::new(),
+            gateway: Lazy::new(),
+            dns: Lazy::new(),
+            netns: Lazy::new(),
+            interfaces: Lazy::new(),
+            cpu: Lazy::new(),
+            mem: Lazy::new(),
+            data_disk: Lazy::new(),
+            uname: Lazy::new(),
+            os_release: Lazy::new(),
+            uptime: Lazy::new(),
+            loadavg: Lazy::new(),
+            virt: Lazy::new(),
+            entropy: Lazy::new(),
+            detected_at: Instant::now(),
+        }
+    }
+
+    /// Returns the groups at least one of whose facts has been discovered, in order.
+    pub fn discovered(&self) -> Vec<FactGroup> {
+        let groups = [(FactGroup::Addresses, self.ip_selection.is_discovered()),
+                      (FactGroup::Hostname, self.hostname.is_discovered()),
+                      (FactGroup::Fqdn, self.fqdn.is_discovered()),
+                      (FactGroup::HostIds,
+                       self.machine_id.is_discovered() || self.boot_id.is_discovered()),
+                      (FactGroup::ReverseDns, self.ptr_hostname.is_discovered()),
+                      (FactGroup::Network,
+                       self.gateway.is_discovered() || self.dns.is_discovered() ||
+                       self.netns.is_discovered()),
+                      (FactGroup::Interfaces, self.interfaces.is_discovered()),
+                      (FactGroup::Cpu, self.cpu.is_discovered()),
+                      (FactGroup::Memory, self.mem.is_discovered()),
+                      (FactGroup::Disk, self.data_disk.is_discovered()),
+                      (FactGroup::Os,
+                       self.uname.is_discovered() || self.os_release.is_discovered() ||
+                       self.uptime.is_discovered() ||
+                       self.virt.is_discovered()),
+                      (FactGroup::Load, self.loadavg.is_discovered()),
+                      (FactGroup::Entropy, self.entropy.is_discovered())];
+        groups.iter().filter(|&&(_, discovered)| discovered).map(|&(group, _)| group).collect()
+    }
+
+    /// Returns the address chosen by `LISTEN_IP_FROM_ENVVAR`, see `ip_for`.
+    pub fn ip(&self) -> Result<IpAddr> {
+        self.ip_for(&try!(IpSelector::from_env()))
+    }
+
+    /// Returns the address the given selector chooses, see `sys::ip_for`.
+    pub fn ip_for(&self, selector: &IpSelector) -> Result<IpAddr> {
+        match *selector {
+            IpSelector::Default => {
+                match try!(self.ips()).primary(IpPreference::from_env()) {
+                    Some(ip) => Ok(ip),
+                    None => Err(Error::NoOutboundAddr),
+                }
+            }
+            _ => select_ip(selector, &try!(self.interfaces())),
+        }
+    }
+
+    /// Returns the addresses other hosts can reach this host on, see `sys::ips`.
+    pub fn ips(&self) -> Result<HostIps> {
+        self.ip_selection().ips().map_err(|e| Error::SysInfoFailed(e.to_string()))
+    }
+
+    /// Returns how the addresses returned by `ips` were chosen, see `sys::ip_selection`.
+    pub fn ip_selection(&self) -> IpSelection {
+        match self.ip_selection.get(|| Ok(self.source.ip_selection())) {
+            Ok(selection) => selection,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Returns the addresses of the interfaces `ips` was chosen from, with their scores.
+    pub fn ip_candidates(&self) -> Vec<IpCandidate> {
+        self.ip_selection().candidates
+    }
+
+    pub fn hostname(&self) -> Result<String> {
+        self.hostname.get(|| self.source.hostname())
+    }
+
+    /// Returns the fully qualified domain name of this host, see `sys::fqdn`.
+    pub fn fqdn(&self) -> Result<String> {
+        self.fqdn.get(|| self.hostname().map(|hostname| self.source.fqdn(&hostname)))
+    }
+
+    /// Returns the default route of this host, see `sys::default_gateway`.
+    pub fn gateway(&self) -> Result<Option<Gateway>> {
+        self.gateway.get(|| self.source.gateway())
+    }
+
+    /// Returns the resolver configuration of this host, see `sys::dns_config`.
+    pub fn dns_config(&self) -> Result<DnsConfig> {
+        self.dns.get(|| self.source.dns_config())
+    }
+
+    /// Returns the network namespace the network facts were read in, see `sys::netns_id`.
+    pub fn netns_id(&self) -> Result<String> {
+        self.netns.get(|| self.source.netns_id())
+    }
+
+    pub fn interfaces(&self) -> Result<Vec<Interface>> {
+        self.interfaces.get(|| self.source.interfaces())
+    }
+
+    /// Returns the processors of this host, see `sys::cpu_info`.
+    pub fn cpu_info(&self) -> Result<CpuInfo> {
+        self.cpu.get(|| self.source.cpu_info())
+    }
+
+    /// Returns the memory of this host, see `sys::mem_info`.
+    pub fn mem_info(&self) -> Result<MemInfo> {
+        self.mem.get(|| self.source.mem_info())
+    }
+
+    /// Returns the space on the filesystem holding Habitat's files, see `sys::data_path`.
+    pub fn data_disk_usage(&self) -> Result<DiskUsage> {
+        self.data_disk.get(|| self.source.data_disk_usage())
+    }
+
+    pub fn uname(&self) -> Result<Uname> {
+        self.uname.get(|| self.source.uname())
+    }
+
+    /// Returns the operating system distribution of this host, see `sys::os_release`.
+    pub fn os_release(&self) -> Result<OsRelease> {
+        self.os_release.get(|| self.source.os_release())
+    }
+
+    /// Returns the time since this host booted, as of now rather than of its measurement.
+    pub fn uptime(&self) -> Result<Duration> {
+        self.measured_uptime().map(|uptime| uptime.uptime + uptime.measured_at.elapsed())
+    }
+
+    /// Returns the time this host booted, see `sys::boot_time`.
+    pub fn boot_time(&self) -> Result<time::Timespec> {
+        self.measured_uptime().map(|uptime| uptime.boot_time)
+    }
+
+    /// Returns the load average of this host, see `sys::loadavg`.
+    pub fn loadavg(&self) -> Result<LoadAvg> {
+        self.loadavg.get(|| self.source.loadavg())
+    }
+
+    /// Returns the container or virtual machine this host runs in, see `sys::virtualization`.
+    pub fn virtualization(&self) -> Result<Virt> {
+        self.virt.get(|| self.source.virtualization())
+    }
+
+    /// Returns the bits of entropy in the kernel's pool, see `sys::entropy_available`.
+    pub fn entropy_available(&self) -> Result<u32> {
+        self.entropy.get(|| self.source.entropy_available())
+    }
+
+    /// Returns the id this host was given when it was installed, see `sys::machine_id`.
+    pub fn machine_id(&self) -> Result<Option<String>> {
+        self.machine_id.get(|| self.source.machine_id())
+    }
+
+    /// Returns the id of the current boot of this host, see `sys::boot_id`.
+    pub fn boot_id(&self) -> Result<Option<String>> {
+        self.boot_id.get(|| self.source.boot_id())
+    }
+
+    /// Returns the name the PTR record of the advertised address names, see `sys::reverse_lookup`.
+    pub fn ptr_hostname(&self) -> Result<Option<String>> {
+        self.ptr_hostname.get(|| self.ip().and_then(|ip| self.source.reverse_lookup(ip)))
+    }
+
+    /// Returns the time since the snapshot was taken.
+    pub fn age(&self) -> Duration {
+        self.detected_at.elapsed()
+    }
+
+    fn measured_uptime(&self) -> Result<Uptime> {
+        self.uptime.get(|| {
+            let uptime = try!(self.source.uptime());
+            Ok(Uptime {
+                uptime: uptime,
+                measured_at: Instant::now(),
+                boot_time: boot_time_at(time::get_time(), uptime),
+            })
+        })
+    }
+}
+
+/// Returns the cached facts about this host, detecting them first if they have never been
+/// detected or are older than the max age.
+pub fn snapshot() -> Arc<SysInfo> {
+    CACHE.snapshot()
+}
+
+/// Detects the facts about this host again and returns them. A detection which started after
+/// this call, while it waited for another one to finish, is returned instead of running a third.
+pub fn refresh() -> Arc<SysInfo> {
+    CACHE.refresh()
+}
+
+/// Sets the age after which `snapshot` detects the facts again. A max age of zero detects them
+/// on every call.
+pub fn set_max_age(max_age: Duration) {
+    *CACHE.max_age.write().expect("Sys info max age lock poisoned") = max_age;
+}
+
+/// Replaces the cached facts with the given ones.
+pub fn store(info: SysInfo) -> Arc<SysInfo> {
+    CACHE.store(Arc::new(info))
+}
+
+fn max_age_from_env() -> Duration {
+    match henv::var(MAX_AGE_ENVVAR) {
+        Ok(val) => {
+            match val.parse() {
+                Ok(secs) => Duration::from_secs(secs),
+                Err(_) => {
+                    warn!("Ignoring {}={}, expected a number of seconds",
+                          MAX_AGE_ENVVAR,
+                          redact::redact_text(&val));
+                    Duration::from_secs(DEFAULT_MAX_AGE_SECS)
+                }
+            }
+        }
+        Err(_) => Duration::from_secs(DEFAULT_MAX_AGE_SECS),
+    }
+}
+
+struct Cache {
+    current: RwLock<Option<Arc<SysInfo>>>,
+    /// Held for the duration of a detection, so that only one runs at a time
+    detecting: Mutex<()>,
+    max_age: RwLock<Duration>,
+    detect: Box<Fn() -> SysInfo + Send + Sync>,
+}
+
+impl Cache {
+    fn new(detect: Box<Fn() -> SysInfo + Send + Sync>, max_age: Duration) -> Cache {
+        Cache {
+            current: RwLock::new(None),
+            detecting: Mutex::new(()),
+            max_age: RwLock::new(max_age),
+            detect: detect,
+        }
+    }
+
+    fn snapshot(&self) -> Arc<SysInfo> {
+        if let Some(info) = self.fresh() {
+            return info;
+        }
+        let _detecting = self.detecting.lock().expect("Sys info detection lock poisoned");
+        // Another caller may have detected the facts while this one waited for the lock
+        if let Some(info) = self.fresh() {
+            return info;
+        }
+        self.store_detection()
+    }
+
+    fn refresh(&self) -> Arc<SysInfo> {
+        let requested_at = Instant::now();
+        let _detecting = self.detecting.lock().expect("Sys info detection lock poisoned");
+        if let Some(info) = self.current() {
+            if info.detected_at >= requested_at {
+                return info;
+            }
+        }
+        self.store_detection()
+    }
+
+    fn current(&self) -> Option<Arc<SysInfo>> {
+        self.current.read().expect("Sys info cache lock poisoned").clone()
+    }
+
+    fn fresh(&self) -> Option<Arc<SysInfo>> {
+        let max_age = *self.max_age.read().expect("Sys info max age lock poisoned");
+        self.current().and_then(|info| if info.age() < max_age {
+            Some(info)
+        } else {
+            None
+        })
+    }
+
+    /// Detects the facts and replaces the cached ones. Callers must hold `detecting`.
+    fn store_detection(&self) -> Arc<SysInfo> {
+        self.store(Arc::new((self.detect)()))
+    }
+
+    fn store(&self, info: Arc<SysInfo>) -> Arc<SysInfo> {
+        *self.current.write().expect("Sys info cache lock poisoned") = Some(info.clone());
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::net::IpAddr;
+    use std::sync::{Arc, Mutex, mpsc};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use error::{Error, Result};
+    use super::*;
+    use super::{CACHE, Cache};
+    use super::super::{CpuInfo, DiskUsage, DnsConfig, ENTROPY_UNLIMITED, Gateway, Interface,
+                       IpAttempt, IpCandidate, IpOutcome, IpPreference, IpScope, IpSelection,
+                       IpSelector, LoadAvg, MemInfo, OsRelease, Uname, Virt, metrics_sample};
+
+    /// Discovers the facts of a database host, recording which it was asked for.
+    struct FakeFacts {
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl FakeFacts {
+        fn call(&self, fact: &'static str) {
+            self.calls.lock().unwrap().push(fact);
+        }
+    }
+
+    impl FactSource for FakeFacts {
+        fn ip_selection(&self) -> IpSelection {
+            self.call("ip_selection");
+            IpSelection {
+                preference: IpPreference::Ipv4First,
+                ipv4_attempts: vec![IpAttempt {
+                                        strategy: "udp-connect",
+                                        outcome: IpOutcome::Found("10.0.0.5".parse().unwrap()),
+                                    }],
+                ipv6_attempts: vec![IpAttempt {
+                                        strategy: "udp-connect ipv6",
+                                        outcome: IpOutcome::NotFound,
+                                    }],
+                candidates: vec![IpCandidate {
+                                     ip: "10.0.0.5".parse().unwrap(),
+                                     interface: "eth0".to_string(),
+                                     scope: IpScope::Global,
+                                     score: Some(3),
+                                     reason: "hardware address, private".to_string(),
+                                 }],
+            }
+        }
+
+        fn hostname(&self) -> Result<String> {
+            self.call("hostname");
+            Ok("db-1".to_string())
+        }
+
+        fn fqdn(&self, hostname: &str) -> String {
+            self.call("fqdn");
+            format!("{}.example.com", hostname)
+        }
+
+        fn machine_id(&self) -> Result<Option<String>> {
+            self.call("machine_id");
+            Ok(Some("4b3f0c2e8a514d3c9d2b6f1e7a0c5d94".to_string()))
+        }
+
+        fn boot_id(&self) -> Result<Option<String>> {
+            self.call("boot_id");
+            Ok(None)
+        }
+
+        fn reverse_lookup(&self, ip: IpAddr) -> Result<Option<String>> {
+            self.call("reverse_lookup");
+            assert_eq!(ip.to_string(), "10.0.0.5");
+            Ok(Some("db-1.example.com".to_string()))
+        }
+
+        fn gateway(&self) -> Result<Option<Gateway>> {
+            self.call("gateway");
+            Ok(None)
+        }
+
+        fn dns_config(&self) -> Result<DnsConfig> {
+            self.call("dns_config");
+            Ok(DnsConfig::default())
+        }
+
+        fn netns_id(&self) -> Result<String> {
+            self.call("netns_id");
+            Ok("4026531992".to_string())
+        }
+
+        fn interfaces(&self) -> Result<Vec<Interface>> {
+            self.call("interfaces");
+            Err(Error::SysInfoFailed("getifaddrs failed".to_string()))
+        }
+
+        fn cpu_info(&self) -> Result<CpuInfo> {
+            self.call("cpu_info");
+            Ok(CpuInfo {
+                logical: 4,
+                physical: Some(2),
+                model: None,
+            })
+        }
+
+        fn mem_info(&self) -> Result<MemInfo> {
+            self.call("mem_info");
+            Err(Error::SysInfoFailed("/proc/meminfo has no MemTotal".to_string()))
+        }
+
+        fn data_disk_usage(&self) -> Result<DiskUsage> {
+            self.call("data_disk_usage");
+            Err(Error::SysInfoFailed("statvfs failed".to_string()))
+        }
+
+        fn uname(&self) -> Result<Uname> {
+            self.call("uname");
+            Err(Error::SysInfoFailed("uname failed".to_string()))
+        }
+
+        fn os_release(&self) -> Result<OsRelease> {
+            self.call("os_release");
+            Ok(OsRelease {
+                id: "ubuntu".to_string(),
+                version_id: Some("16.04".to_string()),
+                pretty_name: None,
+            })
+        }
+
+        fn uptime(&self) -> Result<Duration> {
+            self.call("uptime");
+            Ok(Duration::from_secs(86400))
+        }
+
+        fn loadavg(&self) -> Result<LoadAvg> {
+            self.call("loadavg");
+            Err(Error::SysInfoFailed("Windows doesn't have a load average".to_string()))
+        }
+
+        fn virtualization(&self) -> Result<Virt> {
+            self.call("virtualization");
+            Ok(Virt::Docker)
+        }
+
+        fn entropy_available(&self) -> Result<u32> {
+            self.call("entropy_available");
+            Ok(ENTROPY_UNLIMITED)
+        }
+    }
+
+    /// Returns a snapshot of the facts of `FakeFacts` and the facts it was asked for.
+    fn recorded_info() -> (SysInfo, Arc<Mutex<Vec<&'static str>>>) {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let info = SysInfo::from_source(Box::new(FakeFacts { calls: calls.clone() }));
+        (info, calls)
+    }
+
+    fn fake_info() -> SysInfo {
+        recorded_info().0
+    }
+
+    /// Returns a cache whose detections take `delay_ms` and are counted by the returned counter.
+    fn counting_cache(max_age: Duration, delay_ms: u64) -> (Arc<Cache>, Arc<AtomicUsize>) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        let cache = Cache::new(Box::new(move || {
+                                   counter.fetch_add(1, Ordering::SeqCst);
+                                   thread::sleep(Duration::from_millis(delay_ms));
+                                   fake_info()
+                               }),
+                               max_age);
+        (Arc::new(cache), count)
+    }
+
+    fn same(a: &Arc<SysInfo>, b: &Arc<SysInfo>) -> bool {
+        &**a as *const SysInfo == &**b as *const SysInfo
+    }
+
+    #[test]
+    fn snapshot_is_cached() {
+        let (cache, count) = counting_cache(Duration::from_secs(60), 0);
+        let first = cache.snapshot();
+        let second = cache.snapshot();
+        assert!(same(&first, &second));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn stale_snapshot_is_detected_again() {
+        let (cache, count) = counting_cache(Duration::from_secs(0), 0);
+        cache.snapshot();
+        cache.snapshot();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn refresh_detects_again() {
+        let (cache, count) = counting_cache(Duration::from_secs(60), 0);
+        let first = cache.snapshot();
+        let refreshed = cache.refresh();
+        assert!(!same(&first, &refreshed));
+        assert!(same(&refreshed, &cache.snapshot()));
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn concurrent_snapshots_detect_once() {
+        let (cache, count) = counting_cache(Duration::from_secs(60), 50);
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                thread::spawn(move || cache.snapshot())
+            })
+            .collect();
+        let infos: Vec<Arc<SysInfo>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(infos.iter().all(|info| same(info, &infos[0])));
+    }
+
+    #[test]
+    fn failures_are_kept() {
+        let info = fake_info();
+        assert_eq!(info.hostname().unwrap(), "db-1");
+        assert_eq!(info.ip_for(&IpSelector::Default).unwrap().to_string(),
+                   "10.0.0.5");
+        match info.interfaces() {
+            Err(Error::SysInfoFailed(ref e)) => assert_eq!(e, "getifaddrs failed"),
+            other => panic!("expected a kept failure, got {:?}", other),
+        }
+        match info.ip_for(&IpSelector::Interface("eth0".to_string())) {
+            Err(Error::SysInfoFailed(_)) => (),
+            other => panic!("expected a kept failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ips_are_the_ones_the_selection_chose() {
+        let info = fake_info();
+        let ips = info.ips().unwrap();
+        assert_eq!(ips.ipv4.unwrap().to_string(), "10.0.0.5");
+        assert_eq!(ips.ipv6, None);
+        assert_eq!(info.ip_candidates()[0].interface, "eth0");
+        assert!(info.ip_selection().to_string().starts_with("Chose 10.0.0.5, preferring IPv4"));
+    }
+
+    #[test]
+    fn facts_are_discovered_once_when_first_asked_for() {
+        let (info, calls) = recorded_info();
+        assert!(calls.lock().unwrap().is_empty());
+        assert_eq!(info.fqdn().unwrap(), "db-1.example.com");
+        assert_eq!(info.hostname().unwrap(), "db-1");
+        assert!(info.interfaces().is_err());
+        assert!(info.interfaces().is_err());
+        assert_eq!(*calls.lock().unwrap(), vec!["hostname", "fqdn", "interfaces"]);
+        assert_eq!(info.discovered(),
+                   vec![FactGroup::Hostname, FactGroup::Fqdn, FactGroup::Interfaces]);
+    }
+
+    #[test]
+    fn the_reverse_lookup_is_of_the_advertised_address() {
+        let (info, calls) = recorded_info();
+        assert_eq!(info.ptr_hostname().unwrap(), Some("db-1.example.com".to_string()));
+        assert_eq!(*calls.lock().unwrap(), vec!["ip_selection", "reverse_lookup"]);
+    }
+
+    #[test]
+    fn uptime_and_boot_time_are_measured_together() {
+        let (info, calls) = recorded_info();
+        assert!(info.uptime().unwrap() >= Duration::from_secs(86400));
+        info.boot_time().unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec!["uptime"]);
+        assert_eq!(info.discovered(), vec![FactGroup::Os]);
+    }
+
+    #[test]
+    fn core_selects_the_cheap_groups() {
+        for group in FACT_GROUPS {
+            assert!(FactSelection::All.includes(*group));
+            assert_eq!(FactSelection::Core.includes(*group),
+                       group.cost() == FactCost::Cheap);
+        }
+        assert!(!FactSelection::Core.includes(FactGroup::Fqdn));
+        assert!(!FactSelection::Core.includes(FactGroup::Disk));
+        assert!(FactSelection::Core.includes(FactGroup::Addresses));
+        let keys: BTreeSet<FactGroup> = vec![FactGroup::Cpu].into_iter().collect();
+        assert!(FactSelection::Keys(keys.clone()).includes(FactGroup::Cpu));
+        assert!(!FactSelection::Keys(keys).includes(FactGroup::Memory));
+    }
+
+    #[test]
+    fn group_names_are_unique() {
+        let names: BTreeSet<&str> = FACT_GROUPS.iter().map(|group| group.name()).collect();
+        assert_eq!(names.len(), FACT_GROUPS.len());
+        assert_eq!(FactGroup::ReverseDns.name(), "reverse_dns");
+    }
+
+    #[test]
+    fn metrics_are_sampled_while_the_snapshot_is_locked() {
+        // Sampling while a detection runs and the snapshot is being replaced shows it takes
+        // neither lock, so it works as well before the first snapshot is taken
+        let detecting = CACHE.detecting.lock().unwrap();
+        let current = CACHE.current.write().unwrap();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || tx.send(metrics_sample().is_ok()).unwrap());
+        let sampled = rx.recv_timeout(Duration::from_secs(5));
+        drop(current);
+        drop(detecting);
+        assert_eq!(sampled, Ok(true));
+    }
+}
@@ -0,0 +1,551 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Facts about the cloud instance this host is, from the metadata service of its provider.
+//!
+//! Every provider serves its metadata on the link-local address 169.254.169.254, which nothing
+//! answers on elsewhere, so probing a host which isn't a cloud instance waits until the timeout.
+//! The probe is therefore only run when asked for, and its result is kept for the life of the
+//! process.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::result;
+use std::sync::Mutex;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rustc_serialize::json::Json;
+
+use env as henv;
+use error::{Error, Result};
+use util::redact;
+use util::retry::{self, Backoff, RetryPolicy, Retryable};
+
+/// Environment variable enabling the probe of the metadata service, set to its timeout in
+/// milliseconds
+pub const CLOUD_METADATA_ENVVAR: &'static str = "HAB_CLOUD_METADATA";
+
+/// Address every supported provider serves its metadata on
+const METADATA_ADDR: &'static str = "169.254.169.254:80";
+
+lazy_static! {
+    static ref CLOUD: Mutex<Option<Option<CloudInfo>>> = Mutex::new(None);
+}
+
+/// The cloud provider running this host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloudProvider {
+    Azure,
+    Ec2,
+    Gce,
+}
+
+impl fmt::Display for CloudProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CloudProvider::Azure => write!(f, "azure"),
+            CloudProvider::Ec2 => write!(f, "ec2"),
+            CloudProvider::Gce => write!(f, "gce"),
+        }
+    }
+}
+
+/// The cloud instance this host is. Facts the provider doesn't serve, such as the public address
+/// of an instance without one, are `None`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CloudInfo {
+    pub provider: CloudProvider,
+    pub instance_id: Option<String>,
+    /// Availability zone, or the region on Azure
+    pub zone: Option<String>,
+    /// Address of the instance within its network
+    pub private_ip: Option<IpAddr>,
+    /// Address the instance is reached on from outside its network
+    pub public_ip: Option<IpAddr>,
+}
+
+/// Returns the timeout set by `CLOUD_METADATA_ENVVAR`, or `None` if the probe isn't enabled.
+pub fn cloud_metadata_timeout() -> Option<Duration> {
+    match henv::var(CLOUD_METADATA_ENVVAR) {
+        Ok(val) => {
+            match val.parse() {
+                Ok(millis) => Some(Duration::from_millis(millis)),
+                Err(_) => {
+                    warn!("Ignoring {}={}, expected a number of milliseconds",
+                          CLOUD_METADATA_ENVVAR,
+                          redact::redact_text(&val));
+                    None
+                }
+            }
+        }
+        Err(_) => None,
+    }
+}
+
+/// Returns the cloud instance this host is, or `None` if no metadata service answered within
+/// `timeout`. The timeout covers the whole probe, which asks Google Compute Engine, then Azure,
+/// then EC2. The first answer is kept, so only the first call waits.
+///
+/// # Errors
+///
+/// * A thread to connect to the metadata service on could not be started
+pub fn cloud_metadata(timeout: Duration) -> Result<Option<CloudInfo>> {
+    let mut cached = CLOUD.lock().expect("Cloud metadata lock poisoned");
+    if let Some(ref info) = *cached {
+        return Ok(info.clone());
+    }
+    let addr = METADATA_ADDR.parse().expect("Metadata address is a socket address");
+    let info = try!(probe(&addr, Instant::now() + timeout));
+    match info {
+        Some(ref info) => {
+            debug!("Found cloud instance {}",
+                   redact::redact_text(&format!("{:?}", info)))
+        }
+        None => debug!("No cloud metadata service answered within {:?}", timeout),
+    }
+    *cached = Some(info.clone());
+    Ok(info)
+}
+
+fn probe(addr: &SocketAddr, deadline: Instant) -> Result<Option<CloudInfo>> {
+    if let Some(info) = try!(probe_gce(addr, deadline)) {
+        return Ok(Some(info));
+    }
+    if let Some(info) = try!(probe_azure(addr, deadline)) {
+        return Ok(Some(info));
+    }
+    probe_ec2(addr, deadline)
+}
+
+fn probe_gce(addr: &SocketAddr, deadline: Instant) -> Result<Option<CloudInfo>> {
+    let headers = [("Metadata-Flavor", "Google")];
+    let fetch = |path: &str| -> Result<Option<String>> {
+        let url = format!("/computeMetadata/v1/instance/{}", path);
+        let response = try!(get(addr, "metadata.google.internal", &url, &headers, deadline));
+        // Only Google answers with its flavor, anything else serving the path isn't GCE
+        Ok(response.and_then(|r| if r.header("Metadata-Flavor") == Some("Google") {
+            r.text()
+        } else {
+            None
+        }))
+    };
+    let instance_id = match try!(fetch("id")) {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    Ok(Some(CloudInfo {
+        provider: CloudProvider::Gce,
+        instance_id: Some(instance_id),
+        zone: try!(fetch("zone")).map(|zone| parse_gce_zone(&zone)),
+        private_ip: try!(fetch("network-interfaces/0/ip")).and_then(|ip| ip.parse().ok()),
+        public_ip: try!(fetch("network-interfaces/0/access-configs/0/external-ip"))
+            .and_then(|ip| ip.parse().ok()),
+    }))
+}
+
+fn probe_azure(addr: &SocketAddr, deadline: Instant) -> Result<Option<CloudInfo>> {
+    let response = try!(get(addr,
+                            "169.254.169.254",
+                            "/metadata/instance?api-version=2017-04-02",
+                            &[("Metadata", "true")],
+                            deadline));
+    Ok(response.and_then(|r| r.text()).and_then(|body| parse_azure_instance(&body)))
+}
+
+fn probe_ec2(addr: &SocketAddr, deadline: Instant) -> Result<Option<CloudInfo>> {
+    let fetch = |path: &str| -> Result<Option<String>> {
+        let url = format!("/latest/meta-data/{}", path);
+        Ok(try!(get(addr, "169.254.169.254", &url, &[], deadline)).and_then(|r| r.text()))
+    };
+    let instance_id = match try!(fetch("instance-id")) {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    Ok(Some(CloudInfo {
+        provider: CloudProvider::Ec2,
+        instance_id: Some(instance_id),
+        zone: try!(fetch("placement/availability-zone")),
+        private_ip: try!(fetch("local-ipv4")).and_then(|ip| ip.parse().ok()),
+        public_ip: try!(fetch("public-ipv4")).and_then(|ip| ip.parse().ok()),
+    }))
+}
+
+/// Returns the zone of a GCE instance, which the metadata service qualifies with its project,
+/// as in `projects/123456789/zones/us-central1-a`.
+fn parse_gce_zone(zone: &str) -> String {
+    zone.rsplit('/').next().unwrap_or(zone).to_string()
+}
+
+/// Returns the instance described by the metadata Azure's instance metadata service serves as
+/// JSON, or `None` if it isn't such a description.
+fn parse_azure_instance(body: &str) -> Option<CloudInfo> {
+    let json = match Json::from_str(body) {
+        Ok(json) => json,
+        Err(_) => return None,
+    };
+    let string = |path: &[&str]| -> Option<String> {
+        json.find_path(path)
+            .and_then(|value| value.as_string())
+            .map(|value| value.to_string())
+            .and_then(|value| if value.is_empty() { None } else { Some(value) })
+    };
+    let instance_id = match string(&["compute", "vmId"]) {
+        Some(id) => id,
+        None => return None,
+    };
+    let address = json.find_path(&["network", "interface"])
+        .and_then(|interfaces| interfaces.as_array())
+        .and_then(|interfaces| interfaces.first())
+        .and_then(|interface| interface.find_path(&["ipv4", "ipAddress"]))
+        .and_then(|addresses| addresses.as_array())
+        .and_then(|addresses| addresses.first());
+    let ip = |key: &str| -> Option<IpAddr> {
+        address.and_then(|address| address.find(key))
+            .and_then(|ip| ip.as_string())
+            .and_then(|ip| ip.parse().ok())
+    };
+    Some(CloudInfo {
+        provider: CloudProvider::Azure,
+        instance_id: Some(instance_id),
+        zone: string(&["compute", "zone"]).or(string(&["compute", "location"])),
+        private_ip: ip("privateIpAddress"),
+        public_ip: ip("publicIpAddress"),
+    })
+}
+
+/// An answer of a metadata service.
+#[derive(Debug)]
+struct Response {
+    status: u32,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Response {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|&&(ref n, _)| n.to_lowercase() == name.to_lowercase())
+            .map(|&(_, ref value)| value.as_str())
+    }
+
+    /// Returns the trimmed body of a successful answer, or `None` if it failed or is empty.
+    fn text(&self) -> Option<String> {
+        let body = self.body.trim();
+        if self.status == 200 && !body.is_empty() {
+            Some(body.to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// How a GET of the metadata service failed.
+enum GetFailure {
+    /// A thread to connect on could not be started
+    Thread(Error),
+    /// The connection was refused, as it is by a service which is still starting
+    Refused,
+    /// The service answered with a server error, as it does when throttling
+    Unavailable(Response),
+    /// Nothing which could be read was answered by the deadline
+    Unanswered,
+}
+
+/// Returns how a GET refused or failed by the service is run again. Retries stop at the
+/// deadline, so a host which refuses connections on the metadata address only waits the delays
+/// on top of its probe.
+fn get_retry_policy(remaining: Duration) -> RetryPolicy {
+    let backoff = Backoff::ExponentialJitter {
+        base: Duration::from_millis(50),
+        max: Duration::from_millis(200),
+    };
+    RetryPolicy::new(backoff, 2).with_max_elapsed(remaining)
+}
+
+/// Sends an HTTP/1.0 GET of `path` to `addr` and returns the answer, or `None` if none could be
+/// read by `deadline`. A refused connection or a server error is sent again, see
+/// `get_retry_policy`.
+fn get(addr: &SocketAddr,
+       host: &str,
+       path: &str,
+       headers: &[(&str, &str)],
+       deadline: Instant)
+       -> Result<Option<Response>> {
+    let policy = match remaining(deadline) {
+        Some(remaining) => get_retry_policy(remaining),
+        None => return Ok(None),
+    };
+    let classify = |failure: &GetFailure| match *failure {
+        GetFailure::Refused |
+        GetFailure::Unavailable(_) => Retryable::Transient,
+        GetFailure::Thread(_) |
+        GetFailure::Unanswered => Retryable::Permanent,
+    };
+    match retry::retry(&policy,
+                       classify,
+                       |_| get_once(addr, host, path, headers, deadline)) {
+        Ok(response) => Ok(Some(response)),
+        Err(failure) => {
+            match failure.into_inner() {
+                GetFailure::Thread(err) => Err(err),
+                GetFailure::Unavailable(response) => Ok(Some(response)),
+                GetFailure::Refused |
+                GetFailure::Unanswered => Ok(None),
+            }
+        }
+    }
+}
+
+/// Sends one GET for `get`. Connecting has no timeout of its own, so it runs on a thread which
+/// is left to finish on its own if the deadline passes first.
+fn get_once(addr: &SocketAddr,
+            host: &str,
+            path: &str,
+            headers: &[(&str, &str)],
+            deadline: Instant)
+            -> result::Result<Response, GetFailure> {
+    let remaining = match remaining(deadline) {
+        Some(remaining) => remaining,
+        None => return Err(GetFailure::Unanswered),
+    };
+    let (tx, rx) = mpsc::channel();
+    let connect_addr = *addr;
+    try!(thread::Builder::new()
+        .name("cloud-metadata".to_string())
+        .spawn(move || {
+            let _ = tx.send(TcpStream::connect(connect_addr));
+        })
+        .map_err(|err| GetFailure::Thread(Error::from(err))));
+    let mut stream = match rx.recv_timeout(remaining) {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(ref err)) if err.kind() == io::ErrorKind::ConnectionRefused => {
+            return Err(GetFailure::Refused)
+        }
+        Ok(Err(_)) |
+        Err(RecvTimeoutError::Timeout) |
+        Err(RecvTimeoutError::Disconnected) => return Err(GetFailure::Unanswered),
+    };
+    let remaining = match remaining(deadline) {
+        Some(remaining) => remaining,
+        None => return Err(GetFailure::Unanswered),
+    };
+    if stream.set_read_timeout(Some(remaining)).is_err() ||
+       stream.set_write_timeout(Some(remaining)).is_err() {
+        return Err(GetFailure::Unanswered);
+    }
+    let mut request = format!("GET {} HTTP/1.0\r\nHost: {}\r\n", path, host);
+    for &(name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("Connection: close\r\n\r\n");
+    if stream.write_all(request.as_bytes()).is_err() {
+        return Err(GetFailure::Unanswered);
+    }
+    let mut response = String::new();
+    if stream.read_to_string(&mut response).is_err() {
+        return Err(GetFailure::Unanswered);
+    }
+    match parse_response(&response) {
+        Some(response) => {
+            if response.status >= 500 {
+                Err(GetFailure::Unavailable(response))
+            } else {
+                Ok(response)
+            }
+        }
+        None => Err(GetFailure::Unanswered),
+    }
+}
+
+/// Returns the time left until `deadline`, or `None` if it has passed.
+fn remaining(deadline: Instant) -> Option<Duration> {
+    let now = Instant::now();
+    if now >= deadline {
+        None
+    } else {
+        Some(deadline - now)
+    }
+}
+
+fn parse_response(response: &str) -> Option<Response> {
+    let (head, body) = match response.find("\r\n\r\n") {
+        Some(pos) => (&response[..pos], &response[pos + 4..]),
+        None => (response, ""),
+    };
+    let mut lines = head.lines();
+    let status = match lines.next().and_then(|line| line.split_whitespace().nth(1)) {
+        Some(status) => {
+            match status.parse() {
+                Ok(status) => status,
+                Err(_) => return None,
+            }
+        }
+        None => return None,
+    };
+    let headers = lines.filter_map(|line| {
+            line.find(':')
+                .map(|pos| (line[..pos].trim().to_string(), line[pos + 1..].trim().to_string()))
+        })
+        .collect();
+    Some(Response {
+        status: status,
+        headers: headers,
+        body: body.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use super::{get, parse_azure_instance, parse_gce_zone, parse_response, probe};
+
+    /// Starts a metadata service on a local port which answers each of the given number of
+    /// connections with the first answer whose path prefix the request starts with, or a 404.
+    fn serve(answers: Vec<(&'static str, &'static str)>, connections: usize) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.unwrap();
+                let mut request = [0; 4096];
+                let len = stream.read(&mut request).unwrap();
+                let request = String::from_utf8_lossy(&request[..len]).into_owned();
+                let answer = answers.iter()
+                    .find(|&&(prefix, _)| request.starts_with(prefix))
+                    .map_or("HTTP/1.0 404 Not Found\r\n\r\n", |&(_, answer)| answer);
+                stream.write_all(answer.as_bytes()).unwrap();
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn provider_is_displayed() {
+        assert_eq!(CloudProvider::Ec2.to_string(), "ec2");
+        assert_eq!(CloudProvider::Gce.to_string(), "gce");
+    }
+
+    #[test]
+    fn response_is_parsed() {
+        let response = parse_response("HTTP/1.0 200 OK\r\nMetadata-Flavor: Google\r\n\r\n42\n")
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("metadata-flavor"), Some("Google"));
+        assert_eq!(response.text(), Some("42".to_string()));
+        let response = parse_response("HTTP/1.0 404 Not Found\r\n\r\nnot found").unwrap();
+        assert_eq!(response.text(), None);
+        assert!(parse_response("garbage").is_none());
+    }
+
+    #[test]
+    fn gce_zone_is_unqualified() {
+        assert_eq!(parse_gce_zone("projects/123456789/zones/us-central1-a"),
+                   "us-central1-a");
+        assert_eq!(parse_gce_zone("us-central1-a"), "us-central1-a");
+    }
+
+    #[test]
+    fn azure_instance_is_parsed() {
+        let info = parse_azure_instance(include_str!("../../../tests/fixtures/azure-imds.json"))
+            .unwrap();
+        assert_eq!(info.provider, CloudProvider::Azure);
+        assert_eq!(info.instance_id,
+                   Some("13f56399-bd52-4150-9748-7190aae1ff21".to_string()));
+        assert_eq!(info.zone, Some("westus".to_string()));
+        assert_eq!(info.private_ip, Some("10.1.0.4".parse().unwrap()));
+        assert_eq!(info.public_ip, Some("40.112.0.5".parse().unwrap()));
+        assert!(parse_azure_instance("<html>not found</html>").is_none());
+        assert!(parse_azure_instance("{\"compute\": {}}").is_none());
+    }
+
+    #[test]
+    fn ec2_is_probed() {
+        let addr = serve(vec![("GET /latest/meta-data/instance-id ",
+                               "HTTP/1.0 200 OK\r\n\r\ni-0b22a22eec53b9321"),
+                              ("GET /latest/meta-data/placement/availability-zone ",
+                               "HTTP/1.0 200 OK\r\n\r\nus-east-1a"),
+                              ("GET /latest/meta-data/local-ipv4 ",
+                               "HTTP/1.0 200 OK\r\n\r\n172.31.20.7")],
+                         6);
+        let info = probe(&addr, Instant::now() + Duration::from_secs(10)).unwrap().unwrap();
+        assert_eq!(info,
+                   CloudInfo {
+                       provider: CloudProvider::Ec2,
+                       instance_id: Some("i-0b22a22eec53b9321".to_string()),
+                       zone: Some("us-east-1a".to_string()),
+                       private_ip: Some("172.31.20.7".parse().unwrap()),
+                       public_ip: None,
+                   });
+    }
+
+    #[test]
+    fn gce_requires_its_flavor() {
+        let addr = serve(vec![("GET /computeMetadata/v1/instance/id ",
+                               "HTTP/1.0 200 OK\r\n\r\n4520031799277581759")],
+                         3);
+        assert_eq!(probe(&addr, Instant::now() + Duration::from_secs(10)).unwrap(), None);
+    }
+
+    #[test]
+    fn silent_service_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let started = Instant::now();
+        let response = get(&addr, "localhost", "/", &[], started + Duration::from_millis(200))
+            .unwrap();
+        assert!(response.is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        drop(listener);
+    }
+
+    #[test]
+    fn unavailable_service_is_asked_again() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let answers = ["HTTP/1.0 503 Service Unavailable\r\n\r\n",
+                           "HTTP/1.0 200 OK\r\n\r\ni-0b22a22eec53b9321"];
+            for (stream, answer) in listener.incoming().zip(answers.iter()) {
+                let mut stream = stream.unwrap();
+                let mut request = [0; 4096];
+                stream.read(&mut request).unwrap();
+                stream.write_all(answer.as_bytes()).unwrap();
+            }
+        });
+        let response = get(&addr, "localhost", "/", &[], Instant::now() + Duration::from_secs(10))
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.text(), Some("i-0b22a22eec53b9321".to_string()));
+    }
+
+    #[test]
+    fn refused_connections_are_given_up_on() {
+        // Nothing listens on the port once the listener holding it is gone
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let started = Instant::now();
+        let response = get(&addr, "localhost", "/", &[], started + Duration::from_secs(10))
+            .unwrap();
+        assert!(response.is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,870 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Whether this host reaches the services it depends on, such as the depot, by resolving their
+//! names and connecting to them.
+//!
+//! Each target is checked on a thread of its own, so a target which doesn't answer only holds
+//! the check up until its deadline. A target expected to speak TLS is sent the hello a client
+//! opens with, and passes if it answers with a TLS record; its certificate isn't checked, so a
+//! pass only means a TLS server listens, not that it is the one expected.
+//!
+//! When asked to, each target reached is also probed for the largest packets which reach it
+//! unfragmented, see `path_mtu_probe`, which finds the routes through overlay networks and
+//! tunnels that silently drop packets the size of the interface MTU.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::result;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use extern_url::Url;
+#[cfg(unix)]
+use libc;
+use sodiumoxide::randombytes::randombytes;
+use time;
+use toml;
+
+use env as henv;
+use error::{Error, Result};
+use util::render::{self, Render};
+use super::{interfaces, rfc3339_utc};
+#[cfg(target_os = "linux")]
+use super::linux::path_mtu_probe as platform_path_mtu_probe;
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+          target_os = "netbsd", target_os = "openbsd"))]
+use super::macos::path_mtu_probe as platform_path_mtu_probe;
+#[cfg(windows)]
+use super::windows::path_mtu_probe as platform_path_mtu_probe;
+
+/// Environment variable whose presence has `connectivity_check` probe each target it reaches
+/// for the largest packets which reach it, see `path_mtu_probe`
+pub const PATH_MTU_ENVVAR: &'static str = "HAB_CONN_PATH_MTU";
+
+/// Sizes in bytes of the whole IP packets `path_mtu_probe` sends, smallest first: the least any
+/// IPv4 host takes, the least an IPv6 link carries, sizes common to tunnels and overlay
+/// networks, Ethernet's, and jumbo frames
+pub const PATH_MTU_BUCKETS: &'static [u32] = &[576, 1280, 1400, 1450, 1480, 1500, 9000];
+
+/// MTU probed for when the MTU of the interface a target is reached through isn't known
+pub const ETHERNET_MTU: u32 = 1500;
+
+/// Cipher suites offered by the hello sent to targets expected to speak TLS, which any server
+/// of the last few years accepts at least one of
+const TLS_CIPHER_SUITES: &'static [u16] = &[0xc02f, 0xc02b, 0xc030, 0xc02c, 0x009c, 0x002f];
+
+/// A service to check the host reaches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnTarget {
+    pub host: String,
+    pub port: u16,
+    /// Whether the service is expected to speak TLS once connected to
+    pub tls: bool,
+}
+
+impl ConnTarget {
+    pub fn new(host: &str, port: u16, tls: bool) -> Self {
+        ConnTarget {
+            host: host.to_string(),
+            port: port,
+            tls: tls,
+        }
+    }
+
+    /// Returns the target serving a URL, on the port of its scheme unless it names one, which
+    /// is expected to speak TLS if the scheme is `https`.
+    ///
+    /// # Errors
+    ///
+    /// * The URL can't be parsed, or has no host or port, such as a `file` URL
+    pub fn from_url(url: &str) -> Result<Self> {
+        let parsed = match Url::parse(url) {
+            Ok(parsed) => parsed,
+            Err(e) => return Err(Error::InvalidUrl(format!("{} ({})", url, e))),
+        };
+        match (parsed.host_str(), parsed.port_or_known_default()) {
+            (Some(host), Some(port)) => {
+                // Hosts which are IPv6 addresses are bracketed in URLs, but aren't resolved so
+                let host = host.trim_left_matches('[').trim_right_matches(']');
+                Ok(ConnTarget::new(host, port, parsed.scheme() == "https"))
+            }
+            _ => Err(Error::InvalidUrl(format!("{} has no host and port to connect to", url))),
+        }
+    }
+}
+
+impl fmt::Display for ConnTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.host.contains(':') {
+            try!(write!(f, "[{}]:{}", self.host, self.port));
+        } else {
+            try!(write!(f, "{}:{}", self.host, self.port));
+        }
+        if self.tls {
+            try!(write!(f, " (TLS)"));
+        }
+        Ok(())
+    }
+}
+
+/// Why a target wasn't reached.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnError {
+    /// The host name of the target didn't resolve
+    Resolve(String),
+    /// The host name of the target resolved, but to no addresses
+    NoAddresses,
+    /// Nothing listens on the port of the target
+    Refused,
+    /// The target didn't answer in time
+    TimedOut,
+    /// There is no route to the target or its network
+    Unreachable,
+    /// The target was expected to speak TLS, but answered with something else
+    NotTls,
+    /// The check had not finished when its deadline passed
+    DeadlineExceeded,
+    /// Connecting failed some other way
+    Other(String),
+}
+
+impl fmt::Display for ConnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConnError::Resolve(ref e) => write!(f, "name not resolved ({})", e),
+            ConnError::NoAddresses => write!(f, "name resolved to no addresses"),
+            ConnError::Refused => write!(f, "connection refused"),
+            ConnError::TimedOut => write!(f, "timed out"),
+            ConnError::Unreachable => write!(f, "unreachable"),
+            ConnError::NotTls => write!(f, "answered without TLS"),
+            ConnError::DeadlineExceeded => write!(f, "not finished before the deadline"),
+            ConnError::Other(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// How a check of a target went, with how long each step took.
+#[derive(Clone, Debug)]
+pub struct ConnResult {
+    pub target: ConnTarget,
+    /// Address connected to, the first address of the target which accepted the connection
+    pub addr: Option<SocketAddr>,
+    /// Time the host name of the target took to resolve, if it did
+    pub resolve_time: Option<Duration>,
+    /// Time the connection to `addr` took to be accepted, if it was
+    pub connect_time: Option<Duration>,
+    /// Why the target wasn't reached, or `None` if it was
+    pub error: Option<ConnError>,
+    /// Largest packets which reach the target, if it was reached and `PATH_MTU_ENVVAR` is set
+    pub path_mtu: Option<PathMtuReport>,
+}
+
+impl ConnResult {
+    fn failed(target: ConnTarget, error: ConnError) -> Self {
+        ConnResult {
+            target: target,
+            addr: None,
+            resolve_time: None,
+            connect_time: None,
+            error: Some(error),
+            path_mtu: None,
+        }
+    }
+
+    /// Returns whether the target was reached.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl fmt::Display for ConnResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut steps = vec![];
+        if let Some(resolve_time) = self.resolve_time {
+            steps.push(format!("resolved in {}ms", millis(resolve_time)));
+        }
+        if let (Some(addr), Some(connect_time)) = (self.addr, self.connect_time) {
+            steps.push(format!("connected to {} in {}ms", addr, millis(connect_time)));
+        }
+        match self.error {
+            Some(ref e) => try!(write!(f, "{}: failed, {}", self.target, e)),
+            None => try!(write!(f, "{}: ok", self.target)),
+        }
+        if !steps.is_empty() {
+            try!(write!(f, " ({})", steps.join(", ")));
+        }
+        if let Some(ref path_mtu) = self.path_mtu {
+            try!(write!(f, ", {}", path_mtu));
+        }
+        Ok(())
+    }
+}
+
+/// What `path_mtu_probe` found out about the largest packets which reach a target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathMtuReport {
+    /// The target was probed with packets of up to `ceiling` bytes, the MTU of the interface
+    /// the route to it leaves through, or less if the target asked for smaller segments.
+    /// `largest` is the largest size of `PATH_MTU_BUCKETS` or the ceiling whose packet reached
+    /// the target, `None` if none did. `blackhole` is set if a packet vanished, rather than the
+    /// route answering that it was too large.
+    Probed {
+        largest: Option<u32>,
+        ceiling: u32,
+        interface_mtu: Option<u32>,
+        blackhole: bool,
+    },
+    /// The probe can't be sent, for the given reason
+    NotSupported(String),
+}
+
+impl PathMtuReport {
+    /// Returns whether packets as large as the interface sends don't reach the target.
+    pub fn is_below_interface_mtu(&self) -> bool {
+        match *self {
+            PathMtuReport::Probed { largest, ceiling, .. } => {
+                largest.map_or(true, |largest| largest < ceiling)
+            }
+            PathMtuReport::NotSupported(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for PathMtuReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PathMtuReport::Probed { largest: Some(largest), ceiling, blackhole, .. } => {
+                try!(write!(f, "packets of up to {} bytes reach it", largest));
+                if largest < ceiling {
+                    try!(write!(f, ", below the MTU of {}", ceiling));
+                    if blackhole {
+                        try!(write!(f, ", and larger ones vanish"));
+                    }
+                }
+                Ok(())
+            }
+            PathMtuReport::Probed { largest: None, ceiling, .. } => {
+                write!(f, "no packet of up to {} bytes reaches it", ceiling)
+            }
+            PathMtuReport::NotSupported(ref reason) => {
+                write!(f, "no path MTU probe was sent: {}", reason)
+            }
+        }
+    }
+}
+
+/// Probes for the largest packets which reach `target` without being fragmented, and returns
+/// once it knows, or once `timeout` has passed.
+///
+/// The probe connects to the target over TCP and, with the don't fragment bit set and without
+/// waiting to fill segments, writes a segment for each size of `PATH_MTU_BUCKETS` below the
+/// MTU of the interface the route leaves through, then one of that MTU, stopping at the first
+/// which doesn't reach it. A packet reaches the target if it is acknowledged before its share
+/// of the timeout is up. The connection is reset once the probe ends, so nothing which wasn't
+/// acknowledged is sent again, and no more than a packet per size is sent. The bytes are zeros,
+/// which the service is expected to reject; only its host acknowledging them matters.
+///
+/// Linux asks the kernel how many bytes wait to be acknowledged. Other Unix systems write a
+/// single packet of the interface MTU, and count it as reaching the target if the target
+/// answers or closes the connection, so a target which waits in silence looks like a route
+/// which drops the packet. Windows isn't probed, which is `NotSupported` rather than an error.
+///
+/// # Errors
+///
+/// * The target can't be connected to, or not before the timeout
+/// * A probe can't be written
+pub fn path_mtu_probe(target: SocketAddr, timeout: Duration) -> Result<PathMtuReport> {
+    let deadline = Instant::now() + timeout;
+    let (tx, rx) = mpsc::channel();
+    let spawned = thread::Builder::new()
+        .name(format!("path-mtu-{}", target))
+        .spawn(move || {
+            let probed = match TcpStream::connect(target) {
+                Ok(stream) => {
+                    let interface_mtu = stream.local_addr()
+                        .ok()
+                        .and_then(|local| interface_mtu(local.ip()));
+                    platform_path_mtu_probe(&stream, interface_mtu, deadline)
+                }
+                Err(e) => {
+                    Err(Error::PathMtuProbeFailed(format!("can't connect to {}: {}", target, e)))
+                }
+            };
+            let _ = tx.send(probed);
+        });
+    if let Err(e) = spawned {
+        return Err(Error::PathMtuProbeFailed(e.to_string()));
+    }
+    match rx.recv_timeout(timeout) {
+        Ok(probed) => probed,
+        Err(_) => {
+            Err(Error::PathMtuProbeFailed(format!("{} wasn't probed within {}ms",
+                                                  target,
+                                                  millis(timeout))))
+        }
+    }
+}
+
+/// Returns the sizes to probe a route whose packets can be up to `ceiling` bytes with, the
+/// buckets below it and then the ceiling itself.
+pub fn probe_sizes(ceiling: u32) -> Vec<u32> {
+    let mut sizes: Vec<u32> =
+        PATH_MTU_BUCKETS.iter().cloned().filter(|size| *size < ceiling).collect();
+    sizes.push(ceiling);
+    sizes
+}
+
+/// Returns the MTU of the interface which has the address `ip`, if it is known.
+fn interface_mtu(ip: IpAddr) -> Option<u32> {
+    interfaces()
+        .ok()
+        .and_then(|interfaces| {
+            interfaces.into_iter().find(|interface| interface.addrs.iter().any(|a| a.ip == ip))
+        })
+        .and_then(|interface| interface.mtu)
+}
+
+/// Checks every target concurrently, and returns how each check went, in the order of the
+/// targets. Checks which haven't finished once `timeout` has passed are left running, and
+/// reported as `ConnError::DeadlineExceeded`.
+pub fn connectivity_check(targets: &[ConnTarget], timeout: Duration) -> Vec<ConnResult> {
+    let deadline = Instant::now() + timeout;
+    let probe_mtu = henv::var(PATH_MTU_ENVVAR).is_ok();
+    let mut results: Vec<Option<ConnResult>> = targets.iter().map(|_| None).collect();
+    let (tx, rx) = mpsc::channel();
+    for (i, target) in targets.iter().enumerate() {
+        let tx = tx.clone();
+        let checked = target.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("conn-check-{}", target.host))
+            .spawn(move || {
+                let _ = tx.send((i, check_target(checked, deadline, probe_mtu)));
+            });
+        if let Err(e) = spawned {
+            results[i] = Some(ConnResult::failed(target.clone(), ConnError::Other(e.to_string())));
+        }
+    }
+    drop(tx);
+    while results.iter().any(|result| result.is_none()) {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        match rx.recv_timeout(deadline - now) {
+            Ok((i, result)) => results[i] = Some(result),
+            Err(_) => break,
+        }
+    }
+    results.into_iter()
+        .zip(targets.iter())
+        .map(|(result, target)| {
+            result.unwrap_or_else(|| {
+                ConnResult::failed(target.clone(), ConnError::DeadlineExceeded)
+            })
+        })
+        .collect()
+}
+
+/// A run of `connectivity_check`, with when it began and how long it took.
+///
+/// Its text is that of `connectivity_report`, with the time of the check in the heading. Its
+/// table has the time of the check and the number of targets reached, and a `targets` array with
+/// a table per target in the order they were checked.
+#[derive(Clone, Debug)]
+pub struct ConnectivityCheck {
+    /// When the check began
+    pub checked_at: time::Timespec,
+    /// Time the check of every target took, all together
+    pub duration: Duration,
+    pub results: Vec<ConnResult>,
+}
+
+impl ConnectivityCheck {
+    /// Checks every target, see `connectivity_check`.
+    pub fn run(targets: &[ConnTarget], timeout: Duration) -> Self {
+        let checked_at = time::get_time();
+        let started = Instant::now();
+        let results = connectivity_check(targets, timeout);
+        ConnectivityCheck {
+            checked_at: checked_at,
+            duration: started.elapsed(),
+            results: results,
+        }
+    }
+
+    /// Returns the number of targets reached.
+    pub fn reached(&self) -> usize {
+        self.results.iter().filter(|result| result.is_ok()).count()
+    }
+}
+
+impl Render for ConnectivityCheck {
+    fn to_text(&self, width: usize) -> String {
+        let report = connectivity_report(&self.results);
+        let mut lines = report.lines();
+        let heading = format!("{}, checked at {} in {}ms",
+                              lines.next().unwrap_or(""),
+                              rfc3339_utc(self.checked_at),
+                              millis(self.duration));
+        let mut text = vec![render::wrap(&heading, 2, width)];
+        // The results continue under the addresses of their targets
+        text.extend(lines.map(|line| render::wrap(line, 4, width)));
+        text.join("\n")
+    }
+
+    fn to_toml(&self) -> toml::Table {
+        let targets = self.results
+            .iter()
+            .map(|result| {
+                let mut table = toml::Table::new();
+                let target = &result.target;
+                table.insert("host".to_string(), toml::Value::String(target.host.clone()));
+                table.insert("port".to_string(), toml::Value::Integer(target.port as i64));
+                table.insert("tls".to_string(), toml::Value::Boolean(target.tls));
+                table.insert("reached".to_string(), toml::Value::Boolean(result.is_ok()));
+                if let Some(ref error) = result.error {
+                    table.insert("error".to_string(), toml::Value::String(error.to_string()));
+                }
+                if let Some(addr) = result.addr {
+                    table.insert("addr".to_string(), toml::Value::String(addr.to_string()));
+                }
+                if let Some(resolve_time) = result.resolve_time {
+                    table.insert("resolve_time_ms".to_string(), render::duration_ms(resolve_time));
+                }
+                if let Some(connect_time) = result.connect_time {
+                    table.insert("connect_time_ms".to_string(), render::duration_ms(connect_time));
+                }
+                if let Some(ref path_mtu) = result.path_mtu {
+                    table.insert("path_mtu".to_string(), toml::Value::String(path_mtu.to_string()));
+                }
+                toml::Value::Table(table)
+            })
+            .collect();
+        let mut table = toml::Table::new();
+        table.insert("checked_at".to_string(), render::timestamp(self.checked_at));
+        table.insert("duration_ms".to_string(), render::duration_ms(self.duration));
+        table.insert("reached".to_string(), toml::Value::Integer(self.reached() as i64));
+        table.insert("targets".to_string(), toml::Value::Array(targets));
+        table
+    }
+}
+
+/// Returns a report of the checks of `connectivity_check`, a line per target after a line
+/// counting the targets reached.
+pub fn connectivity_report(results: &[ConnResult]) -> String {
+    let reached = results.iter().filter(|result| result.is_ok()).count();
+    let mut lines = vec![format!("Connectivity: {} of {} targets reached",
+                                 reached,
+                                 results.len())];
+    for result in results.iter() {
+        lines.push(format!("  {}", result));
+    }
+    lines.join("\n")
+}
+
+fn check_target(target: ConnTarget, deadline: Instant, probe_mtu: bool) -> ConnResult {
+    let started = Instant::now();
+    let addrs: Vec<SocketAddr> = match (target.host.as_str(), target.port).to_socket_addrs() {
+        Ok(addrs) => addrs.collect(),
+        Err(e) => return ConnResult::failed(target, ConnError::Resolve(e.to_string())),
+    };
+    let mut result = ConnResult::failed(target, ConnError::NoAddresses);
+    result.resolve_time = Some(started.elapsed());
+    let mut connected = None;
+    for addr in addrs {
+        let attempted = Instant::now();
+        match TcpStream::connect(addr) {
+            Ok(stream) => {
+                result.addr = Some(addr);
+                result.connect_time = Some(attempted.elapsed());
+                connected = Some(stream);
+                break;
+            }
+            Err(e) => result.error = Some(classify(&e)),
+        }
+    }
+    let mut stream = match connected {
+        Some(stream) => stream,
+        None => return result,
+    };
+    result.error = if result.target.tls {
+        expect_tls(&mut stream, &result.target.host, deadline).err()
+    } else {
+        None
+    };
+    if probe_mtu && result.is_ok() {
+        // The probe gets half of what is left, so the check still finishes before the deadline
+        let now = Instant::now();
+        if let (Some(addr), true) = (result.addr, now < deadline) {
+            match path_mtu_probe(addr, (deadline - now) / 2) {
+                Ok(report) => result.path_mtu = Some(report),
+                Err(e) => debug!("Not reporting the path MTU to {} ({})", addr, e),
+            }
+        }
+    }
+    result
+}
+
+/// Sends a TLS client hello for `host`, and returns whether the answer read back before the
+/// deadline opens with a TLS record, a handshake or an alert.
+fn expect_tls(stream: &mut TcpStream,
+              host: &str,
+              deadline: Instant)
+              -> result::Result<(), ConnError> {
+    let now = Instant::now();
+    if now >= deadline {
+        return Err(ConnError::TimedOut);
+    }
+    try!(stream.set_read_timeout(Some(deadline - now)).map_err(|e| classify(&e)));
+    try!(stream.write_all(&client_hello(host)).map_err(|e| classify(&e)));
+    let mut answer = [0; 2];
+    try!(stream.read_exact(&mut answer).map_err(|e| classify(&e)));
+    if is_tls_record(&answer) {
+        Ok(())
+    } else {
+        Err(ConnError::NotTls)
+    }
+}
+
+/// Returns whether bytes open a TLS record of a handshake or an alert, of any version of TLS.
+fn is_tls_record(bytes: &[u8]) -> bool {
+    bytes.len() >= 2 && (bytes[0] == 0x16 || bytes[0] == 0x15) && bytes[1] == 0x03
+}
+
+/// Returns the record of a TLS 1.2 client hello naming `host` as the server it is for.
+fn client_hello(host: &str) -> Vec<u8> {
+    let mut server_name = vec![0x00];
+    push_u16(&mut server_name, host.len() as u16);
+    server_name.extend_from_slice(host.as_bytes());
+    let mut extensions = vec![];
+    // The server name extension lets hosts serving many names pick the one asked for
+    push_u16(&mut extensions, 0x0000);
+    push_u16(&mut extensions, server_name.len() as u16 + 2);
+    push_u16(&mut extensions, server_name.len() as u16);
+    extensions.extend_from_slice(&server_name);
+
+    let mut hello = vec![0x03, 0x03];
+    hello.extend_from_slice(&randombytes(32));
+    hello.push(0x00);
+    push_u16(&mut hello, TLS_CIPHER_SUITES.len() as u16 * 2);
+    for suite in TLS_CIPHER_SUITES.iter() {
+        push_u16(&mut hello, *suite);
+    }
+    hello.extend_from_slice(&[0x01, 0x00]);
+    push_u16(&mut hello, extensions.len() as u16);
+    hello.extend_from_slice(&extensions);
+
+    let mut handshake = vec![0x01, 0x00];
+    push_u16(&mut handshake, hello.len() as u16);
+    handshake.extend_from_slice(&hello);
+
+    let mut record = vec![0x16, 0x03, 0x01];
+    push_u16(&mut record, handshake.len() as u16);
+    record.extend_from_slice(&handshake);
+    record
+}
+
+fn push_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.push((value >> 8) as u8);
+    bytes.push(value as u8);
+}
+
+/// Returns why a connection failed, from the error it failed with.
+fn classify(err: &io::Error) -> ConnError {
+    match err.kind() {
+        io::ErrorKind::ConnectionRefused => return ConnError::Refused,
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => return ConnError::TimedOut,
+        _ => {}
+    }
+    match err.raw_os_error() {
+        Some(code) if is_unreachable(code) => ConnError::Unreachable,
+        _ => ConnError::Other(err.to_string()),
+    }
+}
+
+#[cfg(unix)]
+fn is_unreachable(code: i32) -> bool {
+    code == libc::ENETUNREACH || code == libc::EHOSTUNREACH
+}
+
+#[cfg(windows)]
+fn is_unreachable(code: i32) -> bool {
+    // WSAENETUNREACH and WSAEHOSTUNREACH
+    code == 10051 || code == 10065
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + (duration.subsec_nanos() / 1_000_000) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use time;
+    use toml;
+
+    use util::render::Render;
+    use super::*;
+    use super::{classify, client_hello, is_tls_record, probe_sizes};
+
+    fn listener() -> (TcpListener, u16) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        (listener, port)
+    }
+
+    /// Accepts a connection, reads the hello sent to it, and answers with `answer`.
+    fn answer_with(listener: TcpListener, answer: &'static [u8]) {
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut hello = [0; 5];
+            let _ = stream.read_exact(&mut hello);
+            let _ = stream.write_all(answer);
+        });
+    }
+
+    #[test]
+    fn targets_are_read_from_urls() {
+        assert_eq!(ConnTarget::from_url("https://willem.habitat.sh/v1/depot").unwrap(),
+                   ConnTarget::new("willem.habitat.sh", 443, true));
+        assert_eq!(ConnTarget::from_url("http://depot.example.com:9636/v1/depot").unwrap(),
+                   ConnTarget::new("depot.example.com", 9636, false));
+        assert_eq!(ConnTarget::from_url("https://[::1]:8443").unwrap(),
+                   ConnTarget::new("::1", 8443, true));
+        assert!(ConnTarget::from_url("willem.habitat.sh").is_err());
+        assert!(ConnTarget::from_url("file:///hab/cache/artifacts").is_err());
+    }
+
+    #[test]
+    fn targets_listening_are_reached() {
+        let (_listener, port) = listener();
+        let target = ConnTarget::new("127.0.0.1", port, false);
+        let results = connectivity_check(&[target.clone()], Duration::from_secs(5));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].target, target);
+        assert_eq!(results[0].error, None);
+        assert_eq!(results[0].addr.unwrap().port(), port);
+        assert!(results[0].resolve_time.is_some());
+        assert!(results[0].connect_time.is_some());
+    }
+
+    #[test]
+    fn closed_ports_are_refused_in_target_order() {
+        let (listener, closed) = listener();
+        drop(listener);
+        let (_listener, open) = listener();
+        let results = connectivity_check(&[ConnTarget::new("127.0.0.1", closed, false),
+                                           ConnTarget::new("127.0.0.1", open, false)],
+                                         Duration::from_secs(5));
+        assert_eq!(results[0].error, Some(ConnError::Refused));
+        assert!(results[0].connect_time.is_none());
+        assert_eq!(results[1].error, None);
+    }
+
+    #[test]
+    fn tls_targets_must_answer_with_tls() {
+        let (plain, plain_port) = listener();
+        answer_with(plain, b"HTTP/1.0 400 Bad Request\r\n\r\n");
+        let (tls, tls_port) = listener();
+        answer_with(tls, &[0x15, 0x03, 0x03, 0x00, 0x02, 0x02, 0x28]);
+        let results = connectivity_check(&[ConnTarget::new("127.0.0.1", plain_port, true),
+                                           ConnTarget::new("127.0.0.1", tls_port, true)],
+                                         Duration::from_secs(5));
+        assert_eq!(results[0].error, Some(ConnError::NotTls));
+        assert_eq!(results[1].error, None);
+    }
+
+    #[test]
+    fn silent_targets_are_given_up_on_at_the_deadline() {
+        let (_listener, port) = listener();
+        let started = Instant::now();
+        let results = connectivity_check(&[ConnTarget::new("127.0.0.1", port, true)],
+                                         Duration::from_millis(200));
+        assert!(started.elapsed() < Duration::from_secs(2));
+        match results[0].error {
+            Some(ConnError::TimedOut) |
+            Some(ConnError::DeadlineExceeded) => {}
+            ref other => panic!("Expected the check to time out, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn client_hellos_name_the_host() {
+        let hello = client_hello("willem.habitat.sh");
+        assert!(is_tls_record(&hello));
+        assert_eq!(hello.len(), 5 + ((hello[3] as usize) << 8 | hello[4] as usize));
+        assert!(hello.windows(17).any(|window| window == b"willem.habitat.sh"));
+        assert!(!is_tls_record(b"HTTP/1.0"));
+    }
+
+    #[test]
+    fn errors_are_classified() {
+        let refused = io::Error::new(io::ErrorKind::ConnectionRefused, "refused");
+        assert_eq!(classify(&refused), ConnError::Refused);
+        let timed_out = io::Error::new(io::ErrorKind::TimedOut, "timed out");
+        assert_eq!(classify(&timed_out), ConnError::TimedOut);
+        let other = io::Error::new(io::ErrorKind::Other, "broken");
+        assert_eq!(classify(&other), ConnError::Other("broken".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreachable_hosts_are_classified() {
+        let unreachable = io::Error::from_raw_os_error(::libc::EHOSTUNREACH);
+        assert_eq!(classify(&unreachable), ConnError::Unreachable);
+    }
+
+    /// Returns the results of reaching a depot and of being refused by GitHub.
+    fn reached_and_refused() -> Vec<ConnResult> {
+        let reached = ConnResult {
+            target: ConnTarget::new("willem.habitat.sh", 443, true),
+            addr: Some("52.1.2.3:443".parse().unwrap()),
+            resolve_time: Some(Duration::from_millis(12)),
+            connect_time: Some(Duration::from_millis(30)),
+            error: None,
+            path_mtu: None,
+        };
+        let refused = ConnResult {
+            target: ConnTarget::new("github.com", 443, true),
+            addr: None,
+            resolve_time: Some(Duration::from_millis(5)),
+            connect_time: None,
+            error: Some(ConnError::Refused),
+            path_mtu: None,
+        };
+        vec![reached, refused]
+    }
+
+    fn synthetic_check() -> ConnectivityCheck {
+        let mut results = reached_and_refused();
+        results[0].path_mtu = Some(PathMtuReport::Probed {
+            largest: Some(1400),
+            ceiling: 1500,
+            interface_mtu: Some(1500),
+            blackhole: true,
+        });
+        ConnectivityCheck {
+            checked_at: time::Timespec::new(1476350400, 0),
+            duration: Duration::from_millis(43),
+            results: results,
+        }
+    }
+
+    #[test]
+    fn connectivity_check_text_is_pinned() {
+        let golden = include_str!("../../../tests/fixtures/connectivity-check.txt");
+        assert_eq!(format!("{}\n", synthetic_check().to_text(60)), golden);
+        let unwrapped = synthetic_check().to_text(0);
+        assert_eq!(unwrapped.lines().count(), 3);
+        assert!(unwrapped.starts_with("Connectivity: 1 of 2 targets reached, checked at \
+                                       2016-10-13T09:20:00Z in 43ms\n"));
+    }
+
+    #[test]
+    fn connectivity_check_toml_round_trips() {
+        let table = synthetic_check().to_toml();
+        let parsed = toml::Parser::new(&toml::encode_str(&table)).parse().unwrap();
+        assert_eq!(parsed, table);
+        let value = toml::Value::Table(table);
+        assert_eq!(value.lookup("checked_at").and_then(|v| v.as_str()),
+                   Some("2016-10-13T09:20:00Z"));
+        assert_eq!(value.lookup("duration_ms").and_then(|v| v.as_integer()), Some(43));
+        assert_eq!(value.lookup("reached").and_then(|v| v.as_integer()), Some(1));
+        assert_eq!(value.lookup("targets.0.connect_time_ms").and_then(|v| v.as_integer()),
+                   Some(30));
+        assert_eq!(value.lookup("targets.1.error").and_then(|v| v.as_str()),
+                   Some("connection refused"));
+        assert!(value.lookup("targets.1.addr").is_none());
+    }
+
+    #[test]
+    fn reports_count_the_targets_reached() {
+        assert_eq!(connectivity_report(&reached_and_refused()),
+                   "Connectivity: 1 of 2 targets reached\n  willem.habitat.sh:443 (TLS): ok \
+                    (resolved in 12ms, connected to 52.1.2.3:443 in 30ms)\n  github.com:443 \
+                    (TLS): failed, connection refused (resolved in 5ms)");
+    }
+
+    #[test]
+    fn probes_end_at_the_ceiling() {
+        assert_eq!(probe_sizes(1500), vec![576, 1280, 1400, 1450, 1480, 1500]);
+        assert_eq!(probe_sizes(1420), vec![576, 1280, 1400, 1420]);
+        assert_eq!(probe_sizes(9001), vec![576, 1280, 1400, 1450, 1480, 1500, 9000, 9001]);
+        assert_eq!(probe_sizes(500), vec![500]);
+    }
+
+    #[test]
+    fn path_mtu_reports_say_whether_the_interface_mtu_fits() {
+        let fits = PathMtuReport::Probed {
+            largest: Some(1500),
+            ceiling: 1500,
+            interface_mtu: Some(1500),
+            blackhole: false,
+        };
+        assert!(!fits.is_below_interface_mtu());
+        assert_eq!(fits.to_string(), "packets of up to 1500 bytes reach it");
+        let blackhole = PathMtuReport::Probed {
+            largest: Some(1400),
+            ceiling: 1500,
+            interface_mtu: Some(1500),
+            blackhole: true,
+        };
+        assert!(blackhole.is_below_interface_mtu());
+        assert_eq!(blackhole.to_string(),
+                   "packets of up to 1400 bytes reach it, below the MTU of 1500, and larger \
+                    ones vanish");
+        let none = PathMtuReport::Probed {
+            largest: None,
+            ceiling: 1500,
+            interface_mtu: None,
+            blackhole: true,
+        };
+        assert!(none.is_below_interface_mtu());
+        assert!(!PathMtuReport::NotSupported("Windows".to_string()).is_below_interface_mtu());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn loopback_carries_packets_of_its_mtu() {
+        let (listener, port) = listener();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut probes = vec![];
+            let _ = stream.read_to_end(&mut probes);
+        });
+        let addr = format!("127.0.0.1:{}", port).parse().unwrap();
+        match path_mtu_probe(addr, Duration::from_secs(5)).unwrap() {
+            PathMtuReport::Probed { largest, ceiling, blackhole, .. } => {
+                assert_eq!(largest, Some(ceiling));
+                assert!(!blackhole);
+            }
+            other => panic!("Expected loopback to be probed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn path_mtu_probes_of_closed_ports_fail() {
+        let (listener, port) = listener();
+        drop(listener);
+        let addr = format!("127.0.0.1:{}", port).parse().unwrap();
+        assert!(path_mtu_probe(addr, Duration::from_secs(5)).is_err());
+    }
+}
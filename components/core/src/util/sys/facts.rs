@@ -0,0 +1,185 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Facts contributed to the `[sys]` table by the crates built on this one, such as the peer
+//! count of a supervisor or the size of a depot's package cache.
+//!
+//! Providers are registered at startup, each for a namespace of its own, and their facts are
+//! rendered as the table of that namespace, such as `[sys.depot]`, in the order of their
+//! namespaces so the rendering is the same every time.
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use toml;
+
+use error::{Error, Result};
+use util::redact;
+
+lazy_static! {
+    static ref REGISTRY: FactRegistry = FactRegistry::new();
+}
+
+/// Contributes facts to the `[sys]` table.
+pub trait FactProvider: Send + Sync {
+    /// Key of the table of `[sys]` the facts are rendered in, such as `depot`.
+    fn namespace(&self) -> &str;
+
+    /// Returns the facts, keyed by their name within the namespace. A provider which fails has
+    /// its namespace left out of the rendering, without affecting the others.
+    fn collect(&self) -> Result<BTreeMap<String, toml::Value>>;
+}
+
+/// The fact providers registered, keyed by their namespace.
+pub struct FactRegistry {
+    providers: RwLock<BTreeMap<String, Box<FactProvider>>>,
+}
+
+impl FactRegistry {
+    pub fn new() -> Self {
+        FactRegistry { providers: RwLock::new(BTreeMap::new()) }
+    }
+
+    /// Registers a provider for its namespace.
+    ///
+    /// # Errors
+    ///
+    /// * Another provider is registered for the namespace, or the namespace is empty
+    pub fn register(&self, provider: Box<FactProvider>) -> Result<()> {
+        let namespace = provider.namespace().to_string();
+        let mut providers = self.providers.write().expect("Fact registry lock poisoned");
+        if namespace.is_empty() || providers.contains_key(&namespace) {
+            return Err(Error::FactNamespaceTaken(namespace));
+        }
+        providers.insert(namespace, provider);
+        Ok(())
+    }
+
+    /// Adds the facts of every provider to a `[sys]` table, each under its namespace, in the
+    /// order of the namespaces. A namespace the table already has a key for collides with the
+    /// built in facts and is skipped, as is the namespace of a provider which fails. Returns why
+    /// each namespace was skipped, which is also logged.
+    pub fn merge_into(&self, table: &mut toml::Table) -> Vec<String> {
+        let providers = self.providers.read().expect("Fact registry lock poisoned");
+        let mut skipped = vec![];
+        for (namespace, provider) in providers.iter() {
+            if table.contains_key(namespace) {
+                skipped.push(format!("sys.{} collides with a fact of the same name; skipping \
+                                      its provider",
+                                     namespace));
+                continue;
+            }
+            match provider.collect() {
+                Ok(facts) => {
+                    table.insert(namespace.clone(), toml::Value::Table(facts));
+                }
+                Err(e) => skipped.push(format!("Facts of sys.{} failed; skipping them ({})",
+                                               namespace,
+                                               e)),
+            }
+        }
+        for reason in skipped.iter() {
+            warn!("{}", redact::redact_text(reason));
+        }
+        skipped
+    }
+}
+
+/// Registers a provider whose facts every rendering of `[sys]` includes, see
+/// `FactRegistry::register`.
+pub fn register_fact_provider(provider: Box<FactProvider>) -> Result<()> {
+    REGISTRY.register(provider)
+}
+
+/// Adds the facts of the registered providers to a `[sys]` table, see
+/// `FactRegistry::merge_into`.
+pub fn merge_facts(table: &mut toml::Table) -> Vec<String> {
+    REGISTRY.merge_into(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use toml;
+
+    use error::{Error, Result};
+    use super::*;
+
+    struct Peers(u32);
+
+    impl FactProvider for Peers {
+        fn namespace(&self) -> &str {
+            "peers"
+        }
+
+        fn collect(&self) -> Result<BTreeMap<String, toml::Value>> {
+            let mut facts = BTreeMap::new();
+            facts.insert("count".to_string(), toml::Value::Integer(self.0 as i64));
+            Ok(facts)
+        }
+    }
+
+    struct Failing(&'static str);
+
+    impl FactProvider for Failing {
+        fn namespace(&self) -> &str {
+            self.0
+        }
+
+        fn collect(&self) -> Result<BTreeMap<String, toml::Value>> {
+            Err(Error::FileNotFound("/hab/cache/artifacts".to_string()))
+        }
+    }
+
+    #[test]
+    fn facts_are_merged_under_their_namespace() {
+        let registry = FactRegistry::new();
+        registry.register(Box::new(Peers(3))).unwrap();
+        let mut table = toml::Table::new();
+        assert!(registry.merge_into(&mut table).is_empty());
+        assert_eq!(table["peers"].lookup("count").unwrap().as_integer(), Some(3));
+    }
+
+    #[test]
+    fn namespaces_are_registered_once() {
+        let registry = FactRegistry::new();
+        registry.register(Box::new(Peers(3))).unwrap();
+        match registry.register(Box::new(Peers(4))) {
+            Err(Error::FactNamespaceTaken(ref namespace)) => assert_eq!(namespace, "peers"),
+            other => panic!("Expected the namespace to be taken, got {:?}", other),
+        }
+        assert!(registry.register(Box::new(Failing(""))).is_err());
+    }
+
+    #[test]
+    fn failures_and_collisions_only_skip_their_namespace() {
+        let registry = FactRegistry::new();
+        registry.register(Box::new(Failing("depot"))).unwrap();
+        registry.register(Box::new(Failing("cpu"))).unwrap();
+        registry.register(Box::new(Peers(3))).unwrap();
+        let mut table = toml::Table::new();
+        table.insert("cpu".to_string(), toml::Value::Table(toml::Table::new()));
+        let skipped = registry.merge_into(&mut table);
+        assert_eq!(skipped,
+                   vec!["sys.cpu collides with a fact of the same name; skipping its provider"
+                            .to_string(),
+                        "Facts of sys.depot failed; skipping them (File not found at: \
+                         /hab/cache/artifacts)"
+                            .to_string()]);
+        assert!(table["cpu"].as_table().unwrap().is_empty());
+        assert!(table.get("depot").is_none());
+        assert!(table.get("peers").is_some());
+    }
+}
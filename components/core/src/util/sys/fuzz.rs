@@ -0,0 +1,94 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bodies of the fuzz targets of `components/fuzz` for the parsers of system files and command
+//! output, built with the `fuzzing` feature. The parsers are private to `sys`, so the targets
+//! reach them through here.
+//!
+//! Every parser must be total: whatever the bytes, it returns what it found, `None` or an empty
+//! list, and never panics. Files and output which aren't UTF-8 are read lossily, as `sys` reads
+//! them.
+
+use std::net::Ipv4Addr;
+use std::path::Path;
+
+use super::IpPreference;
+use super::parse::*;
+
+/// Runs the parsers of Linux's `/proc` and `/sys` files, and of the release files, on `data`.
+pub fn proc_files(data: &[u8]) {
+    let contents = String::from_utf8_lossy(data);
+    let contents = contents.as_ref();
+    parse_cpuinfo(contents);
+    parse_cpu_list(contents);
+    parse_meminfo(contents);
+    parse_hugepage_size(contents);
+    parse_bracketed_choice(contents);
+    parse_memory_limit(contents);
+    parse_cgroup_max(contents);
+    parse_cpu_max(contents);
+    parse_mounts(contents, Path::new("/hab/svc"));
+    parse_mountinfo(contents, Path::new("/hab/svc"));
+    parse_mount_table(contents, true);
+    parse_os_release(contents);
+    parse_lsb_release(contents);
+    parse_redhat_release(contents);
+    parse_proc_uptime(contents);
+    parse_proc_loadavg(contents);
+    parse_proc_pid_ppid(contents);
+    parse_proc_stat(contents);
+    parse_selinux_enforce(contents);
+    parse_apparmor_profiles(contents);
+    parse_host_id(contents);
+    parse_mac(contents);
+    parse_mac_octets(contents);
+    parse_ns_link(contents);
+    let mut lines = contents.splitn(2, '\n');
+    parse_cfs_quota(lines.next().unwrap_or(""), lines.next().unwrap_or(""));
+}
+
+/// Runs the parsers of routing tables, neighbor tables and the output of the commands listing
+/// routes and addresses on `data`.
+pub fn route_output(data: &[u8]) {
+    let output = String::from_utf8_lossy(data);
+    let output = output.as_ref();
+    parse_ip_route(output);
+    parse_route_get(output);
+    parse_proc_net_route(output);
+    parse_proc_net_ipv6_route(output);
+    parse_proc_net_arp(output);
+    parse_ip_neigh(output);
+    for family in &[IpPreference::Ipv4First, IpPreference::Ipv6First] {
+        parse_ifconfig(output, *family);
+        parse_ipconfig(output, *family);
+    }
+    parse_arp_conflict(data, Ipv4Addr::new(10, 0, 0, 5), &[0x52, 0x54, 0, 0x12, 0x34, 0x56]);
+}
+
+#[cfg(test)]
+mod tests {
+    use util::fuzz;
+
+    #[test]
+    #[ignore]
+    fn proc_files_smoke() {
+        fuzz::smoke("proc_files", super::proc_files);
+    }
+
+    #[test]
+    #[ignore]
+    fn route_output_smoke() {
+        fuzz::smoke("route_output", super::route_output);
+    }
+}
@@ -0,0 +1,1547 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::fs;
+use std::io::Write;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::result;
+use std::slice;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use errno::{Errno, errno};
+use libc;
+
+use error::{Error, Result};
+use super::{CgroupLimits, ConflictCheck, CpuInfo, CpuStat, DiskUsage, FsUsage, Gateway,
+            HugepageInfo, Interface, IpPreference, IpStrategy, LoadAvg, LsmStatus, MemInfo,
+            MountInfo, Neighbor, ProcCounts, SelinuxMode, Virt, Uname, format_mac, hostname_command,
+            ifaddrs_ipv4, ifaddrs_ipv6, read_file, udp_ipv4, udp_ipv6};
+use super::conn::{PathMtuReport, probe_sizes};
+use super::source::{CommandRunner, FsReader, Host};
+use super::parse::{CpuTopology, ETHERTYPE_ARP, VirtSigns, arp_probe_frame, detect_virt,
+                   parse_apparmor_profiles, parse_arp_conflict, parse_bracketed_choice,
+                   parse_cfs_quota, parse_cgroup_max, parse_cpu_list,
+                   parse_cpu_max, parse_cpuinfo, parse_getenforce, parse_hugepage_size,
+                   parse_ip_neigh, parse_ip_route, parse_mac_octets, parse_ns_link,
+                   parse_meminfo, parse_memory_limit, parse_mount_table, parse_mountinfo,
+                   parse_mounts,
+                   parse_proc_net_arp, parse_proc_net_ipv6_route, parse_proc_loadavg,
+                   parse_proc_pid_ppid, parse_proc_stat, parse_selinux_enforce,
+                   parse_proc_net_route,
+                   parse_proc_uptime};
+use super::unix::{self, getsockopt_int, ifconfig_ipv4, ifconfig_ipv6, reset_on_close,
+                  setsockopt_int};
+
+static KERNEL_HOSTNAME: &'static str = "/proc/sys/kernel/hostname";
+static PROC_NET_ROUTE: &'static str = "/proc/net/route";
+static PROC_NET_IPV6_ROUTE: &'static str = "/proc/net/ipv6_route";
+static PROC_NET_ARP: &'static str = "/proc/net/arp";
+static PROC_THREAD_SELF_NS_NET: &'static str = "/proc/thread-self/ns/net";
+static PROC_SELF_NS_NET: &'static str = "/proc/self/ns/net";
+static PROC_CPUINFO: &'static str = "/proc/cpuinfo";
+static PROC_MEMINFO: &'static str = "/proc/meminfo";
+static THP_ENABLED: &'static str = "/sys/kernel/mm/transparent_hugepage/enabled";
+static PROC_NR_HUGEPAGES: &'static str = "/proc/sys/vm/nr_hugepages";
+static PROC_SELF_MOUNTS: &'static str = "/proc/self/mounts";
+static PROC_SELF_MOUNTINFO: &'static str = "/proc/self/mountinfo";
+static PROC_UPTIME: &'static str = "/proc/uptime";
+static PROC_LOADAVG: &'static str = "/proc/loadavg";
+static PROC_STAT: &'static str = "/proc/stat";
+static PROC: &'static str = "/proc";
+static SELINUX_ENFORCE: &'static str = "/sys/fs/selinux/enforce";
+static APPARMOR_SECURITYFS: &'static str = "/sys/kernel/security/apparmor";
+static APPARMOR_PROFILES: &'static str = "/sys/kernel/security/apparmor/profiles";
+static APPARMOR_ENABLED: &'static str = "/sys/module/apparmor/parameters/enabled";
+static PROC_ENTROPY_AVAIL: &'static str = "/proc/sys/kernel/random/entropy_avail";
+static DOCKERENV: &'static str = "/.dockerenv";
+static PROC_1_CGROUP: &'static str = "/proc/1/cgroup";
+static PROC_XEN: &'static str = "/proc/xen";
+static DMI_SYS_VENDOR: &'static str = "/sys/class/dmi/id/sys_vendor";
+static DMI_PRODUCT_NAME: &'static str = "/sys/class/dmi/id/product_name";
+static PROC_SELF_CGROUP: &'static str = "/proc/self/cgroup";
+static SYS_FS_CGROUP: &'static str = "/sys/fs/cgroup";
+
+/// Memory limits of v1 cgroups at or above which are no limit. v1 reports no limit as the
+/// largest page aligned number, which depends on the page size.
+const CGROUP_V1_NO_MEMORY_LIMIT: u64 = 1 << 62;
+
+const IFNAMSIZ: usize = 16;
+const SIOCGIFMTU: libc::c_ulong = 0x8921;
+const SIOCGIFHWADDR: libc::c_ulong = 0x8927;
+const SIOCGIFINDEX: libc::c_ulong = 0x8933;
+/// Bytes of a socket's send queue not yet acknowledged, `TIOCOUTQ` for sockets
+const SIOCOUTQ: libc::c_ulong = 0x5411;
+
+const TCP_MAXSEG: libc::c_int = 2;
+const IP_MTU_DISCOVER: libc::c_int = 10;
+const IP_MTU: libc::c_int = 14;
+const IPV6_MTU_DISCOVER: libc::c_int = 23;
+const IPV6_MTU: libc::c_int = 24;
+/// Value of `IP_MTU_DISCOVER` and `IPV6_MTU_DISCOVER` which sets the don't fragment bit
+const PMTUDISC_DO: libc::c_int = 2;
+
+/// Bytes of the IPv4 and TCP headers of a segment without options
+const IPV4_TCP_HEADERS: u32 = 40;
+/// Bytes of the IPv6 and TCP headers of a segment without options
+const IPV6_TCP_HEADERS: u32 = 60;
+/// Milliseconds between looks at whether a path MTU probe was acknowledged
+const PROBE_POLL_MS: u64 = 10;
+
+/// Number of CPUs the mask given to `sched_getaffinity` holds, as in glibc's `cpu_set_t`
+const CPU_SETSIZE: usize = 1024;
+
+/// The start of `struct sysinfo`, followed by room for the rest of it, which differs by
+/// architecture and is never read
+#[repr(C)]
+struct SysinfoUptime {
+    uptime: libc::c_long,
+    rest: [u8; 256],
+}
+
+/// `struct timex` of adjtimex(2), with room for the fields after `modes`, which are never read
+#[repr(C)]
+struct Timex {
+    modes: libc::c_uint,
+    rest: [libc::c_long; 32],
+}
+
+/// Clock state adjtimex(2) returns when the clock isn't synchronized
+const TIME_ERROR: libc::c_int = 5;
+
+extern "C" {
+    fn adjtimex(buf: *mut Timex) -> libc::c_int;
+    fn sched_getaffinity(pid: libc::pid_t, size: libc::size_t, mask: *mut u64) -> libc::c_int;
+    fn sysinfo(info: *mut SysinfoUptime) -> libc::c_int;
+}
+
+/// `struct ifreq`, with its union of request values left as raw memory
+#[repr(C)]
+struct IfReq {
+    ifr_name: [libc::c_char; IFNAMSIZ],
+    ifr_ifru: [libc::c_int; 6],
+}
+
+/// Strategies discovering the IPv4 address, in the order they are tried. The native ones come
+/// first: the address a UDP socket to a public resolver is routed from, then the best one
+/// `getifaddrs` lists, for hosts without a default route. `ip` and then `ifconfig` are only run
+/// if both fail, as minimal containers often ship neither.
+pub static IPV4_STRATEGIES: &'static [IpStrategy] = &[IpStrategy {
+                                                          name: "udp-connect",
+                                                          discover: udp_ipv4,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "getifaddrs",
+                                                          discover: ifaddrs_ipv4,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "ip route",
+                                                          discover: ip_route_ipv4,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "ifconfig",
+                                                          discover: ifconfig_ipv4,
+                                                      }];
+
+/// Strategies discovering the IPv6 address, in the order they are tried
+pub static IPV6_STRATEGIES: &'static [IpStrategy] = &[IpStrategy {
+                                                          name: "udp-connect ipv6",
+                                                          discover: udp_ipv6,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "getifaddrs ipv6",
+                                                          discover: ifaddrs_ipv6,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "ip -6 route",
+                                                          discover: ip_route_ipv6,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "ifconfig ipv6",
+                                                          discover: ifconfig_ipv6,
+                                                      }];
+
+/// Returns the host name from `gethostname`, then from the kernel, then from the `hostname`
+/// command.
+pub fn hostname() -> Result<String> {
+    if let Some(name) = unix::gethostname() {
+        return Ok(name);
+    }
+    if let Some(name) = kernel_hostname() {
+        return Ok(name);
+    }
+    debug!("gethostname failed, falling back to the hostname command");
+    hostname_command()
+}
+
+fn kernel_hostname() -> Option<String> {
+    read_file(KERNEL_HOSTNAME)
+}
+
+/// Returns the default route of the kernel's routing tables.
+pub fn default_gateway(preference: IpPreference) -> Result<Option<Gateway>> {
+    gateway_from(&Host, preference)
+}
+
+fn gateway_from(files: &FsReader, preference: IpPreference) -> Result<Option<Gateway>> {
+    let ipv4 = match files.read_to_string(Path::new(PROC_NET_ROUTE)) {
+        Some(routes) => parse_proc_net_route(&routes),
+        None => return Err(Error::FileNotFound(PROC_NET_ROUTE.to_string())),
+    };
+    // The IPv6 table doesn't exist when IPv6 is disabled
+    let ipv6 = files.read_to_string(Path::new(PROC_NET_IPV6_ROUTE))
+        .and_then(|routes| parse_proc_net_ipv6_route(&routes));
+    Ok(match preference {
+        IpPreference::Ipv4First => ipv4.or(ipv6),
+        IpPreference::Ipv6First => ipv6.or(ipv4),
+    })
+}
+
+/// Returns the neighbors of `/proc/net/arp`, or of `ip -o neigh` if it can't be read.
+pub fn neighbors() -> Result<Vec<Neighbor>> {
+    neighbors_from(&Host, &Host)
+}
+
+fn neighbors_from(files: &FsReader, runner: &CommandRunner) -> Result<Vec<Neighbor>> {
+    match files.read_to_string(Path::new(PROC_NET_ARP)) {
+        Some(contents) => Ok(parse_proc_net_arp(&contents)),
+        None => {
+            debug!("{} can't be read, asking ip for the neighbors", PROC_NET_ARP);
+            let output = try!(runner.run("ip", &["-o", "neigh", "show"]));
+            Ok(parse_ip_neigh(&output))
+        }
+    }
+}
+
+/// Returns the inode number of the network namespace of this thread, which is the namespace
+/// the sockets it opens are in, or of the process on kernels before 3.17 which lack
+/// `/proc/thread-self`.
+pub fn netns_id() -> Result<String> {
+    let link = match fs::read_link(PROC_THREAD_SELF_NS_NET)
+        .or_else(|_| fs::read_link(PROC_SELF_NS_NET)) {
+        Ok(link) => link,
+        Err(e) => {
+            return Err(Error::SysInfoFailed(format!("Failed to read {}: {}", PROC_SELF_NS_NET, e)))
+        }
+    };
+    let link = link.to_string_lossy();
+    match parse_ns_link(&link) {
+        Some(id) => Ok(id),
+        None => {
+            Err(Error::SysInfoFailed(format!("{} links to {}, which isn't a namespace",
+                                             PROC_SELF_NS_NET,
+                                             link)))
+        }
+    }
+}
+
+/// Returns every network interface of this host, including loopback interfaces and interfaces
+/// which are down, in the order the system lists them.
+///
+/// The hardware address and MTU are asked of the kernel with an ioctl, which answers for the
+/// network namespace of this process. sysfs isn't read, as it shows the interfaces of the
+/// namespace it was mounted in, which under some container runtimes isn't this one. An
+/// interface which disappears while it is read keeps what `getifaddrs` knew about it.
+pub fn interfaces() -> Result<Vec<Interface>> {
+    let mut interfaces = try!(unix::interfaces(mac_address));
+    for interface in interfaces.iter_mut() {
+        if interface.mac.is_none() {
+            interface.mac = ioctl_mac(&interface.name);
+        }
+        interface.mtu = ioctl_mtu(&interface.name);
+    }
+    Ok(interfaces)
+}
+
+fn ioctl_mtu(name: &str) -> Option<u32> {
+    ifreq_ioctl(name, SIOCGIFMTU).map(|req| req.ifr_ifru[0] as u32)
+}
+
+fn ioctl_ifindex(name: &str) -> Option<libc::c_int> {
+    ifreq_ioctl(name, SIOCGIFINDEX).map(|req| req.ifr_ifru[0])
+}
+
+fn ioctl_mac(name: &str) -> Option<String> {
+    ifreq_ioctl(name, SIOCGIFHWADDR).and_then(|req| {
+        // The union holds a `sockaddr`, whose `sa_data` starts with the address
+        let sockaddr = unsafe {
+            slice::from_raw_parts(req.ifr_ifru.as_ptr() as *const u8,
+                                  mem::size_of_val(&req.ifr_ifru))
+        };
+        format_mac(&sockaddr[2..8])
+    })
+}
+
+/// Issues an interface ioctl on a throwaway socket, returning the filled in request.
+fn ifreq_ioctl(name: &str, request: libc::c_ulong) -> Option<IfReq> {
+    let name = match CString::new(name) {
+        Ok(ref name) if name.as_bytes().len() < IFNAMSIZ => name.clone(),
+        _ => return None,
+    };
+    let mut req: IfReq = unsafe { mem::zeroed() };
+    for (dst, src) in req.ifr_name.iter_mut().zip(name.as_bytes()) {
+        *dst = *src as libc::c_char;
+    }
+    unsafe {
+        let fd = libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0);
+        if fd < 0 {
+            return None;
+        }
+        let rv = libc::ioctl(fd, request as _, &mut req);
+        libc::close(fd);
+        if rv < 0 { None } else { Some(req) }
+    }
+}
+
+pub fn uname() -> Result<Uname> {
+    unix::uname()
+}
+
+/// Sends an ARP probe for `ip` out of `iface` on a raw packet socket, and waits up to `timeout`
+/// for another host to answer for it.
+pub fn arp_probe(ip: Ipv4Addr, iface: &str, timeout: Duration) -> Result<ConflictCheck> {
+    // Loopback interfaces have an all zero address, which nothing answers
+    let mac = match ioctl_mac(iface).and_then(|mac| parse_mac_octets(&mac)) {
+        Some(mac) if mac != [0; 6] => mac,
+        _ => return Ok(ConflictCheck::NotSupported(format!("{} has no hardware address", iface))),
+    };
+    let ifindex = match ioctl_ifindex(iface) {
+        Some(ifindex) => ifindex,
+        None => return Err(Error::ArpProbeFailed(format!("{} has no interface index", iface))),
+    };
+    let socket = match PacketSocket::open(ifindex) {
+        Ok(socket) => socket,
+        Err(e) => {
+            if e.0 == libc::EPERM || e.0 == libc::EACCES {
+                return Ok(ConflictCheck::NotSupported("sending ARP probes needs CAP_NET_RAW"
+                    .to_string()));
+            }
+            return Err(Error::ArpProbeFailed(format!("can't open a packet socket on {}: {}",
+                                                     iface,
+                                                     e)));
+        }
+    };
+    if let Err(e) = socket.send(&arp_probe_frame(&mac, ip)) {
+        return Err(Error::ArpProbeFailed(format!("can't send a probe out of {}: {}", iface, e)));
+    }
+    let deadline = Instant::now() + timeout;
+    let mut frame = [0; 1514];
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(ConflictCheck::NoConflict);
+        }
+        match socket.recv(&mut frame, deadline - now) {
+            Ok(len) => {
+                if let Some(other) = parse_arp_conflict(&frame[..len], ip, &mac) {
+                    let other = format_mac(&other).unwrap_or("00:00:00:00:00:00".to_string());
+                    return Ok(ConflictCheck::Conflict(other));
+                }
+            }
+            Err(ref e) if e.0 == libc::EAGAIN || e.0 == libc::EWOULDBLOCK ||
+                          e.0 == libc::EINTR => {}
+            Err(e) => {
+                return Err(Error::ArpProbeFailed(format!("can't read answers on {}: {}",
+                                                         iface,
+                                                         e)))
+            }
+        }
+    }
+}
+
+/// Writes a probe of each size to the connected `stream`, each in a segment of its own, and
+/// waits for the target to acknowledge it, see `sys::path_mtu_probe`.
+///
+/// When a router answers that a probe is too large, the kernel lowers the path MTU of the
+/// connection and sends the probe again in smaller segments, which the target then
+/// acknowledges. The probe only fits if the path MTU is still at least its size.
+pub fn path_mtu_probe(stream: &TcpStream,
+                      interface_mtu: Option<u32>,
+                      deadline: Instant)
+                      -> Result<PathMtuReport> {
+    let fd = stream.as_raw_fd();
+    let (level, discover, mtu, headers) = match stream.peer_addr() {
+        Ok(SocketAddr::V6(_)) => {
+            (libc::IPPROTO_IPV6, IPV6_MTU_DISCOVER, IPV6_MTU, IPV6_TCP_HEADERS)
+        }
+        _ => (libc::IPPROTO_IP, IP_MTU_DISCOVER, IP_MTU, IPV4_TCP_HEADERS),
+    };
+    if let Err(e) = setsockopt_int(fd, level, discover, PMTUDISC_DO) {
+        let reason = format!("can't set the don't fragment bit: {}", e);
+        return Ok(PathMtuReport::NotSupported(reason));
+    }
+    let prepared = stream.set_nodelay(true)
+        .and_then(|_| reset_on_close(stream))
+        .and_then(|_| getsockopt_int(fd, libc::IPPROTO_TCP, TCP_MAXSEG));
+    let mss = match prepared {
+        Ok(mss) => mss as u32,
+        Err(e) => return Err(Error::PathMtuProbeFailed(format!("can't prepare the socket: {}", e))),
+    };
+    // Segments are no larger than the target asked for, whatever the interface could send
+    let ceiling = match interface_mtu {
+        Some(interface_mtu) => cmp::min(interface_mtu, mss + headers),
+        None => mss + headers,
+    };
+    let sizes = probe_sizes(ceiling);
+    let mut largest = None;
+    let mut blackhole = false;
+    let mut writer = stream;
+    for (i, size) in sizes.iter().enumerate() {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        let until = now + (deadline - now) / (sizes.len() - i) as u32;
+        if let Err(e) = writer.write_all(&vec![0; (size - headers) as usize]) {
+            return Err(Error::PathMtuProbeFailed(format!("can't write a probe of {} bytes: {}",
+                                                         size,
+                                                         e)));
+        }
+        if !acknowledged(fd, until) {
+            blackhole = true;
+            break;
+        }
+        match getsockopt_int(fd, level, mtu) {
+            Ok(path_mtu) if (path_mtu as u32) < *size => break,
+            _ => largest = Some(*size),
+        }
+    }
+    Ok(PathMtuReport::Probed {
+        largest: largest,
+        ceiling: ceiling,
+        interface_mtu: interface_mtu,
+        blackhole: blackhole,
+    })
+}
+
+/// Waits until every byte written to the socket `fd` is acknowledged, and returns whether they
+/// were before `until`.
+fn acknowledged(fd: libc::c_int, until: Instant) -> bool {
+    loop {
+        let mut queued: libc::c_int = 0;
+        if unsafe { libc::ioctl(fd, SIOCOUTQ as _, &mut queued) } < 0 {
+            debug!("Can't count the unacknowledged bytes: {}", errno());
+            return false;
+        }
+        if queued == 0 {
+            return true;
+        }
+        if Instant::now() >= until {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(PROBE_POLL_MS));
+    }
+}
+
+/// A raw packet socket receiving the ARP frames of an interface, closed when dropped.
+struct PacketSocket {
+    fd: libc::c_int,
+    ifindex: libc::c_int,
+}
+
+impl PacketSocket {
+    fn open(ifindex: libc::c_int) -> result::Result<PacketSocket, Errno> {
+        let fd = unsafe {
+            libc::socket(libc::AF_PACKET,
+                         libc::SOCK_RAW,
+                         ETHERTYPE_ARP.to_be() as libc::c_int)
+        };
+        if fd < 0 {
+            return Err(errno());
+        }
+        let socket = PacketSocket {
+            fd: fd,
+            ifindex: ifindex,
+        };
+        let addr = socket.address([0; 6]);
+        let rv = unsafe {
+            libc::bind(fd,
+                       &addr as *const libc::sockaddr_ll as *const libc::sockaddr,
+                       mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t)
+        };
+        if rv < 0 { Err(errno()) } else { Ok(socket) }
+    }
+
+    /// Returns the address of the interface's ARP frames to or from a hardware address.
+    fn address(&self, mac: [u8; 6]) -> libc::sockaddr_ll {
+        let mut addr: libc::sockaddr_ll = unsafe { mem::zeroed() };
+        addr.sll_family = libc::AF_PACKET as libc::c_ushort;
+        addr.sll_protocol = ETHERTYPE_ARP.to_be();
+        addr.sll_ifindex = self.ifindex;
+        addr.sll_halen = mac.len() as libc::c_uchar;
+        addr.sll_addr[..mac.len()].copy_from_slice(&mac);
+        addr
+    }
+
+    /// Broadcasts a frame.
+    fn send(&self, frame: &[u8]) -> result::Result<(), Errno> {
+        let addr = self.address([0xff; 6]);
+        let rv = unsafe {
+            libc::sendto(self.fd,
+                         frame.as_ptr() as *const libc::c_void,
+                         frame.len(),
+                         0,
+                         &addr as *const libc::sockaddr_ll as *const libc::sockaddr,
+                         mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t)
+        };
+        if rv < 0 { Err(errno()) } else { Ok(()) }
+    }
+
+    /// Waits up to `timeout` for a frame and returns its length.
+    fn recv(&self, buf: &mut [u8], timeout: Duration) -> result::Result<usize, Errno> {
+        // A timeout of zero waits forever, so the shortest is a microsecond
+        let timeout = libc::timeval {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_usec: cmp::max(timeout.subsec_nanos() / 1000, 1) as libc::suseconds_t,
+        };
+        unsafe {
+            if libc::setsockopt(self.fd,
+                                libc::SOL_SOCKET,
+                                libc::SO_RCVTIMEO,
+                                &timeout as *const libc::timeval as *const libc::c_void,
+                                mem::size_of::<libc::timeval>() as libc::socklen_t) < 0 {
+                return Err(errno());
+            }
+            let rv = libc::recv(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0);
+            if rv < 0 { Err(errno()) } else { Ok(rv as usize) }
+        }
+    }
+}
+
+impl Drop for PacketSocket {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Returns the processors of this host, counting only the online CPUs this process's affinity
+/// mask and cgroup cpuset allow it to run on.
+pub fn cpu_info() -> Result<CpuInfo> {
+    let mut logical = try!(unix::online_cpus());
+    if let Some(allowed) = affinity_cpus() {
+        logical = cmp::min(logical, allowed);
+    }
+    Ok(cpu_info_from(&Host, logical))
+}
+
+/// Returns the processors of a host with `allowed` CPUs online and in the affinity mask, which
+/// its cgroup's cpuset may allow fewer of.
+fn cpu_info_from(files: &FsReader, allowed: u32) -> CpuInfo {
+    let mut logical = allowed;
+    let cgroup = files.read_to_string(Path::new(PROC_SELF_CGROUP)).unwrap_or(String::new());
+    if let Some(cpuset) = cgroup_cpus(files, Path::new(SYS_FS_CGROUP), &cgroup) {
+        logical = cmp::min(logical, cpuset);
+    }
+    let topology = files.read_to_string(Path::new(PROC_CPUINFO))
+        .map_or(CpuTopology::default(), |c| parse_cpuinfo(&c));
+    CpuInfo {
+        logical: logical,
+        physical: topology.cores.map(|cores| cmp::min(cores, logical)),
+        model: topology.model,
+    }
+}
+
+/// Returns the space on the filesystem holding `path`, which must exist, and the filesystem's
+/// type from the mount table.
+pub fn disk_usage(path: &Path) -> Result<DiskUsage> {
+    let mut usage = try!(unix::disk_usage(path));
+    // Mount points are absolute and free of symlinks
+    let canonical = fs::canonicalize(path).unwrap_or(path.to_path_buf());
+    usage.fs_type = read_file(PROC_SELF_MOUNTS).and_then(|m| parse_mounts(&m, &canonical));
+    Ok(usage)
+}
+
+/// Returns the mount `path` is on from `/proc/self/mountinfo`. The path must be canonical.
+pub fn mount_for(path: &Path) -> Result<MountInfo> {
+    mount_for_from(&Host, path)
+}
+
+fn mount_for_from(files: &FsReader, path: &Path) -> Result<MountInfo> {
+    let mountinfo = match files.read_to_string(Path::new(PROC_SELF_MOUNTINFO)) {
+        Some(mountinfo) => mountinfo,
+        None => {
+            return Err(Error::MountInfoFailed(format!("{} can't be read", PROC_SELF_MOUNTINFO)))
+        }
+    };
+    parse_mountinfo(&mountinfo, path).ok_or_else(|| {
+        Error::MountInfoFailed(format!("No mount in {} holds {}",
+                                       PROC_SELF_MOUNTINFO,
+                                       path.display()))
+    })
+}
+
+/// Returns the real filesystems in `/proc/self/mountinfo` with the space statvfs(3) reports on
+/// each, see `sys::filesystems`.
+pub fn filesystems(include_tmpfs: bool) -> Result<Vec<FsUsage>> {
+    filesystems_from(&Host, include_tmpfs, &unix::disk_usage)
+}
+
+fn filesystems_from(files: &FsReader,
+                    include_tmpfs: bool,
+                    disk_usage: &Fn(&Path) -> Result<DiskUsage>)
+                    -> Result<Vec<FsUsage>> {
+    let mountinfo = match files.read_to_string(Path::new(PROC_SELF_MOUNTINFO)) {
+        Some(mountinfo) => mountinfo,
+        None => {
+            return Err(Error::MountInfoFailed(format!("{} can't be read", PROC_SELF_MOUNTINFO)))
+        }
+    };
+    let filesystems = parse_mount_table(&mountinfo, include_tmpfs)
+        .into_iter()
+        .map(|mount| {
+            let usage = disk_usage(&mount.mount_point)
+                .map(|mut usage| {
+                    usage.fs_type = Some(mount.fs_type.clone());
+                    usage
+                })
+                .map_err(|e| e.to_string());
+            FsUsage {
+                mount: mount,
+                usage: usage,
+            }
+        })
+        .collect();
+    Ok(filesystems)
+}
+
+/// Returns the container or virtual machine the files of this host show it runs in. A file
+/// which can't be read, such as `/proc/1/cgroup` in a restricted container, is no sign.
+pub fn virtualization() -> Result<Virt> {
+    Ok(virtualization_from(&Host))
+}
+
+fn virtualization_from(files: &FsReader) -> Virt {
+    let init_cgroup = files.read_to_string(Path::new(PROC_1_CGROUP));
+    let dmi_vendor = files.read_to_string(Path::new(DMI_SYS_VENDOR));
+    let dmi_product = files.read_to_string(Path::new(DMI_PRODUCT_NAME));
+    let cpuinfo = files.read_to_string(Path::new(PROC_CPUINFO));
+    let signs = VirtSigns {
+        dockerenv: files.exists(Path::new(DOCKERENV)),
+        init_cgroup: init_cgroup.as_ref().map(|c| c.as_str()),
+        dmi_vendor: dmi_vendor.as_ref().map(|v| v.as_str()),
+        dmi_product: dmi_product.as_ref().map(|p| p.as_str()),
+        proc_xen: files.exists(Path::new(PROC_XEN)),
+        cpuinfo: cpuinfo.as_ref().map(|c| c.as_str()),
+    };
+    detect_virt(&signs)
+}
+
+/// Returns the load average from `/proc/loadavg`, or from getloadavg(3) if `/proc` isn't mounted.
+pub fn loadavg() -> Result<LoadAvg> {
+    match proc_loadavg(&Host) {
+        Some(loadavg) => Ok(loadavg),
+        None => unix::loadavg(),
+    }
+}
+
+fn proc_loadavg(files: &FsReader) -> Option<LoadAvg> {
+    files.read_to_string(Path::new(PROC_LOADAVG)).and_then(|c| parse_proc_loadavg(&c))
+}
+
+/// Returns how many processes `/proc` lists and, given `parent`, how many of them descend from
+/// it, see `sys::proc_counts`.
+pub fn proc_counts(parent: Option<u32>) -> Result<ProcCounts> {
+    let entries = match fs::read_dir(PROC) {
+        Ok(entries) => entries,
+        Err(e) => return Err(Error::SysInfoFailed(format!("Failed to list {}: {}", PROC, e))),
+    };
+    let mut total = 0;
+    let mut processes = vec![];
+    for entry in entries {
+        // An entry which can't be read is a process which exited while it was listed
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        let pid = match entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+        total += 1;
+        if parent.is_some() {
+            let ppid = read_file(entry.path().join("stat")).and_then(|s| parse_proc_pid_ppid(&s));
+            if let Some(ppid) = ppid {
+                processes.push((pid, ppid));
+            }
+        }
+    }
+    let tree = parent.map(|parent| count_descendants(&processes, parent));
+    Ok(ProcCounts {
+        total: total,
+        children: tree.map(|(children, _)| children),
+        descendants: tree.map(|(_, descendants)| descendants),
+    })
+}
+
+/// Returns how many of `processes`, pairs of a pid and its parent's, are children of `parent`,
+/// and how many descend from it.
+fn count_descendants(processes: &[(u32, u32)], parent: u32) -> (u32, u32) {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for &(pid, ppid) in processes.iter() {
+        children.entry(ppid).or_insert(vec![]).push(pid);
+    }
+    let direct = children.get(&parent).map_or(0, |pids| pids.len() as u32);
+    // A pid reused while the table is read could seem to be its own ancestor
+    let mut seen = HashSet::new();
+    seen.insert(parent);
+    let mut pending = vec![parent];
+    while let Some(pid) = pending.pop() {
+        if let Some(pids) = children.get(&pid) {
+            for child in pids.iter() {
+                if seen.insert(*child) {
+                    pending.push(*child);
+                }
+            }
+        }
+    }
+    (direct, seen.len() as u32 - 1)
+}
+
+/// Returns the status of SELinux and AppArmor, see `sys::lsm_status`.
+pub fn lsm_status() -> Result<LsmStatus> {
+    Ok(lsm_status_from(&Host, &Host))
+}
+
+fn lsm_status_from(files: &FsReader, runner: &CommandRunner) -> LsmStatus {
+    let selinux = match files.read_to_string(Path::new(SELINUX_ENFORCE))
+        .and_then(|enforce| parse_selinux_enforce(&enforce)) {
+        Some(mode) => mode,
+        None => {
+            runner.run("getenforce", &[])
+                .ok()
+                .and_then(|output| parse_getenforce(&output))
+                .unwrap_or(SelinuxMode::Disabled)
+        }
+    };
+    let apparmor_profiles = files.read_to_string(Path::new(APPARMOR_PROFILES))
+        .map(|profiles| parse_apparmor_profiles(&profiles));
+    // Only root may read the profiles on some kernels, while the module still shows it is on
+    let apparmor = apparmor_profiles.is_some() || files.exists(Path::new(APPARMOR_SECURITYFS)) ||
+                   files.read_to_string(Path::new(APPARMOR_ENABLED))
+        .map_or(false, |enabled| enabled.trim() == "Y");
+    LsmStatus {
+        selinux: selinux,
+        apparmor: apparmor,
+        apparmor_profiles: apparmor_profiles,
+    }
+}
+
+/// Returns the CPU time counters of `/proc/stat`.
+pub fn cpu_stat() -> Result<CpuStat> {
+    cpu_stat_from(&Host)
+}
+
+fn cpu_stat_from(files: &FsReader) -> Result<CpuStat> {
+    match files.read_to_string(Path::new(PROC_STAT)) {
+        Some(contents) => {
+            parse_proc_stat(&contents).ok_or(Error::ProcParse {
+                path: PROC_STAT.to_string(),
+                reason: "It has no cpu line of four counters or more".to_string(),
+            })
+        }
+        None => Err(Error::CpuInfoFailed(format!("{} can't be read", PROC_STAT))),
+    }
+}
+
+/// Returns the time since boot from `/proc/uptime`, or from sysinfo(2) if `/proc` isn't mounted.
+pub fn uptime() -> Result<Duration> {
+    if let Some(uptime) = proc_uptime(&Host) {
+        return Ok(uptime);
+    }
+    let mut info: SysinfoUptime = unsafe { mem::zeroed() };
+    if unsafe { sysinfo(&mut info) } != 0 {
+        let errno = errno();
+        let code = errno.0 as i32;
+        return Err(Error::UptimeFailed(format!("Error {} when calling sysinfo: {}", code, errno)));
+    }
+    Ok(Duration::from_secs(info.uptime as u64))
+}
+
+fn proc_uptime(files: &FsReader) -> Option<Duration> {
+    files.read_to_string(Path::new(PROC_UPTIME)).and_then(|c| parse_proc_uptime(&c))
+}
+
+/// Returns the bits of entropy in the kernel's pool from `/proc/sys/kernel/random/entropy_avail`.
+pub fn entropy_available() -> Result<u32> {
+    entropy_from(&Host)
+}
+
+fn entropy_from(files: &FsReader) -> Result<u32> {
+    match files.read_to_string(Path::new(PROC_ENTROPY_AVAIL)) {
+        Some(contents) => {
+            contents.trim().parse().map_err(|_| {
+                Error::ProcParse {
+                    path: PROC_ENTROPY_AVAIL.to_string(),
+                    reason: format!("It holds {:?}, expected a number", contents.trim()),
+                }
+            })
+        }
+        None => Err(Error::EntropyFailed(format!("{} can't be read", PROC_ENTROPY_AVAIL))),
+    }
+}
+
+/// Returns whether the kernel considers the clock synchronized, from the clock state adjtimex(2)
+/// returns when asked to change nothing.
+pub fn ntp_synchronized() -> Option<bool> {
+    let mut timex: Timex = unsafe { mem::zeroed() };
+    match unsafe { adjtimex(&mut timex) } {
+        -1 => {
+            debug!("adjtimex failed, err={}", errno());
+            None
+        }
+        state => Some(state != TIME_ERROR),
+    }
+}
+
+/// Returns the memory of this host, limited to the memory limit of this process's cgroup.
+pub fn mem_info() -> Result<MemInfo> {
+    mem_info_from(&Host)
+}
+
+fn mem_info_from(files: &FsReader) -> Result<MemInfo> {
+    let contents = match files.read_to_string(Path::new(PROC_MEMINFO)) {
+        Some(contents) => contents,
+        None => return Err(Error::FileNotFound(PROC_MEMINFO.to_string())),
+    };
+    let mut mem = match parse_meminfo(&contents) {
+        Some(mem) => mem,
+        None => {
+            return Err(Error::ProcParse {
+                path: PROC_MEMINFO.to_string(),
+                reason: "It has no MemTotal".to_string(),
+            })
+        }
+    };
+    let cgroup = files.read_to_string(Path::new(PROC_SELF_CGROUP)).unwrap_or(String::new());
+    if let Some(limit) = cgroup_memory(files, Path::new(SYS_FS_CGROUP), &cgroup) {
+        limit_memory(&mut mem, &limit);
+    }
+    Ok(mem)
+}
+
+/// Returns the huge pages of this host, see `sys::hugepage_info`.
+pub fn hugepage_info() -> Result<HugepageInfo> {
+    Ok(hugepage_info_from(&Host))
+}
+
+fn hugepage_info_from(files: &FsReader) -> HugepageInfo {
+    HugepageInfo {
+        transparent: files.read_to_string(Path::new(THP_ENABLED))
+            .and_then(|enabled| parse_bracketed_choice(&enabled)),
+        reserved: files.read_to_string(Path::new(PROC_NR_HUGEPAGES))
+            .and_then(|reserved| reserved.trim().parse().ok()),
+        size_kb: files.read_to_string(Path::new(PROC_MEMINFO))
+            .and_then(|contents| parse_hugepage_size(&contents)),
+    }
+}
+
+/// The memory limit of a cgroup and how much of it is used, in kilobytes.
+#[derive(Debug, PartialEq, Eq)]
+struct CgroupMemory {
+    limit_kb: u64,
+    usage_kb: Option<u64>,
+}
+
+/// Returns the memory limit of this process's cgroup, given the cgroup filesystem and the
+/// contents of `/proc/self/cgroup`, or `None` if it has no limit.
+fn cgroup_memory(files: &FsReader, root: &Path, proc_self_cgroup: &str) -> Option<CgroupMemory> {
+    let limit = cgroup_file(files,
+                            root,
+                            proc_self_cgroup,
+                            "memory",
+                            "memory.limit_in_bytes",
+                            "memory.max")
+        .and_then(|limit| parse_memory_limit(&limit));
+    limit.map(|limit| {
+        let usage = cgroup_file(files,
+                                root,
+                                proc_self_cgroup,
+                                "memory",
+                                "memory.usage_in_bytes",
+                                "memory.current")
+            .and_then(|usage| usage.trim().parse::<u64>().ok());
+        CgroupMemory {
+            limit_kb: limit / 1024,
+            usage_kb: usage.map(|usage| usage / 1024),
+        }
+    })
+}
+
+/// Lowers the memory of the host to a cgroup's limit, if the limit is lower. v1 reports no limit
+/// as the largest page aligned number, which is never lower.
+fn limit_memory(mem: &mut MemInfo, cgroup: &CgroupMemory) {
+    if cgroup.limit_kb >= mem.total_kb {
+        return;
+    }
+    mem.total_kb = cgroup.limit_kb;
+    let unused = cgroup.limit_kb.saturating_sub(cgroup.usage_kb.unwrap_or(0));
+    mem.available_kb = Some(mem.available_kb
+        .map_or(unused, |available| cmp::min(available, unused)));
+    mem.free_kb = mem.free_kb.map(|free| cmp::min(free, unused));
+}
+
+/// Returns the number of CPUs in this process's affinity mask, as set by taskset(1) or
+/// `docker run --cpuset-cpus`.
+fn affinity_cpus() -> Option<u32> {
+    let mut mask = [0u64; CPU_SETSIZE / 64];
+    let rv = unsafe { sched_getaffinity(0, mem::size_of_val(&mask), mask.as_mut_ptr()) };
+    if rv < 0 {
+        return None;
+    }
+    match mask.iter().fold(0, |count, word| count + word.count_ones()) {
+        0 => None,
+        count => Some(count),
+    }
+}
+
+/// Returns the number of CPUs in the cpuset of this process's cgroup, given the cgroup
+/// filesystem and the contents of `/proc/self/cgroup`.
+fn cgroup_cpus(files: &FsReader, root: &Path, proc_self_cgroup: &str) -> Option<u32> {
+    cgroup_file(files,
+                root,
+                proc_self_cgroup,
+                "cpuset",
+                "cpuset.cpus",
+                "cpuset.cpus.effective")
+        .and_then(|cpus| parse_cpu_list(&cpus))
+}
+
+/// Returns the limits of this process's cgroup, or `None` if it has none or isn't in one.
+///
+/// # Errors
+///
+/// * A limit of the cgroup is malformed
+pub fn cgroup_limits() -> Result<Option<CgroupLimits>> {
+    match Host.read_to_string(Path::new(PROC_SELF_CGROUP)) {
+        Some(cgroup) => cgroup_limits_from(&Host, Path::new(SYS_FS_CGROUP), &cgroup),
+        None => Ok(None),
+    }
+}
+
+/// Returns the limits of this process's cgroup, given the cgroup filesystem and the contents of
+/// `/proc/self/cgroup`. v1 limits are read from the hierarchy of their controller, which in
+/// hybrid mode wins over the unified hierarchy, and v2 limits from the unified cgroup.
+fn cgroup_limits_from(files: &FsReader,
+                      root: &Path,
+                      proc_self_cgroup: &str)
+                      -> Result<Option<CgroupLimits>> {
+    let cpus = match cgroup_path_file(files,
+                                      root,
+                                      proc_self_cgroup,
+                                      "cpu",
+                                      "cpu.cfs_quota_us",
+                                      "cpu.max") {
+        Some((ref path, ref quota)) if path.ends_with("cpu.cfs_quota_us") => {
+            let period = path.with_file_name("cpu.cfs_period_us");
+            let period = files.read_to_string(&period).unwrap_or(String::new());
+            try!(cgroup_limit(path, parse_cfs_quota(quota, &period)))
+        }
+        Some((ref path, ref max)) => try!(cgroup_limit(path, parse_cpu_max(max))),
+        None => None,
+    };
+    let memory_bytes = match cgroup_path_file(files,
+                                              root,
+                                              proc_self_cgroup,
+                                              "memory",
+                                              "memory.limit_in_bytes",
+                                              "memory.max") {
+        Some((ref path, ref limit)) => {
+            match try!(cgroup_limit(path, parse_cgroup_max(limit))) {
+                Some(limit) if limit < CGROUP_V1_NO_MEMORY_LIMIT => Some(limit),
+                _ => None,
+            }
+        }
+        None => None,
+    };
+    let pids_max = match cgroup_path_file(files,
+                                          root,
+                                          proc_self_cgroup,
+                                          "pids",
+                                          "pids.max",
+                                          "pids.max") {
+        Some((ref path, ref max)) => try!(cgroup_limit(path, parse_cgroup_max(max))),
+        None => None,
+    };
+    if cpus.is_none() && memory_bytes.is_none() && pids_max.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(CgroupLimits {
+        cpus: cpus,
+        memory_bytes: memory_bytes,
+        pids_max: pids_max,
+    }))
+}
+
+/// Returns a limit a cgroup file was parsed into, or an error naming the file if it was
+/// malformed.
+fn cgroup_limit<T>(path: &Path, parsed: Option<Option<T>>) -> Result<Option<T>> {
+    match parsed {
+        Some(limit) => Ok(limit),
+        None => {
+            Err(Error::ProcParse {
+                path: path.display().to_string(),
+                reason: "It isn't a limit".to_string(),
+            })
+        }
+    }
+}
+
+/// Reads `v1_file` of this process's cgroup of the v1 `controller`, or `v2_file` of its unified
+/// cgroup if the controller isn't mounted as a v1 hierarchy, see `cgroup_path_file`.
+fn cgroup_file(files: &FsReader,
+               root: &Path,
+               proc_self_cgroup: &str,
+               controller: &str,
+               v1_file: &str,
+               v2_file: &str)
+               -> Option<String> {
+    cgroup_path_file(files, root, proc_self_cgroup, controller, v1_file, v2_file)
+        .map(|(_, contents)| contents)
+}
+
+/// Reads `v1_file` of this process's cgroup of the v1 `controller`, or `v2_file` of its unified
+/// cgroup if the controller isn't mounted as a v1 hierarchy, and returns the path it was read
+/// from with its contents. `root` is where the cgroup filesystem is mounted. In a cgroup
+/// namespace, as in a container, the cgroup `/proc/self/cgroup` lists may not exist under
+/// `root`, whose top is then this process's cgroup.
+fn cgroup_path_file(files: &FsReader,
+                    root: &Path,
+                    proc_self_cgroup: &str,
+                    controller: &str,
+                    v1_file: &str,
+                    v2_file: &str)
+                    -> Option<(PathBuf, String)> {
+    let mut unified = None;
+    for line in proc_self_cgroup.lines() {
+        let fields: Vec<&str> = line.splitn(3, ':').collect();
+        if fields.len() != 3 {
+            continue;
+        }
+        let cgroup = fields[2].trim().trim_left_matches('/');
+        if fields[1].split(',').any(|c| c == controller) {
+            let hierarchy = root.join(controller);
+            return read_first(files,
+                              &[hierarchy.join(cgroup).join(v1_file), hierarchy.join(v1_file)]);
+        }
+        if fields[0] == "0" && fields[1].is_empty() {
+            unified = Some(cgroup);
+        }
+    }
+    unified.and_then(|cgroup| {
+        read_first(files,
+                   &[root.join(cgroup).join(v2_file), root.join(v2_file)])
+    })
+}
+
+/// Returns the first of the given files which can be read, with its contents.
+fn read_first(files: &FsReader, paths: &[PathBuf]) -> Option<(PathBuf, String)> {
+    paths.iter()
+        .filter_map(|path| files.read_to_string(path).map(|contents| (path.clone(), contents)))
+        .next()
+}
+
+unsafe fn mac_address(addr: *const libc::sockaddr) -> Option<String> {
+    if (*addr).sa_family as libc::c_int != libc::AF_PACKET {
+        return None;
+    }
+    let sll = &*(addr as *const libc::sockaddr_ll);
+    let len = cmp::min(sll.sll_halen as usize, sll.sll_addr.len());
+    format_mac(&sll.sll_addr[..len])
+}
+
+fn ip_route_ipv4() -> Result<Option<IpAddr>> {
+    ip_route(&Host, &["-o", "route", "get", "8.8.8.8"])
+}
+
+fn ip_route_ipv6() -> Result<Option<IpAddr>> {
+    ip_route(&Host, &["-o", "-6", "route", "get", "2001:4860:4860::8888"])
+}
+
+/// Runs `ip` itself rather than through a shell, so the address is taken from its output by
+/// `parse_ip_route` whatever the shell and its tools on the host format differently.
+fn ip_route(runner: &CommandRunner, args: &[&str]) -> Result<Option<IpAddr>> {
+    let output = try!(runner.run("ip", args));
+    Ok(parse_ip_route(&output))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+    use std::path::{Path, PathBuf};
+    use std::process::{Child, Command};
+    use std::thread;
+
+    use libc;
+
+    use error::Error;
+    use super::super::{CgroupLimits, DiskUsage, HugepageInfo, IpPreference, LsmStatus, MemInfo,
+                       SelinuxMode, THP_NOT_SUPPORTED, Virt, cpu_info, hostname, mem_info};
+    use super::super::source::{FakeHost, Host};
+    use super::{CgroupMemory, cgroup_cpus, cgroup_limits_from, cgroup_memory, count_descendants,
+                cpu_info_from, cpu_stat_from, entropy_from, gateway_from, ip_route,
+                hugepage_info_from, kernel_hostname, limit_memory, lsm_status_from,
+                filesystems_from, mem_info_from,
+                mount_for_from,
+                neighbors_from, netns_id, proc_counts, proc_loadavg, virtualization_from};
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name)
+    }
+
+    #[test]
+    fn hostname_matches_kernel() {
+        let name = hostname().unwrap();
+        assert!(!name.is_empty());
+        if let Some(kernel) = kernel_hostname() {
+            assert_eq!(name, kernel.trim());
+        }
+    }
+
+    #[test]
+    fn cpu_count_is_positive() {
+        let cpus = cpu_info().unwrap();
+        assert!(cpus.logical >= 1);
+        assert!(cpus.physical.map_or(true, |cores| cores <= cpus.logical));
+    }
+
+    #[test]
+    fn cgroup_v1_cpuset_is_counted() {
+        let cgroup = "11:memory:/docker/4f2a\n4:cpuset:/docker/4f2a\n1:name=systemd:/docker/4f2a\n";
+        assert_eq!(cgroup_cpus(&Host, &fixture("cgroup-v1"), cgroup), Some(2));
+    }
+
+    #[test]
+    fn cgroup_v1_namespaced_cpuset_is_counted() {
+        let cgroup = "4:cpuset:/docker/7c9e\n";
+        assert_eq!(cgroup_cpus(&Host, &fixture("cgroup-v1"), cgroup), Some(8));
+    }
+
+    #[test]
+    fn cgroup_v2_cpuset_is_counted() {
+        let cgroup = "0::/system.slice/db.service\n";
+        assert_eq!(cgroup_cpus(&Host, &fixture("cgroup-v2"), cgroup), Some(3));
+    }
+
+    #[test]
+    fn missing_cpuset_is_unlimited() {
+        let cgroup = "3:cpu,cpuacct:/\n";
+        assert_eq!(cgroup_cpus(&Host, &fixture("cgroup-v2"), cgroup), None);
+    }
+
+    #[test]
+    fn memory_total_is_positive() {
+        assert!(mem_info().unwrap().total_kb > 0);
+    }
+
+    #[test]
+    fn cgroup_v1_memory_limit_is_read() {
+        let cgroup = "11:memory:/docker/4f2a\n4:cpuset:/docker/4f2a\n";
+        assert_eq!(cgroup_memory(&Host, &fixture("cgroup-v1"), cgroup),
+                   Some(CgroupMemory {
+                       limit_kb: 524288,
+                       usage_kb: Some(131072),
+                   }));
+    }
+
+    #[test]
+    fn cgroup_v1_unlimited_memory_is_never_lower() {
+        let cgroup = "11:memory:/\n";
+        let limit = cgroup_memory(&Host, &fixture("cgroup-v1"), cgroup).unwrap();
+        let mut mem = host_memory();
+        limit_memory(&mut mem, &limit);
+        assert_eq!(mem, host_memory());
+    }
+
+    #[test]
+    fn cgroup_v2_memory_limit_is_read() {
+        let cgroup = "0::/system.slice/db.service\n";
+        assert_eq!(cgroup_memory(&Host, &fixture("cgroup-v2"), cgroup),
+                   Some(CgroupMemory {
+                       limit_kb: 1048576,
+                       usage_kb: Some(262144),
+                   }));
+    }
+
+    #[test]
+    fn cgroup_v2_max_memory_is_no_limit() {
+        let cgroup = "0::/system.slice/web.service\n";
+        assert_eq!(cgroup_memory(&Host, &fixture("cgroup-v2"), cgroup), None);
+    }
+
+    #[test]
+    fn cgroup_v1_limits_are_read() {
+        let cgroup = "12:pids:/docker/4f2a\n4:memory:/docker/4f2a\n3:cpu,cpuacct:/docker/4f2a\n";
+        assert_eq!(cgroup_limits_from(&Host, &fixture("cgroup-v1"), cgroup).unwrap(),
+                   Some(CgroupLimits {
+                       cpus: Some(1.5),
+                       memory_bytes: Some(536870912),
+                       pids_max: Some(512),
+                   }));
+    }
+
+    #[test]
+    fn cgroup_v1_unlimited_cgroups_have_no_limits() {
+        let cgroup = "12:pids:/\n4:memory:/\n3:cpu,cpuacct:/\n";
+        assert_eq!(cgroup_limits_from(&Host, &fixture("cgroup-v1"), cgroup).unwrap(),
+                   None);
+    }
+
+    #[test]
+    fn cgroup_v2_limits_are_read() {
+        let cgroup = "0::/system.slice/db.service\n";
+        assert_eq!(cgroup_limits_from(&Host, &fixture("cgroup-v2"), cgroup).unwrap(),
+                   Some(CgroupLimits {
+                       cpus: Some(2.0),
+                       memory_bytes: Some(1073741824),
+                       pids_max: Some(1024),
+                   }));
+        let cgroup = "0::/system.slice/web.service\n";
+        assert_eq!(cgroup_limits_from(&Host, &fixture("cgroup-v2"), cgroup).unwrap(),
+                   None);
+    }
+
+    #[test]
+    fn cgroup_hybrid_limits_are_read_from_v1() {
+        let cgroup = "12:pids:/system.slice/db.service\n4:memory:/system.slice/db.service\n\
+                      3:cpu,cpuacct:/system.slice/db.service\n\
+                      1:name=systemd:/system.slice/db.service\n0::/system.slice/db.service\n";
+        let limits = cgroup_limits_from(&Host, &fixture("cgroup-hybrid"), cgroup).unwrap().unwrap();
+        assert_eq!(limits,
+                   CgroupLimits {
+                       cpus: Some(0.5),
+                       memory_bytes: Some(268435456),
+                       pids_max: Some(64),
+                   });
+        assert_eq!(limits.effective_cpus(8), 0.5);
+        assert_eq!(limits.effective_memory_bytes(Some(17179869184)), Some(268435456));
+    }
+
+    #[test]
+    fn malformed_cgroup_limits_are_errors() {
+        let files = FakeHost::new().file("/sys/fs/cgroup/db.service/cpu.max", "lots\n");
+        match cgroup_limits_from(&files, Path::new("/sys/fs/cgroup"), "0::/db.service\n") {
+            Err(Error::ProcParse { ref path, .. }) => {
+                assert_eq!(path, "/sys/fs/cgroup/db.service/cpu.max")
+            }
+            other => panic!("Expected cpu.max to be malformed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn higher_cgroup_limits_are_not_effective() {
+        let limits = CgroupLimits {
+            cpus: Some(16.0),
+            memory_bytes: Some(34359738368),
+            pids_max: None,
+        };
+        assert_eq!(limits.effective_cpus(4), 4.0);
+        assert_eq!(limits.effective_memory_bytes(Some(17179869184)), Some(17179869184));
+        assert_eq!(limits.effective_memory_bytes(None), Some(34359738368));
+    }
+
+    fn host_memory() -> MemInfo {
+        MemInfo {
+            total_kb: 16323820,
+            available_kb: Some(11268476),
+            free_kb: Some(2154900),
+            swap_total_kb: Some(2097148),
+            swap_free_kb: Some(2064380),
+        }
+    }
+
+    #[test]
+    fn lower_cgroup_limit_applies() {
+        let mut mem = host_memory();
+        limit_memory(&mut mem,
+                     &CgroupMemory {
+                         limit_kb: 524288,
+                         usage_kb: Some(131072),
+                     });
+        assert_eq!(mem.total_kb, 524288);
+        assert_eq!(mem.available_kb, Some(393216));
+        assert_eq!(mem.free_kb, Some(393216));
+        assert_eq!(mem.swap_total_kb, Some(2097148));
+    }
+
+    fn cgroup_v2_host() -> FakeHost {
+        FakeHost::new()
+            .file("/proc/meminfo", include_str!("../../../tests/fixtures/meminfo"))
+            .file("/proc/self/cgroup", "0::/system.slice/db.service\n")
+            .file("/sys/fs/cgroup/system.slice/db.service/memory.max", "536870912\n")
+            .file("/sys/fs/cgroup/system.slice/db.service/memory.current", "134217728\n")
+            .file("/sys/fs/cgroup/system.slice/db.service/cpuset.cpus.effective", "0-1\n")
+            .file("/proc/cpuinfo", include_str!("../../../tests/fixtures/cpuinfo"))
+    }
+
+    #[test]
+    fn memory_is_read_from_proc_and_the_cgroup() {
+        let mem = mem_info_from(&cgroup_v2_host()).unwrap();
+        assert_eq!(mem.total_kb, 524288);
+        assert_eq!(mem.available_kb, Some(393216));
+        assert_eq!(mem.swap_total_kb, Some(2097148));
+    }
+
+    #[test]
+    fn missing_meminfo_is_an_error() {
+        match mem_info_from(&FakeHost::new()) {
+            Err(Error::FileNotFound(ref path)) => assert_eq!(path, "/proc/meminfo"),
+            other => panic!("Expected /proc/meminfo to be missing, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn hugepages_are_read_from_sysfs_and_proc() {
+        let files = FakeHost::new()
+            .file("/sys/kernel/mm/transparent_hugepage/enabled", "always [madvise] never\n")
+            .file("/proc/sys/vm/nr_hugepages", "128\n")
+            .file("/proc/meminfo", include_str!("../../../tests/fixtures/meminfo"));
+        let hugepages = hugepage_info_from(&files);
+        assert_eq!(hugepages,
+                   HugepageInfo {
+                       transparent: Some("madvise".to_string()),
+                       reserved: Some(128),
+                       size_kb: Some(2048),
+                   });
+        assert_eq!(hugepages.transparent_setting(), "madvise");
+    }
+
+    #[test]
+    fn missing_transparent_hugepages_are_not_supported() {
+        let files = FakeHost::new().file("/proc/sys/vm/nr_hugepages", "0\n");
+        let hugepages = hugepage_info_from(&files);
+        assert_eq!(hugepages.transparent, None);
+        assert_eq!(hugepages.transparent_setting(), THP_NOT_SUPPORTED);
+        assert_eq!(hugepages.reserved, Some(0));
+        assert_eq!(hugepages.size_kb, None);
+    }
+
+    #[test]
+    fn cpus_are_limited_by_the_cgroup_cpuset() {
+        let cpus = cpu_info_from(&cgroup_v2_host(), 8);
+        assert_eq!(cpus.logical, 2);
+        assert!(cpus.physical.map_or(true, |cores| cores <= 2));
+        assert!(cpus.model.is_some());
+    }
+
+    #[test]
+    fn gateways_are_read_from_the_routing_tables() {
+        let files = FakeHost::new()
+            .file("/proc/net/route", include_str!("../../../tests/fixtures/proc-net-route"));
+        let gateway = gateway_from(&files, IpPreference::Ipv6First).unwrap().unwrap();
+        assert_eq!(gateway.ip, "192.0.2.1".parse::<IpAddr>().unwrap());
+        assert_eq!(gateway.interface, "eth0");
+        assert_eq!(files.reads(),
+                   vec![PathBuf::from("/proc/net/route"), PathBuf::from("/proc/net/ipv6_route")]);
+        assert!(gateway_from(&FakeHost::new(), IpPreference::Ipv4First).is_err());
+    }
+
+    #[test]
+    fn neighbors_are_asked_of_ip_without_the_arp_table() {
+        let host = FakeHost::new()
+            .file("/proc/net/arp", include_str!("../../../tests/fixtures/proc-net-arp"))
+            .output("ip -o neigh show", include_str!("../../../tests/fixtures/ip-neigh"));
+        assert_eq!(neighbors_from(&host, &host).unwrap().len(), 3);
+        assert!(host.runs().is_empty());
+        let host = FakeHost::new()
+            .output("ip -o neigh show", include_str!("../../../tests/fixtures/ip-neigh"));
+        let neighbors = neighbors_from(&host, &host).unwrap();
+        assert_eq!(neighbors.len(), 4);
+        assert_eq!(neighbors[2].ip, "fe80::1".parse::<IpAddr>().unwrap());
+        assert_eq!(host.runs(), vec!["ip -o neigh show".to_string()]);
+        assert!(neighbors_from(&FakeHost::new(), &FakeHost::new()).is_err());
+    }
+
+    #[test]
+    fn containers_are_detected_from_their_files() {
+        let files = FakeHost::new()
+            .file("/proc/1/cgroup",
+                  include_str!("../../../tests/fixtures/proc-1-cgroup-docker"));
+        assert_eq!(virtualization_from(&files), Virt::Docker);
+        assert_eq!(virtualization_from(&FakeHost::new()), Virt::None);
+    }
+
+    #[test]
+    fn mounts_are_read_from_mountinfo() {
+        let files = FakeHost::new()
+            .file("/proc/self/mountinfo",
+                  include_str!("../../../tests/fixtures/proc-self-mountinfo-bind"));
+        let mount = mount_for_from(&files, Path::new("/hab/svc/redis")).unwrap();
+        assert_eq!(mount.mount_point, PathBuf::from("/hab/svc"));
+        assert!(mount_for_from(&FakeHost::new(), Path::new("/")).is_err());
+    }
+
+    #[test]
+    fn filesystems_record_each_failure_of_their_own() {
+        let files = FakeHost::new()
+            .file("/proc/self/mountinfo",
+                  include_str!("../../../tests/fixtures/proc-self-mountinfo-bind"));
+        let usage = |path: &Path| if path == Path::new("/mnt/backup disk") {
+            Err(Error::DiskUsageFailed("Stale file handle".to_string()))
+        } else {
+            Ok(DiskUsage {
+                path: path.to_path_buf(),
+                total_bytes: 1000,
+                available_bytes: 400,
+                used_bytes: 600,
+                inodes_total: None,
+                inodes_free: None,
+                fs_type: None,
+            })
+        };
+        let filesystems = filesystems_from(&files, false, &usage).unwrap();
+        assert_eq!(filesystems.len(), 5);
+        let hab = filesystems[1].usage.as_ref().unwrap();
+        assert_eq!(hab.path, PathBuf::from("/hab"));
+        assert_eq!(hab.fs_type, Some("ext4".to_string()));
+        assert_eq!(filesystems[3].mount.fs_type, "nfs4");
+        assert_eq!(filesystems[3].usage, Err("Stale file handle".to_string()));
+        assert!(filesystems_from(&FakeHost::new(), false, &usage).is_err());
+    }
+
+    #[test]
+    fn loadavg_and_entropy_are_read_from_proc() {
+        let files = FakeHost::new()
+            .file("/proc/loadavg", include_str!("../../../tests/fixtures/proc-loadavg"))
+            .file("/proc/sys/kernel/random/entropy_avail", "3018\n");
+        assert_eq!(proc_loadavg(&files).unwrap().running, Some(2));
+        assert_eq!(entropy_from(&files).unwrap(), 3018);
+        assert_eq!(proc_loadavg(&FakeHost::new()), None);
+        let files = FakeHost::new().file("/proc/sys/kernel/random/entropy_avail", "lots\n");
+        match entropy_from(&files) {
+            Err(Error::ProcParse { ref path, .. }) => {
+                assert_eq!(path, "/proc/sys/kernel/random/entropy_avail")
+            }
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn descendants_are_counted_through_the_tree() {
+        let processes = [(1, 0), (100, 1), (101, 100), (102, 100), (103, 101), (200, 1)];
+        assert_eq!(count_descendants(&processes, 100), (2, 3));
+        assert_eq!(count_descendants(&processes, 1), (2, 5));
+        assert_eq!(count_descendants(&processes, 103), (0, 0));
+        // A pid reused as its own ancestor's isn't followed forever
+        assert_eq!(count_descendants(&[(5, 6), (6, 5)], 5), (1, 1));
+    }
+
+    #[test]
+    fn children_of_this_process_are_counted() {
+        let mut children: Vec<Child> = (0..3)
+            .map(|_| Command::new("sleep").arg("30").spawn().unwrap())
+            .collect();
+        let pid = unsafe { libc::getpid() } as u32;
+        let counts = proc_counts(Some(pid));
+        for child in children.iter_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        let counts = counts.unwrap();
+        // Other tests may have children of their own running
+        assert!(counts.children.unwrap() >= 3);
+        assert!(counts.descendants.unwrap() >= counts.children.unwrap());
+        assert!(counts.total > counts.descendants.unwrap());
+        assert_eq!(proc_counts(None).unwrap().children, None);
+    }
+
+    #[test]
+    fn selinux_mode_is_read_from_selinuxfs_or_getenforce() {
+        let files = FakeHost::new().file("/sys/fs/selinux/enforce", "1\n");
+        assert_eq!(lsm_status_from(&files, &files).selinux, SelinuxMode::Enforcing);
+        assert!(files.runs().is_empty());
+        let host = FakeHost::new().output("getenforce", "Permissive\n");
+        assert_eq!(lsm_status_from(&host, &host).selinux, SelinuxMode::Permissive);
+        let host = FakeHost::new();
+        assert_eq!(lsm_status_from(&host, &host).selinux, SelinuxMode::Disabled);
+    }
+
+    #[test]
+    fn apparmor_profiles_are_read_from_securityfs() {
+        let files = FakeHost::new()
+            .file("/sys/kernel/security/apparmor/profiles",
+                  include_str!("../../../tests/fixtures/apparmor-profiles"));
+        let status = lsm_status_from(&files, &files);
+        assert!(status.apparmor);
+        assert_eq!(status.apparmor_profiles, Some(7));
+        let files = FakeHost::new().file("/sys/module/apparmor/parameters/enabled", "Y\n");
+        let status = lsm_status_from(&files, &files);
+        assert!(status.apparmor);
+        assert_eq!(status.apparmor_profiles, None);
+        // Without securityfs, as in most containers, AppArmor isn't present
+        let host = FakeHost::new();
+        assert_eq!(lsm_status_from(&host, &host),
+                   LsmStatus {
+                       selinux: SelinuxMode::Disabled,
+                       apparmor: false,
+                       apparmor_profiles: None,
+                   });
+    }
+
+    #[test]
+    fn cpu_stat_is_read_from_proc() {
+        let files = FakeHost::new()
+            .file("/proc/stat", include_str!("../../../tests/fixtures/proc-stat"));
+        assert_eq!(cpu_stat_from(&files).unwrap().cores.len(), 3);
+        match cpu_stat_from(&FakeHost::new().file("/proc/stat", "intr 2063375 17\n")) {
+            Err(Error::ProcParse { ref path, .. }) => assert_eq!(path, "/proc/stat"),
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+        assert!(cpu_stat_from(&FakeHost::new()).is_err());
+    }
+
+    #[test]
+    fn ip_route_asks_for_the_route_to_a_public_address() {
+        let output = "8.8.8.8 via 10.0.0.1 dev eth0 src 10.0.0.5 uid 0 \\    cache \n";
+        let runner = FakeHost::new().output("ip -o route get 8.8.8.8", output);
+        assert_eq!(ip_route(&runner, &["-o", "route", "get", "8.8.8.8"]).unwrap(),
+                   Some("10.0.0.5".parse().unwrap()));
+        assert_eq!(runner.runs(), vec!["ip -o route get 8.8.8.8".to_string()]);
+        assert!(ip_route(&FakeHost::new(), &["-o", "route", "get", "8.8.8.8"]).is_err());
+    }
+
+    #[test]
+    fn netns_id_is_the_same_for_every_thread_in_the_namespace() {
+        let id = netns_id().unwrap();
+        assert!(id.chars().all(|c| c.is_digit(10)));
+        assert_eq!(thread::spawn(|| netns_id().unwrap()).join().unwrap(), id);
+    }
+
+    /// Needs CAP_SYS_ADMIN to create a namespace, so only runs with the `privileged` feature.
+    #[test]
+    #[cfg(feature = "privileged")]
+    fn netns_id_changes_in_a_new_namespace() {
+        const CLONE_NEWNET: libc::c_int = 0x40000000;
+        extern "C" {
+            fn unshare(flags: libc::c_int) -> libc::c_int;
+        }
+        let before = netns_id().unwrap();
+        // Only the spawned thread moves to the new namespace
+        let after = thread::spawn(|| {
+                assert_eq!(unsafe { unshare(CLONE_NEWNET) }, 0);
+                netns_id().unwrap()
+            })
+            .join()
+            .unwrap();
+        assert!(after != before);
+        assert_eq!(netns_id().unwrap(), before);
+    }
+}
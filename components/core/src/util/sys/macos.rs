@@ -0,0 +1,342 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Discovery on macOS and the BSDs.
+
+use std::cmp;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream};
+use std::path::Path;
+use std::time::{Duration, Instant};
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+use std::slice;
+
+use libc;
+use time;
+
+use error::{Error, Result};
+use super::{CgroupLimits, ConflictCheck, CpuInfo, CpuStat, DiskUsage, ENTROPY_UNLIMITED, FsUsage,
+            Gateway, HugepageInfo, Interface, IpPreference, IpStrategy, LoadAvg, LsmStatus, MemInfo,
+            MountInfo, Neighbor, ProcCounts, SelinuxMode, Virt, Uname, command_output,
+            hostname_command, ifaddrs_ipv4, ifaddrs_ipv6, is_usable, preferred_interface_ip,
+            udp_ipv4, udp_ipv6};
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+use super::format_mac;
+use super::conn::{ETHERNET_MTU, PathMtuReport};
+use super::parse::{parse_kern_boottime, parse_route_get};
+use super::source::{CommandRunner, Host};
+use super::unix::{self, ifconfig_ipv4, ifconfig_ipv6, reset_on_close};
+
+/// Strategies discovering the IPv4 address, in the order they are tried
+pub static IPV4_STRATEGIES: &'static [IpStrategy] = &[IpStrategy {
+                                                          name: "udp-connect",
+                                                          discover: udp_ipv4,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "getifaddrs",
+                                                          discover: ifaddrs_ipv4,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "route",
+                                                          discover: route_ipv4,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "ifconfig",
+                                                          discover: ifconfig_ipv4,
+                                                      }];
+
+/// Strategies discovering the IPv6 address, in the order they are tried
+pub static IPV6_STRATEGIES: &'static [IpStrategy] = &[IpStrategy {
+                                                          name: "udp-connect ipv6",
+                                                          discover: udp_ipv6,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "getifaddrs ipv6",
+                                                          discover: ifaddrs_ipv6,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "route -inet6",
+                                                          discover: route_ipv6,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "ifconfig ipv6",
+                                                          discover: ifconfig_ipv6,
+                                                      }];
+
+/// Returns the host name from `gethostname`, then from the `hostname` command.
+pub fn hostname() -> Result<String> {
+    match unix::gethostname() {
+        Some(name) => Ok(name),
+        None => {
+            debug!("gethostname failed, falling back to the hostname command");
+            hostname_command()
+        }
+    }
+}
+
+/// Returns every network interface of this host, including loopback interfaces and interfaces
+/// which are down, in the order the system lists them.
+pub fn interfaces() -> Result<Vec<Interface>> {
+    unix::interfaces(mac_address)
+}
+
+pub fn uname() -> Result<Uname> {
+    unix::uname()
+}
+
+/// Returns the processors of this host. macOS names its processors in `machdep.cpu.brand_string`
+/// and the BSDs in `hw.model`, which on macOS is the model of the Mac instead.
+pub fn cpu_info() -> Result<CpuInfo> {
+    let logical = try!(unix::online_cpus());
+    let physical = sysctl("hw.physicalcpu").and_then(|cores| cores.parse().ok());
+    Ok(CpuInfo {
+        logical: logical,
+        physical: physical.map(|cores| cmp::min(cores, logical)),
+        model: sysctl("machdep.cpu.brand_string").or_else(|| sysctl("hw.model")),
+    })
+}
+
+pub fn disk_usage(path: &Path) -> Result<DiskUsage> {
+    unix::disk_usage(path)
+}
+
+/// Returns the physical memory of this host, which macOS reports in `hw.memsize` and the BSDs in
+/// `hw.physmem`.
+pub fn mem_info() -> Result<MemInfo> {
+    let bytes = sysctl("hw.memsize")
+        .or_else(|| sysctl("hw.physmem"))
+        .and_then(|bytes| bytes.parse::<u64>().ok());
+    match bytes {
+        Some(bytes) => {
+            Ok(MemInfo {
+                total_kb: bytes / 1024,
+                available_kb: None,
+                free_kb: None,
+                swap_total_kb: None,
+                swap_free_kb: None,
+            })
+        }
+        None => Err(Error::MemInfoFailed("sysctl reported no physical memory".to_string())),
+    }
+}
+
+pub fn virtualization() -> Result<Virt> {
+    Ok(Virt::None)
+}
+
+pub fn ntp_synchronized() -> Option<bool> {
+    None
+}
+
+pub fn arp_probe(_: Ipv4Addr, _: &str, _: Duration) -> Result<ConflictCheck> {
+    Ok(ConflictCheck::NotSupported("ARP probes are only sent on Linux".to_string()))
+}
+
+/// Writes a single probe of the interface MTU, or of Ethernet's if it isn't known, ending in
+/// the blank line which ends an HTTP request so that any service answers it, and counts it as
+/// reaching the target if the target answers or closes the connection before the deadline.
+/// How many bytes wait to be acknowledged can't be asked, so smaller sizes aren't probed.
+pub fn path_mtu_probe(stream: &TcpStream,
+                      interface_mtu: Option<u32>,
+                      deadline: Instant)
+                      -> Result<PathMtuReport> {
+    let ceiling = interface_mtu.unwrap_or(ETHERNET_MTU);
+    let headers = match stream.peer_addr() {
+        Ok(SocketAddr::V6(_)) => 60,
+        _ => 40,
+    };
+    let now = Instant::now();
+    if now >= deadline {
+        return Err(Error::PathMtuProbeFailed("no time was left to probe".to_string()));
+    }
+    let mut probe = vec![0; (ceiling - headers) as usize];
+    let end = probe.len() - 4;
+    probe[end..].copy_from_slice(b"\r\n\r\n");
+    let mut socket = stream;
+    let written = stream.set_nodelay(true)
+        .and_then(|_| reset_on_close(stream))
+        .and_then(|_| stream.set_read_timeout(Some(deadline - now)))
+        .and_then(|_| socket.write_all(&probe));
+    if let Err(e) = written {
+        return Err(Error::PathMtuProbeFailed(format!("can't write a probe of {} bytes: {}",
+                                                     ceiling,
+                                                     e)));
+    }
+    let mut answer = [0; 1];
+    let reached = match socket.read(&mut answer) {
+        Ok(_) => true,
+        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock ||
+                      e.kind() == io::ErrorKind::TimedOut => false,
+        // A reset is the target rejecting the probe, which it only does once it has it
+        Err(_) => true,
+    };
+    Ok(PathMtuReport::Probed {
+        largest: if reached { Some(ceiling) } else { None },
+        ceiling: ceiling,
+        interface_mtu: interface_mtu,
+        blackhole: !reached,
+    })
+}
+
+pub fn cgroup_limits() -> Result<Option<CgroupLimits>> {
+    Ok(None)
+}
+
+pub fn proc_counts(_: Option<u32>) -> Result<ProcCounts> {
+    Err(Error::SysInfoFailed("Only the process table of Linux is read".to_string()))
+}
+
+pub fn hugepage_info() -> Result<HugepageInfo> {
+    Ok(HugepageInfo {
+        transparent: None,
+        reserved: None,
+        size_kb: None,
+    })
+}
+
+pub fn lsm_status() -> Result<LsmStatus> {
+    Ok(LsmStatus {
+        selinux: SelinuxMode::Disabled,
+        apparmor: false,
+        apparmor_profiles: None,
+    })
+}
+
+pub fn cpu_stat() -> Result<CpuStat> {
+    Err(Error::CpuInfoFailed("Only the CPU counters of Linux are read".to_string()))
+}
+
+pub fn entropy_available() -> Result<u32> {
+    Ok(ENTROPY_UNLIMITED)
+}
+
+pub fn mount_for(_path: &Path) -> Result<MountInfo> {
+    Err(Error::MountInfoFailed("Only the mount table of Linux is read".to_string()))
+}
+
+pub fn filesystems(_include_tmpfs: bool) -> Result<Vec<FsUsage>> {
+    Err(Error::MountInfoFailed("Only the mount table of Linux is read".to_string()))
+}
+
+pub fn neighbors() -> Result<Vec<Neighbor>> {
+    Err(Error::SysInfoFailed("Only the neighbor table of Linux is read".to_string()))
+}
+
+pub fn netns_id() -> Result<String> {
+    Err(Error::SysInfoFailed("Only Linux has network namespaces".to_string()))
+}
+
+pub fn loadavg() -> Result<LoadAvg> {
+    unix::loadavg()
+}
+
+/// Returns the time since the `kern.boottime` of sysctl(8).
+pub fn uptime() -> Result<Duration> {
+    let booted = sysctl("kern.boottime").and_then(|b| parse_kern_boottime(&b));
+    match booted {
+        Some(booted) => {
+            let secs = time::get_time().sec - booted;
+            Ok(Duration::from_secs(if secs < 0 { 0 } else { secs as u64 }))
+        }
+        None => Err(Error::UptimeFailed("sysctl reported no kern.boottime".to_string())),
+    }
+}
+
+/// Returns the value of a sysctl(8) variable, or `None` if it doesn't exist.
+fn sysctl(name: &str) -> Option<String> {
+    match command_output("sysctl", &["-n", name]) {
+        Ok(ref value) if !value.trim().is_empty() => Some(value.trim().to_string()),
+        _ => None,
+    }
+}
+
+/// Reads the hardware address of a link level `sockaddr_dl`, which follows the interface name
+/// in `sdl_data`.
+#[cfg(any(target_os = "macos", target_os = "freebsd"))]
+unsafe fn mac_address(addr: *const libc::sockaddr) -> Option<String> {
+    if (*addr).sa_family as libc::c_int != libc::AF_LINK {
+        return None;
+    }
+    let sdl = &*(addr as *const libc::sockaddr_dl);
+    let data = sdl.sdl_data.as_ptr().offset(sdl.sdl_nlen as isize) as *const u8;
+    format_mac(slice::from_raw_parts(data, sdl.sdl_alen as usize))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "freebsd")))]
+unsafe fn mac_address(_addr: *const libc::sockaddr) -> Option<String> {
+    None
+}
+
+/// Returns the default route route(8) reports. A failing route(8) is taken to mean there is no
+/// default route, as that is how it reports one missing.
+pub fn default_gateway(preference: IpPreference) -> Result<Option<Gateway>> {
+    Ok(gateway_from(&Host, preference))
+}
+
+fn gateway_from(runner: &CommandRunner, preference: IpPreference) -> Option<Gateway> {
+    let ipv4 = route_gateway(runner, &["-n", "get", "default"]);
+    let ipv6 = route_gateway(runner, &["-n", "get", "-inet6", "default"]);
+    match preference {
+        IpPreference::Ipv4First => ipv4.or(ipv6),
+        IpPreference::Ipv6First => ipv6.or(ipv4),
+    }
+}
+
+fn route_gateway(runner: &CommandRunner, args: &[&str]) -> Option<Gateway> {
+    let output = match runner.run("route", args) {
+        Ok(output) => output,
+        Err(e) => {
+            debug!("route {} failed, err={}", args.join(" "), e);
+            return None;
+        }
+    };
+    let route = parse_route_get(&output);
+    match (route.gateway, route.interface) {
+        (Some(ip), Some(interface)) => {
+            Some(Gateway {
+                ip: ip,
+                interface: interface,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn route_ipv4() -> Result<Option<IpAddr>> {
+    let output = try!(command_output("route", &["-n", "get", "default"]));
+    route_ip(&output, IpPreference::Ipv4First)
+}
+
+fn route_ipv6() -> Result<Option<IpAddr>> {
+    let output = try!(command_output("route", &["-n", "get", "-inet6", "default"]));
+    route_ip(&output, IpPreference::Ipv6First)
+}
+
+/// Returns the address of the default route's interface. route(8) only reports the address
+/// itself on OpenBSD, elsewhere it is looked up by the interface's name.
+fn route_ip(output: &str, family: IpPreference) -> Result<Option<IpAddr>> {
+    let route = parse_route_get(output);
+    if route.if_address.is_some() {
+        return Ok(route.if_address);
+    }
+    let name = match route.interface {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let interfaces = try!(interfaces());
+    Ok(interfaces.iter().find(|i| i.name == name).and_then(|i| {
+        let ips: Vec<IpAddr> = i.addrs.iter().map(|a| a.ip).filter(is_usable).collect();
+        preferred_interface_ip(&ips, family)
+    }))
+}
@@ -0,0 +1,505 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Samples of the facts about this host which change from moment to moment, cheap enough to
+//! take every few seconds.
+//!
+//! A sample asks the kernel for each fact directly rather than going through `snapshot`, so it
+//! never waits for the snapshot's lock or for a detection to finish, and can be taken before
+//! any snapshot is. Each sample reads the load average, the memory info and the space of the
+//! filesystem holding Habitat's files, which takes a few tens of microseconds on Linux.
+//!
+//! How busy the CPUs are is measured over an interval rather than at a moment, by a
+//! `CpuSampler` comparing the counters of `/proc/stat` with those it last read.
+//!
+//! A `MetricsSampler` takes a sample every interval on a thread of its own, keeping the last
+//! ones in a `MetricsHistory` and reporting each to a `MetricsSink`.
+
+use std::collections::VecDeque;
+use std::collections::vec_deque::Iter;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::u32;
+
+use time;
+
+use error::{Error, Result};
+use util::metrics::{self, keys, MetricsSink};
+use util::shutdown::Shutdown;
+use super::{CpuStat, CpuTimes, cpu_stat, data_path, disk_usage, loadavg, mem_info};
+
+/// The metrics of this host at a moment, each `None` if it couldn't be read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricsSample {
+    pub taken: time::Timespec,
+    /// Load average over the last minute. Windows doesn't have one.
+    pub load_one: Option<f64>,
+    /// Bytes of memory which can be allocated without swapping
+    pub mem_available_bytes: Option<u64>,
+    /// Bytes unprivileged users may still write on the filesystem holding Habitat's files, see
+    /// `data_path`
+    pub disk_available_bytes: Option<u64>,
+}
+
+impl MetricsSample {
+    /// Returns the value of one of the metrics of the sample, if it was read.
+    pub fn value(&self, metric: Metric) -> Option<f64> {
+        match metric {
+            Metric::LoadOne => self.load_one,
+            Metric::MemAvailableBytes => self.mem_available_bytes.map(|bytes| bytes as f64),
+            Metric::DiskAvailableBytes => self.disk_available_bytes.map(|bytes| bytes as f64),
+        }
+    }
+}
+
+/// The metrics of a `MetricsSample`, which `MetricsHistory` summarizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    LoadOne,
+    MemAvailableBytes,
+    DiskAvailableBytes,
+}
+
+/// Returns the metrics of this host now. A metric which can't be read is left unset rather than
+/// failing the others.
+///
+/// # Errors
+///
+/// * None of the metrics can be read. The error lists why each failed.
+pub fn metrics_sample() -> Result<MetricsSample> {
+    let taken = time::get_time();
+    let mut failures = vec![];
+    let load_one = match loadavg() {
+        Ok(load) => Some(load.one),
+        Err(e) => {
+            failures.push(format!("load average: {}", e));
+            None
+        }
+    };
+    let mem_available_bytes = match mem_info() {
+        Ok(mem) => mem.available_kb.map(|kb| kb * 1024),
+        Err(e) => {
+            failures.push(format!("memory: {}", e));
+            None
+        }
+    };
+    let disk_available_bytes = match disk_usage(&data_path()) {
+        Ok(disk) => Some(disk.available_bytes),
+        Err(e) => {
+            failures.push(format!("disk: {}", e));
+            None
+        }
+    };
+    if failures.len() == 3 {
+        return Err(Error::SysInfoFailed(format!("No metric could be sampled ({})",
+                                                failures.join(", "))));
+    }
+    Ok(MetricsSample {
+        taken: taken,
+        load_one: load_one,
+        mem_available_bytes: mem_available_bytes,
+        disk_available_bytes: disk_available_bytes,
+    })
+}
+
+/// The last samples taken, oldest first, up to a number of them after which the oldest are
+/// dropped.
+#[derive(Clone, Debug)]
+pub struct MetricsHistory {
+    capacity: usize,
+    samples: VecDeque<MetricsSample>,
+}
+
+impl MetricsHistory {
+    /// Returns an empty history which keeps the last `capacity` samples, at least one.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = if capacity == 0 { 1 } else { capacity };
+        MetricsHistory {
+            capacity: capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Adds a sample, dropping the oldest if the history is full.
+    pub fn push(&mut self, sample: MetricsSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn latest(&self) -> Option<&MetricsSample> {
+        self.samples.back()
+    }
+
+    pub fn samples(&self) -> Iter<MetricsSample> {
+        self.samples.iter()
+    }
+
+    /// Returns the lowest value of a metric in the history, or `None` if no sample read it.
+    pub fn min(&self, metric: Metric) -> Option<f64> {
+        self.values(metric).into_iter().fold(None, |min: Option<f64>, v| {
+            Some(min.map_or(v, |min| min.min(v)))
+        })
+    }
+
+    /// Returns the highest value of a metric in the history, or `None` if no sample read it.
+    pub fn max(&self, metric: Metric) -> Option<f64> {
+        self.values(metric).into_iter().fold(None, |max: Option<f64>, v| {
+            Some(max.map_or(v, |max| max.max(v)))
+        })
+    }
+
+    /// Returns the mean of a metric over the samples which read it, or `None` if none did.
+    pub fn avg(&self, metric: Metric) -> Option<f64> {
+        let values = self.values(metric);
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.iter().fold(0.0, |sum, v| sum + v) / values.len() as f64)
+        }
+    }
+
+    fn values(&self, metric: Metric) -> Vec<f64> {
+        self.samples.iter().filter_map(|sample| sample.value(metric)).collect()
+    }
+}
+
+/// Samples the metrics of this host every interval on a thread of its own, which stops when the
+/// `Shutdown` it was started with is shut down.
+pub struct MetricsSampler {
+    history: Arc<Mutex<MetricsHistory>>,
+}
+
+impl MetricsSampler {
+    /// Starts sampling now and then every `interval`, keeping the last `capacity` samples.
+    /// Sampling which fails is logged and skipped.
+    ///
+    /// # Errors
+    ///
+    /// * The sampling thread could not be started
+    pub fn start(interval: Duration, capacity: usize, shutdown: &Shutdown) -> Result<Self> {
+        MetricsSampler::with_sink(interval, capacity, shutdown, metrics::noop())
+    }
+
+    /// Starts sampling like `start`, reporting the metrics of every sample to `sink` as gauges,
+    /// see `keys`, and every sample which fails as `keys::SYS_SAMPLE_FAILURES`.
+    ///
+    /// # Errors
+    ///
+    /// * The sampling thread could not be started
+    pub fn with_sink(interval: Duration,
+                     capacity: usize,
+                     shutdown: &Shutdown,
+                     sink: Arc<MetricsSink>)
+                     -> Result<Self> {
+        let history = Arc::new(Mutex::new(MetricsHistory::new(capacity)));
+        let thread_history = history.clone();
+        try!(shutdown.spawn("metrics-sampler", move |shutdown| {
+            loop {
+                match metrics_sample() {
+                    Ok(sample) => {
+                        report(&*sink, &sample);
+                        thread_history.lock().expect("Metrics history lock poisoned").push(sample)
+                    }
+                    Err(e) => {
+                        sink.incr(keys::SYS_SAMPLE_FAILURES, 1);
+                        debug!("Skipping a metrics sample, {}", e)
+                    }
+                }
+                if shutdown.sleep(interval) {
+                    return;
+                }
+            }
+        }));
+        Ok(MetricsSampler { history: history })
+    }
+
+    /// Returns the samples taken so far, up to the capacity it was started with.
+    pub fn history(&self) -> MetricsHistory {
+        self.history.lock().expect("Metrics history lock poisoned").clone()
+    }
+}
+
+/// Reports the metrics of the sample which were read as gauges.
+fn report(sink: &MetricsSink, sample: &MetricsSample) {
+    let gauges = [(keys::SYS_LOAD_ONE, Metric::LoadOne),
+                  (keys::SYS_MEM_AVAILABLE_BYTES, Metric::MemAvailableBytes),
+                  (keys::SYS_DISK_AVAILABLE_BYTES, Metric::DiskAvailableBytes)];
+    for &(key, metric) in gauges.iter() {
+        if let Some(value) = sample.value(metric) {
+            sink.gauge(key, value);
+        }
+    }
+}
+
+/// How busy the CPUs of this host were over an interval, in percent of the time of the CPUs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CpuUsage {
+    /// Time between the readings of the counters the usage was computed from
+    pub interval: Duration,
+    /// Percent of the time of all CPUs together they spent running processes or handling
+    /// interrupts, from 0 to 100
+    pub busy_percent: f64,
+    /// Percent of the time they were idle waiting for I/O, which isn't counted as busy
+    pub iowait_percent: f64,
+    /// Percent of the time the hypervisor took for something else, which isn't counted as busy
+    pub steal_percent: f64,
+    /// Busy percent of each CPU online at both readings, with its number
+    pub cores: Vec<(u32, f64)>,
+}
+
+/// Measures how busy the CPUs are between one reading of their counters and the next, the
+/// first of which is taken when the sampler is created.
+#[derive(Debug)]
+pub struct CpuSampler {
+    last: CpuStat,
+    read_at: Instant,
+}
+
+impl CpuSampler {
+    /// Returns a sampler whose first sample covers the time from now.
+    ///
+    /// # Errors
+    ///
+    /// * The CPU counters can't be read, see `cpu_stat`
+    pub fn new() -> Result<Self> {
+        Ok(CpuSampler::from_stat(try!(cpu_stat())))
+    }
+
+    fn from_stat(stat: CpuStat) -> Self {
+        CpuSampler {
+            last: stat,
+            read_at: Instant::now(),
+        }
+    }
+
+    /// Returns how busy the CPUs were since the last sample, or since the sampler was created.
+    ///
+    /// # Errors
+    ///
+    /// * The CPU counters can't be read, see `cpu_stat`
+    pub fn sample(&mut self) -> Result<CpuUsage> {
+        let stat = try!(cpu_stat());
+        Ok(self.advance(stat))
+    }
+
+    fn advance(&mut self, stat: CpuStat) -> CpuUsage {
+        let now = Instant::now();
+        let (busy, iowait, steal) = percentages(&self.last.total, &stat.total);
+        let mut cores = vec![];
+        for &(cpu, ref times) in stat.cores.iter() {
+            // CPUs which came online since the last reading have nothing to compare with
+            if let Some(&(_, ref last)) = self.last.cores.iter().find(|&&(c, _)| c == cpu) {
+                cores.push((cpu, percentages(last, times).0));
+            }
+        }
+        let usage = CpuUsage {
+            interval: now - self.read_at,
+            busy_percent: busy,
+            iowait_percent: iowait,
+            steal_percent: steal,
+            cores: cores,
+        };
+        self.last = stat;
+        self.read_at = now;
+        usage
+    }
+}
+
+/// Returns how busy the CPUs were over the next `interval`, which it sleeps for.
+///
+/// # Errors
+///
+/// * The CPU counters can't be read, see `cpu_stat`
+pub fn cpu_usage(interval: Duration) -> Result<CpuUsage> {
+    let mut sampler = try!(CpuSampler::new());
+    thread::sleep(interval);
+    sampler.sample()
+}
+
+/// Returns the percent of the time between two readings of a CPU's counters it was busy,
+/// waiting for I/O, and had stolen from it.
+fn percentages(before: &CpuTimes, after: &CpuTimes) -> (f64, f64, f64) {
+    let busy = ticks_between(before.user, after.user) + ticks_between(before.nice, after.nice) +
+               ticks_between(before.system, after.system) +
+               ticks_between(before.irq, after.irq) +
+               ticks_between(before.softirq, after.softirq);
+    let idle = ticks_between(before.idle, after.idle);
+    let iowait = ticks_between(before.iowait, after.iowait);
+    let steal = ticks_between(before.steal, after.steal);
+    let total = busy + idle + iowait + steal;
+    if total == 0 {
+        return (0.0, 0.0, 0.0);
+    }
+    let percent = |ticks: u64| ticks as f64 * 100.0 / total as f64;
+    (percent(busy), percent(iowait), percent(steal))
+}
+
+/// Returns how far a counter advanced between two readings. The counters of 32 bit kernels wrap
+/// past `u32::MAX`, which is taken to have happened if the counter went back from the top half
+/// of that range. A counter which went back otherwise, as a CPU's do when it is brought back
+/// online, is taken not to have advanced.
+fn ticks_between(before: u64, after: u64) -> u64 {
+    let max = u32::MAX as u64;
+    if after >= before {
+        after - before
+    } else if before <= max && before > max / 2 {
+        after + (max - before) + 1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::UdpSocket;
+    use std::time::Duration;
+    use std::u32;
+
+    use time;
+
+    use util::metrics::StatsdSink;
+    use super::*;
+    use super::{report, ticks_between};
+    use super::super::{CpuStat, CpuTimes};
+
+    fn sample(secs: i64,
+              load_one: Option<f64>,
+              mem_available_bytes: Option<u64>)
+              -> MetricsSample {
+        MetricsSample {
+            taken: time::Timespec::new(secs, 0),
+            load_one: load_one,
+            mem_available_bytes: mem_available_bytes,
+            disk_available_bytes: Some(1 << 30),
+        }
+    }
+
+    #[test]
+    fn history_drops_the_oldest_samples() {
+        let mut history = MetricsHistory::new(3);
+        assert!(history.is_empty());
+        assert_eq!(history.min(Metric::LoadOne), None);
+        for secs in 0..5 {
+            history.push(sample(secs, Some(secs as f64), None));
+        }
+        assert_eq!(history.len(), 3);
+        let taken: Vec<i64> = history.samples().map(|s| s.taken.sec).collect();
+        assert_eq!(taken, vec![2, 3, 4]);
+        assert_eq!(history.latest().unwrap().taken.sec, 4);
+        assert_eq!(MetricsHistory::new(0).capacity, 1);
+    }
+
+    #[test]
+    fn history_summarizes_the_samples_which_read_a_metric() {
+        let mut history = MetricsHistory::new(10);
+        history.push(sample(0, Some(0.5), Some(4096)));
+        history.push(sample(1, None, Some(1024)));
+        history.push(sample(2, Some(1.5), None));
+        assert_eq!(history.min(Metric::LoadOne), Some(0.5));
+        assert_eq!(history.max(Metric::LoadOne), Some(1.5));
+        assert_eq!(history.avg(Metric::LoadOne), Some(1.0));
+        assert_eq!(history.min(Metric::MemAvailableBytes), Some(1024.0));
+        assert_eq!(history.avg(Metric::MemAvailableBytes), Some(2560.0));
+        assert_eq!(history.max(Metric::DiskAvailableBytes), Some((1u64 << 30) as f64));
+    }
+
+    #[test]
+    fn metrics_are_sampled() {
+        let sample = metrics_sample().unwrap();
+        assert!(sample.disk_available_bytes.is_some());
+        if cfg!(unix) {
+            assert!(sample.load_one.is_some());
+        }
+    }
+
+    fn times(user: u64, idle: u64, iowait: u64, steal: u64) -> CpuTimes {
+        CpuTimes {
+            user: user,
+            idle: idle,
+            iowait: iowait,
+            steal: steal,
+            ..CpuTimes::default()
+        }
+    }
+
+    #[test]
+    fn cpu_usage_is_the_share_of_ticks_busy() {
+        let mut sampler = CpuSampler::from_stat(CpuStat {
+            total: times(100, 100, 0, 0),
+            cores: vec![(0, times(50, 50, 0, 0)), (1, times(50, 50, 0, 0))],
+        });
+        let usage = sampler.advance(CpuStat {
+            total: times(160, 120, 10, 10),
+            cores: vec![(0, times(100, 50, 0, 0)),
+                        (1, times(60, 70, 10, 10)),
+                        (2, times(5, 5, 0, 0))],
+        });
+        assert_eq!(usage.busy_percent, 60.0);
+        assert_eq!(usage.iowait_percent, 10.0);
+        assert_eq!(usage.steal_percent, 10.0);
+        // cpu2 came online in between, so has no usage yet
+        assert_eq!(usage.cores, vec![(0, 100.0), (1, 20.0)]);
+        let usage = sampler.advance(CpuStat {
+            total: times(160, 120, 10, 10),
+            cores: vec![],
+        });
+        assert_eq!(usage.busy_percent, 0.0);
+    }
+
+    #[test]
+    fn samples_are_reported_as_gauges() {
+        let daemon = UdpSocket::bind("127.0.0.1:0").unwrap();
+        daemon.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+        let sink = StatsdSink::new(daemon.local_addr().unwrap(), "").unwrap();
+        report(&sink, &sample(0, Some(0.5), None));
+        let mut buf = [0; 512];
+        let mut datagrams = vec![];
+        for _ in 0..2 {
+            let (len, _) = daemon.recv_from(&mut buf).unwrap();
+            datagrams.push(String::from_utf8(buf[..len].to_vec()).unwrap());
+        }
+        // The memory wasn't read, so it isn't reported
+        assert_eq!(datagrams,
+                   vec!["sys.load.one:0.5|g", "sys.disk.available_bytes:1073741824|g"]);
+    }
+
+    #[test]
+    fn wrapped_counters_advance() {
+        assert_eq!(ticks_between(10, 25), 15);
+        assert_eq!(ticks_between(u32::MAX as u64 - 4, 5), 10);
+        // A CPU brought back online starts counting again
+        assert_eq!(ticks_between(1000, 10), 0);
+        assert_eq!(ticks_between(u32::MAX as u64 + 100, 10), 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn cpu_usage_is_sampled() {
+        let usage = cpu_usage(Duration::from_millis(50)).unwrap();
+        assert!(usage.busy_percent >= 0.0 && usage.busy_percent <= 100.0);
+        assert!(!usage.cores.is_empty());
+        assert!(usage.interval >= Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,3453 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Facts about this host, such as its addresses and its host name.
+//!
+//! The public API is the same on every platform. What differs lives in one module per platform,
+//! `linux`, `macos`, which also serves the BSDs, and `windows`, with the parts they share on Unix
+//! in `unix`. Parsers of command output live in `parse` so their tests run on every platform,
+//! and detection reads files and runs commands through the traits of `source`, so its tests can
+//! hand it fixtures.
+//!
+//! Discovery runs subprocesses on some platforms, so callers which need the facts often should
+//! read them from the cached `snapshot` instead, or, for those which change from moment to
+//! moment, take a sample with `metrics`. Crates built on this one add facts of their own
+//! to the rendering of `[sys]` through the providers of `facts`. Whether the host reaches the
+//! services it depends on is checked by `conn`.
+//!
+//! Network facts describe the network namespace the process runs in, identified by `netns_id`,
+//! so a supervisor in a container sees the container's network rather than the host's.
+
+use std::cmp;
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::fs::{File, read_link};
+use std::io::{self, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::result;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::Duration;
+
+use env as henv;
+use error::{Error, Result};
+use fs;
+use time;
+use util::redact;
+
+mod cache;
+mod cloud;
+mod conn;
+mod facts;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
+mod metrics;
+mod parse;
+mod port;
+mod source;
+mod watch;
+#[cfg(unix)]
+mod unix;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+          target_os = "netbsd", target_os = "openbsd"))]
+mod macos;
+#[cfg(windows)]
+mod windows;
+
+pub use self::cache::{FACT_GROUPS, FactCost, FactGroup, FactSelection, FactSource, HostFacts,
+                      MAX_AGE_ENVVAR, SysInfo, refresh, set_max_age, snapshot};
+pub use self::cloud::{CLOUD_METADATA_ENVVAR, CloudInfo, CloudProvider, cloud_metadata,
+                      cloud_metadata_timeout};
+pub use self::conn::{ConnError, ConnResult, ConnTarget, ConnectivityCheck, PATH_MTU_BUCKETS,
+                     PATH_MTU_ENVVAR, PathMtuReport, connectivity_check, connectivity_report,
+                     path_mtu_probe};
+pub use self::facts::{FactProvider, FactRegistry, merge_facts, register_fact_provider};
+pub use self::metrics::{CpuSampler, CpuUsage, Metric, MetricsHistory, MetricsSample,
+                        MetricsSampler, cpu_usage, metrics_sample};
+pub use self::port::{BindPolicy, advertise_addr, ephemeral_port, listen_addr, port_available,
+                     reserve_port};
+pub use self::watch::{SysChange, SysChangeCallback, SysWatch, watch, watch_with};
+#[cfg(target_os = "linux")]
+pub use self::linux::{IPV4_STRATEGIES, IPV6_STRATEGIES, interfaces, uname};
+#[cfg(target_os = "linux")]
+use self::linux::{arp_probe as platform_arp_probe,
+                  cgroup_limits as platform_cgroup_limits, cpu_info as platform_cpu_info,
+                  cpu_stat as platform_cpu_stat,
+                  default_gateway as platform_default_gateway,
+                  disk_usage as platform_disk_usage,
+                  entropy_available as platform_entropy_available,
+                  filesystems as platform_filesystems, hostname as platform_hostname,
+                  hugepage_info as platform_hugepage_info, lsm_status as platform_lsm_status,
+                  loadavg as platform_loadavg, mem_info as platform_mem_info,
+                  mount_for as platform_mount_for, neighbors as platform_neighbors,
+                  netns_id as platform_netns_id, proc_counts as platform_proc_counts,
+                  ntp_synchronized as platform_ntp_synchronized, uptime as platform_uptime,
+                  virtualization as platform_virtualization};
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+          target_os = "netbsd", target_os = "openbsd"))]
+pub use self::macos::{IPV4_STRATEGIES, IPV6_STRATEGIES, interfaces, uname};
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+          target_os = "netbsd", target_os = "openbsd"))]
+use self::macos::{arp_probe as platform_arp_probe,
+                  cgroup_limits as platform_cgroup_limits, cpu_info as platform_cpu_info,
+                  cpu_stat as platform_cpu_stat,
+                  default_gateway as platform_default_gateway,
+                  disk_usage as platform_disk_usage,
+                  entropy_available as platform_entropy_available,
+                  filesystems as platform_filesystems, hostname as platform_hostname,
+                  hugepage_info as platform_hugepage_info, lsm_status as platform_lsm_status,
+                  loadavg as platform_loadavg, mem_info as platform_mem_info,
+                  mount_for as platform_mount_for, neighbors as platform_neighbors,
+                  netns_id as platform_netns_id, proc_counts as platform_proc_counts,
+                  ntp_synchronized as platform_ntp_synchronized, uptime as platform_uptime,
+                  virtualization as platform_virtualization};
+#[cfg(windows)]
+pub use self::windows::{IPV4_STRATEGIES, IPV6_STRATEGIES, interfaces, uname};
+#[cfg(windows)]
+use self::windows::{arp_probe as platform_arp_probe,
+                    cgroup_limits as platform_cgroup_limits, cpu_info as platform_cpu_info,
+                    cpu_stat as platform_cpu_stat,
+                    default_gateway as platform_default_gateway,
+                    disk_usage as platform_disk_usage,
+                    entropy_available as platform_entropy_available,
+                    filesystems as platform_filesystems, hostname as platform_hostname,
+                    hugepage_info as platform_hugepage_info, lsm_status as platform_lsm_status,
+                    loadavg as platform_loadavg, mem_info as platform_mem_info,
+                    mount_for as platform_mount_for, neighbors as platform_neighbors,
+                    netns_id as platform_netns_id, proc_counts as platform_proc_counts,
+                    ntp_synchronized as platform_ntp_synchronized, uptime as platform_uptime,
+                    virtualization as platform_virtualization};
+#[cfg(unix)]
+use self::unix::{canonical_name, kill_process_group as platform_kill_process_group,
+                 new_process_group as platform_new_process_group,
+                 process_info as platform_process_info,
+                 reverse_lookup as platform_reverse_lookup, rlimits as platform_rlimits};
+#[cfg(windows)]
+use self::windows::{canonical_name, kill_process_group as platform_kill_process_group,
+                    new_process_group as platform_new_process_group,
+                    process_info as platform_process_info,
+                    reverse_lookup as platform_reverse_lookup, rlimits as platform_rlimits};
+use self::parse::{is_loopback, parse_etc_timezone, parse_host_aliases, parse_host_id,
+                  parse_localtime_link, parse_lsb_release, parse_os_release, parse_redhat_release,
+                  parse_resolv_conf, parse_resolv_conf_domain};
+use self::source::{Host, Resolver};
+
+/// Seconds a command run to discover a fact gets to finish before it is killed
+const COMMAND_TIMEOUT_SECS: u64 = 5;
+
+/// Bytes of each of the stdout and stderr of a command which an error about it carries
+const COMMAND_OUTPUT_LIMIT: usize = 2048;
+
+static GOOGLE_DNS: &'static str = "8.8.8.8:53";
+static GOOGLE_DNS6: &'static str = "[2001:4860:4860::8888]:53";
+static ETC_HOSTNAME: &'static str = "/etc/hostname";
+static RESOLV_CONF: &'static str = "/etc/resolv.conf";
+#[cfg(unix)]
+static ETC_HOSTS: &'static str = "/etc/hosts";
+#[cfg(windows)]
+static ETC_HOSTS: &'static str = r"C:\Windows\System32\drivers\etc\hosts";
+/// Release files naming the operating system, in the order they are tried
+static OS_RELEASE: &'static str = "/etc/os-release";
+static USR_LIB_OS_RELEASE: &'static str = "/usr/lib/os-release";
+static LSB_RELEASE: &'static str = "/etc/lsb-release";
+static REDHAT_RELEASE: &'static str = "/etc/redhat-release";
+static LOCALTIME: &'static str = "/etc/localtime";
+static ETC_TIMEZONE: &'static str = "/etc/timezone";
+static ETC_MACHINE_ID: &'static str = "/etc/machine-id";
+static DBUS_MACHINE_ID: &'static str = "/var/lib/dbus/machine-id";
+static PROC_BOOT_ID: &'static str = "/proc/sys/kernel/random/boot_id";
+/// Lists the upstream servers of systemd-resolved, whose stub resolver `/etc/resolv.conf` names
+static RESOLVED_RESOLV_CONF: &'static str = "/run/systemd/resolve/resolv.conf";
+
+/// Environment variable choosing which address family `ip` prefers, `ipv4` or `ipv6`
+pub const IP_PREFERENCE_ENVVAR: &'static str = "HAB_IP_PREFERENCE";
+
+/// Address family preferred when a host has both an IPv4 and an IPv6 address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IpPreference {
+    Ipv4First,
+    Ipv6First,
+}
+
+impl IpPreference {
+    /// Returns the preference set by `IP_PREFERENCE_ENVVAR`, or `Ipv4First` if it isn't set.
+    pub fn from_env() -> Self {
+        match henv::var(IP_PREFERENCE_ENVVAR) {
+            Ok(val) => {
+                match val.to_lowercase().as_str() {
+                    "ipv4" => IpPreference::Ipv4First,
+                    "ipv6" => IpPreference::Ipv6First,
+                    _ => {
+                        warn!("Ignoring {}={}, expected ipv4 or ipv6",
+                              IP_PREFERENCE_ENVVAR,
+                              redact::redact_text(&val));
+                        IpPreference::default()
+                    }
+                }
+            }
+            Err(_) => IpPreference::default(),
+        }
+    }
+}
+
+impl Default for IpPreference {
+    fn default() -> Self {
+        IpPreference::Ipv4First
+    }
+}
+
+/// The addresses other hosts can reach this host on, one per address family.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HostIps {
+    pub ipv4: Option<Ipv4Addr>,
+    pub ipv6: Option<Ipv6Addr>,
+}
+
+impl HostIps {
+    /// Returns the address of the preferred family, or of the other family if the host has no
+    /// address of the preferred one.
+    pub fn primary(&self, preference: IpPreference) -> Option<IpAddr> {
+        let ipv4 = self.ipv4.map(IpAddr::V4);
+        let ipv6 = self.ipv6.map(IpAddr::V6);
+        match preference {
+            IpPreference::Ipv4First => ipv4.or(ipv6),
+            IpPreference::Ipv6First => ipv6.or(ipv4),
+        }
+    }
+}
+
+/// Environment variable selecting which address of this host `ip_for` returns, see `IpSelector`
+pub const LISTEN_IP_FROM_ENVVAR: &'static str = "HAB_LISTEN_IP_FROM";
+
+/// Chooses one of this host's addresses, for hosts with more than one network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IpSelector {
+    /// The address `ip` returns, parsed from `default` or an empty string
+    Default,
+    /// An address of the interface with the given name, parsed from the name
+    Interface(String),
+    /// An address within the network with the given address and prefix length, parsed from
+    /// CIDR notation such as `10.0.0.0/8` or `fd00::/8`
+    Cidr(IpAddr, u32),
+}
+
+impl IpSelector {
+    /// Returns the selector set by `LISTEN_IP_FROM_ENVVAR`, or `Default` if it isn't set.
+    pub fn from_env() -> Result<Self> {
+        match henv::var(LISTEN_IP_FROM_ENVVAR) {
+            Ok(val) => val.parse(),
+            Err(_) => Ok(IpSelector::Default),
+        }
+    }
+
+    fn matches(&self, interface: &Interface, addr: &InterfaceAddr) -> bool {
+        match *self {
+            IpSelector::Default => true,
+            IpSelector::Interface(ref name) => interface.name == *name,
+            IpSelector::Cidr(ref network, prefix_len) => in_network(&addr.ip, network, prefix_len),
+        }
+    }
+}
+
+impl FromStr for IpSelector {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if value.is_empty() || value == "default" {
+            return Ok(IpSelector::Default);
+        }
+        let pos = match value.find('/') {
+            Some(pos) => pos,
+            None => return Ok(IpSelector::Interface(value.to_string())),
+        };
+        let network = match value[..pos].parse::<IpAddr>() {
+            Ok(network) => network,
+            Err(_) => return Err(Error::InvalidIpSelector(value.to_string())),
+        };
+        let max_len = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        match value[pos + 1..].parse::<u32>() {
+            Ok(prefix_len) if prefix_len <= max_len => Ok(IpSelector::Cidr(network, prefix_len)),
+            _ => Err(Error::InvalidIpSelector(value.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for IpSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IpSelector::Default => write!(f, "default"),
+            IpSelector::Interface(ref name) => write!(f, "interface {}", name),
+            IpSelector::Cidr(ref network, prefix_len) => write!(f, "{}/{}", network, prefix_len),
+        }
+    }
+}
+
+/// Returns the address of this host the given selector chooses. If several addresses match,
+/// the first of the family preferred by `IP_PREFERENCE_ENVVAR` is returned. Unspecified and
+/// link-local IPv6 addresses never match.
+///
+/// # Errors
+///
+/// * No address matches the selector. The error lists every interface with its addresses.
+pub fn ip_for(selector: &IpSelector) -> Result<IpAddr> {
+    if *selector == IpSelector::Default {
+        return ip();
+    }
+    select_ip(selector, &try!(interfaces()))
+}
+
+/// Returns the address of the given interfaces the selector chooses, see `ip_for`.
+fn select_ip(selector: &IpSelector, interfaces: &[Interface]) -> Result<IpAddr> {
+    let mut matches = vec![];
+    for interface in interfaces.iter() {
+        for addr in interface.addrs.iter() {
+            if is_selectable(&addr.ip) && selector.matches(interface, addr) {
+                matches.push(addr.ip);
+            }
+        }
+    }
+    let preference = IpPreference::from_env();
+    match matches.iter().find(|ip| is_preferred(ip, preference)).or(matches.first()) {
+        Some(ip) => Ok(*ip),
+        None => {
+            Err(Error::NoMatchingIp(selector.to_string(), describe_interfaces(interfaces)))
+        }
+    }
+}
+
+fn is_selectable(ip: &IpAddr) -> bool {
+    match *ip {
+        IpAddr::V4(ref ip) => !ip.is_unspecified(),
+        IpAddr::V6(ref ip) => !ip.is_unspecified() && !is_link_local(ip),
+    }
+}
+
+fn is_preferred(ip: &IpAddr, preference: IpPreference) -> bool {
+    match (*ip, preference) {
+        (IpAddr::V4(_), IpPreference::Ipv4First) |
+        (IpAddr::V6(_), IpPreference::Ipv6First) => true,
+        _ => false,
+    }
+}
+
+/// True if the first `prefix_len` bits of both addresses are equal.
+fn in_network(ip: &IpAddr, network: &IpAddr, prefix_len: u32) -> bool {
+    let (ip, network) = match (*ip, *network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => (ip.octets().to_vec(), network.octets().to_vec()),
+        (IpAddr::V6(ip), IpAddr::V6(network)) => (ip.octets().to_vec(), network.octets().to_vec()),
+        _ => return false,
+    };
+    let mut bits = prefix_len;
+    for (a, b) in ip.iter().zip(network.iter()) {
+        if bits == 0 {
+            break;
+        }
+        let mask = if bits >= 8 { 0xff } else { 0xffu8 << (8 - bits) };
+        if a & mask != b & mask {
+            return false;
+        }
+        bits = bits.saturating_sub(8);
+    }
+    true
+}
+
+/// Lists interfaces with their addresses, such as `eth0 (10.0.0.5/24), lo (127.0.0.1/8)`.
+fn describe_interfaces(interfaces: &[Interface]) -> String {
+    let described: Vec<String> = interfaces.iter()
+        .map(|i| {
+            let addrs: Vec<String> = i.addrs
+                .iter()
+                .map(|a| format!("{}/{}", a.ip, a.prefix_len))
+                .collect();
+            if addrs.is_empty() {
+                format!("{} (no addresses)", i.name)
+            } else {
+                format!("{} ({})", i.name, addrs.join(", "))
+            }
+        })
+        .collect();
+    if described.is_empty() {
+        "none".to_string()
+    } else {
+        described.join(", ")
+    }
+}
+
+/// Returns the IP address other hosts can reach this host on, preferring the address family
+/// chosen by `IP_PREFERENCE_ENVVAR`. See `ips`.
+pub fn ip() -> Result<IpAddr> {
+    ip_with_preference(IpPreference::from_env())
+}
+
+/// Returns the IP address other hosts can reach this host on, preferring the given address
+/// family. See `ips`.
+pub fn ip_with_preference(preference: IpPreference) -> Result<IpAddr> {
+    match try!(ips()).primary(preference) {
+        Some(ip) => Ok(ip),
+        None => Err(Error::NoOutboundAddr),
+    }
+}
+
+/// Returns the addresses other hosts can reach this host on.
+///
+/// The address of each family is discovered by the first of `IPV4_STRATEGIES` or
+/// `IPV6_STRATEGIES` which finds one. Link-local IPv6 addresses are never used.
+///
+/// # Errors
+///
+/// * The host has no usable address of either family. The error lists why each strategy failed.
+pub fn ips() -> Result<HostIps> {
+    host_ips(&try_strategies(IPV4_STRATEGIES), &try_strategies(IPV6_STRATEGIES))
+}
+
+/// Returns the addresses the strategies tried for each family found, see `ips`.
+fn host_ips(ipv4_attempts: &[IpAttempt], ipv6_attempts: &[IpAttempt]) -> Result<HostIps> {
+    let mut ips = HostIps::default();
+    match found_ip(ipv4_attempts) {
+        Some(IpAddr::V4(ip)) => ips.ipv4 = Some(ip),
+        Some(ip) => debug!("ignoring {}, expected an IPv4 address", ip),
+        None => (),
+    }
+    match found_ip(ipv6_attempts) {
+        Some(IpAddr::V6(ip)) => ips.ipv6 = Some(ip),
+        Some(ip) => debug!("ignoring {}, expected an IPv6 address", ip),
+        None => (),
+    }
+    if ips.ipv4.is_none() && ips.ipv6.is_none() {
+        let failures = ipv4_attempts.iter().chain(ipv6_attempts).map(|a| a.to_string()).collect();
+        return Err(Error::IpDiscoveryFailed(failures));
+    }
+    // An address of one family is a good answer even if the interfaces couldn't be listed to
+    // look for the other, so the other's failures are only logged
+    let families = [("IPv4", ips.ipv4.is_some(), ipv4_attempts),
+                    ("IPv6", ips.ipv6.is_some(), ipv6_attempts)];
+    for &(family, _, attempts) in families.iter().filter(|&&(_, found, _)| !found) {
+        for attempt in attempts.iter() {
+            debug!("no {} address, {}", family, attempt);
+        }
+    }
+    Ok(ips)
+}
+
+/// How the addresses of this host are chosen: what each strategy tried found, and how the scan
+/// of the interfaces scores each of their addresses. `ips` chooses from the same attempts, so
+/// the explanation the selection displays as is what was chosen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpSelection {
+    /// Family the address is chosen from when the host has an address of both
+    pub preference: IpPreference,
+    /// Strategies tried for each family in order, up to the first which found a usable address
+    pub ipv4_attempts: Vec<IpAttempt>,
+    pub ipv6_attempts: Vec<IpAttempt>,
+    /// Addresses of the interfaces in the order they are listed, scored as the strategies
+    /// scanning them rank them. Empty if the interfaces can't be listed.
+    pub candidates: Vec<IpCandidate>,
+}
+
+impl IpSelection {
+    /// Returns the addresses chosen, see `ips`.
+    pub fn ips(&self) -> Result<HostIps> {
+        host_ips(&self.ipv4_attempts, &self.ipv6_attempts)
+    }
+
+    /// Returns the address chosen of the preferred family, see `ip`.
+    pub fn primary(&self) -> Option<IpAddr> {
+        self.ips().ok().and_then(|ips| ips.primary(self.preference))
+    }
+}
+
+impl fmt::Display for IpSelection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let family = match self.preference {
+            IpPreference::Ipv4First => "IPv4",
+            IpPreference::Ipv6First => "IPv6",
+        };
+        match self.primary() {
+            Some(ip) => try!(write!(f, "Chose {}", ip)),
+            None => try!(write!(f, "Found no address")),
+        }
+        try!(writeln!(f, ", preferring {} ({})", family, IP_PREFERENCE_ENVVAR));
+        for &(name, attempts) in [("IPv4", &self.ipv4_attempts), ("IPv6", &self.ipv6_attempts)]
+            .iter() {
+            try!(writeln!(f, "{} strategies, tried until one finds a usable address:", name));
+            for attempt in attempts.iter() {
+                try!(writeln!(f, "  {}", attempt));
+            }
+        }
+        if self.candidates.is_empty() {
+            return write!(f, "No interface addresses could be listed");
+        }
+        try!(write!(f, "Interface addresses, of which the scan takes the highest scoring:"));
+        for candidate in self.candidates.iter() {
+            try!(write!(f, "\n  {}", candidate));
+        }
+        Ok(())
+    }
+}
+
+/// Returns how the addresses of this host are chosen, see `IpSelection`.
+pub fn ip_selection() -> IpSelection {
+    let candidates = match interfaces() {
+        Ok(interfaces) => ip_candidates(&interfaces, &virtual_interface_prefixes()),
+        Err(e) => {
+            debug!("Not listing the candidate addresses, err={}", e);
+            vec![]
+        }
+    };
+    IpSelection {
+        preference: IpPreference::from_env(),
+        ipv4_attempts: try_strategies(IPV4_STRATEGIES),
+        ipv6_attempts: try_strategies(IPV6_STRATEGIES),
+        candidates: candidates,
+    }
+}
+
+/// Returns a description, over several lines, of how the addresses of the current snapshot were
+/// chosen and of the addresses they were chosen from, for troubleshooting an unexpected choice.
+/// It is the selection the addresses were chosen by which is described, see `IpSelection`.
+pub fn explain_ip_selection() -> String {
+    snapshot().ip_selection().to_string()
+}
+
+/// What `arp_probe` found out about another host using an address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConflictCheck {
+    /// No other host answered for the address before the timeout
+    NoConflict,
+    /// Another host, with this hardware address, answered for the address
+    Conflict(String),
+    /// The probe can't be sent, for the given reason, such as a missing capability
+    NotSupported(String),
+}
+
+impl fmt::Display for ConflictCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConflictCheck::NoConflict => write!(f, "no other host answered for the address"),
+            ConflictCheck::Conflict(ref mac) => {
+                write!(f, "the host with hardware address {} answered for the address", mac)
+            }
+            ConflictCheck::NotSupported(ref reason) => write!(f, "no probe was sent: {}", reason),
+        }
+    }
+}
+
+/// Asks the local network, out of the interface named `iface`, whether another host uses `ip`,
+/// such as a virtual machine cloned from this one, and waits up to `timeout` for an answer.
+///
+/// The probe is the ARP request RFC 5227 describes, whose sender address is unset so that it
+/// doesn't claim `ip` itself. It is sent on a raw packet socket, which only Linux offers and
+/// which needs `CAP_NET_RAW`; without them, and for IPv6 addresses, which ARP doesn't cover,
+/// the check is `NotSupported` rather than an error, and returns at once.
+///
+/// # Errors
+///
+/// * The interface has no index, or the probe can't be sent or its answers read
+pub fn arp_probe(ip: IpAddr, iface: &str, timeout: Duration) -> Result<ConflictCheck> {
+    match ip {
+        IpAddr::V4(ip) => platform_arp_probe(ip, iface, timeout),
+        IpAddr::V6(_) => {
+            Ok(ConflictCheck::NotSupported("ARP doesn't cover IPv6 addresses".to_string()))
+        }
+    }
+}
+
+/// A way of discovering the address other hosts can reach this host on.
+pub struct IpStrategy {
+    /// Name the strategy is logged and reported as
+    pub name: &'static str,
+    /// Returns the discovered address, or `None` if the strategy found no address
+    pub discover: fn() -> Result<Option<IpAddr>>,
+}
+
+/// What a strategy tried found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IpOutcome {
+    Found(IpAddr),
+    /// An address `is_usable` rejects, such as a loopback address
+    Unusable(IpAddr),
+    NotFound,
+    /// The strategy failed, for the given reason
+    Failed(String),
+}
+
+/// A strategy tried and what it found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpAttempt {
+    pub strategy: &'static str,
+    pub outcome: IpOutcome,
+}
+
+impl fmt::Display for IpAttempt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.outcome {
+            IpOutcome::Found(ref ip) => write!(f, "{}: found {}", self.strategy, ip),
+            IpOutcome::Unusable(ref ip) => {
+                write!(f, "{}: found unusable address {}", self.strategy, ip)
+            }
+            IpOutcome::NotFound => write!(f, "{}: no address found", self.strategy),
+            IpOutcome::Failed(ref reason) => write!(f, "{}: {}", self.strategy, reason),
+        }
+    }
+}
+
+/// Returns the first usable address found by the given strategies, trying each in order.
+///
+/// # Errors
+///
+/// * No strategy found a usable address. The error carries every strategy's failure reason.
+pub fn discover_ip(strategies: &[IpStrategy]) -> Result<IpAddr> {
+    let attempts = try_strategies(strategies);
+    match found_ip(&attempts) {
+        Some(ip) => Ok(ip),
+        None => Err(Error::IpDiscoveryFailed(attempts.iter().map(|a| a.to_string()).collect())),
+    }
+}
+
+/// Tries the given strategies in order, up to the first which finds a usable address.
+fn try_strategies(strategies: &[IpStrategy]) -> Vec<IpAttempt> {
+    let mut attempts = vec![];
+    for strategy in strategies {
+        let outcome = match (strategy.discover)() {
+            Ok(Some(ip)) if is_usable(&ip) => {
+                debug!("discovered IP address {} with {}", ip, strategy.name);
+                IpOutcome::Found(ip)
+            }
+            Ok(Some(ip)) => IpOutcome::Unusable(ip),
+            Ok(None) => IpOutcome::NotFound,
+            Err(e) => IpOutcome::Failed(e.to_string()),
+        };
+        let found = match outcome {
+            IpOutcome::Found(_) => true,
+            _ => false,
+        };
+        attempts.push(IpAttempt {
+            strategy: strategy.name,
+            outcome: outcome,
+        });
+        if found {
+            break;
+        }
+    }
+    attempts
+}
+
+/// Returns the address the last of the attempts found, if it found one.
+fn found_ip(attempts: &[IpAttempt]) -> Option<IpAddr> {
+    match attempts.last() {
+        Some(&IpAttempt { outcome: IpOutcome::Found(ip), .. }) => Some(ip),
+        _ => None,
+    }
+}
+
+fn udp_ipv4() -> Result<Option<IpAddr>> {
+    routed_ip("0.0.0.0:0", GOOGLE_DNS).map(Some)
+}
+
+fn udp_ipv6() -> Result<Option<IpAddr>> {
+    routed_ip("[::]:0", GOOGLE_DNS6).map(Some)
+}
+
+fn ifaddrs_ipv4() -> Result<Option<IpAddr>> {
+    interface_ips(IpPreference::Ipv4First).map(|ips| ips.first().cloned())
+}
+
+fn ifaddrs_ipv6() -> Result<Option<IpAddr>> {
+    interface_ips(IpPreference::Ipv6First).map(|ips| ips.first().cloned())
+}
+
+/// Returns the first address of the preferred family, favouring global addresses over IPv4
+/// link-local ones.
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+          target_os = "netbsd", target_os = "openbsd"))]
+fn preferred_interface_ip(ips: &[IpAddr], family: IpPreference) -> Option<IpAddr> {
+    let mut fallback = None;
+    for ip in ips.iter().filter(|ip| is_preferred(ip, family)) {
+        match *ip {
+            IpAddr::V4(ref v4) if v4.is_link_local() => {
+                fallback = fallback.or(Some(*ip));
+            }
+            _ => return Some(*ip),
+        }
+    }
+    fallback
+}
+
+fn command_output(program: &str, args: &[&str]) -> Result<String> {
+    let timeout = Duration::from_secs(COMMAND_TIMEOUT_SECS);
+    let output = try!(run_with_timeout(program, args, timeout));
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(Error::SysCommandFailed {
+            cmd: command_line(program, args),
+            status: output.status.to_string(),
+            stdout: output_prefix(&output.stdout),
+            stderr: output_prefix(&output.stderr),
+        })
+    }
+}
+
+/// Returns the first `COMMAND_OUTPUT_LIMIT` bytes of what a command wrote, for an error about
+/// it, saying how much was left out.
+fn output_prefix(output: &[u8]) -> String {
+    if output.len() <= COMMAND_OUTPUT_LIMIT {
+        return String::from_utf8_lossy(output).into_owned();
+    }
+    format!("{}... ({} more bytes)",
+            String::from_utf8_lossy(&output[..COMMAND_OUTPUT_LIMIT]),
+            output.len() - COMMAND_OUTPUT_LIMIT)
+}
+
+fn command_line(program: &str, args: &[&str]) -> String {
+    let mut cmd = vec![program];
+    cmd.extend_from_slice(args);
+    cmd.join(" ")
+}
+
+/// Runs a command and returns its output once it exits. A command which is still running after
+/// `timeout` is killed, along with the processes it started on Unix, where they share its process
+/// group, and the error carries what it wrote until then.
+fn run_with_timeout(program: &str, args: &[&str], timeout: Duration) -> Result<Output> {
+    let mut command = Command::new(program);
+    command.args(args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    platform_new_process_group(&mut command);
+    let mut child = try!(command.spawn());
+    let pid = child.id();
+    let stdout = try!(Capture::new(child.stdout.take()));
+    let stderr = try!(Capture::new(child.stderr.take()));
+    let (tx, rx) = mpsc::channel();
+    try!(thread::Builder::new()
+        .name("sys-command".to_string())
+        .spawn(move || {
+            let _ = tx.send(child.wait());
+        }));
+    match rx.recv_timeout(timeout) {
+        Ok(status) => {
+            Ok(Output {
+                status: try!(status),
+                stdout: stdout.finish(),
+                stderr: stderr.finish(),
+            })
+        }
+        Err(_) => {
+            platform_kill_process_group(pid);
+            Err(Error::SysCommandTimeout {
+                cmd: command_line(program, args),
+                timeout: timeout,
+                stdout: output_prefix(&stdout.partial()),
+                stderr: output_prefix(&stderr.partial()),
+            })
+        }
+    }
+}
+
+/// Output of a child process, read on a thread of its own so the child never blocks on a full
+/// pipe and what it wrote is at hand if it has to be killed.
+struct Capture {
+    buf: Arc<Mutex<Vec<u8>>>,
+    reader: Option<thread::JoinHandle<()>>,
+}
+
+impl Capture {
+    fn new<R: Read + Send + 'static>(pipe: Option<R>) -> Result<Capture> {
+        let buf = Arc::new(Mutex::new(vec![]));
+        let reader = match pipe {
+            Some(mut pipe) => {
+                let buf = buf.clone();
+                Some(try!(thread::Builder::new()
+                    .name("sys-command-output".to_string())
+                    .spawn(move || {
+                        let mut chunk = [0; 4096];
+                        loop {
+                            match pipe.read(&mut chunk) {
+                                Ok(0) | Err(_) => break,
+                                Ok(n) => buf.lock().unwrap().extend_from_slice(&chunk[..n]),
+                            }
+                        }
+                    })))
+            }
+            None => None,
+        };
+        Ok(Capture {
+            buf: buf,
+            reader: reader,
+        })
+    }
+
+    /// Returns everything written, once the pipe is closed.
+    fn finish(mut self) -> Vec<u8> {
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+        self.partial()
+    }
+
+    /// Returns what has been written so far.
+    fn partial(&self) -> Vec<u8> {
+        self.buf.lock().unwrap().clone()
+    }
+}
+
+fn routed_ip(bind: &str, addr: &str) -> Result<IpAddr> {
+    let socket = try!(UdpSocket::bind(bind));
+    try!(socket.connect(addr));
+    let ip = try!(socket.local_addr()).ip();
+    if is_usable(&ip) {
+        Ok(ip)
+    } else {
+        Err(Error::NoOutboundAddr)
+    }
+}
+
+/// Environment variable naming more virtual interfaces, as comma separated name prefixes added
+/// to `VIRTUAL_INTERFACE_PREFIXES`
+pub const VIRTUAL_INTERFACES_ENVVAR: &'static str = "HAB_VIRTUAL_INTERFACES";
+
+/// Name prefixes of the bridges and devices container runtimes, hypervisors and overlay networks
+/// create. Their addresses are only reachable from this host and its guests, so scanning the
+/// interfaces never returns them. They can still be chosen by name or network with `IpSelector`.
+pub static VIRTUAL_INTERFACE_PREFIXES: &'static [&'static str] = &["docker", "veth", "br-",
+                                                                   "virbr", "flannel", "cni",
+                                                                   "cali", "weave", "lxcbr",
+                                                                   "lxdbr", "vboxnet", "vmnet"];
+
+/// Returns `VIRTUAL_INTERFACE_PREFIXES` and the prefixes `VIRTUAL_INTERFACES_ENVVAR` adds.
+pub fn virtual_interface_prefixes() -> Vec<String> {
+    let mut prefixes: Vec<String> =
+        VIRTUAL_INTERFACE_PREFIXES.iter().map(|p| p.to_string()).collect();
+    if let Ok(val) = henv::var(VIRTUAL_INTERFACES_ENVVAR) {
+        prefixes.extend(val.split(',')
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .map(|p| p.to_string()));
+    }
+    prefixes
+}
+
+/// Returns the addresses of the given family found by scanning this host's interfaces, best
+/// first, see `rank_interface_ips`.
+fn interface_ips(family: IpPreference) -> Result<Vec<IpAddr>> {
+    let interfaces = try!(interfaces());
+    Ok(rank_interface_ips(&interfaces, family, &virtual_interface_prefixes()))
+}
+
+/// Returns the addresses of the given family other hosts may reach this host on, best first,
+/// which are the candidates `ip_candidates` scores, highest first. Addresses scored the same
+/// keep the order of `interfaces`.
+fn rank_interface_ips(interfaces: &[Interface],
+                      family: IpPreference,
+                      virtual_prefixes: &[String])
+                      -> Vec<IpAddr> {
+    let mut ranked: Vec<IpCandidate> = ip_candidates(interfaces, virtual_prefixes)
+        .into_iter()
+        .filter(|c| c.score.is_some() && is_preferred(&c.ip, family))
+        .collect();
+    // The sort is stable, so equally scored addresses keep their order
+    ranked.sort_by(|a, b| b.score.cmp(&a.score));
+    ranked.into_iter().map(|c| c.ip).collect()
+}
+
+/// Points an address scores for being on an interface with a hardware address, as physical NICs
+/// have and tunnels don't
+const SCORE_HARDWARE_ADDRESS: u32 = 2;
+/// Points an address scores for being private, an RFC 1918 or IPv6 unique local address
+const SCORE_PRIVATE: u32 = 1;
+
+/// How far an address of this host reaches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IpScope {
+    Global,
+    /// Reachable on its own link only, `169.254.0.0/16` or `fe80::/10`
+    LinkLocal,
+    /// Reachable from this host only
+    Loopback,
+}
+
+impl IpScope {
+    pub fn of(ip: &IpAddr) -> IpScope {
+        match *ip {
+            IpAddr::V4(ref ip) if ip.is_loopback() => IpScope::Loopback,
+            IpAddr::V4(ref ip) if ip.is_link_local() => IpScope::LinkLocal,
+            IpAddr::V6(ref ip) if ip.is_loopback() => IpScope::Loopback,
+            IpAddr::V6(ref ip) if is_link_local(ip) => IpScope::LinkLocal,
+            _ => IpScope::Global,
+        }
+    }
+}
+
+impl fmt::Display for IpScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IpScope::Global => write!(f, "global"),
+            IpScope::LinkLocal => write!(f, "link-local"),
+            IpScope::Loopback => write!(f, "loopback"),
+        }
+    }
+}
+
+/// An address of an interface of this host, as the scan of the interfaces sees it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpCandidate {
+    pub ip: IpAddr,
+    /// Name of the interface the address is assigned to
+    pub interface: String,
+    pub scope: IpScope,
+    /// Score the scan ranks the address by, higher first, or `None` if the scan skips it
+    pub score: Option<u32>,
+    /// Why the scan skips the address, or what its score is made up of
+    pub reason: String,
+}
+
+impl fmt::Display for IpCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{} on {}, {}, ", self.ip, self.interface, self.scope));
+        match self.score {
+            Some(score) => write!(f, "score {}: {}", score, self.reason),
+            None => write!(f, "skipped: {}", self.reason),
+        }
+    }
+}
+
+/// Returns every address of the given interfaces, scored the way the strategies scanning the
+/// interfaces rank them.
+///
+/// Interfaces which are down, loopback interfaces and interfaces named with one of
+/// `virtual_prefixes` are skipped, as are link-local addresses and addresses `is_usable`
+/// rejects. An address scores `SCORE_HARDWARE_ADDRESS` if its interface has a hardware address
+/// and `SCORE_PRIVATE` if it is private, so private addresses come before public ones on the
+/// same kind of interface.
+fn ip_candidates(interfaces: &[Interface], virtual_prefixes: &[String]) -> Vec<IpCandidate> {
+    let mut candidates = vec![];
+    for interface in interfaces.iter() {
+        let skipped = if !interface.up {
+            Some("interface is down".to_string())
+        } else if interface.loopback {
+            Some("loopback interface".to_string())
+        } else {
+            virtual_prefixes.iter()
+                .find(|p| interface.name.starts_with(p.as_str()))
+                .map(|p| format!("virtual interface, named {}*", p))
+        };
+        for addr in interface.addrs.iter() {
+            let scope = IpScope::of(&addr.ip);
+            let skipped = skipped.clone().or_else(|| {
+                if scope == IpScope::LinkLocal {
+                    Some("link-local address".to_string())
+                } else if !is_usable(&addr.ip) {
+                    Some("unusable address".to_string())
+                } else {
+                    None
+                }
+            });
+            let (score, reason) = match skipped {
+                Some(reason) => (None, reason),
+                None => {
+                    let mut score = 0;
+                    let mut reasons = vec![];
+                    if interface.mac.is_some() {
+                        score += SCORE_HARDWARE_ADDRESS;
+                        reasons.push("hardware address");
+                    } else {
+                        reasons.push("no hardware address");
+                    }
+                    if is_private(&addr.ip) {
+                        score += SCORE_PRIVATE;
+                        reasons.push("private");
+                    } else {
+                        reasons.push("public");
+                    }
+                    (Some(score), reasons.join(", "))
+                }
+            };
+            candidates.push(IpCandidate {
+                ip: addr.ip,
+                interface: interface.name.clone(),
+                scope: scope,
+                score: score,
+                reason: reason,
+            });
+        }
+    }
+    candidates
+}
+
+/// True for RFC 1918 addresses and IPv6 unique local addresses in `fc00::/7`.
+fn is_private(ip: &IpAddr) -> bool {
+    match *ip {
+        IpAddr::V4(ref ip) => ip.is_private(),
+        IpAddr::V6(ref ip) => ip.segments()[0] & 0xfe00 == 0xfc00,
+    }
+}
+
+/// A network interface of this host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Interface {
+    pub name: String,
+    /// IPv4 and IPv6 addresses assigned to the interface
+    pub addrs: Vec<InterfaceAddr>,
+    /// Hardware address of the interface as lowercase colon separated octets, such as
+    /// `52:54:00:12:34:56`. Virtual interfaces such as tun devices have none.
+    pub mac: Option<String>,
+    /// Maximum transmission unit of the interface in bytes. Only determined on Linux.
+    pub mtu: Option<u32>,
+    pub up: bool,
+    pub loopback: bool,
+}
+
+/// An address assigned to a network interface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InterfaceAddr {
+    pub ip: IpAddr,
+    /// Length of the network prefix, such as 24 for a netmask of 255.255.255.0
+    pub prefix_len: u32,
+}
+
+/// Formats a hardware address as colon separated hex octets. An empty or all zero address, which
+/// loopback interfaces report, is `None`.
+#[cfg(unix)]
+fn format_mac(octets: &[u8]) -> Option<String> {
+    if octets.iter().all(|o| *o == 0) {
+        return None;
+    }
+    let octets: Vec<String> = octets.iter().map(|o| format!("{:02x}", o)).collect();
+    Some(octets.join(":"))
+}
+
+/// Returns the netmask of a network prefix of the given length, of the same family as `ip`.
+pub fn netmask(ip: &IpAddr, prefix_len: u32) -> IpAddr {
+    match *ip {
+        IpAddr::V4(_) => {
+            let mask = if prefix_len == 0 {
+                0
+            } else {
+                u32::max_value() << (32 - cmp::min(prefix_len, 32))
+            };
+            IpAddr::V4(Ipv4Addr::from(mask))
+        }
+        IpAddr::V6(_) => {
+            let mut segments = [0u16; 8];
+            for (i, segment) in segments.iter_mut().enumerate() {
+                let bits = cmp::min(prefix_len.saturating_sub(i as u32 * 16), 16);
+                *segment = if bits == 0 {
+                    0
+                } else {
+                    u16::max_value() << (16 - bits)
+                };
+            }
+            IpAddr::V6(Ipv6Addr::new(segments[0],
+                                     segments[1],
+                                     segments[2],
+                                     segments[3],
+                                     segments[4],
+                                     segments[5],
+                                     segments[6],
+                                     segments[7]))
+        }
+    }
+}
+
+/// Returns the number of leading one bits of a netmask.
+fn prefix_len(mask: &IpAddr) -> u32 {
+    match *mask {
+        IpAddr::V4(ref mask) => u32::from(*mask).count_ones(),
+        IpAddr::V6(ref mask) => mask.octets().iter().fold(0, |n, o| n + o.count_ones()),
+    }
+}
+
+fn is_usable(ip: &IpAddr) -> bool {
+    match *ip {
+        IpAddr::V4(ref ip) => !ip.is_unspecified() && !ip.is_loopback(),
+        IpAddr::V6(ref ip) => !ip.is_unspecified() && !ip.is_loopback() && !is_link_local(ip),
+    }
+}
+
+/// True for addresses in `fe80::/10`, which are only reachable on their own link.
+fn is_link_local(ip: &Ipv6Addr) -> bool {
+    ip.segments()[0] & 0xffc0 == 0xfe80
+}
+
+/// Returns the host name of this host.
+///
+/// How the name is read depends on the platform. Everywhere the output of the `hostname` command
+/// is the last resort.
+pub fn hostname() -> Result<String> {
+    hostname_from(platform_hostname)
+}
+
+/// Returns the host name found by `source`. However the source fails, the error is a
+/// `HostnameFailed` carrying its detail, never an error about addresses.
+fn hostname_from<F>(source: F) -> Result<String>
+    where F: FnOnce() -> Result<String>
+{
+    let name = match source() {
+        Ok(name) => name,
+        Err(Error::HostnameFailed(reason)) => return Err(Error::HostnameFailed(reason)),
+        Err(e) => return Err(Error::HostnameFailed(e.to_string())),
+    };
+    match trim_hostname(&name) {
+        Some(name) => Ok(name),
+        None => Err(Error::HostnameFailed(format!("The name found, {:?}, is empty", name))),
+    }
+}
+
+fn hostname_command() -> Result<String> {
+    command_output("hostname", &[])
+}
+
+/// Strips trailing NULs and whitespace from a host name, returning `None` if nothing is left.
+fn trim_hostname(name: &str) -> Option<String> {
+    let name = name.trim_right_matches('\0').trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Longest host name RFC 1123 allows, without a trailing dot
+const MAX_HOSTNAME_LEN: usize = 253;
+
+/// Longest label of a host name RFC 1123 allows
+const MAX_LABEL_LEN: usize = 63;
+
+/// A host name which follows the rules of RFC 1123, see `validate_hostname`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hostname {
+    name: String,
+}
+
+impl Hostname {
+    /// Returns the host name as it was validated.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the host name in lowercase without a trailing dot, as host names compare equal
+    /// regardless of both.
+    pub fn sanitized(&self) -> String {
+        self.name.trim_right_matches('.').to_lowercase()
+    }
+}
+
+impl fmt::Display for Hostname {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Why a host name breaks the rules of RFC 1123.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HostnameError {
+    Empty,
+    /// The name is longer than 253 characters, without a trailing dot
+    TooLong(usize),
+    /// A label, which the name separates by dots, is empty
+    EmptyLabel,
+    /// A label is longer than 63 characters
+    LabelTooLong(String),
+    /// A label has a character other than an ASCII letter, digit or hyphen
+    InvalidChar(String, char),
+    /// A label starts or ends with a hyphen
+    HyphenAtEdge(String),
+}
+
+impl fmt::Display for HostnameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HostnameError::Empty => write!(f, "host name is empty"),
+            HostnameError::TooLong(len) => {
+                write!(f,
+                       "host name is {} characters long, at most {} are allowed",
+                       len,
+                       MAX_HOSTNAME_LEN)
+            }
+            HostnameError::EmptyLabel => write!(f, "host name has an empty label"),
+            HostnameError::LabelTooLong(ref label) => {
+                write!(f,
+                       "label {} is {} characters long, at most {} are allowed",
+                       label,
+                       label.len(),
+                       MAX_LABEL_LEN)
+            }
+            HostnameError::InvalidChar(ref label, c) => {
+                write!(f, "label {:?} has the invalid character {:?}", label, c)
+            }
+            HostnameError::HyphenAtEdge(ref label) => {
+                write!(f, "label {} starts or ends with a hyphen", label)
+            }
+        }
+    }
+}
+
+impl error::Error for HostnameError {
+    fn description(&self) -> &str {
+        match *self {
+            HostnameError::Empty => "Host name is empty",
+            HostnameError::TooLong(_) => "Host name is too long",
+            HostnameError::EmptyLabel => "Host name has an empty label",
+            HostnameError::LabelTooLong(_) => "Host name label is too long",
+            HostnameError::InvalidChar(_, _) => "Host name has an invalid character",
+            HostnameError::HyphenAtEdge(_) => "Host name label starts or ends with a hyphen",
+        }
+    }
+}
+
+/// Returns the given host name if it follows the rules of RFC 1123: dot separated labels of at
+/// most 63 ASCII letters, digits and hyphens, which don't start or end with a hyphen, and at
+/// most 253 characters in all. A trailing dot, as fully qualified names may have, is allowed.
+/// Uppercase letters are allowed too, see `Hostname::sanitized`.
+pub fn validate_hostname(name: &str) -> result::Result<Hostname, HostnameError> {
+    if name.is_empty() || name == "." {
+        return Err(HostnameError::Empty);
+    }
+    let unrooted = if name.ends_with('.') {
+        &name[..name.len() - 1]
+    } else {
+        name
+    };
+    if unrooted.len() > MAX_HOSTNAME_LEN {
+        return Err(HostnameError::TooLong(unrooted.len()));
+    }
+    for label in unrooted.split('.') {
+        if label.is_empty() {
+            return Err(HostnameError::EmptyLabel);
+        }
+        if let Some(c) = label.chars().find(|c| !is_label_char(*c)) {
+            return Err(HostnameError::InvalidChar(label.to_string(), c));
+        }
+        if label.len() > MAX_LABEL_LEN {
+            return Err(HostnameError::LabelTooLong(label.to_string()));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(HostnameError::HyphenAtEdge(label.to_string()));
+        }
+    }
+    Ok(Hostname { name: name.to_string() })
+}
+
+/// Returns a host name which is safe to use in identifiers for any name, valid or not: the name
+/// in lowercase without surrounding whitespace or a trailing dot, with every character RFC 1123
+/// doesn't allow replaced by a hyphen. A valid name becomes what `Hostname::sanitized` returns.
+pub fn sanitize_hostname(name: &str) -> String {
+    name.trim()
+        .trim_right_matches('.')
+        .to_lowercase()
+        .chars()
+        .map(|c| if is_label_char(c) || c == '.' { c } else { '-' })
+        .collect()
+}
+
+fn is_label_char(c: char) -> bool {
+    (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || (c >= '0' && c <= '9') || c == '-'
+}
+
+/// The default route of this host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gateway {
+    /// Address of the next hop
+    pub ip: IpAddr,
+    /// Name of the interface the route leaves through
+    pub interface: String,
+}
+
+/// Returns the default route of this host, of the address family preferred by
+/// `IP_PREFERENCE_ENVVAR` if it has one of each, or `None` if it has no default route, such as on
+/// an isolated network.
+///
+/// Linux reads the routing tables in `/proc/net`, macOS and the BSDs ask route(8). Windows
+/// doesn't report a gateway yet.
+pub fn default_gateway() -> Result<Option<Gateway>> {
+    platform_default_gateway(IpPreference::from_env())
+}
+
+/// Environment variable enabling the neighbors of `[sys.network.neighbors]`, set to the most of
+/// them to render. The neighbor table of a busy network is large and changes all the time, so
+/// they aren't rendered unless asked for.
+pub const NEIGHBORS_ENVVAR: &'static str = "HAB_SYS_NEIGHBORS";
+
+/// A host on a link of this one which this one has recently exchanged packets with, from the
+/// kernel's ARP and neighbor tables.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Neighbor {
+    pub ip: IpAddr,
+    /// Hardware address, in lowercase
+    pub mac: String,
+    /// Name of the interface the neighbor is reached through
+    pub interface: String,
+    /// State of the entry in lowercase, such as `reachable` or `stale`, see `parse_ip_neigh`
+    pub state: String,
+}
+
+/// Returns the most neighbors to render set by `NEIGHBORS_ENVVAR`, or `None` if they aren't
+/// rendered.
+pub fn neighbors_limit() -> Option<usize> {
+    match henv::var(NEIGHBORS_ENVVAR) {
+        Ok(val) => {
+            match val.parse() {
+                Ok(0) => None,
+                Ok(limit) => Some(limit),
+                Err(_) => {
+                    warn!("Ignoring {}={}, expected a number of neighbors",
+                          NEIGHBORS_ENVVAR,
+                          redact::redact_text(&val));
+                    None
+                }
+            }
+        }
+        Err(_) => None,
+    }
+}
+
+/// Returns the neighbors of this host whose hardware addresses are known, in the order of the
+/// kernel's table. Entries still being resolved and entries which failed to resolve are left
+/// out.
+///
+/// Linux reads `/proc/net/arp`, which only holds IPv4 neighbors, and asks `ip -o neigh` if it
+/// can't be read.
+///
+/// # Errors
+///
+/// * Neither the table nor `ip` can be read, as on platforms other than Linux
+pub fn neighbors() -> Result<Vec<Neighbor>> {
+    platform_neighbors()
+}
+
+/// Returns the id of the network namespace this thread is in, the inode number of
+/// `/proc/thread-self/ns/net`, which is the same for every process in that namespace.
+///
+/// Every network fact, the interfaces, routes, neighbors and addresses, is read within this
+/// namespace, through its sockets and its `/proc/net`, never by entering another namespace or
+/// reading the host's through a path which happens to be mounted. A process in a container with
+/// a network namespace of its own sees the container's network, not the host's. The facts of the
+/// host's network need the process run in the host's namespace, as with `--net=host`.
+///
+/// # Errors
+///
+/// * The namespace link can't be read, as on platforms other than Linux
+pub fn netns_id() -> Result<String> {
+    platform_netns_id()
+}
+
+/// The resolver configuration of this host.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DnsConfig {
+    /// Name servers, in the order they are queried
+    pub nameservers: Vec<IpAddr>,
+    /// Domains short names are searched in
+    pub search: Vec<String>,
+    /// Resolver options, such as `timeout:2`
+    pub options: Vec<String>,
+}
+
+impl DnsConfig {
+    /// True if queries go to the stub resolver of systemd-resolved, which listens on
+    /// `127.0.0.53`.
+    pub fn uses_resolved_stub(&self) -> bool {
+        self.nameservers.contains(&IpAddr::V4(Ipv4Addr::new(127, 0, 0, 53)))
+    }
+}
+
+/// Returns the resolver configuration of `/etc/resolv.conf`, which is empty if the file doesn't
+/// exist. When it names the stub resolver of systemd-resolved, the upstream servers the stub
+/// forwards to are returned instead, if they can be read.
+///
+/// # Errors
+///
+/// * `/etc/resolv.conf` exists but can't be read
+pub fn dns_config() -> Result<DnsConfig> {
+    let mut contents = String::new();
+    match File::open(RESOLV_CONF).and_then(|mut f| f.read_to_string(&mut contents)) {
+        Ok(_) => (),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(DnsConfig::default()),
+        Err(e) => return Err(Error::IO(e)),
+    }
+    let config = parse_resolv_conf(&contents);
+    if config.uses_resolved_stub() {
+        Ok(upstream_dns_config(config, read_file(RESOLVED_RESOLV_CONF)))
+    } else {
+        Ok(config)
+    }
+}
+
+/// Returns the names `/etc/hosts` gives the addresses of this host's interfaces, other than its
+/// loopback addresses and the names of localhost, see `parse_host_aliases`. A host without the
+/// file has none.
+///
+/// # Errors
+///
+/// * The hosts file exists but can't be read
+/// * The interfaces of this host can't be listed
+pub fn host_aliases() -> Result<Vec<String>> {
+    let mut contents = String::new();
+    match File::open(ETC_HOSTS).and_then(|mut f| f.read_to_string(&mut contents)) {
+        Ok(_) => (),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(Error::IO(e)),
+    }
+    let addrs: Vec<IpAddr> = try!(interfaces())
+        .into_iter()
+        .flat_map(|interface| interface.addrs.into_iter().map(|addr| addr.ip))
+        .collect();
+    Ok(parse_host_aliases(&contents, &addrs))
+}
+
+/// Returns the configuration of systemd-resolved's upstream servers, or the stub's configuration
+/// if they are unknown.
+fn upstream_dns_config(stub: DnsConfig, upstream: Option<String>) -> DnsConfig {
+    match upstream.map(|contents| parse_resolv_conf(&contents)) {
+        Some(upstream) => {
+            if upstream.nameservers.is_empty() {
+                stub
+            } else {
+                upstream
+            }
+        }
+        None => stub,
+    }
+}
+
+/// Returns the fully qualified domain name of this host.
+///
+/// The name is the canonical name this host's name resolves to. If that isn't qualified, the
+/// name in `/etc/hostname` is qualified with the domain of `/etc/resolv.conf`. When no domain is
+/// known the short host name is returned rather than an error.
+pub fn fqdn() -> Result<String> {
+    let hostname = try!(hostname());
+    Ok(qualify_hostname(&hostname))
+}
+
+fn qualify_hostname(hostname: &str) -> String {
+    qualify(hostname,
+            canonical_name(hostname),
+            read_file(ETC_HOSTNAME),
+            read_file(RESOLV_CONF).and_then(|c| parse_resolv_conf_domain(&c)))
+}
+
+/// Chooses the fully qualified name of a host from what is known about it, see `fqdn`.
+fn qualify(hostname: &str,
+           canonical: Option<String>,
+           etc_hostname: Option<String>,
+           domain: Option<String>)
+           -> String {
+    if let Some(name) = canonical.and_then(|n| trim_hostname(&n)) {
+        if name.contains('.') && !name.starts_with("localhost") {
+            return name;
+        }
+    }
+    let short = etc_hostname.and_then(|n| trim_hostname(&n)).unwrap_or(hostname.to_string());
+    if short.contains('.') {
+        return short;
+    }
+    match domain {
+        Some(domain) => format!("{}.{}", short, domain),
+        None => short,
+    }
+}
+
+/// What a name of this host resolves to, see `self_resolution`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NameResolution {
+    pub name: String,
+    /// Addresses the resolver gives the name, in its order, none if the name doesn't resolve
+    pub addrs: Vec<IpAddr>,
+    /// Whether any of the addresses is an address of an interface of this host other than a
+    /// loopback address, so other hosts reach this one by the name
+    pub local: bool,
+    /// Whether the name resolves to loopback addresses only, as the host name does by default on
+    /// Debian, whose `/etc/hosts` maps it to `127.0.1.1`
+    pub loopback_only: bool,
+    /// Why the name doesn't resolve
+    pub error: Option<String>,
+}
+
+/// What the host name and the fully qualified domain name of this host resolve to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelfResolution {
+    pub hostname: NameResolution,
+    pub fqdn: NameResolution,
+}
+
+impl SelfResolution {
+    /// Returns whether the host name resolves to an address other hosts can reach this one on.
+    pub fn hostname_resolves_locally(&self) -> bool {
+        self.hostname.local
+    }
+
+    /// Returns whether either name resolves to loopback addresses only, which breaks clustered
+    /// services configured with the name, as every member of the cluster reaches only itself.
+    pub fn loopback_only(&self) -> bool {
+        self.hostname.loopback_only || self.fqdn.loopback_only
+    }
+}
+
+/// Returns what the host name and the fully qualified domain name of this host resolve to,
+/// asked of the system resolver, so `/etc/hosts` answers too. See `fqdn`.
+///
+/// # Errors
+///
+/// * The host name or the interfaces of this host can't be determined
+pub fn self_resolution() -> Result<SelfResolution> {
+    let hostname = try!(hostname());
+    let fqdn = qualify_hostname(&hostname);
+    let interfaces = try!(interfaces());
+    Ok(self_resolution_from(&Host, &hostname, &fqdn, &interfaces))
+}
+
+fn self_resolution_from(resolver: &Resolver,
+                        hostname: &str,
+                        fqdn: &str,
+                        interfaces: &[Interface])
+                        -> SelfResolution {
+    SelfResolution {
+        hostname: name_resolution(resolver, hostname, interfaces),
+        fqdn: name_resolution(resolver, fqdn, interfaces),
+    }
+}
+
+fn name_resolution(resolver: &Resolver, name: &str, interfaces: &[Interface]) -> NameResolution {
+    let (addrs, error) = match resolver.resolve(name) {
+        Ok(addrs) => (addrs, None),
+        Err(e) => (vec![], Some(e.to_string())),
+    };
+    let assigned = |ip: &IpAddr| interfaces.iter().any(|i| i.addrs.iter().any(|a| a.ip == *ip));
+    NameResolution {
+        name: name.to_string(),
+        local: addrs.iter().any(|ip| !is_loopback(ip) && assigned(ip)),
+        loopback_only: !addrs.is_empty() && addrs.iter().all(is_loopback),
+        addrs: addrs,
+        error: error,
+    }
+}
+
+/// Returns the name the PTR record of an address names, which services such as Hadoop expect to
+/// match the name they look the address up by, or `None` if the address has no PTR record or the
+/// lookup doesn't answer within `timeout`. The trailing dot of the name is stripped.
+///
+/// The lookup asks the system resolver, so `/etc/hosts` answers it too. The resolver has no
+/// timeout of its own short of its retries, so the lookup runs on a thread which is left to
+/// finish on its own if the timeout passes first. Windows has no reverse lookup yet.
+///
+/// # Errors
+///
+/// * The thread the lookup runs on can't be started
+pub fn reverse_lookup(ip: IpAddr, timeout: Duration) -> Result<Option<String>> {
+    let (tx, rx) = mpsc::channel();
+    try!(thread::Builder::new()
+        .name("sys-reverse-lookup".to_string())
+        .spawn(move || {
+            let _ = tx.send(platform_reverse_lookup(ip));
+        }));
+    match rx.recv_timeout(timeout) {
+        Ok(name) => Ok(name.and_then(|name| trim_hostname(name.trim_right_matches('.')))),
+        Err(_) => {
+            debug!("Reverse lookup of {} didn't answer in time", ip);
+            Ok(None)
+        }
+    }
+}
+
+/// The processors of this host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CpuInfo {
+    /// Number of CPUs this process may run on
+    pub logical: u32,
+    /// Number of physical cores, at most `logical`, if it is known
+    pub physical: Option<u32>,
+    /// Model name of the processors, such as `Intel(R) Xeon(R) CPU E5-2686 v4 @ 2.30GHz`
+    pub model: Option<String>,
+}
+
+/// Returns the processors of this host.
+///
+/// On Linux the online CPUs are limited to those of this process's CPU affinity mask and of its
+/// cgroup's cpuset, so a service in a container counts only the CPUs it was given. The cores and
+/// model are read from `/proc/cpuinfo`. macOS and the BSDs ask sysctl(8), and Windows only knows
+/// the number of logical CPUs.
+///
+/// # Errors
+///
+/// * The number of online CPUs can't be determined
+pub fn cpu_info() -> Result<CpuInfo> {
+    platform_cpu_info()
+}
+
+/// The memory of this host, in kilobytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemInfo {
+    /// Memory services may use
+    pub total_kb: u64,
+    /// Memory which can be allocated without swapping, if it is known
+    pub available_kb: Option<u64>,
+    /// Memory holding nothing at all, not even the page cache, if it is known
+    pub free_kb: Option<u64>,
+    pub swap_total_kb: Option<u64>,
+    pub swap_free_kb: Option<u64>,
+}
+
+/// Returns the memory of this host.
+///
+/// Linux reads `/proc/meminfo`, lowering the totals to the memory limit of this process's cgroup
+/// when it has one, so a service in a container sees its own budget rather than the host's.
+/// macOS and the BSDs ask sysctl(8) and Windows asks `GlobalMemoryStatusEx`, and neither reports
+/// swap.
+///
+/// # Errors
+///
+/// * The total memory of this host can't be determined
+pub fn mem_info() -> Result<MemInfo> {
+    platform_mem_info()
+}
+
+/// What `HugepageInfo::transparent_setting` returns on kernels without transparent huge pages.
+pub const THP_NOT_SUPPORTED: &'static str = "not_supported";
+
+/// The huge pages of this host, which databases such as PostgreSQL and Redis want set a certain
+/// way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HugepageInfo {
+    /// When transparent huge pages back memory, `always`, `madvise` or `never`, or `None` if the
+    /// kernel doesn't support them
+    pub transparent: Option<String>,
+    /// Number of huge pages reserved, `vm.nr_hugepages`
+    pub reserved: Option<u64>,
+    /// Size of a huge page in kilobytes
+    pub size_kb: Option<u64>,
+}
+
+impl HugepageInfo {
+    /// Returns when transparent huge pages back memory, or `THP_NOT_SUPPORTED`.
+    pub fn transparent_setting(&self) -> &str {
+        self.transparent.as_ref().map_or(THP_NOT_SUPPORTED, |setting| setting.as_str())
+    }
+}
+
+/// Returns the huge pages of this host.
+///
+/// Linux reads the setting of transparent huge pages from
+/// `/sys/kernel/mm/transparent_hugepage/enabled`, the pages reserved from
+/// `/proc/sys/vm/nr_hugepages` and their size from `/proc/meminfo`. Whatever can't be read is
+/// left unset, and other platforms have none of it.
+pub fn hugepage_info() -> Result<HugepageInfo> {
+    platform_hugepage_info()
+}
+
+/// The space on the filesystem holding a path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// Path the filesystem was found by, the nearest existing ancestor of the one asked for
+    pub path: PathBuf,
+    pub total_bytes: u64,
+    /// Bytes unprivileged users may still write, which excludes the blocks reserved for root
+    pub available_bytes: u64,
+    pub used_bytes: u64,
+    /// Inodes of the filesystem and how many of them are free, which is what runs out on hosts
+    /// with many small files. `None` on filesystems which allocate inodes as they go and report
+    /// none, such as btrfs, and on Windows.
+    pub inodes_total: Option<u64>,
+    pub inodes_free: Option<u64>,
+    /// Type of the filesystem, such as `ext4`. Only determined on Linux.
+    pub fs_type: Option<String>,
+}
+
+impl DiskUsage {
+    /// Returns whether at least `threshold_pct` percent of the inodes of the filesystem are
+    /// used, or `false` if it doesn't report its inodes.
+    pub fn inode_pressure(&self, threshold_pct: f64) -> bool {
+        match (self.inodes_total, self.inodes_free) {
+            (Some(total), Some(free)) if total > 0 => {
+                let used = total.saturating_sub(free) as f64;
+                used * 100.0 >= threshold_pct * total as f64
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Returns the space on the filesystem holding `path`. A path which doesn't exist yet, such as a
+/// service directory about to be created, is measured on the filesystem of its nearest existing
+/// ancestor.
+///
+/// # Errors
+///
+/// * Neither the path nor any of its ancestors exist
+/// * The filesystem can't be asked about its space
+pub fn disk_usage(path: &Path) -> Result<DiskUsage> {
+    let existing = try!(nearest_existing(path));
+    platform_disk_usage(&existing)
+}
+
+/// The mount a path is on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MountInfo {
+    /// Where the filesystem is mounted
+    pub mount_point: PathBuf,
+    /// Directory of the filesystem mounted there, `/` unless it is a bind mount
+    pub root: String,
+    /// What is mounted, such as `/dev/sda1`, `backup:/export` or `overlay`
+    pub source: String,
+    /// Type of the filesystem, such as `ext4`
+    pub fs_type: String,
+    /// Whether writes are refused, by the mount or by the filesystem
+    pub read_only: bool,
+    /// Whether programs can't be executed from the mount
+    pub noexec: bool,
+    /// Whether the set-user-id and set-group-id bits of its files are ignored
+    pub nosuid: bool,
+}
+
+/// Returns the mount holding `path`, from `/proc/self/mountinfo`. A path which doesn't exist yet
+/// is looked up by its nearest existing ancestor, with its symlinks resolved.
+///
+/// # Errors
+///
+/// * Neither the path nor any of its ancestors exist
+/// * The mount table can't be read, as on platforms other than Linux, which have none
+pub fn mount_for(path: &Path) -> Result<MountInfo> {
+    let existing = try!(nearest_existing(path));
+    // Mount points are absolute and free of symlinks
+    let canonical = try!(existing.canonicalize());
+    platform_mount_for(&canonical)
+}
+
+/// Types of filesystems `filesystems` leaves out, which hold no files services write, such as
+/// the views of the kernel in `/proc` and `/sys`.
+pub const PSEUDO_FILESYSTEMS: &'static [&'static str] = &["autofs",
+                                                          "binfmt_misc",
+                                                          "bpf",
+                                                          "cgroup",
+                                                          "cgroup2",
+                                                          "configfs",
+                                                          "debugfs",
+                                                          "devpts",
+                                                          "devtmpfs",
+                                                          "efivarfs",
+                                                          "fusectl",
+                                                          "hugetlbfs",
+                                                          "mqueue",
+                                                          "nsfs",
+                                                          "proc",
+                                                          "pstore",
+                                                          "rpc_pipefs",
+                                                          "securityfs",
+                                                          "selinuxfs",
+                                                          "sysfs",
+                                                          "tracefs"];
+
+/// Environment variable which, when set, has `filesystems` include `tmpfs` mounts, whose files
+/// are held in memory.
+pub const FILESYSTEMS_TMPFS_ENVVAR: &'static str = "HAB_SYS_FILESYSTEMS_TMPFS";
+
+/// A mounted filesystem and its space, or why its space couldn't be determined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FsUsage {
+    pub mount: MountInfo,
+    pub usage: result::Result<DiskUsage, String>,
+}
+
+/// Returns every real filesystem mounted on this host with its space, from
+/// `/proc/self/mountinfo`. Pseudo filesystems are left out, see `PSEUDO_FILESYSTEMS`, as are
+/// `tmpfs` mounts unless `FILESYSTEMS_TMPFS_ENVVAR` is set. A filesystem mounted more than once,
+/// such as by bind mounts, is listed once. A filesystem whose space can't be determined, such as
+/// a network share which is gone, is listed with the error rather than failing the others.
+///
+/// # Errors
+///
+/// * The mount table can't be read, as on platforms other than Linux, which have none
+pub fn filesystems() -> Result<Vec<FsUsage>> {
+    platform_filesystems(henv::var(FILESYSTEMS_TMPFS_ENVVAR).is_ok())
+}
+
+/// Returns the root of Habitat's files, such as `/hab`, whose space `SysInfo` reports.
+pub fn data_path() -> PathBuf {
+    Path::new(fs::FS_ROOT_PATH).join(fs::ROOT_PATH)
+}
+
+/// Returns `path` if it exists, or else its nearest ancestor which does. The ancestors of a
+/// relative path end with the current directory.
+fn nearest_existing(path: &Path) -> Result<PathBuf> {
+    let mut candidate = Some(path);
+    while let Some(path) = candidate {
+        let path = if path.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            path
+        };
+        if path.exists() {
+            return Ok(path.to_path_buf());
+        }
+        candidate = path.parent();
+    }
+    Err(Error::FileNotFound(path.display().to_string()))
+}
+
+/// Returns the bytes of a number of filesystem blocks. The counts of statvfs(3) are as wide as
+/// a `long`, so they are widened before multiplying and saturate rather than wrap on filesystems
+/// too large to count.
+#[cfg(unix)]
+fn block_bytes(blocks: u64, block_size: u64) -> u64 {
+    blocks.saturating_mul(block_size)
+}
+
+/// Returns the inodes of statvfs(3), `f_files` and `f_ffree`, or neither if the filesystem
+/// reports no inodes at all, as btrfs does, which would otherwise read as all of them used.
+#[cfg(unix)]
+fn inode_counts(files: u64, free: u64) -> (Option<u64>, Option<u64>) {
+    if files == 0 {
+        (None, None)
+    } else {
+        (Some(files), Some(cmp::min(free, files)))
+    }
+}
+
+fn read_file<P: AsRef<Path>>(path: P) -> Option<String> {
+    let mut contents = String::new();
+    match File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+        Ok(_) => Some(contents),
+        Err(_) => None,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Uname {
+    pub sys_name: String,
+    pub node_name: String,
+    pub release: String,
+    pub version: String,
+    pub machine: String,
+}
+
+/// Architectures as uname(2) and Windows name them, and the names Habitat gives them. Packages
+/// are named after the architecture, so the names must not change.
+static ARCH_NAMES: &'static [(&'static str, &'static str)] = &[("x86_64", "x86_64"),
+                                                              ("amd64", "x86_64"),
+                                                              ("i386", "x86"),
+                                                              ("i486", "x86"),
+                                                              ("i586", "x86"),
+                                                              ("i686", "x86"),
+                                                              ("x86", "x86"),
+                                                              ("aarch64", "aarch64"),
+                                                              ("arm64", "aarch64"),
+                                                              ("armv7l", "armv7l"),
+                                                              ("armv7", "armv7l"),
+                                                              ("armv6l", "armv6l"),
+                                                              ("ppc64le", "ppc64le"),
+                                                              ("s390x", "s390x")];
+
+/// Returns the architecture of this host, such as `x86_64`, `x86`, `aarch64` or `armv7l`. The
+/// machine uname(2) reports is used, so a 32-bit build running on a 64-bit kernel reports the
+/// kernel's architecture. A machine without a name in Habitat falls back to the architecture
+/// this program was built for.
+pub fn arch() -> &'static str {
+    match uname() {
+        Ok(uname) => normalize_arch(&uname.machine).unwrap_or(build_arch()),
+        Err(_) => build_arch(),
+    }
+}
+
+/// Returns Habitat's name of the architecture uname(2) or Windows calls `machine`.
+fn normalize_arch(machine: &str) -> Option<&'static str> {
+    let machine = machine.trim().to_lowercase();
+    ARCH_NAMES.iter().find(|&&(name, _)| name == machine).map(|&(_, arch)| arch)
+}
+
+/// Returns the architecture this program was built for.
+fn build_arch() -> &'static str {
+    if cfg!(target_arch = "x86_64") {
+        "x86_64"
+    } else if cfg!(target_arch = "x86") {
+        "x86"
+    } else if cfg!(target_arch = "aarch64") {
+        "aarch64"
+    } else if cfg!(target_arch = "arm") {
+        "armv7l"
+    } else if cfg!(target_arch = "powerpc64") {
+        "ppc64le"
+    } else {
+        "unknown"
+    }
+}
+
+/// Byte order of the processors of this host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl fmt::Display for Endianness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Endianness::Little => write!(f, "little"),
+            Endianness::Big => write!(f, "big"),
+        }
+    }
+}
+
+/// Returns the byte order of this host, which is the one this program was built for.
+pub fn endianness() -> Endianness {
+    if cfg!(target_endian = "big") {
+        Endianness::Big
+    } else {
+        Endianness::Little
+    }
+}
+
+/// The time the CPUs of this host spent on each kind of work since it booted, in the clock
+/// ticks of Linux's `/proc/stat`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CpuTimes {
+    /// Time running processes, including the time virtual machines ran as guests
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    /// Time idle while waiting for I/O. Kernels before 2.6 don't count it apart from `idle`.
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    /// Time the hypervisor ran something else while this host's virtual CPU was ready to run
+    pub steal: u64,
+}
+
+/// The CPU time counters of this host, of all its CPUs together and of each online CPU.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CpuStat {
+    pub total: CpuTimes,
+    /// The counters of each online CPU, with its number, in the order the kernel lists them
+    pub cores: Vec<(u32, CpuTimes)>,
+}
+
+/// Returns the CPU time counters of this host since it booted, see `CpuSampler` for how busy
+/// they show the CPUs were.
+///
+/// # Errors
+///
+/// * `/proc/stat` can't be read or parsed, as on platforms other than Linux
+pub fn cpu_stat() -> Result<CpuStat> {
+    platform_cpu_stat()
+}
+
+/// The load average of this host.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoadAvg {
+    /// Average number of runnable processes over the last minute
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+    /// Number of processes running now, and in total. Only determined on Linux.
+    pub running: Option<u32>,
+    pub total: Option<u32>,
+}
+
+/// Returns the load average of this host, from `/proc/loadavg` on Linux and from getloadavg(3)
+/// elsewhere on Unix.
+///
+/// # Errors
+///
+/// * The load average can't be determined, as on Windows, which doesn't have one
+pub fn loadavg() -> Result<LoadAvg> {
+    platform_loadavg()
+}
+
+/// The container or virtual machine this host runs in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Virt {
+    /// Bare metal, or no sign of virtualization was found
+    None,
+    Docker,
+    Lxc,
+    Kvm,
+    Xen,
+    VMware,
+    /// Another container or hypervisor, named in lowercase
+    Other(String),
+}
+
+impl fmt::Display for Virt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Virt::None => write!(f, "none"),
+            Virt::Docker => write!(f, "docker"),
+            Virt::Lxc => write!(f, "lxc"),
+            Virt::Kvm => write!(f, "kvm"),
+            Virt::Xen => write!(f, "xen"),
+            Virt::VMware => write!(f, "vmware"),
+            Virt::Other(ref name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Returns the container or virtual machine this host runs in. A container is reported rather
+/// than the virtual machine it runs in, see `parse::detect_virt` for the order signs are
+/// considered in. Only Linux is examined; other platforms report `Virt::None`.
+pub fn virtualization() -> Result<Virt> {
+    platform_virtualization()
+}
+
+/// The identity of this process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// Id of the parent process. Only determined on Unix, as are the ids and the group.
+    pub ppid: Option<u32>,
+    /// Real user id
+    pub uid: Option<u32>,
+    /// Effective user id, the one files are created and checked with
+    pub euid: Option<u32>,
+    /// Real group id
+    pub gid: Option<u32>,
+    /// Name of the effective user, or its id if the user database has no entry for it, as in
+    /// minimal containers
+    pub user: String,
+    /// Name of the real group, or its id if the group database has no entry for it
+    pub group: Option<String>,
+}
+
+/// Returns the identity of this process. Unix resolves the names of its user and group with the
+/// reentrant getpwuid_r(3) and getgrgid_r(3), and Windows reads the name of its user from
+/// `USERNAME`.
+pub fn process_info() -> ProcessInfo {
+    platform_process_info()
+}
+
+/// How many processes run on this host, and how many of them descend from a given process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcCounts {
+    /// Processes of the host, or of its pid namespace in a container
+    pub total: u32,
+    /// Processes whose parent is the process asked about, `None` if none was
+    pub children: Option<u32>,
+    /// Processes descending from the process asked about, its children included, `None` if
+    /// none was
+    pub descendants: Option<u32>,
+}
+
+/// Returns how many processes run on this host and, given the pid of a process such as the
+/// supervisor, how many of them are its children and how many descend from it.
+///
+/// Linux lists the numbered directories of `/proc` and, to follow the tree down from `parent`,
+/// reads the parent of each from `/proc/<pid>/stat`. Processes which exit while the table is
+/// read are counted as they were seen, and left out of the tree if their parent couldn't be
+/// read, so the counts are those of a moment give or take the processes which came and went.
+///
+/// # Errors
+///
+/// * `/proc` can't be listed, as on platforms other than Linux
+pub fn proc_counts(parent: Option<u32>) -> Result<ProcCounts> {
+    platform_proc_counts(parent)
+}
+
+/// Mode SELinux runs in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelinuxMode {
+    /// Denies what its policy doesn't allow
+    Enforcing,
+    /// Logs what its policy doesn't allow, but allows it
+    Permissive,
+    /// Not in the kernel, or not loaded
+    Disabled,
+}
+
+impl SelinuxMode {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            SelinuxMode::Enforcing => "enforcing",
+            SelinuxMode::Permissive => "permissive",
+            SelinuxMode::Disabled => "disabled",
+        }
+    }
+}
+
+impl fmt::Display for SelinuxMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The Linux security modules of this host which confine what processes may do, for packages
+/// which install policies of their own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LsmStatus {
+    pub selinux: SelinuxMode,
+    /// Whether AppArmor is in the kernel and enabled
+    pub apparmor: bool,
+    /// Number of profiles AppArmor has loaded, `None` if it isn't enabled or they can't be read
+    pub apparmor_profiles: Option<u32>,
+}
+
+/// Returns whether SELinux and AppArmor confine the processes of this host.
+///
+/// Linux reads the mode of SELinux from `/sys/fs/selinux/enforce`, or asks `getenforce` if
+/// selinuxfs isn't mounted, and the profiles of AppArmor from securityfs. Either missing means
+/// the module isn't present, not that the status can't be determined, so hosts without them,
+/// containers not shown them, and platforms other than Linux all have both off.
+pub fn lsm_status() -> Result<LsmStatus> {
+    platform_lsm_status()
+}
+
+/// A limit on a resource of this process, see getrlimit(2). `None` is unlimited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rlimit {
+    /// Limit the process is held to, which it may raise up to `hard`
+    pub soft: Option<u64>,
+    pub hard: Option<u64>,
+}
+
+/// The limits on the resources of this process which services most often run into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rlimits {
+    /// Number of open files, counting sockets
+    pub nofile: Rlimit,
+    /// Number of processes of the user
+    pub nproc: Rlimit,
+    /// Size of core dumps in bytes, 0 if they are disabled
+    pub core: Rlimit,
+    /// Bytes of memory which can be locked into RAM
+    pub memlock: Rlimit,
+}
+
+impl Rlimits {
+    /// Returns whether this process can open at least the given number of files without raising
+    /// its limit.
+    pub fn nofile_at_least(&self, n: u64) -> bool {
+        self.nofile.soft.map_or(true, |soft| soft >= n)
+    }
+}
+
+/// Returns the resource limits of this process.
+///
+/// # Errors
+///
+/// * A limit can't be read, as on Windows, which doesn't have them
+pub fn rlimits() -> Result<Rlimits> {
+    platform_rlimits()
+}
+
+/// The limits the cgroup of this process places on it, which in a container are usually lower
+/// than what the host has. Limits the cgroup doesn't set are `None`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CgroupLimits {
+    /// CPUs the CPU bandwidth limit lets the cgroup use, which may be fractional, such as `1.5`
+    pub cpus: Option<f64>,
+    pub memory_bytes: Option<u64>,
+    /// Processes and threads the cgroup may have
+    pub pids_max: Option<u64>,
+}
+
+impl CgroupLimits {
+    /// Returns the CPUs services may use: the host's, or the cgroup's limit if it is lower.
+    pub fn effective_cpus(&self, host_cpus: u32) -> f64 {
+        match self.cpus {
+            Some(cpus) if cpus < host_cpus as f64 => cpus,
+            _ => host_cpus as f64,
+        }
+    }
+
+    /// Returns the bytes of memory services may use: the host's, or the cgroup's limit if it is
+    /// lower or the host's are unknown.
+    pub fn effective_memory_bytes(&self, host_bytes: Option<u64>) -> Option<u64> {
+        match (self.memory_bytes, host_bytes) {
+            (Some(limit), Some(host)) => Some(cmp::min(limit, host)),
+            (limit, host) => limit.or(host),
+        }
+    }
+}
+
+/// Returns the limits of the cgroup of this process, v1's `cpu.cfs_quota_us`,
+/// `memory.limit_in_bytes` and `pids.max` or v2's `cpu.max`, `memory.max` and `pids.max`. The
+/// cgroup is the one `/proc/self/cgroup` lists. `None` if the cgroup sets no limit, or on hosts
+/// other than Linux, which don't have cgroups.
+///
+/// # Errors
+///
+/// * A limit of the cgroup is malformed
+pub fn cgroup_limits() -> Result<Option<CgroupLimits>> {
+    platform_cgroup_limits()
+}
+
+/// The timezone and clock of this host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeInfo {
+    /// IANA name of the timezone, such as `Europe/Berlin`, or `UTC` if the host has no timezone
+    /// data
+    pub timezone: String,
+    /// Offset of local time from UTC now, in seconds east of UTC
+    pub utc_offset_seconds: i32,
+    /// Whether the kernel considers the clock synchronized, as by NTP. Only determined on Linux.
+    pub ntp_synchronized: Option<bool>,
+}
+
+/// Returns the timezone and clock of this host. The timezone is the one `/etc/localtime` links
+/// to, or else the one named in `/etc/timezone`, or else UTC.
+pub fn time_info() -> TimeInfo {
+    TimeInfo {
+        timezone: timezone(),
+        utc_offset_seconds: time::now().tm_utcoff,
+        ntp_synchronized: platform_ntp_synchronized(),
+    }
+}
+
+fn timezone() -> String {
+    read_link(LOCALTIME)
+        .ok()
+        .and_then(|target| parse_localtime_link(&target.to_string_lossy()))
+        .or_else(|| read_file(ETC_TIMEZONE).and_then(|c| parse_etc_timezone(&c)))
+        .unwrap_or("UTC".to_string())
+}
+
+/// Returns the id systemd or D-Bus gave this host when it was installed, from `/etc/machine-id`
+/// or else `/var/lib/dbus/machine-id`, or `None` if neither holds one, as on platforms other than
+/// Linux and in many containers. Callers rely on the id staying the same, so none is ever made
+/// up.
+///
+/// # Errors
+///
+/// * A machine id file holds something other than an id
+pub fn machine_id() -> Result<Option<String>> {
+    match try!(read_host_id(ETC_MACHINE_ID)) {
+        Some(id) => Ok(Some(id)),
+        None => read_host_id(DBUS_MACHINE_ID),
+    }
+}
+
+/// Returns the id Linux gave the current boot of this host, from
+/// `/proc/sys/kernel/random/boot_id`, or `None` elsewhere.
+///
+/// # Errors
+///
+/// * The boot id file holds something other than an id
+pub fn boot_id() -> Result<Option<String>> {
+    read_host_id(PROC_BOOT_ID)
+}
+
+/// Reads an id from a file, see `parse::parse_host_id`. A file which is missing or empty, or holds
+/// `uninitialized` as systemd writes before the first boot completes, holds no id.
+fn read_host_id(path: &str) -> Result<Option<String>> {
+    let contents = match read_file(path) {
+        Some(contents) => contents,
+        None => return Ok(None),
+    };
+    match contents.trim() {
+        "" | "uninitialized" => Ok(None),
+        id => {
+            match parse_host_id(id) {
+                Some(id) => Ok(Some(id)),
+                None => Err(Error::InvalidHostId(path.to_string(), id.to_string())),
+            }
+        }
+    }
+}
+
+/// Environment variable setting the bits of entropy below which `entropy_low` reports the
+/// kernel's pool as low
+pub const ENTROPY_FLOOR_ENVVAR: &'static str = "HAB_ENTROPY_FLOOR";
+
+/// Entropy reported on platforms whose random number generator never blocks, that of a full
+/// Linux pool
+pub const ENTROPY_UNLIMITED: u32 = 4096;
+
+/// Bits of entropy below which the pool is low if `ENTROPY_FLOOR_ENVVAR` isn't set. Generating
+/// keys from `/dev/random` stalls at about this level.
+const DEFAULT_ENTROPY_FLOOR: u32 = 200;
+
+/// Returns the bits of entropy in the kernel's pool, from
+/// `/proc/sys/kernel/random/entropy_avail` on Linux. Other platforms don't have a pool reads of
+/// random numbers block on, and report `ENTROPY_UNLIMITED`. The read is a single small file, so
+/// it is part of every snapshot.
+///
+/// # Errors
+///
+/// * The entropy file can't be read or doesn't hold a number
+pub fn entropy_available() -> Result<u32> {
+    platform_entropy_available()
+}
+
+/// Returns the floor set by `ENTROPY_FLOOR_ENVVAR`, or 200 bits if it isn't set.
+pub fn entropy_floor() -> u32 {
+    match henv::var(ENTROPY_FLOOR_ENVVAR) {
+        Ok(val) => {
+            match val.parse() {
+                Ok(floor) => floor,
+                Err(_) => {
+                    warn!("Ignoring {}={}, expected a number of bits",
+                          ENTROPY_FLOOR_ENVVAR,
+                          redact::redact_text(&val));
+                    DEFAULT_ENTROPY_FLOOR
+                }
+            }
+        }
+        Err(_) => DEFAULT_ENTROPY_FLOOR,
+    }
+}
+
+/// Returns whether the kernel's pool holds less entropy than the floor, see `entropy_floor`.
+pub fn entropy_low() -> Result<bool> {
+    let available = try!(entropy_available());
+    Ok(available < entropy_floor())
+}
+
+/// Returns the time since this host booted.
+///
+/// Linux reads `/proc/uptime`, falling back to sysinfo(2), macOS and the BSDs subtract the
+/// `kern.boottime` of sysctl(8) from the current time, and Windows asks `GetTickCount64`.
+///
+/// # Errors
+///
+/// * The time since boot can't be determined
+pub fn uptime() -> Result<Duration> {
+    platform_uptime()
+}
+
+/// Returns the time this host booted, to the second, computed from `uptime` and the current
+/// time.
+pub fn boot_time() -> Result<time::Timespec> {
+    let uptime = try!(uptime());
+    Ok(boot_time_at(time::get_time(), uptime))
+}
+
+fn boot_time_at(now: time::Timespec, uptime: Duration) -> time::Timespec {
+    time::Timespec::new(now.sec - uptime.as_secs() as i64, 0)
+}
+
+/// Formats a time as an RFC 3339 timestamp in UTC, such as `2016-10-14T09:20:00Z`, whatever the
+/// local time zone is.
+pub fn rfc3339_utc(at: time::Timespec) -> String {
+    time::at_utc(at).rfc3339().to_string()
+}
+
+/// The operating system distribution of this host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OsRelease {
+    /// Lowercase identifier of the distribution, such as `ubuntu` or `centos`
+    pub id: String,
+    /// Version of the distribution, such as `16.04`, which rolling releases don't have
+    pub version_id: Option<String>,
+    /// Name of the distribution for display, such as `Ubuntu 16.04.1 LTS`
+    pub pretty_name: Option<String>,
+}
+
+/// Returns the operating system distribution of this host from `/etc/os-release`, or on
+/// distributions older than it from `/etc/lsb-release` or `/etc/redhat-release`.
+///
+/// # Errors
+///
+/// * None of the release files exist or name a distribution, as on macOS and Windows
+pub fn os_release() -> Result<OsRelease> {
+    let release = read_file(OS_RELEASE)
+        .or_else(|| read_file(USR_LIB_OS_RELEASE))
+        .and_then(|c| parse_os_release(&c))
+        .or_else(|| read_file(LSB_RELEASE).and_then(|c| parse_lsb_release(&c)))
+        .or_else(|| read_file(REDHAT_RELEASE).and_then(|c| parse_redhat_release(&c)));
+    match release {
+        Some(release) => Ok(release),
+        None => {
+            Err(Error::OsReleaseFailed(format!("None of {}, {}, {} or {} name the operating \
+                                                system",
+                                               OS_RELEASE,
+                                               USR_LIB_OS_RELEASE,
+                                               LSB_RELEASE,
+                                               REDHAT_RELEASE)))
+        }
+    }
+}
+
+/// Environment variable naming, comma separated, the environment variables whose values are
+/// rendered in `[sys.env]`, such as `DATACENTER,RACK`
+pub const ENV_FACTS_ENVVAR: &'static str = "HAB_ENV_FACTS";
+
+/// Words which mark the name of an environment variable as holding a secret, which is never
+/// rendered even if `ENV_FACTS_ENVVAR` names it, as rendered facts end up in templates and logs
+pub const SECRET_ENV_PATTERNS: &'static [&'static str] = &["TOKEN", "SECRET", "PASSWORD", "KEY"];
+
+/// Returns the names of the environment variables `ENV_FACTS_ENVVAR` names.
+pub fn env_fact_names() -> Vec<String> {
+    match henv::var(ENV_FACTS_ENVVAR) {
+        Ok(val) => {
+            val.split(',')
+                .map(|name| name.trim())
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_string())
+                .collect()
+        }
+        Err(_) => vec![],
+    }
+}
+
+/// Returns the values of the environment variables `ENV_FACTS_ENVVAR` names, keyed by their
+/// names. Variables which aren't set are left out, as are variables whose names look like they
+/// hold secrets, see `SECRET_ENV_PATTERNS`, which are warned about instead. Secrets within the
+/// other values, such as the password of a URL, are redacted, see `util::redact`.
+pub fn env_facts() -> BTreeMap<String, String> {
+    env_facts_from(&env_fact_names(), |name| henv::var(name).ok())
+}
+
+fn env_facts_from<F>(names: &[String], lookup: F) -> BTreeMap<String, String>
+    where F: Fn(&str) -> Option<String>
+{
+    let mut facts = BTreeMap::new();
+    for name in names {
+        if is_secret_env_name(name) {
+            warn!("Not rendering {} in sys.env, its name looks like it holds a secret", name);
+            continue;
+        }
+        if let Some(value) = lookup(name) {
+            let redacted = redact::redact_text(&value).into_owned();
+            if redacted != value {
+                warn!("Redacted secrets from the value of {} in sys.env", name);
+            }
+            facts.insert(name.clone(), redacted);
+        }
+    }
+    facts
+}
+
+/// Returns whether the name of an environment variable contains any of `SECRET_ENV_PATTERNS`,
+/// whatever its case.
+pub fn is_secret_env_name(name: &str) -> bool {
+    let name = name.to_uppercase();
+    SECRET_ENV_PATTERNS.iter().any(|pattern| name.contains(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::iter;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::path::{Path, PathBuf};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[cfg(unix)]
+    use libc;
+    use time;
+
+    use super::*;
+    use error::Error;
+    #[cfg(unix)]
+    use super::{COMMAND_OUTPUT_LIMIT, block_bytes, command_output, format_mac, inode_counts};
+    #[cfg(unix)]
+    use super::unix::{group_name, rlim_value, user_name};
+    use super::{ARCH_NAMES, boot_time_at, build_arch, describe_interfaces, env_facts_from,
+                host_ips, hostname_from, in_network, interface_ips, ip_candidates, is_private,
+                is_usable, nearest_existing, normalize_arch, prefix_len, qualify,
+                rank_interface_ips, self_resolution_from, trim_hostname, try_strategies,
+                upstream_dns_config};
+    #[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+              target_os = "netbsd", target_os = "openbsd"))]
+    use super::preferred_interface_ip;
+    use super::parse::parse_resolv_conf;
+    use super::source::FakeHost;
+
+    #[test]
+    fn ip_is_reachable() {
+        let ip = ip().unwrap();
+        assert_eq!(ip.to_string().parse::<IpAddr>().unwrap(), ip);
+        assert!(ip.to_string() != "0.0.0.0");
+        assert!(ip.to_string() != "127.0.0.1");
+    }
+
+    #[test]
+    fn resolved_stub_is_replaced_by_upstreams() {
+        let stub = parse_resolv_conf(include_str!("../../../tests/fixtures/resolv.conf-stub"));
+        assert!(stub.uses_resolved_stub());
+        let upstream = include_str!("../../../tests/fixtures/resolv.conf-upstream");
+        let config = upstream_dns_config(stub.clone(), Some(upstream.to_string()));
+        assert!(!config.uses_resolved_stub());
+        assert_eq!(config.nameservers,
+                   vec!["192.168.1.1".parse::<IpAddr>().unwrap(),
+                        "1.1.1.1".parse::<IpAddr>().unwrap()]);
+        assert_eq!(config.search, vec!["example.com".to_string(), "lan".to_string()]);
+    }
+
+    #[test]
+    fn resolved_stub_is_kept_without_upstreams() {
+        let stub = parse_resolv_conf(include_str!("../../../tests/fixtures/resolv.conf-stub"));
+        assert_eq!(upstream_dns_config(stub.clone(), None), stub);
+        assert_eq!(upstream_dns_config(stub.clone(), Some("# empty\n".to_string())),
+                   stub);
+    }
+
+    #[test]
+    fn fqdn_prefers_canonical_name() {
+        assert_eq!(qualify("db-1",
+                           Some("db-1.corp.example.com".to_string()),
+                           Some("db-1\n".to_string()),
+                           Some("example.com".to_string())),
+                   "db-1.corp.example.com");
+    }
+
+    #[test]
+    fn fqdn_falls_back_to_resolv_conf_domain() {
+        assert_eq!(qualify("db-1",
+                           None,
+                           Some("db-1\n".to_string()),
+                           Some("example.com".to_string())),
+                   "db-1.example.com");
+        assert_eq!(qualify("db-1",
+                           Some("localhost.localdomain".to_string()),
+                           None,
+                           Some("example.com".to_string())),
+                   "db-1.example.com");
+        assert_eq!(qualify("db-1",
+                           Some("db-1".to_string()),
+                           Some("db-1.corp.example.com\n".to_string()),
+                           Some("example.com".to_string())),
+                   "db-1.corp.example.com");
+    }
+
+    #[test]
+    fn fqdn_without_domain_is_short_hostname() {
+        assert_eq!(qualify("db-1", None, None, None), "db-1");
+        assert_eq!(qualify("db-1", Some("db-1".to_string()), Some("\n".to_string()), None),
+                   "db-1");
+    }
+
+    #[test]
+    fn host_aliases_leave_out_localhost() {
+        let aliases = host_aliases().unwrap();
+        assert!(!aliases.iter().any(|alias| alias == "localhost"));
+    }
+
+    #[test]
+    fn hostname_failures_are_not_ip_failures() {
+        fn failing_hostname() -> Result<String> {
+            Err(Error::SysCommandFailed {
+                cmd: "hostname".to_string(),
+                status: "exit code: 1".to_string(),
+                stdout: String::new(),
+                stderr: "hostname: not found".to_string(),
+            })
+        }
+        for result in vec![hostname_from(failing_hostname),
+                           hostname_from(|| Ok("\0 \n".to_string()))] {
+            match result {
+                Err(Error::HostnameFailed(ref reason)) => {
+                    let message = Error::HostnameFailed(reason.clone()).to_string();
+                    assert!(message.contains("hosts name"), "{}", message);
+                    assert!(!message.contains("IP"), "{}", message);
+                }
+                other => panic!("expected the host name to fail, got {:?}", other),
+            }
+        }
+        match hostname_from(failing_hostname) {
+            Err(Error::HostnameFailed(ref reason)) => {
+                assert!(reason.contains("hostname: not found"), "{}", reason)
+            }
+            other => panic!("expected the host name to fail, got {:?}", other),
+        }
+        assert_eq!(hostname_from(|| Ok("db-1\n".to_string())).unwrap(), "db-1");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn failed_commands_carry_their_output() {
+        let e = command_output("sh", &["-c", "echo out; echo err >&2; exit 3"]).unwrap_err();
+        let message = e.to_string();
+        assert!(message.starts_with("sh -c echo out; echo err >&2; exit 3 failed with "));
+        assert!(message.contains("stdout: \"out\", stderr: \"err\""));
+        match e {
+            Error::SysCommandFailed { status, stdout, stderr, .. } => {
+                assert!(status.ends_with(": 3"));
+                assert!(message.contains(&status));
+                assert_eq!(stdout, "out\n");
+                assert_eq!(stderr, "err\n");
+            }
+            other => panic!("Expected the command to fail, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn failed_commands_carry_the_start_of_long_output() {
+        match command_output("sh", &["-c", "yes | head -c 10000; exit 1"]) {
+            Err(Error::SysCommandFailed { stdout, stderr, .. }) => {
+                assert!(stdout.starts_with("y\ny\n"));
+                assert!(stdout.ends_with("... (7952 more bytes)"));
+                assert_eq!(stdout.len(), COMMAND_OUTPUT_LIMIT + "... (7952 more bytes)".len());
+                assert_eq!(stderr, "");
+            }
+            other => panic!("Expected the command to fail, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn commands_finishing_in_time_are_not_killed() {
+        let output = run_with_timeout("sh", &["-c", "echo done"], Duration::from_secs(5)).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"done\n");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn hung_commands_are_killed_with_their_children() {
+        let started = Instant::now();
+        let script = "sleep 30 & echo $!; wait";
+        let child = match run_with_timeout("sh", &["-c", script], Duration::from_millis(500)) {
+            Err(Error::SysCommandTimeout { stdout, .. }) => stdout.trim().parse().unwrap(),
+            other => panic!("Expected the command to time out, got {:?}", other),
+        };
+        assert!(started.elapsed() < Duration::from_secs(10));
+        // The killed sleep is reaped by init, so it may linger as a zombie for a moment
+        let mut alive = true;
+        for _ in 0..50 {
+            alive = unsafe { libc::kill(child, 0) } == 0;
+            if !alive {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!alive);
+    }
+
+    #[test]
+    fn localhost_is_looked_up_in_time() {
+        let started = Instant::now();
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        let name = reverse_lookup(ip, Duration::from_secs(2)).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(3));
+        if let Some(name) = name {
+            assert!(!name.is_empty());
+            assert!(!name.ends_with('.'));
+        }
+    }
+
+    #[test]
+    fn unresolvable_private_addresses_have_no_name() {
+        let started = Instant::now();
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 255, 255, 254));
+        assert_eq!(reverse_lookup(ip, Duration::from_millis(500)).unwrap(), None);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn hostnames_are_trimmed() {
+        assert_eq!(trim_hostname("builder\n"), Some("builder".to_string()));
+        assert_eq!(trim_hostname("builder\0\0"), Some("builder".to_string()));
+        assert_eq!(trim_hostname(" \n"), None);
+        assert_eq!(trim_hostname(""), None);
+    }
+
+    fn no_address() -> Result<Option<IpAddr>> {
+        Ok(None)
+    }
+
+    fn failing() -> Result<Option<IpAddr>> {
+        Err(Error::NoOutboundAddr)
+    }
+
+    fn loopback() -> Result<Option<IpAddr>> {
+        Ok(Some("127.0.0.1".parse().unwrap()))
+    }
+
+    fn private() -> Result<Option<IpAddr>> {
+        Ok(Some("10.0.0.5".parse().unwrap()))
+    }
+
+    fn unreachable() -> Result<Option<IpAddr>> {
+        panic!("strategies after the winning one must not run")
+    }
+
+    #[test]
+    fn first_successful_strategy_wins() {
+        let strategies = [IpStrategy {
+                              name: "none",
+                              discover: no_address,
+                          },
+                          IpStrategy {
+                              name: "loopback",
+                              discover: loopback,
+                          },
+                          IpStrategy {
+                              name: "private",
+                              discover: private,
+                          },
+                          IpStrategy {
+                              name: "unreachable",
+                              discover: unreachable,
+                          }];
+        assert_eq!(discover_ip(&strategies).unwrap().to_string(), "10.0.0.5");
+    }
+
+    #[test]
+    fn failed_strategies_are_all_reported() {
+        let strategies = [IpStrategy {
+                              name: "none",
+                              discover: no_address,
+                          },
+                          IpStrategy {
+                              name: "failing",
+                              discover: failing,
+                          },
+                          IpStrategy {
+                              name: "loopback",
+                              discover: loopback,
+                          }];
+        match discover_ip(&strategies) {
+            Err(Error::IpDiscoveryFailed(reasons)) => {
+                assert_eq!(reasons,
+                           vec!["none: no address found".to_string(),
+                                "failing: Failed to discover this hosts outbound IP address"
+                                    .to_string(),
+                                "loopback: found unusable address 127.0.0.1".to_string()]);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    /// Returns an interface which is up with the given addresses, and a hardware address unless
+    /// `physical` is false.
+    fn interface(name: &str, physical: bool, ips: &[&str]) -> Interface {
+        Interface {
+            name: name.to_string(),
+            addrs: ips.iter()
+                .map(|ip| {
+                    InterfaceAddr {
+                        ip: ip.parse().unwrap(),
+                        prefix_len: 24,
+                    }
+                })
+                .collect(),
+            mac: if physical { Some("52:54:00:12:34:56".to_string()) } else { None },
+            mtu: None,
+            up: true,
+            loopback: false,
+        }
+    }
+
+    fn ranked(interfaces: &[Interface], family: IpPreference) -> Vec<String> {
+        let prefixes = virtual_interface_prefixes();
+        rank_interface_ips(interfaces, family, &prefixes).iter().map(|ip| ip.to_string()).collect()
+    }
+
+    #[test]
+    fn virtual_interfaces_are_not_ranked() {
+        let interfaces = vec![interface("docker0", true, &["172.17.0.1"]),
+                              interface("veth1a2b3c", true, &["172.17.0.2"]),
+                              interface("br-5f3a", true, &["172.18.0.1"]),
+                              interface("virbr0", true, &["192.168.122.1"]),
+                              interface("flannel.1", false, &["10.244.0.0"]),
+                              interface("cni0", true, &["10.244.0.1"]),
+                              interface("eth0", true, &["10.0.0.5"])];
+        assert_eq!(ranked(&interfaces, IpPreference::Ipv4First), vec!["10.0.0.5"]);
+        assert_eq!(ranked(&interfaces[..6], IpPreference::Ipv4First),
+                   Vec::<String>::new());
+    }
+
+    #[test]
+    fn virtual_interfaces_can_be_added() {
+        let interfaces = vec![interface("tailscale0", false, &["100.101.102.103"]),
+                              interface("eth0", true, &["203.0.113.5"])];
+        let mut prefixes = virtual_interface_prefixes();
+        assert_eq!(rank_interface_ips(&interfaces, IpPreference::Ipv4First, &prefixes).len(),
+                   2);
+        prefixes.push("tailscale".to_string());
+        assert_eq!(rank_interface_ips(&interfaces, IpPreference::Ipv4First, &prefixes),
+                   vec!["203.0.113.5".parse::<IpAddr>().unwrap()]);
+    }
+
+    #[test]
+    fn down_loopback_and_link_local_are_not_ranked() {
+        let mut down = interface("eth1", true, &["10.0.1.5"]);
+        down.up = false;
+        let mut lo = interface("lo", false, &["127.0.0.1", "::1"]);
+        lo.loopback = true;
+        let link_local = interface("eth0", true, &["169.254.10.20", "fe80::5054:ff:fe12:3456"]);
+        let interfaces = vec![down, lo, link_local];
+        assert!(ranked(&interfaces, IpPreference::Ipv4First).is_empty());
+        assert!(ranked(&interfaces, IpPreference::Ipv6First).is_empty());
+    }
+
+    #[test]
+    fn private_addresses_on_physical_interfaces_rank_first() {
+        let interfaces = vec![interface("tun0", false, &["10.8.0.6"]),
+                              interface("eth0", true, &["203.0.113.5"]),
+                              interface("wg0", false, &["198.51.100.7"]),
+                              interface("eth1", true, &["192.168.1.20", "172.16.4.2"])];
+        assert_eq!(ranked(&interfaces, IpPreference::Ipv4First),
+                   vec!["192.168.1.20", "172.16.4.2", "203.0.113.5", "10.8.0.6", "198.51.100.7"]);
+    }
+
+    #[test]
+    fn ranking_keeps_to_the_family() {
+        let interfaces = vec![interface("eth0", true, &["2001:db8::5", "10.0.0.5", "fd12::5"])];
+        assert_eq!(ranked(&interfaces, IpPreference::Ipv4First), vec!["10.0.0.5"]);
+        assert_eq!(ranked(&interfaces, IpPreference::Ipv6First),
+                   vec!["fd12::5", "2001:db8::5"]);
+    }
+
+    #[test]
+    fn candidates_are_scored_or_skipped_with_a_reason() {
+        let mut lo = interface("lo", false, &["127.0.0.1"]);
+        lo.loopback = true;
+        let interfaces = vec![lo,
+                              interface("docker0", true, &["172.17.0.1"]),
+                              interface("eth0", true, &["10.0.0.5", "fe80::5054:ff:fe12:3456"]),
+                              interface("tun0", false, &["203.0.113.5"])];
+        let described: Vec<String> = ip_candidates(&interfaces, &virtual_interface_prefixes())
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(described,
+                   vec!["127.0.0.1 on lo, loopback, skipped: loopback interface",
+                        "172.17.0.1 on docker0, global, skipped: virtual interface, named docker*",
+                        "10.0.0.5 on eth0, global, score 3: hardware address, private",
+                        "fe80::5054:ff:fe12:3456 on eth0, link-local, skipped: link-local address",
+                        "203.0.113.5 on tun0, global, score 0: no hardware address, public"]);
+    }
+
+    #[test]
+    fn selections_explain_their_choice() {
+        let strategies = [IpStrategy {
+                              name: "loopback",
+                              discover: loopback,
+                          },
+                          IpStrategy {
+                              name: "private",
+                              discover: private,
+                          },
+                          IpStrategy {
+                              name: "unreachable",
+                              discover: unreachable,
+                          }];
+        let selection = IpSelection {
+            preference: IpPreference::Ipv4First,
+            ipv4_attempts: try_strategies(&strategies),
+            ipv6_attempts: vec![],
+            candidates: ip_candidates(&[interface("eth0", true, &["10.0.0.5"])], &[]),
+        };
+        assert_eq!(selection.primary(), Some("10.0.0.5".parse().unwrap()));
+        assert_eq!(selection.to_string(),
+                   "Chose 10.0.0.5, preferring IPv4 (HAB_IP_PREFERENCE)\n\
+                    IPv4 strategies, tried until one finds a usable address:\n  \
+                    loopback: found unusable address 127.0.0.1\n  \
+                    private: found 10.0.0.5\n\
+                    IPv6 strategies, tried until one finds a usable address:\n\
+                    Interface addresses, of which the scan takes the highest scoring:\n  \
+                    10.0.0.5 on eth0, global, score 3: hardware address, private");
+    }
+
+    #[test]
+    fn found_addresses_survive_failures_of_the_other_family() {
+        let found = [IpStrategy {
+                         name: "private",
+                         discover: private,
+                     }];
+        let failed = [IpStrategy {
+                          name: "getifaddrs ipv6",
+                          discover: failing,
+                      }];
+        let ips = host_ips(&try_strategies(&found), &try_strategies(&failed)).unwrap();
+        assert_eq!(ips.ipv4, Some("10.0.0.5".parse().unwrap()));
+        assert_eq!(ips.ipv6, None);
+    }
+
+    #[test]
+    fn selections_without_an_address_fail_like_ips() {
+        let strategies = [IpStrategy {
+                              name: "none",
+                              discover: no_address,
+                          }];
+        let selection = IpSelection {
+            preference: IpPreference::Ipv6First,
+            ipv4_attempts: try_strategies(&strategies),
+            ipv6_attempts: try_strategies(&strategies),
+            candidates: vec![],
+        };
+        assert_eq!(selection.primary(), None);
+        match selection.ips() {
+            Err(Error::IpDiscoveryFailed(reasons)) => {
+                assert_eq!(reasons,
+                           vec!["none: no address found".to_string(),
+                                "none: no address found".to_string()])
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(selection.to_string().starts_with("Found no address, preferring IPv6"));
+        assert!(selection.to_string().ends_with("No interface addresses could be listed"));
+    }
+
+    #[test]
+    fn scopes() {
+        assert_eq!(IpScope::of(&"::1".parse().unwrap()), IpScope::Loopback);
+        assert_eq!(IpScope::of(&"169.254.1.1".parse().unwrap()), IpScope::LinkLocal);
+        assert_eq!(IpScope::of(&"2001:db8::5".parse().unwrap()), IpScope::Global);
+    }
+
+    #[test]
+    fn private_addresses() {
+        for ip in &["10.0.0.5", "172.16.0.1", "172.31.255.254", "192.168.1.1", "fd00::1",
+                    "fc00::1"] {
+            assert!(is_private(&ip.parse().unwrap()));
+        }
+        for ip in &["172.32.0.1", "203.0.113.5", "100.64.0.1", "2001:db8::5", "fe80::1"] {
+            assert!(!is_private(&ip.parse().unwrap()));
+        }
+    }
+
+    #[test]
+    #[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "dragonfly",
+              target_os = "netbsd", target_os = "openbsd"))]
+    fn interface_ips_prefer_global_addresses() {
+        let ips = vec!["169.254.0.5".parse().unwrap(),
+                       "2001:db8::5".parse().unwrap(),
+                       "10.0.0.5".parse().unwrap()];
+        assert_eq!(preferred_interface_ip(&ips, IpPreference::Ipv4First),
+                   Some("10.0.0.5".parse().unwrap()));
+        assert_eq!(preferred_interface_ip(&ips[..1], IpPreference::Ipv4First),
+                   Some("169.254.0.5".parse().unwrap()));
+        assert_eq!(preferred_interface_ip(&ips[..1], IpPreference::Ipv6First), None);
+    }
+
+    #[test]
+    fn ip_selectors_parse() {
+        assert_eq!("".parse::<IpSelector>().unwrap(), IpSelector::Default);
+        assert_eq!("default".parse::<IpSelector>().unwrap(), IpSelector::Default);
+        assert_eq!("eth1".parse::<IpSelector>().unwrap(),
+                   IpSelector::Interface("eth1".to_string()));
+        assert_eq!("10.0.0.0/8".parse::<IpSelector>().unwrap(),
+                   IpSelector::Cidr("10.0.0.0".parse().unwrap(), 8));
+        assert_eq!("fd00::/8".parse::<IpSelector>().unwrap(),
+                   IpSelector::Cidr("fd00::".parse().unwrap(), 8));
+        assert!("10.0.0.0/33".parse::<IpSelector>().is_err());
+        assert!("10.0.0/8".parse::<IpSelector>().is_err());
+    }
+
+    #[test]
+    fn ips_in_network() {
+        let network = "10.1.0.0".parse().unwrap();
+        assert!(in_network(&"10.1.2.3".parse().unwrap(), &network, 16));
+        assert!(!in_network(&"10.2.2.3".parse().unwrap(), &network, 16));
+        assert!(in_network(&"192.168.1.1".parse().unwrap(), &network, 0));
+        assert!(in_network(&"10.1.0.129".parse().unwrap(),
+                           &"10.1.0.128".parse().unwrap(),
+                           25));
+        assert!(!in_network(&"10.1.0.127".parse().unwrap(),
+                            &"10.1.0.128".parse().unwrap(),
+                            25));
+        assert!(!in_network(&"fd00::1".parse().unwrap(), &network, 0));
+        assert!(in_network(&"fd00::1".parse().unwrap(), &"fd00::".parse().unwrap(), 8));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn ip_for_loopback_interface() {
+        let interfaces = interfaces().unwrap();
+        let lo = interfaces.iter().find(|i| i.loopback).unwrap();
+        let selector = IpSelector::Interface(lo.name.clone());
+        assert!(ip_for(&selector).unwrap().is_loopback());
+        let selector = "127.0.0.0/8".parse().unwrap();
+        assert_eq!(ip_for(&selector).unwrap().to_string(), "127.0.0.1");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unmatched_ip_selector_lists_interfaces() {
+        let selector = IpSelector::Interface("no-such-interface0".to_string());
+        match ip_for(&selector) {
+            Err(Error::NoMatchingIp(selector, interfaces)) => {
+                assert_eq!(selector, "interface no-such-interface0");
+                assert!(interfaces.contains("127.0.0.1/8"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn interfaces_are_described() {
+        let interfaces = vec![Interface {
+                                  name: "eth0".to_string(),
+                                  addrs: vec![InterfaceAddr {
+                                                  ip: "10.0.0.5".parse().unwrap(),
+                                                  prefix_len: 24,
+                                              }],
+                                  mac: None,
+                                  mtu: None,
+                                  up: true,
+                                  loopback: false,
+                              },
+                              Interface {
+                                  name: "docker0".to_string(),
+                                  addrs: vec![],
+                                  mac: None,
+                                  mtu: None,
+                                  up: false,
+                                  loopback: false,
+                              }];
+        assert_eq!(describe_interfaces(&interfaces),
+                   "eth0 (10.0.0.5/24), docker0 (no addresses)");
+        assert_eq!(describe_interfaces(&[]), "none");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn interfaces_include_loopback() {
+        let interfaces = interfaces().unwrap();
+        let lo = interfaces.iter().find(|i| i.loopback).unwrap();
+        assert!(lo.addrs.iter().any(|a| a.ip.to_string() == "127.0.0.1" && a.prefix_len == 8));
+        assert_eq!(lo.mac, None);
+    }
+
+    #[test]
+    fn netmask_prefix_lengths() {
+        assert_eq!(prefix_len(&"255.255.255.0".parse().unwrap()), 24);
+        assert_eq!(prefix_len(&"0.0.0.0".parse().unwrap()), 0);
+        assert_eq!(prefix_len(&"ffff:ffff:ffff:ffff::".parse().unwrap()), 64);
+        let v4: IpAddr = "10.0.0.5".parse().unwrap();
+        assert_eq!(netmask(&v4, 24).to_string(), "255.255.255.0");
+        assert_eq!(netmask(&v4, 0).to_string(), "0.0.0.0");
+        assert_eq!(netmask(&v4, 32).to_string(), "255.255.255.255");
+        let v6: IpAddr = "fe80::1".parse().unwrap();
+        assert_eq!(netmask(&v6, 64).to_string(), "ffff:ffff:ffff:ffff::");
+        assert_eq!(netmask(&v6, 20).to_string(), "ffff:f000::");
+        assert_eq!(prefix_len(&netmask(&v6, 128)), 128);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn mac_addresses_are_formatted() {
+        assert_eq!(format_mac(&[0x02, 0x42, 0xac, 0x11, 0x00, 0x02]),
+                   Some("02:42:ac:11:00:02".to_string()));
+        assert_eq!(format_mac(&[0; 6]), None);
+        assert_eq!(format_mac(&[]), None);
+    }
+
+    #[test]
+    fn primary_ip_follows_preference() {
+        let v4 = Ipv4Addr::new(10, 0, 0, 5);
+        let v6 = "2001:db8::5".parse::<Ipv6Addr>().unwrap();
+        let both = HostIps {
+            ipv4: Some(v4),
+            ipv6: Some(v6),
+        };
+        assert_eq!(both.primary(IpPreference::Ipv4First), Some(IpAddr::V4(v4)));
+        assert_eq!(both.primary(IpPreference::Ipv6First), Some(IpAddr::V6(v6)));
+        let v6_only = HostIps {
+            ipv4: None,
+            ipv6: Some(v6),
+        };
+        assert_eq!(v6_only.primary(IpPreference::Ipv4First), Some(IpAddr::V6(v6)));
+        assert_eq!(HostIps::default().primary(IpPreference::Ipv6First), None);
+    }
+
+    #[test]
+    fn missing_paths_are_measured_on_their_ancestor() {
+        let tmp = env::temp_dir();
+        let usage = disk_usage(&tmp.join("hab-disk-usage-missing").join("svc")).unwrap();
+        assert_eq!(usage.path, tmp);
+        assert!(usage.total_bytes >= usage.available_bytes);
+        assert!(usage.total_bytes >= usage.used_bytes);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn mount_for_finds_the_root() {
+        let root = mount_for(Path::new("/")).unwrap();
+        assert_eq!(root.mount_point, PathBuf::from("/"));
+        let missing = mount_for(Path::new("/hab-mount-missing/svc")).unwrap();
+        assert_eq!(missing.mount_point, PathBuf::from("/"));
+        assert!(!mount_for(Path::new("/proc/self")).unwrap().fs_type.is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn filesystems_leave_out_pseudo_filesystems() {
+        let filesystems = filesystems().unwrap();
+        assert!(!filesystems.is_empty());
+        for fs in filesystems.iter() {
+            assert!(!PSEUDO_FILESYSTEMS.contains(&fs.mount.fs_type.as_str()));
+            assert!(fs.mount.mount_point != PathBuf::from("/proc"));
+        }
+    }
+
+    #[test]
+    fn nearest_existing_ends_at_the_current_directory() {
+        assert_eq!(nearest_existing(Path::new("hab-disk-usage-missing")).unwrap(),
+                   PathBuf::from("."));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn block_bytes_do_not_overflow() {
+        assert_eq!(block_bytes(4294967295, 4096), 17592186040320);
+        assert_eq!(block_bytes(u64::max_value() / 2, 4096), u64::max_value());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn filesystems_without_inodes_report_none() {
+        assert_eq!(inode_counts(6553600, 6400000), (Some(6553600), Some(6400000)));
+        assert_eq!(inode_counts(0, 0), (None, None));
+    }
+
+    fn usage(inodes_total: Option<u64>, inodes_free: Option<u64>) -> DiskUsage {
+        DiskUsage {
+            path: PathBuf::from("/hab"),
+            total_bytes: 107374182400,
+            available_bytes: 53687091200,
+            used_bytes: 53687091200,
+            inodes_total: inodes_total,
+            inodes_free: inodes_free,
+            fs_type: None,
+        }
+    }
+
+    #[test]
+    fn inode_pressure_is_the_share_of_inodes_used() {
+        let disk = usage(Some(1000), Some(100));
+        assert!(disk.inode_pressure(90.0));
+        assert!(!disk.inode_pressure(90.5));
+        assert!(!usage(None, None).inode_pressure(0.0));
+    }
+
+    #[test]
+    fn machines_are_normalized() {
+        let cases = [("x86_64", "x86_64"),
+                     ("AMD64", "x86_64"),
+                     ("i386", "x86"),
+                     ("i686", "x86"),
+                     ("x86", "x86"),
+                     ("aarch64", "aarch64"),
+                     ("arm64", "aarch64"),
+                     ("ARM64", "aarch64"),
+                     ("armv7l", "armv7l"),
+                     ("armv7l\n", "armv7l")];
+        for &(machine, arch) in cases.iter() {
+            assert_eq!(normalize_arch(machine), Some(arch));
+        }
+        assert_eq!(normalize_arch("vax"), None);
+    }
+
+    #[test]
+    fn arch_is_named() {
+        assert!(ARCH_NAMES.iter().any(|&(_, name)| name == arch()) || arch() == build_arch());
+    }
+
+    #[test]
+    fn endianness_is_displayed() {
+        assert_eq!(Endianness::Little.to_string(), "little");
+        assert_eq!(Endianness::Big.to_string(), "big");
+    }
+
+    #[test]
+    fn boot_time_is_uptime_before_now() {
+        let now = time::Timespec::new(1476436800, 500000000);
+        let booted = boot_time_at(now, Duration::from_millis(86400250));
+        assert_eq!(booted, time::Timespec::new(1476350400, 0));
+        assert_eq!(rfc3339_utc(booted), "2016-10-13T09:20:00Z");
+    }
+
+    #[test]
+    fn uptime_is_before_now() {
+        let booted = boot_time().unwrap();
+        assert!(booted <= time::get_time());
+        assert!(uptime().unwrap() > Duration::from_secs(0));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn process_user_is_the_current_user() {
+        let process = process_info();
+        let id = |arg| command_output("id", &[arg]).unwrap().trim().to_string();
+        assert_eq!(process.euid.unwrap().to_string(), id("-u"));
+        assert_eq!(process.uid.unwrap().to_string(), id("-ru"));
+        assert_eq!(process.gid.unwrap().to_string(), id("-rg"));
+        // id(1) fails to name a user the user database has no entry for
+        match command_output("id", &["-un"]) {
+            Ok(name) => assert_eq!(process.user, name.trim()),
+            Err(_) => assert_eq!(process.user, id("-u")),
+        }
+        assert!(process.pid != 0);
+        assert!(process.ppid.is_some());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unknown_ids_have_no_names() {
+        assert_eq!(user_name(4000000000), None);
+        assert_eq!(group_name(4000000000), None);
+    }
+
+    #[test]
+    fn nofile_limits_are_compared_to_the_soft_limit() {
+        let limit = |soft, hard| {
+            Rlimit {
+                soft: soft,
+                hard: hard,
+            }
+        };
+        let limits = Rlimits {
+            nofile: limit(Some(1024), Some(4096)),
+            nproc: limit(None, None),
+            core: limit(Some(0), None),
+            memlock: limit(Some(65536), Some(65536)),
+        };
+        assert!(limits.nofile_at_least(1024));
+        assert!(!limits.nofile_at_least(1025));
+        let unlimited = Rlimits { nofile: limit(None, None), ..limits };
+        assert!(unlimited.nofile_at_least(u64::max_value()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn infinite_rlimits_are_unlimited() {
+        assert_eq!(rlim_value(libc::RLIM_INFINITY), None);
+        assert_eq!(rlim_value(0), Some(0));
+        assert_eq!(rlim_value(1024), Some(1024));
+        assert_eq!(rlim_value(libc::RLIM_INFINITY - 1),
+                   Some((libc::RLIM_INFINITY - 1) as u64));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn rlimits_are_read() {
+        let limits = rlimits().unwrap();
+        for limit in &[limits.nofile, limits.nproc, limits.core, limits.memlock] {
+            if let (Some(soft), Some(hard)) = (limit.soft, limit.hard) {
+                assert!(soft <= hard);
+            }
+            // An unlimited soft limit can't be held under a hard one
+            if limit.soft.is_none() {
+                assert_eq!(limit.hard, None);
+            }
+        }
+        assert!(limits.nofile.soft.map_or(true, |soft| soft > 0));
+    }
+
+    #[test]
+    fn time_info_names_a_timezone() {
+        let info = time_info();
+        assert!(!info.timezone.is_empty());
+        assert!(info.utc_offset_seconds.abs() <= 14 * 3600);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn boot_id_is_a_uuid() {
+        let id = boot_id().unwrap().unwrap();
+        assert_eq!(id.len(), 36);
+        assert_eq!(boot_id().unwrap(), Some(id));
+    }
+
+    #[test]
+    fn machine_id_is_stable() {
+        assert_eq!(machine_id().unwrap(), machine_id().unwrap());
+    }
+
+    #[test]
+    fn entropy_is_available() {
+        let available = entropy_available().unwrap();
+        assert!(available <= ENTROPY_UNLIMITED);
+    }
+
+    #[test]
+    fn entropy_floor_is_configurable() {
+        env::set_var(ENTROPY_FLOOR_ENVVAR, "5000");
+        assert_eq!(entropy_floor(), 5000);
+        assert!(entropy_low().unwrap());
+        env::set_var(ENTROPY_FLOOR_ENVVAR, "0");
+        assert!(!entropy_low().unwrap());
+        env::set_var(ENTROPY_FLOOR_ENVVAR, "plenty");
+        assert_eq!(entropy_floor(), 200);
+        env::remove_var(ENTROPY_FLOOR_ENVVAR);
+        assert_eq!(entropy_floor(), 200);
+    }
+
+    #[test]
+    fn hostnames_are_validated() {
+        let long_label: String = iter::repeat('a').take(64).collect();
+        let long_name = vec![&long_label[1..]; 4].join(".");
+        let accepted = ["db-1",
+                        "DB-1",
+                        "db-1.example.com",
+                        "db-1.example.com.",
+                        "1db",
+                        "123",
+                        "x",
+                        &long_label[1..],
+                        &long_name[2..]];
+        for name in accepted.iter() {
+            assert_eq!(validate_hostname(name).unwrap().as_str(), *name);
+        }
+        let rejected = [("", HostnameError::Empty),
+                        (".", HostnameError::Empty),
+                        ("db..example.com", HostnameError::EmptyLabel),
+                        (".db", HostnameError::EmptyLabel),
+                        ("db_1", HostnameError::InvalidChar("db_1".to_string(), '_')),
+                        ("db 1", HostnameError::InvalidChar("db 1".to_string(), ' ')),
+                        (" db", HostnameError::InvalidChar(" db".to_string(), ' ')),
+                        ("db1\n", HostnameError::InvalidChar("db1\n".to_string(), '\n')),
+                        ("db\"1\"", HostnameError::InvalidChar("db\"1\"".to_string(), '"')),
+                        ("dé", HostnameError::InvalidChar("dé".to_string(), 'é')),
+                        ("-db", HostnameError::HyphenAtEdge("-db".to_string())),
+                        ("db-.example.com", HostnameError::HyphenAtEdge("db-".to_string())),
+                        (&long_label, HostnameError::LabelTooLong(long_label.clone())),
+                        (&long_name, HostnameError::TooLong(255))];
+        for &(name, ref err) in rejected.iter() {
+            assert_eq!(validate_hostname(name), Err(err.clone()));
+        }
+    }
+
+    #[test]
+    fn hostnames_are_sanitized() {
+        assert_eq!(validate_hostname("DB-1.Example.COM.").unwrap().sanitized(),
+                   "db-1.example.com");
+        assert_eq!(sanitize_hostname("DB-1.Example.COM."), "db-1.example.com");
+        assert_eq!(sanitize_hostname(" db_1 \n"), "db-1");
+        assert_eq!(sanitize_hostname("db\"1\""), "db-1-");
+    }
+
+    #[test]
+    fn hostname_errors_are_displayed() {
+        assert_eq!(HostnameError::InvalidChar("db_1".to_string(), '_').to_string(),
+                   "label \"db_1\" has the invalid character '_'");
+    }
+
+    #[test]
+    fn virt_is_displayed() {
+        assert_eq!(Virt::None.to_string(), "none");
+        assert_eq!(Virt::VMware.to_string(), "vmware");
+        assert_eq!(Virt::Other("virtualbox".to_string()).to_string(), "virtualbox");
+    }
+
+    #[test]
+    fn link_local_ips_are_not_usable() {
+        assert!(!is_usable(&"fe80::1".parse().unwrap()));
+        assert!(!is_usable(&"febf::1".parse().unwrap()));
+        assert!(!is_usable(&"::1".parse().unwrap()));
+        assert!(!is_usable(&"0.0.0.0".parse().unwrap()));
+        assert!(is_usable(&"2001:db8::5".parse().unwrap()));
+        assert!(is_usable(&"10.0.0.5".parse().unwrap()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn interface_ips_skip_loopback() {
+        for ip in interface_ips(IpPreference::Ipv6First).unwrap() {
+            assert!(!ip.to_string().starts_with("fe80:"));
+        }
+        for ip in interface_ips(IpPreference::Ipv4First).unwrap() {
+            assert!(ip.to_string() != "127.0.0.1");
+            assert!(!ip.to_string().starts_with("fe80:"));
+        }
+    }
+
+    #[test]
+    fn arp_probes_of_ipv6_addresses_are_not_supported() {
+        match arp_probe("2001:db8::5".parse().unwrap(), "eth0", Duration::from_secs(60)) {
+            Ok(ConflictCheck::NotSupported(_)) => (),
+            other => panic!("Expected no probe, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn arp_probes_without_a_hardware_address_return_at_once() {
+        let started = Instant::now();
+        let check = arp_probe("127.0.0.1".parse().unwrap(), "lo", Duration::from_secs(60));
+        assert!(started.elapsed() < Duration::from_secs(30));
+        match check {
+            Ok(ConflictCheck::NotSupported(_)) => (),
+            other => panic!("Expected no probe, got {:?}", other),
+        }
+    }
+
+    fn tags(name: &str) -> Option<String> {
+        match name {
+            "DATACENTER" => Some("us-east".to_string()),
+            "RACK" => Some("r12".to_string()),
+            "DEPOT_TOKEN" => Some("0c2f738a7d0bd300de10".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn allowlisted_env_facts_are_captured() {
+        let names = vec!["DATACENTER".to_string(), "RACK".to_string()];
+        let facts = env_facts_from(&names, tags);
+        assert_eq!(facts.len(), 2);
+        assert_eq!(facts["DATACENTER"], "us-east");
+        assert_eq!(facts["RACK"], "r12");
+    }
+
+    #[test]
+    fn missing_env_facts_are_omitted() {
+        let names = vec!["DATACENTER".to_string(), "ROW".to_string()];
+        let facts = env_facts_from(&names, tags);
+        assert_eq!(facts.keys().collect::<Vec<_>>(), vec!["DATACENTER"]);
+    }
+
+    #[test]
+    fn secret_env_facts_are_never_captured() {
+        let names = vec!["DEPOT_TOKEN".to_string(), "RACK".to_string()];
+        let facts = env_facts_from(&names, tags);
+        assert!(facts.get("DEPOT_TOKEN").is_none());
+        assert_eq!(facts.len(), 1);
+        assert!(is_secret_env_name("aws_secret_access_key"));
+        assert!(is_secret_env_name("DB_PASSWORD"));
+        assert!(!is_secret_env_name("DATACENTER"));
+    }
+
+    #[test]
+    fn secrets_within_env_facts_are_redacted() {
+        let names = vec!["DATABASE_URL".to_string(), "RACK".to_string()];
+        let facts = env_facts_from(&names, |name| match name {
+            "DATABASE_URL" => Some("postgres://hab:s3cret@db:5432/builder".to_string()),
+            _ => tags(name),
+        });
+        assert_eq!(facts["DATABASE_URL"], "postgres://hab:REDACTED@db:5432/builder");
+        assert_eq!(facts["RACK"], "r12");
+    }
+
+    #[test]
+    fn host_names_resolving_to_loopback_only_are_flagged() {
+        // Debian's /etc/hosts maps the host name to 127.0.1.1, and the name of a cloud instance
+        // often resolves in DNS as well
+        let resolver = FakeHost::new()
+            .resolves("db-1", &["127.0.1.1"])
+            .resolves("db-1.example.com", &["10.0.0.5", "127.0.1.1"]);
+        let interfaces = vec![interface("lo", false, &["127.0.0.1", "::1"]),
+                              interface("eth0", true, &["10.0.0.5"])];
+        let resolution = self_resolution_from(&resolver, "db-1", "db-1.example.com", &interfaces);
+        assert_eq!(resolution.hostname.addrs, vec!["127.0.1.1".parse::<IpAddr>().unwrap()]);
+        assert!(resolution.hostname.loopback_only);
+        assert!(!resolution.hostname_resolves_locally());
+        assert!(resolution.loopback_only());
+        assert!(resolution.fqdn.local);
+        assert!(!resolution.fqdn.loopback_only);
+    }
+
+    #[test]
+    fn unresolved_host_names_are_neither_local_nor_loopback() {
+        let resolver = FakeHost::new().resolves("db-1", &["10.0.0.5"]);
+        let interfaces = vec![interface("eth0", true, &["10.0.0.5"])];
+        let resolution = self_resolution_from(&resolver, "db-1", "db-1.example.com", &interfaces);
+        assert!(resolution.hostname_resolves_locally());
+        assert!(resolution.fqdn.addrs.is_empty());
+        assert!(!resolution.fqdn.local && !resolution.fqdn.loopback_only);
+        assert_eq!(resolution.fqdn.error,
+                   Some("db-1.example.com doesn't resolve".to_string()));
+        assert!(!resolution.loopback_only());
+    }
+}
@@ -0,0 +1,2040 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsers of the output of the commands IP discovery falls back to, and of the system files
+//! which describe the network and the processors.
+
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{CpuStat, CpuTimes, DnsConfig, Gateway, IpPreference, LoadAvg, MemInfo, MountInfo,
+            Neighbor, OsRelease, PSEUDO_FILESYSTEMS, SelinuxMode, Virt, is_usable};
+
+/// Returns the source address of the output of `ip route get`, such as
+/// `8.8.8.8 via 10.0.0.1 dev eth0 src 10.0.0.5 uid 0`, which is the word after `src`. The
+/// fields around it differ between kernels, and the last of them may be unrelated to the
+/// address, such as the expiry of a cached route, so no position is relied upon. The lines of
+/// the output may be joined by `\` as `ip -o` does.
+pub fn parse_ip_route(output: &str) -> Option<IpAddr> {
+    let mut words = output.split_whitespace();
+    while let Some(word) = words.next() {
+        if word == "src" {
+            return words.next().and_then(|ip| ip.parse().ok());
+        }
+    }
+    None
+}
+
+/// Returns the first usable address of the given family in the output of `ifconfig`. Both the
+/// `inet addr:10.0.0.5` form of net-tools and the `inet 10.0.0.5` form of BSD and newer
+/// net-tools are understood.
+pub fn parse_ifconfig(output: &str, family: IpPreference) -> Option<IpAddr> {
+    let keyword = match family {
+        IpPreference::Ipv4First => "inet",
+        IpPreference::Ipv6First => "inet6",
+    };
+    for line in output.lines() {
+        let mut words = line.split_whitespace();
+        if words.next() != Some(keyword) {
+            continue;
+        }
+        let mut addr = match words.next() {
+            Some("addr:") => words.next().unwrap_or(""),
+            Some(addr) => addr.trim_left_matches("addr:"),
+            None => continue,
+        };
+        // Strip the prefix length and zone of addresses such as `fe80::1/64` or `fe80::1%lo0`
+        if let Some(pos) = addr.find(|c: char| c == '/' || c == '%') {
+            addr = &addr[..pos];
+        }
+        match addr.parse::<IpAddr>() {
+            Ok(ip) if is_usable(&ip) => return Some(ip),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Parses the contents of `/etc/resolv.conf`. A `domain` line is the same as a `search` line
+/// listing one domain, and as for the resolver the last of them wins. Lines starting with `#` or
+/// `;` are comments.
+pub fn parse_resolv_conf(contents: &str) -> DnsConfig {
+    let mut config = DnsConfig::default();
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("nameserver") => {
+                if let Some(ip) = fields.next().and_then(|ip| ip.parse().ok()) {
+                    config.nameservers.push(ip);
+                }
+            }
+            Some("domain") | Some("search") => {
+                config.search = fields.map(|d| d.trim_right_matches('.'))
+                    .filter(|d| !d.is_empty())
+                    .map(|d| d.to_string())
+                    .collect();
+            }
+            Some("options") => config.options.extend(fields.map(|o| o.to_string())),
+            _ => (),
+        }
+    }
+    config
+}
+
+/// Returns the local domain named by the contents of `/etc/resolv.conf`, the first of its search
+/// domains.
+pub fn parse_resolv_conf_domain(contents: &str) -> Option<String> {
+    parse_resolv_conf(contents).search.into_iter().next()
+}
+
+/// The fields of the output of BSD `route -n get` which identify the outgoing interface.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RouteGet {
+    /// Address of the next hop, without the zone of a link-local address
+    pub gateway: Option<IpAddr>,
+    /// Name of the interface, such as `en0`
+    pub interface: Option<String>,
+    /// Address of the interface. Only OpenBSD reports it.
+    pub if_address: Option<IpAddr>,
+}
+
+/// Parses the output of BSD `route -n get`, which lists one `key: value` field per line, such as
+/// `  interface: en0`.
+pub fn parse_route_get(output: &str) -> RouteGet {
+    let mut route = RouteGet::default();
+    for line in output.lines() {
+        let mut parts = line.splitn(2, ':');
+        let key = parts.next().unwrap_or("").trim();
+        let value = parts.next().unwrap_or("").trim();
+        match key {
+            "interface" if !value.is_empty() => route.interface = Some(value.to_string()),
+            "gateway" => route.gateway = value.split('%').next().and_then(|v| v.parse().ok()),
+            "if address" => route.if_address = value.parse().ok(),
+            _ => (),
+        }
+    }
+    route
+}
+
+/// Parses the contents of `/etc/hosts` into its entries, each an address and the names it is
+/// given, in the order of the file. Comments start with `#` anywhere on a line. A line which
+/// doesn't start with an address, or gives it no names, is skipped and logged rather than
+/// failing the rest of the file.
+pub fn parse_etc_hosts(contents: &str) -> Vec<(IpAddr, Vec<String>)> {
+    let mut entries = vec![];
+    for (i, line) in contents.lines().enumerate() {
+        let mut fields = line.split('#').next().unwrap_or("").split_whitespace();
+        let addr = match fields.next() {
+            Some(addr) => addr,
+            None => continue,
+        };
+        // Link-local IPv6 addresses may name the interface they are on, as in `fe80::1%eth0`
+        let ip = match addr.split('%').next().unwrap_or(addr).parse() {
+            Ok(ip) => ip,
+            Err(_) => {
+                debug!("Skipping line {} of the hosts file, {} isn't an address", i + 1, addr);
+                continue;
+            }
+        };
+        let names: Vec<String> = fields.map(|name| name.to_string()).collect();
+        if names.is_empty() {
+            debug!("Skipping line {} of the hosts file, which gives {} no names", i + 1, ip);
+            continue;
+        }
+        entries.push((ip, names));
+    }
+    entries
+}
+
+/// Returns the names the contents of `/etc/hosts` give any of the addresses, once each, in the
+/// order of the file. Loopback addresses and the names of localhost are left out, as every
+/// host answers to them.
+pub fn parse_host_aliases(contents: &str, addrs: &[IpAddr]) -> Vec<String> {
+    let mut aliases: Vec<String> = vec![];
+    for (ip, names) in parse_etc_hosts(contents) {
+        if is_loopback(&ip) || !addrs.contains(&ip) {
+            continue;
+        }
+        for name in names {
+            if !is_localhost_name(&name) && !aliases.contains(&name) {
+                aliases.push(name);
+            }
+        }
+    }
+    aliases
+}
+
+/// True for `127.0.0.0/8` and `::1`.
+pub fn is_loopback(ip: &IpAddr) -> bool {
+    match *ip {
+        IpAddr::V4(ref ip) => ip.is_loopback(),
+        IpAddr::V6(ref ip) => ip.is_loopback(),
+    }
+}
+
+/// True for the names distributions give the loopback addresses, such as `localhost4` and
+/// `ip6-localhost`.
+fn is_localhost_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.starts_with("localhost") || name.starts_with("ip6-localhost") ||
+    name == "ip6-loopback"
+}
+
+/// Returns the hardware address in the contents of a Linux `/sys/class/net/<name>/address` file
+/// as lowercase colon separated octets, or `None` for an interface without one, whose address is
+/// empty or all zeros.
+pub fn parse_mac(contents: &str) -> Option<String> {
+    let contents = contents.trim();
+    if contents.is_empty() {
+        return None;
+    }
+    let mut octets = vec![];
+    for octet in contents.split(':') {
+        match u8::from_str_radix(octet, 16) {
+            Ok(octet) => octets.push(octet),
+            Err(_) => return None,
+        }
+    }
+    if octets.iter().all(|o| *o == 0) {
+        return None;
+    }
+    let octets: Vec<String> = octets.iter().map(|o| format!("{:02x}", o)).collect();
+    Some(octets.join(":"))
+}
+
+/// Returns the octets of a hardware address of six colon separated hex octets, such as
+/// `52:54:00:12:34:56`.
+pub fn parse_mac_octets(mac: &str) -> Option<[u8; 6]> {
+    let parts: Vec<&str> = mac.trim().split(':').collect();
+    if parts.len() != 6 {
+        return None;
+    }
+    let mut octets = [0; 6];
+    for (octet, part) in octets.iter_mut().zip(parts) {
+        match u8::from_str_radix(part, 16) {
+            Ok(value) if part.len() == 2 => *octet = value,
+            _ => return None,
+        }
+    }
+    Some(octets)
+}
+
+/// Returns the inode number in the target of a Linux namespace link such as
+/// `/proc/self/ns/net`, which reads `net:[4026531992]`, and which is the same for every process
+/// in that namespace.
+pub fn parse_ns_link(link: &str) -> Option<String> {
+    let start = match link.find(":[") {
+        Some(colon) => colon + 2,
+        None => return None,
+    };
+    if !link.ends_with(']') || start >= link.len() - 1 {
+        return None;
+    }
+    let inode = &link[start..link.len() - 1];
+    if inode.chars().all(|c| c.is_digit(10)) {
+        Some(inode.to_string())
+    } else {
+        None
+    }
+}
+
+/// Returns the id in the contents of `/etc/machine-id` or `/proc/sys/kernel/random/boot_id` in
+/// lowercase, if they hold 32 hex digits or a UUID such as
+/// `4b3f0c2e-8a51-4d3c-9d2b-6f1e7a0c5d94`.
+pub fn parse_host_id(contents: &str) -> Option<String> {
+    let id = contents.trim();
+    let is_hex = |part: &str| part.chars().all(|c| c.is_digit(16));
+    let valid = match id.len() {
+        32 => is_hex(id),
+        36 => {
+            let groups: Vec<&str> = id.split('-').collect();
+            groups.iter().map(|g| g.len()).collect::<Vec<_>>() == [8, 4, 4, 4, 12] &&
+            groups.iter().all(|g| is_hex(g))
+        }
+        _ => false,
+    };
+    if valid { Some(id.to_lowercase()) } else { None }
+}
+
+/// `RTF_UP` of the flags of a Linux route
+const RTF_UP: u32 = 0x0001;
+/// `RTF_GATEWAY` of the flags of a Linux route
+const RTF_GATEWAY: u32 = 0x0002;
+
+/// Returns the default route in the contents of Linux's `/proc/net/route`. Of several default
+/// routes the one with the lowest metric wins, as it does in the kernel.
+///
+/// Addresses in the table are the hexadecimal value of the address in network byte order read as
+/// a native integer, so `010200C0` is `192.0.2.1` on a little-endian host.
+pub fn parse_proc_net_route(contents: &str) -> Option<Gateway> {
+    let mut best: Option<(u32, Gateway)> = None;
+    // Iface Destination Gateway Flags RefCnt Use Metric Mask MTU Window IRTT
+    for line in contents.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            continue;
+        }
+        let (destination, gateway, flags, metric, mask) =
+            match (hex_u32(fields[1]), hex_u32(fields[2]), hex_u32(fields[3]), fields[6].parse(),
+                   hex_u32(fields[7])) {
+                (Some(d), Some(g), Some(f), Ok(m), Some(k)) => (d, g, f, m, k),
+                _ => continue,
+            };
+        if destination != 0 || mask != 0 || !is_gateway_route(flags) {
+            continue;
+        }
+        if best.as_ref().map_or(true, |&(best_metric, _)| metric < best_metric) {
+            best = Some((metric,
+                         Gateway {
+                             ip: IpAddr::V4(Ipv4Addr::from(u32::from_be(gateway))),
+                             interface: fields[0].to_string(),
+                         }));
+        }
+    }
+    best.map(|(_, gateway)| gateway)
+}
+
+/// Returns the default route in the contents of Linux's `/proc/net/ipv6_route`, preferring the
+/// lowest metric like `parse_proc_net_route`.
+pub fn parse_proc_net_ipv6_route(contents: &str) -> Option<Gateway> {
+    let mut best: Option<(u32, Gateway)> = None;
+    // Destination DestPrefixLen Source SourcePrefixLen NextHop Metric RefCnt Use Flags Iface
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 || fields[9] == "lo" {
+            continue;
+        }
+        let (destination, prefix_len, next_hop, metric, flags) =
+            match (hex_ipv6(fields[0]), hex_u32(fields[1]), hex_ipv6(fields[4]),
+                   hex_u32(fields[5]), hex_u32(fields[8])) {
+                (Some(d), Some(p), Some(n), Some(m), Some(f)) => (d, p, n, m, f),
+                _ => continue,
+            };
+        if !destination.is_unspecified() || prefix_len != 0 || next_hop.is_unspecified() ||
+           !is_gateway_route(flags) {
+            continue;
+        }
+        if best.as_ref().map_or(true, |&(best_metric, _)| metric < best_metric) {
+            best = Some((metric,
+                         Gateway {
+                             ip: IpAddr::V6(next_hop),
+                             interface: fields[9].to_string(),
+                         }));
+        }
+    }
+    best.map(|(_, gateway)| gateway)
+}
+
+/// `ATF_COM` of the flags of a Linux ARP entry, set once the hardware address is known
+const ATF_COM: u32 = 0x02;
+/// `ATF_PERM` of the flags of a Linux ARP entry, set on entries added by hand
+const ATF_PERM: u32 = 0x04;
+
+/// Returns the neighbors in the contents of Linux's `/proc/net/arp`. Entries whose hardware
+/// address isn't known yet, or was never found, are left out. The table has no states other than
+/// `permanent` for entries added by hand and `complete` for the others.
+pub fn parse_proc_net_arp(contents: &str) -> Vec<Neighbor> {
+    let mut neighbors = vec![];
+    // IP address HW type Flags HW address Mask Device
+    for line in contents.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 {
+            continue;
+        }
+        let (ip, flags, mac) =
+            match (fields[0].parse(), hex_u32(fields[2].trim_left_matches("0x")),
+                   parse_mac(fields[3])) {
+                (Ok(ip), Some(flags), Some(mac)) => (ip, flags, mac),
+                _ => continue,
+            };
+        if flags & ATF_COM == 0 {
+            continue;
+        }
+        neighbors.push(Neighbor {
+            ip: ip,
+            mac: mac,
+            interface: fields[5].to_string(),
+            state: if flags & ATF_PERM != 0 { "permanent" } else { "complete" }.to_string(),
+        });
+    }
+    neighbors
+}
+
+/// Returns the neighbors in the output of `ip -o neigh show`, such as
+/// `10.0.0.1 dev eth0 lladdr 52:54:00:12:34:01 REACHABLE`, with their states in lowercase.
+/// Entries which are `INCOMPLETE` or `FAILED`, or have no hardware address, are left out.
+pub fn parse_ip_neigh(output: &str) -> Vec<Neighbor> {
+    let mut neighbors = vec![];
+    for line in output.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let ip = match words.first().and_then(|ip| ip.parse().ok()) {
+            Some(ip) => ip,
+            None => continue,
+        };
+        let after = |keyword| {
+            words.iter().position(|word| *word == keyword).and_then(|pos| words.get(pos + 1))
+        };
+        let (interface, mac) = match (after("dev"), after("lladdr")) {
+            (Some(interface), Some(mac)) => {
+                match parse_mac(mac) {
+                    Some(mac) => (interface, mac),
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+        let state = words.last().map_or(String::new(), |state| state.to_lowercase());
+        if state == "incomplete" || state == "failed" {
+            continue;
+        }
+        neighbors.push(Neighbor {
+            ip: ip,
+            mac: mac,
+            interface: interface.to_string(),
+            state: state,
+        });
+    }
+    neighbors
+}
+
+fn is_gateway_route(flags: u32) -> bool {
+    flags & RTF_UP != 0 && flags & RTF_GATEWAY != 0
+}
+
+fn hex_u32(value: &str) -> Option<u32> {
+    u32::from_str_radix(value, 16).ok()
+}
+
+/// Parses an IPv6 address written as 32 hexadecimal digits without separators.
+fn hex_ipv6(value: &str) -> Option<Ipv6Addr> {
+    let digits = value.as_bytes();
+    if digits.len() != 32 {
+        return None;
+    }
+    let mut octets = [0u8; 16];
+    for (i, octet) in octets.iter_mut().enumerate() {
+        match ((digits[i * 2] as char).to_digit(16), (digits[i * 2 + 1] as char).to_digit(16)) {
+            (Some(high), Some(low)) => *octet = (high * 16 + low) as u8,
+            _ => return None,
+        }
+    }
+    Some(Ipv6Addr::from(octets))
+}
+
+/// What Linux's `/proc/cpuinfo` says about the processors of a host.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CpuTopology {
+    /// Number of distinct cores, if the kernel reports the `core id` of each processor
+    pub cores: Option<u32>,
+    pub model: Option<String>,
+}
+
+/// Returns the cores and model name in the contents of Linux's `/proc/cpuinfo`. Core ids are
+/// only unique within a socket, so cores are told apart by their `physical id` and `core id`.
+pub fn parse_cpuinfo(contents: &str) -> CpuTopology {
+    let mut cores = HashSet::new();
+    let mut model = None;
+    let mut physical_id = None;
+    let mut core_id = None;
+    // Processors are separated by a blank line, and the one appended ends the last of them
+    for line in contents.lines().chain(Some("")) {
+        if line.trim().is_empty() {
+            if let Some(core) = core_id.take() {
+                cores.insert((physical_id.unwrap_or("0"), core));
+            }
+            physical_id = None;
+            continue;
+        }
+        let mut fields = line.splitn(2, ':');
+        let key = fields.next().unwrap_or("").trim();
+        let value = fields.next().unwrap_or("").trim();
+        match key {
+            "physical id" => physical_id = Some(value),
+            "core id" => core_id = Some(value),
+            "model name" if model.is_none() && !value.is_empty() => {
+                model = Some(value.to_string())
+            }
+            _ => (),
+        }
+    }
+    CpuTopology {
+        cores: if cores.is_empty() {
+            None
+        } else {
+            Some(cores.len() as u32)
+        },
+        model: model,
+    }
+}
+
+/// Returns the number of CPUs in a Linux CPU list, such as `0-3,8,10-11` in a cgroup's
+/// `cpuset.cpus`, or `None` if it is empty or malformed. A list of more CPUs than a `u32` counts
+/// is malformed.
+pub fn parse_cpu_list(list: &str) -> Option<u32> {
+    let mut count: u32 = 0;
+    for range in list.trim().split(',') {
+        let mut bounds = range.splitn(2, '-');
+        let first: u32 = match bounds.next().and_then(|b| b.trim().parse().ok()) {
+            Some(first) => first,
+            None => return None,
+        };
+        let last: u32 = match bounds.next() {
+            Some(last) => {
+                match last.trim().parse() {
+                    Ok(last) => last,
+                    Err(_) => return None,
+                }
+            }
+            None => first,
+        };
+        if last < first {
+            return None;
+        }
+        count = match (last - first).checked_add(1).and_then(|n| count.checked_add(n)) {
+            Some(count) => count,
+            None => return None,
+        };
+    }
+    Some(count)
+}
+
+/// Returns the memory in the contents of Linux's `/proc/meminfo`, or `None` if it has no
+/// `MemTotal`. Kernels older than 3.14 don't report `MemAvailable`, which is then estimated as
+/// the free memory plus the page cache.
+pub fn parse_meminfo(contents: &str) -> Option<MemInfo> {
+    let mut total = None;
+    let mut available = None;
+    let mut free = None;
+    let mut buffers = None;
+    let mut cached = None;
+    let mut swap_total = None;
+    let mut swap_free = None;
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        let field = match fields.next() {
+            Some("MemTotal:") => &mut total,
+            Some("MemAvailable:") => &mut available,
+            Some("MemFree:") => &mut free,
+            Some("Buffers:") => &mut buffers,
+            Some("Cached:") => &mut cached,
+            Some("SwapTotal:") => &mut swap_total,
+            Some("SwapFree:") => &mut swap_free,
+            _ => continue,
+        };
+        *field = fields.next().and_then(|kb| kb.parse::<u64>().ok());
+    }
+    let available = match (available, free) {
+        (Some(available), _) => Some(available),
+        (None, Some(free)) => {
+            Some(free.saturating_add(buffers.unwrap_or(0)).saturating_add(cached.unwrap_or(0)))
+        }
+        (None, None) => None,
+    };
+    total.map(|total| {
+        MemInfo {
+            total_kb: total,
+            available_kb: available,
+            free_kb: free,
+            swap_total_kb: swap_total,
+            swap_free_kb: swap_free,
+        }
+    })
+}
+
+/// Returns the size of a huge page, in kilobytes, in the contents of Linux's `/proc/meminfo`.
+pub fn parse_hugepage_size(contents: &str) -> Option<u64> {
+    contents.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("Hugepagesize:") => fields.next().and_then(|kb| kb.parse().ok()),
+                _ => None,
+            }
+        })
+        .next()
+}
+
+/// Returns the active value of a sysfs setting which lists every value with the active one in
+/// brackets, such as `always [madvise] never`.
+pub fn parse_bracketed_choice(contents: &str) -> Option<String> {
+    contents.split_whitespace()
+        .filter(|value| value.len() > 2 && value.starts_with('[') && value.ends_with(']'))
+        .map(|value| value[1..value.len() - 1].to_string())
+        .next()
+}
+
+/// Returns the bytes of a cgroup's memory limit, the contents of v1's `memory.limit_in_bytes` or
+/// v2's `memory.max`, or `None` for v2's `max`, which is no limit.
+pub fn parse_memory_limit(contents: &str) -> Option<u64> {
+    contents.trim().parse().ok()
+}
+
+/// Returns the limit in a cgroup file holding a number or `max`, such as `pids.max` or v2's
+/// `memory.max`: `Some(None)` for `max`, which is no limit, or `None` if the file is malformed.
+pub fn parse_cgroup_max(contents: &str) -> Option<Option<u64>> {
+    match contents.trim() {
+        "max" => Some(None),
+        limit => limit.parse().ok().map(Some),
+    }
+}
+
+/// Returns the CPUs the CPU bandwidth limit of a v2 cgroup allows, given its `cpu.max`, such as
+/// `150000 100000` for one and a half: `Some(None)` for a quota of `max`, which is no limit, or
+/// `None` if the file is malformed.
+pub fn parse_cpu_max(contents: &str) -> Option<Option<f64>> {
+    let fields: Vec<&str> = contents.split_whitespace().collect();
+    match fields.len() {
+        2 => parse_cfs_quota(if fields[0] == "max" { "-1" } else { fields[0] }, fields[1]),
+        _ => None,
+    }
+}
+
+/// Returns the CPUs the CPU bandwidth limit of a v1 cgroup allows, given its `cpu.cfs_quota_us`
+/// and `cpu.cfs_period_us`: `Some(None)` for a quota of -1, which is no limit, or `None` if
+/// either is malformed.
+pub fn parse_cfs_quota(quota: &str, period: &str) -> Option<Option<f64>> {
+    let period = match period.trim().parse::<u64>() {
+        Ok(period) if period > 0 => period,
+        _ => return None,
+    };
+    match quota.trim().parse::<i64>() {
+        Ok(-1) => Some(None),
+        Ok(quota) if quota > 0 => Some(Some(quota as f64 / period as f64)),
+        _ => None,
+    }
+}
+
+/// Returns the type of the filesystem `path` is on, given the contents of Linux's
+/// `/proc/self/mounts`. The filesystem is the one of the deepest mount point `path` is under,
+/// and of those mounted on the same point, the last, which hides the others.
+pub fn parse_mounts(contents: &str, path: &Path) -> Option<String> {
+    let mut found: Option<(usize, &str)> = None;
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 3 {
+            continue;
+        }
+        let mount_point = unescape_mount_field(fields[1]);
+        if !path.starts_with(&mount_point) {
+            continue;
+        }
+        let depth = Path::new(&mount_point).components().count();
+        if found.map_or(true, |(deepest, _)| depth >= deepest) {
+            found = Some((depth, fields[2]));
+        }
+    }
+    found.map(|(_, fs_type)| fs_type.to_string())
+}
+
+/// Returns the mount `path` is on, given the contents of Linux's `/proc/self/mountinfo`, such as
+/// `43 41 8:33 /export/svc /hab/svc ro,nosuid - xfs /dev/sdc1 rw,attr2`. The mount is the one of
+/// the deepest mount point `path` is under, and of those mounted on the same point, the last.
+pub fn parse_mountinfo(contents: &str, path: &Path) -> Option<MountInfo> {
+    let mut found: Option<(usize, MountInfo)> = None;
+    for (_, mount) in contents.lines().filter_map(parse_mountinfo_line) {
+        if !path.starts_with(&mount.mount_point) {
+            continue;
+        }
+        let depth = mount.mount_point.components().count();
+        if found.as_ref().map_or(false, |&(deepest, _)| depth < deepest) {
+            continue;
+        }
+        found = Some((depth, mount));
+    }
+    found.map(|(_, mount)| mount)
+}
+
+/// Returns the mounts of real filesystems in the contents of Linux's `/proc/self/mountinfo`, in
+/// the order they are listed. Pseudo filesystems such as `proc` are left out, see
+/// `PSEUDO_FILESYSTEMS`, as are `tmpfs` mounts unless `include_tmpfs` is set.
+///
+/// A filesystem mounted more than once, such as by bind mounts, is listed once so its space is
+/// counted once: as the mount showing the most of it, the one whose directory of the filesystem
+/// is the shallowest, or the first of those.
+pub fn parse_mount_table(contents: &str, include_tmpfs: bool) -> Vec<MountInfo> {
+    let mut mounts: Vec<(String, MountInfo)> = vec![];
+    for (device, mount) in contents.lines().filter_map(parse_mountinfo_line) {
+        if PSEUDO_FILESYSTEMS.contains(&mount.fs_type.as_str()) ||
+           (mount.fs_type == "tmpfs" && !include_tmpfs) {
+            continue;
+        }
+        match mounts.iter().position(|&(ref seen, _)| *seen == device) {
+            Some(i) => {
+                if root_depth(&mount) < root_depth(&mounts[i].1) {
+                    mounts[i].1 = mount;
+                }
+            }
+            None => mounts.push((device, mount)),
+        }
+    }
+    mounts.into_iter().map(|(_, mount)| mount).collect()
+}
+
+fn root_depth(mount: &MountInfo) -> usize {
+    Path::new(&mount.root).components().count()
+}
+
+/// Returns the device and the mount of a line of `/proc/self/mountinfo`, see `parse_mountinfo`.
+///
+/// Each line holds the mount's id, its parent's id, the device, the directory of the filesystem
+/// mounted, the mount point, the options of the mount, any number of optional tags ended by `-`,
+/// the filesystem type, the source and the options of the filesystem. Either set of options can
+/// make the mount read only.
+fn parse_mountinfo_line(line: &str) -> Option<(String, MountInfo)> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let separator = match fields.iter().skip(6).position(|f| *f == "-") {
+        Some(pos) => pos + 6,
+        None => return None,
+    };
+    if fields.len() < separator + 3 {
+        return None;
+    }
+    let mount_options: Vec<&str> = fields[5].split(',').collect();
+    let fs_read_only = fields.get(separator + 3)
+        .map_or(false, |options| options.split(',').any(|o| o == "ro"));
+    Some((fields[2].to_string(),
+          MountInfo {
+        mount_point: PathBuf::from(unescape_mount_field(fields[4])),
+        root: unescape_mount_field(fields[3]),
+        source: unescape_mount_field(fields[separator + 2]),
+        fs_type: fields[separator + 1].to_string(),
+        read_only: mount_options.contains(&"ro") || fs_read_only,
+        noexec: mount_options.contains(&"noexec"),
+        nosuid: mount_options.contains(&"nosuid"),
+    }))
+}
+
+/// Decodes the octal escapes, such as `\040` for a space, the mount table writes whitespace and
+/// backslashes in paths as.
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() &&
+           bytes[i + 1..i + 4].iter().all(|b| *b >= b'0' && *b <= b'7') {
+            let value = bytes[i + 1..i + 4].iter().fold(0u32, |v, b| v * 8 + (*b - b'0') as u32);
+            decoded.push(value as u8);
+            i += 4;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Returns the distribution in the contents of `/etc/os-release`, or `None` if it has no `ID`.
+pub fn parse_os_release(contents: &str) -> Option<OsRelease> {
+    let mut id = None;
+    let mut version_id = None;
+    let mut pretty_name = None;
+    for (key, value) in shell_assignments(contents) {
+        match key {
+            "ID" => id = Some(value.to_lowercase()),
+            "VERSION_ID" => version_id = Some(value),
+            "PRETTY_NAME" => pretty_name = Some(value),
+            _ => (),
+        }
+    }
+    id.map(|id| {
+        OsRelease {
+            id: id,
+            version_id: version_id,
+            pretty_name: pretty_name,
+        }
+    })
+}
+
+/// Returns the distribution in the contents of `/etc/lsb-release`, which Ubuntu shipped before
+/// `/etc/os-release`, or `None` if it has no `DISTRIB_ID`.
+pub fn parse_lsb_release(contents: &str) -> Option<OsRelease> {
+    let mut id = None;
+    let mut version_id = None;
+    let mut pretty_name = None;
+    for (key, value) in shell_assignments(contents) {
+        match key {
+            "DISTRIB_ID" => id = Some(value.to_lowercase()),
+            "DISTRIB_RELEASE" => version_id = Some(value),
+            "DISTRIB_DESCRIPTION" => pretty_name = Some(value),
+            _ => (),
+        }
+    }
+    id.map(|id| {
+        OsRelease {
+            id: id,
+            version_id: version_id,
+            pretty_name: pretty_name,
+        }
+    })
+}
+
+/// Returns the distribution in the contents of `/etc/redhat-release`, such as `CentOS release
+/// 6.8 (Final)`, which the distributions of the Red Hat family shipped before `/etc/os-release`.
+/// The ids are the ones their `/etc/os-release` uses.
+pub fn parse_redhat_release(contents: &str) -> Option<OsRelease> {
+    let line = match contents.lines().map(|l| l.trim()).find(|l| !l.is_empty()) {
+        Some(line) => line,
+        None => return None,
+    };
+    let id = if line.starts_with("Red Hat") {
+        "rhel".to_string()
+    } else {
+        match line.split_whitespace().next() {
+            Some(name) => name.to_lowercase(),
+            None => return None,
+        }
+    };
+    let mut words = line.split_whitespace().skip_while(|w| *w != "release");
+    let version_id = words.nth(1).map(|v| v.to_string());
+    Some(OsRelease {
+        id: id,
+        version_id: version_id,
+        pretty_name: Some(line.to_string()),
+    })
+}
+
+/// Returns the `KEY=value` assignments of a release file, which are shell variable assignments
+/// whose values may be quoted. Comments and malformed lines are skipped.
+fn shell_assignments(contents: &str) -> Vec<(&str, String)> {
+    let mut assignments = vec![];
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) if !key.is_empty() => {
+                assignments.push((key.trim(), unquote_shell(value.trim())))
+            }
+            _ => (),
+        }
+    }
+    assignments
+}
+
+/// Removes the quotes of a shell value, undoing the backslash escapes of a double quoted one.
+fn unquote_shell(value: &str) -> String {
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        return value[1..value.len() - 1].to_string();
+    }
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        let mut unquoted = String::new();
+        let mut chars = value[1..value.len() - 1].chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    unquoted.push(escaped);
+                }
+            } else {
+                unquoted.push(c);
+            }
+        }
+        return unquoted;
+    }
+    value.to_string()
+}
+
+/// Returns the time since boot in the contents of Linux's `/proc/uptime`, such as
+/// `350735.47 234388.90`, whose first field is the seconds since boot.
+pub fn parse_proc_uptime(contents: &str) -> Option<Duration> {
+    contents.split_whitespace().next().and_then(|secs| parse_fractional_secs(secs))
+}
+
+/// Returns the boot time in the output of `sysctl -n kern.boottime` on macOS and the BSDs, such
+/// as `{ sec = 1476350400, usec = 0 } Thu Oct 13 09:20:00 2016`, as seconds since the epoch.
+pub fn parse_kern_boottime(output: &str) -> Option<i64> {
+    let mut words = output.split(|c: char| c.is_whitespace() || c == ',');
+    while let Some(word) = words.next() {
+        if word == "sec" {
+            return words.find(|w| !w.is_empty() && *w != "=").and_then(|s| s.parse().ok());
+        }
+    }
+    None
+}
+
+/// Returns the IANA name of the timezone `/etc/localtime` links to, such as `Europe/Berlin` for
+/// `/usr/share/zoneinfo/Europe/Berlin`. The link may be relative, and may point into the `posix`
+/// or `right` trees of the zoneinfo database, or into `/var/db/timezone/zoneinfo` as on macOS.
+pub fn parse_localtime_link(target: &str) -> Option<String> {
+    let name = match target.find("zoneinfo/") {
+        Some(pos) => &target[pos + "zoneinfo/".len()..],
+        None => return None,
+    };
+    let name = ["posix/", "right/"]
+        .iter()
+        .find(|tree| name.starts_with(*tree))
+        .map_or(name, |tree| &name[tree.len()..]);
+    if name.is_empty() || name.ends_with('/') {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Returns the IANA name of the timezone in the contents of Debian's `/etc/timezone`, its first
+/// line which isn't a comment.
+pub fn parse_etc_timezone(contents: &str) -> Option<String> {
+    contents.lines()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+}
+
+/// Parses a number of seconds with an optional fraction, such as `350735.47`, without the
+/// rounding of a float.
+fn parse_fractional_secs(value: &str) -> Option<Duration> {
+    let mut parts = value.splitn(2, '.');
+    let secs: u64 = match parts.next().and_then(|s| s.parse().ok()) {
+        Some(secs) => secs,
+        None => return None,
+    };
+    let nanos = match parts.next() {
+        Some("") | None => 0,
+        Some(fraction) => {
+            if !fraction.bytes().all(|b| b >= b'0' && b <= b'9') {
+                return None;
+            }
+            // Nanoseconds are the first nine digits of the fraction
+            let digits: String = fraction.chars().chain("000000000".chars()).take(9).collect();
+            digits.parse().unwrap_or(0)
+        }
+    };
+    Some(Duration::new(secs, nanos))
+}
+
+/// Returns the load average in the contents of Linux's `/proc/loadavg`, such as
+/// `0.42 0.36 0.30 2/345 12345`, whose fourth field is the running and total processes.
+pub fn parse_proc_loadavg(contents: &str) -> Option<LoadAvg> {
+    let fields: Vec<&str> = contents.split_whitespace().collect();
+    if fields.len() < 3 {
+        return None;
+    }
+    let mut loads = vec![];
+    for load in &fields[..3] {
+        match load.parse::<f64>() {
+            Ok(load) => loads.push(load),
+            Err(_) => return None,
+        }
+    }
+    let mut processes = fields.get(3).map_or("", |p| *p).splitn(2, '/');
+    Some(LoadAvg {
+        one: loads[0],
+        five: loads[1],
+        fifteen: loads[2],
+        running: processes.next().and_then(|r| r.parse().ok()),
+        total: processes.next().and_then(|t| t.parse().ok()),
+    })
+}
+
+/// Returns the parent pid in the contents of a Linux `/proc/<pid>/stat`, such as
+/// `4242 (sleep) S 4100 4242 4100 ...`. The command in parentheses may itself hold spaces and
+/// parentheses, so the fields are read from after the last closing one.
+pub fn parse_proc_pid_ppid(contents: &str) -> Option<u32> {
+    let end = match contents.rfind(')') {
+        Some(end) => end,
+        None => return None,
+    };
+    contents[end + 1..].split_whitespace().nth(1).and_then(|ppid| ppid.parse().ok())
+}
+
+/// Returns the mode in the contents of Linux's `/sys/fs/selinux/enforce`, `1` when enforcing
+/// and `0` when permissive.
+pub fn parse_selinux_enforce(contents: &str) -> Option<SelinuxMode> {
+    match contents.trim() {
+        "1" => Some(SelinuxMode::Enforcing),
+        "0" => Some(SelinuxMode::Permissive),
+        _ => None,
+    }
+}
+
+/// Returns the mode in the output of `getenforce`, such as `Enforcing`.
+pub fn parse_getenforce(output: &str) -> Option<SelinuxMode> {
+    match output.trim().to_lowercase().as_str() {
+        "enforcing" => Some(SelinuxMode::Enforcing),
+        "permissive" => Some(SelinuxMode::Permissive),
+        "disabled" => Some(SelinuxMode::Disabled),
+        _ => None,
+    }
+}
+
+/// Returns the number of profiles in the contents of AppArmor's `profiles` file of securityfs,
+/// a line for each such as `/usr/sbin/ntpd (enforce)`.
+pub fn parse_apparmor_profiles(contents: &str) -> u32 {
+    contents.lines().filter(|line| !line.trim().is_empty()).count() as u32
+}
+
+/// Returns the CPU time counters in the contents of Linux's `/proc/stat`, from its `cpu` line of
+/// all CPUs together and its `cpuN` lines of each online CPU, such as
+/// `cpu0 21480 302 5990 877012 1187 0 797 204 0 0`.
+///
+/// The kernels of 2.4 only had the first four columns, and iowait, irq, softirq, steal, guest
+/// and guest_nice were added over 2.6, so missing columns count as 0 and columns past those
+/// known are ignored. Guest time is already part of the user and nice columns.
+pub fn parse_proc_stat(contents: &str) -> Option<CpuStat> {
+    let mut total = None;
+    let mut cores = vec![];
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        let name = match fields.next() {
+            Some(name) if name.starts_with("cpu") => name,
+            _ => continue,
+        };
+        let mut counters = vec![];
+        for field in fields {
+            match field.parse::<u64>() {
+                Ok(counter) => counters.push(counter),
+                Err(_) => return None,
+            }
+        }
+        if counters.len() < 4 {
+            return None;
+        }
+        counters.resize(8, 0);
+        let times = CpuTimes {
+            user: counters[0],
+            nice: counters[1],
+            system: counters[2],
+            idle: counters[3],
+            iowait: counters[4],
+            irq: counters[5],
+            softirq: counters[6],
+            steal: counters[7],
+        };
+        if name == "cpu" {
+            total = Some(times);
+        } else {
+            match name[3..].parse::<u32>() {
+                Ok(cpu) => cores.push((cpu, times)),
+                Err(_) => return None,
+            }
+        }
+    }
+    total.map(|total| {
+        CpuStat {
+            total: total,
+            cores: cores,
+        }
+    })
+}
+
+/// What a Linux host shows of the container or virtual machine it runs in.
+#[derive(Debug, Default)]
+pub struct VirtSigns<'a> {
+    /// `/.dockerenv` exists
+    pub dockerenv: bool,
+    /// Contents of `/proc/1/cgroup`
+    pub init_cgroup: Option<&'a str>,
+    /// Contents of `/sys/class/dmi/id/sys_vendor` and `/sys/class/dmi/id/product_name`
+    pub dmi_vendor: Option<&'a str>,
+    pub dmi_product: Option<&'a str>,
+    /// `/proc/xen` exists
+    pub proc_xen: bool,
+    /// Contents of `/proc/cpuinfo`
+    pub cpuinfo: Option<&'a str>,
+}
+
+/// DMI vendors and product names of virtual machines, and what they are, in the order they are
+/// matched. Names are matched as substrings of either.
+static DMI_VIRTS: &'static [(&'static str, &'static str)] = &[("VMware", "vmware"),
+                                                             ("KVM", "kvm"),
+                                                             ("QEMU", "kvm"),
+                                                             ("Xen", "xen"),
+                                                             ("HVM domU", "xen"),
+                                                             ("VirtualBox", "virtualbox"),
+                                                             ("innotek", "virtualbox"),
+                                                             ("Microsoft Corporation",
+                                                              "hyperv"),
+                                                             ("Parallels", "parallels"),
+                                                             ("Bochs", "bochs")];
+
+/// Returns the container or virtual machine the signs point to, in this order:
+///
+/// 1. A container: `/.dockerenv`, then the cgroups of init naming `docker` or `lxc`
+/// 1. A virtual machine the DMI vendor or product name is known for
+/// 1. `/proc/xen`, which only Xen guests have
+/// 1. The `hypervisor` cpu flag, which names no hypervisor and is reported as `hypervisor`
+///
+/// A container is reported rather than the virtual machine it runs in, as it is what limits
+/// the services in it.
+pub fn detect_virt(signs: &VirtSigns) -> Virt {
+    if signs.dockerenv {
+        return Virt::Docker;
+    }
+    if let Some(cgroup) = signs.init_cgroup {
+        for line in cgroup.lines() {
+            let path = match line.splitn(3, ':').nth(2) {
+                Some(path) => path,
+                None => continue,
+            };
+            if path.starts_with("/docker/") || path.contains("/docker-") {
+                return Virt::Docker;
+            }
+            if path.starts_with("/lxc/") || path.starts_with("/lxc.payload") {
+                return Virt::Lxc;
+            }
+        }
+    }
+    for &(name, virt) in DMI_VIRTS.iter() {
+        let named = |dmi: Option<&str>| dmi.map_or(false, |dmi| dmi.contains(name));
+        if named(signs.dmi_vendor) || named(signs.dmi_product) {
+            return match virt {
+                "vmware" => Virt::VMware,
+                "kvm" => Virt::Kvm,
+                "xen" => Virt::Xen,
+                other => Virt::Other(other.to_string()),
+            };
+        }
+    }
+    if signs.proc_xen {
+        return Virt::Xen;
+    }
+    let hypervisor = signs.cpuinfo.map_or(false, |cpuinfo| {
+        cpuinfo.lines()
+            .filter(|line| line.starts_with("flags"))
+            .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"))
+    });
+    if hypervisor {
+        return Virt::Other("hypervisor".to_string());
+    }
+    Virt::None
+}
+
+/// Returns the first usable address of the given family in the output of Windows' `ipconfig`,
+/// such as `   IPv4 Address. . . . . . . . . . . : 10.0.0.5(Preferred)`.
+pub fn parse_ipconfig(output: &str, family: IpPreference) -> Option<IpAddr> {
+    let label = match family {
+        IpPreference::Ipv4First => "IPv4 Address",
+        IpPreference::Ipv6First => "IPv6 Address",
+    };
+    for line in output.lines() {
+        if !line.contains(label) {
+            continue;
+        }
+        let addr = match line.find(": ") {
+            Some(pos) => line[pos + 2..].trim(),
+            None => continue,
+        };
+        // Strip the status and zone of addresses such as `10.0.0.5(Preferred)` or `fe80::1%4`
+        let addr = match addr.find(|c: char| c == '(' || c == '%') {
+            Some(pos) => &addr[..pos],
+            None => addr,
+        };
+        match addr.parse::<IpAddr>() {
+            Ok(ip) if is_usable(&ip) && super::is_preferred(&ip, family) => return Some(ip),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// EtherType of ARP
+pub const ETHERTYPE_ARP: u16 = 0x0806;
+
+/// Opcode of an ARP request
+const ARP_REQUEST: u16 = 1;
+
+/// Hardware type, protocol type and address lengths of ARP for IPv4 over Ethernet
+const ARP_ETHERNET_IPV4: [u8; 6] = [0, 1, 0x08, 0, 6, 4];
+
+/// Length of an Ethernet frame holding an ARP packet, without its padding
+const ARP_FRAME_LEN: usize = 42;
+
+/// Shortest Ethernet frame, without its checksum, which ARP frames are padded to
+const MIN_FRAME_LEN: usize = 60;
+
+/// Returns the Ethernet frame of an ARP probe for `ip` from the hardware address `mac`, as
+/// RFC 5227 describes: a broadcast request whose sender address is all zeros.
+pub fn arp_probe_frame(mac: &[u8; 6], ip: Ipv4Addr) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(MIN_FRAME_LEN);
+    frame.extend_from_slice(&[0xff; 6]);
+    frame.extend_from_slice(mac);
+    frame.extend_from_slice(&be_bytes(ETHERTYPE_ARP));
+    frame.extend_from_slice(&ARP_ETHERNET_IPV4);
+    frame.extend_from_slice(&be_bytes(ARP_REQUEST));
+    frame.extend_from_slice(mac);
+    frame.extend_from_slice(&[0; 4]);
+    frame.extend_from_slice(&[0; 6]);
+    frame.extend_from_slice(&ip.octets());
+    frame.resize(MIN_FRAME_LEN, 0);
+    frame
+}
+
+/// Returns the hardware address of the other host whose ARP packet in an Ethernet frame shows it
+/// uses `ip`, by giving it as its sender address or by probing for it too. Frames sent from
+/// `own_mac`, such as this host's own probe, and frames of anything but IPv4 over Ethernet ARP
+/// are `None`.
+pub fn parse_arp_conflict(frame: &[u8], ip: Ipv4Addr, own_mac: &[u8; 6]) -> Option<[u8; 6]> {
+    if frame.len() < ARP_FRAME_LEN || be_u16(&frame[12..14]) != ETHERTYPE_ARP ||
+       frame[14..20] != ARP_ETHERNET_IPV4[..] {
+        return None;
+    }
+    let mut sender_mac = [0; 6];
+    sender_mac.copy_from_slice(&frame[22..28]);
+    if sender_mac == *own_mac {
+        return None;
+    }
+    let ipv4 = |octets: &[u8]| Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]);
+    let sender_ip = ipv4(&frame[28..32]);
+    let probing = be_u16(&frame[20..22]) == ARP_REQUEST &&
+                  sender_ip == Ipv4Addr::new(0, 0, 0, 0) &&
+                  ipv4(&frame[38..42]) == ip;
+    if sender_ip == ip || probing {
+        Some(sender_mac)
+    } else {
+        None
+    }
+}
+
+fn be_bytes(value: u16) -> [u8; 2] {
+    [(value >> 8) as u8, value as u8]
+}
+
+fn be_u16(bytes: &[u8]) -> u16 {
+    (bytes[0] as u16) << 8 | bytes[1] as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    use super::*;
+    use super::super::{CpuTimes, DnsConfig, Gateway, IpPreference, LoadAvg, MemInfo, MountInfo,
+                       Neighbor, OsRelease, SelinuxMode, Virt};
+
+    #[test]
+    fn ip_route_output_is_parsed() {
+        let output = "8.8.8.8 via 10.0.0.1 dev eth0 src 10.0.0.5 uid 0\n    cache\n";
+        assert_eq!(parse_ip_route(output), Some("10.0.0.5".parse().unwrap()));
+        let output = "2001:4860:4860::8888 from :: via fe80::1 dev eth0 proto ra src \
+                      2001:db8::5 metric 100 pref medium";
+        assert_eq!(parse_ip_route(output), Some("2001:db8::5".parse().unwrap()));
+        assert_eq!(parse_ip_route("RTNETLINK answers: Network is unreachable"), None);
+    }
+
+    #[test]
+    fn ip_route_output_of_each_kernel_is_parsed() {
+        // Captured on CentOS 7 with kernel 3.10, Ubuntu 16.04 with 4.4 and an expiring route
+        // cached, Ubuntu 18.04 with 4.15 and `ip -o`, Debian 10 with 4.19 for an address on the
+        // link, Alpine's busybox with 5.4, and Debian 11 with 5.10 for IPv6 and `ip -o`
+        let outputs = [("8.8.8.8 via 10.0.2.2 dev eth0  src 10.0.2.15 \n    cache \n", "10.0.2.15"),
+                       ("8.8.8.8 via 10.0.0.1 dev eth0  src 10.0.0.5 \n    cache  expires \
+                         568sec mtu 1450\n",
+                        "10.0.0.5"),
+                       ("8.8.8.8 via 172.31.0.1 dev ens5 src 172.31.20.7 uid 1000 \\    \
+                         cache \n",
+                        "172.31.20.7"),
+                       ("10.0.0.7 dev eth0 src 10.0.0.5 uid 0 \n    cache \n", "10.0.0.5"),
+                       ("8.8.8.8 via 192.168.1.1 dev wlan0  src 192.168.1.23\n", "192.168.1.23"),
+                       ("2001:4860:4860::8888 from :: via fe80::1 dev eth0 proto ra src \
+                         2001:db8::5 metric 1024 pref medium\n",
+                        "2001:db8::5")];
+        for &(output, src) in outputs.iter() {
+            assert_eq!(parse_ip_route(output), Some(src.parse().unwrap()));
+        }
+        assert_eq!(parse_ip_route("10.0.0.7 dev eth0 \\    cache "), None);
+        assert_eq!(parse_ip_route("8.8.8.8 via 10.0.0.1 dev eth0 src"), None);
+    }
+
+    #[test]
+    fn ifconfig_output_is_parsed() {
+        let net_tools = "eth0      Link encap:Ethernet  HWaddr 02:42:ac:11:00:02\n          inet \
+                         addr:172.17.0.2  Bcast:0.0.0.0  Mask:255.255.0.0\n          inet6 \
+                         addr: fe80::42:acff:fe11:2/64 Scope:Link\n";
+        let bsd = "lo0: flags=8049<UP,LOOPBACK,RUNNING,MULTICAST> mtu 16384\n\tinet 127.0.0.1 \
+                   netmask 0xff000000\n\tinet6 fe80::1%lo0 prefixlen 64 scopeid 0x1\nen0: \
+                   flags=8863<UP> mtu 1500\n\tinet6 2001:db8::5 prefixlen 64\n\tinet \
+                   192.168.1.5 netmask 0xffffff00 broadcast 192.168.1.255\n";
+        assert_eq!(parse_ifconfig(net_tools, IpPreference::Ipv4First),
+                   Some("172.17.0.2".parse().unwrap()));
+        assert_eq!(parse_ifconfig(net_tools, IpPreference::Ipv6First), None);
+        assert_eq!(parse_ifconfig(bsd, IpPreference::Ipv4First),
+                   Some("192.168.1.5".parse().unwrap()));
+        assert_eq!(parse_ifconfig(bsd, IpPreference::Ipv6First),
+                   Some("2001:db8::5".parse().unwrap()));
+    }
+
+    #[test]
+    fn cpuinfo_cores_and_model_are_parsed() {
+        let topology = parse_cpuinfo(include_str!("../../../tests/fixtures/cpuinfo"));
+        assert_eq!(topology.cores, Some(2));
+        assert_eq!(topology.model,
+                   Some("Intel(R) Xeon(R) CPU E5-2686 v4 @ 2.30GHz".to_string()));
+    }
+
+    #[test]
+    fn cpuinfo_cores_of_every_socket_are_counted() {
+        let topology = parse_cpuinfo(include_str!("../../../tests/fixtures/cpuinfo-multi-socket"));
+        assert_eq!(topology.cores, Some(4));
+        assert_eq!(topology.model,
+                   Some("Intel(R) Xeon(R) CPU E5-2630 v3 @ 2.40GHz".to_string()));
+    }
+
+    #[test]
+    fn cpuinfo_without_core_ids_has_no_cores() {
+        let topology = parse_cpuinfo(include_str!("../../../tests/fixtures/cpuinfo-arm"));
+        assert_eq!(topology.cores, None);
+        assert_eq!(topology.model, Some("ARMv7 Processor rev 4 (v7l)".to_string()));
+    }
+
+    #[test]
+    fn cpu_lists_are_counted() {
+        assert_eq!(parse_cpu_list("0-3\n"), Some(4));
+        assert_eq!(parse_cpu_list("0-3,8,10-11\n"), Some(7));
+        assert_eq!(parse_cpu_list("5"), Some(1));
+        assert_eq!(parse_cpu_list("\n"), None);
+        assert_eq!(parse_cpu_list("3-1"), None);
+        assert_eq!(parse_cpu_list("0-4294967295"), None);
+        assert_eq!(parse_cpu_list("0-4294967294,0-4294967294"), None);
+    }
+
+    #[test]
+    fn meminfo_is_parsed() {
+        assert_eq!(parse_meminfo(include_str!("../../../tests/fixtures/meminfo")),
+                   Some(MemInfo {
+                       total_kb: 16323820,
+                       available_kb: Some(11268476),
+                       free_kb: Some(2154900),
+                       swap_total_kb: Some(2097148),
+                       swap_free_kb: Some(2064380),
+                   }));
+    }
+
+    #[test]
+    fn meminfo_without_available_is_estimated() {
+        let mem = parse_meminfo(include_str!("../../../tests/fixtures/meminfo-no-available"))
+            .unwrap();
+        assert_eq!(mem.total_kb, 3882048);
+        assert_eq!(mem.available_kb, Some(412304 + 2048 + 2583200));
+        assert_eq!(mem.free_kb, Some(412304));
+        assert_eq!(mem.swap_total_kb, Some(0));
+        let contents = "MemTotal: 1 kB\nMemFree: 18446744073709551615 kB\nCached: 1 kB\n";
+        assert_eq!(parse_meminfo(contents).unwrap().available_kb, Some(u64::max_value()));
+    }
+
+    #[test]
+    fn hugepage_size_is_parsed() {
+        assert_eq!(parse_hugepage_size(include_str!("../../../tests/fixtures/meminfo")),
+                   Some(2048));
+        assert_eq!(parse_hugepage_size("MemTotal: 2048 kB\n"), None);
+    }
+
+    #[test]
+    fn bracketed_choices_are_parsed() {
+        assert_eq!(parse_bracketed_choice("always [madvise] never\n"),
+                   Some("madvise".to_string()));
+        assert_eq!(parse_bracketed_choice("[always] madvise never"),
+                   Some("always".to_string()));
+        assert_eq!(parse_bracketed_choice("always madvise [never]\n"),
+                   Some("never".to_string()));
+        assert_eq!(parse_bracketed_choice("always madvise never"), None);
+        assert_eq!(parse_bracketed_choice("[]"), None);
+        assert_eq!(parse_bracketed_choice(""), None);
+    }
+
+    #[test]
+    fn meminfo_without_total_is_not_parsed() {
+        assert_eq!(parse_meminfo("MemFree: 412304 kB\n"), None);
+    }
+
+    #[test]
+    fn cgroup_limits_are_parsed() {
+        assert_eq!(parse_cgroup_max("max\n"), Some(None));
+        assert_eq!(parse_cgroup_max("512\n"), Some(Some(512)));
+        assert_eq!(parse_cgroup_max("lots\n"), None);
+        assert_eq!(parse_cpu_max("150000 100000\n"), Some(Some(1.5)));
+        assert_eq!(parse_cpu_max("max 100000\n"), Some(None));
+        assert_eq!(parse_cpu_max("150000\n"), None);
+        assert_eq!(parse_cfs_quota("50000\n", "100000\n"), Some(Some(0.5)));
+        assert_eq!(parse_cfs_quota("-1\n", "100000\n"), Some(None));
+        assert_eq!(parse_cfs_quota("50000\n", "0\n"), None);
+    }
+
+    #[test]
+    fn memory_limits_are_parsed() {
+        assert_eq!(parse_memory_limit("536870912\n"), Some(536870912));
+        assert_eq!(parse_memory_limit("max\n"), None);
+    }
+
+    #[test]
+    fn mounts_find_the_deepest_filesystem() {
+        let mounts = include_str!("../../../tests/fixtures/proc-self-mounts");
+        assert_eq!(parse_mounts(mounts, Path::new("/hab/svc/redis")),
+                   Some("xfs".to_string()));
+        assert_eq!(parse_mounts(mounts, Path::new("/habitat")), Some("ext4".to_string()));
+        assert_eq!(parse_mounts(mounts, Path::new("/tmp")), Some("tmpfs".to_string()));
+        assert_eq!(parse_mounts(mounts, Path::new("/mnt/backup disk/db")),
+                   Some("nfs4".to_string()));
+        assert_eq!(parse_mounts("", Path::new("/")), None);
+    }
+
+    #[test]
+    fn mountinfo_finds_bind_mounts() {
+        let mountinfo = include_str!("../../../tests/fixtures/proc-self-mountinfo-bind");
+        let svc = parse_mountinfo(mountinfo, Path::new("/hab/svc/redis")).unwrap();
+        assert_eq!(svc,
+                   MountInfo {
+                       mount_point: PathBuf::from("/hab/svc"),
+                       root: "/export/svc".to_string(),
+                       source: "/dev/sdc1".to_string(),
+                       fs_type: "xfs".to_string(),
+                       read_only: true,
+                       noexec: false,
+                       nosuid: true,
+                   });
+        let cache = parse_mountinfo(mountinfo, Path::new("/hab/cache/keys")).unwrap();
+        assert_eq!(cache.root, "/pkgs-cache");
+        assert!(!cache.read_only);
+        let pkgs = parse_mountinfo(mountinfo, Path::new("/hab/pkgs")).unwrap();
+        assert_eq!(pkgs.mount_point, PathBuf::from("/hab"));
+        assert_eq!(pkgs.root, "/");
+        assert!(parse_mountinfo(mountinfo, Path::new("/tmp")).unwrap().noexec);
+        assert_eq!(parse_mountinfo(mountinfo, Path::new("/habitat")).unwrap().mount_point,
+                   PathBuf::from("/"));
+    }
+
+    #[test]
+    fn mountinfo_unescapes_paths() {
+        let mountinfo = include_str!("../../../tests/fixtures/proc-self-mountinfo-bind");
+        let backup = parse_mountinfo(mountinfo, Path::new("/mnt/backup disk/db")).unwrap();
+        assert_eq!(backup.mount_point, PathBuf::from("/mnt/backup disk"));
+        assert_eq!(backup.source, "backup:/export");
+        let mnt = parse_mountinfo(mountinfo, Path::new("/mnt/backup")).unwrap();
+        assert_eq!(mnt.mount_point, PathBuf::from("/"));
+    }
+
+    #[test]
+    fn mountinfo_honours_read_only_filesystems() {
+        let mountinfo = include_str!("../../../tests/fixtures/proc-self-mountinfo-bind");
+        let legacy = parse_mountinfo(mountinfo, Path::new("/srv/legacy")).unwrap();
+        assert!(legacy.read_only);
+        assert!(!legacy.nosuid);
+    }
+
+    #[test]
+    fn mountinfo_finds_overlay_roots() {
+        let mountinfo = include_str!("../../../tests/fixtures/proc-self-mountinfo-overlay");
+        let root = parse_mountinfo(mountinfo, Path::new("/hab/svc/redis")).unwrap();
+        assert_eq!(root.fs_type, "overlay");
+        assert_eq!(root.source, "overlay");
+        assert!(!root.read_only && !root.noexec);
+        let pkgs = parse_mountinfo(mountinfo, Path::new("/hab/pkgs/core")).unwrap();
+        assert_eq!(pkgs.root, "/srv/hab/pkgs");
+        assert!(pkgs.read_only);
+        assert!(parse_mountinfo(mountinfo, Path::new("/tmp/hab")).unwrap().noexec);
+        assert_eq!(parse_mountinfo("", Path::new("/")), None);
+        assert_eq!(parse_mountinfo("612 490 0:58 / / rw\n", Path::new("/")), None);
+    }
+
+    #[test]
+    fn mount_table_lists_real_filesystems_once() {
+        let mount_points = |contents: &str, include_tmpfs: bool| -> Vec<PathBuf> {
+            parse_mount_table(contents, include_tmpfs)
+                .into_iter()
+                .map(|mount| mount.mount_point)
+                .collect()
+        };
+        let bind = include_str!("../../../tests/fixtures/proc-self-mountinfo-bind");
+        assert_eq!(mount_points(bind, false),
+                   vec![PathBuf::from("/"),
+                        PathBuf::from("/hab"),
+                        PathBuf::from("/hab/svc"),
+                        PathBuf::from("/mnt/backup disk"),
+                        PathBuf::from("/srv/legacy")]);
+        assert_eq!(mount_points(bind, true)[1], PathBuf::from("/tmp"));
+        // The bind mounts of /dev/sda1 into the container count once, as the one showing more
+        let overlay = include_str!("../../../tests/fixtures/proc-self-mountinfo-overlay");
+        let mounts = parse_mount_table(overlay, false);
+        assert_eq!(mounts.len(), 2);
+        assert_eq!(mounts[0].fs_type, "overlay");
+        assert_eq!(mounts[1].mount_point, PathBuf::from("/hab/pkgs"));
+        assert_eq!(mounts[1].root, "/srv/hab/pkgs");
+        assert!(parse_mount_table("", true).is_empty());
+    }
+
+    #[test]
+    fn ubuntu_os_release_is_parsed() {
+        assert_eq!(parse_os_release(include_str!("../../../tests/fixtures/os-release-ubuntu")),
+                   Some(OsRelease {
+                       id: "ubuntu".to_string(),
+                       version_id: Some("16.04".to_string()),
+                       pretty_name: Some("Ubuntu 16.04.1 LTS".to_string()),
+                   }));
+    }
+
+    #[test]
+    fn centos_os_release_is_parsed() {
+        assert_eq!(parse_os_release(include_str!("../../../tests/fixtures/os-release-centos")),
+                   Some(OsRelease {
+                       id: "centos".to_string(),
+                       version_id: Some("7".to_string()),
+                       pretty_name: Some("CentOS Linux 7 (Core)".to_string()),
+                   }));
+    }
+
+    #[test]
+    fn alpine_os_release_is_parsed() {
+        assert_eq!(parse_os_release(include_str!("../../../tests/fixtures/os-release-alpine")),
+                   Some(OsRelease {
+                       id: "alpine".to_string(),
+                       version_id: Some("3.4.4".to_string()),
+                       pretty_name: Some("Alpine Linux v3.4".to_string()),
+                   }));
+    }
+
+    #[test]
+    fn quoted_os_release_values_are_unquoted() {
+        let release = parse_os_release(include_str!("../../../tests/fixtures/os-release-quoted"))
+            .unwrap();
+        assert_eq!(release.id, "acme");
+        assert_eq!(release.version_id, Some("2016.09".to_string()));
+        assert_eq!(release.pretty_name,
+                   Some("Acme \"Roadrunner\" Linux $2016.09".to_string()));
+    }
+
+    #[test]
+    fn os_release_without_id_is_not_parsed() {
+        assert_eq!(parse_os_release("NAME=\"Ubuntu\"\n"), None);
+    }
+
+    #[test]
+    fn lsb_release_is_parsed() {
+        assert_eq!(parse_lsb_release(include_str!("../../../tests/fixtures/lsb-release")),
+                   Some(OsRelease {
+                       id: "ubuntu".to_string(),
+                       version_id: Some("12.04".to_string()),
+                       pretty_name: Some("Ubuntu 12.04.5 LTS".to_string()),
+                   }));
+    }
+
+    #[test]
+    fn redhat_release_is_parsed() {
+        assert_eq!(parse_redhat_release(include_str!("../../../tests/fixtures/redhat-release")),
+                   Some(OsRelease {
+                       id: "centos".to_string(),
+                       version_id: Some("6.8".to_string()),
+                       pretty_name: Some("CentOS release 6.8 (Final)".to_string()),
+                   }));
+        let rhel = parse_redhat_release("Red Hat Enterprise Linux Server release 6.9 (Santiago)\n")
+            .unwrap();
+        assert_eq!(rhel.id, "rhel");
+        assert_eq!(rhel.version_id, Some("6.9".to_string()));
+    }
+
+    #[test]
+    fn proc_uptime_is_parsed() {
+        assert_eq!(parse_proc_uptime("350735.47 234388.90\n"),
+                   Some(Duration::new(350735, 470000000)));
+        assert_eq!(parse_proc_uptime("12 30\n"), Some(Duration::from_secs(12)));
+        assert_eq!(parse_proc_uptime("0.05 0.10\n"), Some(Duration::from_millis(50)));
+        assert_eq!(parse_proc_uptime("soon\n"), None);
+        assert_eq!(parse_proc_uptime(""), None);
+    }
+
+    #[test]
+    fn kern_boottime_is_parsed() {
+        assert_eq!(parse_kern_boottime("{ sec = 1476350400, usec = 0 } Thu Oct 13 09:20:00 2016\n"),
+                   Some(1476350400));
+        assert_eq!(parse_kern_boottime("kern.boottime: unknown oid\n"), None);
+    }
+
+    #[test]
+    fn localtime_links_are_parsed() {
+        let links = [("/usr/share/zoneinfo/Europe/Berlin", "Europe/Berlin"),
+                     ("../usr/share/zoneinfo/America/New_York", "America/New_York"),
+                     ("/usr/share/zoneinfo/UTC", "UTC"),
+                     ("/usr/share/zoneinfo/posix/Asia/Tokyo", "Asia/Tokyo"),
+                     ("/usr/share/zoneinfo/right/Etc/GMT+5", "Etc/GMT+5"),
+                     ("/var/db/timezone/zoneinfo/America/Los_Angeles", "America/Los_Angeles")];
+        for &(target, name) in links.iter() {
+            assert_eq!(parse_localtime_link(target), Some(name.to_string()));
+        }
+        assert_eq!(parse_localtime_link("/usr/share/zoneinfo/"), None);
+        assert_eq!(parse_localtime_link("/usr/share/zoneinfo/posix/"), None);
+        assert_eq!(parse_localtime_link("/etc/localtime.orig"), None);
+    }
+
+    #[test]
+    fn etc_timezone_is_parsed() {
+        assert_eq!(parse_etc_timezone("Europe/Berlin\n"),
+                   Some("Europe/Berlin".to_string()));
+        assert_eq!(parse_etc_timezone("# set by tzdata\n\n  Etc/UTC  \n"),
+                   Some("Etc/UTC".to_string()));
+        assert_eq!(parse_etc_timezone("\n"), None);
+    }
+
+    #[test]
+    fn host_ids_are_parsed() {
+        assert_eq!(parse_host_id("4B3F0C2E8A514D3C9D2B6F1E7A0C5D94\n"),
+                   Some("4b3f0c2e8a514d3c9d2b6f1e7a0c5d94".to_string()));
+        assert_eq!(parse_host_id("4b3f0c2e-8a51-4d3c-9d2b-6f1e7a0c5d94\n"),
+                   Some("4b3f0c2e-8a51-4d3c-9d2b-6f1e7a0c5d94".to_string()));
+        let invalid = ["",
+                       "uninitialized",
+                       "4b3f0c2e8a514d3c9d2b6f1e7a0c5d9",
+                       "4b3f0c2e8a514d3c9d2b6f1e7a0c5dzz",
+                       "4b3f0c2e8-a51-4d3c-9d2b-6f1e7a0c5d94",
+                       "4b3f0c2e-8a51-4d3c-9d2b-6f1e7a0c5d9-"];
+        for id in invalid.iter() {
+            assert_eq!(parse_host_id(id), None);
+        }
+    }
+
+    #[test]
+    fn proc_loadavg_is_parsed() {
+        assert_eq!(parse_proc_loadavg(include_str!("../../../tests/fixtures/proc-loadavg")),
+                   Some(LoadAvg {
+                       one: 0.42,
+                       five: 1.07,
+                       fifteen: 12.5,
+                       running: Some(2),
+                       total: Some(345),
+                   }));
+        let old = parse_proc_loadavg("0.00 0.01 0.05\n").unwrap();
+        assert_eq!(old.fifteen, 0.05);
+        assert_eq!(old.running, None);
+        assert_eq!(parse_proc_loadavg("0.00 busy 0.05 1/80 4242\n"), None);
+    }
+
+    #[test]
+    fn bare_metal_is_not_virtualized() {
+        let signs = VirtSigns {
+            init_cgroup: Some(include_str!("../../../tests/fixtures/proc-1-cgroup-host")),
+            dmi_vendor: Some("Dell Inc.\n"),
+            dmi_product: Some("PowerEdge R630\n"),
+            cpuinfo: Some(include_str!("../../../tests/fixtures/cpuinfo")),
+            ..VirtSigns::default()
+        };
+        assert_eq!(detect_virt(&signs), Virt::None);
+        assert_eq!(detect_virt(&VirtSigns::default()), Virt::None);
+    }
+
+    #[test]
+    fn docker_is_detected() {
+        let signs = VirtSigns {
+            init_cgroup: Some(include_str!("../../../tests/fixtures/proc-1-cgroup-docker")),
+            ..VirtSigns::default()
+        };
+        assert_eq!(detect_virt(&signs), Virt::Docker);
+        let signs = VirtSigns {
+            dockerenv: true,
+            ..VirtSigns::default()
+        };
+        assert_eq!(detect_virt(&signs), Virt::Docker);
+    }
+
+    #[test]
+    fn lxc_is_detected() {
+        let signs = VirtSigns {
+            init_cgroup: Some(include_str!("../../../tests/fixtures/proc-1-cgroup-lxc")),
+            ..VirtSigns::default()
+        };
+        assert_eq!(detect_virt(&signs), Virt::Lxc);
+    }
+
+    #[test]
+    fn containers_win_over_virtual_machines() {
+        let signs = VirtSigns {
+            init_cgroup: Some(include_str!("../../../tests/fixtures/proc-1-cgroup-docker")),
+            dmi_vendor: Some("QEMU\n"),
+            dmi_product: Some("KVM\n"),
+            proc_xen: true,
+            cpuinfo: Some(include_str!("../../../tests/fixtures/cpuinfo-hypervisor")),
+            ..VirtSigns::default()
+        };
+        assert_eq!(detect_virt(&signs), Virt::Docker);
+    }
+
+    #[test]
+    fn virtual_machines_are_detected_by_dmi() {
+        let dmi = [("QEMU", "Standard PC (i440FX + PIIX, 1996)", Virt::Kvm),
+                   ("VMware, Inc.", "VMware Virtual Platform", Virt::VMware),
+                   ("Xen", "HVM domU", Virt::Xen),
+                   ("innotek GmbH", "VirtualBox", Virt::Other("virtualbox".to_string())),
+                   ("Microsoft Corporation", "Virtual Machine", Virt::Other("hyperv".to_string()))];
+        for &(vendor, product, ref virt) in dmi.iter() {
+            let signs = VirtSigns {
+                dmi_vendor: Some(vendor),
+                dmi_product: Some(product),
+                cpuinfo: Some(include_str!("../../../tests/fixtures/cpuinfo-hypervisor")),
+                ..VirtSigns::default()
+            };
+            assert_eq!(detect_virt(&signs), *virt);
+        }
+    }
+
+    #[test]
+    fn unknown_hypervisors_are_detected_by_cpu_flag() {
+        let signs = VirtSigns {
+            dmi_vendor: Some("Amazon EC2\n"),
+            cpuinfo: Some(include_str!("../../../tests/fixtures/cpuinfo-hypervisor")),
+            ..VirtSigns::default()
+        };
+        assert_eq!(detect_virt(&signs), Virt::Other("hypervisor".to_string()));
+        let signs = VirtSigns {
+            proc_xen: true,
+            cpuinfo: Some(include_str!("../../../tests/fixtures/cpuinfo-hypervisor")),
+            ..VirtSigns::default()
+        };
+        assert_eq!(detect_virt(&signs), Virt::Xen);
+    }
+
+    #[test]
+    fn selinux_modes_are_parsed() {
+        assert_eq!(parse_selinux_enforce("1\n"), Some(SelinuxMode::Enforcing));
+        assert_eq!(parse_selinux_enforce("0"), Some(SelinuxMode::Permissive));
+        assert_eq!(parse_selinux_enforce(""), None);
+        assert_eq!(parse_getenforce("Enforcing\n"), Some(SelinuxMode::Enforcing));
+        assert_eq!(parse_getenforce("Disabled\n"), Some(SelinuxMode::Disabled));
+        assert_eq!(parse_getenforce("getenforce: command not found"), None);
+    }
+
+    #[test]
+    fn apparmor_profiles_are_counted() {
+        let contents = include_str!("../../../tests/fixtures/apparmor-profiles");
+        assert_eq!(parse_apparmor_profiles(contents), 7);
+        assert_eq!(parse_apparmor_profiles("\n"), 0);
+    }
+
+    #[test]
+    fn parent_pids_are_parsed() {
+        assert_eq!(parse_proc_pid_ppid("4242 (sleep) S 4100 4242 4100 0 -1 4194304 90 0 0 0\n"),
+                   Some(4100));
+        assert_eq!(parse_proc_pid_ppid("77 (a (b) c) R 1 77 77 0 -1\n"), Some(1));
+        assert_eq!(parse_proc_pid_ppid("77 (sleep"), None);
+        assert_eq!(parse_proc_pid_ppid("77 (sleep) S\n"), None);
+    }
+
+    #[test]
+    fn proc_stat_is_parsed() {
+        let stat = parse_proc_stat(include_str!("../../../tests/fixtures/proc-stat")).unwrap();
+        assert_eq!(stat.total,
+                   CpuTimes {
+                       user: 84935,
+                       nice: 1270,
+                       system: 23654,
+                       idle: 3510348,
+                       iowait: 4301,
+                       irq: 0,
+                       softirq: 1322,
+                       steal: 812,
+                   });
+        // cpu2 is offline, so has no line
+        let cpus: Vec<u32> = stat.cores.iter().map(|&(cpu, _)| cpu).collect();
+        assert_eq!(cpus, vec![0, 1, 3]);
+        assert_eq!(stat.cores[2].1.user, 42179);
+    }
+
+    #[test]
+    fn proc_stat_of_older_kernels_is_parsed() {
+        let contents = include_str!("../../../tests/fixtures/proc-stat-2.6.9");
+        let stat = parse_proc_stat(contents).unwrap();
+        assert_eq!(stat.total.softirq, 402);
+        assert_eq!(stat.total.steal, 0);
+        assert_eq!(stat.cores.len(), 2);
+        let stat = parse_proc_stat("cpu  100 0 50 1000\ncpu0 100 0 50 1000\n").unwrap();
+        assert_eq!(stat.total.idle, 1000);
+        assert_eq!(stat.total.iowait, 0);
+        assert_eq!(parse_proc_stat("cpu  100 0 50\n"), None);
+        assert_eq!(parse_proc_stat("cpu  100 0 50 lots\n"), None);
+        assert_eq!(parse_proc_stat("intr 2063375 17\n"), None);
+    }
+
+    #[test]
+    fn sysfs_mac_is_parsed() {
+        assert_eq!(parse_mac("52:54:00:AB:cd:0e\n"),
+                   Some("52:54:00:ab:cd:0e".to_string()));
+        assert_eq!(parse_mac("00:00:00:00:00:00\n"), None);
+        assert_eq!(parse_mac("\n"), None);
+        assert_eq!(parse_mac("not a mac"), None);
+    }
+
+    #[test]
+    fn namespace_links_are_parsed() {
+        assert_eq!(parse_ns_link("net:[4026531992]"),
+                   Some("4026531992".to_string()));
+        assert_eq!(parse_ns_link("net:[]"), None);
+        assert_eq!(parse_ns_link("net:[40265x]"), None);
+        assert_eq!(parse_ns_link("/proc/self/ns/net"), None);
+    }
+
+    #[test]
+    fn proc_net_route_default_is_parsed() {
+        assert_eq!(parse_proc_net_route(include_str!("../../../tests/fixtures/proc-net-route")),
+                   Some(Gateway {
+                       ip: "192.0.2.1".parse().unwrap(),
+                       interface: "eth0".to_string(),
+                   }));
+    }
+
+    #[test]
+    fn proc_net_route_lowest_metric_wins() {
+        let contents = include_str!("../../../tests/fixtures/proc-net-route-multi");
+        assert_eq!(parse_proc_net_route(contents),
+                   Some(Gateway {
+                       ip: "10.0.0.1".parse().unwrap(),
+                       interface: "eth0".to_string(),
+                   }));
+    }
+
+    #[test]
+    fn proc_net_route_without_default_is_none() {
+        let contents = include_str!("../../../tests/fixtures/proc-net-route-isolated");
+        assert_eq!(parse_proc_net_route(contents), None);
+        assert_eq!(parse_proc_net_route(""), None);
+    }
+
+    fn neighbor(ip: &str, mac: &str, interface: &str, state: &str) -> Neighbor {
+        Neighbor {
+            ip: ip.parse().unwrap(),
+            mac: mac.to_string(),
+            interface: interface.to_string(),
+            state: state.to_string(),
+        }
+    }
+
+    #[test]
+    fn proc_net_arp_leaves_out_incomplete_entries() {
+        let contents = include_str!("../../../tests/fixtures/proc-net-arp");
+        assert_eq!(parse_proc_net_arp(contents),
+                   vec![neighbor("10.0.0.1", "52:54:00:12:34:01", "eth0", "complete"),
+                        neighbor("10.0.0.9", "52:54:00:12:34:09", "eth0", "permanent"),
+                        neighbor("172.17.0.2", "02:42:ac:11:00:02", "docker0", "complete")]);
+        assert!(parse_proc_net_arp("").is_empty());
+    }
+
+    #[test]
+    fn ip_neigh_leaves_out_incomplete_and_failed_entries() {
+        let output = include_str!("../../../tests/fixtures/ip-neigh");
+        assert_eq!(parse_ip_neigh(output),
+                   vec![neighbor("10.0.0.1", "52:54:00:12:34:01", "eth0", "reachable"),
+                        neighbor("10.0.0.9", "52:54:00:12:34:09", "eth0", "permanent"),
+                        neighbor("fe80::1", "52:54:00:12:34:01", "eth0", "stale"),
+                        neighbor("2001:db8::7", "52:54:00:12:34:07", "eth0", "delay")]);
+        // A failed entry keeps the hardware address it last had
+        assert!(parse_ip_neigh("10.0.0.8 dev eth0 lladdr 52:54:00:12:34:08 FAILED").is_empty());
+    }
+
+    #[test]
+    fn proc_net_ipv6_route_default_is_parsed() {
+        let contents = include_str!("../../../tests/fixtures/proc-net-ipv6_route");
+        assert_eq!(parse_proc_net_ipv6_route(contents),
+                   Some(Gateway {
+                       ip: "fd00::1".parse().unwrap(),
+                       interface: "eth0".to_string(),
+                   }));
+        let contents = include_str!("../../../tests/fixtures/proc-net-ipv6_route-isolated");
+        assert_eq!(parse_proc_net_ipv6_route(contents), None);
+    }
+
+    #[test]
+    fn resolv_conf_is_parsed() {
+        assert_eq!(parse_resolv_conf(include_str!("../../../tests/fixtures/resolv.conf")),
+                   DnsConfig {
+                       nameservers: vec!["10.0.0.2".parse().unwrap(),
+                                         "10.0.0.3".parse().unwrap(),
+                                         "2001:db8::53".parse().unwrap()],
+                       search: vec!["corp.example.com".to_string(), "example.com".to_string()],
+                       options: vec!["timeout:2".to_string(),
+                                     "attempts:3".to_string(),
+                                     "rotate".to_string()],
+                   });
+    }
+
+    #[test]
+    fn resolv_conf_last_search_line_wins() {
+        let config = parse_resolv_conf(include_str!("../../../tests/fixtures/resolv.conf-domain"));
+        assert_eq!(config.search, vec!["corp.example.com".to_string()]);
+        assert_eq!(config.nameservers, vec!["10.0.0.2".parse::<IpAddr>().unwrap()]);
+    }
+
+    #[test]
+    fn resolved_stub_resolv_conf_is_parsed() {
+        let config = parse_resolv_conf(include_str!("../../../tests/fixtures/resolv.conf-stub"));
+        assert_eq!(config.nameservers, vec!["127.0.0.53".parse::<IpAddr>().unwrap()]);
+        assert_eq!(config.search, vec!["example.com".to_string()]);
+        assert_eq!(config.options,
+                   vec!["edns0".to_string(), "trust-ad".to_string()]);
+        assert_eq!(parse_resolv_conf(""), DnsConfig::default());
+    }
+
+    #[test]
+    fn etc_hosts_is_parsed() {
+        let entries = parse_etc_hosts(include_str!("../../../tests/fixtures/hosts"));
+        assert_eq!(entries.len(), 8);
+        assert_eq!(entries[0],
+                   ("127.0.0.1".parse().unwrap(),
+                    vec!["localhost".to_string(), "localhost.localdomain".to_string()]));
+        assert_eq!(entries[2].1,
+                   vec!["localhost".to_string(),
+                        "ip6-localhost".to_string(),
+                        "ip6-loopback".to_string()]);
+        assert_eq!(entries[6],
+                   ("fe80::5054:ff:fe12:3456".parse().unwrap(), vec!["db-1-link".to_string()]));
+        // Malformed lines are skipped without affecting the rest
+        assert_eq!(entries[7].0, "10.0.0.7".parse::<IpAddr>().unwrap());
+        assert!(parse_etc_hosts("# only comments\n\n").is_empty());
+    }
+
+    #[test]
+    fn host_aliases_are_the_names_of_local_addresses() {
+        let addrs: Vec<IpAddr> = vec!["127.0.0.1".parse().unwrap(),
+                                      "::1".parse().unwrap(),
+                                      "10.0.0.5".parse().unwrap(),
+                                      "2001:db8::5".parse().unwrap(),
+                                      "fe80::5054:ff:fe12:3456".parse().unwrap()];
+        assert_eq!(parse_host_aliases(include_str!("../../../tests/fixtures/hosts"), &addrs),
+                   vec!["db-1.example.com".to_string(),
+                        "db-1".to_string(),
+                        "db-primary".to_string(),
+                        "db-primary.example.com".to_string(),
+                        "db-1.v6.example.com".to_string(),
+                        "db-1-link".to_string()]);
+        assert!(parse_host_aliases(include_str!("../../../tests/fixtures/hosts"), &[]).is_empty());
+    }
+
+    #[test]
+    fn resolv_conf_domain_is_parsed() {
+        let contents = "# Generated by NetworkManager\nsearch corp.example.com example.com\n\
+                        nameserver 10.0.0.2\n";
+        assert_eq!(parse_resolv_conf_domain(contents),
+                   Some("corp.example.com".to_string()));
+        let contents = "search example.com\ndomain corp.example.com.\nnameserver 10.0.0.2\n";
+        assert_eq!(parse_resolv_conf_domain(contents),
+                   Some("corp.example.com".to_string()));
+        assert_eq!(parse_resolv_conf_domain("; search example.com\nnameserver 10.0.0.2\n"),
+                   None);
+        assert_eq!(parse_resolv_conf_domain("domain\n"), None);
+    }
+
+    #[test]
+    fn macos_route_get_output_is_parsed() {
+        let output = "   route to: default\ndestination: default\n       mask: default\n    \
+                      gateway: 192.168.1.1\n  interface: en0\n      flags: \
+                      <UP,GATEWAY,DONE,STATIC,PRCLONING>\n recvpipe  sendpipe  ssthresh  \
+                      rtt,msec    rttvar  hopcount      mtu     expire\n       0         0 \
+                             0         0         0         0      1500         0\n";
+        assert_eq!(parse_route_get(output),
+                   RouteGet {
+                       gateway: Some("192.168.1.1".parse().unwrap()),
+                       interface: Some("en0".to_string()),
+                       if_address: None,
+                   });
+    }
+
+    #[test]
+    fn freebsd_route_get_output_is_parsed() {
+        let output = "   route to: default\ndestination: default\n       mask: default\n    \
+                      gateway: 10.0.2.2\n        fib: 0\n  interface: em0\n      flags: \
+                      <UP,GATEWAY,DONE,STATIC>\n";
+        assert_eq!(parse_route_get(output).interface, Some("em0".to_string()));
+        let output = "   route to: ::\ndestination: ::\n       mask: default\n    gateway: \
+                      fe80::1%em0\n  interface: em0\n";
+        assert_eq!(parse_route_get(output).interface, Some("em0".to_string()));
+        assert_eq!(parse_route_get(output).gateway,
+                   Some("fe80::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn openbsd_route_get_output_is_parsed() {
+        let output = "   route to: 0.0.0.0\ndestination: default\n       mask: default\n    \
+                      gateway: 10.0.2.2\n  interface: vio0\n if address: 10.0.2.15\n   \
+                      priority: 8 (static)\n";
+        assert_eq!(parse_route_get(output),
+                   RouteGet {
+                       gateway: Some("10.0.2.2".parse().unwrap()),
+                       interface: Some("vio0".to_string()),
+                       if_address: Some("10.0.2.15".parse().unwrap()),
+                   });
+        assert_eq!(parse_route_get("route: writing to routing socket: not in table"),
+                   RouteGet::default());
+    }
+
+    #[test]
+    fn ipconfig_output_is_parsed() {
+        let output = "Windows IP Configuration\r\n\r\nEthernet adapter Ethernet:\r\n\r\n   \
+                      Connection-specific DNS Suffix  . : corp.example.com\r\n   Link-local \
+                      IPv6 Address . . . . . : fe80::1c4a:5dff:fe3a:7b21%4(Preferred)\r\n   \
+                      IPv6 Address. . . . . . . . . . . : 2001:db8::5(Preferred)\r\n   IPv4 \
+                      Address. . . . . . . . . . . : 10.0.0.5(Preferred)\r\n   Subnet Mask . . \
+                      . . . . . . . . . : 255.255.255.0\r\n";
+        assert_eq!(parse_ipconfig(output, IpPreference::Ipv4First),
+                   Some("10.0.0.5".parse().unwrap()));
+        assert_eq!(parse_ipconfig(output, IpPreference::Ipv6First),
+                   Some("2001:db8::5".parse().unwrap()));
+        assert_eq!(parse_ipconfig("Windows IP Configuration\r\n", IpPreference::Ipv4First),
+                   None);
+    }
+
+    const OWN_MAC: [u8; 6] = [0x52, 0x54, 0x00, 0x12, 0x34, 0x56];
+    const OTHER_MAC: [u8; 6] = [0x52, 0x54, 0x00, 0xab, 0xcd, 0xef];
+
+    /// Returns the frame of an ARP packet of the given opcode from `OTHER_MAC`.
+    fn arp_frame(op: u8, sender_ip: [u8; 4], target_ip: [u8; 4]) -> Vec<u8> {
+        let mut frame = arp_probe_frame(&OTHER_MAC, Ipv4Addr::from(target_ip));
+        frame[21] = op;
+        frame[28..32].copy_from_slice(&sender_ip);
+        frame
+    }
+
+    #[test]
+    fn arp_probes_have_no_sender_address() {
+        let frame = arp_probe_frame(&OWN_MAC, "10.0.0.5".parse().unwrap());
+        assert_eq!(frame.len(), 60);
+        assert_eq!(&frame[0..6], &[0xff; 6]);
+        assert_eq!(&frame[6..12], &OWN_MAC);
+        assert_eq!(&frame[12..22], &[0x08, 0x06, 0, 1, 0x08, 0, 6, 4, 0, 1]);
+        assert_eq!(&frame[22..28], &OWN_MAC);
+        assert_eq!(&frame[28..32], &[0; 4]);
+        assert_eq!(&frame[38..42], &[10, 0, 0, 5]);
+    }
+
+    #[test]
+    fn arp_conflicts_are_found() {
+        let ip = "10.0.0.5".parse().unwrap();
+        let reply = arp_frame(2, [10, 0, 0, 5], [10, 0, 0, 9]);
+        assert_eq!(parse_arp_conflict(&reply, ip, &OWN_MAC), Some(OTHER_MAC));
+        let probe = arp_frame(1, [0; 4], [10, 0, 0, 5]);
+        assert_eq!(parse_arp_conflict(&probe, ip, &OWN_MAC), Some(OTHER_MAC));
+    }
+
+    #[test]
+    fn unrelated_arp_packets_are_no_conflict() {
+        let ip = "10.0.0.5".parse().unwrap();
+        let own_probe = arp_probe_frame(&OWN_MAC, ip);
+        assert_eq!(parse_arp_conflict(&own_probe, ip, &OWN_MAC), None);
+        let other_host = arp_frame(2, [10, 0, 0, 7], [10, 0, 0, 9]);
+        assert_eq!(parse_arp_conflict(&other_host, ip, &OWN_MAC), None);
+        let asking_for_us = arp_frame(1, [10, 0, 0, 7], [10, 0, 0, 5]);
+        assert_eq!(parse_arp_conflict(&asking_for_us, ip, &OWN_MAC), None);
+        let reply = arp_frame(2, [10, 0, 0, 5], [10, 0, 0, 9]);
+        assert_eq!(parse_arp_conflict(&reply[..41], ip, &OWN_MAC), None);
+        let mut ipv6 = reply.clone();
+        ipv6[12..14].copy_from_slice(&[0x86, 0xdd]);
+        assert_eq!(parse_arp_conflict(&ipv6, ip, &OWN_MAC), None);
+    }
+
+    #[test]
+    fn mac_octets_are_parsed() {
+        assert_eq!(parse_mac_octets("52:54:00:12:34:56\n"), Some(OWN_MAC));
+        assert_eq!(parse_mac_octets("52:54:00:12:34"), None);
+        assert_eq!(parse_mac_octets("52:54:00:12:34:5g"), None);
+        assert_eq!(parse_mac_octets("52:54:00:12:34:567"), None);
+    }
+}
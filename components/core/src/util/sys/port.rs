@@ -0,0 +1,291 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Whether TCP ports of the host are free, by binding them.
+//!
+//! A port found free can be taken by another process before it is used, so checking a port and
+//! then binding it is a race. Callers which can hold on to the bound listener until they hand it
+//! over, or until their service takes the port, should use `reserve_port` instead.
+//!
+//! Listeners are bound with the defaults of the standard library: on Unix these set
+//! `SO_REUSEADDR`, so a port which only has connections left in `TIME_WAIT` is free, as it is to
+//! any service which binds it the same way. On Windows `SO_REUSEADDR` would let the port be
+//! shared with a listening socket, so it isn't set and such a port is taken.
+//!
+//! The address a service binds follows a `BindPolicy` and is checked against the addresses of
+//! the interfaces of the host, while the address it advertises to other hosts is always the one
+//! chosen by `LISTEN_IP_FROM_ENVVAR`. The two differ when a service listens on every address.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
+
+use error::{Error, Result};
+use super::{Interface, describe_interfaces, is_selectable, snapshot};
+
+/// Which address of the host a service listens on, see `listen_addr`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BindPolicy {
+    /// The unspecified address of the family of the advertised address, `0.0.0.0` or `::`
+    Any,
+    /// The advertised address only, see `advertise_addr`
+    AdvertisedIp,
+    /// An address of the interface with the given name, of the family of the advertised address
+    /// if the interface has one
+    Interface(String),
+}
+
+impl fmt::Display for BindPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BindPolicy::Any => write!(f, "any address"),
+            BindPolicy::AdvertisedIp => write!(f, "the advertised address"),
+            BindPolicy::Interface(ref name) => write!(f, "interface {}", name),
+        }
+    }
+}
+
+/// Returns the address a service listening on the given port binds, following the policy.
+///
+/// # Errors
+///
+/// * No address is advertised, see `SysInfo::ip`
+/// * The address the policy chooses isn't an address of the host. The error lists every
+///   interface with its addresses.
+pub fn listen_addr(port: u16, policy: BindPolicy) -> Result<SocketAddr> {
+    let info = snapshot();
+    let advertised = try!(info.ip());
+    let interfaces = match policy {
+        BindPolicy::Any => vec![],
+        _ => try!(info.interfaces()),
+    };
+    listen_addr_for(port, &policy, advertised, &interfaces)
+}
+
+/// Returns the address the policy chooses of the given interfaces, see `listen_addr`.
+fn listen_addr_for(port: u16,
+                   policy: &BindPolicy,
+                   advertised: IpAddr,
+                   interfaces: &[Interface])
+                   -> Result<SocketAddr> {
+    let ip = match *policy {
+        BindPolicy::Any => {
+            match advertised {
+                IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
+                IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0)),
+            }
+        }
+        BindPolicy::AdvertisedIp => {
+            if !interfaces.iter().any(|i| i.addrs.iter().any(|a| a.ip == advertised)) {
+                return Err(Error::ListenAddrUnavailable(advertised.to_string(),
+                                                        describe_interfaces(interfaces)));
+            }
+            advertised
+        }
+        BindPolicy::Interface(ref name) => {
+            let ips: Vec<IpAddr> = interfaces.iter()
+                .filter(|i| i.name == *name)
+                .flat_map(|i| i.addrs.iter().map(|a| a.ip))
+                .filter(is_selectable)
+                .collect();
+            match ips.iter().find(|ip| same_family(ip, &advertised)).or(ips.first()) {
+                Some(ip) => *ip,
+                None => {
+                    return Err(Error::ListenAddrUnavailable(policy.to_string(),
+                                                            describe_interfaces(interfaces)))
+                }
+            }
+        }
+    };
+    Ok(SocketAddr::new(ip, port))
+}
+
+/// Returns the address other hosts reach a service listening on the given port at, which is
+/// the address chosen by `LISTEN_IP_FROM_ENVVAR` whatever the `BindPolicy` of the service.
+///
+/// # Errors
+///
+/// * No address is advertised, see `SysInfo::ip`
+pub fn advertise_addr(port: u16) -> Result<SocketAddr> {
+    let ip = try!(snapshot().ip());
+    Ok(SocketAddr::new(ip, port))
+}
+
+fn same_family(a: &IpAddr, b: &IpAddr) -> bool {
+    match (*a, *b) {
+        (IpAddr::V4(_), IpAddr::V4(_)) |
+        (IpAddr::V6(_), IpAddr::V6(_)) => true,
+        _ => false,
+    }
+}
+
+/// Returns whether a TCP listener can be bound to a port of an address of the host. Port 0
+/// asks for any free port, and isn't free itself.
+pub fn port_available(addr: IpAddr, port: u16) -> bool {
+    port != 0 && TcpListener::bind(SocketAddr::new(addr, port)).is_ok()
+}
+
+/// Returns a port of an address of the host which is free, picked by the system from its range
+/// of ephemeral ports. The port is released before it is returned, see `reserve_port` to keep it.
+///
+/// # Errors
+///
+/// * No listener can be bound to the address, such as when it isn't an address of the host
+pub fn ephemeral_port(addr: IpAddr) -> Result<u16> {
+    let listener = try!(reserve_port(addr));
+    let local = try!(listener.local_addr());
+    Ok(local.port())
+}
+
+/// Returns a listener bound to a free port of an address of the host, picked by the system from
+/// its range of ephemeral ports, which holds the port until it is dropped.
+///
+/// # Errors
+///
+/// * No listener can be bound to the address, such as when it isn't an address of the host
+pub fn reserve_port(addr: IpAddr) -> Result<TcpListener> {
+    let listener = try!(TcpListener::bind(SocketAddr::new(addr, 0)));
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, TcpListener};
+
+    use error::Error;
+    use super::*;
+    use super::listen_addr_for;
+    use super::super::{Interface, InterfaceAddr};
+
+    fn localhost() -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))
+    }
+
+    fn interface(name: &str, ips: &[&str]) -> Interface {
+        Interface {
+            name: name.to_string(),
+            addrs: ips.iter()
+                .map(|ip| {
+                    InterfaceAddr {
+                        ip: ip.parse().unwrap(),
+                        prefix_len: 24,
+                    }
+                })
+                .collect(),
+            mac: None,
+            mtu: None,
+            up: true,
+            loopback: false,
+        }
+    }
+
+    fn host() -> Vec<Interface> {
+        vec![interface("lo", &["127.0.0.1", "::1"]),
+             interface("eth0", &["fe80::1", "2001:db8::5", "10.0.0.5"])]
+    }
+
+    #[test]
+    fn bound_ports_are_not_available() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(!port_available(localhost(), port));
+        drop(listener);
+        assert!(port_available(localhost(), port));
+    }
+
+    #[test]
+    fn port_zero_is_not_available() {
+        assert!(!port_available(localhost(), 0));
+    }
+
+    #[test]
+    fn ephemeral_ports_are_released() {
+        let port = ephemeral_port(localhost()).unwrap();
+        assert!(port != 0);
+        assert!(TcpListener::bind((localhost(), port)).is_ok());
+    }
+
+    #[test]
+    fn reserved_ports_are_held() {
+        let listener = reserve_port(localhost()).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(port != 0);
+        assert!(!port_available(localhost(), port));
+        drop(listener);
+        assert!(port_available(localhost(), port));
+    }
+
+    #[test]
+    fn foreign_addresses_are_errors() {
+        // TEST-NET-1 is reserved for documentation, so it is never an address of the host
+        let addr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        assert!(!port_available(addr, 8080));
+        assert!(ephemeral_port(addr).is_err());
+        assert!(reserve_port(addr).is_err());
+    }
+
+    #[test]
+    fn any_address_follows_the_advertised_family() {
+        let v4 = listen_addr_for(8080, &BindPolicy::Any, "10.0.0.5".parse().unwrap(), &[])
+            .unwrap();
+        assert_eq!(v4.to_string(), "0.0.0.0:8080");
+        let v6 = listen_addr_for(8080, &BindPolicy::Any, "2001:db8::5".parse().unwrap(), &[])
+            .unwrap();
+        assert_eq!(v6.to_string(), "[::]:8080");
+    }
+
+    #[test]
+    fn advertised_addresses_must_be_assigned() {
+        let addr = listen_addr_for(9631,
+                                   &BindPolicy::AdvertisedIp,
+                                   "10.0.0.5".parse().unwrap(),
+                                   &host())
+            .unwrap();
+        assert_eq!(addr.to_string(), "10.0.0.5:9631");
+        match listen_addr_for(9631,
+                              &BindPolicy::AdvertisedIp,
+                              "192.0.2.1".parse().unwrap(),
+                              &host()) {
+            Err(Error::ListenAddrUnavailable(addr, candidates)) => {
+                assert_eq!(addr, "192.0.2.1");
+                assert!(candidates.contains("eth0 (fe80::1/24, 2001:db8::5/24, 10.0.0.5/24)"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn interfaces_bind_the_advertised_family() {
+        let eth0 = BindPolicy::Interface("eth0".to_string());
+        let v4 = listen_addr_for(80, &eth0, "10.0.0.5".parse().unwrap(), &host()).unwrap();
+        assert_eq!(v4.to_string(), "10.0.0.5:80");
+        let v6 = listen_addr_for(80, &eth0, "2001:db8::5".parse().unwrap(), &host()).unwrap();
+        assert_eq!(v6.to_string(), "[2001:db8::5]:80");
+        let ipv4_only = vec![interface("eth1", &["10.1.0.5"])];
+        let eth1 = BindPolicy::Interface("eth1".to_string());
+        let v4 = listen_addr_for(80, &eth1, "2001:db8::5".parse().unwrap(), &ipv4_only).unwrap();
+        assert_eq!(v4.to_string(), "10.1.0.5:80");
+    }
+
+    #[test]
+    fn unknown_interfaces_are_errors() {
+        let policy = BindPolicy::Interface("eth9".to_string());
+        match listen_addr_for(80, &policy, "10.0.0.5".parse().unwrap(), &host()) {
+            Err(Error::ListenAddrUnavailable(addr, candidates)) => {
+                assert_eq!(addr, "interface eth9");
+                assert!(candidates.starts_with("lo (127.0.0.1/24, ::1/24)"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Where facts about this host are read from: its files, such as those of `/proc` and `/sys`,
+//! the commands run when the files don't tell, and its resolver.
+//!
+//! Detection takes a `FsReader`, a `CommandRunner` and a `Resolver` rather than reading files,
+//! running commands and resolving names itself, so its tests can stand a `FakeHost` holding
+//! fixtures in for this host. The public functions of `sys` pass `Host`.
+
+#[cfg(test)]
+use std::cell::RefCell;
+#[cfg(test)]
+use std::collections::HashMap;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::path::Path;
+#[cfg(test)]
+use std::path::PathBuf;
+
+#[cfg(test)]
+use error::Error;
+use error::Result;
+use super::{command_output, read_file};
+
+/// Runs the commands facts are read from.
+pub trait CommandRunner {
+    /// Runs a program and returns what it wrote to stdout, or an error if it couldn't be run, it
+    /// failed or it didn't finish in time.
+    fn run(&self, program: &str, args: &[&str]) -> Result<String>;
+}
+
+/// Reads the files facts are read from.
+pub trait FsReader {
+    /// Returns the contents of a file, or `None` if it can't be read.
+    fn read_to_string(&self, path: &Path) -> Option<String>;
+
+    /// Returns whether a file exists, which some files only tell by existing.
+    fn exists(&self, path: &Path) -> bool;
+}
+
+/// Resolves the names facts are about.
+pub trait Resolver {
+    /// Returns the addresses the system resolver gives a name, in the order it gives them, or an
+    /// error if the name doesn't resolve.
+    fn resolve(&self, name: &str) -> Result<Vec<IpAddr>>;
+}
+
+/// This host, whose files are read, whose commands are run and whose resolver is asked.
+pub struct Host;
+
+impl CommandRunner for Host {
+    fn run(&self, program: &str, args: &[&str]) -> Result<String> {
+        command_output(program, args)
+    }
+}
+
+impl FsReader for Host {
+    fn read_to_string(&self, path: &Path) -> Option<String> {
+        read_file(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+}
+
+impl Resolver for Host {
+    fn resolve(&self, name: &str) -> Result<Vec<IpAddr>> {
+        // getaddrinfo(3) returns an address once for each socket type, so repeats are dropped
+        let mut addrs: Vec<IpAddr> = vec![];
+        for addr in try!((name, 0).to_socket_addrs()) {
+            if !addrs.contains(&addr.ip()) {
+                addrs.push(addr.ip());
+            }
+        }
+        Ok(addrs)
+    }
+}
+
+/// A host made of the files, command output and names it is given, which records what is read
+/// and run. A file it isn't given can't be read, a command it isn't given fails and a name it
+/// isn't given doesn't resolve.
+#[cfg(test)]
+#[derive(Default)]
+pub struct FakeHost {
+    files: HashMap<PathBuf, String>,
+    outputs: HashMap<String, String>,
+    names: HashMap<String, Vec<IpAddr>>,
+    reads: RefCell<Vec<PathBuf>>,
+    runs: RefCell<Vec<String>>,
+}
+
+#[cfg(test)]
+impl FakeHost {
+    pub fn new() -> Self {
+        FakeHost::default()
+    }
+
+    /// Adds a file holding `contents`.
+    pub fn file<P: Into<PathBuf>>(mut self, path: P, contents: &str) -> Self {
+        self.files.insert(path.into(), contents.to_string());
+        self
+    }
+
+    /// Adds the output of a command line, a program and its arguments joined by spaces.
+    pub fn output(mut self, cmd: &str, stdout: &str) -> Self {
+        self.outputs.insert(cmd.to_string(), stdout.to_string());
+        self
+    }
+
+    /// Adds a name which resolves to the given addresses.
+    pub fn resolves(mut self, name: &str, addrs: &[&str]) -> Self {
+        let addrs = addrs.iter().map(|addr| addr.parse().unwrap()).collect();
+        self.names.insert(name.to_string(), addrs);
+        self
+    }
+
+    /// Returns every path looked at, whether it was read or checked for, in order.
+    pub fn reads(&self) -> Vec<PathBuf> {
+        self.reads.borrow().clone()
+    }
+
+    /// Returns every command line run, in order.
+    pub fn runs(&self) -> Vec<String> {
+        self.runs.borrow().clone()
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for FakeHost {
+    fn run(&self, program: &str, args: &[&str]) -> Result<String> {
+        let mut cmd = vec![program];
+        cmd.extend_from_slice(args);
+        let cmd = cmd.join(" ");
+        self.runs.borrow_mut().push(cmd.clone());
+        match self.outputs.get(&cmd) {
+            Some(stdout) => Ok(stdout.clone()),
+            None => {
+                Err(Error::SysCommandFailed {
+                    cmd: cmd,
+                    status: "exit code: 127".to_string(),
+                    stdout: String::new(),
+                    stderr: "command not found".to_string(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl FsReader for FakeHost {
+    fn read_to_string(&self, path: &Path) -> Option<String> {
+        self.reads.borrow_mut().push(path.to_path_buf());
+        self.files.get(path).cloned()
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.reads.borrow_mut().push(path.to_path_buf());
+        self.files.contains_key(path)
+    }
+}
+
+#[cfg(test)]
+impl Resolver for FakeHost {
+    fn resolve(&self, name: &str) -> Result<Vec<IpAddr>> {
+        match self.names.get(name) {
+            Some(addrs) => Ok(addrs.clone()),
+            None => Err(Error::SysInfoFailed(format!("{} doesn't resolve", name))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    #[test]
+    fn fake_hosts_record_what_is_read_and_run() {
+        let host = FakeHost::new()
+            .file("/proc/loadavg", "0.5 0.4 0.3 1/100 42\n")
+            .output("ip route", "");
+        assert_eq!(host.read_to_string(Path::new("/proc/loadavg")),
+                   Some("0.5 0.4 0.3 1/100 42\n".to_string()));
+        assert_eq!(host.read_to_string(Path::new("/proc/uptime")), None);
+        assert!(!host.exists(Path::new("/.dockerenv")));
+        assert_eq!(host.run("ip", &["route"]).unwrap(), "");
+        assert!(host.run("ifconfig", &[]).is_err());
+        assert_eq!(host.reads(),
+                   vec![PathBuf::from("/proc/loadavg"),
+                        PathBuf::from("/proc/uptime"),
+                        PathBuf::from("/.dockerenv")]);
+        assert_eq!(host.runs(), vec!["ip route".to_string(), "ifconfig".to_string()]);
+    }
+
+    #[test]
+    fn names_resolve_to_the_addresses_given() {
+        let host = FakeHost::new().resolves("db-1", &["127.0.1.1"]);
+        assert_eq!(host.resolve("db-1").unwrap(), vec!["127.0.1.1".parse::<IpAddr>().unwrap()]);
+        assert!(host.resolve("db-2").is_err());
+        assert!(Host.resolve("localhost").unwrap().iter().all(|addr| addr.is_loopback()));
+    }
+}
@@ -0,0 +1,495 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Discovery shared by Linux and macOS.
+
+use std::ffi::{CStr, CString};
+use std::io;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, TcpStream};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+use std::ptr;
+
+use libc;
+
+use errno::errno;
+use error::{Error, Result};
+use super::{DiskUsage, Interface, InterfaceAddr, IpPreference, LoadAvg, ProcessInfo, Rlimit,
+            Rlimits, Uname, block_bytes, inode_counts, prefix_len};
+use super::parse::parse_ifconfig;
+use super::source::{CommandRunner, Host};
+
+/// Size of the longest host name getnameinfo(3) returns, with its terminating NUL
+const NI_MAXHOST: usize = 1025;
+
+/// Largest host name `gethostname` is asked for before giving up
+const MAX_HOSTNAME_LEN: usize = 4096;
+/// Largest buffer an entry of the user or group database is read into before giving up
+const MAX_ENTRY_BUF_LEN: usize = 1 << 20;
+/// Buffer an entry of the user or group database is first read into if sysconf(3) has no hint
+const DEFAULT_ENTRY_BUF_LEN: usize = 1024;
+
+/// Lists the network interfaces of this host with `getifaddrs`. Entries which aren't IP addresses
+/// are given to `mac_address`, which returns the interface's hardware address if the entry
+/// carries it.
+pub fn interfaces(mac_address: unsafe fn(*const libc::sockaddr) -> Option<String>)
+                  -> Result<Vec<Interface>> {
+    let mut interfaces: Vec<Interface> = vec![];
+    unsafe {
+        let mut addrs: *mut libc::ifaddrs = ptr::null_mut();
+        if libc::getifaddrs(&mut addrs) != 0 {
+            return Err(Error::InterfaceEnumeration(io::Error::from_raw_os_error(errno().0)));
+        }
+        let mut cur = addrs;
+        while !cur.is_null() {
+            let ifa = &*cur;
+            cur = ifa.ifa_next;
+            let name = CStr::from_ptr(ifa.ifa_name).to_string_lossy().into_owned();
+            let pos = match interfaces.iter().position(|i| i.name == name) {
+                Some(pos) => pos,
+                None => {
+                    let flags = ifa.ifa_flags as libc::c_int;
+                    interfaces.push(Interface {
+                        name: name,
+                        addrs: vec![],
+                        mac: None,
+                        mtu: None,
+                        up: flags & libc::IFF_UP != 0,
+                        loopback: flags & libc::IFF_LOOPBACK != 0,
+                    });
+                    interfaces.len() - 1
+                }
+            };
+            if ifa.ifa_addr.is_null() {
+                continue;
+            }
+            match sockaddr_ip(ifa.ifa_addr) {
+                Some(ip) => {
+                    let prefix_len = if ifa.ifa_netmask.is_null() {
+                        0
+                    } else {
+                        sockaddr_ip(ifa.ifa_netmask).map_or(0, |mask| prefix_len(&mask))
+                    };
+                    interfaces[pos].addrs.push(InterfaceAddr {
+                        ip: ip,
+                        prefix_len: prefix_len,
+                    });
+                }
+                None => {
+                    if let Some(mac) = mac_address(ifa.ifa_addr) {
+                        interfaces[pos].mac = Some(mac);
+                    }
+                }
+            }
+        }
+        libc::freeifaddrs(addrs);
+    }
+    Ok(interfaces)
+}
+
+unsafe fn sockaddr_ip(addr: *const libc::sockaddr) -> Option<IpAddr> {
+    match (*addr).sa_family as libc::c_int {
+        libc::AF_INET => {
+            let sin = &*(addr as *const libc::sockaddr_in);
+            Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr))))
+        }
+        libc::AF_INET6 => {
+            let sin6 = &*(addr as *const libc::sockaddr_in6);
+            Some(IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr)))
+        }
+        _ => None,
+    }
+}
+
+/// Puts the process a command starts in a process group of its own, so it can be killed along
+/// with every process it starts.
+pub fn new_process_group(command: &mut Command) {
+    unsafe {
+        command.before_exec(|| {
+            libc::setpgid(0, 0);
+            Ok(())
+        });
+    }
+}
+
+/// Kills the process group led by a process started by a command given to `new_process_group`.
+pub fn kill_process_group(pid: u32) {
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+/// Returns the name getnameinfo(3) finds for an address, or `None` if it finds none rather than
+/// the address itself.
+pub fn reverse_lookup(ip: IpAddr) -> Option<String> {
+    let mut host = [0 as libc::c_char; NI_MAXHOST];
+    let rv = unsafe {
+        match ip {
+            IpAddr::V4(ref ip) => {
+                let mut addr: libc::sockaddr_in = mem::zeroed();
+                addr.sin_family = libc::AF_INET as libc::sa_family_t;
+                addr.sin_addr.s_addr = u32::from(*ip).to_be();
+                nameinfo(&addr as *const _ as *const libc::sockaddr,
+                         mem::size_of_val(&addr),
+                         &mut host)
+            }
+            IpAddr::V6(ref ip) => {
+                let mut addr: libc::sockaddr_in6 = mem::zeroed();
+                addr.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+                addr.sin6_addr.s6_addr = ip.octets();
+                nameinfo(&addr as *const _ as *const libc::sockaddr,
+                         mem::size_of_val(&addr),
+                         &mut host)
+            }
+        }
+    };
+    if rv != 0 {
+        debug!("getnameinfo failed for {}, err={}", ip, rv);
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(host.as_ptr()) };
+    name.to_str().ok().map(|name| name.to_string())
+}
+
+unsafe fn nameinfo(addr: *const libc::sockaddr,
+                   len: usize,
+                   host: &mut [libc::c_char; NI_MAXHOST])
+                   -> libc::c_int {
+    libc::getnameinfo(addr,
+                      len as libc::socklen_t,
+                      host.as_mut_ptr(),
+                      host.len() as libc::socklen_t,
+                      ptr::null_mut(),
+                      0,
+                      libc::NI_NAMEREQD)
+}
+
+/// Returns the canonical name `getaddrinfo` resolves the given host name to, which is its fully
+/// qualified domain name when DNS or `/etc/hosts` knows it.
+pub fn canonical_name(hostname: &str) -> Option<String> {
+    let node = match CString::new(hostname) {
+        Ok(node) => node,
+        Err(_) => return None,
+    };
+    let mut hints: libc::addrinfo = unsafe { mem::zeroed() };
+    hints.ai_flags = libc::AI_CANONNAME;
+    hints.ai_family = libc::AF_UNSPEC;
+    hints.ai_socktype = libc::SOCK_DGRAM;
+    let mut res: *mut libc::addrinfo = ptr::null_mut();
+    let rv = unsafe { libc::getaddrinfo(node.as_ptr(), ptr::null(), &hints, &mut res) };
+    if rv != 0 {
+        debug!("getaddrinfo failed for {}, err={}", hostname, rv);
+        return None;
+    }
+    let name = unsafe {
+        if res.is_null() || (*res).ai_canonname.is_null() {
+            None
+        } else {
+            CStr::from_ptr((*res).ai_canonname).to_str().ok().map(|name| name.to_string())
+        }
+    };
+    unsafe { libc::freeaddrinfo(res) };
+    name
+}
+
+/// Returns the ids of this process, with the names of its effective user and real group.
+pub fn process_info() -> ProcessInfo {
+    let (pid, ppid) = unsafe { (libc::getpid(), libc::getppid()) };
+    let (uid, euid, gid) = unsafe { (libc::getuid(), libc::geteuid(), libc::getgid()) };
+    ProcessInfo {
+        pid: pid as u32,
+        ppid: Some(ppid as u32),
+        uid: Some(uid as u32),
+        euid: Some(euid as u32),
+        gid: Some(gid as u32),
+        user: user_name(euid).unwrap_or_else(|| euid.to_string()),
+        group: Some(group_name(gid).unwrap_or_else(|| gid.to_string())),
+    }
+}
+
+/// Returns the limits getrlimit(2) reports on the resources of this process.
+pub fn rlimits() -> Result<Rlimits> {
+    Ok(Rlimits {
+        nofile: try!(rlimit("RLIMIT_NOFILE",
+                            |limit| unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, limit) })),
+        nproc: try!(rlimit("RLIMIT_NPROC",
+                           |limit| unsafe { libc::getrlimit(libc::RLIMIT_NPROC, limit) })),
+        core: try!(rlimit("RLIMIT_CORE",
+                          |limit| unsafe { libc::getrlimit(libc::RLIMIT_CORE, limit) })),
+        memlock: try!(rlimit("RLIMIT_MEMLOCK",
+                             |limit| unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, limit) })),
+    })
+}
+
+/// Reads a limit with `getrlimit`, which is given the limit to fill in. The type of the resource
+/// differs between the C libraries, so the caller names it.
+fn rlimit<F>(resource: &str, getrlimit: F) -> Result<Rlimit>
+    where F: FnOnce(*mut libc::rlimit) -> libc::c_int
+{
+    let mut limit: libc::rlimit = unsafe { mem::zeroed() };
+    if getrlimit(&mut limit) != 0 {
+        let errno = errno();
+        let code = errno.0 as i32;
+        return Err(Error::RlimitsFailed(format!("Error {} when calling getrlimit for {}: {}",
+                                                code,
+                                                resource,
+                                                errno)));
+    }
+    Ok(Rlimit {
+        soft: rlim_value(limit.rlim_cur),
+        hard: rlim_value(limit.rlim_max),
+    })
+}
+
+/// Returns a limit of getrlimit(2) as a number of 64 bits, which `rlim_t` may be narrower than on
+/// 32-bit platforms, or `None` if it is `RLIM_INFINITY`. The sentinel is compared before the
+/// limit is widened, as it is a value of the narrower type.
+pub fn rlim_value(value: libc::rlim_t) -> Option<u64> {
+    if value == libc::RLIM_INFINITY {
+        None
+    } else {
+        Some(value as u64)
+    }
+}
+
+/// Returns the name of a user from the user database, if it has an entry for the user.
+pub fn user_name(uid: libc::uid_t) -> Option<String> {
+    lookup_name("getpwuid_r", libc::_SC_GETPW_R_SIZE_MAX, |buf| unsafe {
+        let mut entry: libc::passwd = mem::zeroed();
+        let mut found: *mut libc::passwd = ptr::null_mut();
+        let rv = libc::getpwuid_r(uid, &mut entry, buf.as_mut_ptr(), buf.len(), &mut found);
+        (rv, if found.is_null() { None } else { entry_name(entry.pw_name) })
+    })
+}
+
+/// Returns the name of a group from the group database, if it has an entry for the group.
+pub fn group_name(gid: libc::gid_t) -> Option<String> {
+    lookup_name("getgrgid_r", libc::_SC_GETGR_R_SIZE_MAX, |buf| unsafe {
+        let mut entry: libc::group = mem::zeroed();
+        let mut found: *mut libc::group = ptr::null_mut();
+        let rv = libc::getgrgid_r(gid, &mut entry, buf.as_mut_ptr(), buf.len(), &mut found);
+        (rv, if found.is_null() { None } else { entry_name(entry.gr_name) })
+    })
+}
+
+/// Calls a reentrant lookup of the user or group database with a growing buffer until the entry
+/// fits, as entries with long names or many members don't fit the size sysconf(3) suggests.
+/// `lookup` returns the error number of the call, and the name of the entry it found.
+fn lookup_name<F>(call: &str, size_hint: libc::c_int, mut lookup: F) -> Option<String>
+    where F: FnMut(&mut [libc::c_char]) -> (libc::c_int, Option<String>)
+{
+    let hint = unsafe { libc::sysconf(size_hint) };
+    let mut len = if hint > 0 {
+        hint as usize
+    } else {
+        DEFAULT_ENTRY_BUF_LEN
+    };
+    loop {
+        let mut buf: Vec<libc::c_char> = vec![0; len];
+        match lookup(&mut buf) {
+            (0, name) => return name,
+            (libc::ERANGE, _) if len < MAX_ENTRY_BUF_LEN => len *= 2,
+            (rv, _) => {
+                debug!("{} failed, err={}", call, rv);
+                return None;
+            }
+        }
+    }
+}
+
+unsafe fn entry_name(name: *const libc::c_char) -> Option<String> {
+    if name.is_null() {
+        return None;
+    }
+    let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+    if name.is_empty() { None } else { Some(name) }
+}
+
+/// Calls `gethostname` with a growing buffer until the name fits. A name which exactly fills the
+/// buffer may have been truncated without a terminating NUL, so the buffer is also grown then.
+pub fn gethostname() -> Option<String> {
+    let mut len = 256;
+    while len <= MAX_HOSTNAME_LEN {
+        let mut buf = vec![0u8; len];
+        let rv = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, len) };
+        if rv == 0 {
+            if let Some(end) = buf.iter().position(|b| *b == 0) {
+                buf.truncate(end);
+                return String::from_utf8(buf).ok();
+            }
+        } else {
+            let errno = errno();
+            if errno.0 != libc::ENAMETOOLONG && errno.0 != libc::EINVAL {
+                debug!("gethostname failed, err={}", errno);
+                return None;
+            }
+        }
+        len *= 2;
+    }
+    None
+}
+
+/// Returns the space statvfs(3) reports on the filesystem holding `path`, which must exist.
+pub fn disk_usage(path: &Path) -> Result<DiskUsage> {
+    let c_path = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(c_path) => c_path,
+        Err(_) => {
+            return Err(Error::DiskUsageFailed(format!("{} contains a nul byte", path.display())))
+        }
+    };
+    let stat = unsafe {
+        let mut stat: libc::statvfs = mem::zeroed();
+        if libc::statvfs(c_path.as_ptr(), &mut stat) != 0 {
+            let errno = errno();
+            let code = errno.0 as i32;
+            return Err(Error::DiskUsageFailed(format!("Error {} when calling statvfs on {}: {}",
+                                                      code,
+                                                      path.display(),
+                                                      errno)));
+        }
+        stat
+    };
+    // Block counts are in units of the fragment size, which some systems leave unset
+    let block_size = if stat.f_frsize != 0 {
+        stat.f_frsize as u64
+    } else {
+        stat.f_bsize as u64
+    };
+    let blocks = stat.f_blocks as u64;
+    let (inodes_total, inodes_free) = inode_counts(stat.f_files as u64, stat.f_ffree as u64);
+    Ok(DiskUsage {
+        path: path.to_path_buf(),
+        total_bytes: block_bytes(blocks, block_size),
+        available_bytes: block_bytes(stat.f_bavail as u64, block_size),
+        used_bytes: block_bytes(blocks.saturating_sub(stat.f_bfree as u64), block_size),
+        inodes_total: inodes_total,
+        inodes_free: inodes_free,
+        fs_type: None,
+    })
+}
+
+extern "C" {
+    fn getloadavg(loadavg: *mut libc::c_double, nelem: libc::c_int) -> libc::c_int;
+}
+
+/// Returns the load average getloadavg(3) reports, which doesn't count processes.
+pub fn loadavg() -> Result<LoadAvg> {
+    let mut loads = [0.0 as libc::c_double; 3];
+    if unsafe { getloadavg(loads.as_mut_ptr(), 3) } != 3 {
+        return Err(Error::LoadAvgFailed("getloadavg failed".to_string()));
+    }
+    Ok(LoadAvg {
+        one: loads[0],
+        five: loads[1],
+        fifteen: loads[2],
+        running: None,
+        total: None,
+    })
+}
+
+/// Returns the number of CPUs which are online.
+pub fn online_cpus() -> Result<u32> {
+    let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    if count < 1 {
+        let errno = errno();
+        let code = errno.0 as i32;
+        return Err(Error::CpuInfoFailed(format!("Error {} when calling sysconf: {}", code, errno)));
+    }
+    Ok(count as u32)
+}
+
+pub fn uname() -> Result<Uname> {
+    unsafe { uname_libc() }
+}
+
+unsafe fn uname_libc() -> Result<Uname> {
+    let mut utsname: libc::utsname = mem::uninitialized();
+    let rv = libc::uname(&mut utsname);
+    if rv < 0 {
+        let errno = errno();
+        let code = errno.0 as i32;
+        return Err(Error::UnameFailed(format!("Error {} when calling uname: {}", code, errno)));
+    }
+    Ok(Uname {
+        sys_name: CStr::from_ptr(utsname.sysname.as_ptr()).to_string_lossy().into_owned(),
+        node_name: CStr::from_ptr(utsname.nodename.as_ptr()).to_string_lossy().into_owned(),
+        release: CStr::from_ptr(utsname.release.as_ptr()).to_string_lossy().into_owned(),
+        version: CStr::from_ptr(utsname.version.as_ptr()).to_string_lossy().into_owned(),
+        machine: CStr::from_ptr(utsname.machine.as_ptr()).to_string_lossy().into_owned(),
+    })
+}
+
+pub fn ifconfig_ipv4() -> Result<Option<IpAddr>> {
+    ifconfig(&Host, IpPreference::Ipv4First)
+}
+
+pub fn ifconfig_ipv6() -> Result<Option<IpAddr>> {
+    ifconfig(&Host, IpPreference::Ipv6First)
+}
+
+fn ifconfig(runner: &CommandRunner, family: IpPreference) -> Result<Option<IpAddr>> {
+    let output = try!(runner.run("ifconfig", &[]));
+    Ok(parse_ifconfig(&output, family))
+}
+
+/// Has closing `stream` reset the connection, discarding what wasn't yet acknowledged rather
+/// than sending it again until the peer does.
+pub fn reset_on_close(stream: &TcpStream) -> io::Result<()> {
+    let linger = libc::linger {
+        l_onoff: 1,
+        l_linger: 0,
+    };
+    let rv = unsafe {
+        libc::setsockopt(stream.as_raw_fd(),
+                         libc::SOL_SOCKET,
+                         libc::SO_LINGER,
+                         &linger as *const libc::linger as *const libc::c_void,
+                         mem::size_of::<libc::linger>() as libc::socklen_t)
+    };
+    if rv < 0 { Err(io::Error::last_os_error()) } else { Ok(()) }
+}
+
+/// Sets an integer socket option of `fd`.
+pub fn setsockopt_int(fd: libc::c_int,
+                      level: libc::c_int,
+                      name: libc::c_int,
+                      value: libc::c_int)
+                      -> io::Result<()> {
+    let rv = unsafe {
+        libc::setsockopt(fd,
+                         level,
+                         name,
+                         &value as *const libc::c_int as *const libc::c_void,
+                         mem::size_of::<libc::c_int>() as libc::socklen_t)
+    };
+    if rv < 0 { Err(io::Error::last_os_error()) } else { Ok(()) }
+}
+
+/// Returns an integer socket option of `fd`.
+pub fn getsockopt_int(fd: libc::c_int, level: libc::c_int, name: libc::c_int) -> io::Result<i32> {
+    let mut value: libc::c_int = 0;
+    let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    let rv = unsafe {
+        libc::getsockopt(fd,
+                         level,
+                         name,
+                         &mut value as *mut libc::c_int as *mut libc::c_void,
+                         &mut len)
+    };
+    if rv < 0 { Err(io::Error::last_os_error()) } else { Ok(value) }
+}
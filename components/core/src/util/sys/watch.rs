@@ -0,0 +1,314 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Notifications of changes to this host's IP address and host name, such as after a DHCP
+//! renewal or when a cloud instance is given its address late in boot.
+
+use std::fmt;
+use std::mem;
+use std::net::IpAddr;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use error::Result;
+use util::redact;
+use util::shutdown::Shutdown;
+use super::cache::{self, SysInfo, snapshot};
+
+/// Number of consecutive detections which must agree on a changed value before it is reported,
+/// so that a value flapping between detections is never reported.
+const DEBOUNCE_DETECTIONS: u32 = 2;
+
+/// How long dropping a `SysWatch` waits for a detection in progress to finish
+const DROP_JOIN_TIMEOUT_SECS: u64 = 10;
+
+/// A change to a fact about this host. `None` means the fact could no longer be discovered, or
+/// could not be discovered before.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SysChange {
+    Ip {
+        old: Option<IpAddr>,
+        new: Option<IpAddr>,
+    },
+    Hostname {
+        old: Option<String>,
+        new: Option<String>,
+    },
+}
+
+impl fmt::Display for SysChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn or_none<T: fmt::Display>(value: &Option<T>) -> String {
+            value.as_ref().map_or("none".to_string(), |v| v.to_string())
+        }
+        match *self {
+            SysChange::Ip { ref old, ref new } => {
+                write!(f, "IP address changed from {} to {}", or_none(old), or_none(new))
+            }
+            SysChange::Hostname { ref old, ref new } => {
+                write!(f, "Host name changed from {} to {}", or_none(old), or_none(new))
+            }
+        }
+    }
+}
+
+/// Called by the watching thread with each change it finds, see `SysWatch::on_change`.
+pub type SysChangeCallback = Box<Fn(&SysChange) + Send>;
+
+/// Receives the changes found by `watch`. Dropping one started by `watch` stops the watching
+/// thread, while one started by `watch_with` leaves it to its `Shutdown`.
+pub struct SysWatch {
+    receiver: Receiver<SysChange>,
+    callbacks: Arc<Mutex<Vec<SysChangeCallback>>>,
+    shutdown: Shutdown,
+    owns_shutdown: bool,
+}
+
+impl SysWatch {
+    /// Registers a callback the watching thread calls with each change it finds, before the
+    /// change is sent to the receiver, in the order the callbacks were registered. A callback
+    /// runs on the watching thread, so it should hand any slow work off rather than do it.
+    pub fn on_change(&self, callback: SysChangeCallback) {
+        self.callbacks.lock().expect("Sys watch callbacks lock poisoned").push(callback);
+    }
+}
+
+impl Deref for SysWatch {
+    type Target = Receiver<SysChange>;
+
+    fn deref(&self) -> &Receiver<SysChange> {
+        &self.receiver
+    }
+}
+
+impl Drop for SysWatch {
+    fn drop(&mut self) {
+        if !self.owns_shutdown {
+            return;
+        }
+        self.shutdown.shutdown();
+        if !self.shutdown.join(Duration::from_secs(DROP_JOIN_TIMEOUT_SECS)) {
+            warn!("Sys watch didn't stop within {}s, leaving it to finish on its own",
+                  DROP_JOIN_TIMEOUT_SECS);
+        }
+    }
+}
+
+/// Starts a thread which detects this host's IP address and host name every `interval` and
+/// sends a `SysChange` for each one which differs from the last known value. The cached
+/// `snapshot` is replaced by the detection which found a change, so everything rendered from it
+/// afterwards sees the new values.
+///
+/// # Errors
+///
+/// * The watching thread could not be started
+pub fn watch(interval: Duration) -> Result<SysWatch> {
+    let mut watch = try!(watch_with(interval, &Shutdown::new()));
+    watch.owns_shutdown = true;
+    Ok(watch)
+}
+
+/// Like `watch`, except that the watching thread stops when `shutdown` is shut down, and
+/// `Shutdown::join` waits for it.
+///
+/// # Errors
+///
+/// * The watching thread could not be started
+pub fn watch_with(interval: Duration, shutdown: &Shutdown) -> Result<SysWatch> {
+    let (tx, rx) = mpsc::channel();
+    let callbacks = Arc::new(Mutex::new(Vec::new()));
+    let thread_callbacks = callbacks.clone();
+    try!(shutdown.spawn("sys-watch", move |shutdown| {
+            let mut watcher = Watcher::new(&snapshot());
+            while !shutdown.sleep(interval) {
+                let info = SysInfo::detect();
+                let changes = watcher.observe(&info);
+                if changes.is_empty() {
+                    continue;
+                }
+                cache::store(info);
+                for change in changes {
+                    debug!("{}", redact::redact_text(&change.to_string()));
+                    notify(&thread_callbacks, &change);
+                    if tx.send(change).is_err() {
+                        return;
+                    }
+                }
+            }
+        }));
+    Ok(SysWatch {
+        receiver: rx,
+        callbacks: callbacks,
+        shutdown: shutdown.clone(),
+        owns_shutdown: false,
+    })
+}
+
+/// Calls each registered callback with a change.
+fn notify(callbacks: &Mutex<Vec<SysChangeCallback>>, change: &SysChange) {
+    for callback in callbacks.lock().expect("Sys watch callbacks lock poisoned").iter() {
+        callback(change);
+    }
+}
+
+struct Watcher {
+    ip: Debounced<Option<IpAddr>>,
+    hostname: Debounced<Option<String>>,
+}
+
+impl Watcher {
+    fn new(info: &SysInfo) -> Watcher {
+        Watcher {
+            ip: Debounced::new(info.ip().ok(), DEBOUNCE_DETECTIONS),
+            hostname: Debounced::new(info.hostname().ok(), DEBOUNCE_DETECTIONS),
+        }
+    }
+
+    fn observe(&mut self, info: &SysInfo) -> Vec<SysChange> {
+        let mut changes = vec![];
+        if let Some((old, new)) = self.ip.observe(info.ip().ok()) {
+            changes.push(SysChange::Ip {
+                old: old,
+                new: new,
+            });
+        }
+        if let Some((old, new)) = self.hostname.observe(info.hostname().ok()) {
+            changes.push(SysChange::Hostname {
+                old: old,
+                new: new,
+            });
+        }
+        changes
+    }
+}
+
+/// A value which only changes once `detections` consecutive observations agree on a new one.
+struct Debounced<T> {
+    current: T,
+    pending: Option<T>,
+    seen: u32,
+    detections: u32,
+}
+
+impl<T: Clone + PartialEq> Debounced<T> {
+    fn new(current: T, detections: u32) -> Debounced<T> {
+        Debounced {
+            current: current,
+            pending: None,
+            seen: 0,
+            detections: detections,
+        }
+    }
+
+    /// Records an observation, returning the old and new value if it confirms a change.
+    fn observe(&mut self, value: T) -> Option<(T, T)> {
+        if value == self.current {
+            self.pending = None;
+            self.seen = 0;
+            return None;
+        }
+        if self.pending.as_ref() == Some(&value) {
+            self.seen += 1;
+        } else {
+            self.pending = Some(value.clone());
+            self.seen = 1;
+        }
+        if self.seen < self.detections {
+            return None;
+        }
+        self.pending = None;
+        self.seen = 0;
+        let old = mem::replace(&mut self.current, value.clone());
+        Some((old, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    use util::shutdown::Shutdown;
+    use super::*;
+    use super::{Debounced, notify};
+
+    #[test]
+    fn changes_are_confirmed() {
+        let mut ip = Debounced::new("10.0.0.5", 2);
+        assert_eq!(ip.observe("10.0.0.6"), None);
+        assert_eq!(ip.observe("10.0.0.6"), Some(("10.0.0.5", "10.0.0.6")));
+        assert_eq!(ip.observe("10.0.0.6"), None);
+    }
+
+    #[test]
+    fn flapping_values_are_not_reported() {
+        let mut ip = Debounced::new("10.0.0.5", 2);
+        for _ in 0..5 {
+            assert_eq!(ip.observe("10.0.0.6"), None);
+            assert_eq!(ip.observe("10.0.0.5"), None);
+        }
+        assert_eq!(ip.observe("10.0.0.6"), None);
+        assert_eq!(ip.observe("10.0.0.7"), None);
+        assert_eq!(ip.observe("10.0.0.7"), Some(("10.0.0.5", "10.0.0.7")));
+    }
+
+    #[test]
+    fn change_is_displayed() {
+        let change = SysChange::Ip {
+            old: None,
+            new: Some("10.0.0.5".parse().unwrap()),
+        };
+        assert_eq!(change.to_string(), "IP address changed from none to 10.0.0.5");
+    }
+
+    #[test]
+    fn dropping_the_watch_stops_it() {
+        let started = Instant::now();
+        drop(watch(Duration::from_secs(600)).unwrap());
+        assert!(started.elapsed() < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn shutting_down_stops_the_watch() {
+        let shutdown = Shutdown::new();
+        let watch = watch_with(Duration::from_secs(600), &shutdown).unwrap();
+        shutdown.shutdown();
+        assert!(shutdown.join(Duration::from_secs(60)));
+        // The thread dropped its sender on the way out
+        assert!(watch.recv().is_err());
+    }
+
+    #[test]
+    fn callbacks_are_called_in_order() {
+        let (tx, rx) = mpsc::channel();
+        let callbacks: Mutex<Vec<SysChangeCallback>> = Mutex::new(vec![]);
+        for name in vec!["census", "config"] {
+            let tx = tx.clone();
+            callbacks.lock().unwrap().push(Box::new(move |change: &SysChange| {
+                tx.send(format!("{}: {}", name, change)).unwrap();
+            }));
+        }
+        notify(&callbacks,
+               &SysChange::Hostname {
+                   old: Some("db-1".to_string()),
+                   new: Some("db-2".to_string()),
+               });
+        assert_eq!(rx.try_recv().unwrap(), "census: Host name changed from db-1 to db-2");
+        assert_eq!(rx.try_recv().unwrap(), "config: Host name changed from db-1 to db-2");
+        assert!(rx.try_recv().is_err());
+    }
+}
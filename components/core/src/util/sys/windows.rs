@@ -0,0 +1,334 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::env;
+use std::io;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, TcpStream};
+use std::os::windows::ffi::OsStrExt;
+use std::os::raw::c_void;
+use std::path::Path;
+use std::process::Command;
+use std::ptr;
+use std::time::{Duration, Instant};
+
+use error::{Error, Result};
+use super::{CgroupLimits, ConflictCheck, CpuInfo, CpuStat, DiskUsage, ENTROPY_UNLIMITED, FsUsage,
+            Gateway, HugepageInfo, Interface, IpPreference, IpStrategy, LoadAvg, LsmStatus, MemInfo,
+            MountInfo, Neighbor, ProcCounts, ProcessInfo, Rlimits, SelinuxMode, Virt, Uname,
+            hostname_command, ifaddrs_ipv4, ifaddrs_ipv6, udp_ipv4, udp_ipv6};
+use super::conn::PathMtuReport;
+use super::parse::parse_ipconfig;
+use super::source::{CommandRunner, Host};
+
+/// `ComputerNamePhysicalDnsHostname` of the `COMPUTER_NAME_FORMAT` enumeration
+const COMPUTER_NAME_PHYSICAL_DNS_HOSTNAME: u32 = 5;
+/// `ComputerNamePhysicalDnsFullyQualified` of the `COMPUTER_NAME_FORMAT` enumeration
+const COMPUTER_NAME_PHYSICAL_DNS_FULLY_QUALIFIED: u32 = 7;
+
+/// `PROCESS_TERMINATE` access right of `OpenProcess`
+const PROCESS_TERMINATE: u32 = 0x0001;
+
+/// `MEMORYSTATUSEX` of `GlobalMemoryStatusEx`
+#[repr(C)]
+struct MemoryStatusEx {
+    length: u32,
+    memory_load: u32,
+    total_phys: u64,
+    avail_phys: u64,
+    total_page_file: u64,
+    avail_page_file: u64,
+    total_virtual: u64,
+    avail_virtual: u64,
+    avail_extended_virtual: u64,
+}
+
+extern "system" {
+    fn GetComputerNameExW(name_type: u32, buffer: *mut u16, size: *mut u32) -> i32;
+    fn GlobalMemoryStatusEx(buffer: *mut MemoryStatusEx) -> i32;
+    fn GetTickCount64() -> u64;
+    fn GetCurrentProcessId() -> u32;
+    fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> *mut c_void;
+    fn TerminateProcess(process: *mut c_void, exit_code: u32) -> i32;
+    fn CloseHandle(handle: *mut c_void) -> i32;
+    fn GetDiskFreeSpaceExW(directory: *const u16,
+                           free_bytes_available: *mut u64,
+                           total_bytes: *mut u64,
+                           total_free_bytes: *mut u64)
+                           -> i32;
+}
+
+/// Strategies discovering the IPv4 address, in the order they are tried
+pub static IPV4_STRATEGIES: &'static [IpStrategy] = &[IpStrategy {
+                                                          name: "udp-connect",
+                                                          discover: udp_ipv4,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "interfaces",
+                                                          discover: ifaddrs_ipv4,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "ipconfig",
+                                                          discover: ipconfig_ipv4,
+                                                      }];
+
+/// Strategies discovering the IPv6 address, in the order they are tried
+pub static IPV6_STRATEGIES: &'static [IpStrategy] = &[IpStrategy {
+                                                          name: "udp-connect ipv6",
+                                                          discover: udp_ipv6,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "interfaces ipv6",
+                                                          discover: ifaddrs_ipv6,
+                                                      },
+                                                      IpStrategy {
+                                                          name: "ipconfig ipv6",
+                                                          discover: ipconfig_ipv6,
+                                                      }];
+
+/// Returns the DNS host name of this computer, or the output of the `hostname` command if it
+/// can't be read.
+pub fn hostname() -> Result<String> {
+    match computer_name(COMPUTER_NAME_PHYSICAL_DNS_HOSTNAME) {
+        Some(name) => Ok(name),
+        None => {
+            debug!("GetComputerNameExW failed, falling back to the hostname command");
+            hostname_command()
+        }
+    }
+}
+
+/// Windows has no process groups which can be killed at once, so only the process a command
+/// starts is killed by `kill_process_group`.
+pub fn new_process_group(_command: &mut Command) {}
+
+/// Kills a process started by a command given to `new_process_group`.
+pub fn kill_process_group(pid: u32) {
+    unsafe {
+        let process = OpenProcess(PROCESS_TERMINATE, 0, pid);
+        if !process.is_null() {
+            TerminateProcess(process, 1);
+            CloseHandle(process);
+        }
+    }
+}
+
+/// Reverse lookups aren't implemented on Windows yet, so no address has a name.
+pub fn reverse_lookup(_ip: IpAddr) -> Option<String> {
+    None
+}
+
+/// Returns the fully qualified DNS name of this computer. Windows knows it without a lookup.
+pub fn canonical_name(_hostname: &str) -> Option<String> {
+    computer_name(COMPUTER_NAME_PHYSICAL_DNS_FULLY_QUALIFIED)
+}
+
+/// Returns the id of this process and the name of its user. Windows has no user and group ids.
+pub fn process_info() -> ProcessInfo {
+    ProcessInfo {
+        pid: unsafe { GetCurrentProcessId() },
+        ppid: None,
+        uid: None,
+        euid: None,
+        gid: None,
+        user: env::var("USERNAME").unwrap_or_else(|_| "unknown".to_string()),
+        group: None,
+    }
+}
+
+/// Calls `GetComputerNameExW` once to learn the size of the name and again to read it.
+fn computer_name(name_type: u32) -> Option<String> {
+    let mut len: u32 = 0;
+    unsafe {
+        GetComputerNameExW(name_type, ptr::null_mut(), &mut len);
+    }
+    if len == 0 {
+        debug!("GetComputerNameExW failed, err={}", io::Error::last_os_error());
+        return None;
+    }
+    let mut buf = vec![0u16; len as usize];
+    let ok = unsafe { GetComputerNameExW(name_type, buf.as_mut_ptr(), &mut len) };
+    if ok == 0 {
+        debug!("GetComputerNameExW failed, err={}", io::Error::last_os_error());
+        return None;
+    }
+    buf.truncate(len as usize);
+    String::from_utf16(&buf).ok()
+}
+
+/// Reading the routing table isn't implemented on Windows yet, so no gateway is ever reported.
+pub fn default_gateway(_preference: IpPreference) -> Result<Option<Gateway>> {
+    Ok(None)
+}
+
+/// Listing interfaces isn't implemented on Windows yet, so the result is always an error and
+/// IP discovery relies on the UDP probe and `ipconfig`.
+pub fn interfaces() -> Result<Vec<Interface>> {
+    Err(Error::InterfaceEnumeration(io::Error::new(io::ErrorKind::Other,
+                                                   "Listing network interfaces is not supported \
+                                                    on Windows")))
+}
+
+/// Returns the number of logical CPUs, which Windows sets in `NUMBER_OF_PROCESSORS`.
+pub fn cpu_info() -> Result<CpuInfo> {
+    match env::var("NUMBER_OF_PROCESSORS").ok().and_then(|n| n.parse().ok()) {
+        Some(logical) => {
+            Ok(CpuInfo {
+                logical: logical,
+                physical: None,
+                model: None,
+            })
+        }
+        None => Err(Error::CpuInfoFailed("NUMBER_OF_PROCESSORS is not set".to_string())),
+    }
+}
+
+/// Returns the space `GetDiskFreeSpaceExW` reports on the volume holding `path`, which must exist.
+pub fn disk_usage(path: &Path) -> Result<DiskUsage> {
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut available: u64 = 0;
+    let mut total: u64 = 0;
+    let mut free: u64 = 0;
+    if unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, &mut total, &mut free) } == 0 {
+        return Err(Error::DiskUsageFailed(format!("GetDiskFreeSpaceExW failed on {}: {}",
+                                                  path.display(),
+                                                  io::Error::last_os_error())));
+    }
+    Ok(DiskUsage {
+        path: path.to_path_buf(),
+        total_bytes: total,
+        available_bytes: available,
+        used_bytes: total.saturating_sub(free),
+        inodes_total: None,
+        inodes_free: None,
+        fs_type: None,
+    })
+}
+
+pub fn virtualization() -> Result<Virt> {
+    Ok(Virt::None)
+}
+
+pub fn ntp_synchronized() -> Option<bool> {
+    None
+}
+
+pub fn arp_probe(_: Ipv4Addr, _: &str, _: Duration) -> Result<ConflictCheck> {
+    Ok(ConflictCheck::NotSupported("ARP probes are only sent on Linux".to_string()))
+}
+
+pub fn path_mtu_probe(_: &TcpStream, _: Option<u32>, _: Instant) -> Result<PathMtuReport> {
+    Ok(PathMtuReport::NotSupported("path MTU probes are only sent on Unix".to_string()))
+}
+
+pub fn cgroup_limits() -> Result<Option<CgroupLimits>> {
+    Ok(None)
+}
+
+pub fn proc_counts(_: Option<u32>) -> Result<ProcCounts> {
+    Err(Error::SysInfoFailed("Only the process table of Linux is read".to_string()))
+}
+
+pub fn hugepage_info() -> Result<HugepageInfo> {
+    Ok(HugepageInfo {
+        transparent: None,
+        reserved: None,
+        size_kb: None,
+    })
+}
+
+pub fn lsm_status() -> Result<LsmStatus> {
+    Ok(LsmStatus {
+        selinux: SelinuxMode::Disabled,
+        apparmor: false,
+        apparmor_profiles: None,
+    })
+}
+
+pub fn cpu_stat() -> Result<CpuStat> {
+    Err(Error::CpuInfoFailed("Only the CPU counters of Linux are read".to_string()))
+}
+
+pub fn entropy_available() -> Result<u32> {
+    Ok(ENTROPY_UNLIMITED)
+}
+
+pub fn mount_for(_path: &Path) -> Result<MountInfo> {
+    Err(Error::MountInfoFailed("Only the mount table of Linux is read".to_string()))
+}
+
+pub fn filesystems(_include_tmpfs: bool) -> Result<Vec<FsUsage>> {
+    Err(Error::MountInfoFailed("Only the mount table of Linux is read".to_string()))
+}
+
+pub fn neighbors() -> Result<Vec<Neighbor>> {
+    Err(Error::SysInfoFailed("Only the neighbor table of Linux is read".to_string()))
+}
+
+pub fn netns_id() -> Result<String> {
+    Err(Error::SysInfoFailed("Only Linux has network namespaces".to_string()))
+}
+
+pub fn loadavg() -> Result<LoadAvg> {
+    Err(Error::LoadAvgFailed("Windows doesn't have a load average".to_string()))
+}
+
+pub fn rlimits() -> Result<Rlimits> {
+    Err(Error::RlimitsFailed("Windows doesn't have resource limits".to_string()))
+}
+
+/// Returns the time since boot, which `GetTickCount64` counts in milliseconds.
+pub fn uptime() -> Result<Duration> {
+    Ok(Duration::from_millis(unsafe { GetTickCount64() }))
+}
+
+/// Returns the physical memory of this host and how much of it is available.
+pub fn mem_info() -> Result<MemInfo> {
+    let mut status: MemoryStatusEx = unsafe { mem::zeroed() };
+    status.length = mem::size_of::<MemoryStatusEx>() as u32;
+    if unsafe { GlobalMemoryStatusEx(&mut status) } == 0 {
+        return Err(Error::MemInfoFailed(format!("GlobalMemoryStatusEx failed: {}",
+                                                io::Error::last_os_error())));
+    }
+    Ok(MemInfo {
+        total_kb: status.total_phys / 1024,
+        available_kb: Some(status.avail_phys / 1024),
+        free_kb: None,
+        swap_total_kb: None,
+        swap_free_kb: None,
+    })
+}
+
+pub fn uname() -> Result<Uname> {
+    Ok(Uname {
+        sys_name: "Windows".to_string(),
+        node_name: try!(super::hostname()),
+        release: String::new(),
+        version: String::new(),
+        machine: env::var("PROCESSOR_ARCHITECTURE").unwrap_or(String::new()),
+    })
+}
+
+fn ipconfig_ipv4() -> Result<Option<IpAddr>> {
+    ipconfig(&Host, IpPreference::Ipv4First)
+}
+
+fn ipconfig_ipv6() -> Result<Option<IpAddr>> {
+    ipconfig(&Host, IpPreference::Ipv6First)
+}
+
+fn ipconfig(runner: &CommandRunner, family: IpPreference) -> Result<Option<IpAddr>> {
+    let output = try!(runner.run("ipconfig", &[]));
+    Ok(parse_ipconfig(&output, family))
+}
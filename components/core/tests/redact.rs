@@ -0,0 +1,69 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Redacting a corpus of lines with planted secrets, see `tests/fixtures/redact-corpus`.
+
+extern crate habitat_core;
+extern crate url;
+
+use habitat_core::util::redact::{redact_text, redact_url};
+use url::Url;
+
+const CORPUS: &'static str = include_str!("fixtures/redact-corpus");
+
+/// Returns the planted values and the line of every entry of the corpus.
+fn corpus() -> Vec<(Vec<&'static str>, &'static str)> {
+    CORPUS.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut fields = line.splitn(2, '\t');
+            let planted = fields.next().unwrap().split(',').collect();
+            let text = fields.next().expect("corpus lines are tab separated");
+            (planted, text)
+        })
+        .collect()
+}
+
+#[test]
+fn no_planted_secret_survives() {
+    let entries = corpus();
+    assert!(entries.len() > 20);
+    let planted: Vec<&str> = entries.iter()
+        .flat_map(|&(ref planted, _)| planted.iter().cloned())
+        .collect();
+    for &(_, text) in entries.iter() {
+        let mut redacted = vec![redact_text(text).into_owned()];
+        if text.starts_with("http") {
+            redacted.push(redact_url(&Url::parse(text).unwrap()));
+        }
+        for redacted in redacted {
+            for secret in planted.iter() {
+                assert!(!redacted.contains(secret),
+                        "{:?} is left in {:?}, redacted from {:?}",
+                        secret,
+                        redacted,
+                        text);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_line_is_redacted() {
+    for &(_, text) in corpus().iter() {
+        assert!(redact_text(text).contains("REDACTED"), "nothing was redacted from {:?}", text);
+    }
+    assert_eq!(redact_text("GitHub API request, route=primary, auth=token"),
+               "GitHub API request, route=primary, auth=token");
+}
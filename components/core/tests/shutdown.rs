@@ -0,0 +1,60 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Starting the background threads of `sys` and stopping them together.
+
+extern crate habitat_core;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use habitat_core::util::shutdown::Shutdown;
+use habitat_core::util::sys::{self, MetricsSampler};
+
+/// Upper bound on stopping every thread, far above what it takes
+const STOP_TIMEOUT_SECS: u64 = 10;
+
+#[test]
+fn watcher_and_sampler_stop_together() {
+    let shutdown = Shutdown::new();
+    let watch = sys::watch_with(Duration::from_secs(600), &shutdown).unwrap();
+    let sampler = MetricsSampler::start(Duration::from_millis(10), 1_000, &shutdown).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(STOP_TIMEOUT_SECS);
+    while sampler.history().len() < 2 {
+        assert!(Instant::now() < deadline, "no samples were taken");
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    let started = Instant::now();
+    shutdown.shutdown();
+    assert!(shutdown.join(Duration::from_secs(STOP_TIMEOUT_SECS)));
+    assert!(started.elapsed() < Duration::from_secs(STOP_TIMEOUT_SECS));
+    assert!(watch.recv().is_err());
+
+    // Nothing is sampled once the sampler stopped
+    let taken = sampler.history().len();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(sampler.history().len(), taken);
+}
+
+#[test]
+fn dropping_a_watch_leaves_a_shared_shutdown_running() {
+    let shutdown = Shutdown::new();
+    drop(sys::watch_with(Duration::from_secs(600), &shutdown).unwrap());
+    assert!(!shutdown.is_shutdown());
+    assert!(!shutdown.join(Duration::from_millis(10)));
+    shutdown.shutdown();
+    assert!(shutdown.join(Duration::from_secs(STOP_TIMEOUT_SECS)));
+}
@@ -0,0 +1,22 @@
+#!/bin/sh
+#
+# Copies the recorded fixtures listed in seeds/<target> into the corpus cargo-fuzz starts the
+# target from, for every target or the ones given.
+#
+# Usage: ./seed-corpus.sh [TARGET ...]
+
+set -eu
+
+cd "$(dirname "$0")"
+
+if [ $# -eq 0 ]; then
+  set -- $(ls seeds)
+fi
+
+for target in "$@"; do
+  mkdir -p "corpus/$target"
+  grep -v -e '^#' -e '^$' "seeds/$target" | while read -r fixture; do
+    # Fixtures of different directories may share a name
+    cp "../$fixture" "corpus/$target/$(echo "$fixture" | tr '/' '_')"
+  done
+done
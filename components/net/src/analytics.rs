@@ -0,0 +1,91 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pseudonymous identifiers for product analytics.
+//!
+//! Analytics events identify a user by an HMAC-SHA256 of their GitHub numeric id keyed with a
+//! per-deployment salt. The same user always maps to the same identifier within a deployment,
+//! while the identifier can't be linked back to the user without the salt. Changing the salt
+//! changes every identifier. Logins and email addresses never enter the analytics path.
+
+use openssl::crypto::hash::Type;
+use openssl::crypto::hmac::hmac;
+use protocol::sessionsrv;
+use rustc_serialize::hex::ToHex;
+
+/// Log target analytics events are written to
+pub const ANALYTICS_TARGET: &'static str = "analytics";
+
+/// Returns the pseudonymous analytics identifier of the user with the given GitHub id.
+pub fn analytics_id(salt: &str, extern_id: u64) -> String {
+    hmac(Type::SHA256, salt.as_bytes(), extern_id.to_string().as_bytes()).to_hex()
+}
+
+pub trait AnalyticsId {
+    /// Returns the pseudonymous analytics identifier of the account, or `None` if the account
+    /// doesn't carry its GitHub id.
+    fn analytics_id(&self, salt: &str) -> Option<String>;
+}
+
+impl AnalyticsId for sessionsrv::Account {
+    fn analytics_id(&self, salt: &str) -> Option<String> {
+        if self.has_extern_id() {
+            Some(analytics_id(salt, self.get_extern_id()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Record a login of the user with the given analytics identifier in the analytics log.
+pub fn login_event(id: &str) {
+    info!(target: ANALYTICS_TARGET, "event=login, id={}", id);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use protocol::sessionsrv;
+
+    use super::*;
+
+    #[test]
+    fn analytics_id_is_deterministic() {
+        assert_eq!(analytics_id("salt", 583231), analytics_id("salt", 583231));
+        assert_eq!(analytics_id("salt", 583231).len(), 64);
+    }
+
+    #[test]
+    fn analytics_id_depends_on_salt() {
+        assert!(analytics_id("salt", 583231) != analytics_id("pepper", 583231));
+    }
+
+    #[test]
+    fn analytics_ids_do_not_collide() {
+        let ids: HashSet<String> = (1..5_001).map(|id| analytics_id("salt", id)).collect();
+        assert_eq!(ids.len(), 5_000);
+    }
+
+    #[test]
+    fn account_analytics_id_does_not_use_login() {
+        let mut account = sessionsrv::Account::new();
+        account.set_name("octocat".to_string());
+        assert_eq!(account.analytics_id("salt"), None);
+        account.set_extern_id(583231);
+        let id = account.analytics_id("salt").unwrap();
+        assert_eq!(id, analytics_id("salt", 583231));
+        assert!(!id.contains("octocat"));
+    }
+}
@@ -12,9 +12,51 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::hash::Hasher;
 use std::net;
+use std::sync::Mutex;
 
+use fnv::FnvHasher;
+use hcore::config::ParseInto;
+use hcore::env;
 use num_cpus;
+use toml;
+use url::Url;
+
+use error::{Error, Result};
+
+/// URL to GitHub API endpoint
+pub const GITHUB_URL: &'static str = "https://api.github.com";
+/// URL to GitHub web endpoint
+pub const GITHUB_WEB_URL: &'static str = "https://github.com";
+/// OAuth scopes requested from GitHub unless configured otherwise
+pub const GITHUB_DEFAULT_SCOPES: &'static [&'static str] = &["user:email"];
+/// OAuth scope letting a token read the organization and team memberships of its owner
+pub const GITHUB_ORG_SCOPE: &'static str = "read:org";
+/// Host serving the public GitHub web frontend
+pub const GITHUB_HOST: &'static str = "github.com";
+/// Host serving the public GitHub API
+pub const GITHUB_API_HOST: &'static str = "api.github.com";
+/// Path prefix of the API on a GitHub Enterprise appliance
+pub const GITHUB_ENTERPRISE_API_PATH: &'static str = "/api/v3";
+/// Names of the GitHub profiles which can be selected with the `profile` key
+pub const GITHUB_PROFILES: &'static [&'static str] = &["github.com", "enterprise"];
+/// Name of the GitHub OAuth application used when a request doesn't ask for a specific one
+pub const DEFAULT_GITHUB_APP: &'static str = "default";
+/// Keys of a GitHub configuration table which have been renamed, as `(old, new)` pairs
+pub const GITHUB_RENAMED_KEYS: &'static [(&'static str, &'static str)] = &[("url", "api_url")];
+/// URL to the public GitLab instance
+pub const GITLAB_URL: &'static str = "https://gitlab.com";
+/// Names of the OAuth providers which can be selected with the `provider` key of the
+/// authentication section
+pub const OAUTH_PROVIDERS: &'static [&'static str] = &["github", "gitlab", "none"];
+
+lazy_static! {
+    /// Deprecated keys which a warning has already been logged for
+    static ref WARNED_KEYS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
 
 pub trait DispatcherCfg {
     fn default_worker_count() -> usize {
@@ -28,9 +70,1201 @@ pub trait DispatcherCfg {
 }
 
 pub trait GitHubOAuth {
-    fn github_url(&self) -> &str;
+    fn github_api_url(&self) -> &str;
     fn github_client_id(&self) -> &str;
     fn github_client_secret(&self) -> &str;
+
+    /// Path to a file containing the client secret. When present the contents of the file are
+    /// used instead of `github_client_secret()`.
+    fn github_client_secret_file(&self) -> Option<&str> {
+        None
+    }
+
+    /// URL to the GitHub web frontend which hosts the OAuth endpoints.
+    fn github_web_url(&self) -> &str {
+        GITHUB_WEB_URL
+    }
+
+    /// OAuth scopes which must be granted to an access token.
+    fn github_scopes(&self) -> Vec<String> {
+        GITHUB_DEFAULT_SCOPES.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Organization whose members, or whose `github_admin_team` members, are administrators.
+    fn github_admin_org(&self) -> Option<&str> {
+        None
+    }
+
+    /// Team within `github_admin_org` whose members are administrators.
+    fn github_admin_team(&self) -> Option<&str> {
+        None
+    }
+
+    /// Team within `github_admin_org` whose members may run builds.
+    fn github_builder_team(&self) -> Option<&str> {
+        None
+    }
+
+    /// Team within `github_admin_org` whose members may create origins.
+    fn github_origin_creator_team(&self) -> Option<&str> {
+        None
+    }
+
+    /// Organization a user must be a member of to log in.
+    fn github_required_org(&self) -> Option<&str> {
+        None
+    }
+
+    /// Logins, compared case-insensitively, of users who are operators.
+    fn github_admin_logins(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// GitHub ids of users who are operators.
+    fn github_admin_ids(&self) -> Vec<u64> {
+        vec![]
+    }
+
+    /// Make GitHub Enterprise site administrators operators.
+    fn github_honor_site_admin(&self) -> bool {
+        false
+    }
+
+    /// Logins, compared case-insensitively, of users who may log in. See `AccessPolicy`.
+    fn github_allow_logins(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Logins, compared case-insensitively, of users who may never log in.
+    fn github_deny_logins(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Organizations whose members may log in.
+    fn github_allow_orgs(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Only let members of `github_allow_orgs` log in.
+    fn github_require_org_membership(&self) -> bool {
+        false
+    }
+
+    /// Access token, granted `read:org`, used to list the members of the teams of
+    /// `github_admin_org`, which are cached across sessions, and to check at startup that the
+    /// configured teams exist. Without one the teams of each user are looked up as they log in.
+    fn github_service_token(&self) -> Option<&str> {
+        None
+    }
+
+    /// Proxy, TLS, and timeout settings for requests to GitHub.
+    fn github_http(&self) -> HttpCfg {
+        HttpCfg::from_env()
+    }
+}
+
+/// Configuration for additional, named GitHub OAuth applications. Each application is used by a
+/// different frontend since the callback URL of an OAuth application is fixed.
+pub trait GitHubApps: GitHubOAuth {
+    fn github_apps(&self) -> &BTreeMap<String, GitHubCfg>;
+
+    /// Client options of the default application.
+    fn github_client_options(&self) -> &GitHubClientOptions;
+}
+
+/// Configuration of the OAuth provider users log in with, whichever it is. Only what every
+/// provider has in common is described here; the GitHub specific settings stay in `GitHubOAuth`.
+pub trait OAuth {
+    /// Name of the provider, one of `OAUTH_PROVIDERS`.
+    fn oauth_provider(&self) -> &str {
+        "github"
+    }
+
+    /// URL to the provider: the API endpoint of GitHub, or the root of a GitLab instance, which
+    /// serves both its OAuth endpoints and its API.
+    fn oauth_url(&self) -> &str;
+    fn oauth_client_id(&self) -> &str;
+    fn oauth_client_secret(&self) -> &str;
+
+    /// Callback URL the OAuth application was registered with. GitLab only exchanges a code when
+    /// it is given the same URL the code was issued for, GitHub doesn't need it.
+    fn oauth_redirect_uri(&self) -> Option<&str> {
+        None
+    }
+
+    /// Proxy, TLS, and timeout settings for requests to the provider.
+    fn oauth_http(&self) -> HttpCfg {
+        HttpCfg::from_env()
+    }
+}
+
+/// Configuration for a single GitHub OAuth application
+#[derive(Clone, PartialEq, Eq)]
+pub struct GitHubCfg {
+    /// Name of the defaults profile this configuration was built from
+    pub profile: Option<String>,
+    /// URL to GitHub API
+    pub api_url: String,
+    /// URL to GitHub web frontend
+    pub web_url: String,
+    /// OAuth scopes which must be granted to an access token
+    pub scopes: Vec<String>,
+    /// Allow the API and web URLs to use plain HTTP
+    pub allow_insecure_github_url: bool,
+    /// Organization whose members, or whose `admin_team` members, are administrators
+    pub admin_org: Option<String>,
+    /// Team within `admin_org` whose members are administrators
+    pub admin_team: Option<String>,
+    /// Team within `admin_org` whose members may run builds
+    pub builder_team: Option<String>,
+    /// Team within `admin_org` whose members may create origins
+    pub origin_creator_team: Option<String>,
+    /// Organization a user must be a member of to log in
+    pub required_org: Option<String>,
+    /// Logins, compared case-insensitively, of users who are operators
+    pub admin_logins: Vec<String>,
+    /// GitHub ids of users who are operators
+    pub admin_ids: Vec<u64>,
+    /// Make GitHub Enterprise site administrators operators
+    pub honor_site_admin: bool,
+    /// Logins, compared case-insensitively, of users who may log in
+    pub allow_logins: Vec<String>,
+    /// Logins, compared case-insensitively, of users who may never log in
+    pub deny_logins: Vec<String>,
+    /// Organizations whose members may log in
+    pub allow_orgs: Vec<String>,
+    /// Only let members of `allow_orgs` log in
+    pub require_org_membership: bool,
+    /// Client identifier used for GitHub API requests
+    pub client_id: String,
+    /// Client secret used for GitHub API requests
+    pub client_secret: String,
+    /// Path to a file containing the client secret
+    pub client_secret_file: Option<String>,
+    /// Access token used to list the members of the teams of `admin_org`
+    pub service_token: Option<String>,
+    /// Retry, timeout, and caching behaviour of the client
+    pub options: GitHubClientOptions,
+    /// Proxy, TLS, and timeout settings of outbound requests
+    pub http: HttpCfg,
+}
+
+impl GitHubCfg {
+    /// Returns the baked-in defaults for the named profile.
+    ///
+    /// The `github.com` profile points at the public GitHub service. The `enterprise` profile
+    /// leaves the web URL to be configured and derives the API URL from it.
+    ///
+    /// # Errors
+    ///
+    /// * No profile exists with the given name
+    pub fn profile(name: &str) -> Result<Self> {
+        let mut cfg = GitHubCfg::default();
+        match name {
+            "github.com" => (),
+            "enterprise" => {
+                cfg.api_url = String::new();
+                cfg.web_url = String::new();
+            }
+            _ => return Err(Error::UnknownGitHubProfile(name.to_string())),
+        }
+        cfg.profile = Some(name.to_string());
+        Ok(cfg)
+    }
+
+    /// Parse where a GitHub application is found from the given TOML table: its `profile`, URLs,
+    /// and scopes. The credentials and access settings are left at their defaults, so the table
+    /// of an application configured key by key, such as the default one of a service, is resolved
+    /// like a named application's.
+    pub fn endpoints_from_toml(toml: &toml::Value) -> Result<Self> {
+        let toml = &try!(migrate_keys(toml, "", GITHUB_RENAMED_KEYS));
+        let mut profile: Option<String> = None;
+        try!(toml.parse_into("profile", &mut profile));
+        let mut cfg = match profile {
+            Some(ref name) => try!(GitHubCfg::profile(name)),
+            None => GitHubCfg::default(),
+        };
+        try!(toml.parse_into("web_url", &mut cfg.web_url));
+        if cfg.web_url.is_empty() {
+            return Err(Error::RequiredConfigField("github.web_url"));
+        }
+        try!(toml.parse_into("allow_insecure_github_url",
+                             &mut cfg.allow_insecure_github_url));
+        cfg.web_url = try!(normalize_web_url(&cfg.web_url, cfg.allow_insecure_github_url));
+        if !try!(toml.parse_into("api_url", &mut cfg.api_url)) && cfg.api_url.is_empty() {
+            cfg.api_url = format!("{}{}", cfg.web_url, GITHUB_ENTERPRISE_API_PATH);
+        }
+        cfg.api_url = try!(normalize_api_url(&cfg.api_url, cfg.allow_insecure_github_url));
+        if let Some(scopes) = toml.lookup("scopes") {
+            cfg.scopes = try!(parse_str_array(scopes, "github.scopes"));
+        }
+        Ok(cfg)
+    }
+
+    /// Parse a GitHub application from the given TOML table, for example the value found at
+    /// `cfg.github.apps.<name>`. If the table selects a `profile` its defaults are applied first
+    /// and any explicit keys override them.
+    pub fn from_toml(toml: &toml::Value) -> Result<Self> {
+        let toml = &try!(migrate_keys(toml, "", GITHUB_RENAMED_KEYS));
+        let mut cfg = try!(GitHubCfg::endpoints_from_toml(toml));
+        if !try!(toml.parse_into("client_id", &mut cfg.client_id)) {
+            return Err(Error::RequiredConfigField("github.client_id"));
+        }
+        try!(toml.parse_into("client_secret_file", &mut cfg.client_secret_file));
+        if !try!(toml.parse_into("client_secret", &mut cfg.client_secret)) &&
+           cfg.client_secret_file.is_none() {
+            return Err(Error::RequiredConfigField("github.client_secret"));
+        }
+        try!(toml.parse_into("admin_org", &mut cfg.admin_org));
+        try!(toml.parse_into("admin_team", &mut cfg.admin_team));
+        try!(toml.parse_into("builder_team", &mut cfg.builder_team));
+        try!(toml.parse_into("origin_creator_team", &mut cfg.origin_creator_team));
+        try!(toml.parse_into("required_org", &mut cfg.required_org));
+        if let Some(logins) = toml.lookup("admin_logins") {
+            cfg.admin_logins = try!(parse_str_array(logins, "github.admin_logins"));
+        }
+        try!(toml.parse_into("admin_ids", &mut cfg.admin_ids));
+        try!(toml.parse_into("honor_site_admin", &mut cfg.honor_site_admin));
+        if let Some(logins) = toml.lookup("allow_logins") {
+            cfg.allow_logins = try!(parse_str_array(logins, "github.allow_logins"));
+        }
+        if let Some(logins) = toml.lookup("deny_logins") {
+            cfg.deny_logins = try!(parse_str_array(logins, "github.deny_logins"));
+        }
+        if let Some(orgs) = toml.lookup("allow_orgs") {
+            cfg.allow_orgs = try!(parse_str_array(orgs, "github.allow_orgs"));
+        }
+        try!(toml.parse_into("require_org_membership", &mut cfg.require_org_membership));
+        try!(toml.parse_into("service_token", &mut cfg.service_token));
+        cfg.options = try!(GitHubClientOptions::from_toml(toml));
+        if let Some(http) = toml.lookup("http") {
+            cfg.http = try!(HttpCfg::from_toml(http));
+        }
+        Ok(cfg)
+    }
+
+    /// Parse every named GitHub application found in the given TOML table, for example the value
+    /// found at `cfg.github.apps`.
+    pub fn apps_from_toml(toml: &toml::Value) -> Result<BTreeMap<String, GitHubCfg>> {
+        let mut apps = BTreeMap::new();
+        match toml.as_table() {
+            Some(table) => {
+                for (name, value) in table.iter() {
+                    let app = try!(GitHubCfg::from_toml(value));
+                    apps.insert(name.clone(), app);
+                }
+                Ok(apps)
+            }
+            None => Err(Error::RequiredConfigField("github.apps")),
+        }
+    }
+
+    /// Returns a short, stable fingerprint of this configuration. See `github_fingerprint()`.
+    pub fn fingerprint(&self) -> String {
+        github_fingerprint(self, &self.client_secret)
+    }
+
+    /// Returns the effective configuration, with every profile default and override applied, in a
+    /// form suitable for logging. Secrets are redacted.
+    pub fn resolved(&self) -> String {
+        format!("profile={} api_url={} web_url={} client_id={} client_secret=<redacted> \
+                 client_secret_file={} scopes={}",
+                self.profile.as_ref().map(|p| p.as_str()).unwrap_or("none"),
+                self.api_url,
+                self.web_url,
+                self.client_id,
+                self.client_secret_file.as_ref().map(|f| f.as_str()).unwrap_or("none"),
+                self.scopes.join(","))
+    }
+}
+
+impl Default for GitHubCfg {
+    fn default() -> Self {
+        GitHubCfg {
+            profile: None,
+            api_url: GITHUB_URL.to_string(),
+            web_url: GITHUB_WEB_URL.to_string(),
+            scopes: GITHUB_DEFAULT_SCOPES.iter().map(|s| s.to_string()).collect(),
+            allow_insecure_github_url: false,
+            admin_org: None,
+            admin_team: None,
+            builder_team: None,
+            origin_creator_team: None,
+            required_org: None,
+            admin_logins: vec![],
+            admin_ids: vec![],
+            honor_site_admin: false,
+            allow_logins: vec![],
+            deny_logins: vec![],
+            allow_orgs: vec![],
+            require_org_membership: false,
+            client_id: String::new(),
+            client_secret: String::new(),
+            client_secret_file: None,
+            service_token: None,
+            options: GitHubClientOptions::default(),
+            http: HttpCfg::default(),
+        }
+    }
+}
+
+impl fmt::Debug for GitHubCfg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GitHubCfg")
+            .field("profile", &self.profile)
+            .field("api_url", &self.api_url)
+            .field("web_url", &self.web_url)
+            .field("scopes", &self.scopes)
+            .field("allow_insecure_github_url", &self.allow_insecure_github_url)
+            .field("admin_org", &self.admin_org)
+            .field("admin_team", &self.admin_team)
+            .field("builder_team", &self.builder_team)
+            .field("origin_creator_team", &self.origin_creator_team)
+            .field("required_org", &self.required_org)
+            .field("admin_logins", &self.admin_logins)
+            .field("admin_ids", &self.admin_ids)
+            .field("honor_site_admin", &self.honor_site_admin)
+            .field("allow_logins", &self.allow_logins)
+            .field("deny_logins", &self.deny_logins)
+            .field("allow_orgs", &self.allow_orgs)
+            .field("require_org_membership", &self.require_org_membership)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"<redacted>")
+            .field("client_secret_file", &self.client_secret_file)
+            .field("service_token", &self.service_token.as_ref().map(|_| "<redacted>"))
+            .field("options", &self.options)
+            .field("http", &self.http)
+            .finish()
+    }
+}
+
+impl GitHubOAuth for GitHubCfg {
+    fn github_api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    fn github_client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn github_client_secret(&self) -> &str {
+        &self.client_secret
+    }
+
+    fn github_client_secret_file(&self) -> Option<&str> {
+        self.client_secret_file.as_ref().map(|f| f.as_str())
+    }
+
+    fn github_web_url(&self) -> &str {
+        &self.web_url
+    }
+
+    fn github_scopes(&self) -> Vec<String> {
+        self.scopes.clone()
+    }
+
+    fn github_admin_org(&self) -> Option<&str> {
+        self.admin_org.as_ref().map(|o| o.as_str())
+    }
+
+    fn github_admin_team(&self) -> Option<&str> {
+        self.admin_team.as_ref().map(|t| t.as_str())
+    }
+
+    fn github_builder_team(&self) -> Option<&str> {
+        self.builder_team.as_ref().map(|t| t.as_str())
+    }
+
+    fn github_origin_creator_team(&self) -> Option<&str> {
+        self.origin_creator_team.as_ref().map(|t| t.as_str())
+    }
+
+    fn github_required_org(&self) -> Option<&str> {
+        self.required_org.as_ref().map(|o| o.as_str())
+    }
+
+    fn github_admin_logins(&self) -> Vec<String> {
+        self.admin_logins.clone()
+    }
+
+    fn github_admin_ids(&self) -> Vec<u64> {
+        self.admin_ids.clone()
+    }
+
+    fn github_honor_site_admin(&self) -> bool {
+        self.honor_site_admin
+    }
+
+    fn github_allow_logins(&self) -> Vec<String> {
+        self.allow_logins.clone()
+    }
+
+    fn github_deny_logins(&self) -> Vec<String> {
+        self.deny_logins.clone()
+    }
+
+    fn github_allow_orgs(&self) -> Vec<String> {
+        self.allow_orgs.clone()
+    }
+
+    fn github_require_org_membership(&self) -> bool {
+        self.require_org_membership
+    }
+
+    fn github_service_token(&self) -> Option<&str> {
+        self.service_token.as_ref().map(|t| t.as_str())
+    }
+
+    fn github_http(&self) -> HttpCfg {
+        self.http.clone()
+    }
+}
+
+impl OAuth for GitHubCfg {
+    fn oauth_url(&self) -> &str {
+        &self.api_url
+    }
+
+    fn oauth_client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn oauth_client_secret(&self) -> &str {
+        &self.client_secret
+    }
+
+    fn oauth_http(&self) -> HttpCfg {
+        self.http.clone()
+    }
+}
+
+/// Returns the OAuth scopes to request of GitHub and to require of access tokens: the configured
+/// scopes, plus `read:org` if logins or privileges depend on the organizations or teams of users.
+pub fn github_required_scopes<T: GitHubOAuth + ?Sized>(config: &T) -> Vec<String> {
+    let mut scopes = config.github_scopes();
+    let org_gated = config.github_required_org().is_some() ||
+                    config.github_admin_org().is_some() ||
+                    !config.github_allow_orgs().is_empty();
+    if org_gated && !scopes.iter().any(|scope| scope == GITHUB_ORG_SCOPE) {
+        scopes.push(GITHUB_ORG_SCOPE.to_string());
+    }
+    scopes
+}
+
+/// Returns a short fingerprint identifying the given GitHub configuration and secret.
+///
+/// The fingerprint covers every non-secret field identifying the application plus a hash of the
+/// secret, so rotating the secret changes the fingerprint without revealing it. The hash is
+/// 64-bit FNV-1a over the raw bytes of each field, which is the same on every platform.
+pub fn github_fingerprint<T: GitHubOAuth + ?Sized>(config: &T, secret: &str) -> String {
+    let mut secret_hasher = FnvHasher::default();
+    secret_hasher.write(secret.as_bytes());
+    let secret_hash = format!("{:016x}", secret_hasher.finish());
+    let scopes = config.github_scopes().join(",");
+    let fields = [config.github_api_url(),
+                  config.github_web_url(),
+                  config.github_client_id(),
+                  config.github_client_secret_file().unwrap_or(""),
+                  &scopes,
+                  config.github_admin_org().unwrap_or(""),
+                  config.github_admin_team().unwrap_or(""),
+                  config.github_required_org().unwrap_or("")];
+    let mut hasher = FnvHasher::default();
+    for field in fields.iter() {
+        hasher.write(field.as_bytes());
+        hasher.write(&[0]);
+    }
+    hasher.write(secret_hash.as_bytes());
+    format!("{:016x}", hasher.finish())[..12].to_string()
+}
+
+/// Normalize the URL to a GitHub API endpoint.
+///
+/// Trailing slashes are removed so that joining a request path never yields a double slash. A
+/// GitHub Enterprise URL which is missing the API prefix, for example `https://github.mycorp.com`,
+/// has `/api/v3` appended to it.
+///
+/// # Errors
+///
+/// * The URL can't be parsed or has no host
+/// * The URL uses plain HTTP and `allow_insecure` is not set
+/// * The URL points at the GitHub web frontend instead of the API
+/// * The URL has a path which isn't the GitHub Enterprise API prefix
+pub fn normalize_api_url(url: &str, allow_insecure: bool) -> Result<String> {
+    let url = try!(normalize_web_url(url, allow_insecure));
+    let parsed = Url::parse(&url).unwrap();
+    let host = parsed.host_str().unwrap_or("").to_string();
+    let path = parsed.path().trim_right_matches('/').to_string();
+    if host == GITHUB_API_HOST {
+        if !path.is_empty() {
+            return Err(Error::InvalidGitHubUrl(url,
+                                               format!("the GitHub API is served from the root \
+                                                        of {}",
+                                                       GITHUB_API_HOST)));
+        }
+        return Ok(url);
+    }
+    if host == "github.com" || host == "www.github.com" {
+        return Err(Error::InvalidGitHubUrl(url,
+                                           format!("this is the GitHub web frontend, use \
+                                                    https://{} instead",
+                                                   GITHUB_API_HOST)));
+    }
+    match path.as_str() {
+        "" => {
+            let normalized = format!("{}{}", url, GITHUB_ENTERPRISE_API_PATH);
+            warn!("GitHub Enterprise URL {} is missing the API prefix, using {}",
+                  url,
+                  normalized);
+            Ok(normalized)
+        }
+        GITHUB_ENTERPRISE_API_PATH => Ok(url),
+        _ => {
+            Err(Error::InvalidGitHubUrl(url,
+                                        format!("expected the GitHub Enterprise API prefix {}, \
+                                                 found {}",
+                                                GITHUB_ENTERPRISE_API_PATH,
+                                                path)))
+        }
+    }
+}
+
+/// Normalize the URL to a GitHub web frontend by removing trailing slashes.
+///
+/// # Errors
+///
+/// * The URL can't be parsed or has no host
+/// * The URL uses plain HTTP and `allow_insecure` is not set
+pub fn normalize_web_url(url: &str, allow_insecure: bool) -> Result<String> {
+    let parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(e) => return Err(Error::InvalidGitHubUrl(url.to_string(), format!("{}", e))),
+    };
+    match parsed.scheme() {
+        "https" => (),
+        "http" if allow_insecure => (),
+        "http" => {
+            return Err(Error::InvalidGitHubUrl(url.to_string(),
+                                               "plain HTTP requires \
+                                                allow_insecure_github_url = true"
+                                                   .to_string()))
+        }
+        scheme => {
+            return Err(Error::InvalidGitHubUrl(url.to_string(),
+                                               format!("unsupported scheme {}", scheme)))
+        }
+    }
+    if parsed.host_str().map(|h| h.is_empty()).unwrap_or(true) {
+        return Err(Error::InvalidGitHubUrl(url.to_string(), "missing host".to_string()));
+    }
+    Ok(url.trim_right_matches('/').to_string())
+}
+
+/// Returns a copy of the given TOML with renamed keys of the table at `path` moved to their new
+/// names, for example `migrate_keys(&toml, "cfg.github", GITHUB_RENAMED_KEYS)`. An empty path
+/// migrates the top level table.
+///
+/// A deprecation warning naming the replacement is logged the first time each old key is seen.
+///
+/// # Errors
+///
+/// * Both the old and the new key are set to different values
+pub fn migrate_keys(toml: &toml::Value,
+                    path: &str,
+                    renames: &[(&'static str, &'static str)])
+                    -> Result<toml::Value> {
+    let mut migrated = toml.clone();
+    {
+        let parts: Vec<&str> = path.split('.').filter(|p| !p.is_empty()).collect();
+        if let toml::Value::Table(ref mut table) = migrated {
+            try!(migrate_table(table, &parts, path, renames));
+        }
+    }
+    Ok(migrated)
+}
+
+fn migrate_table(table: &mut toml::Table,
+                 parts: &[&str],
+                 path: &str,
+                 renames: &[(&'static str, &'static str)])
+                 -> Result<()> {
+    if let Some((first, rest)) = parts.split_first() {
+        return match table.get_mut(*first) {
+            Some(&mut toml::Value::Table(ref mut child)) => {
+                migrate_table(child, rest, path, renames)
+            }
+            _ => Ok(()),
+        };
+    }
+    for &(old, new) in renames {
+        let (old_key, new_key) = if path.is_empty() {
+            (old.to_string(), new.to_string())
+        } else {
+            (format!("{}.{}", path, old), format!("{}.{}", path, new))
+        };
+        let value = match table.remove(old) {
+            Some(value) => value,
+            None => continue,
+        };
+        if let Some(current) = table.get(new) {
+            if *current != value {
+                return Err(Error::ConflictingConfigKeys(old_key, new_key));
+            }
+        }
+        if WARNED_KEYS.lock().unwrap().insert(old_key.clone()) {
+            warn!("Configuration key '{}' is deprecated and will be removed in a future \
+                   release, use '{}' instead",
+                  old_key,
+                  new_key);
+        }
+        table.insert(new.to_string(), value);
+    }
+    Ok(())
+}
+
+fn parse_str_array(toml: &toml::Value, field: &'static str) -> Result<Vec<String>> {
+    let mut values = vec![];
+    match toml.as_slice() {
+        Some(slice) => {
+            for entry in slice.iter() {
+                match entry.as_str() {
+                    Some(value) => values.push(value.to_string()),
+                    None => return Err(Error::RequiredConfigField(field)),
+                }
+            }
+            Ok(values)
+        }
+        None => Err(Error::RequiredConfigField(field)),
+    }
+}
+
+/// Returns the names and values of a table of strings, ordered by name.
+fn parse_str_table(toml: &toml::Value, field: &'static str) -> Result<Vec<(String, String)>> {
+    let mut pairs = vec![];
+    match toml.as_table() {
+        Some(table) => {
+            for (name, value) in table.iter() {
+                match value.as_str() {
+                    Some(value) => pairs.push((name.clone(), value.to_string())),
+                    None => return Err(Error::RequiredConfigField(field)),
+                }
+            }
+            Ok(pairs)
+        }
+        None => Err(Error::RequiredConfigField(field)),
+    }
+}
+
+/// Configuration for the stub identity provider used by air-gapped installations which can't reach
+/// an OAuth provider. Every login is authenticated as the single account described here.
+#[derive(Clone, PartialEq, Eq)]
+pub struct StubAuthCfg {
+    /// Secret which must be presented as the OAuth code to log in
+    pub shared_secret: String,
+    /// Access token handed out to a successful login
+    pub token: String,
+    /// Login of the synthesized account
+    pub login: String,
+    /// Email address of the synthesized account
+    pub email: String,
+    /// Numeric identifier of the synthesized account
+    pub id: u64,
+}
+
+impl StubAuthCfg {
+    /// Parse the authentication section, for example the value found at `cfg.auth`. Returns `None`
+    /// unless the section selects `provider = "none"`.
+    ///
+    /// # Errors
+    ///
+    /// * The provider is unknown
+    /// * The stub provider was selected without also setting `insecure_stub_auth = true`
+    /// * The shared secret or token are missing
+    pub fn from_toml(toml: &toml::Value) -> Result<Option<Self>> {
+        if try!(oauth_provider(toml)) != "none" {
+            return Ok(None);
+        }
+        let mut insecure = false;
+        try!(toml.parse_into("insecure_stub_auth", &mut insecure));
+        if !insecure {
+            return Err(Error::RequiredConfigField("auth.insecure_stub_auth"));
+        }
+        let mut cfg = StubAuthCfg {
+            shared_secret: String::new(),
+            token: String::new(),
+            login: "admin".to_string(),
+            email: String::new(),
+            id: 1,
+        };
+        if !try!(toml.parse_into("shared_secret", &mut cfg.shared_secret)) ||
+           cfg.shared_secret.is_empty() {
+            return Err(Error::RequiredConfigField("auth.shared_secret"));
+        }
+        if !try!(toml.parse_into("token", &mut cfg.token)) || cfg.token.is_empty() {
+            return Err(Error::RequiredConfigField("auth.token"));
+        }
+        try!(toml.parse_into("login", &mut cfg.login));
+        try!(toml.parse_into("email", &mut cfg.email));
+        try!(toml.parse_into("id", &mut cfg.id));
+        Ok(Some(cfg))
+    }
+}
+
+/// Configuration for logging in with an OAuth application of a GitLab instance instead of GitHub.
+#[derive(Clone, PartialEq, Eq)]
+pub struct GitLabCfg {
+    /// Root URL of the GitLab instance
+    pub url: String,
+    /// Application id of the OAuth application
+    pub client_id: String,
+    /// Secret of the OAuth application
+    pub client_secret: String,
+    /// Callback URL the OAuth application was registered with
+    pub redirect_uri: String,
+}
+
+impl GitLabCfg {
+    /// Parse the authentication section, for example the value found at `cfg.auth`. Returns `None`
+    /// unless the section selects `provider = "gitlab"`. The public GitLab instance is used
+    /// unless `url` is set.
+    ///
+    /// # Errors
+    ///
+    /// * The provider is unknown
+    /// * The client id, client secret, or redirect URI are missing
+    /// * The URL can't be parsed, or uses plain HTTP without `allow_insecure_url = true`
+    pub fn from_toml(toml: &toml::Value) -> Result<Option<Self>> {
+        if try!(oauth_provider(toml)) != "gitlab" {
+            return Ok(None);
+        }
+        let mut cfg = GitLabCfg {
+            url: GITLAB_URL.to_string(),
+            client_id: String::new(),
+            client_secret: String::new(),
+            redirect_uri: String::new(),
+        };
+        let mut insecure = false;
+        try!(toml.parse_into("allow_insecure_url", &mut insecure));
+        try!(toml.parse_into("url", &mut cfg.url));
+        cfg.url = try!(normalize_web_url(&cfg.url, insecure));
+        if !try!(toml.parse_into("client_id", &mut cfg.client_id)) || cfg.client_id.is_empty() {
+            return Err(Error::RequiredConfigField("auth.client_id"));
+        }
+        if !try!(toml.parse_into("client_secret", &mut cfg.client_secret)) ||
+           cfg.client_secret.is_empty() {
+            return Err(Error::RequiredConfigField("auth.client_secret"));
+        }
+        if !try!(toml.parse_into("redirect_uri", &mut cfg.redirect_uri)) ||
+           cfg.redirect_uri.is_empty() {
+            return Err(Error::RequiredConfigField("auth.redirect_uri"));
+        }
+        Ok(Some(cfg))
+    }
+}
+
+impl fmt::Debug for GitLabCfg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GitLabCfg")
+            .field("url", &self.url)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"<redacted>")
+            .field("redirect_uri", &self.redirect_uri)
+            .finish()
+    }
+}
+
+impl OAuth for GitLabCfg {
+    fn oauth_provider(&self) -> &str {
+        "gitlab"
+    }
+
+    fn oauth_url(&self) -> &str {
+        &self.url
+    }
+
+    fn oauth_client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn oauth_client_secret(&self) -> &str {
+        &self.client_secret
+    }
+
+    fn oauth_redirect_uri(&self) -> Option<&str> {
+        Some(&self.redirect_uri)
+    }
+}
+
+/// Returns the provider selected by the `provider` key of an authentication section, GitHub if
+/// there is none.
+///
+/// # Errors
+///
+/// * The provider isn't one of `OAUTH_PROVIDERS`
+fn oauth_provider(toml: &toml::Value) -> Result<String> {
+    let mut provider = "github".to_string();
+    try!(toml.parse_into("provider", &mut provider));
+    if OAUTH_PROVIDERS.contains(&provider.as_str()) {
+        Ok(provider)
+    } else {
+        Err(Error::UnknownOAuthProvider(provider))
+    }
+}
+
+impl fmt::Debug for StubAuthCfg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StubAuthCfg")
+            .field("shared_secret", &"<redacted>")
+            .field("token", &"<redacted>")
+            .field("login", &self.login)
+            .field("email", &self.email)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+/// Proxy, TLS, and timeout settings shared by every outbound HTTP client in this crate.
+///
+/// Defaults are taken from the process environment (`HTTPS_PROXY`, `NO_PROXY`, and
+/// `SSL_CERT_FILE`) and any key set in an explicit `http` configuration section overrides them.
+#[derive(Clone, PartialEq, Eq)]
+pub struct HttpCfg {
+    /// URL of the proxy server to send HTTPS requests through
+    pub proxy: Option<String>,
+    /// Hosts which bypass the proxy, see `no_proxy_matches`
+    pub no_proxy: Vec<String>,
+    /// Path to a PEM bundle of trusted certificate authorities
+    pub ca_bundle: Option<String>,
+    /// Skip verification of the server's TLS certificate
+    pub insecure: bool,
+    /// Upper bound on establishing a connection. Overrides the consumer's own default when set.
+    pub connect_timeout_ms: Option<u64>,
+    /// Upper bound on waiting for a response. Overrides the consumer's own default when set.
+    pub read_timeout_ms: Option<u64>,
+    /// Headers added to every request, for example for a proxy in front of a GitHub Enterprise
+    /// appliance which requires one. See `http::decorate::StaticHeaders`.
+    pub headers: Vec<(String, String)>,
+}
+
+impl HttpCfg {
+    /// Returns the process-wide defaults read from the environment. The proxy is taken from
+    /// `HTTPS_PROXY` or `https_proxy`, or else from `http_proxy`, which proxies that tunnel
+    /// HTTPS as well are often only configured with. Like curl, the upper case `HTTP_PROXY` is
+    /// ignored since a CGI environment sets it from the `Proxy` header of a request.
+    pub fn from_env() -> Self {
+        let proxy = env::var("HTTPS_PROXY")
+            .or(env::var("https_proxy"))
+            .or(env::var("http_proxy"))
+            .ok()
+            .and_then(|p| if p.trim().is_empty() { None } else { Some(p) });
+        let no_proxy = env::var("NO_PROXY")
+            .or(env::var("no_proxy"))
+            .map(|v| {
+                v.split(',')
+                    .map(|d| d.trim().to_string())
+                    .filter(|d| !d.is_empty())
+                    .collect()
+            })
+            .unwrap_or(vec![]);
+        HttpCfg {
+            proxy: proxy,
+            no_proxy: no_proxy,
+            ca_bundle: env::var("SSL_CERT_FILE").ok(),
+            insecure: false,
+            connect_timeout_ms: None,
+            read_timeout_ms: None,
+            headers: vec![],
+        }
+    }
+
+    /// Parse an `http` configuration section. Keys which are absent keep the process-wide default.
+    pub fn from_toml(toml: &toml::Value) -> Result<Self> {
+        let mut cfg = HttpCfg::from_env();
+        if toml.lookup("proxy").is_some() {
+            try!(toml.parse_into("proxy", &mut cfg.proxy));
+        }
+        if let Some(no_proxy) = toml.lookup("no_proxy") {
+            cfg.no_proxy = try!(parse_str_array(no_proxy, "http.no_proxy"));
+        }
+        if toml.lookup("ca_bundle").is_some() {
+            try!(toml.parse_into("ca_bundle", &mut cfg.ca_bundle));
+        }
+        try!(toml.parse_into("insecure", &mut cfg.insecure));
+        let mut timeout = 0;
+        if try!(toml.parse_into("connect_timeout_ms", &mut timeout)) {
+            try!(in_range("http.connect_timeout_ms", timeout, 1_000, 300_000));
+            cfg.connect_timeout_ms = Some(timeout);
+        }
+        if try!(toml.parse_into("read_timeout_ms", &mut timeout)) {
+            try!(in_range("http.read_timeout_ms", timeout, 1_000, 300_000));
+            cfg.read_timeout_ms = Some(timeout);
+        }
+        if let Some(headers) = toml.lookup("headers") {
+            cfg.headers = try!(parse_str_table(headers, "http.headers"));
+        }
+        Ok(cfg)
+    }
+
+    /// Returns the proxy to use for HTTPS requests to the given host on the default port, if any.
+    pub fn proxy_for(&self, host: &str) -> Option<&str> {
+        self.proxy_for_port(host, 443)
+    }
+
+    /// Returns the proxy to use for a request to the given URL, if any. Only HTTPS requests are
+    /// proxied: the proxy is reached through a tunnel, which plain HTTP requests, as to a GitHub
+    /// Enterprise appliance allowed with `allow_insecure_github_url`, aren't sent through.
+    pub fn proxy_for_url(&self, url: &Url) -> Option<&str> {
+        match (url.scheme(), url.host_str()) {
+            ("https", Some(host)) => self.proxy_for_port(host, url.port().unwrap_or(443)),
+            _ => None,
+        }
+    }
+
+    fn proxy_for_port(&self, host: &str, port: u16) -> Option<&str> {
+        if self.no_proxy.iter().any(|entry| no_proxy_matches(entry, host, port)) {
+            None
+        } else {
+            self.proxy.as_ref().map(|p| p.as_str())
+        }
+    }
+}
+
+/// True if an entry of a `no_proxy` list exempts requests to the given host and port from the
+/// proxy. The entry `*` exempts every host. Any other entry is a domain, optionally led by `.` or
+/// `*.`, which exempts the domain and every host under it, and may end with `:port` to only
+/// exempt requests to that port. Hosts are compared case-insensitively, and IPv6 addresses may
+/// be written with or without brackets.
+pub fn no_proxy_matches(entry: &str, host: &str, port: u16) -> bool {
+    let entry = entry.trim().to_lowercase();
+    if entry == "*" {
+        return true;
+    }
+    let (domain, entry_port) = split_host_port(&entry);
+    if entry_port.map_or(false, |p| p != port) {
+        return false;
+    }
+    let domain = domain.trim_left_matches("*.").trim_left_matches('.');
+    if domain.is_empty() {
+        return false;
+    }
+    let host = host.to_lowercase();
+    let host = host.trim_left_matches('[').trim_right_matches(']');
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// Splits a `host:port` pair, returning the host without brackets and the port, if there is
+/// one. A bare IPv6 address, with more than one colon, has no port.
+fn split_host_port(entry: &str) -> (&str, Option<u16>) {
+    if entry.starts_with('[') {
+        return match entry.find(']') {
+            Some(end) => {
+                let port = entry[end + 1..].trim_left_matches(':').parse().ok();
+                (&entry[1..end], port)
+            }
+            None => (entry, None),
+        };
+    }
+    match entry.rfind(':') {
+        Some(colon) if entry[..colon].find(':').is_none() => {
+            match entry[colon + 1..].parse() {
+                Ok(port) => (&entry[..colon], Some(port)),
+                Err(_) => (entry, None),
+            }
+        }
+        _ => (entry, None),
+    }
+}
+
+impl Default for HttpCfg {
+    fn default() -> Self {
+        HttpCfg::from_env()
+    }
+}
+
+impl fmt::Debug for HttpCfg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let headers: Vec<(&str, &str)> = self.headers
+            .iter()
+            .map(|&(ref name, _)| (name.as_str(), "<redacted>"))
+            .collect();
+        f.debug_struct("HttpCfg")
+            .field("proxy", &self.proxy)
+            .field("no_proxy", &self.no_proxy)
+            .field("ca_bundle", &self.ca_bundle)
+            .field("insecure", &self.insecure)
+            .field("connect_timeout_ms", &self.connect_timeout_ms)
+            .field("read_timeout_ms", &self.read_timeout_ms)
+            .field("headers", &headers)
+            .finish()
+    }
+}
+
+/// Upper bound on establishing a connection to GitHub unless configured otherwise, so a login
+/// fails quickly when GitHub can't be reached.
+pub const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 10_000;
+
+/// Upper bound on waiting for a response of GitHub unless configured otherwise.
+pub const DEFAULT_READ_TIMEOUT_MS: u64 = 30_000;
+
+/// Seconds a response to a user's token, such as their profile, is served from the cache unless
+/// configured otherwise.
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+
+/// Size, in pixels, of the avatar images recorded for users unless configured otherwise.
+pub const DEFAULT_AVATAR_SIZE: u32 = 120;
+
+/// Number of requests to GitHub kept for debugging unless configured otherwise.
+pub const DEFAULT_TRACE_CAPACITY: usize = 64;
+
+/// Tunables for the retry, timeout, caching, and circuit breaking behaviour of a `GitHubClient`.
+/// Each value is read from the same TOML table as the application's credentials.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitHubClientOptions {
+    /// Number of times a failed request is retried before giving up
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each subsequent attempt
+    pub base_backoff_ms: u64,
+    /// Upper bound on establishing a connection to the GitHub API
+    pub connect_timeout_ms: u64,
+    /// Upper bound on waiting for a response from the GitHub API
+    pub read_timeout_ms: u64,
+    /// Seconds a cached response remains valid. Zero disables caching.
+    pub cache_ttl_secs: u64,
+    /// Maximum number of cached responses
+    pub cache_max_entries: usize,
+    /// Consecutive failures before the circuit breaker opens. Zero disables the breaker.
+    pub breaker_threshold: u32,
+    /// Seconds the circuit breaker stays open before allowing a trial request
+    pub breaker_cooldown_secs: u64,
+    /// URL to a GitHub API endpoint, such as a staging Enterprise appliance, which receives a
+    /// fraction of API requests
+    pub canary_url: Option<String>,
+    /// Percentage of API requests routed to `canary_url`
+    pub canary_percent: u32,
+    /// Seconds after which a stored access token is checked with GitHub again before being
+    /// trusted
+    pub token_validation_secs: u64,
+    /// Size, in pixels, of the avatar images recorded for users
+    pub avatar_size: u32,
+    /// Whether the most recent requests to GitHub are kept in memory for debugging, see
+    /// `GitHubClient::recent_requests`
+    pub trace_requests: bool,
+    /// Number of requests kept when `trace_requests` is set
+    pub trace_capacity: usize,
+    /// Seconds a successful request keeps `GitHubClient::health` reporting GitHub as reachable
+    /// without probing it
+    pub health_fresh_secs: u64,
+    /// Least number of seconds between two probes made by `GitHubClient::health`
+    pub health_probe_interval_secs: u64,
+    /// Seconds the members of a team listed with the service token are trusted before being
+    /// listed again, zero to not cache them
+    pub team_cache_ttl_secs: u64,
+}
+
+impl GitHubClientOptions {
+    /// Parse client options from the given TOML table. Keys which are absent keep their default
+    /// value.
+    ///
+    /// # Errors
+    ///
+    /// * A value is outside of its valid range
+    pub fn from_toml(toml: &toml::Value) -> Result<Self> {
+        let mut opts = GitHubClientOptions::default();
+        try!(toml.parse_into("max_retries", &mut opts.max_retries));
+        try!(toml.parse_into("base_backoff_ms", &mut opts.base_backoff_ms));
+        try!(toml.parse_into("connect_timeout_ms", &mut opts.connect_timeout_ms));
+        try!(toml.parse_into("read_timeout_ms", &mut opts.read_timeout_ms));
+        try!(toml.parse_into("cache_ttl_secs", &mut opts.cache_ttl_secs));
+        try!(toml.parse_into("cache_max_entries", &mut opts.cache_max_entries));
+        try!(toml.parse_into("breaker_threshold", &mut opts.breaker_threshold));
+        try!(toml.parse_into("breaker_cooldown_secs", &mut opts.breaker_cooldown_secs));
+        try!(toml.parse_into("canary_url", &mut opts.canary_url));
+        try!(toml.parse_into("canary_percent", &mut opts.canary_percent));
+        try!(toml.parse_into("token_validation_secs", &mut opts.token_validation_secs));
+        try!(toml.parse_into("avatar_size", &mut opts.avatar_size));
+        try!(toml.parse_into("trace_requests", &mut opts.trace_requests));
+        try!(toml.parse_into("trace_capacity", &mut opts.trace_capacity));
+        try!(toml.parse_into("health_fresh_secs", &mut opts.health_fresh_secs));
+        try!(toml.parse_into("health_probe_interval_secs",
+                             &mut opts.health_probe_interval_secs));
+        try!(toml.parse_into("team_cache_ttl_secs", &mut opts.team_cache_ttl_secs));
+        if let Some(url) = opts.canary_url.take() {
+            let mut allow_insecure = false;
+            try!(toml.parse_into("allow_insecure_github_url", &mut allow_insecure));
+            opts.canary_url = Some(try!(normalize_api_url(&url, allow_insecure)));
+        }
+        try!(opts.validate());
+        Ok(opts)
+    }
+
+    /// Check every value is within its valid range, as `from_toml` does.
+    ///
+    /// # Errors
+    ///
+    /// * A value is outside of its valid range
+    /// * `canary_percent` is set without a `canary_url`
+    pub fn validate(&self) -> Result<()> {
+        try!(in_range("github.max_retries", self.max_retries as u64, 0, 10));
+        try!(in_range("github.base_backoff_ms", self.base_backoff_ms, 1, 60_000));
+        try!(in_range("github.connect_timeout_ms",
+                      self.connect_timeout_ms,
+                      1_000,
+                      300_000));
+        try!(in_range("github.read_timeout_ms", self.read_timeout_ms, 1_000, 300_000));
+        try!(in_range("github.cache_ttl_secs", self.cache_ttl_secs, 0, 86_400));
+        try!(in_range("github.breaker_cooldown_secs",
+                      self.breaker_cooldown_secs,
+                      1,
+                      3_600));
+        try!(in_range("github.canary_percent", self.canary_percent as u64, 0, 100));
+        try!(in_range("github.token_validation_secs",
+                      self.token_validation_secs,
+                      60,
+                      86_400));
+        try!(in_range("github.avatar_size", self.avatar_size as u64, 1, 2_048));
+        try!(in_range("github.trace_capacity", self.trace_capacity as u64, 1, 4_096));
+        try!(in_range("github.health_fresh_secs", self.health_fresh_secs, 1, 3_600));
+        try!(in_range("github.health_probe_interval_secs",
+                      self.health_probe_interval_secs,
+                      1,
+                      3_600));
+        try!(in_range("github.team_cache_ttl_secs", self.team_cache_ttl_secs, 0, 86_400));
+        if self.canary_percent > 0 && self.canary_url.is_none() {
+            return Err(Error::RequiredConfigField("github.canary_url"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for GitHubClientOptions {
+    fn default() -> Self {
+        GitHubClientOptions {
+            max_retries: 0,
+            base_backoff_ms: 100,
+            connect_timeout_ms: DEFAULT_CONNECT_TIMEOUT_MS,
+            read_timeout_ms: DEFAULT_READ_TIMEOUT_MS,
+            cache_ttl_secs: DEFAULT_CACHE_TTL_SECS,
+            cache_max_entries: 1_024,
+            breaker_threshold: 0,
+            breaker_cooldown_secs: 30,
+            canary_url: None,
+            canary_percent: 0,
+            token_validation_secs: 3_600,
+            avatar_size: DEFAULT_AVATAR_SIZE,
+            trace_requests: true,
+            trace_capacity: DEFAULT_TRACE_CAPACITY,
+            health_fresh_secs: 60,
+            health_probe_interval_secs: 30,
+            team_cache_ttl_secs: 300,
+        }
+    }
+}
+
+fn in_range(field: &'static str, value: u64, min: u64, max: u64) -> Result<()> {
+    if value < min || value > max {
+        return Err(Error::ConfigOutOfRange(field, min, max));
+    }
+    Ok(())
 }
 
 pub trait RouteAddrs {
@@ -54,3 +1288,518 @@ impl ToAddrString for net::SocketAddrV4 {
         format!("tcp://{}:{}", self.ip(), self.port())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use toml;
+    use url::Url;
+
+    use super::*;
+    use error::Error;
+
+    #[test]
+    fn github_apps_from_toml() {
+        let raw = r#"
+        [internal]
+        api_url = "https://github.mycorp.com/api/v3"
+        client_id = "abc"
+        client_secret = "123"
+
+        [public]
+        client_id = "def"
+        client_secret = "456"
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let apps = GitHubCfg::apps_from_toml(&value).unwrap();
+        assert_eq!(apps.len(), 2);
+        assert_eq!(apps["internal"].api_url, "https://github.mycorp.com/api/v3");
+        assert_eq!(apps["internal"].client_id, "abc");
+        assert_eq!(apps["public"].api_url, GITHUB_URL);
+        assert_eq!(apps["public"].client_secret, "456");
+    }
+
+    #[test]
+    fn github_app_secret_from_file() {
+        let raw = r#"
+        client_id = "abc"
+        client_secret_file = "/hab/svc/hab-builder-api/files/github.secret"
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.client_secret, "");
+        assert_eq!(app.github_client_secret_file(),
+                   Some("/hab/svc/hab-builder-api/files/github.secret"));
+    }
+
+    #[test]
+    fn github_app_debug_redacts_secret() {
+        let mut app = GitHubCfg::default();
+        app.client_secret = "supersecret".to_string();
+        app.service_token = Some("servicetoken".to_string());
+        let debug = format!("{:?}", app);
+        assert!(!debug.contains("supersecret") && !debug.contains("servicetoken"));
+    }
+
+    #[test]
+    fn github_client_options_from_toml() {
+        let raw = r#"
+        max_retries = 3
+        base_backoff_ms = 250
+        connect_timeout_ms = 5000
+        read_timeout_ms = 15000
+        cache_ttl_secs = 300
+        cache_max_entries = 512
+        breaker_threshold = 5
+        breaker_cooldown_secs = 60
+        canary_url = "https://github-staging.example.com/api/v3/"
+        canary_percent = 5
+        token_validation_secs = 600
+        avatar_size = 64
+        trace_requests = false
+        trace_capacity = 16
+        health_fresh_secs = 120
+        health_probe_interval_secs = 10
+        team_cache_ttl_secs = 0
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let opts = GitHubClientOptions::from_toml(&value).unwrap();
+        assert_eq!(opts,
+                   GitHubClientOptions {
+                       max_retries: 3,
+                       base_backoff_ms: 250,
+                       connect_timeout_ms: 5000,
+                       read_timeout_ms: 15000,
+                       cache_ttl_secs: 300,
+                       cache_max_entries: 512,
+                       breaker_threshold: 5,
+                       breaker_cooldown_secs: 60,
+                       canary_url: Some("https://github-staging.example.com/api/v3".to_string()),
+                       canary_percent: 5,
+                       token_validation_secs: 600,
+                       avatar_size: 64,
+                       trace_requests: false,
+                       trace_capacity: 16,
+                       health_fresh_secs: 120,
+                       health_probe_interval_secs: 10,
+                       team_cache_ttl_secs: 0,
+                   });
+    }
+
+    #[test]
+    fn github_client_options_canary_requires_url() {
+        let value: toml::Value = "canary_percent = 10".parse().unwrap();
+        assert!(GitHubClientOptions::from_toml(&value).is_err());
+        let value: toml::Value = r#"
+        canary_url = "https://github-staging.example.com"
+        canary_percent = 101
+        "#
+            .parse()
+            .unwrap();
+        assert!(GitHubClientOptions::from_toml(&value).is_err());
+    }
+
+    #[test]
+    fn github_client_options_out_of_range() {
+        let value: toml::Value = "read_timeout_ms = 600000".parse().unwrap();
+        assert!(GitHubClientOptions::from_toml(&value).is_err());
+    }
+
+    #[test]
+    fn github_enterprise_profile() {
+        let raw = r#"
+        profile = "enterprise"
+        web_url = "https://github.example.com"
+        client_id = "abc"
+        client_secret = "def"
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.api_url, "https://github.example.com/api/v3");
+        assert_eq!(app.web_url, "https://github.example.com");
+        assert!(!app.resolved().contains("def"));
+    }
+
+    #[test]
+    fn github_endpoints_apply_the_profile() {
+        let raw = r#"
+        profile = "enterprise"
+        web_url = "https://github.example.com/"
+        admin_org = "habitat-sh"
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let endpoints = GitHubCfg::endpoints_from_toml(&value).unwrap();
+        assert_eq!(endpoints.profile, Some("enterprise".to_string()));
+        assert_eq!(endpoints.api_url, "https://github.example.com/api/v3");
+        assert_eq!(endpoints.web_url, "https://github.example.com");
+        assert_eq!(endpoints.admin_org, None);
+        let defaults: toml::Value = "".parse().unwrap();
+        let endpoints = GitHubCfg::endpoints_from_toml(&defaults).unwrap();
+        assert_eq!(endpoints.api_url, GITHUB_URL);
+        assert_eq!(endpoints.web_url, GITHUB_WEB_URL);
+    }
+
+    #[test]
+    fn github_profile_overrides() {
+        let raw = r#"
+        profile = "github.com"
+        api_url = "https://github.example.com/api/v3"
+        scopes = ["user:email", "read:org"]
+        client_id = "abc"
+        client_secret = "def"
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.api_url, "https://github.example.com/api/v3");
+        assert_eq!(app.web_url, GITHUB_WEB_URL);
+        assert_eq!(app.scopes, vec!["user:email", "read:org"]);
+    }
+
+    #[test]
+    fn github_unknown_profile() {
+        let raw = r#"
+        profile = "gitlab"
+        client_id = "abc"
+        client_secret = "def"
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        match GitHubCfg::from_toml(&value) {
+            Err(Error::UnknownGitHubProfile(ref p)) => assert_eq!(p, "gitlab"),
+            _ => panic!("expected unknown profile error"),
+        }
+    }
+
+    #[test]
+    fn stub_auth_requires_insecure_flag() {
+        let raw = r#"
+        provider = "none"
+        shared_secret = "letmein"
+        token = "static-token"
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        assert!(StubAuthCfg::from_toml(&value).is_err());
+        let value: toml::Value = format!("insecure_stub_auth = true\n{}", raw).parse().unwrap();
+        let stub = StubAuthCfg::from_toml(&value).unwrap().unwrap();
+        assert_eq!(stub.login, "admin");
+    }
+
+    #[test]
+    fn gitlab_auth_from_toml() {
+        let raw = r#"
+        provider = "gitlab"
+        url = "https://gitlab.example.com/"
+        client_id = "6f1c"
+        client_secret = "s3cr3t"
+        redirect_uri = "https://builder.example.com/"
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let gitlab = GitLabCfg::from_toml(&value).unwrap().unwrap();
+        assert_eq!(gitlab.oauth_provider(), "gitlab");
+        assert_eq!(gitlab.oauth_url(), "https://gitlab.example.com");
+        assert_eq!(gitlab.oauth_redirect_uri(), Some("https://builder.example.com/"));
+        assert!(!format!("{:?}", gitlab).contains("s3cr3t"));
+        assert!(StubAuthCfg::from_toml(&value).unwrap().is_none());
+        let value: toml::Value = "provider = \"gitlab\"\nclient_id = \"6f1c\"".parse().unwrap();
+        match GitLabCfg::from_toml(&value) {
+            Err(Error::RequiredConfigField(field)) => assert_eq!(field, "auth.client_secret"),
+            other => panic!("expected a missing secret, got {:?}", other),
+        }
+        let value: toml::Value = "provider = \"bitbucket\"".parse().unwrap();
+        match GitLabCfg::from_toml(&value) {
+            Err(Error::UnknownOAuthProvider(ref p)) => assert_eq!(p, "bitbucket"),
+            other => panic!("expected an unknown provider, got {:?}", other),
+        }
+        let value: toml::Value = "provider = \"github\"".parse().unwrap();
+        assert!(GitLabCfg::from_toml(&value).unwrap().is_none());
+    }
+
+    #[test]
+    fn normalize_github_dot_com() {
+        assert_eq!(normalize_api_url("https://api.github.com", false).unwrap(),
+                   "https://api.github.com");
+        assert!(normalize_api_url("https://github.com", false).is_err());
+    }
+
+    #[test]
+    fn normalize_enterprise_with_suffix() {
+        assert_eq!(normalize_api_url("https://github.mycorp.com/api/v3", false).unwrap(),
+                   "https://github.mycorp.com/api/v3");
+    }
+
+    #[test]
+    fn normalize_enterprise_without_suffix() {
+        assert_eq!(normalize_api_url("https://github.mycorp.com", false).unwrap(),
+                   "https://github.mycorp.com/api/v3");
+        assert!(normalize_api_url("https://github.mycorp.com/api/v2", false).is_err());
+    }
+
+    #[test]
+    fn normalize_trailing_slash() {
+        assert_eq!(normalize_api_url("https://api.github.com/", false).unwrap(),
+                   "https://api.github.com");
+        assert_eq!(normalize_api_url("https://github.mycorp.com/api/v3/", false).unwrap(),
+                   "https://github.mycorp.com/api/v3");
+    }
+
+    #[test]
+    fn normalize_plain_http() {
+        assert!(normalize_api_url("http://github.mycorp.com/api/v3", false).is_err());
+        assert_eq!(normalize_api_url("http://github.mycorp.com/api/v3", true).unwrap(),
+                   "http://github.mycorp.com/api/v3");
+    }
+
+    #[test]
+    fn github_admin_allowlist() {
+        let raw = r#"
+        client_id = "abc"
+        client_secret = "def"
+        admin_logins = ["octocat", "Hubot"]
+        admin_ids = [583231]
+        honor_site_admin = true
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.github_admin_logins(),
+                   vec!["octocat".to_string(), "Hubot".to_string()]);
+        assert_eq!(app.github_admin_ids(), vec![583231]);
+        assert!(app.github_honor_site_admin());
+    }
+
+    #[test]
+    fn github_access_lists() {
+        let raw = r#"
+        client_id = "abc"
+        client_secret = "def"
+        allow_logins = ["octocat"]
+        deny_logins = ["Hubot"]
+        allow_orgs = ["habitat-sh"]
+        require_org_membership = true
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.github_allow_logins(), vec!["octocat".to_string()]);
+        assert_eq!(app.github_deny_logins(), vec!["Hubot".to_string()]);
+        assert_eq!(app.github_allow_orgs(), vec!["habitat-sh".to_string()]);
+        assert!(app.github_require_org_membership());
+        let value: toml::Value = r#"
+        client_id = "abc"
+        client_secret = "def"
+        deny_logins = "hubot"
+        "#
+            .parse()
+            .unwrap();
+        assert!(GitHubCfg::from_toml(&value).is_err());
+    }
+
+    #[test]
+    fn github_http_inherits_defaults() {
+        let raw = r#"
+        client_id = "abc"
+        client_secret = "def"
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.http, HttpCfg::from_env());
+    }
+
+    #[test]
+    fn github_http_overrides_defaults() {
+        let raw = r#"
+        client_id = "abc"
+        client_secret = "def"
+
+        [http]
+        proxy = "http://proxy.example.com:3128"
+        no_proxy = [".example.com"]
+        ca_bundle = "/etc/ssl/corp.pem"
+        read_timeout_ms = 5000
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.http.proxy, Some("http://proxy.example.com:3128".to_string()));
+        assert_eq!(app.http.ca_bundle, Some("/etc/ssl/corp.pem".to_string()));
+        assert_eq!(app.http.read_timeout_ms, Some(5000));
+        assert_eq!(app.http.proxy_for("github.example.com"), None);
+        assert_eq!(app.http.proxy_for("api.github.com"),
+                   Some("http://proxy.example.com:3128"));
+    }
+
+    #[test]
+    fn no_proxy_entries_match_domains_wildcards_and_ports() {
+        let ghe = "ghe.example.com";
+        assert!(no_proxy_matches("*", ghe, 443));
+        assert!(no_proxy_matches("ghe.example.com", ghe, 443));
+        assert!(no_proxy_matches("GHE.Example.com", ghe, 443));
+        assert!(no_proxy_matches("example.com", ghe, 443));
+        assert!(no_proxy_matches(".example.com", ghe, 443));
+        assert!(no_proxy_matches("*.example.com", ghe, 443));
+        assert!(no_proxy_matches(".example.com", "example.com", 443));
+        assert!(!no_proxy_matches("ample.com", ghe, 443));
+        assert!(!no_proxy_matches("other.example.com", ghe, 443));
+        assert!(!no_proxy_matches("", ghe, 443));
+        assert!(!no_proxy_matches("*.", ghe, 443));
+        // A port restricts the entry to requests to that port
+        assert!(no_proxy_matches("ghe.example.com:8443", ghe, 8443));
+        assert!(!no_proxy_matches("ghe.example.com:8443", ghe, 443));
+        assert!(no_proxy_matches("[::1]:8443", "[::1]", 8443));
+        assert!(!no_proxy_matches("[::1]:8443", "[::1]", 443));
+        assert!(no_proxy_matches("::1", "[::1]", 443));
+    }
+
+    #[test]
+    fn only_https_urls_are_proxied() {
+        let mut http = HttpCfg::from_env();
+        http.proxy = Some("http://proxy.example.com:3128".to_string());
+        http.no_proxy = vec!["ghe.example.com:8443".to_string()];
+        let proxy_for = |url: &str| http.proxy_for_url(&Url::parse(url).unwrap()).is_some();
+        assert!(proxy_for("https://api.github.com/user"));
+        assert!(proxy_for("https://ghe.example.com/api/v3"));
+        assert!(!proxy_for("https://ghe.example.com:8443/api/v3"));
+        assert!(!proxy_for("http://ghe.example.com/api/v3"));
+        http.proxy = None;
+        assert!(!proxy_for("https://api.github.com/user"));
+    }
+
+    #[test]
+    fn github_timeouts_default_to_failing_fast() {
+        let options = GitHubClientOptions::default();
+        assert_eq!(options.connect_timeout_ms, 10_000);
+        assert_eq!(options.read_timeout_ms, 30_000);
+        assert_eq!(options.cache_ttl_secs, 300);
+        assert!(options.validate().is_ok());
+        let http = HttpCfg::from_toml(&"connect_timeout_ms = 2000".parse().unwrap()).unwrap();
+        assert_eq!(http.connect_timeout_ms, Some(2000));
+        assert_eq!(http.read_timeout_ms, None);
+    }
+
+    #[test]
+    fn github_http_headers() {
+        let raw = r#"
+        client_id = "abc"
+        client_secret = "def"
+
+        [http.headers]
+        X-Proxy-Key = "s3cr3t"
+        X-Deployment = "builder"
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.http.headers,
+                   vec![("X-Deployment".to_string(), "builder".to_string()),
+                        ("X-Proxy-Key".to_string(), "s3cr3t".to_string())]);
+        assert!(!format!("{:?}", app).contains("s3cr3t"));
+        let raw = r#"
+        client_id = "abc"
+        client_secret = "def"
+
+        [http.headers]
+        X-Retries = 3
+        "#;
+        let value: toml::Value = raw.parse().unwrap();
+        assert!(GitHubCfg::from_toml(&value).is_err());
+    }
+
+    #[test]
+    fn github_fingerprint_is_stable() {
+        let mut app = GitHubCfg::default();
+        app.client_id = "abc".to_string();
+        app.client_secret = "def".to_string();
+        assert_eq!(app.fingerprint(), "5fcb94427db0");
+        app.client_secret = "rotated".to_string();
+        assert_eq!(app.fingerprint(), "35a92701ff03");
+    }
+
+    #[test]
+    fn org_gating_requires_the_org_scope() {
+        let mut app = GitHubCfg::default();
+        assert_eq!(github_required_scopes(&app), vec!["user:email"]);
+        app.allow_orgs = vec!["habitat-sh".to_string()];
+        assert_eq!(github_required_scopes(&app), vec!["user:email", "read:org"]);
+        app.allow_orgs = vec![];
+        app.required_org = Some("habitat-sh".to_string());
+        app.scopes = vec!["read:org".to_string(), "user:email".to_string()];
+        assert_eq!(github_required_scopes(&app), vec!["read:org", "user:email"]);
+    }
+
+    #[test]
+    fn github_api_url_old_key_only() {
+        let value: toml::Value = r#"
+        url = "https://github.example.com/api/v3"
+        web_url = "https://github.example.com"
+        client_id = "abc"
+        client_secret = "def"
+        "#
+            .parse()
+            .unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.api_url, "https://github.example.com/api/v3");
+    }
+
+    #[test]
+    fn github_api_url_new_key_only() {
+        let value: toml::Value = r#"
+        api_url = "https://github.example.com/api/v3"
+        web_url = "https://github.example.com"
+        client_id = "abc"
+        client_secret = "def"
+        "#
+            .parse()
+            .unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.api_url, "https://github.example.com/api/v3");
+    }
+
+    #[test]
+    fn github_api_url_both_keys_agreeing() {
+        let value: toml::Value = r#"
+        url = "https://github.example.com/api/v3"
+        api_url = "https://github.example.com/api/v3"
+        web_url = "https://github.example.com"
+        client_id = "abc"
+        client_secret = "def"
+        "#
+            .parse()
+            .unwrap();
+        let app = GitHubCfg::from_toml(&value).unwrap();
+        assert_eq!(app.api_url, "https://github.example.com/api/v3");
+    }
+
+    #[test]
+    fn github_api_url_both_keys_conflicting() {
+        let value: toml::Value = r#"
+        url = "https://github.example.com/api/v3"
+        api_url = "https://github.other.com/api/v3"
+        web_url = "https://github.example.com"
+        client_id = "abc"
+        client_secret = "def"
+        "#
+            .parse()
+            .unwrap();
+        match GitHubCfg::from_toml(&value) {
+            Err(Error::ConflictingConfigKeys(old, new)) => {
+                assert_eq!(old, "url");
+                assert_eq!(new, "api_url");
+            }
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn migrate_keys_nested_table() {
+        let value: toml::Value = r#"
+        [cfg.github]
+        url = "https://api.github.com"
+        "#
+            .parse()
+            .unwrap();
+        let migrated = migrate_keys(&value, "cfg.github", GITHUB_RENAMED_KEYS).unwrap();
+        assert!(migrated.lookup("cfg.github.url").is_none());
+        assert_eq!(migrated.lookup("cfg.github.api_url").and_then(|v| v.as_str()),
+                   Some("https://api.github.com"));
+    }
+
+    #[test]
+    fn github_app_requires_credentials() {
+        let value: toml::Value = "url = \"https://api.github.com\"".parse().unwrap();
+        assert!(GitHubCfg::from_toml(&value).is_err());
+    }
+}
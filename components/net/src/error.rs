@@ -12,31 +12,67 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
 use std::error;
 use std::fmt;
 use std::io;
 use std::result;
 
+#[cfg(feature = "client")]
+use hab_http;
+use hcore;
+#[cfg(feature = "client")]
 use hyper;
 use protobuf;
 use rustc_serialize::json;
 use zmq;
 
+use config;
 use oauth;
+use redact;
+use session;
 
 #[derive(Debug)]
 pub enum Error {
+    AccessDenied(oauth::policy::AccessDenied),
     Auth(oauth::github::AuthErr),
-    GitHubAPI(HashMap<String, String>),
+    CircuitOpen(String),
+    ConfigOutOfRange(&'static str, u64, u64),
+    ConflictingConfigKeys(String, String),
+    GitHubAPI(oauth::github::GitHubError),
+    /// GitHub refused a request until the rate limit resets, at `reset` seconds since the epoch
+    GitHubRateLimited { reset: u64 },
+    /// A request to GitHub, to the given redacted URL, took longer than the configured timeouts
+    GitHubTimeout(String),
+    HabitatCore(hcore::Error),
+    #[cfg(feature = "client")]
+    HttpClient(hab_http::Error),
     IO(io::Error),
+    InvalidGitHubUrl(String, String),
+    InvalidLinkHeader(String),
+    InvalidSessionToken(&'static str),
+    #[cfg(feature = "client")]
     HyperError(hyper::error::Error),
     JsonDecode(json::DecoderError),
     MaxHops,
+    #[cfg(feature = "client")]
     HTTP(hyper::status::StatusCode),
     MissingScope(String),
+    OrgAccessDenied(String),
     Protobuf(protobuf::ProtobufError),
+    RequestRefused(String),
+    RequiredConfigField(&'static str),
+    SecretFileEmpty(String),
+    SecretFileIO(String, io::Error),
+    SessionKeyTooShort(usize),
+    SessionTokenExpired,
     Sys,
+    TeamMembersUnlisted(String, String),
+    TooManyPages(String, usize),
+    TokenClientMismatch(String),
+    TokenRevoked,
+    UnknownGitHubApp(String),
+    UnknownGitHubProfile(String),
+    UnknownOAuthProvider(String),
     Zmq(zmq::Error),
 }
 
@@ -45,40 +81,165 @@ pub type Result<T> = result::Result<T, Error>;
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match *self {
+            Error::AccessDenied(ref reason) => {
+                format!("Login refused by the access policy, {}", reason.code())
+            }
             Error::Auth(ref e) => format!("GitHub Authentication error, {}", e),
-            Error::GitHubAPI(ref e) => format!("GitHub API error, {:?}", e),
+            Error::CircuitOpen(ref host) => {
+                format!("GitHub API at {} is failing, not sending requests until it recovers",
+                        host)
+            }
+            Error::ConfigOutOfRange(ref f, ref min, ref max) => {
+                format!("Configuration value out of range, {} must be between {} and {}",
+                        f,
+                        min,
+                        max)
+            }
+            Error::ConflictingConfigKeys(ref old, ref new) => {
+                format!("Conflicting values in configuration, {} is deprecated and replaced by \
+                         {}, set only {}",
+                        old,
+                        new,
+                        new)
+            }
+            Error::GitHubAPI(ref e) => format!("{}", e),
+            Error::GitHubRateLimited { reset } => {
+                format!("GitHub API rate limit exceeded, it resets at {}", reset)
+            }
+            Error::GitHubTimeout(ref url) => format!("GitHub request timed out, {}", url),
+            Error::HabitatCore(ref e) => format!("{}", e),
+            #[cfg(feature = "client")]
+            Error::HttpClient(ref e) => format!("{}", e),
             Error::IO(ref e) => format!("{}", e),
+            Error::InvalidGitHubUrl(ref url, ref reason) => {
+                format!("Invalid GitHub URL, {}, {}", url, reason)
+            }
+            Error::InvalidLinkHeader(ref reason) => {
+                format!("Invalid Link header in a response, {}", reason)
+            }
+            Error::InvalidSessionToken(ref reason) => {
+                format!("Invalid session token, {}", reason)
+            }
+            #[cfg(feature = "client")]
             Error::HyperError(ref e) => format!("{}", e),
             Error::JsonDecode(ref e) => format!("JSON decoding error, {}", e),
             Error::MaxHops => format!("Received a message containing too many network hops"),
+            #[cfg(feature = "client")]
             Error::HTTP(ref e) => format!("{}", e),
             Error::MissingScope(ref e) => format!("Missing GitHub permission: {}", e),
+            Error::OrgAccessDenied(ref e) => {
+                format!("GitHub refused to show the organizations and teams of the user, {}", e)
+            }
             Error::Protobuf(ref e) => format!("{}", e),
+            Error::RequestRefused(ref reason) => {
+                format!("Request refused before it was sent, {}", reason)
+            }
+            Error::RequiredConfigField(ref e) => {
+                format!("Missing required field in configuration, {}", e)
+            }
+            Error::SecretFileEmpty(ref p) => format!("Secret file is empty, {}", p),
+            Error::SecretFileIO(ref p, ref e) => {
+                format!("Unable to read secret file, {}, {}", p, e)
+            }
+            Error::SessionKeyTooShort(ref len) => {
+                format!("Session token key is too short, {} bytes, at least {} are required",
+                        len,
+                        session::token::MIN_SESSION_KEY_LEN)
+            }
+            Error::SessionTokenExpired => format!("Session token has expired"),
             Error::Sys => format!("Internal system error"),
+            Error::TeamMembersUnlisted(ref team, ref reason) => {
+                format!("Unable to list the members of GitHub team {}, {}", team, reason)
+            }
+            Error::TooManyPages(ref path, ref pages) => {
+                format!("GitHub listing {} has more than {} pages", path, pages)
+            }
+            Error::TokenClientMismatch(ref e) => {
+                format!("Access token was issued to a different OAuth application, {}", e)
+            }
+            Error::TokenRevoked => format!("Access token has been revoked or has expired"),
+            Error::UnknownGitHubApp(ref e) => {
+                format!("No GitHub OAuth application configured with the name, {}", e)
+            }
+            Error::UnknownGitHubProfile(ref e) => {
+                format!("Unknown GitHub profile, {}, expected one of: {}",
+                        e,
+                        config::GITHUB_PROFILES.join(", "))
+            }
+            Error::UnknownOAuthProvider(ref e) => {
+                format!("Unknown OAuth provider, {}, expected one of: {}",
+                        e,
+                        config::OAUTH_PROVIDERS.join(", "))
+            }
             Error::Zmq(ref e) => format!("{}", e),
         };
-        write!(f, "{}", msg)
+        // Errors end up in logs and responses, and may carry bodies or URLs holding secrets
+        write!(f, "{}", redact::redact_text(&msg))
     }
 }
 
 impl error::Error for Error {
     fn description(&self) -> &str {
         match *self {
+            Error::AccessDenied(_) => "Login refused by the access policy.",
             Error::Auth(_) => "GitHub authorization error.",
+            Error::CircuitOpen(_) => "Circuit breaker open for GitHub API host.",
+            Error::ConfigOutOfRange(_, _, _) => "Configuration value out of range.",
+            Error::ConflictingConfigKeys(_, _) => "Conflicting values in configuration.",
             Error::GitHubAPI(_) => "GitHub API error.",
+            Error::GitHubRateLimited { .. } => "GitHub API rate limit exceeded.",
+            Error::GitHubTimeout(_) => "GitHub request timed out.",
+            Error::HabitatCore(ref err) => err.description(),
+            #[cfg(feature = "client")]
+            Error::HttpClient(ref err) => err.description(),
             Error::IO(ref err) => err.description(),
+            Error::InvalidGitHubUrl(_, _) => "Invalid GitHub URL.",
+            Error::InvalidLinkHeader(_) => "Invalid Link header in a response.",
+            Error::InvalidSessionToken(_) => "Invalid session token.",
+            #[cfg(feature = "client")]
             Error::HyperError(ref err) => err.description(),
+            #[cfg(feature = "client")]
             Error::HTTP(_) => "Non-200 HTTP response.",
             Error::JsonDecode(ref err) => err.description(),
             Error::MaxHops => "Received a message containing too many network hops",
             Error::MissingScope(_) => "Missing GitHub authorization scope.",
+            Error::OrgAccessDenied(_) => "GitHub refused access to organization memberships.",
             Error::Protobuf(ref err) => err.description(),
+            Error::RequestRefused(_) => "Request refused by a request decorator.",
+            Error::RequiredConfigField(_) => "Missing required field in configuration.",
+            Error::SecretFileEmpty(_) => "Secret file is empty.",
+            Error::SecretFileIO(_, _) => "Unable to read secret file.",
+            Error::SessionKeyTooShort(_) => "Session token key is too short.",
+            Error::SessionTokenExpired => "Session token has expired.",
             Error::Sys => "Internal system error",
+            Error::TeamMembersUnlisted(_, _) => "Unable to list the members of a GitHub team.",
+            Error::TooManyPages(_, _) => "GitHub listing has too many pages.",
+            Error::TokenClientMismatch(_) => {
+                "Access token was issued to a different OAuth application."
+            }
+            Error::TokenRevoked => "Access token has been revoked or has expired.",
+            Error::UnknownGitHubApp(_) => "No GitHub OAuth application configured by that name.",
+            Error::UnknownGitHubProfile(_) => "Unknown GitHub profile.",
+            Error::UnknownOAuthProvider(_) => "Unknown OAuth provider.",
             Error::Zmq(ref err) => err.description(),
         }
     }
 }
 
+impl From<hcore::Error> for Error {
+    fn from(err: hcore::Error) -> Error {
+        Error::HabitatCore(err)
+    }
+}
+
+#[cfg(feature = "client")]
+impl From<hab_http::Error> for Error {
+    fn from(err: hab_http::Error) -> Error {
+        Error::HttpClient(err)
+    }
+}
+
+#[cfg(feature = "client")]
 impl From<hyper::error::Error> for Error {
     fn from(err: hyper::error::Error) -> Self {
         Error::HyperError(err)
@@ -0,0 +1,111 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Changing the requests of an `ApiClient` just before they are sent, for example to sign them
+//! for an authenticating proxy in front of a GitHub Enterprise appliance.
+//!
+//! The decorators of a client run in order on every attempt at a request, after the client has
+//! set its own headers, so they see the request as it is sent. A decorator returning an error
+//! stops the request from being sent, and `ApiRequest::send` returns the error without retrying.
+
+use std::fmt;
+use std::sync::Arc;
+
+use hyper::Url;
+use hyper::header::Headers;
+use hyper::method::Method;
+
+use config::HttpCfg;
+use error::Result;
+
+/// A request about to be sent.
+pub struct OutboundRequest {
+    pub method: Method,
+    /// URL the request is sent to, query included
+    pub url: Url,
+    pub headers: Headers,
+    /// Body of the request, empty if it has none
+    pub body: Vec<u8>,
+}
+
+/// Changes requests before they are sent, or refuses to let them be sent.
+pub trait RequestDecorator: Send + Sync {
+    /// Changes the request, or fails so it isn't sent, with `Error::RequestRefused` unless a
+    /// more specific error applies.
+    fn decorate(&self, req: &mut OutboundRequest) -> Result<()>;
+}
+
+/// The decorators of an `ApiClient`, applied in the order they were added.
+#[derive(Clone, Default)]
+pub struct DecoratorChain {
+    decorators: Vec<Arc<RequestDecorator>>,
+}
+
+impl DecoratorChain {
+    /// Returns the chain a client configured by `cfg` starts with, adding `cfg.headers` to every
+    /// request if there are any.
+    pub fn for_cfg(cfg: &HttpCfg) -> Self {
+        let mut chain = DecoratorChain::default();
+        if !cfg.headers.is_empty() {
+            chain.push(Arc::new(StaticHeaders::new(cfg.headers.clone())));
+        }
+        chain
+    }
+
+    pub fn push(&mut self, decorator: Arc<RequestDecorator>) {
+        self.decorators.push(decorator);
+    }
+
+    pub fn len(&self) -> usize {
+        self.decorators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decorators.is_empty()
+    }
+
+    /// Applies every decorator to the request, stopping at the first which fails.
+    pub fn decorate(&self, req: &mut OutboundRequest) -> Result<()> {
+        for decorator in self.decorators.iter() {
+            try!(decorator.decorate(req));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for DecoratorChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DecoratorChain").field("len", &self.decorators.len()).finish()
+    }
+}
+
+/// Sets the same headers on every request, replacing any of the same name the client set.
+pub struct StaticHeaders {
+    headers: Vec<(String, String)>,
+}
+
+impl StaticHeaders {
+    pub fn new(headers: Vec<(String, String)>) -> Self {
+        StaticHeaders { headers: headers }
+    }
+}
+
+impl RequestDecorator for StaticHeaders {
+    fn decorate(&self, req: &mut OutboundRequest) -> Result<()> {
+        for &(ref name, ref value) in self.headers.iter() {
+            req.headers.set_raw(name.clone(), vec![value.as_bytes().to_vec()]);
+        }
+        Ok(())
+    }
+}
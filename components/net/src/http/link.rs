@@ -0,0 +1,259 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `Link` header of RFC 5988, which paginated APIs such as GitHub's link the pages of a
+//! listing with, as in
+//!
+//! ```text
+//! Link: <https://api.github.com/user/emails?page=2>; rel="next",
+//!       <https://api.github.com/user/emails?page=3>; rel="last"
+//! ```
+
+use hyper::header::Headers;
+
+use error::{Error, Result};
+
+/// A link of a `Link` header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Link {
+    /// Target of the link, as written between the angle brackets
+    pub url: String,
+    /// Relation types of the link, such as `next` and `last`, in lowercase
+    pub rels: Vec<String>,
+}
+
+/// Returns the links of the value of a `Link` header, in order. Parameters other than `rel` are
+/// ignored.
+///
+/// # Errors
+///
+/// * A link's target isn't enclosed in angle brackets
+/// * A parameter has no value, a quoted value isn't closed, or an unquoted one has spaces
+pub fn parse_link_header(value: &str) -> Result<Vec<Link>> {
+    let invalid = |reason: &str| Error::InvalidLinkHeader(format!("{}, {:?}", reason, value));
+    let mut links = vec![];
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            return Err(invalid("a link doesn't start with <"));
+        }
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => return Err(invalid("a link isn't closed with >")),
+        };
+        let mut link = Link {
+            url: rest[1..end].trim().to_string(),
+            rels: vec![],
+        };
+        rest = rest[end + 1..].trim_left();
+        while rest.starts_with(';') {
+            rest = rest[1..].trim_left();
+            let eq = match rest.find('=') {
+                Some(eq) => eq,
+                None => return Err(invalid("a parameter has no value")),
+            };
+            let name = rest[..eq].trim().to_lowercase();
+            rest = rest[eq + 1..].trim_left();
+            let param = if rest.starts_with('"') {
+                let close = match rest[1..].find('"') {
+                    Some(close) => close + 1,
+                    None => return Err(invalid("a quoted value isn't closed")),
+                };
+                let param = rest[1..close].to_string();
+                rest = rest[close + 1..].trim_left();
+                param
+            } else {
+                let end = rest.find(|c: char| c == ';' || c == ',').unwrap_or(rest.len());
+                let param = rest[..end].trim().to_string();
+                if param.contains(char::is_whitespace) {
+                    return Err(invalid("an unquoted value has spaces"));
+                }
+                rest = &rest[end..];
+                param
+            };
+            if name == "rel" {
+                link.rels.extend(param.split_whitespace().map(|rel| rel.to_lowercase()));
+            }
+        }
+        links.push(link);
+        if rest.starts_with(',') {
+            rest = rest[1..].trim_left();
+        } else if !rest.is_empty() {
+            return Err(invalid("links aren't separated by commas"));
+        }
+    }
+    Ok(links)
+}
+
+/// Returns the target of the `next` link of the `Link` headers, or `None` if there is none, as
+/// on the last page of a listing.
+///
+/// # Errors
+///
+/// * A `Link` header doesn't parse, see `parse_link_header`
+pub fn next_link(headers: &Headers) -> Result<Option<String>> {
+    let values = match headers.get_raw("Link") {
+        Some(values) => values,
+        None => return Ok(None),
+    };
+    for value in values.iter() {
+        let value = String::from_utf8_lossy(value);
+        for link in try!(parse_link_header(&value)).into_iter() {
+            if link.rels.iter().any(|rel| rel == "next") {
+                return Ok(Some(link.url));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::Headers;
+    use quickcheck::{quickcheck, Arbitrary, Gen};
+
+    use super::*;
+    use error::Error;
+
+    /// Relation types GitHub links its pages with, which the generated ones are mostly drawn
+    /// from.
+    const KNOWN_RELS: &'static [&'static str] = &["next", "last", "first", "prev"];
+
+    /// Chars a relation type is made of.
+    const REL_CHARS: &'static str = "abcdefghijklmnopqrstuvwxyz.-";
+
+    /// Chars a target is made of, which leave out `>` and whitespace.
+    const URL_CHARS: &'static str = "abcdefghijklmnopqrstuvwxyz0123456789:/?&=.;,\"";
+
+    #[derive(Clone, Debug)]
+    struct Rel(String);
+
+    impl Arbitrary for Rel {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            if g.gen_weighted_bool(4) {
+                let chars: Vec<char> = REL_CHARS.chars().collect();
+                let len = g.gen_range(1, 8);
+                Rel((0..len).map(|_| *g.choose(&chars).unwrap()).collect())
+            } else {
+                Rel(g.choose(KNOWN_RELS).unwrap().to_string())
+            }
+        }
+
+        fn shrink(&self) -> Box<Iterator<Item = Self>> {
+            Box::new(self.0
+                .shrink()
+                .filter(|s| !s.is_empty() && s.chars().all(|c| REL_CHARS.contains(c)))
+                .map(Rel))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct Url(String);
+
+    impl Arbitrary for Url {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            let chars: Vec<char> = URL_CHARS.chars().collect();
+            let len = g.gen_range(0, 40);
+            Url((0..len).map(|_| *g.choose(&chars).unwrap()).collect())
+        }
+
+        fn shrink(&self) -> Box<Iterator<Item = Self>> {
+            Box::new(self.0
+                .shrink()
+                .filter(|s| s.chars().all(|c| URL_CHARS.contains(c)))
+                .map(Url))
+        }
+    }
+
+    /// Writes a `Link` header of the links, quoting the `rel` parameter unless it is a single
+    /// relation type and `bare` is set.
+    fn format_links(links: &[(Url, Vec<Rel>, bool)]) -> String {
+        let links: Vec<String> = links.iter()
+            .map(|&(ref url, ref rels, bare)| {
+                let rels: Vec<&str> = rels.iter().map(|rel| &rel.0[..]).collect();
+                match rels.len() {
+                    0 => format!("<{}>", url.0),
+                    1 if bare => format!("<{}>; rel={}", url.0, rels[0]),
+                    _ => format!("<{}>; rel=\"{}\"", url.0, rels.join(" ")),
+                }
+            })
+            .collect();
+        links.join(", ")
+    }
+
+    #[test]
+    fn github_links_parse() {
+        let value = "<https://api.github.com/user/emails?page=2>; rel=\"next\", \
+                     <https://api.github.com/user/emails?page=3>; rel=\"last\"";
+        let links = parse_link_header(value).unwrap();
+        assert_eq!(links,
+                   vec![Link {
+                            url: "https://api.github.com/user/emails?page=2".to_string(),
+                            rels: vec!["next".to_string()],
+                        },
+                        Link {
+                            url: "https://api.github.com/user/emails?page=3".to_string(),
+                            rels: vec!["last".to_string()],
+                        }]);
+        let links = parse_link_header("<a>;title=\"x, y\";REL=\"Prev First\",<b>; rel=next")
+            .unwrap();
+        assert_eq!(links[0].rels, vec!["prev", "first"]);
+        assert_eq!(links[1].url, "b");
+        assert_eq!(links[1].rels, vec!["next"]);
+        assert!(parse_link_header("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn links_round_trip() {
+        fn prop(links: Vec<(Url, Vec<Rel>, bool)>) -> bool {
+            let expected: Vec<Link> = links.iter()
+                .map(|&(ref url, ref rels, _)| {
+                    Link {
+                        url: url.0.clone(),
+                        rels: rels.iter().map(|rel| rel.0.clone()).collect(),
+                    }
+                })
+                .collect();
+            parse_link_header(&format_links(&links)).ok() == Some(expected)
+        }
+        quickcheck(prop as fn(Vec<(Url, Vec<Rel>, bool)>) -> bool);
+    }
+
+    #[test]
+    fn malformed_links_fail() {
+        for value in &["https://api.github.com/user/emails?page=2; rel=\"next\"",
+                       "<https://api.github.com/user/emails?page=2; rel=\"next\"",
+                       "<a>; rel",
+                       "<a>; rel=\"next",
+                       "<a>; rel=next <b>"] {
+            match parse_link_header(value) {
+                Err(Error::InvalidLinkHeader(_)) => (),
+                other => panic!("{:?} parsed to {:?}", value, other),
+            }
+        }
+    }
+
+    #[test]
+    fn next_links_are_found_across_headers() {
+        let mut headers = Headers::new();
+        assert_eq!(next_link(&headers).unwrap(), None);
+        headers.set_raw("Link",
+                        vec![b"<p1>; rel=\"prev\"".to_vec(), b"<p3>; rel=\"next\"".to_vec()]);
+        assert_eq!(next_link(&headers).unwrap(), Some("p3".to_string()));
+        headers.set_raw("Link", vec![b"<p1>; rel=\"first\"".to_vec()]);
+        assert_eq!(next_link(&headers).unwrap(), None);
+        headers.set_raw("Link", vec![b"p1; rel=\"next\"".to_vec()]);
+        assert!(next_link(&headers).is_err());
+    }
+}
@@ -0,0 +1,825 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Outbound HTTP requests, configured by an `HttpCfg`.
+//!
+//! `client` builds a hyper client sending requests through the configured proxy with a TLS
+//! context built from the configured TLS settings. `ApiClient` builds the context once and
+//! requests on top of the client with the headers APIs expect, and retries
+//! idempotent requests which fail on the way or with a server error. Every attempt is traced,
+//! redacted, in a buffer of the most recent ones, see `trace`, after the client's decorators
+//! have had their say, see `decorate`.
+
+pub mod decorate;
+pub mod link;
+pub mod trace;
+
+pub use self::decorate::{DecoratorChain, OutboundRequest, RequestDecorator, StaticHeaders};
+pub use self::trace::{RequestTrace, TraceBuffer};
+
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use hab_http;
+use hab_http::net::ProxyHttpsConnector;
+use hab_http::proxy::ProxyInfo;
+use hcore::util::retry::{self, Backoff, RetryPolicy, Retryable};
+use hyper::{self, Url};
+use hyper::client::pool::{Config, Pool};
+use hyper::client::response::Response;
+use hyper::header::{qitem, Accept, Authorization, Basic, Bearer, Headers, UserAgent};
+use hyper::http::h1::Http11Protocol;
+use hyper::method::Method;
+use hyper::mime::{Mime, SubLevel, TopLevel};
+use hyper::net::{HttpsConnector, NetworkConnector, Openssl};
+use hyper::status::StatusCode;
+use openssl::ssl::{SslContext, SslMethod, SSL_OP_NO_COMPRESSION, SSL_OP_NO_SSLV2,
+                   SSL_OP_NO_SSLV3, SSL_VERIFY_NONE, SSL_VERIFY_PEER};
+
+use config::HttpCfg;
+use error::{Error, Result};
+use redact;
+
+/// Upper bound on the delay before retrying a request
+const MAX_BACKOFF_MS: u64 = 30_000;
+
+/// Build a client for requests to the given URL, over TLS with the given context, see
+/// `tls_context`. The request is sent through the configured proxy unless the URL's host is
+/// exempted from it. Establishing a connection, including the TLS handshake and the tunnel
+/// through the proxy, is bounded by the connect timeout, and reading or writing on it by the read
+/// timeout. Timeouts configured in `cfg` take precedence over the given defaults.
+///
+/// # Errors
+///
+/// * The configured proxy URL is invalid
+pub fn client(cfg: &HttpCfg,
+              tls: &Openssl,
+              url: &Url,
+              connect_timeout_ms: u64,
+              read_timeout_ms: u64)
+              -> Result<hyper::Client> {
+    let ssl = tls.clone();
+    let connect_timeout_ms = cfg.connect_timeout_ms.unwrap_or(connect_timeout_ms);
+    let mut client = match cfg.proxy_for_url(url) {
+        Some(proxy) => {
+            let proxy_url = try!(Url::parse(proxy).map_err(hab_http::Error::from));
+            let info = try!(ProxyInfo::new(proxy_url, None));
+            debug!("Using proxy {}:{} for {}",
+                   info.host(),
+                   info.port(),
+                   redact::redact_url(url));
+            let connector = try!(ProxyHttpsConnector::new(info, ssl));
+            let connector = TimedConnector::new(connector, connect_timeout_ms);
+            let pool = Pool::with_connector(Config::default(), connector);
+            hyper::Client::with_protocol(Http11Protocol::with_connector(pool))
+        }
+        None => {
+            let connector = TimedConnector::new(HttpsConnector::new(ssl), connect_timeout_ms);
+            let pool = Pool::with_connector(Config::default(), connector);
+            hyper::Client::with_protocol(Http11Protocol::with_connector(pool))
+        }
+    };
+    let read_timeout = Duration::from_millis(cfg.read_timeout_ms.unwrap_or(read_timeout_ms));
+    client.set_read_timeout(Some(read_timeout));
+    client.set_write_timeout(Some(read_timeout));
+    Ok(client)
+}
+
+/// Connects with another connector, giving up once a timeout has passed. hyper has no connect
+/// timeout of its own, so the connection is established on its own thread, which is left to finish
+/// or fail in the background if it takes too long.
+struct TimedConnector<C> {
+    inner: Arc<C>,
+    timeout_ms: u64,
+}
+
+impl<C> TimedConnector<C> {
+    fn new(inner: C, timeout_ms: u64) -> Self {
+        TimedConnector {
+            inner: Arc::new(inner),
+            timeout_ms: timeout_ms,
+        }
+    }
+}
+
+impl<C> NetworkConnector for TimedConnector<C>
+    where C: NetworkConnector + Send + Sync + 'static,
+          C::Stream: Send + 'static
+{
+    type Stream = C::Stream;
+
+    fn connect(&self, host: &str, port: u16, scheme: &str) -> hyper::Result<Self::Stream> {
+        let (tx, rx) = mpsc::channel();
+        let inner = self.inner.clone();
+        let (connect_host, connect_scheme) = (host.to_string(), scheme.to_string());
+        try!(thread::Builder::new()
+            .name("http-connect".to_string())
+            .spawn(move || {
+                let _ = tx.send(inner.connect(&connect_host, port, &connect_scheme));
+            }));
+        match rx.recv_timeout(Duration::from_millis(self.timeout_ms)) {
+            Ok(connected) => connected,
+            Err(RecvTimeoutError::Timeout) => {
+                let msg = format!("connecting to {}:{} took longer than {}ms",
+                                  host,
+                                  port,
+                                  self.timeout_ms);
+                Err(hyper::Error::Io(io::Error::new(io::ErrorKind::TimedOut, msg)))
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                let msg = format!("connecting to {}:{} was abandoned", host, port);
+                Err(hyper::Error::Io(io::Error::new(io::ErrorKind::Other, msg)))
+            }
+        }
+    }
+}
+
+/// How an `ApiClient` identifies itself, how long it waits, and how it retries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiOptions {
+    /// Value of the `User-Agent` header of every request
+    pub user_agent: String,
+    /// Upper bound on establishing a connection, unless the `HttpCfg` sets one
+    pub connect_timeout_ms: u64,
+    /// Upper bound on waiting for a response, unless the `HttpCfg` sets one
+    pub read_timeout_ms: u64,
+    /// Number of times an idempotent request which failed is retried before giving up
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each subsequent attempt
+    pub base_backoff_ms: u64,
+    /// Number of the most recent attempts traced, 0 to trace none
+    pub trace_capacity: usize,
+}
+
+/// Sends requests to HTTP APIs through the proxy and with the TLS settings of an `HttpCfg`.
+#[derive(Clone, Debug)]
+pub struct ApiClient {
+    cfg: HttpCfg,
+    options: ApiOptions,
+    traces: Arc<TraceBuffer>,
+    decorators: DecoratorChain,
+    tls: Arc<TlsCache>,
+}
+
+/// TLS context of an `ApiClient`, built for its first request and reused by every later one, so
+/// the CA bundle is read once.
+#[derive(Default)]
+struct TlsCache {
+    context: Mutex<Option<Openssl>>,
+}
+
+impl fmt::Debug for TlsCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let built = self.context.lock().map(|c| c.is_some()).unwrap_or(false);
+        f.debug_struct("TlsCache").field("built", &built).finish()
+    }
+}
+
+impl ApiClient {
+    /// Create a client whose requests carry the headers of `cfg.headers`.
+    pub fn new(cfg: HttpCfg, options: ApiOptions) -> Self {
+        let traces = Arc::new(TraceBuffer::new(options.trace_capacity));
+        let decorators = DecoratorChain::for_cfg(&cfg);
+        ApiClient {
+            cfg: cfg,
+            options: options,
+            traces: traces,
+            decorators: decorators,
+            tls: Arc::new(TlsCache::default()),
+        }
+    }
+
+    /// Applies the given decorator to every request after the decorators added before it.
+    pub fn add_decorator(&mut self, decorator: Arc<RequestDecorator>) {
+        self.decorators.push(decorator);
+    }
+
+    /// Returns the most recent attempts at requests, oldest first.
+    pub fn recent_requests(&self) -> Vec<RequestTrace> {
+        self.traces.recent()
+    }
+
+    /// Traces into the buffer of `other`, keeping its history, if both trace as many attempts.
+    pub fn share_traces(&mut self, other: &ApiClient) {
+        if self.traces.capacity() == other.traces.capacity() {
+            self.traces = other.traces.clone();
+        }
+    }
+
+    /// Reuses the TLS context of `other` if both verify servers the same way, so a client
+    /// rebuilt for another proxy or set of headers doesn't read the CA bundle again.
+    pub fn share_tls(&mut self, other: &ApiClient) {
+        if self.cfg.ca_bundle == other.cfg.ca_bundle && self.cfg.insecure == other.cfg.insecure {
+            self.tls = other.tls.clone();
+        }
+    }
+
+    /// Returns the TLS context of this client, building it if no request has needed it yet.
+    ///
+    /// # Errors
+    ///
+    /// * The TLS context could not be created or the CA bundle could not be loaded
+    fn tls(&self) -> Result<Openssl> {
+        let mut context = self.tls.context.lock().unwrap();
+        if let Some(ref tls) = *context {
+            return Ok(tls.clone());
+        }
+        let tls = try!(tls_context(&self.cfg));
+        *context = Some(tls.clone());
+        Ok(tls)
+    }
+
+    /// Builds a GET request for the given URL.
+    pub fn get(&self, url: Url) -> ApiRequest {
+        self.request(Method::Get, url)
+    }
+
+    /// Builds a POST request for the given URL. POST requests are never retried.
+    pub fn post(&self, url: Url) -> ApiRequest {
+        self.request(Method::Post, url)
+    }
+
+    /// Builds a request with the given method for the given URL.
+    pub fn request(&self, method: Method, url: Url) -> ApiRequest {
+        let mut headers = Headers::new();
+        headers.set(UserAgent(self.options.user_agent.clone()));
+        ApiRequest {
+            client: self,
+            method: method,
+            url: url,
+            headers: headers,
+            body: vec![],
+        }
+    }
+}
+
+/// A request of an `ApiClient` being built. Nothing is sent until `send`.
+pub struct ApiRequest<'a> {
+    client: &'a ApiClient,
+    method: Method,
+    url: Url,
+    headers: Headers,
+    body: Vec<u8>,
+}
+
+impl<'a> ApiRequest<'a> {
+    /// Asks for a JSON response.
+    pub fn accept_json(mut self) -> Self {
+        self.headers.set(Accept(vec![qitem(Mime(TopLevel::Application, SubLevel::Json, vec![]))]));
+        self
+    }
+
+    /// Authorizes the request with a bearer token.
+    pub fn bearer(mut self, token: &str) -> Self {
+        self.headers.set(Authorization(Bearer { token: token.to_string() }));
+        self
+    }
+
+    /// Authorizes the request with a username and password.
+    pub fn basic(mut self, username: &str, password: &str) -> Self {
+        self.headers.set(Authorization(Basic {
+            username: username.to_string(),
+            password: Some(password.to_string()),
+        }));
+        self
+    }
+
+    /// Sets a header hyper has no type for, such as `If-None-Match`.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.set_raw(name.to_string(), vec![value.as_bytes().to_vec()]);
+        self
+    }
+
+    /// Sets the body of the request.
+    pub fn body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Sends the request, and returns the response whatever its status.
+    ///
+    /// GET, HEAD, PUT, DELETE, and OPTIONS requests which fail on the way or are answered with
+    /// a server error are sent again up to `max_retries` times, after a delay doubling from
+    /// `base_backoff_ms` on each attempt. The response to the last attempt is returned.
+    ///
+    /// # Errors
+    ///
+    /// * The client could not be built, see `client` and `tls_context`
+    /// * A decorator refused the request
+    /// * The last attempt failed on the way
+    pub fn send(self) -> Result<ApiResponse> {
+        let options = &self.client.options;
+        let retries = if is_idempotent(&self.method) {
+            options.max_retries
+        } else {
+            0
+        };
+        let policy = RetryPolicy::new(Backoff::Exponential {
+                                          base: Duration::from_millis(options.base_backoff_ms),
+                                          max: Duration::from_millis(MAX_BACKOFF_MS),
+                                      },
+                                      retries);
+        // A server error is answered, so it fails the attempt with the response in place of an
+        // error, which is returned as the response if it is the last
+        let sent = retry::retry(&policy, classify_failure, |attempt| {
+            if attempt > 1 {
+                debug!("Retrying {} {}, attempt {} of {}",
+                       self.method,
+                       redact::redact_url(&self.url),
+                       attempt,
+                       retries + 1);
+            }
+            match self.send_once(attempt) {
+                Ok(rep) => {
+                    if rep.status.is_server_error() {
+                        Err(Ok(rep))
+                    } else {
+                        Ok(rep)
+                    }
+                }
+                Err(err) => Err(Err(err)),
+            }
+        });
+        match sent {
+            Ok(rep) => Ok(rep),
+            Err(failure) => failure.into_inner(),
+        }
+    }
+
+    fn send_once(&self, attempt: u32) -> Result<ApiResponse> {
+        let tls = try!(self.client.tls());
+        let client = try!(client(&self.client.cfg,
+                                 &tls,
+                                 &self.url,
+                                 self.client.options.connect_timeout_ms,
+                                 self.client.options.read_timeout_ms));
+        let mut req = OutboundRequest {
+            method: self.method.clone(),
+            url: self.url.clone(),
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+        };
+        let at = SystemTime::now();
+        let started = Instant::now();
+        let result = self.client.decorators.decorate(&mut req).and_then(|()| {
+            let mut builder = client.request(req.method.clone(), req.url.clone())
+                .headers(req.headers.clone());
+            if !req.body.is_empty() {
+                builder = builder.body(&req.body[..]);
+            }
+            builder.send()
+                .map_err(Error::from)
+                .and_then(ApiResponse::from_hyper)
+                .map_err(|err| timed_out(err, &req.url))
+        });
+        if self.client.traces.is_enabled() {
+            self.client.traces.record(RequestTrace {
+                at: at,
+                method: req.method.to_string(),
+                url: redact::redact_url(&req.url),
+                request_headers: redact::redact_headers(&req.headers),
+                attempt: attempt,
+                status: result.as_ref().ok().map(|rep| rep.status.to_u16()),
+                error: result.as_ref().err().map(|err| err.to_string()),
+                duration: started.elapsed(),
+                response_body: result.as_ref()
+                    .ok()
+                    .map(|rep| trace::redact_body(&String::from_utf8_lossy(rep.body.get_ref()))),
+            });
+        }
+        result
+    }
+}
+
+/// A response of an `ApiClient`, read in full so it can be traced.
+pub struct ApiResponse {
+    /// URL the response came from, after any redirects
+    pub url: Url,
+    pub status: StatusCode,
+    pub headers: Headers,
+    body: io::Cursor<Vec<u8>>,
+}
+
+impl ApiResponse {
+    fn from_hyper(mut rep: Response) -> Result<Self> {
+        let mut body = vec![];
+        try!(rep.read_to_end(&mut body));
+        Ok(ApiResponse {
+            url: rep.url.clone(),
+            status: rep.status,
+            headers: rep.headers.clone(),
+            body: io::Cursor::new(body),
+        })
+    }
+}
+
+impl Read for ApiResponse {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.body.read(buf)
+    }
+}
+
+/// Returns a `GitHubTimeout` for an error of a socket whose timeout expired, which reads and
+/// writes report as `TimedOut` or, on some platforms, `WouldBlock`, and the error otherwise.
+fn timed_out(err: Error, url: &Url) -> Error {
+    let expired = match err {
+        Error::HyperError(hyper::Error::Io(ref e)) |
+        Error::IO(ref e) => {
+            e.kind() == io::ErrorKind::TimedOut || e.kind() == io::ErrorKind::WouldBlock
+        }
+        _ => false,
+    };
+    if expired {
+        Error::GitHubTimeout(redact::redact_url(url))
+    } else {
+        err
+    }
+}
+
+/// Classifies a failed attempt, a server error or an error on the way, for `send`.
+fn classify_failure(failure: &Result<ApiResponse>) -> Retryable {
+    match *failure {
+        Ok(_) |
+        Err(Error::GitHubTimeout(_)) |
+        Err(Error::HyperError(_)) => Retryable::Transient,
+        Err(_) => Retryable::Permanent,
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    match *method {
+        Method::Get | Method::Head | Method::Put | Method::Delete | Method::Options => true,
+        _ => false,
+    }
+}
+
+/// Returns the delay before the retry following the given attempt, counting from 1, which
+/// doubles from `base_ms` on each attempt up to `MAX_BACKOFF_MS`.
+pub fn backoff(base_ms: u64, attempt: u32) -> Duration {
+    let backoff = Backoff::Exponential {
+        base: Duration::from_millis(base_ms),
+        max: Duration::from_millis(MAX_BACKOFF_MS),
+    };
+    backoff.delay(attempt)
+}
+
+/// Build the TLS context of the given configuration, which verifies servers against the
+/// configured CA bundle or the system's certificates, unless verification is disabled.
+///
+/// # Errors
+///
+/// * The TLS context could not be created or the CA bundle could not be loaded
+pub fn tls_context(cfg: &HttpCfg) -> Result<Openssl> {
+    let ctx = try!(ssl_ctx(cfg));
+    Ok(Openssl { context: Arc::new(ctx) })
+}
+
+fn ssl_ctx(cfg: &HttpCfg) -> Result<SslContext> {
+    let mut ctx = try!(SslContext::new(SslMethod::Sslv23).map_err(hab_http::Error::from));
+    match cfg.ca_bundle {
+        Some(ref path) => try!(ctx.set_CA_file(path).map_err(hab_http::Error::from)),
+        None => try!(ctx.set_default_verify_paths().map_err(hab_http::Error::from)),
+    }
+    if cfg.insecure {
+        warn!("TLS certificate verification is disabled for outbound HTTP requests");
+        ctx.set_verify(SSL_VERIFY_NONE, None);
+    } else {
+        ctx.set_verify(SSL_VERIFY_PEER, None);
+    }
+    ctx.set_options(SSL_OP_NO_SSLV2 | SSL_OP_NO_SSLV3 | SSL_OP_NO_COMPRESSION);
+    try!(ctx.set_cipher_list("ALL!EXPORT!EXPORT40!EXPORT56!aNULL!LOW!RC4@STRENGTH")
+        .map_err(hab_http::Error::from));
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use hyper::Url;
+    use hyper::status::StatusCode;
+
+    use super::*;
+    use config::HttpCfg;
+    use error::{Error, Result};
+
+    /// How the test server answers one request.
+    #[derive(Clone, Copy)]
+    enum Answer {
+        Status(u16),
+        /// Close the connection without answering
+        Hangup,
+        /// Keep the connection open without answering for longer than the read timeout
+        Stall,
+    }
+
+    /// Starts a server answering requests with `answers` in turn, and returns its URL and the
+    /// head of every request it received.
+    fn serve(answers: Vec<Answer>) -> (Url, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/things", listener.local_addr().unwrap()))
+            .unwrap();
+        let heads = Arc::new(Mutex::new(vec![]));
+        let received = heads.clone();
+        thread::spawn(move || {
+            for (stream, answer) in listener.incoming().zip(answers.into_iter()) {
+                let mut stream = stream.unwrap();
+                let mut head = vec![];
+                let mut byte = [0; 1];
+                while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap() == 1 {
+                    head.push(byte[0]);
+                }
+                received.lock().unwrap().push(String::from_utf8_lossy(&head).into_owned());
+                if let Answer::Status(status) = answer {
+                    write!(stream,
+                           "HTTP/1.1 {} Test\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                           status)
+                        .unwrap();
+                } else if let Answer::Stall = answer {
+                    thread::sleep(Duration::from_millis(1_500));
+                }
+            }
+        });
+        (url, heads)
+    }
+
+    fn api(max_retries: u32) -> ApiClient {
+        let mut cfg = HttpCfg::default();
+        cfg.proxy = None;
+        api_with(cfg, max_retries)
+    }
+
+    fn api_with(cfg: HttpCfg, max_retries: u32) -> ApiClient {
+        ApiClient::new(cfg,
+                       ApiOptions {
+                           user_agent: "test-agent".to_string(),
+                           connect_timeout_ms: 1_000,
+                           read_timeout_ms: 1_000,
+                           max_retries: max_retries,
+                           base_backoff_ms: 1,
+                           trace_capacity: 8,
+                       })
+    }
+
+    #[test]
+    fn requests_carry_the_headers() {
+        let (url, heads) = serve(vec![Answer::Status(200)]);
+        let rep = api(0)
+            .get(url)
+            .accept_json()
+            .bearer("secret")
+            .header("If-None-Match", "\"abc\"")
+            .send()
+            .unwrap();
+        assert_eq!(rep.status, StatusCode::Ok);
+        let head = heads.lock().unwrap()[0].to_lowercase();
+        assert!(head.starts_with("get /things http/1.1\r\n"), "{}", head);
+        assert!(head.contains("user-agent: test-agent\r\n"), "{}", head);
+        assert!(head.contains("accept: application/json\r\n"), "{}", head);
+        assert!(head.contains("authorization: bearer secret\r\n"), "{}", head);
+        assert!(head.contains("if-none-match: \"abc\"\r\n"), "{}", head);
+    }
+
+    #[test]
+    fn basic_authorization() {
+        let (url, heads) = serve(vec![Answer::Status(204)]);
+        api(0).request(Method::Delete, url).basic("id", "secret").send().unwrap();
+        // base64 of "id:secret"
+        assert!(heads.lock().unwrap()[0].contains("Authorization: Basic aWQ6c2VjcmV0\r\n"));
+    }
+
+    #[test]
+    fn server_errors_are_retried() {
+        let answers = vec![Answer::Status(503), Answer::Status(502), Answer::Status(200)];
+        let (url, heads) = serve(answers);
+        let rep = api(2).get(url).send().unwrap();
+        assert_eq!(rep.status, StatusCode::Ok);
+        assert_eq!(heads.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn dropped_connections_are_retried() {
+        let (url, heads) = serve(vec![Answer::Hangup, Answer::Status(200)]);
+        let rep = api(1).get(url).send().unwrap();
+        assert_eq!(rep.status, StatusCode::Ok);
+        assert_eq!(heads.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn stalled_responses_time_out() {
+        let (url, heads) = serve(vec![Answer::Stall, Answer::Status(200)]);
+        match api(0).get(url.clone()).send() {
+            Err(Error::GitHubTimeout(ref target)) => assert!(target.ends_with("/things")),
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(rep) => panic!("the request was answered, {}", rep.status),
+        }
+        assert_eq!(api(0).get(url).send().unwrap().status, StatusCode::Ok);
+        assert_eq!(heads.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn connecting_times_out() {
+        // Nothing answers on this address, so connecting hangs unless the network rejects it
+        let url = Url::parse("http://10.255.255.1/things").unwrap();
+        let mut cfg = HttpCfg::default();
+        cfg.proxy = None;
+        cfg.connect_timeout_ms = Some(200);
+        let started = Instant::now();
+        match api_with(cfg, 0).get(url).send() {
+            Err(Error::GitHubTimeout(ref target)) => assert!(target.ends_with("/things")),
+            Err(_) => (),
+            Ok(rep) => panic!("the request was answered, {}", rep.status),
+        }
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn the_last_response_is_returned_once_retries_run_out() {
+        let (url, heads) = serve(vec![Answer::Status(503), Answer::Status(500)]);
+        let rep = api(1).get(url).send().unwrap();
+        assert_eq!(rep.status, StatusCode::InternalServerError);
+        assert_eq!(heads.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn client_errors_and_posts_are_not_retried() {
+        let (url, heads) = serve(vec![Answer::Status(404), Answer::Status(503)]);
+        assert_eq!(api(3).get(url.clone()).send().unwrap().status, StatusCode::NotFound);
+        assert_eq!(api(3).post(url).send().unwrap().status,
+                   StatusCode::ServiceUnavailable);
+        assert_eq!(heads.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn attempts_are_traced_redacted() {
+        let (url, _) = serve(vec![Answer::Status(503), Answer::Status(200)]);
+        let url = Url::parse(&format!("{}?access_token=secret", url)).unwrap();
+        let api = api(1);
+        api.get(url).bearer("secret").send().unwrap();
+        let traces = api.recent_requests();
+        assert_eq!(traces.iter().map(|trace| trace.status).collect::<Vec<_>>(),
+                   vec![Some(503), Some(200)]);
+        assert_eq!(traces.iter().map(|trace| trace.attempt).collect::<Vec<_>>(),
+                   vec![1, 2]);
+        for trace in traces {
+            assert_eq!(trace.method, "GET");
+            assert!(trace.url.ends_with("/things?access_token=REDACTED"), "{}", trace.url);
+            assert!(trace.request_headers
+                .contains(&("Authorization".to_string(), "REDACTED".to_string())));
+            assert_eq!(trace.response_body, Some(String::new()));
+        }
+    }
+
+    #[test]
+    fn failed_attempts_are_traced() {
+        let (url, _) = serve(vec![Answer::Hangup]);
+        let api = api(0);
+        assert!(api.get(url).send().is_err());
+        let traces = api.recent_requests();
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].status, None);
+        assert!(traces[0].error.is_some());
+    }
+
+    #[test]
+    fn shared_traces_are_kept() {
+        let (url, _) = serve(vec![Answer::Status(200)]);
+        let old = api(0);
+        old.get(url).send().unwrap();
+        let mut new = api(0);
+        new.share_traces(&old);
+        assert_eq!(new.recent_requests().len(), 1);
+    }
+
+    /// Records the requests it sees and signs them with a header naming their URL.
+    #[derive(Default)]
+    struct Signer {
+        seen: Mutex<Vec<(String, String, Option<String>, Vec<u8>)>>,
+    }
+
+    impl RequestDecorator for Signer {
+        fn decorate(&self, req: &mut OutboundRequest) -> Result<()> {
+            let accept = req.headers
+                .get_raw("Accept")
+                .map(|values| String::from_utf8_lossy(&values[0]).into_owned());
+            self.seen
+                .lock()
+                .unwrap()
+                .push((req.method.to_string(), req.url.to_string(), accept, req.body.clone()));
+            let signature = format!("sig:{}", req.url.path());
+            req.headers.set_raw("X-Signature", vec![signature.into_bytes()]);
+            Ok(())
+        }
+    }
+
+    struct Refuser;
+
+    impl RequestDecorator for Refuser {
+        fn decorate(&self, _req: &mut OutboundRequest) -> Result<()> {
+            Err(Error::RequestRefused("no signing key".to_string()))
+        }
+    }
+
+    #[test]
+    fn decorators_see_the_final_request() {
+        let (url, heads) = serve(vec![Answer::Status(200), Answer::Status(200)]);
+        let url = Url::parse(&format!("{}?page=2", url)).unwrap();
+        let signer = Arc::new(Signer::default());
+        let mut api = api(0);
+        api.add_decorator(signer.clone());
+        api.get(url.clone()).accept_json().send().unwrap();
+        api.post(url.clone()).body(b"name=core".to_vec()).send().unwrap();
+        let seen = signer.seen.lock().unwrap();
+        assert_eq!(seen[0],
+                   ("GET".to_string(),
+                    url.to_string(),
+                    Some("application/json".to_string()),
+                    vec![]));
+        assert_eq!(seen[1],
+                   ("POST".to_string(), url.to_string(), None, b"name=core".to_vec()));
+        let heads = heads.lock().unwrap();
+        assert!(heads[0].contains("X-Signature: sig:/things\r\n"), "{}", heads[0]);
+        let traces = api.recent_requests();
+        assert!(traces[0]
+            .request_headers
+            .contains(&("X-Signature".to_string(), "sig:/things".to_string())));
+    }
+
+    #[test]
+    fn refused_requests_are_not_sent() {
+        let (url, heads) = serve(vec![Answer::Status(200)]);
+        let mut api = api(3);
+        api.add_decorator(Arc::new(Signer::default()));
+        api.add_decorator(Arc::new(Refuser));
+        match api.get(url).send() {
+            Err(Error::RequestRefused(ref reason)) => assert_eq!(reason, "no signing key"),
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(rep) => panic!("the request was sent, {}", rep.status),
+        }
+        thread::sleep(Duration::from_millis(50));
+        assert!(heads.lock().unwrap().is_empty());
+        assert_eq!(api.recent_requests().len(), 1);
+    }
+
+    #[test]
+    fn configured_headers_are_sent() {
+        let (url, heads) = serve(vec![Answer::Status(200)]);
+        let mut cfg = HttpCfg::default();
+        cfg.proxy = None;
+        cfg.headers = vec![("X-Proxy-Key".to_string(), "abc".to_string())];
+        api_with(cfg, 0).get(url).send().unwrap();
+        assert!(heads.lock().unwrap()[0].contains("X-Proxy-Key: abc\r\n"));
+    }
+
+    #[test]
+    fn the_tls_context_is_built_once() {
+        fn same(a: &Openssl, b: &Openssl) -> bool {
+            &*a.context as *const _ == &*b.context as *const _
+        }
+        let (url, _) = serve(vec![Answer::Status(200), Answer::Status(200)]);
+        let first = api(0);
+        first.get(url.clone()).send().unwrap();
+        first.get(url).send().unwrap();
+        let built = first.tls().unwrap();
+        assert!(same(&built, &first.tls().unwrap()));
+
+        let mut reloaded = api(0);
+        reloaded.share_tls(&first);
+        assert!(same(&built, &reloaded.tls().unwrap()));
+
+        let mut cfg = HttpCfg::default();
+        cfg.proxy = None;
+        cfg.insecure = true;
+        let mut insecure = api_with(cfg, 0);
+        insecure.share_tls(&first);
+        assert!(!same(&built, &insecure.tls().unwrap()));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_limit() {
+        assert_eq!(backoff(100, 1), Duration::from_millis(100));
+        assert_eq!(backoff(100, 2), Duration::from_millis(200));
+        assert_eq!(backoff(100, 4), Duration::from_millis(800));
+        assert_eq!(backoff(100, 20), Duration::from_millis(MAX_BACKOFF_MS));
+        assert_eq!(backoff(100, 200), Duration::from_millis(MAX_BACKOFF_MS));
+    }
+}
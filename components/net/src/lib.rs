@@ -14,6 +14,10 @@
 
 extern crate fnv;
 extern crate habitat_builder_protocol as protocol;
+extern crate habitat_core as hcore;
+#[cfg(feature = "client")]
+extern crate habitat_http_client as hab_http;
+#[cfg(feature = "client")]
 extern crate hyper;
 #[macro_use]
 extern crate lazy_static;
@@ -21,42 +25,46 @@ extern crate libc;
 #[macro_use]
 extern crate log;
 extern crate num_cpus;
+extern crate openssl;
 extern crate protobuf;
+#[cfg(test)]
+extern crate quickcheck;
 extern crate rustc_serialize;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+#[cfg(test)]
+extern crate tempdir;
 extern crate time;
+extern crate toml;
+extern crate url;
 extern crate zmq;
 
+#[cfg(feature = "serde")]
+#[macro_use]
+mod serde_bridge;
+
+pub mod analytics;
 pub mod config;
 pub mod error;
 pub mod dispatcher;
+#[cfg(feature = "client")]
+pub mod http;
 pub mod oauth;
+pub mod redact;
 pub mod routing;
 pub mod server;
+pub mod session;
 pub mod supervisor;
 
-use std::process::Command;
-
 pub use self::error::{Error, Result};
 pub use self::server::{Application, ServerReg};
 pub use self::supervisor::Supervisor;
 
+/// Returns the host name of this host.
 pub fn hostname() -> Result<String> {
-    let output = try!(Command::new("sh")
-        .arg("-c")
-        .arg("hostname | awk '{printf \"%s\", $NF; exit}'")
-        .output());
-    match output.status.success() {
-        true => {
-            debug!("Hostname address is {}",
-                   String::from_utf8_lossy(&output.stdout));
-            let hostname = try!(String::from_utf8(output.stdout).or(Err(Error::Sys)));
-            Ok(hostname)
-        }
-        false => {
-            debug!("Hostname address command returned: OUT: {} ERR: {}",
-                   String::from_utf8_lossy(&output.stdout),
-                   String::from_utf8_lossy(&output.stderr));
-            Err(Error::Sys)
-        }
-    }
+    let hostname = try!(hcore::util::sys::hostname());
+    debug!("Hostname is {}", hostname);
+    Ok(hostname)
 }
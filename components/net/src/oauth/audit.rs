@@ -0,0 +1,447 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Audit trail of login attempts.
+//!
+//! `OAuthProvider::login_audited` hands the outcome of each login to an `Auditor`, which records
+//! an `AuditEvent` in its `AuditSink`: when the attempt ended, through which OAuth application,
+//! how, and who it was for once that is known. The user is named by their GitHub id and their
+//! pseudonymous analytics id only. Codes, tokens, logins and email addresses never enter an
+//! event, and failures are described by an error code rather than the error's message, which may
+//! quote what GitHub answered.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use protocol::sessionsrv;
+use rustc_serialize::{Encodable, Encoder};
+use rustc_serialize::json;
+
+use analytics::AnalyticsId;
+use error::{Error, Result};
+use super::{LoginOutcome, OAuthProvider};
+use super::token;
+
+/// Size of the audit log past which `FileSink` rotates it, in bytes
+pub const DEFAULT_AUDIT_LOG_MAX_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Rotated audit logs `FileSink` keeps
+pub const DEFAULT_AUDIT_LOG_KEEP: usize = 5;
+
+/// Longest error code an event carries. Codes taken from GitHub's answers are cut to it.
+const MAX_ERROR_CODE_LEN: usize = 64;
+
+/// How a login attempt ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditOutcome {
+    /// The user's whole profile was fetched and they may create a session
+    Success,
+    /// The user may create a session, but some sections of their profile couldn't be fetched
+    Partial,
+    /// The user was looked up but the access policy refused them, or the team policy doesn't
+    /// make them a member
+    Denied,
+    /// The login failed before the user's privileges were known
+    Failed,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            AuditOutcome::Success => "success",
+            AuditOutcome::Partial => "partial",
+            AuditOutcome::Denied => "denied",
+            AuditOutcome::Failed => "failed",
+        }
+    }
+}
+
+impl Encodable for AuditOutcome {
+    fn encode<S: Encoder>(&self, s: &mut S) -> ::std::result::Result<(), S::Error> {
+        s.emit_str(self.as_str())
+    }
+}
+
+/// A login attempt, as recorded in the audit trail.
+#[derive(Clone, Debug, PartialEq, RustcEncodable)]
+pub struct AuditEvent {
+    /// When the attempt ended, in seconds since the epoch
+    pub timestamp: u64,
+    /// Kind of identity provider, such as `github`
+    pub provider: String,
+    /// Host of the identity provider, such as `github.com`
+    pub provider_host: String,
+    /// Client id of the OAuth application the code was issued to
+    pub client_id: String,
+    pub outcome: AuditOutcome,
+    /// Why the attempt didn't succeed, see `error_code`
+    pub error_code: Option<String>,
+    /// GitHub id of the user, once they were looked up
+    pub extern_id: Option<u64>,
+    /// Pseudonymous analytics id of the user, if they were looked up and analytics are enabled
+    pub analytics_id: Option<String>,
+}
+
+impl AuditEvent {
+    /// Returns the event as a line of JSON, ended by a newline.
+    pub fn to_json_line(&self) -> String {
+        let mut line = json::encode(self).expect("audit events always encode");
+        line.push('\n');
+        line
+    }
+}
+
+/// Returns the code recorded for a login which failed with `err`. GitHub's own code is kept for
+/// a rejected OAuth code, such as `auth.bad_verification_code`; other errors are named by their
+/// kind.
+pub fn error_code(err: &Error) -> String {
+    match *err {
+        Error::AccessDenied(ref reason) => format!("access_denied.{}", reason.code()),
+        Error::Auth(ref e) => {
+            let code: String = e.error
+                .chars()
+                .filter(|c| c.is_alphanumeric() || *c == '_')
+                .take(MAX_ERROR_CODE_LEN)
+                .collect();
+            format!("auth.{}", code)
+        }
+        Error::CircuitOpen(_) => "circuit_open".to_string(),
+        Error::GitHubAPI(_) => "github_api".to_string(),
+        Error::GitHubRateLimited { .. } => "rate_limited".to_string(),
+        Error::GitHubTimeout(_) => "timeout".to_string(),
+        #[cfg(feature = "client")]
+        Error::HTTP(status) => format!("http_{}", status.to_u16()),
+        #[cfg(feature = "client")]
+        Error::HttpClient(_) |
+        Error::HyperError(_) => "transport".to_string(),
+        Error::IO(_) => "io".to_string(),
+        Error::InvalidLinkHeader(_) |
+        Error::JsonDecode(_) => "bad_reply".to_string(),
+        Error::MissingScope(_) => "missing_scope".to_string(),
+        Error::OrgAccessDenied(_) => "org_access_denied".to_string(),
+        Error::RequestRefused(_) => "request_refused".to_string(),
+        Error::TokenClientMismatch(_) => "token_client_mismatch".to_string(),
+        Error::TokenRevoked => "token_revoked".to_string(),
+        Error::TooManyPages(_, _) => "too_many_pages".to_string(),
+        _ => "other".to_string(),
+    }
+}
+
+/// Where audit events are recorded. Recording never fails: a sink which can't record an event
+/// logs why and drops it, so an unavailable audit trail doesn't stop logins.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, event: &AuditEvent);
+}
+
+/// A sink which drops every event, used when no audit log is configured.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopSink;
+
+impl AuditSink for NoopSink {
+    fn record(&self, _event: &AuditEvent) {}
+}
+
+/// Returns a shared `NoopSink`.
+pub fn noop() -> Arc<AuditSink> {
+    Arc::new(NoopSink)
+}
+
+/// A sink appending each event as a line of JSON to a file, readable by its owner only.
+///
+/// A line is written with a single append to a file opened for appending, so lines of concurrent
+/// logins, or of other processes appending to the same file, don't interleave. Once the file
+/// would grow past `max_bytes` it is rotated: `audit.log` becomes `audit.log.1`, `audit.log.1`
+/// becomes `audit.log.2` and so on, and the oldest of the `keep` rotated files is dropped.
+pub struct FileSink {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: Mutex<File>,
+}
+
+impl FileSink {
+    /// Returns a sink appending to the file at `path`, which is created if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// * The file can't be opened for appending
+    pub fn new<P: AsRef<Path>>(path: P, max_bytes: u64, keep: usize) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = try!(open_append(&path));
+        Ok(FileSink {
+            path: path,
+            max_bytes: max_bytes,
+            keep: keep,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Path of the file events are appended to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn append(&self, line: &str) -> io::Result<()> {
+        let mut file = self.file.lock().expect("FileSink lock poisoned");
+        let len = try!(file.metadata()).len();
+        if len > 0 && len + line.len() as u64 > self.max_bytes {
+            try!(self.rotate());
+            *file = try!(open_append(&self.path));
+        }
+        file.write_all(line.as_bytes())
+    }
+
+    /// Shifts the rotated files up by one and moves the current file in as the first of them.
+    fn rotate(&self) -> io::Result<()> {
+        if self.keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+        for i in (1..self.keep).rev() {
+            match fs::rename(self.rotated(i), self.rotated(i + 1)) {
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+                other => try!(other),
+            }
+        }
+        fs::rename(&self.path, self.rotated(1))
+    }
+
+    fn rotated(&self, i: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_os_string();
+        name.push(format!(".{}", i));
+        PathBuf::from(name)
+    }
+}
+
+impl AuditSink for FileSink {
+    fn record(&self, event: &AuditEvent) {
+        if let Err(e) = self.append(&event.to_json_line()) {
+            warn!("unable to record audit event in {}, err={}",
+                  self.path.display(),
+                  e);
+        }
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options.open(path)
+}
+
+/// Records the outcomes of logins in a sink.
+#[derive(Clone)]
+pub struct Auditor {
+    sink: Arc<AuditSink>,
+    analytics_salt: Option<String>,
+}
+
+impl Auditor {
+    /// Returns an auditor recording in `sink`, naming users by the analytics id derived from
+    /// `analytics_salt` too if one is given.
+    pub fn new(sink: Arc<AuditSink>, analytics_salt: Option<String>) -> Self {
+        Auditor {
+            sink: sink,
+            analytics_salt: analytics_salt,
+        }
+    }
+
+    /// Returns an auditor which records nothing.
+    pub fn disabled() -> Self {
+        Auditor::new(noop(), None)
+    }
+
+    /// Records the outcome of a login through `provider`. A login refused by the access policy,
+    /// or whose owner the team policy doesn't make a member, is `Denied`, as no session can be
+    /// created from it.
+    pub fn login<P: OAuthProvider + ?Sized>(&self, provider: &P, outcome: &Result<LoginOutcome>) {
+        let (outcome, error_code, account) = match *outcome {
+            Ok(ref outcome) => {
+                let (login, partial) = match *outcome {
+                    LoginOutcome::Complete(ref login) => (login, false),
+                    LoginOutcome::Partial(ref partial) => (&partial.login, true),
+                };
+                if !login.flags.contains(sessionsrv::MEMBER) {
+                    (AuditOutcome::Denied, Some("not_a_member".to_string()), Some(&login.account))
+                } else if partial {
+                    (AuditOutcome::Partial, None, Some(&login.account))
+                } else {
+                    (AuditOutcome::Success, None, Some(&login.account))
+                }
+            }
+            Err(ref e @ Error::AccessDenied(_)) => {
+                (AuditOutcome::Denied, Some(error_code(e)), None)
+            }
+            Err(ref e) => (AuditOutcome::Failed, Some(error_code(e)), None),
+        };
+        let extern_id = match account {
+            Some(account) if account.has_extern_id() => Some(account.get_extern_id()),
+            _ => None,
+        };
+        let analytics_id = match (account, self.analytics_salt.as_ref()) {
+            (Some(account), Some(salt)) => account.analytics_id(salt),
+            _ => None,
+        };
+        self.sink.record(&AuditEvent {
+            timestamp: token::now(),
+            provider: format!("{:?}", provider.provider()).to_lowercase(),
+            provider_host: provider.provider_host(),
+            client_id: provider.client_id(),
+            outcome: outcome,
+            error_code: error_code,
+            extern_id: extern_id,
+            analytics_id: analytics_id,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Read;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::thread;
+
+    use rustc_serialize::json::Json;
+    use tempdir::TempDir;
+
+    use super::*;
+    use error::Error;
+    use oauth::github::AuthErr;
+    use oauth::policy::AccessDenied;
+
+    fn event() -> AuditEvent {
+        AuditEvent {
+            timestamp: 1476350400,
+            provider: "github".to_string(),
+            provider_host: "github.com".to_string(),
+            client_id: "Iv1.0123456789abcdef".to_string(),
+            outcome: AuditOutcome::Success,
+            error_code: None,
+            extern_id: Some(583231),
+            analytics_id: None,
+        }
+    }
+
+    fn read(path: &Path) -> String {
+        let mut contents = String::new();
+        File::open(path).unwrap().read_to_string(&mut contents).unwrap();
+        contents
+    }
+
+    #[test]
+    fn events_are_json_lines() {
+        let line = event().to_json_line();
+        assert!(line.ends_with('\n'));
+        assert_eq!(line.lines().count(), 1);
+        let json = Json::from_str(&line).unwrap();
+        assert_eq!(json.find("outcome").and_then(|o| o.as_string()), Some("success"));
+        assert_eq!(json.find("extern_id").and_then(|i| i.as_u64()), Some(583231));
+        assert_eq!(json.find("client_id").and_then(|i| i.as_string()),
+                   Some("Iv1.0123456789abcdef"));
+    }
+
+    #[test]
+    fn error_codes_name_the_kind_of_error() {
+        let rejected = Error::Auth(AuthErr {
+            error: "bad_verification_code\n\"injected\": 1".to_string(),
+            error_description: "The code passed is incorrect or expired.".to_string(),
+            error_uri: "https://developer.github.com/v3/oauth/".to_string(),
+        });
+        assert_eq!(error_code(&rejected), "auth.bad_verification_codeinjected1");
+        assert_eq!(error_code(&Error::MissingScope("read:org".to_string())),
+                   "missing_scope");
+        assert_eq!(error_code(&Error::TokenRevoked), "token_revoked");
+        assert_eq!(error_code(&Error::AccessDenied(AccessDenied::NotInAllowedOrg)),
+                   "access_denied.not_in_allowed_org");
+    }
+
+    #[test]
+    fn events_are_appended() {
+        let dir = TempDir::new("audit").unwrap();
+        let path = dir.path().join("audit.log");
+        let sink = FileSink::new(&path, DEFAULT_AUDIT_LOG_MAX_BYTES, 1).unwrap();
+        sink.record(&event());
+        let mut failed = event();
+        failed.outcome = AuditOutcome::Failed;
+        failed.error_code = Some("http_502".to_string());
+        sink.record(&failed);
+        // A sink opened later appends after what is there
+        FileSink::new(&path, DEFAULT_AUDIT_LOG_MAX_BYTES, 1).unwrap().record(&event());
+        let contents = read(&path);
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(Json::from_str(lines[1]).unwrap().find("error_code").unwrap().as_string(),
+                   Some("http_502"));
+    }
+
+    #[test]
+    fn concurrent_events_are_whole_lines() {
+        let dir = TempDir::new("audit").unwrap();
+        let sink = Arc::new(FileSink::new(dir.path().join("audit.log"),
+                                          DEFAULT_AUDIT_LOG_MAX_BYTES,
+                                          1)
+            .unwrap());
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let sink = sink.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        sink.record(&event());
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        let expected = Json::from_str(&event().to_json_line()).unwrap();
+        let contents = read(sink.path());
+        assert_eq!(contents.lines().count(), 800);
+        assert!(contents.lines().all(|line| Json::from_str(line).ok().as_ref() == Some(&expected)));
+    }
+
+    #[test]
+    fn logs_are_rotated_by_size() {
+        let dir = TempDir::new("audit").unwrap();
+        let path = dir.path().join("audit.log");
+        let line_len = event().to_json_line().len() as u64;
+        let sink = FileSink::new(&path, line_len * 2, 2).unwrap();
+        for _ in 0..7 {
+            sink.record(&event());
+        }
+        // Two lines fit in each file, and the oldest of them is dropped
+        assert_eq!(read(&path).lines().count(), 1);
+        assert_eq!(read(&dir.path().join("audit.log.1")).lines().count(), 2);
+        assert_eq!(read(&dir.path().join("audit.log.2")).lines().count(), 2);
+        assert!(!dir.path().join("audit.log.3").exists());
+    }
+
+    #[test]
+    fn events_larger_than_the_limit_are_still_recorded() {
+        let dir = TempDir::new("audit").unwrap();
+        let path = dir.path().join("audit.log");
+        let sink = FileSink::new(&path, 10, 1).unwrap();
+        sink.record(&event());
+        sink.record(&event());
+        assert_eq!(read(&path).lines().count(), 1);
+        assert_eq!(read(&dir.path().join("audit.log.1")).lines().count(), 1);
+    }
+}
@@ -0,0 +1,2409 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! The HTTP client of the GitHub API, compiled with the `client` feature.
+
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use hcore::util::metrics::{self, keys, MetricsSink};
+use hyper::{self, Url};
+use hyper::method::Method;
+use hyper::status::StatusCode;
+use openssl::crypto::hash::{self as openssl_hash, Type};
+use protocol::sessionsrv;
+use rustc_serialize::Decodable;
+use time;
+
+use config;
+use error::{Error, Result};
+use http;
+use redact;
+use super::OAuthProvider;
+use super::github::{decode_json, org_forbidden_error, parse_error_body, parse_scopes, AuthErr,
+                    AuthOk, Email, Org, RateLimits, Team, TeamMember, TeamMembership, User};
+use super::gitlab::GitLabClient;
+use super::health::{self, Health, HealthStatus, ProbeSchedule};
+use super::identity::Identity;
+use super::list;
+use super::policy::{team_matches, AccessPolicy, AdminPolicy};
+use super::stub::StubProvider;
+use super::team_cache::{Members, OrgTeamCache};
+use super::token::{self, TokenInfo, TokenRecord};
+
+const USER_AGENT: &'static str = "Habitat-Builder";
+/// Bulk operations pause until the rate limit resets once fewer requests than this remain.
+const RATE_LIMIT_FLOOR: u32 = 50;
+/// Members of a team listed per request, the most GitHub allows.
+const TEAM_MEMBERS_PER_PAGE: usize = 100;
+/// Most pages of members listed for a team before giving up on it.
+const TEAM_MEMBER_PAGES: usize = 100;
+/// Most pages of a listing followed through its `Link` headers before giving up on it.
+const MAX_LIST_PAGES: usize = 50;
+
+pub struct GitHubClient {
+    state: RwLock<Arc<ClientState>>,
+    shared: Arc<Shared>,
+    base_url: Option<String>,
+}
+
+/// How a request to the GitHub API is authorized.
+#[derive(Clone, Copy)]
+enum ApiAuth<'a> {
+    /// With a user's access token
+    Token(&'a str),
+    /// With the application's credentials in a basic authorization header, so they are never
+    /// part of a URL. The request path may contain an access token, which `redact::redact_url`
+    /// keeps out of the logs.
+    AppBasic,
+}
+
+impl<'a> ApiAuth<'a> {
+    /// Name of the authorization, for logging.
+    fn name(&self) -> &'static str {
+        match *self {
+            ApiAuth::Token(_) => "token",
+            ApiAuth::AppBasic => "app_basic",
+        }
+    }
+}
+
+/// State shared between a `GitHubClient` and every client derived from it with `with_base_url`.
+struct Shared {
+    breakers: Breakers,
+    requests: AtomicUsize,
+    rate_limit: Mutex<Option<RateLimit>>,
+    cache: ResponseCache,
+    sink: Arc<MetricsSink>,
+    /// When the most recent request which didn't fail completed
+    last_success: Mutex<Option<Instant>>,
+    probes: Mutex<ProbeSchedule>,
+    /// Members of the teams listed with the service token
+    teams: OrgTeamCache,
+}
+
+impl Shared {
+    fn new(sink: Arc<MetricsSink>) -> Self {
+        Shared {
+            breakers: Breakers::default(),
+            requests: AtomicUsize::new(0),
+            rate_limit: Mutex::new(None),
+            cache: ResponseCache::default(),
+            sink: sink,
+            last_success: Mutex::new(None),
+            probes: Mutex::new(ProbeSchedule::default()),
+            teams: OrgTeamCache::default(),
+        }
+    }
+}
+
+/// Bodies of successful responses to requests made with a user's access token, such as its
+/// owner's profile and emails, keyed by the SHA-256 digest of the token and the API path, so the
+/// cache doesn't hold on to tokens. An entry is served without contacting GitHub until it is
+/// `cache_ttl_secs` old, after which it is revalidated with its ETag. Once `cache_max_entries`
+/// are cached, the least recently used one makes room for the next. Failed requests are never
+/// cached.
+#[derive(Default)]
+struct ResponseCache {
+    entries: Mutex<HashMap<(Vec<u8>, String), CachedResponse>>,
+}
+
+#[derive(Clone)]
+struct CachedResponse {
+    body: String,
+    etag: Option<String>,
+    /// Next page of the listing the body is a page of, see `http::link::next_link`
+    next: Option<String>,
+    stored_at: Instant,
+    /// When the entry was last stored or served, which the least recently used is evicted by
+    used_at: Instant,
+}
+
+impl ResponseCache {
+    fn get(&self, token: &str, path: &str) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.get_mut(&cache_key(token, path)).map(|entry| {
+            entry.used_at = Instant::now();
+            entry.clone()
+        })
+    }
+
+    fn insert(&self,
+              token: &str,
+              path: &str,
+              body: String,
+              etag: Option<String>,
+              next: Option<String>,
+              max_entries: usize) {
+        let mut entries = self.entries.lock().unwrap();
+        let key = cache_key(token, path);
+        while !entries.contains_key(&key) && entries.len() >= max_entries {
+            let unused = entries.iter().min_by_key(|&(_, e)| e.used_at).map(|(k, _)| k.clone());
+            match unused {
+                Some(unused) => {
+                    entries.remove(&unused);
+                }
+                None => return,
+            }
+        }
+        let now = Instant::now();
+        entries.insert(key,
+                       CachedResponse {
+                           body: body,
+                           etag: etag,
+                           next: next,
+                           stored_at: now,
+                           used_at: now,
+                       });
+    }
+
+    /// Mark the entry as revalidated just now.
+    fn touch(&self, token: &str, path: &str) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&cache_key(token, path)) {
+            entry.stored_at = Instant::now();
+            entry.used_at = entry.stored_at;
+        }
+    }
+
+    /// Remove every entry for the given token, returning the number removed.
+    fn flush_token(&self, token: &str) -> usize {
+        let digest = token_digest(token);
+        let mut entries = self.entries.lock().unwrap();
+        let keys: Vec<(Vec<u8>, String)> =
+            entries.keys().filter(|&&(ref d, _)| *d == digest).cloned().collect();
+        for key in keys.iter() {
+            entries.remove(key);
+        }
+        keys.len()
+    }
+
+    fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+}
+
+fn cache_key(token: &str, path: &str) -> (Vec<u8>, String) {
+    (token_digest(token), path.to_string())
+}
+
+fn token_digest(token: &str) -> Vec<u8> {
+    openssl_hash::hash(Type::SHA256, token.as_bytes())
+}
+
+/// Rate limit status GitHub reported on the most recent API response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct RateLimit {
+    /// Requests remaining in the current window
+    remaining: u32,
+    /// Seconds since the unix epoch at which the window resets
+    reset: u64,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &hyper::header::Headers) -> Option<Self> {
+        let remaining = header_value(headers, "X-RateLimit-Remaining");
+        let reset = header_value(headers, "X-RateLimit-Reset");
+        match (remaining, reset) {
+            (Some(remaining), Some(reset)) => {
+                Some(RateLimit {
+                    remaining: remaining as u32,
+                    reset: reset,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns how long to wait before the next request, or `None` if more than `floor` requests
+    /// remain or the window has already reset.
+    fn pause(&self, now: u64, floor: u32) -> Option<Duration> {
+        if self.remaining >= floor || self.reset <= now {
+            None
+        } else {
+            Some(Duration::from_secs(self.reset - now + 1))
+        }
+    }
+}
+
+/// Returns `Error::GitHubRateLimited` if GitHub refused a request because a rate limit is
+/// exhausted: the primary one, with no `X-RateLimit-Remaining` requests until
+/// `X-RateLimit-Reset`, or a secondary one, which asks to wait `Retry-After` seconds.
+fn rate_limit_error(status: StatusCode,
+                    headers: &hyper::header::Headers,
+                    now: u64)
+                    -> Option<Error> {
+    if status != StatusCode::Forbidden && status != StatusCode::TooManyRequests {
+        return None;
+    }
+    if header_value(headers, "X-RateLimit-Remaining") == Some(0) {
+        let reset = header_value(headers, "X-RateLimit-Reset").unwrap_or(now);
+        return Some(Error::GitHubRateLimited { reset: reset });
+    }
+    header_value(headers, "Retry-After")
+        .map(|secs| Error::GitHubRateLimited { reset: now.saturating_add(secs) })
+}
+
+fn header_value(headers: &hyper::header::Headers, name: &str) -> Option<u64> {
+    header_string(headers, name).and_then(|value| value.parse().ok())
+}
+
+fn header_string(headers: &hyper::header::Headers, name: &str) -> Option<String> {
+    headers.get_raw(name)
+        .and_then(|values| values.first())
+        .map(|value| String::from_utf8_lossy(value).trim().to_string())
+}
+
+/// Outcome of `GitHubClient::refresh_accounts`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RefreshReport {
+    /// Number of accounts whose stored details changed
+    pub updated: usize,
+    /// Number of accounts whose stored details were already current
+    pub unchanged: usize,
+    /// Number of accounts which no longer exist on GitHub
+    pub missing: usize,
+    /// Number of accounts which couldn't be refreshed
+    pub errors: usize,
+    /// GitHub ids of the accounts which no longer exist on GitHub
+    pub missing_ids: Vec<u64>,
+    /// GitHub ids of the accounts which couldn't be refreshed and the reason
+    pub failures: Vec<(u64, String)>,
+}
+
+impl RefreshReport {
+    /// Record the outcome of refreshing one account. `Ok(None)` means the user no longer exists,
+    /// otherwise `Ok` says whether the stored details changed.
+    fn record(&mut self, id: u64, outcome: Result<Option<bool>>) {
+        match outcome {
+            Ok(Some(true)) => self.updated += 1,
+            Ok(Some(false)) => self.unchanged += 1,
+            Ok(None) => {
+                self.missing += 1;
+                self.missing_ids.push(id);
+            }
+            Err(e) => {
+                self.errors += 1;
+                self.failures.push((id, e.to_string()));
+            }
+        }
+    }
+}
+
+/// Progress and outcome of `GitHubClient::migrate_tokens`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Number of records to migrate, including those already migrated
+    pub total: usize,
+    /// Number of records looked at so far
+    pub done: usize,
+    /// Number of legacy records backfilled from GitHub
+    pub migrated: usize,
+    /// Number of records which weren't legacy and were left alone
+    pub already_migrated: usize,
+    /// Number of legacy records whose token is no longer valid
+    pub revoked: usize,
+    /// Number of legacy records which couldn't be migrated
+    pub errors: usize,
+    /// Indexes of the records whose token is no longer valid
+    pub revoked_indexes: Vec<usize>,
+    /// Indexes of the records which couldn't be migrated and the reason
+    pub failures: Vec<(usize, String)>,
+}
+
+impl MigrationReport {
+    /// Record the outcome of migrating the record at the given index. `Ok(None)` means the
+    /// token is no longer valid, otherwise `Ok` says whether the record was legacy.
+    fn record(&mut self, index: usize, outcome: Result<Option<bool>>) {
+        self.done += 1;
+        match outcome {
+            Ok(Some(true)) => self.migrated += 1,
+            Ok(Some(false)) => self.already_migrated += 1,
+            Ok(None) => {
+                self.revoked += 1;
+                self.revoked_indexes.push(index);
+            }
+            Err(e) => {
+                self.errors += 1;
+                self.failures.push((index, e.to_string()));
+            }
+        }
+    }
+}
+
+/// Circuit breakers keyed by API host, so failures of one host never stop requests to another.
+#[derive(Default)]
+struct Breakers {
+    hosts: Mutex<HashMap<String, BreakerState>>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl Breakers {
+    /// Returns an error if the breaker of the given host is open and its cooldown hasn't elapsed.
+    fn check(&self, host: &str, options: &config::GitHubClientOptions) -> Result<()> {
+        if options.breaker_threshold == 0 {
+            return Ok(());
+        }
+        let cooldown = Duration::from_secs(options.breaker_cooldown_secs);
+        match self.hosts.lock().unwrap().get(host).and_then(|b| b.opened_at) {
+            Some(opened_at) if opened_at.elapsed() < cooldown => {
+                Err(Error::CircuitOpen(host.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the number of consecutive failed requests to the given host and whether its
+    /// breaker is open at `now`.
+    fn status_at(&self,
+                 host: &str,
+                 options: &config::GitHubClientOptions,
+                 now: Instant)
+                 -> (u32, bool) {
+        let cooldown = Duration::from_secs(options.breaker_cooldown_secs);
+        match self.hosts.lock().unwrap().get(host) {
+            Some(breaker) => {
+                let open = options.breaker_threshold > 0 &&
+                           breaker.opened_at.map_or(false, |at| health::since(now, at) < cooldown);
+                (breaker.failures, open)
+            }
+            None => (0, false),
+        }
+    }
+
+    /// Record the outcome of a request to the given host, opening its breaker once the number of
+    /// consecutive failures reaches the configured threshold.
+    fn record(&self, host: &str, options: &config::GitHubClientOptions, failed: bool) {
+        if options.breaker_threshold == 0 {
+            return;
+        }
+        let mut hosts = self.hosts.lock().unwrap();
+        let breaker = hosts.entry(host.to_string()).or_insert_with(BreakerState::default);
+        if !failed {
+            *breaker = BreakerState::default();
+            return;
+        }
+        breaker.failures += 1;
+        if breaker.failures >= options.breaker_threshold {
+            if breaker.opened_at.is_none() {
+                warn!("GitHub circuit breaker opened, host={}, failures={}",
+                      host,
+                      breaker.failures);
+            }
+            breaker.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Credentials and endpoint used by a `GitHubClient`. A request takes a snapshot of the state when
+/// it begins so an in-flight request completes with the credentials it started with even if the
+/// client is reconfigured underneath it.
+struct ClientState {
+    url: String,
+    web_url: String,
+    scopes: Vec<String>,
+    client_id: String,
+    client_secret: String,
+    client_secret_file: Option<PathBuf>,
+    service_token: Option<String>,
+    options: config::GitHubClientOptions,
+    http: config::HttpCfg,
+    api: http::ApiClient,
+    admin_org: Option<String>,
+    admin_team: Option<String>,
+    required_org: Option<String>,
+    admin_policy: AdminPolicy,
+    access_policy: AccessPolicy,
+    /// Decorators added to the client, which `api` applies after those of `http`
+    decorators: Vec<Arc<http::RequestDecorator>>,
+}
+
+impl ClientState {
+    fn new<T: config::GitHubOAuth>(config: &T,
+                                   options: config::GitHubClientOptions)
+                                   -> Result<Self> {
+        let secret_file = config.github_client_secret_file().map(PathBuf::from);
+        let secret = match secret_file {
+            Some(ref path) => try!(read_secret_file(path)),
+            None => config.github_client_secret().to_string(),
+        };
+        Ok(ClientState {
+            url: config.github_api_url().to_string(),
+            web_url: config.github_web_url().to_string(),
+            scopes: config.github_scopes(),
+            client_id: config.github_client_id().to_string(),
+            client_secret: secret,
+            client_secret_file: secret_file,
+            service_token: config.github_service_token().map(|t| t.to_string()),
+            api: http::ApiClient::new(config.github_http(), api_options(&options)),
+            options: options,
+            http: config.github_http(),
+            admin_org: config.github_admin_org().map(|o| o.to_string()),
+            admin_team: config.github_admin_team().map(|t| t.to_string()),
+            required_org: config.github_required_org().map(|o| o.to_string()),
+            admin_policy: AdminPolicy::new(config),
+            access_policy: AccessPolicy::new(config),
+            decorators: vec![],
+        })
+    }
+
+    /// Returns the state with `decorators` applied to every request after those configured.
+    fn with_decorators(mut self, decorators: Vec<Arc<http::RequestDecorator>>) -> Self {
+        for decorator in decorators.iter() {
+            self.api.add_decorator(decorator.clone());
+        }
+        self.decorators = decorators;
+        self
+    }
+
+    fn fingerprint(&self) -> String {
+        config::github_fingerprint(self, &self.client_secret)
+    }
+}
+
+impl config::GitHubOAuth for ClientState {
+    fn github_api_url(&self) -> &str {
+        &self.url
+    }
+
+    fn github_client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn github_client_secret(&self) -> &str {
+        &self.client_secret
+    }
+
+    fn github_client_secret_file(&self) -> Option<&str> {
+        self.client_secret_file.as_ref().and_then(|f| f.to_str())
+    }
+
+    fn github_web_url(&self) -> &str {
+        &self.web_url
+    }
+
+    fn github_scopes(&self) -> Vec<String> {
+        self.scopes.clone()
+    }
+
+    fn github_admin_org(&self) -> Option<&str> {
+        self.admin_org.as_ref().map(|o| o.as_str())
+    }
+
+    fn github_admin_team(&self) -> Option<&str> {
+        self.admin_team.as_ref().map(|t| t.as_str())
+    }
+
+    fn github_required_org(&self) -> Option<&str> {
+        self.required_org.as_ref().map(|o| o.as_str())
+    }
+
+    fn github_admin_logins(&self) -> Vec<String> {
+        self.admin_policy.logins.clone()
+    }
+
+    fn github_admin_ids(&self) -> Vec<u64> {
+        self.admin_policy.ids.clone()
+    }
+
+    fn github_honor_site_admin(&self) -> bool {
+        self.admin_policy.honor_site_admin
+    }
+
+    fn github_allow_logins(&self) -> Vec<String> {
+        self.access_policy.allow_logins.clone()
+    }
+
+    fn github_deny_logins(&self) -> Vec<String> {
+        self.access_policy.deny_logins.clone()
+    }
+
+    fn github_allow_orgs(&self) -> Vec<String> {
+        self.access_policy.allow_orgs.clone()
+    }
+
+    fn github_require_org_membership(&self) -> bool {
+        self.access_policy.require_org_membership
+    }
+
+    fn github_service_token(&self) -> Option<&str> {
+        self.service_token.as_ref().map(|t| t.as_str())
+    }
+
+    fn github_http(&self) -> config::HttpCfg {
+        self.http.clone()
+    }
+}
+
+impl GitHubClient {
+    /// Create a new client from the given configuration and client options. If the configuration
+    /// names a secret file the client secret is read from it, otherwise the inline secret is used.
+    ///
+    /// # Errors
+    ///
+    /// * The secret file could not be read
+    /// * The secret file was empty
+    pub fn new<T: config::GitHubOAuth>(config: &T,
+                                       options: config::GitHubClientOptions)
+                                       -> Result<Self> {
+        GitHubClientBuilder::from_config(config).options(options).assemble()
+    }
+
+    /// Returns a builder of a client of github.com with no credentials, for tools and tests which
+    /// have no configuration to create a client from.
+    pub fn builder() -> GitHubClientBuilder {
+        GitHubClientBuilder::default()
+    }
+
+    /// Create a new client like `new` which reports its requests to `sink`, under the
+    /// `keys::GITHUB_*` keys. Clients derived from it with `with_base_url` report to the same
+    /// sink.
+    ///
+    /// # Errors
+    ///
+    /// * The secret file could not be read
+    /// * The secret file was empty
+    pub fn with_sink<T: config::GitHubOAuth>(config: &T,
+                                             options: config::GitHubClientOptions,
+                                             sink: Arc<MetricsSink>)
+                                             -> Result<Self> {
+        GitHubClientBuilder::from_config(config).options(options).sink(sink).assemble()
+    }
+
+    /// Returns a client which sends every API request to the given URL instead of the configured
+    /// one, for example to try a staging Enterprise appliance. The new client shares the circuit
+    /// breakers of this client and starts with its current credentials; reconfiguring this client
+    /// afterwards doesn't affect it.
+    pub fn with_base_url(&self, url: Url) -> GitHubClient {
+        GitHubClient {
+            state: RwLock::new(self.state()),
+            shared: self.shared.clone(),
+            base_url: Some(url.as_str().trim_right_matches('/').to_string()),
+        }
+    }
+
+    /// Returns a snapshot of the client's configuration for diagnostics.
+    pub fn stats(&self) -> ClientStats {
+        let state = self.state();
+        ClientStats {
+            url: state.url.clone(),
+            client_id: state.client_id.clone(),
+            fingerprint: state.fingerprint(),
+        }
+    }
+
+    /// Returns how healthy GitHub looks to this client, see `health_at`.
+    pub fn health(&self) -> HealthStatus {
+        self.health_at(Instant::now())
+    }
+
+    /// Returns the most recent attempts at GitHub requests, oldest first, with their secrets
+    /// redacted. Nothing is returned if `trace_requests` is off. The history survives
+    /// `reconfigure` unless the trace capacity changes.
+    pub fn recent_requests(&self) -> Vec<http::RequestTrace> {
+        self.state().api.recent_requests()
+    }
+
+    /// Returns the fingerprint of the client's current configuration. The fingerprint changes when
+    /// the configuration or the secret changes, without revealing the secret.
+    pub fn fingerprint(&self) -> String {
+        self.state().fingerprint()
+    }
+
+    /// URL to the GitHub API this client is currently configured for.
+    pub fn url(&self) -> String {
+        self.state().url.clone()
+    }
+
+    /// Client identifier this client is currently configured with.
+    pub fn client_id(&self) -> String {
+        self.state().client_id.clone()
+    }
+
+    /// OAuth scopes a frontend should request of GitHub, which `authenticate` requires of the
+    /// tokens it is handed, see `config::github_required_scopes`.
+    pub fn scopes(&self) -> Vec<String> {
+        config::github_required_scopes(&*self.state())
+    }
+
+    /// Atomically replace the endpoint and credentials of this client. Requests already in flight
+    /// complete with the previous configuration while new requests use the new one. Decorators
+    /// added with `GitHubClientBuilder::decorator` are kept.
+    ///
+    /// # Errors
+    ///
+    /// * The new configuration is missing a client id or client secret
+    /// * The secret file of the new configuration could not be read
+    ///
+    /// The previous configuration is left in place if an error is returned.
+    pub fn reconfigure(&self, cfg: &config::GitHubCfg) -> Result<()> {
+        let current = self.state();
+        let mut state = try!(validated_state(cfg, cfg.options.clone()))
+            .with_decorators(current.decorators.clone());
+        state.api.share_traces(&current.api);
+        state.api.share_tls(&current.api);
+        info!("GitHub client reconfigured, url={}, fingerprint={}",
+              redact::redact_text(&state.url),
+              state.fingerprint());
+        *self.state.write().unwrap() = Arc::new(state);
+        Ok(())
+    }
+
+    /// Re-read the client secret from the secret file so a rotated secret is picked up without a
+    /// restart. This is a no-op for clients configured with an inline secret.
+    pub fn reload_credentials(&self) -> Result<()> {
+        // Held until the new secret is stored, so a concurrent `reconfigure` isn't reverted
+        let mut current = self.state.write().unwrap();
+        let path = match current.client_secret_file {
+            Some(ref path) => path.clone(),
+            None => return Ok(()),
+        };
+        let secret = try!(read_secret_file(&path));
+        let state = ClientState {
+            url: current.url.clone(),
+            web_url: current.web_url.clone(),
+            scopes: current.scopes.clone(),
+            client_id: current.client_id.clone(),
+            client_secret: secret,
+            client_secret_file: current.client_secret_file.clone(),
+            service_token: current.service_token.clone(),
+            options: current.options.clone(),
+            http: current.http.clone(),
+            api: current.api.clone(),
+            admin_org: current.admin_org.clone(),
+            admin_team: current.admin_team.clone(),
+            required_org: current.required_org.clone(),
+            admin_policy: current.admin_policy.clone(),
+            access_policy: current.access_policy.clone(),
+            decorators: current.decorators.clone(),
+        };
+        info!("GitHub client credentials reloaded, fingerprint={}",
+              state.fingerprint());
+        *current = Arc::new(state);
+        Ok(())
+    }
+
+    /// Exchange an OAuth code for an access token, which must be granted every scope of `scopes`.
+    /// The client secret is sent in a basic authorization header, never in the URL.
+    pub fn authenticate(&self, code: &str) -> Result<TokenRecord> {
+        let state = self.state();
+        let base = format!("{}/login/oauth/access_token", state.web_url);
+        let mut url = match Url::parse(&base) {
+            Ok(url) => url,
+            Err(e) => return Err(Error::InvalidGitHubUrl(base, e.to_string())),
+        };
+        url.query_pairs_mut()
+            .append_pair("client_id", &state.client_id)
+            .append_pair("code", code);
+        let mut rep = try!(http_basic(Method::Post, url, &state));
+        if rep.status.is_success() {
+            let mut encoded = String::new();
+            try!(rep.read_to_string(&mut encoded));
+            match decode_json(&encoded) {
+                Ok(msg @ AuthOk { .. }) => {
+                    let required = config::github_required_scopes(&*state);
+                    match required.iter().find(|scope| !msg.has_scope(scope)) {
+                        Some(scope) => Err(Error::MissingScope(scope.clone())),
+                        None => {
+                            let scopes = parse_scopes(&msg.scope);
+                            Ok(TokenRecord::new(msg.access_token, scopes, state.client_id.clone()))
+                        }
+                    }
+                }
+                Err(_) => {
+                    let err: AuthErr = try!(decode_json(&encoded));
+                    Err(Error::from(err))
+                }
+            }
+        } else {
+            Err(Error::HTTP(rep.status))
+        }
+    }
+
+    pub fn user(&self, token: &str) -> Result<User> {
+        let state = self.state();
+        let body = try!(self.cached_get(&state, "/user", token));
+        decode_json(&body)
+    }
+
+    pub fn emails(&self, token: &str) -> Result<Vec<Email>> {
+        let state = self.state();
+        self.cached_get_all(&state, "/user/emails", token)
+    }
+
+    /// Revoke the given access token and forget every response cached for it. A token GitHub no
+    /// longer knows about is treated as already revoked.
+    pub fn logout(&self, token: &str) -> Result<()> {
+        let flushed = self.shared.cache.flush_token(token);
+        debug!("flushed {} cached GitHub responses for logout", flushed);
+        match self.revoke_token(token) {
+            Err(Error::TokenRevoked) => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Revoke the given access token of this client's OAuth application, authenticating with the
+    /// application's credentials.
+    ///
+    /// # Errors
+    ///
+    /// * The token has already been revoked, has expired, or belongs to another application
+    /// * GitHub refused to revoke it
+    pub fn revoke_token(&self, token: &str) -> Result<()> {
+        let state = self.state();
+        let path = format!("/applications/{}/tokens/{}", state.client_id, token);
+        let mut rep = try!(self.api_send(&state,
+                                         &path,
+                                         ApiAuth::AppBasic,
+                                         |url, state| http_basic(Method::Delete, url, state)));
+        match rep.status {
+            StatusCode::NoContent => Ok(()),
+            StatusCode::NotFound => Err(Error::TokenRevoked),
+            _ => {
+                let mut body = String::new();
+                try!(rep.read_to_string(&mut body));
+                Err(api_error(&rep, &body))
+            }
+        }
+    }
+
+    /// Returns the organizations the owner of the given token is a member of.
+    pub fn orgs(&self, token: &str) -> Result<Vec<Org>> {
+        let state = self.state();
+        self.api_get_all(&state,
+                         "/user/orgs",
+                         ApiAuth::Token(token),
+                         decode_membership_response)
+    }
+
+    /// Returns the rate limit status of the given token. Requests for it don't count against
+    /// the limit. Returns `None` if rate limiting is disabled, as GitHub Enterprise may be
+    /// configured to.
+    pub fn rate_limit(&self, token: &str) -> Result<Option<RateLimits>> {
+        let state = self.state();
+        let rep = try!(self.api_get(&state, "/rate_limit", ApiAuth::Token(token)));
+        if rep.status == StatusCode::NotFound {
+            return Ok(None);
+        }
+        decode_response(rep).map(Some)
+    }
+
+    /// Returns the teams of the given organization. Teams are requested with the application's own
+    /// credentials when no token is given.
+    pub fn teams(&self, token: Option<&str>, org: &str) -> Result<Vec<Team>> {
+        let state = self.state();
+        let auth = match token {
+            Some(token) => ApiAuth::Token(token),
+            None => ApiAuth::AppBasic,
+        };
+        self.api_get_all(&state,
+                         &format!("/orgs/{}/teams", org),
+                         auth,
+                         decode_membership_response)
+    }
+
+    /// Returns the user with the given GitHub id, or `None` if the account no longer exists.
+    pub fn user_by_id(&self, token: &str, id: u64) -> Result<Option<User>> {
+        let state = self.state();
+        let rep = try!(self.api_get(&state, &format!("/user/{}", id), ApiAuth::Token(token)));
+        if rep.status == StatusCode::NotFound {
+            return Ok(None);
+        }
+        decode_response(rep).map(Some)
+    }
+
+    /// Fetch the current details of every given GitHub user with the given service token and hand
+    /// each to `apply`, which stores them and returns whether the stored account changed.
+    ///
+    /// At most `concurrency` requests are in flight at once. When GitHub reports fewer than
+    /// `RATE_LIMIT_FLOOR` requests remaining, requests pause until the rate limit resets. Users
+    /// which no longer exist are counted as missing; failures are recorded per id and never stop
+    /// the remaining refreshes.
+    pub fn refresh_accounts<I, F>(&self,
+                                  token: &str,
+                                  ids: I,
+                                  concurrency: usize,
+                                  mut apply: F)
+                                  -> RefreshReport
+        where I: Iterator<Item = u64>,
+              F: FnMut(Identity) -> Result<bool>
+    {
+        let client = Arc::new(GitHubClient {
+            state: RwLock::new(self.state()),
+            shared: self.shared.clone(),
+            base_url: self.base_url.clone(),
+        });
+        let token = Arc::new(token.to_string());
+        let (id_tx, id_rx) = mpsc::sync_channel::<u64>(concurrency);
+        let id_rx = Arc::new(Mutex::new(id_rx));
+        let (result_tx, result_rx) = mpsc::channel();
+        let mut workers = Vec::new();
+        for _ in 0..cmp::max(concurrency, 1) {
+            let client = client.clone();
+            let token = token.clone();
+            let id_rx = id_rx.clone();
+            let result_tx = result_tx.clone();
+            workers.push(thread::spawn(move || {
+                loop {
+                    let id = match id_rx.lock().unwrap().recv() {
+                        Ok(id) => id,
+                        Err(_) => break,
+                    };
+                    client.wait_for_rate_limit();
+                    if result_tx.send((id, client.user_by_id(&token, id))).is_err() {
+                        break;
+                    }
+                }
+            }));
+        }
+        drop(result_tx);
+        for id in ids {
+            id_tx.send(id).unwrap();
+        }
+        drop(id_tx);
+        let mut report = RefreshReport::default();
+        for (id, result) in result_rx {
+            let outcome = match result {
+                Ok(Some(user)) => apply(Identity::from(&user)).map(Some),
+                Ok(None) => {
+                    debug!("GitHub user no longer exists, id={}", id);
+                    Ok(None)
+                }
+                Err(e) => Err(e),
+            };
+            report.record(id, outcome);
+        }
+        for worker in workers {
+            let _ = worker.join();
+        }
+        info!("GitHub account refresh finished, updated={}, unchanged={}, missing={}, errors={}",
+              report.updated,
+              report.unchanged,
+              report.missing,
+              report.errors);
+        report
+    }
+
+    /// Ask GitHub whether the given token is still valid for this client's OAuth application.
+    /// Returns `None` if the token has been revoked, has expired, or belongs to another
+    /// application.
+    pub fn check_token(&self, token: &str) -> Result<Option<TokenRecord>> {
+        match self.token_info(token) {
+            Ok(info) => Ok(Some(info.into_record(token.to_string()))),
+            Err(Error::TokenRevoked) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Ask GitHub what it knows about the given token of this client's OAuth application,
+    /// authenticating with the application's credentials.
+    ///
+    /// # Errors
+    ///
+    /// * The token has been revoked, has expired, or belongs to another application
+    /// * GitHub couldn't be asked, or answered with an error
+    pub fn token_info(&self, token: &str) -> Result<TokenInfo> {
+        let state = self.state();
+        let path = format!("/applications/{}/tokens/{}", state.client_id, token);
+        let rep = try!(self.api_get(&state, &path, ApiAuth::AppBasic));
+        if rep.status == StatusCode::NotFound {
+            return Err(Error::TokenRevoked);
+        }
+        let check: TokenCheck = try!(decode_response(rep));
+        let created_at = github_time(&check.created_at).unwrap_or(token::now());
+        let updated_at = check.updated_at
+            .as_ref()
+            .and_then(|updated_at| github_time(updated_at))
+            .unwrap_or(created_at);
+        let (user_login, user_id) = match check.user {
+            Some(user) => (Some(user.login), Some(user.id)),
+            None => (None, None),
+        };
+        Ok(TokenInfo {
+            scopes: check.scopes,
+            client_id: check.app.client_id,
+            created_at: created_at,
+            updated_at: updated_at,
+            user_login: user_login,
+            user_id: user_id,
+        })
+    }
+
+    /// Backfill every legacy record, see `TokenRecord::from_legacy`, one at a time, pausing
+    /// whenever GitHub reports the rate limit is nearly exhausted. Records which aren't legacy
+    /// are left alone without asking GitHub, so a migration which was interrupted can be run
+    /// again. `progress` is called with the report after each record.
+    ///
+    /// Records whose token is no longer valid are left as they were and listed in the report,
+    /// for the caller to delete their sessions.
+    pub fn migrate_tokens<F>(&self,
+                             records: &mut [TokenRecord],
+                             mut progress: F)
+                             -> MigrationReport
+        where F: FnMut(&MigrationReport)
+    {
+        let mut report = MigrationReport::default();
+        report.total = records.len();
+        for (index, record) in records.iter_mut().enumerate() {
+            let outcome = if record.is_legacy() {
+                self.wait_for_rate_limit();
+                match TokenRecord::from_legacy(&record.token, self) {
+                    Ok(migrated) => {
+                        *record = migrated;
+                        Ok(Some(true))
+                    }
+                    Err(Error::TokenRevoked) => Ok(None),
+                    Err(e) => Err(e),
+                }
+            } else {
+                Ok(Some(false))
+            };
+            report.record(index, outcome);
+            progress(&report);
+        }
+        info!("GitHub token migration finished, migrated={}, already_migrated={}, revoked={}, \
+               errors={}",
+              report.migrated,
+              report.already_migrated,
+              report.revoked,
+              report.errors);
+        report
+    }
+
+    /// Re-check the given record with GitHub if it hasn't been validated within the configured
+    /// `token_validation_secs`, updating its scopes and validation time. A legacy record is
+    /// replaced by its backfilled record, see `TokenRecord::from_legacy`, the first time it is
+    /// validated.
+    ///
+    /// # Errors
+    ///
+    /// * The token was issued to a different OAuth application, for example before the client
+    ///   credentials were rotated
+    /// * The token has been revoked or has expired
+    pub fn refresh_validation(&self, record: &mut TokenRecord) -> Result<()> {
+        if record.is_legacy() {
+            *record = try!(TokenRecord::from_legacy(&record.token, self));
+            debug!("Migrated a legacy GitHub token, client_id={}", record.client_id);
+            return Ok(());
+        }
+        let state = self.state();
+        if record.client_id != state.client_id {
+            return Err(Error::TokenClientMismatch(record.client_id.clone()));
+        }
+        if !record.is_stale(state.options.token_validation_secs) {
+            return Ok(());
+        }
+        match try!(self.check_token(&record.token)) {
+            Some(checked) => {
+                record.scopes = checked.scopes;
+                record.last_validated_at = checked.last_validated_at;
+                Ok(())
+            }
+            None => Err(Error::TokenRevoked),
+        }
+    }
+
+    /// Returns the teams, across every organization, the owner of the given token is a member of.
+    pub fn user_teams(&self, token: &str) -> Result<Vec<Team>> {
+        let state = self.state();
+        self.api_get_all(&state,
+                         "/user/teams",
+                         ApiAuth::Token(token),
+                         decode_membership_response)
+    }
+
+    /// Returns the membership of the given user in a team, or `None` if they aren't a member.
+    pub fn team_membership(&self,
+                           token: &str,
+                           team_id: u64,
+                           login: &str)
+                           -> Result<Option<TeamMembership>> {
+        let state = self.state();
+        let rep = try!(self.api_get(&state,
+                                    &format!("/teams/{}/memberships/{}", team_id, login),
+                                    ApiAuth::Token(token)));
+        if rep.status == StatusCode::NotFound {
+            return Ok(None);
+        }
+        decode_response(rep).map(Some)
+    }
+
+    /// Returns the members of a team of an organization, listed with the given token.
+    ///
+    /// # Errors
+    ///
+    /// * The organization has no such team, or none the token can see
+    /// * The team has more members than are listed
+    pub fn team_members(&self, token: &str, org: &str, team: &str) -> Result<Members> {
+        let teams = try!(self.teams(Some(token), org));
+        let unlisted = |reason: String| {
+            Error::TeamMembersUnlisted(format!("{}/{}", org, team), reason)
+        };
+        let id = match teams.iter().find(|t| team_matches(&t.slug, &t.name, team)) {
+            Some(t) => t.id,
+            None => return Err(unlisted("the team was not found".to_string())),
+        };
+        let state = self.state();
+        let mut members = Members::default();
+        for page in 1..TEAM_MEMBER_PAGES + 1 {
+            let path = format!("/teams/{}/members?per_page={}&page={}",
+                               id,
+                               TEAM_MEMBERS_PER_PAGE,
+                               page);
+            let rep = try!(self.api_get(&state, &path, ApiAuth::Token(token)));
+            let listed: Vec<TeamMember> = try!(decode_list_response(rep));
+            for member in listed.iter() {
+                members.add(member);
+            }
+            if listed.len() < TEAM_MEMBERS_PER_PAGE {
+                return Ok(members);
+            }
+        }
+        Err(unlisted(format!("the team has more than {} members",
+                             TEAM_MEMBER_PAGES * TEAM_MEMBERS_PER_PAGE)))
+    }
+
+    /// Returns whether the given user is a member of a team of an organization according to its
+    /// members listed with the service token, which are cached for `team_cache_ttl_secs`. Returns
+    /// `None` without a service token, when caching is disabled, or if the members couldn't be
+    /// listed.
+    pub fn cached_team_member(&self, org: &str, team: &str, user: &User) -> Option<bool> {
+        let state = self.state();
+        let token = match state.service_token {
+            Some(ref token) => token,
+            None => return None,
+        };
+        if state.options.team_cache_ttl_secs == 0 {
+            return None;
+        }
+        let ttl = Duration::from_secs(state.options.team_cache_ttl_secs);
+        self.shared
+            .teams
+            .get(org, team, ttl, || self.team_members(token, org, team))
+            .map(|members| members.contains(&user.login, user.id))
+    }
+
+    /// Forget the cached members of every team of the given organization so they are listed
+    /// again, for example after its teams were reorganized. Returns how many teams were
+    /// forgotten.
+    pub fn invalidate_org(&self, org: &str) -> usize {
+        let forgotten = self.shared.teams.invalidate_org(org);
+        info!("GitHub team members of {} invalidated, teams={}", org, forgotten);
+        forgotten
+    }
+
+    fn from_state(state: ClientState, sink: Arc<MetricsSink>) -> Self {
+        info!("GitHub client configured, url={}, fingerprint={}",
+              redact::redact_text(&state.url),
+              state.fingerprint());
+        GitHubClient {
+            state: RwLock::new(Arc::new(state)),
+            shared: Arc::new(Shared::new(sink)),
+            base_url: None,
+        }
+    }
+
+    fn state(&self) -> Arc<ClientState> {
+        self.state.read().unwrap().clone()
+    }
+
+    /// Returns how healthy GitHub looks at `now`.
+    ///
+    /// GitHub is unhealthy while the circuit breaker of its host is open. Otherwise a request
+    /// which succeeded within `health_fresh_secs` shows it is reachable; failing that it is probed
+    /// with a request for the rate limit, at most once every `health_probe_interval_secs` however
+    /// often this is called, and the last probe's finding is returned in between. A reachable
+    /// GitHub is degraded while requests to it fail or its rate limit is nearly exhausted.
+    fn health_at(&self, now: Instant) -> HealthStatus {
+        let state = self.state();
+        let url = self.base_url.clone().unwrap_or(state.url.clone());
+        let host = Url::parse(&url)
+            .ok()
+            .and_then(|url| url.host_str().map(|h| h.to_string()))
+            .unwrap_or(String::new());
+        let (failures, open) = self.shared.breakers.status_at(&host, &state.options, now);
+        if open {
+            return HealthStatus::unhealthy(format!("the circuit breaker of {} is open after {} \
+                                                    failed requests",
+                                                   host,
+                                                   failures));
+        }
+        let fresh = Duration::from_secs(state.options.health_fresh_secs);
+        let last_success = *self.shared.last_success.lock().unwrap();
+        let status = match last_success {
+            Some(at) if health::since(now, at) < fresh => {
+                HealthStatus::healthy(format!("a request to {} succeeded {}s ago",
+                                              host,
+                                              health::since(now, at).as_secs()))
+            }
+            _ => self.probe_at(&state, now),
+        };
+        if status.health != Health::Healthy {
+            return status;
+        }
+        let (failures, _) = self.shared.breakers.status_at(&host, &state.options, now);
+        if failures > 0 {
+            return HealthStatus::degraded(format!("the last {} requests to {} failed",
+                                                  failures,
+                                                  host));
+        }
+        let limit = *self.shared.rate_limit.lock().unwrap();
+        match limit.and_then(|l| l.pause(token::now(), RATE_LIMIT_FLOOR)) {
+            Some(pause) => {
+                HealthStatus::degraded(format!("the rate limit is nearly exhausted, it resets in \
+                                                {}s",
+                                               pause.as_secs()))
+            }
+            None => status,
+        }
+    }
+
+    /// Probe GitHub with a request for the rate limit, unless a probe began less than
+    /// `health_probe_interval_secs` before `now`, in which case what that probe found is returned.
+    fn probe_at(&self, state: &ClientState, now: Instant) -> HealthStatus {
+        let interval = Duration::from_secs(state.options.health_probe_interval_secs);
+        {
+            let mut probes = self.shared.probes.lock().unwrap();
+            if !probes.begin(now, interval) {
+                return probes.last_status();
+            }
+        }
+        let status = match self.api_get(state, "/rate_limit", ApiAuth::AppBasic) {
+            Ok(ref rep) if rep.status == StatusCode::Unauthorized => {
+                HealthStatus::unhealthy("GitHub refused the application's credentials")
+            }
+            Ok(ref rep) if rep.status.is_server_error() => {
+                HealthStatus::unhealthy(format!("GitHub answered a probe with {}", rep.status))
+            }
+            Ok(_) => HealthStatus::healthy("GitHub answered a probe"),
+            Err(e) => {
+                let reason = format!("GitHub couldn't be probed, {}", e);
+                HealthStatus::unhealthy(redact::redact_text(&reason).into_owned())
+            }
+        };
+        self.shared.probes.lock().unwrap().finish(status.clone());
+        status
+    }
+
+    /// Sleep until the rate limit resets if GitHub reported it is nearly exhausted.
+    fn wait_for_rate_limit(&self) {
+        let limit = *self.shared.rate_limit.lock().unwrap();
+        if let Some(pause) = limit.and_then(|l| l.pause(token::now(), RATE_LIMIT_FLOOR)) {
+            info!("GitHub rate limit nearly exhausted, pausing for {}s",
+                  pause.as_secs());
+            thread::sleep(pause);
+        }
+    }
+
+    /// Returns the API URL the next request is sent to and a tag naming the route for log lines.
+    /// Requests go to the canary URL, if one is configured, at the configured percentage.
+    fn route(&self, state: &ClientState) -> (String, &'static str) {
+        if let Some(ref url) = self.base_url {
+            return (url.clone(), "override");
+        }
+        if let Some(ref url) = state.options.canary_url {
+            let n = self.shared.requests.fetch_add(1, Ordering::Relaxed);
+            if n % 100 < state.options.canary_percent as usize {
+                return (url.clone(), "canary");
+            }
+        }
+        (state.url.clone(), "primary")
+    }
+
+    /// Return the body of a successful GET request for the given API path, made with the given
+    /// access token. Responses are cached for `cache_ttl_secs` and revalidated with their ETag
+    /// afterwards.
+    fn cached_get(&self, state: &ClientState, path: &str, token: &str) -> Result<String> {
+        self.cached_page(state, path, token).map(|(body, _)| body)
+    }
+
+    /// Return the items of every page of a listing, starting at the given API path and following
+    /// the `next` links GitHub answers with, each page requested and cached as `cached_get`
+    /// does.
+    ///
+    /// # Errors
+    ///
+    /// * A page fails as `cached_get` does, or doesn't decode
+    /// * A link doesn't parse or points away from the GitHub API, see `page_path`
+    /// * The listing has more than `MAX_LIST_PAGES` pages
+    fn cached_get_all<T: Decodable>(&self,
+                                    state: &ClientState,
+                                    path: &str,
+                                    token: &str)
+                                    -> Result<Vec<T>> {
+        let mut items = vec![];
+        let mut page = path.to_string();
+        for _ in 0..MAX_LIST_PAGES {
+            let (body, next) = try!(self.cached_page(state, &page, token));
+            let listed: Vec<T> = try!(decode_json(&body));
+            items.extend(listed);
+            match next {
+                Some(url) => page = try!(self.page_path(state, &url)),
+                None => return Ok(items),
+            }
+        }
+        Err(Error::TooManyPages(path.to_string(), MAX_LIST_PAGES))
+    }
+
+    /// Return the items of every page of a listing as `cached_get_all` does, requesting each
+    /// page as `api_get` does and decoding it with `decode`.
+    fn api_get_all<T, F>(&self,
+                         state: &ClientState,
+                         path: &str,
+                         auth: ApiAuth,
+                         decode: F)
+                         -> Result<Vec<T>>
+        where F: Fn(http::ApiResponse) -> Result<Vec<T>>
+    {
+        let mut items = vec![];
+        let mut page = path.to_string();
+        for _ in 0..MAX_LIST_PAGES {
+            let rep = try!(self.api_get(state, &page, auth));
+            let next = http::link::next_link(&rep.headers);
+            items.extend(try!(decode(rep)));
+            match try!(next) {
+                Some(url) => page = try!(self.page_path(state, &url)),
+                None => return Ok(items),
+            }
+        }
+        Err(Error::TooManyPages(path.to_string(), MAX_LIST_PAGES))
+    }
+
+    /// Returns the API path of the page a `Link` header points at, without any application
+    /// credentials a server put in it, as `api_get` never sends them in a URL. Only pages of a
+    /// GitHub API this client sends requests to are followed, so a token is never sent to another
+    /// host.
+    fn page_path(&self, state: &ClientState, url: &str) -> Result<String> {
+        let mut link = match Url::parse(url) {
+            Ok(link) => link,
+            Err(e) => return Err(Error::InvalidLinkHeader(format!("{}, {}", e, url))),
+        };
+        let pairs: Vec<(String, String)> = link.query_pairs()
+            .filter(|&(ref name, _)| name != "client_id" && name != "client_secret")
+            .map(|(name, value)| (name.into_owned(), value.into_owned()))
+            .collect();
+        if pairs.is_empty() {
+            link.set_query(None);
+        } else {
+            link.query_pairs_mut().clear().extend_pairs(pairs);
+        }
+        let bases = self.base_url.iter().chain(Some(&state.url)).chain(state.options
+            .canary_url
+            .iter());
+        for base in bases {
+            let link = link.as_str();
+            if link.starts_with(base.as_str()) && link[base.len()..].starts_with('/') {
+                return Ok(link[base.len()..].to_string());
+            }
+        }
+        Err(Error::InvalidLinkHeader(format!("the next page isn't on the GitHub API, {}", link)))
+    }
+
+    /// Return the body of a successful GET request as `cached_get` does, with the next page of
+    /// the listing it is a page of, if any.
+    fn cached_page(&self,
+                   state: &ClientState,
+                   path: &str,
+                   token: &str)
+                   -> Result<(String, Option<String>)> {
+        let ttl = Duration::from_secs(state.options.cache_ttl_secs);
+        let cached = if ttl == Duration::from_secs(0) {
+            None
+        } else {
+            self.shared.cache.get(token, path)
+        };
+        if let Some(ref entry) = cached {
+            if entry.stored_at.elapsed() < ttl {
+                self.shared.sink.incr(keys::GITHUB_CACHE_HITS, 1);
+                return Ok((entry.body.clone(), entry.next.clone()));
+            }
+        }
+        let etag = cached.as_ref().and_then(|e| e.etag.clone());
+        let mut rep = try!(self.api_send(state, path, ApiAuth::Token(token), |url, state| {
+            http_get(url, token, etag.as_ref().map(|e| e.as_str()), state)
+        }));
+        if rep.status == StatusCode::NotModified {
+            if let Some(entry) = cached {
+                self.shared.cache.touch(token, path);
+                self.shared.sink.incr(keys::GITHUB_CACHE_REVALIDATIONS, 1);
+                return Ok((entry.body, entry.next));
+            }
+        }
+        let mut body = String::new();
+        try!(rep.read_to_string(&mut body));
+        if rep.status != StatusCode::Ok {
+            return Err(api_error(&rep, &body));
+        }
+        let next = try!(http::link::next_link(&rep.headers));
+        if ttl > Duration::from_secs(0) {
+            let etag = header_string(&rep.headers, "ETag");
+            self.shared.cache.insert(token,
+                                     path,
+                                     body.clone(),
+                                     etag,
+                                     next.clone(),
+                                     state.options.cache_max_entries);
+        }
+        Ok((body, next))
+    }
+
+    /// Send a GET request for the given API path, authorized as described by `auth`.
+    fn api_get(&self,
+               state: &ClientState,
+               path: &str,
+               auth: ApiAuth)
+               -> Result<http::ApiResponse> {
+        self.api_send(state, path, auth, |url, state| {
+            match auth {
+                ApiAuth::Token(token) => http_get(url, token, None, state),
+                ApiAuth::AppBasic => http_basic(Method::Get, url, state),
+            }
+        })
+    }
+
+    /// Send a request for the given API path with `send` through the circuit breaker of the host
+    /// it is routed to. `auth` only describes how `send` authorizes the request, for logging. A
+    /// response refusing the request over a rate limit fails it, see `rate_limit_error`.
+    fn api_send<F>(&self,
+                   state: &ClientState,
+                   path: &str,
+                   auth: ApiAuth,
+                   send: F)
+                   -> Result<http::ApiResponse>
+        where F: FnOnce(Url, &ClientState) -> Result<http::ApiResponse>
+    {
+        let (base, route) = self.route(state);
+        let url = Url::parse(&format!("{}{}", base, path)).unwrap();
+        let host = url.host_str().unwrap_or("").to_string();
+        let sink = &self.shared.sink;
+        if let Err(e) = self.shared.breakers.check(&host, &state.options) {
+            sink.incr(keys::GITHUB_BREAKER_REJECTIONS, 1);
+            return Err(e);
+        }
+        let logged = redact::redact_url(&url);
+        debug!("GitHub API request, route={}, auth={}, url={}",
+               route,
+               auth.name(),
+               logged);
+        sink.incr(keys::GITHUB_REQUESTS, 1);
+        let started = Instant::now();
+        let result = send(url, state);
+        sink.timing(keys::GITHUB_REQUEST_TIME, started.elapsed());
+        let failed = match result {
+            Ok(ref rep) => {
+                if let Some(limit) = RateLimit::from_headers(&rep.headers) {
+                    sink.gauge(keys::GITHUB_RATE_LIMIT_REMAINING, limit.remaining as f64);
+                    *self.shared.rate_limit.lock().unwrap() = Some(limit);
+                }
+                rep.status.is_server_error()
+            }
+            Err(_) => true,
+        };
+        if failed {
+            sink.incr(keys::GITHUB_REQUEST_FAILURES, 1);
+            warn!("GitHub API request failed, route={}, auth={}, url={}",
+                  route,
+                  auth.name(),
+                  logged);
+        } else {
+            *self.shared.last_success.lock().unwrap() = Some(Instant::now());
+        }
+        self.shared.breakers.record(&host, &state.options, failed);
+        match result {
+            Ok(rep) => {
+                match rate_limit_error(rep.status, &rep.headers, token::now()) {
+                    Some(e) => {
+                        warn!("GitHub API request rate limited, route={}, auth={}, url={}, {}",
+                              route,
+                              auth.name(),
+                              logged,
+                              e);
+                        Err(e)
+                    }
+                    None => Ok(rep),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl OAuthProvider for GitHubClient {
+    fn authenticate(&self, code: &str) -> Result<TokenRecord> {
+        GitHubClient::authenticate(self, code)
+    }
+
+    fn client_id(&self) -> String {
+        GitHubClient::client_id(self)
+    }
+
+    fn check_token(&self, token: &str) -> Result<Option<TokenRecord>> {
+        GitHubClient::check_token(self, token)
+    }
+
+    fn refresh_validation(&self, record: &mut TokenRecord) -> Result<()> {
+        GitHubClient::refresh_validation(self, record)
+    }
+
+    fn user(&self, token: &str) -> Result<User> {
+        GitHubClient::user(self, token)
+    }
+
+    fn emails(&self, token: &str) -> Result<Vec<Email>> {
+        GitHubClient::emails(self, token)
+    }
+
+    fn orgs(&self, token: &str) -> Result<Vec<Org>> {
+        GitHubClient::orgs(self, token)
+    }
+
+    fn teams(&self, token: Option<&str>, org: &str) -> Result<Vec<Team>> {
+        GitHubClient::teams(self, token, org)
+    }
+
+    fn user_teams(&self, token: &str) -> Result<Vec<Team>> {
+        GitHubClient::user_teams(self, token)
+    }
+
+    fn team_membership(&self,
+                       token: &str,
+                       team_id: u64,
+                       login: &str)
+                       -> Result<Option<TeamMembership>> {
+        GitHubClient::team_membership(self, token, team_id, login)
+    }
+
+    fn cached_team_member(&self, org: &str, team: &str, user: &User) -> Option<bool> {
+        GitHubClient::cached_team_member(self, org, team, user)
+    }
+
+    fn invalidate_org(&self, org: &str) -> usize {
+        GitHubClient::invalidate_org(self, org)
+    }
+
+    fn logout(&self, token: &str) -> Result<()> {
+        GitHubClient::logout(self, token)
+    }
+
+    fn reconfigure(&self, cfg: &config::GitHubCfg) -> Result<()> {
+        GitHubClient::reconfigure(self, cfg)
+    }
+
+    fn avatar_size(&self) -> u32 {
+        self.state().options.avatar_size
+    }
+
+    fn admin_policy(&self) -> AdminPolicy {
+        self.state().admin_policy.clone()
+    }
+
+    fn access_policy(&self) -> AccessPolicy {
+        self.state().access_policy.clone()
+    }
+
+    fn health(&self) -> HealthStatus {
+        GitHubClient::health(self)
+    }
+
+    fn provider(&self) -> sessionsrv::OAuthProvider {
+        sessionsrv::OAuthProvider::GitHub
+    }
+
+    fn provider_host(&self) -> String {
+        let state = self.state();
+        Url::parse(&state.web_url)
+            .ok()
+            .and_then(|url| url.host_str().map(|h| h.to_lowercase()))
+            .unwrap_or(config::GITHUB_HOST.to_string())
+    }
+}
+
+/// Diagnostic snapshot of a `GitHubClient`.
+#[derive(Clone, Debug, PartialEq, Eq, RustcEncodable)]
+pub struct ClientStats {
+    pub url: String,
+    pub client_id: String,
+    pub fingerprint: String,
+}
+
+#[cfg(feature = "serde")]
+serialize_via_rustc_serialize!(ClientStats);
+
+/// Builder of a `GitHubClient`, see `GitHubClient::builder`. Unset values keep the defaults of a
+/// `config::GitHubCfg`.
+pub struct GitHubClientBuilder {
+    cfg: config::GitHubCfg,
+    sink: Arc<MetricsSink>,
+    decorators: Vec<Arc<http::RequestDecorator>>,
+}
+
+impl Default for GitHubClientBuilder {
+    fn default() -> Self {
+        GitHubClientBuilder {
+            cfg: config::GitHubCfg::default(),
+            sink: metrics::noop(),
+            decorators: vec![],
+        }
+    }
+}
+
+impl GitHubClientBuilder {
+    /// Returns a builder starting from every value of the given configuration.
+    pub fn from_config<T: config::GitHubOAuth>(config: &T) -> Self {
+        let mut cfg = config::GitHubCfg::default();
+        cfg.api_url = config.github_api_url().to_string();
+        cfg.web_url = config.github_web_url().to_string();
+        cfg.scopes = config.github_scopes();
+        cfg.client_id = config.github_client_id().to_string();
+        cfg.client_secret = config.github_client_secret().to_string();
+        cfg.client_secret_file = config.github_client_secret_file().map(|f| f.to_string());
+        cfg.admin_org = config.github_admin_org().map(|o| o.to_string());
+        cfg.admin_team = config.github_admin_team().map(|t| t.to_string());
+        cfg.builder_team = config.github_builder_team().map(|t| t.to_string());
+        cfg.origin_creator_team = config.github_origin_creator_team().map(|t| t.to_string());
+        cfg.required_org = config.github_required_org().map(|o| o.to_string());
+        cfg.admin_logins = config.github_admin_logins();
+        cfg.admin_ids = config.github_admin_ids();
+        cfg.honor_site_admin = config.github_honor_site_admin();
+        cfg.allow_logins = config.github_allow_logins();
+        cfg.deny_logins = config.github_deny_logins();
+        cfg.allow_orgs = config.github_allow_orgs();
+        cfg.require_org_membership = config.github_require_org_membership();
+        cfg.service_token = config.github_service_token().map(|t| t.to_string());
+        cfg.http = config.github_http();
+        GitHubClientBuilder {
+            cfg: cfg,
+            sink: metrics::noop(),
+            decorators: vec![],
+        }
+    }
+
+    /// URL to the GitHub API, `https://api.github.com` unless set.
+    pub fn api_url<S: Into<String>>(mut self, url: S) -> Self {
+        self.cfg.api_url = url.into();
+        self
+    }
+
+    /// URL to the GitHub web frontend which hosts the OAuth endpoints, `https://github.com`
+    /// unless set.
+    pub fn web_url<S: Into<String>>(mut self, url: S) -> Self {
+        self.cfg.web_url = url.into();
+        self
+    }
+
+    /// Let the API and web URLs use plain HTTP, for example to reach a fake GitHub in tests.
+    pub fn allow_insecure_urls(mut self, allow: bool) -> Self {
+        self.cfg.allow_insecure_github_url = allow;
+        self
+    }
+
+    pub fn client_id<S: Into<String>>(mut self, client_id: S) -> Self {
+        self.cfg.client_id = client_id.into();
+        self
+    }
+
+    pub fn client_secret<S: Into<String>>(mut self, client_secret: S) -> Self {
+        self.cfg.client_secret = client_secret.into();
+        self
+    }
+
+    /// OAuth scopes which must be granted to an access token.
+    pub fn scopes(mut self, scopes: Vec<String>) -> Self {
+        self.cfg.scopes = scopes;
+        self
+    }
+
+    /// Retry, timeout, caching, and circuit breaking behaviour of the client.
+    pub fn options(mut self, options: config::GitHubClientOptions) -> Self {
+        self.cfg.options = options;
+        self
+    }
+
+    /// Proxy, TLS, and timeout settings of the requests the client sends.
+    pub fn transport(mut self, http: config::HttpCfg) -> Self {
+        self.cfg.http = http;
+        self
+    }
+
+    /// Sink the client reports its requests to, see `GitHubClient::with_sink`.
+    pub fn sink(mut self, sink: Arc<MetricsSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    /// Apply `decorator` to every request of the client, after the headers of the transport and
+    /// the decorators added before it. See `http::decorate`.
+    pub fn decorator(mut self, decorator: Arc<http::RequestDecorator>) -> Self {
+        self.decorators.push(decorator);
+        self
+    }
+
+    /// Create the client, checking its values as they are checked when read from a
+    /// configuration file.
+    ///
+    /// # Errors
+    ///
+    /// * The web URL is empty
+    /// * The API or web URL doesn't point at GitHub or a GitHub Enterprise, or uses plain HTTP
+    ///   without `allow_insecure_urls`
+    /// * The client id or client secret is missing
+    /// * An option is outside of its valid range
+    pub fn build(mut self) -> Result<GitHubClient> {
+        let insecure = self.cfg.allow_insecure_github_url;
+        if self.cfg.web_url.is_empty() {
+            return Err(Error::RequiredConfigField("github.web_url"));
+        }
+        self.cfg.web_url = try!(config::normalize_web_url(&self.cfg.web_url, insecure));
+        self.cfg.api_url = try!(config::normalize_api_url(&self.cfg.api_url, insecure));
+        if let Some(url) = self.cfg.options.canary_url.take() {
+            self.cfg.options.canary_url = Some(try!(config::normalize_api_url(&url, insecure)));
+        }
+        try!(self.cfg.options.validate());
+        let state = try!(validated_state(&self.cfg, self.cfg.options.clone()));
+        Ok(GitHubClient::from_state(state.with_decorators(self.decorators), self.sink))
+    }
+
+    /// Create the client from values which were already checked.
+    fn assemble(self) -> Result<GitHubClient> {
+        let state = try!(ClientState::new(&self.cfg, self.cfg.options.clone()));
+        Ok(GitHubClient::from_state(state.with_decorators(self.decorators), self.sink))
+    }
+}
+
+/// A registry of GitHub clients, one per configured OAuth application. Every client is constructed
+/// when the registry is created so configuration errors surface at startup instead of on the first
+/// login request.
+pub struct GitHubClients {
+    default: Box<OAuthProvider>,
+    named: HashMap<String, Box<OAuthProvider>>,
+}
+
+impl GitHubClients {
+    pub fn new<T: config::GitHubApps>(config: &T) -> Result<Self> {
+        GitHubClients::with_sink(config, metrics::noop())
+    }
+
+    /// Create a registry like `new` whose clients report their requests to `sink`, see
+    /// `GitHubClient::with_sink`.
+    pub fn with_sink<T: config::GitHubApps>(config: &T, sink: Arc<MetricsSink>) -> Result<Self> {
+        let default = try!(validated_client(config,
+                                            config.github_client_options().clone(),
+                                            sink.clone()));
+        let mut named = HashMap::new();
+        for (name, app) in config.github_apps().iter() {
+            info!("GitHub application {}, {}", name, app.resolved());
+            let client = try!(validated_client(app, app.options.clone(), sink.clone()));
+            named.insert(name.clone(), Box::new(client) as Box<OAuthProvider>);
+        }
+        Ok(GitHubClients {
+            default: Box::new(default),
+            named: named,
+        })
+    }
+
+    /// Create a registry which authenticates every request against the stub provider instead of
+    /// GitHub.
+    pub fn stub(config: config::StubAuthCfg) -> Self {
+        warn!("GitHub authentication is DISABLED, every login with the shared secret is \
+               authenticated as {}",
+              config.login);
+        GitHubClients {
+            default: Box::new(StubProvider::new(config)),
+            named: HashMap::new(),
+        }
+    }
+
+    /// Create a registry which authenticates every request with the OAuth application of a
+    /// GitLab instance instead of GitHub, see `GitLabClient`.
+    pub fn gitlab<T: config::OAuth>(config: &T) -> Result<Self> {
+        let client = try!(GitLabClient::new(config));
+        info!("GitLab authentication, url={}, client_id={}",
+              config.oauth_url(),
+              config.oauth_client_id());
+        Ok(GitHubClients {
+            default: Box::new(client),
+            named: HashMap::new(),
+        })
+    }
+
+    /// Returns the client ids of every OAuth application in the registry.
+    pub fn client_ids(&self) -> Vec<String> {
+        let mut ids = vec![self.default.client_id()];
+        ids.extend(self.named.values().map(|client| client.client_id()));
+        ids
+    }
+
+    /// Returns the health of the least healthy client in the registry. The reason given by the
+    /// client of a named OAuth application starts with its name.
+    pub fn health(&self) -> HealthStatus {
+        let mut worst = self.default.health();
+        for (name, client) in self.named.iter() {
+            let mut status = client.health();
+            status.reason = format!("{}: {}", name, status.reason);
+            worst = worst.worst(status);
+        }
+        worst
+    }
+
+    /// Forget the cached team members of the given organization in every client of the registry,
+    /// see `GitHubClient::invalidate_org`. Returns how many teams were forgotten.
+    pub fn invalidate_org(&self, org: &str) -> usize {
+        self.named.values().fold(self.default.invalidate_org(org),
+                                 |forgotten, client| forgotten + client.invalidate_org(org))
+    }
+
+    /// Apply new configurations to the running clients of the registry, see
+    /// `OAuthProvider::reconfigure`: `default` to the default OAuth application, and each of
+    /// `apps` to the named application of the same name. Applications can't be added or removed
+    /// without a restart, so configurations of applications the registry doesn't have are
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// * A configuration is invalid. The client it was meant for keeps its previous
+    ///   configuration, while every other client is reconfigured.
+    pub fn reconfigure(&self,
+                       default: &config::GitHubCfg,
+                       apps: &BTreeMap<String, config::GitHubCfg>)
+                       -> Result<()> {
+        let mut result = self.default.reconfigure(default);
+        for (name, app) in apps.iter() {
+            let reconfigured = match self.named.get(name) {
+                Some(client) => client.reconfigure(app),
+                None => {
+                    warn!("GitHub application {} isn't running, it is only added on restart",
+                          name);
+                    continue;
+                }
+            };
+            if let Err(e) = reconfigured {
+                error!("rejected configuration of GitHub application {}, err={}", name, e);
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Re-check the access token of a looked up session with the OAuth application it was issued
+    /// to, see `OAuthProvider::refresh_validation`. Returns the record of the token if it was
+    /// checked again, to be stored with the session, or `None` if it was validated recently
+    /// enough. A token stored by a builder which didn't record its application is checked with
+    /// the default one.
+    ///
+    /// # Errors
+    ///
+    /// * The token was issued to an OAuth application which isn't in the registry
+    /// * The token has been revoked or has expired
+    /// * The OAuth provider couldn't be asked
+    pub fn refresh_session(&self, session: &sessionsrv::Session) -> Result<Option<TokenRecord>> {
+        let mut record = TokenRecord::from(session);
+        let client = if record.is_legacy() {
+            &*self.default
+        } else {
+            match self.by_client_id(&record.client_id) {
+                Some(client) => client,
+                None => return Err(Error::TokenClientMismatch(record.client_id)),
+            }
+        };
+        let stored = record.clone();
+        try!(client.refresh_validation(&mut record));
+        if record == stored {
+            Ok(None)
+        } else {
+            Ok(Some(record))
+        }
+    }
+
+    /// Returns the client of the OAuth application with the given client id.
+    fn by_client_id(&self, client_id: &str) -> Option<&OAuthProvider> {
+        if self.default.client_id() == client_id {
+            return Some(&*self.default);
+        }
+        self.named.values().find(|client| client.client_id() == client_id).map(|client| &**client)
+    }
+
+    /// Returns the client for the default OAuth application.
+    pub fn default(&self) -> &OAuthProvider {
+        &*self.default
+    }
+
+    /// Returns the client for the OAuth application with the given name, or the default client if
+    /// no name was given.
+    ///
+    /// # Errors
+    ///
+    /// * No OAuth application was configured with the given name
+    pub fn get(&self, name: Option<&str>) -> Result<&OAuthProvider> {
+        match name {
+            None => Ok(&*self.default),
+            Some(config::DEFAULT_GITHUB_APP) => Ok(&*self.default),
+            Some(name) => {
+                self.named
+                    .get(name)
+                    .map(|client| &**client)
+                    .ok_or(Error::UnknownGitHubApp(name.to_string()))
+            }
+        }
+    }
+}
+
+/// Response of the GitHub endpoint checking an access token.
+#[derive(RustcDecodable)]
+struct TokenCheck {
+    scopes: Vec<String>,
+    created_at: String,
+    updated_at: Option<String>,
+    app: TokenCheckApp,
+    user: Option<TokenCheckUser>,
+}
+
+#[derive(RustcDecodable)]
+struct TokenCheckApp {
+    client_id: String,
+}
+
+#[derive(RustcDecodable)]
+struct TokenCheckUser {
+    login: String,
+    id: u64,
+}
+
+/// Returns the seconds since the unix epoch of a timestamp of the GitHub API, such as
+/// `2016-05-01T12:00:00Z`.
+fn github_time(value: &str) -> Option<u64> {
+    time::strptime(value, "%Y-%m-%dT%H:%M:%SZ").ok().map(|tm| tm.to_timespec().sec as u64)
+}
+
+fn validated_client<T: config::GitHubOAuth>(config: &T,
+                                           options: config::GitHubClientOptions,
+                                           sink: Arc<MetricsSink>)
+                                           -> Result<GitHubClient> {
+    let state = try!(validated_state(config, options));
+    Ok(GitHubClient::from_state(state, sink))
+}
+
+fn validated_state<T: config::GitHubOAuth>(config: &T,
+                                          options: config::GitHubClientOptions)
+                                          -> Result<ClientState> {
+    if config.github_client_id().is_empty() {
+        return Err(Error::RequiredConfigField("github.client_id"));
+    }
+    let state = try!(ClientState::new(config, options));
+    if state.client_secret.is_empty() {
+        return Err(Error::RequiredConfigField("github.client_secret"));
+    }
+    Ok(state)
+}
+
+fn read_secret_file(path: &Path) -> Result<String> {
+    let mut secret = String::new();
+    try!(File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut secret))
+        .map_err(|e| Error::SecretFileIO(path.display().to_string(), e)));
+    let secret = secret.trim_right_matches(|c| c == '\n' || c == '\r').to_string();
+    if secret.is_empty() {
+        return Err(Error::SecretFileEmpty(path.display().to_string()));
+    }
+    Ok(secret)
+}
+
+fn api_options(options: &config::GitHubClientOptions) -> http::ApiOptions {
+    http::ApiOptions {
+        user_agent: USER_AGENT.to_string(),
+        connect_timeout_ms: options.connect_timeout_ms,
+        read_timeout_ms: options.read_timeout_ms,
+        max_retries: options.max_retries,
+        base_backoff_ms: options.base_backoff_ms,
+        trace_capacity: if options.trace_requests {
+            options.trace_capacity
+        } else {
+            0
+        },
+    }
+}
+
+fn http_get(url: Url,
+            token: &str,
+            etag: Option<&str>,
+            state: &ClientState)
+            -> Result<http::ApiResponse> {
+    let mut req = state.api.get(url).accept_json().bearer(token);
+    if let Some(etag) = etag {
+        req = req.header("If-None-Match", etag);
+    }
+    req.send()
+}
+
+fn http_basic(method: Method,
+              url: Url,
+              state: &ClientState)
+              -> Result<http::ApiResponse> {
+    state.api
+        .request(method, url)
+        .accept_json()
+        .basic(&state.client_id, &state.client_secret)
+        .send()
+}
+
+fn decode_response<T: Decodable>(mut rep: http::ApiResponse) -> Result<T> {
+    let mut body = String::new();
+    try!(rep.read_to_string(&mut body));
+    if rep.status != StatusCode::Ok {
+        return Err(api_error(&rep, &body));
+    }
+    decode_json(&body)
+}
+
+/// Returns the error GitHub answered a request with, the given body, as an `Error::GitHubAPI`
+/// naming the status and redacted path. A body which isn't one of GitHub's error objects, such
+/// as the HTML page of a proxy in the way, leaves only the status, as an `Error::HTTP`.
+fn api_error(rep: &http::ApiResponse, body: &str) -> Error {
+    match parse_error_body(body) {
+        Ok(err) => {
+            let path = Url::parse(&redact::redact_url(&rep.url))
+                .map(|url| url.path().to_string())
+                .unwrap_or(String::new());
+            Error::GitHubAPI(err.with_request(rep.status.to_u16(), &path))
+        }
+        Err(_) => Error::HTTP(rep.status),
+    }
+}
+
+/// Decodes a listing GitHub answered with, see `list::decode_list`, or fails with the error
+/// it answered with instead.
+fn decode_list_response<T: Decodable + 'static>(rep: http::ApiResponse) -> Result<Vec<T>> {
+    if rep.status != StatusCode::Ok {
+        return decode_response(rep);
+    }
+    list::decode_list(rep).collect()
+}
+
+/// Decodes a listing of organizations or teams as `decode_list_response` does, failing with
+/// `Error::OrgAccessDenied` if GitHub won't show them, see `github::org_forbidden_error`.
+fn decode_membership_response<T>(mut rep: http::ApiResponse) -> Result<Vec<T>>
+    where T: Decodable + 'static
+{
+    if rep.status != StatusCode::Forbidden {
+        return decode_list_response(rep);
+    }
+    let mut body = String::new();
+    try!(rep.read_to_string(&mut body));
+    match api_error(&rep, &body) {
+        Error::GitHubAPI(err) => Err(org_forbidden_error(err)),
+        err => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use hcore::util::metrics::{self, keys, MetricsSink};
+    use hyper::{self, Url};
+    use hyper::status::StatusCode;
+    use toml;
+    use protocol::sessionsrv;
+
+    use super::*;
+    use config::{self, GitHubCfg};
+    use error::{Error, Result};
+    use oauth::OAuthProvider;
+    use oauth::fixtures::UserFixture;
+    use oauth::health::{Health, HealthStatus};
+    use oauth::token;
+    use oauth::policy::{AccessDenied, AccessPolicy, AdminPolicy};
+    use oauth::token::TokenRecord;
+    use redact;
+
+    fn cfg(client_id: &str, client_secret: &str) -> GitHubCfg {
+        let mut cfg = GitHubCfg::default();
+        cfg.client_id = client_id.to_string();
+        cfg.client_secret = client_secret.to_string();
+        cfg
+    }
+
+    /// Counts the requests a client sends.
+    #[derive(Default)]
+    struct RequestCounter(AtomicUsize);
+
+    impl MetricsSink for RequestCounter {
+        fn incr(&self, key: &str, n: u64) {
+            if key == keys::GITHUB_REQUESTS {
+                self.0.fetch_add(n as usize, Ordering::SeqCst);
+            }
+        }
+
+        fn timing(&self, _key: &str, _d: Duration) {}
+
+        fn gauge(&self, _key: &str, _v: f64) {}
+    }
+
+    /// Returns a client of a GitHub which refuses every connection, and the count of the
+    /// requests it sends.
+    fn unreachable(options: config::GitHubClientOptions) -> (GitHubClient, Arc<RequestCounter>) {
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let counter = Arc::new(RequestCounter::default());
+        let client = GitHubClient::with_sink(&cfg("abc", "def"), options, counter.clone())
+            .unwrap()
+            .with_base_url(Url::parse(&format!("http://127.0.0.1:{}", port)).unwrap());
+        (client, counter)
+    }
+
+    fn requests(counter: &RequestCounter) -> usize {
+        counter.0.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn health_probes_at_most_once_per_interval() {
+        let (client, counter) = unreachable(Default::default());
+        let now = Instant::now();
+        let first = client.health_at(now);
+        assert_eq!(first.health, Health::Unhealthy);
+        assert!(first.reason.contains("couldn't be probed"), "{}", first.reason);
+        for secs in 1..30 {
+            assert_eq!(client.health_at(now + Duration::from_secs(secs)), first);
+        }
+        assert_eq!(requests(&counter), 1);
+        client.health_at(now + Duration::from_secs(30));
+        assert_eq!(requests(&counter), 2);
+    }
+
+    #[test]
+    fn health_is_unhealthy_while_the_breaker_is_open() {
+        let mut options = config::GitHubClientOptions::default();
+        options.breaker_threshold = 1;
+        options.health_probe_interval_secs = 1;
+        let (client, counter) = unreachable(options);
+        let now = Instant::now();
+        client.health_at(now);
+        let status = client.health_at(now + Duration::from_secs(5));
+        assert_eq!(status.health, Health::Unhealthy);
+        assert!(status.reason.contains("circuit breaker"), "{}", status.reason);
+        assert_eq!(requests(&counter), 1);
+        client.health_at(now + Duration::from_secs(31));
+        assert_eq!(requests(&counter), 2);
+    }
+
+    #[test]
+    fn recent_successes_skip_the_probe() {
+        let (client, counter) = unreachable(Default::default());
+        let now = Instant::now();
+        *client.shared.last_success.lock().unwrap() = Some(now);
+        let status = client.health_at(now + Duration::from_secs(10));
+        assert_eq!(status,
+                   HealthStatus::healthy("a request to 127.0.0.1 succeeded 10s ago"));
+        *client.shared.rate_limit.lock().unwrap() = Some(RateLimit {
+            remaining: 10,
+            reset: token::now() + 600,
+        });
+        assert_eq!(client.health_at(now + Duration::from_secs(10)).health,
+                   Health::Degraded);
+        assert_eq!(requests(&counter), 0);
+        assert_eq!(client.health_at(now + Duration::from_secs(60)).health,
+                   Health::Unhealthy);
+        assert_eq!(requests(&counter), 1);
+    }
+
+    fn rejection(result: Result<GitHubClient>) -> String {
+        match result {
+            Ok(_) => panic!("the client was created"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    /// Create a client the way `GitHubClients` does from an application's configuration.
+    fn from_config_file(raw: &str) -> Result<GitHubClient> {
+        let cfg = try!(GitHubCfg::from_toml(&raw.parse::<toml::Value>().unwrap()));
+        validated_client(&cfg, cfg.options.clone(), metrics::noop())
+    }
+
+    #[test]
+    fn builder_normalizes_urls() {
+        let client = GitHubClient::builder()
+            .api_url("https://github.example.com/api/v3/")
+            .web_url("https://github.example.com/")
+            .client_id("abc")
+            .client_secret("def")
+            .build()
+            .unwrap();
+        assert_eq!(client.url(), "https://github.example.com/api/v3");
+        assert_eq!(client.state().web_url, "https://github.example.com");
+        assert_eq!(client.client_id(), "abc");
+    }
+
+    #[test]
+    fn builder_rejects_what_the_config_file_does() {
+        let valid = || GitHubClient::builder().client_id("abc").client_secret("def");
+        let mut timeouts = config::GitHubClientOptions::default();
+        timeouts.read_timeout_ms = 600_000;
+        let cases = vec![("client_id = \"\"\nclient_secret = \"def\"",
+                          valid().client_id("")),
+                         ("client_id = \"abc\"", GitHubClient::builder().client_id("abc")),
+                         ("client_id = \"abc\"\nclient_secret = \"def\"\n\
+                           api_url = \"http://github.example.com/api/v3\"",
+                          valid().api_url("http://github.example.com/api/v3")),
+                         ("client_id = \"abc\"\nclient_secret = \"def\"\n\
+                           api_url = \"https://github.com\"",
+                          valid().api_url("https://github.com")),
+                         ("client_id = \"abc\"\nclient_secret = \"def\"\n\
+                           read_timeout_ms = 600000",
+                          valid().options(timeouts))];
+        for (raw, builder) in cases {
+            let expected = rejection(from_config_file(raw));
+            assert_eq!(rejection(builder.build()), expected, "{}", raw);
+        }
+        valid().build().unwrap();
+        valid()
+            .api_url("http://github.example.com/api/v3")
+            .web_url("http://github.example.com")
+            .allow_insecure_urls(true)
+            .build()
+            .unwrap();
+    }
+
+    #[test]
+    fn refresh_validation_rejects_previous_client() {
+        let client = GitHubClient::new(&cfg("new", "new-secret"), Default::default()).unwrap();
+        let mut record = TokenRecord::new("token".to_string(), vec![], "old".to_string());
+        match client.refresh_validation(&mut record) {
+            Err(Error::TokenClientMismatch(id)) => assert_eq!(id, "old"),
+            e => panic!("Unexpected result: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn refresh_validation_skips_fresh_records() {
+        let client = GitHubClient::new(&cfg("abc", "def"), Default::default()).unwrap();
+        let mut record = TokenRecord::new("token".to_string(), vec![], "abc".to_string());
+        assert!(client.refresh_validation(&mut record).is_ok());
+    }
+
+    #[test]
+    fn check_token_url_is_logged_redacted() {
+        let url = Url::parse("https://api.github.com/applications/abc/tokens/secret").unwrap();
+        assert_eq!(redact::redact_url(&url),
+                   "https://api.github.com/applications/abc/tokens/REDACTED");
+        let url = Url::parse("https://api.github.com/orgs/habitat-sh/teams").unwrap();
+        assert_eq!(redact::redact_url(&url),
+                   "https://api.github.com/orgs/habitat-sh/teams");
+    }
+
+    #[test]
+    fn reconfigure_replaces_credentials() {
+        let client = GitHubClient::new(&cfg("old", "old-secret"), Default::default()).unwrap();
+        client.reconfigure(&cfg("new", "new-secret")).unwrap();
+        assert_eq!(client.client_id(), "new");
+    }
+
+    #[test]
+    fn reconfigure_replaces_admin_policy() {
+        let client = GitHubClient::new(&cfg("abc", "def"), Default::default()).unwrap();
+        assert_eq!(client.admin_policy(), AdminPolicy::default());
+        let mut app = cfg("abc", "def");
+        app.admin_logins = vec!["octocat".to_string()];
+        client.reconfigure(&app).unwrap();
+        assert_eq!(client.admin_policy().logins, vec!["octocat".to_string()]);
+        let user = UserFixture::default().login("Octocat").build();
+        assert_eq!(client.admin_policy().flags(&user), sessionsrv::OPERATOR);
+        let user = UserFixture::default().login("hubot").build();
+        assert!(client.admin_policy().flags(&user).is_empty());
+    }
+
+    #[test]
+    fn reconfigure_replaces_access_policy() {
+        let client = GitHubClient::new(&cfg("abc", "def"), Default::default()).unwrap();
+        assert!(client.access_policy().is_unrestricted());
+        let mut app = cfg("abc", "def");
+        app.deny_logins = vec!["hubot".to_string()];
+        client.reconfigure(&app).unwrap();
+        assert_eq!(client.access_policy(),
+                   AccessPolicy { deny_logins: vec!["hubot".to_string()], ..Default::default() });
+        assert_eq!(client.access_policy().evaluate("Hubot", &[]),
+                   Err(AccessDenied::DeniedLogin));
+    }
+
+    #[test]
+    fn reconfigure_keeps_previous_config_on_error() {
+        let client = GitHubClient::new(&cfg("old", "old-secret"), Default::default()).unwrap();
+        assert!(client.reconfigure(&cfg("new", "")).is_err());
+        assert_eq!(client.client_id(), "old");
+    }
+
+    #[test]
+    fn with_base_url_overrides_host() {
+        let client = GitHubClient::new(&cfg("abc", "def"), Default::default()).unwrap();
+        let url = Url::parse("https://github.canary.com/api/v3/").unwrap();
+        let canary = client.with_base_url(url);
+        let state = client.state();
+        assert_eq!(canary.route(&state),
+                   ("https://github.canary.com/api/v3".to_string(), "override"));
+        assert_eq!(client.route(&state), (state.url.clone(), "primary"));
+        assert_eq!(canary.client_id(), "abc");
+    }
+
+    #[test]
+    fn canary_percent_routes_fraction_of_requests() {
+        let mut app = cfg("abc", "def");
+        app.options.canary_url = Some("https://github.canary.com/api/v3".to_string());
+        app.options.canary_percent = 25;
+        let client = GitHubClient::new(&app, app.options.clone()).unwrap();
+        let state = client.state();
+        let canary = (0..100).filter(|_| client.route(&state).1 == "canary").count();
+        assert_eq!(canary, 25);
+    }
+
+    #[test]
+    fn breaker_state_is_per_host() {
+        let mut options = config::GitHubClientOptions::default();
+        options.breaker_threshold = 2;
+        let breakers = Breakers::default();
+        breakers.record("github.canary.com", &options, true);
+        assert!(breakers.check("github.canary.com", &options).is_ok());
+        breakers.record("github.canary.com", &options, true);
+        assert!(breakers.check("github.canary.com", &options).is_err());
+        assert!(breakers.check("api.github.com", &options).is_ok());
+        breakers.record("github.canary.com", &options, false);
+        assert!(breakers.check("github.canary.com", &options).is_ok());
+    }
+
+    #[test]
+    fn exhausted_rate_limits_are_errors() {
+        let mut headers = hyper::header::Headers::new();
+        headers.set_raw("X-RateLimit-Remaining", vec![b"0".to_vec()]);
+        headers.set_raw("X-RateLimit-Reset", vec![b"1000".to_vec()]);
+        match rate_limit_error(StatusCode::Forbidden, &headers, 900) {
+            Some(Error::GitHubRateLimited { reset }) => assert_eq!(reset, 1000),
+            other => panic!("expected a rate limit error, got {:?}", other),
+        }
+        // Only refusals are rate limited, the last request of a window succeeds
+        assert!(rate_limit_error(StatusCode::Ok, &headers, 900).is_none());
+
+        let mut headers = hyper::header::Headers::new();
+        headers.set_raw("X-RateLimit-Remaining", vec![b"4000".to_vec()]);
+        assert!(rate_limit_error(StatusCode::Forbidden, &headers, 900).is_none());
+        headers.set_raw("Retry-After", vec![b"60".to_vec()]);
+        match rate_limit_error(StatusCode::TooManyRequests, &headers, 900) {
+            Some(Error::GitHubRateLimited { reset }) => assert_eq!(reset, 960),
+            other => panic!("expected a rate limit error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rate_limit_pause() {
+        let mut headers = hyper::header::Headers::new();
+        headers.set_raw("X-RateLimit-Remaining", vec![b"10".to_vec()]);
+        headers.set_raw("X-RateLimit-Reset", vec![b"1000".to_vec()]);
+        let limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!(limit,
+                   RateLimit {
+                       remaining: 10,
+                       reset: 1000,
+                   });
+        assert_eq!(limit.pause(990, 50), Some(Duration::from_secs(11)));
+        assert_eq!(limit.pause(990, 10), None);
+        assert_eq!(limit.pause(1000, 50), None);
+        assert_eq!(RateLimit::from_headers(&hyper::header::Headers::new()), None);
+    }
+
+    #[test]
+    fn refresh_report_counts_outcomes() {
+        let mut report = RefreshReport::default();
+        report.record(1, Ok(Some(true)));
+        report.record(2, Ok(Some(false)));
+        report.record(3, Ok(None));
+        report.record(4, Err(Error::TokenRevoked));
+        assert_eq!(report.updated, 1);
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.missing, 1);
+        assert_eq!(report.missing_ids, vec![3]);
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.failures[0].0, 4);
+    }
+
+    #[test]
+    fn response_cache_flushes_one_token() {
+        let cache = ResponseCache::default();
+        cache.insert("abc", "/user", "{}".to_string(), Some("\"1\"".to_string()), None, 10);
+        cache.insert("abc", "/user/emails", "[]".to_string(), None, None, 10);
+        cache.insert("def", "/user", "{}".to_string(), None, None, 10);
+        assert_eq!(cache.flush_token("abc"), 2);
+        assert!(cache.get("abc", "/user").is_none());
+        assert!(cache.get("def", "/user").is_some());
+        assert_eq!(cache.flush_token("abc"), 0);
+    }
+
+    #[test]
+    fn response_cache_evicts_oldest() {
+        let cache = ResponseCache::default();
+        cache.insert("abc", "/user", "{}".to_string(), None, None, 2);
+        thread::sleep(Duration::from_millis(5));
+        cache.insert("def", "/user", "{}".to_string(), None, None, 2);
+        thread::sleep(Duration::from_millis(5));
+        cache.insert("ghi", "/user", "{}".to_string(), None, None, 2);
+        assert!(cache.get("abc", "/user").is_none());
+        assert!(cache.get("def", "/user").is_some());
+        assert!(cache.get("ghi", "/user").is_some());
+    }
+
+    #[test]
+    fn response_cache_evicts_the_least_recently_used() {
+        let cache = ResponseCache::default();
+        cache.insert("abc", "/user", "{}".to_string(), None, None, 2);
+        thread::sleep(Duration::from_millis(5));
+        cache.insert("def", "/user", "{}".to_string(), None, None, 2);
+        thread::sleep(Duration::from_millis(5));
+        // Serving the older entry keeps it over the newer one
+        assert!(cache.get("abc", "/user").is_some());
+        thread::sleep(Duration::from_millis(5));
+        cache.insert("ghi", "/user", "{}".to_string(), None, None, 2);
+        assert!(cache.get("def", "/user").is_none());
+        assert!(cache.get("abc", "/user").is_some());
+        assert!(cache.get("ghi", "/user").is_some());
+        // Replacing an entry evicts nothing, and a lower bound evicts down to it
+        cache.insert("abc", "/user", "[]".to_string(), None, None, 2);
+        assert_eq!(cache.len(), 2);
+        cache.insert("jkl", "/user", "{}".to_string(), None, None, 1);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get("jkl", "/user").is_some());
+    }
+
+    #[test]
+    fn response_cache_keys_by_digest() {
+        let cache = ResponseCache::default();
+        cache.insert("abc", "/user", "{}".to_string(), None, None, 10);
+        let entries = cache.entries.lock().unwrap();
+        let &(ref digest, ref path) = entries.keys().next().unwrap();
+        assert_eq!(digest.len(), 32);
+        assert!(!String::from_utf8_lossy(digest).contains("abc"));
+        assert_eq!(path, "/user");
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deterministic GitHub payloads for tests of the conversion layer.
+//!
+//! `UserFixture::default()` describes a user the way GitHub returns them from `/user`; builder
+//! methods override single fields. The canned email sets and expected accounts pair with the
+//! default user so a test can assert on a whole conversion at once. `AppsFixture` configures a
+//! registry of OAuth applications.
+
+use std::collections::BTreeMap;
+
+use protocol::sessionsrv;
+
+use config::{GitHubApps, GitHubCfg, GitHubClientOptions, GitHubOAuth, HttpCfg};
+use super::github::{Email, User};
+
+pub const LOGIN: &'static str = "octocat";
+pub const ID: u64 = 583231;
+pub const NAME: &'static str = "The Octocat";
+pub const EMAIL: &'static str = "octocat@github.com";
+pub const AVATAR_URL: &'static str = "https://avatars.githubusercontent.com/u/583231?v=3";
+
+/// Builder of a `User` as returned by the GitHub API.
+#[derive(Clone, Debug)]
+pub struct UserFixture {
+    login: String,
+    id: u64,
+    avatar_url: String,
+    name: Option<String>,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    site_admin: bool,
+}
+
+impl Default for UserFixture {
+    fn default() -> Self {
+        UserFixture {
+            login: LOGIN.to_string(),
+            id: ID,
+            avatar_url: AVATAR_URL.to_string(),
+            name: Some(NAME.to_string()),
+            email: Some(EMAIL.to_string()),
+            email_verified: None,
+            site_admin: false,
+        }
+    }
+}
+
+impl UserFixture {
+    /// A user with no avatar, name, or public email.
+    pub fn minimal() -> Self {
+        UserFixture::default().avatar_url("").name(None).email(None)
+    }
+
+    pub fn login(mut self, login: &str) -> Self {
+        self.login = login.to_string();
+        self
+    }
+
+    pub fn id(mut self, id: u64) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn avatar_url(mut self, url: &str) -> Self {
+        self.avatar_url = url.to_string();
+        self
+    }
+
+    pub fn name(mut self, name: Option<&str>) -> Self {
+        self.name = name.map(|n| n.to_string());
+        self
+    }
+
+    pub fn email(mut self, email: Option<&str>) -> Self {
+        self.email = email.map(|e| e.to_string());
+        self
+    }
+
+    pub fn email_verified(mut self, verified: Option<bool>) -> Self {
+        self.email_verified = verified;
+        self
+    }
+
+    pub fn site_admin(mut self, site_admin: bool) -> Self {
+        self.site_admin = site_admin;
+        self
+    }
+
+    pub fn build(self) -> User {
+        let api = format!("https://api.github.com/users/{}", self.login);
+        User {
+            html_url: format!("https://github.com/{}", self.login),
+            followers_url: format!("{}/followers", api),
+            following_url: format!("{}/following{{/other_user}}", api),
+            gists_url: format!("{}/gists{{/gist_id}}", api),
+            starred_url: format!("{}/starred{{/owner}}{{/repo}}", api),
+            subscriptions_url: format!("{}/subscriptions", api),
+            organizations_url: format!("{}/orgs", api),
+            repos_url: format!("{}/repos", api),
+            events_url: format!("{}/events{{/privacy}}", api),
+            received_events_url: format!("{}/received_events", api),
+            url: api,
+            login: self.login,
+            id: self.id,
+            avatar_url: self.avatar_url,
+            gravatar_id: String::new(),
+            site_admin: self.site_admin,
+            name: self.name,
+            company: None,
+            blog: None,
+            location: None,
+            email: self.email,
+            email_verified: self.email_verified,
+            hireable: None,
+            bio: None,
+            public_repos: 8,
+            public_gists: 8,
+            followers: 20,
+            following: 0,
+            created_at: "2011-01-25T18:44:36Z".to_string(),
+            updated_at: "2016-08-12T21:32:05Z".to_string(),
+        }
+    }
+}
+
+pub fn email(address: &str, primary: bool, verified: bool) -> Email {
+    Email {
+        email: address.to_string(),
+        primary: primary,
+        verified: verified,
+    }
+}
+
+/// A verified primary address alongside an unverified secondary one.
+pub fn verified_primary_emails() -> Vec<Email> {
+    vec![email(EMAIL, true, true), email("octo@example.com", false, false)]
+}
+
+/// Addresses none of which have been verified.
+pub fn unverified_emails() -> Vec<Email> {
+    vec![email(EMAIL, true, false), email("octo@example.com", false, false)]
+}
+
+/// Only the noreply address GitHub assigns to users who keep their email private.
+pub fn noreply_emails() -> Vec<Email> {
+    vec![email(&format!("{}@users.noreply.github.com", LOGIN), true, true)]
+}
+
+/// The account `UserFixture::default()` converts to.
+pub fn expected_account() -> sessionsrv::Account {
+    let mut account = expected_minimal_account();
+    account.set_avatar_url(format!("{}&s=120", AVATAR_URL));
+    account.set_display_name(NAME.to_string());
+    account.set_email(EMAIL.to_string());
+    account
+}
+
+/// Configuration of a registry of OAuth applications, see `GitHubClients`, with the default
+/// application of `default` and the named ones of `apps`.
+pub struct AppsFixture {
+    pub default: GitHubCfg,
+    pub apps: BTreeMap<String, GitHubCfg>,
+}
+
+impl AppsFixture {
+    /// Applications with the given client ids and secrets, the first of which is the default.
+    pub fn new(default: (&str, &str), apps: &[(&str, &str, &str)]) -> Self {
+        AppsFixture {
+            default: app(default.0, default.1),
+            apps: apps.iter()
+                .map(|&(name, id, secret)| (name.to_string(), app(id, secret)))
+                .collect(),
+        }
+    }
+}
+
+impl GitHubOAuth for AppsFixture {
+    fn github_api_url(&self) -> &str {
+        self.default.github_api_url()
+    }
+
+    fn github_client_id(&self) -> &str {
+        self.default.github_client_id()
+    }
+
+    fn github_client_secret(&self) -> &str {
+        self.default.github_client_secret()
+    }
+
+    fn github_client_secret_file(&self) -> Option<&str> {
+        self.default.github_client_secret_file()
+    }
+
+    fn github_web_url(&self) -> &str {
+        self.default.github_web_url()
+    }
+
+    fn github_scopes(&self) -> Vec<String> {
+        self.default.github_scopes()
+    }
+
+    fn github_admin_org(&self) -> Option<&str> {
+        self.default.github_admin_org()
+    }
+
+    fn github_admin_team(&self) -> Option<&str> {
+        self.default.github_admin_team()
+    }
+
+    fn github_builder_team(&self) -> Option<&str> {
+        self.default.github_builder_team()
+    }
+
+    fn github_origin_creator_team(&self) -> Option<&str> {
+        self.default.github_origin_creator_team()
+    }
+
+    fn github_required_org(&self) -> Option<&str> {
+        self.default.github_required_org()
+    }
+
+    fn github_admin_logins(&self) -> Vec<String> {
+        self.default.github_admin_logins()
+    }
+
+    fn github_admin_ids(&self) -> Vec<u64> {
+        self.default.github_admin_ids()
+    }
+
+    fn github_honor_site_admin(&self) -> bool {
+        self.default.github_honor_site_admin()
+    }
+
+    fn github_allow_logins(&self) -> Vec<String> {
+        self.default.github_allow_logins()
+    }
+
+    fn github_deny_logins(&self) -> Vec<String> {
+        self.default.github_deny_logins()
+    }
+
+    fn github_allow_orgs(&self) -> Vec<String> {
+        self.default.github_allow_orgs()
+    }
+
+    fn github_require_org_membership(&self) -> bool {
+        self.default.github_require_org_membership()
+    }
+
+    fn github_service_token(&self) -> Option<&str> {
+        self.default.github_service_token()
+    }
+
+    fn github_http(&self) -> HttpCfg {
+        self.default.github_http()
+    }
+}
+
+impl GitHubApps for AppsFixture {
+    fn github_apps(&self) -> &BTreeMap<String, GitHubCfg> {
+        &self.apps
+    }
+
+    fn github_client_options(&self) -> &GitHubClientOptions {
+        &self.default.options
+    }
+}
+
+/// An application with the given client id and secret, which sends no request through a proxy.
+pub fn app(client_id: &str, client_secret: &str) -> GitHubCfg {
+    let mut cfg = GitHubCfg::default();
+    cfg.client_id = client_id.to_string();
+    cfg.client_secret = client_secret.to_string();
+    cfg.http.proxy = None;
+    cfg
+}
+
+/// The account `UserFixture::minimal()` converts to.
+pub fn expected_minimal_account() -> sessionsrv::Account {
+    let mut account = sessionsrv::Account::new();
+    account.set_provider(sessionsrv::OAuthProvider::GitHub);
+    account.set_provider_host("github.com".to_string());
+    account.set_name(LOGIN.to_string());
+    account.set_extern_id(ID);
+    account
+}
@@ -12,96 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+
+//! Payloads of the GitHub API and the checks and conversions applied to them.
+//!
+//! Nothing in this module sends a request, so it is available without the `client` feature. The
+//! `GitHubClient` lives in `oauth::client` and is re-exported here when the feature is enabled.
+
 use std::fmt;
-use std::io::Read;
 
-use hyper::{self, Url};
-use hyper::status::StatusCode;
-use hyper::header::{Authorization, Accept, Bearer, UserAgent, qitem};
-use hyper::mime::{Mime, TopLevel, SubLevel};
 use protocol::sessionsrv;
-use rustc_serialize::json;
+use rustc_serialize::Decodable;
+use rustc_serialize::json::{self, DecoderError, Json};
+use url::Url;
 
 use config;
 use error::{Error, Result};
 
-const USER_AGENT: &'static str = "Habitat-Builder";
-
-pub struct GitHubClient {
-    pub url: String,
-    pub client_id: String,
-    pub client_secret: String,
-}
-
-impl GitHubClient {
-    pub fn new<T: config::GitHubOAuth>(config: &T) -> Self {
-        GitHubClient {
-            url: config.github_url().to_string(),
-            client_id: config.github_client_id().to_string(),
-            client_secret: config.github_client_secret().to_string(),
-        }
-    }
-
-    pub fn authenticate(&self, code: &str) -> Result<String> {
-        let url =
-            Url::parse(&format!("https://github.\
-                                 com/login/oauth/access_token?client_id={}&client_secret={}&code={}",
-                                self.client_id,
-                                self.client_secret,
-                                code))
-                .unwrap();
-        let mut rep = try!(http_post(url));
-        if rep.status.is_success() {
-            let mut encoded = String::new();
-            try!(rep.read_to_string(&mut encoded));
-            match json::decode(&encoded) {
-                Ok(msg @ AuthOk { .. }) => {
-                    let scope = "user:email".to_string();
-                    if msg.has_scope(&scope) {
-                        Ok(msg.access_token)
-                    } else {
-                        Err(Error::MissingScope(scope))
-                    }
-                }
-                Err(_) => {
-                    let err: AuthErr = try!(json::decode(&encoded));
-                    Err(Error::from(err))
-                }
-            }
-        } else {
-            Err(Error::HTTP(rep.status))
-        }
-    }
-
-    pub fn user(&self, token: &str) -> Result<User> {
-        let url = Url::parse(&format!("{}/user", self.url)).unwrap();
-        let mut rep = try!(http_get(url, token));
-        let mut body = String::new();
-        try!(rep.read_to_string(&mut body));
-        if rep.status != StatusCode::Ok {
-            let err: HashMap<String, String> = try!(json::decode(&body));
-            return Err(Error::GitHubAPI(err));
-        }
-        let user: User = json::decode(&body).unwrap();
-        Ok(user)
-    }
+#[cfg(feature = "client")]
+pub use super::client::{ClientStats, GitHubClient, GitHubClientBuilder, GitHubClients,
+                        MigrationReport, RefreshReport};
 
-    pub fn emails(&self, token: &str) -> Result<Vec<Email>> {
-        let url = Url::parse(&format!("{}/user/emails", self.url)).unwrap();
-        let mut rep = try!(http_get(url, token));
-        let mut body = String::new();
-        try!(rep.read_to_string(&mut body));
-        if rep.status != StatusCode::Ok {
-            let err: HashMap<String, String> = try!(json::decode(&body));
-            return Err(Error::GitHubAPI(err));
-        }
-        let emails: Vec<Email> = try!(json::decode(&body));
-        Ok(emails)
-    }
-}
-
-#[derive(Debug, RustcEncodable, RustcDecodable)]
+#[derive(Clone, Debug, RustcEncodable, RustcDecodable)]
 pub struct User {
     pub login: String,
     pub id: u64,
@@ -124,6 +55,8 @@ pub struct User {
     pub blog: Option<String>,
     pub location: Option<String>,
     pub email: Option<String>,
+    /// Whether the provider verified `email`, for providers whose profiles say. GitHub's don't.
+    pub email_verified: Option<bool>,
     pub hireable: Option<bool>,
     pub bio: Option<String>,
     pub public_repos: u32,
@@ -134,10 +67,36 @@ pub struct User {
     pub updated_at: String,
 }
 
+impl User {
+    /// Returns the user's avatar URL requesting an image of the given size in pixels. See
+    /// `sized_avatar_url`.
+    pub fn avatar_url_sized(&self, px: u32) -> String {
+        sized_avatar_url(&self.avatar_url, px)
+    }
+
+    /// Returns the host of the GitHub instance the user belongs to, taken from their profile URL.
+    /// Public GitHub is assumed if the profile URL can't be parsed.
+    pub fn provider_host(&self) -> String {
+        Url::parse(&self.html_url)
+            .ok()
+            .and_then(|url| url.host_str().map(|h| h.to_lowercase()))
+            .unwrap_or(config::GITHUB_HOST.to_string())
+    }
+}
+
 impl From<User> for sessionsrv::Account {
     fn from(user: User) -> sessionsrv::Account {
         let mut account = sessionsrv::Account::new();
+        if !user.avatar_url.is_empty() {
+            account.set_avatar_url(user.avatar_url_sized(config::DEFAULT_AVATAR_SIZE));
+        }
+        account.set_provider(sessionsrv::OAuthProvider::GitHub);
+        account.set_provider_host(user.provider_host());
         account.set_name(user.login);
+        account.set_extern_id(user.id);
+        if let Some(name) = user.name {
+            account.set_display_name(name);
+        }
         if let Some(email) = user.email {
             account.set_email(email);
         }
@@ -145,13 +104,151 @@ impl From<User> for sessionsrv::Account {
     }
 }
 
-#[derive(Debug, RustcEncodable, RustcDecodable)]
+/// Returns the given avatar URL with its `s` query parameter set to the given size in pixels.
+/// GitHub and Gravatar both read the size from `s`; any previous `s` or `size` parameter is
+/// replaced and every other parameter is kept. An empty URL, a size of zero, or a URL which can't
+/// be parsed as http or https is returned unchanged.
+pub fn sized_avatar_url(avatar_url: &str, px: u32) -> String {
+    if avatar_url.is_empty() || px == 0 {
+        return avatar_url.to_string();
+    }
+    let mut url = match Url::parse(avatar_url) {
+        Ok(ref url) if url.scheme() == "http" || url.scheme() == "https" => url.clone(),
+        _ => return avatar_url.to_string(),
+    };
+    let pairs: Vec<(String, String)> = url.query_pairs()
+        .filter(|&(ref k, _)| k != "s" && k != "size")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("s", &px.to_string());
+    url.into_string()
+}
+
+#[derive(Clone, Debug, RustcEncodable, RustcDecodable)]
 pub struct Email {
     pub email: String,
     pub primary: bool,
     pub verified: bool,
 }
 
+/// True if the given address is a GitHub noreply address, for example
+/// `octocat@users.noreply.github.com`, which can't receive mail.
+pub fn is_noreply_email(email: &str) -> bool {
+    match email.rsplit('@').next() {
+        Some(domain) => {
+            let domain = domain.to_lowercase();
+            domain.starts_with("users.noreply.") || domain.starts_with("noreply.")
+        }
+        None => false,
+    }
+}
+
+/// Returns the primary email of the given list if it has been verified and isn't a noreply
+/// address.
+pub fn primary_verified_email(emails: &[Email]) -> Option<&Email> {
+    emails.iter().find(|e| e.primary && e.verified && !is_noreply_email(&e.email))
+}
+
+#[derive(Clone, Debug, RustcEncodable, RustcDecodable)]
+pub struct Org {
+    pub login: String,
+    pub id: u64,
+}
+
+#[derive(Debug, RustcEncodable, RustcDecodable)]
+pub struct Team {
+    pub id: u64,
+    pub name: String,
+    pub slug: String,
+    /// Organization owning the team. Only present when teams are listed across organizations.
+    pub organization: Option<Org>,
+}
+
+/// A member of a team, as listed by `GET /teams/:id/members`.
+#[derive(Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct TeamMember {
+    pub login: String,
+    pub id: u64,
+}
+
+/// True if the given error was caused by an organization enforcing SAML single sign-on, which an
+/// access token must be authorized for before the organization's resources can be read.
+pub fn is_sso_error(err: &Error) -> bool {
+    match *err {
+        Error::GitHubAPI(ref e) => e.is_sso(),
+        _ => false,
+    }
+}
+
+/// Returns the error a `403 Forbidden` answer, with the given body, to a listing of memberships
+/// stands for. GitHub answers so when the token may not see the organizations or teams of its
+/// owner, such as without the `read:org` scope, which is an `Error::OrgAccessDenied` with its
+/// message. It answers so when single sign-on or the rate limit is in the way too, which stay an
+/// `Error::GitHubAPI`.
+pub fn org_forbidden_error(body: GitHubError) -> Error {
+    if body.is_sso() || body.message.contains("rate limit") {
+        Error::GitHubAPI(body)
+    } else {
+        Error::OrgAccessDenied(body.message)
+    }
+}
+
+/// True if the given error is likely to go away when the request is retried: the connection to
+/// GitHub failed, its circuit breaker is open, or it answered with a server error.
+pub fn is_transient_error(err: &Error) -> bool {
+    match *err {
+        Error::CircuitOpen(_) |
+        Error::GitHubTimeout(_) |
+        Error::IO(_) => true,
+        #[cfg(feature = "client")]
+        Error::HttpClient(_) |
+        Error::HyperError(_) => true,
+        #[cfg(feature = "client")]
+        Error::HTTP(status) => status.is_server_error(),
+        _ => false,
+    }
+}
+
+#[derive(Debug, RustcEncodable, RustcDecodable)]
+pub struct TeamMembership {
+    pub state: String,
+    pub role: String,
+}
+
+impl TeamMembership {
+    /// True if the membership has been accepted. Pending invitations don't count.
+    pub fn is_active(&self) -> bool {
+        self.state == "active"
+    }
+}
+
+/// Body of `GET /rate_limit`, the rate limit status of the token asking, which doesn't count
+/// against it.
+#[derive(Clone, Debug, RustcEncodable, RustcDecodable)]
+pub struct RateLimits {
+    pub resources: RateLimitResources,
+}
+
+#[derive(Clone, Debug, RustcEncodable, RustcDecodable)]
+pub struct RateLimitResources {
+    /// Window of the REST API, which every request of the client counts against
+    pub core: RateLimitWindow,
+    pub search: RateLimitWindow,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct RateLimitWindow {
+    /// Requests allowed in a window
+    pub limit: u32,
+    /// Requests remaining in the current window
+    pub remaining: u32,
+    /// Seconds since the unix epoch at which the window resets
+    pub reset: u64,
+}
+
 #[derive(Debug, RustcDecodable, RustcEncodable)]
 pub struct AuthOk {
     pub access_token: String,
@@ -161,10 +258,26 @@ pub struct AuthOk {
 
 impl AuthOk {
     pub fn has_scope(&self, grant: &str) -> bool {
-        self.scope.split(",").collect::<Vec<&str>>().iter().any(|&p| p == grant)
+        parse_scopes(&self.scope).iter().any(|scope| scope == grant)
     }
 }
 
+/// Returns the scopes of a comma separated list, the way GitHub grants them in the token
+/// exchange and the `X-OAuth-Scopes` header. Whitespace around a scope and empty entries are
+/// dropped, and the order of the list is kept.
+pub fn parse_scopes(scopes: &str) -> Vec<String> {
+    scopes.split(',')
+        .map(|scope| scope.trim())
+        .filter(|scope| !scope.is_empty())
+        .map(|scope| scope.to_string())
+        .collect()
+}
+
+/// Returns the comma separated list of the given scopes `parse_scopes` parses back.
+pub fn format_scopes(scopes: &[String]) -> String {
+    scopes.join(",")
+}
+
 #[derive(RustcDecodable, RustcEncodable, Debug)]
 pub struct AuthErr {
     pub error: String,
@@ -182,26 +295,681 @@ impl fmt::Display for AuthErr {
     }
 }
 
+/// A request GitHub refused or failed, with what GitHub said about why, as in
+///
+/// ```text
+/// {"message":"Validation Failed",
+///  "errors":[{"resource":"Repository","field":"name","code":"already_exists"}],
+///  "documentation_url":"https://developer.github.com/v3/repos/#create"}
+/// ```
+///
+/// Displays as a single line, such as `GitHub API error 422 on /user/repos: Validation Failed
+/// (Repository.name already_exists), see https://developer.github.com/v3/repos/#create`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, RustcEncodable)]
+pub struct GitHubError {
+    /// HTTP status GitHub answered with, 0 if it isn't known
+    pub status: u16,
+    /// Path of the request, with secrets such as the token of a token check redacted
+    pub path: String,
+    pub message: String,
+    pub documentation_url: Option<String>,
+    /// The reasons a request failed validation for, listed by a `422 Unprocessable Entity`
+    pub errors: Vec<FieldError>,
+}
+
+impl GitHubError {
+    /// Returns the error as answered with the given status to a request for the given path,
+    /// which must already be redacted.
+    pub fn with_request(mut self, status: u16, path: &str) -> Self {
+        self.status = status;
+        self.path = path.to_string();
+        self
+    }
+
+    /// True if an organization enforcing SAML single sign-on refused the request, see
+    /// `is_sso_error`.
+    pub fn is_sso(&self) -> bool {
+        self.message.contains("SAML") || self.message.contains("SSO")
+    }
+}
+
+impl fmt::Display for GitHubError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "GitHub API error"));
+        if self.status != 0 {
+            try!(write!(f, " {}", self.status));
+        }
+        if !self.path.is_empty() {
+            try!(write!(f, " on {}", self.path));
+        }
+        if !self.message.is_empty() {
+            try!(write!(f, ": {}", one_line(&self.message)));
+        }
+        if !self.errors.is_empty() {
+            let errors: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+            try!(write!(f, " ({})", errors.join(", ")));
+        }
+        if let Some(ref url) = self.documentation_url {
+            try!(write!(f, ", see {}", one_line(url)));
+        }
+        Ok(())
+    }
+}
+
+/// A reason a request failed validation, such as a missing field. `code` is one of `missing`,
+/// `missing_field`, `invalid`, `already_exists`, or `custom`, which `message` explains.
+#[derive(Clone, Debug, Default, PartialEq, Eq, RustcEncodable)]
+pub struct FieldError {
+    pub resource: Option<String>,
+    pub field: Option<String>,
+    pub code: String,
+    pub message: Option<String>,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let target: Vec<&str> = self.resource
+            .iter()
+            .chain(self.field.iter())
+            .map(|s| s.as_str())
+            .collect();
+        let reason = match self.message {
+            Some(ref message) => one_line(message),
+            None => one_line(&self.code),
+        };
+        if target.is_empty() {
+            write!(f, "{}", reason)
+        } else {
+            write!(f, "{} {}", target.join("."), reason)
+        }
+    }
+}
+
+/// Returns the text with its line breaks replaced by spaces, so an error displays on one line
+/// of a log.
+fn one_line(text: &str) -> String {
+    text.replace(|c: char| c == '\r' || c == '\n', " ")
+}
+
 #[derive(RustcDecodable, RustcEncodable)]
 pub enum AuthResp {
     AuthOk,
     AuthErr,
 }
 
-fn http_get(url: Url, token: &str) -> Result<hyper::client::response::Response> {
-    hyper::Client::new()
-        .get(url)
-        .header(Accept(vec![qitem(Mime(TopLevel::Application, SubLevel::Json, vec![]))]))
-        .header(Authorization(Bearer { token: token.to_owned() }))
-        .header(UserAgent(USER_AGENT.to_string()))
-        .send()
-        .map_err(|e| Error::from(e))
+/// Deepest nesting of arrays and objects a body of the GitHub API may have. rustc_serialize
+/// builds the tree of a body recursively, so one nested deeper than the stack allows would crash
+/// the process rather than fail to decode. No payload of the API comes close.
+pub const MAX_JSON_DEPTH: usize = 64;
+
+/// Decodes a body of the GitHub API.
+///
+/// # Errors
+///
+/// * The body isn't JSON, is nested deeper than `MAX_JSON_DEPTH`, or doesn't decode to `T`
+pub fn decode_json<T: Decodable>(body: &str) -> Result<T> {
+    try!(check_json_depth(body));
+    Ok(try!(json::decode(body)))
 }
 
-fn http_post(url: Url) -> Result<hyper::client::response::Response> {
-    hyper::Client::new()
-        .post(url)
-        .header(Accept(vec![qitem(Mime(TopLevel::Application, SubLevel::Json, vec![]))]))
-        .send()
-        .map_err(|e| Error::from(e))
+/// Returns the error of the JSON object GitHub answers a failed request with, such as
+/// `{"message":"Bad credentials","documentation_url":"https://developer.github.com/v3"}`. The
+/// status and path are left for the caller to add with `GitHubError::with_request`.
+///
+/// Members of an unexpected type, such as a `message` which isn't a string, are left out rather
+/// than failing the whole body. So are the entries of `errors` which are neither objects nor the
+/// strings some endpoints list instead, which are taken to be `custom` errors.
+///
+/// # Errors
+///
+/// * The body isn't JSON, is nested deeper than `MAX_JSON_DEPTH`, or isn't an object
+pub fn parse_error_body(body: &str) -> Result<GitHubError> {
+    try!(check_json_depth(body));
+    match try!(Json::from_str(body).map_err(DecoderError::ParseError)) {
+        Json::Object(members) => {
+            let string = |name: &str| members.get(name).and_then(|v| v.as_string());
+            let errors = match members.get("errors").and_then(|v| v.as_array()) {
+                Some(errors) => errors.iter().filter_map(field_error).collect(),
+                None => vec![],
+            };
+            Ok(GitHubError {
+                status: 0,
+                path: String::new(),
+                message: string("message").unwrap_or("").to_string(),
+                documentation_url: string("documentation_url").map(|url| url.to_string()),
+                errors: errors,
+            })
+        }
+        other => {
+            let found = match other {
+                Json::Array(_) => "Array",
+                Json::String(_) => "String",
+                Json::Boolean(_) => "Boolean",
+                Json::Null => "Null",
+                _ => "Number",
+            };
+            Err(Error::from(DecoderError::ExpectedError("Object".to_string(), found.to_string())))
+        }
+    }
+}
+
+/// Returns the reason an entry of the `errors` of an error body gives, see `parse_error_body`.
+fn field_error(entry: &Json) -> Option<FieldError> {
+    match *entry {
+        Json::Object(ref members) => {
+            let string = |name: &str| {
+                members.get(name).and_then(|v| v.as_string()).map(|s| s.to_string())
+            };
+            Some(FieldError {
+                resource: string("resource"),
+                field: string("field"),
+                code: string("code").unwrap_or(String::new()),
+                message: string("message"),
+            })
+        }
+        Json::String(ref message) => {
+            Some(FieldError {
+                code: "custom".to_string(),
+                message: Some(message.clone()),
+                ..FieldError::default()
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Fails a body whose arrays and objects nest deeper than `MAX_JSON_DEPTH`, counting the
+/// brackets outside of strings.
+fn check_json_depth(body: &str) -> Result<()> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for b in body.bytes() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => (),
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > MAX_JSON_DEPTH {
+                    let msg = format!("JSON nested deeper than {} levels", MAX_JSON_DEPTH);
+                    return Err(Error::from(DecoderError::ApplicationError(msg)));
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+serde_via_rustc_serialize!(User,
+                           Email,
+                           Org,
+                           Team,
+                           TeamMember,
+                           TeamMembership,
+                           RateLimits,
+                           RateLimitResources,
+                           RateLimitWindow,
+                           AuthOk,
+                           AuthErr);
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+    use std::str;
+
+    use hcore::util::fuzz;
+    use protobuf::{self, Message};
+    use protocol::sessionsrv;
+    use quickcheck::{quickcheck, Arbitrary, Gen};
+
+    use super::*;
+    use error::Error;
+    use oauth::fixtures::{self, email, UserFixture};
+    use oauth::identity::{AccountKey, Identity};
+
+    #[test]
+    fn account_from_full_user() {
+        let account = sessionsrv::Account::from(UserFixture::default().build());
+        assert_eq!(account, fixtures::expected_account());
+    }
+
+    #[test]
+    fn account_from_minimal_user() {
+        let account = sessionsrv::Account::from(UserFixture::minimal().build());
+        assert_eq!(account, fixtures::expected_minimal_account());
+        assert!(!account.has_avatar_url());
+        assert!(!account.has_display_name());
+        assert!(!account.has_email());
+    }
+
+    #[test]
+    fn account_conversion_snapshot() {
+        let account = sessionsrv::Account::from(UserFixture::default().login("bob").build());
+        assert_eq!(protobuf::text_format::print_to_string(&account),
+                   "email: \"octocat@github.com\" name: \"bob\" extern_id: 583231 avatar_url: \
+                    \"https://avatars.githubusercontent.com/u/583231?v=3&s=120\" display_name: \
+                    \"The Octocat\" provider: GitHub provider_host: \"github.com\"");
+    }
+
+    #[test]
+    fn account_fields_are_covered_by_conversion() {
+        // A field added to `Account` must be considered by `From<User> for Account`, the
+        // fixtures, and the snapshot above before it is added to this list.
+        let account = sessionsrv::Account::new();
+        let fields: Vec<&str> = account.descriptor().fields().iter().map(|f| f.name()).collect();
+        assert_eq!(fields,
+                   vec!["id",
+                        "email",
+                        "name",
+                        "extern_id",
+                        "avatar_url",
+                        "display_name",
+                        "email_verified",
+                        "provider",
+                        "provider_host"]);
+    }
+
+    /// An account as a session service which predates external ids decodes it: the fields it
+    /// knows, and how many it kept as unknown.
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct OldAccount {
+        id: u64,
+        email: Option<String>,
+        name: String,
+        unknown: usize,
+    }
+
+    fn decode_as_old(bytes: &[u8]) -> OldAccount {
+        let mut is = protobuf::CodedInputStream::from_bytes(bytes);
+        let mut unknown_fields = protobuf::UnknownFields::new();
+        let mut old = OldAccount::default();
+        while !is.eof().unwrap() {
+            let (field_number, wire_type) = is.read_tag_unpack().unwrap();
+            match field_number {
+                1 => old.id = is.read_uint64().unwrap(),
+                2 => old.email = Some(is.read_string().unwrap()),
+                3 => old.name = is.read_string().unwrap(),
+                _ => {
+                    protobuf::rt::read_unknown_or_skip_group(field_number,
+                                                             wire_type,
+                                                             &mut is,
+                                                             &mut unknown_fields)
+                        .unwrap();
+                    old.unknown += 1;
+                }
+            }
+        }
+        old
+    }
+
+    /// Returns an account encoded as a session service which predates external ids encodes it.
+    /// Only the fields it knew are set, which encode the same as they did then.
+    fn encode_as_old(id: u64, email: Option<&str>, name: &str) -> Vec<u8> {
+        let mut account = sessionsrv::Account::new();
+        account.set_id(id);
+        if let Some(email) = email {
+            account.set_email(email.to_string());
+        }
+        account.set_name(name.to_string());
+        account.write_to_bytes().unwrap()
+    }
+
+    #[test]
+    fn new_accounts_round_trip() {
+        let mut account = sessionsrv::Account::from(UserFixture::default().build());
+        account.set_id(42);
+        let bytes = account.write_to_bytes().unwrap();
+        let decoded: sessionsrv::Account = protobuf::parse_from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, account);
+        assert_eq!(AccountKey::from(&decoded),
+                   AccountKey::Extern {
+                       provider: sessionsrv::OAuthProvider::GitHub,
+                       provider_host: "github.com".to_string(),
+                       extern_id: fixtures::ID,
+                   });
+        assert!(AccountKey::from(&decoded).account_get().is_none());
+    }
+
+    #[test]
+    fn new_accounts_decode_in_old_session_services() {
+        let mut account = sessionsrv::Account::from(UserFixture::default().build());
+        account.set_id(42);
+        let old = decode_as_old(&account.write_to_bytes().unwrap());
+        // extern_id, avatar_url, display_name, provider and provider_host are skipped
+        assert_eq!(old,
+                   OldAccount {
+                       id: 42,
+                       email: Some(fixtures::EMAIL.to_string()),
+                       name: fixtures::LOGIN.to_string(),
+                       unknown: 5,
+                   });
+    }
+
+    #[test]
+    fn old_accounts_decode_in_new_gateways() {
+        let bytes = encode_as_old(42, Some(fixtures::EMAIL), fixtures::LOGIN);
+        assert_eq!(decode_as_old(&bytes).unknown, 0);
+        let account: sessionsrv::Account = protobuf::parse_from_bytes(&bytes).unwrap();
+        assert_eq!(account.get_id(), 42);
+        assert!(!account.has_extern_id());
+        assert!(!account.has_avatar_url());
+        assert!(!account.has_provider());
+        assert!(!account.has_provider_host());
+        let identity = Identity::from(&account);
+        assert_eq!(identity.login, fixtures::LOGIN);
+        assert_eq!(identity.email, Some(fixtures::EMAIL.to_string()));
+        assert_eq!(identity.avatar_url, "");
+        // Without an external id the account can only be found again by its login
+        let key = AccountKey::from(&account);
+        assert_eq!(key, AccountKey::Login(fixtures::LOGIN.to_string()));
+        assert_eq!(key.account_get().unwrap().get_name(), fixtures::LOGIN);
+        let account: sessionsrv::Account =
+            protobuf::parse_from_bytes(&encode_as_old(42, None, fixtures::LOGIN)).unwrap();
+        assert!(!account.has_email());
+        assert_eq!(Identity::from(&account).email, None);
+    }
+
+    #[test]
+    fn accounts_without_a_provider_host_are_from_public_github() {
+        let mut account = sessionsrv::Account::new();
+        account.set_name(fixtures::LOGIN.to_string());
+        account.set_extern_id(fixtures::ID);
+        assert_eq!(AccountKey::from(&account),
+                   AccountKey::Extern {
+                       provider: sessionsrv::OAuthProvider::GitHub,
+                       provider_host: "github.com".to_string(),
+                       extern_id: fixtures::ID,
+                   });
+    }
+
+    #[test]
+    fn provider_host_from_profile_url() {
+        let mut user = UserFixture::default().build();
+        assert_eq!(user.provider_host(), "github.com");
+        user.html_url = "https://GHE.example.com/octocat".to_string();
+        assert_eq!(user.provider_host(), "ghe.example.com");
+        user.html_url = String::new();
+        assert_eq!(user.provider_host(), "github.com");
+    }
+
+    #[test]
+    fn identity_from_user_matches_account() {
+        let user = UserFixture::default().build();
+        let identity = Identity::from(&user);
+        assert_eq!(identity.external_id, fixtures::ID);
+        assert_eq!(identity.login, fixtures::LOGIN);
+        assert_eq!(identity, Identity::from(&sessionsrv::Account::from(user)));
+    }
+
+    #[test]
+    fn sso_errors() {
+        let body = GitHubError {
+            message: "Resource protected by organization SAML enforcement.".to_string(),
+            ..GitHubError::default()
+        };
+        assert!(is_sso_error(&Error::GitHubAPI(body)));
+        let body = GitHubError {
+            message: "Not Found".to_string(),
+            ..GitHubError::default()
+        };
+        assert!(!is_sso_error(&Error::GitHubAPI(body)));
+        assert!(!is_sso_error(&Error::Sys));
+    }
+
+    #[test]
+    fn transient_errors() {
+        assert!(is_transient_error(&Error::CircuitOpen("github.com".to_string())));
+        assert!(!is_transient_error(&Error::GitHubAPI(GitHubError::default())));
+        assert!(!is_transient_error(&Error::Sys));
+    }
+
+    #[test]
+    #[cfg(feature = "client")]
+    fn transient_http_errors() {
+        use hyper::status::StatusCode;
+
+        assert!(is_transient_error(&Error::HTTP(StatusCode::BadGateway)));
+        assert!(!is_transient_error(&Error::HTTP(StatusCode::Forbidden)));
+    }
+
+    #[test]
+    fn noreply_emails() {
+        assert!(is_noreply_email("octocat@users.noreply.github.com"));
+        assert!(is_noreply_email("octocat@users.noreply.github.mycorp.com"));
+        assert!(is_noreply_email("noreply@noreply.github.com"));
+        assert!(!is_noreply_email("octocat@github.com"));
+    }
+
+    #[test]
+    fn primary_verified_email_skips_unusable() {
+        assert!(primary_verified_email(&fixtures::noreply_emails()).is_none());
+        assert!(primary_verified_email(&fixtures::unverified_emails()).is_none());
+        assert_eq!(primary_verified_email(&fixtures::verified_primary_emails()).unwrap().email,
+                   fixtures::EMAIL);
+        let emails = vec![email("octocat@example.com", true, false),
+                          email("octo@example.com", false, true)];
+        assert!(primary_verified_email(&emails).is_none());
+        let emails = vec![email("octo@example.com", false, true),
+                          email("octocat@example.com", true, true)];
+        assert_eq!(primary_verified_email(&emails).unwrap().email,
+                   "octocat@example.com");
+    }
+
+    #[test]
+    fn sized_avatar_urls() {
+        assert_eq!(sized_avatar_url("https://avatars.githubusercontent.com/u/583231", 40),
+                   "https://avatars.githubusercontent.com/u/583231?s=40");
+        assert_eq!(sized_avatar_url("https://avatars.githubusercontent.com/u/583231?v=3", 40),
+                   "https://avatars.githubusercontent.com/u/583231?v=3&s=40");
+        assert_eq!(sized_avatar_url("https://avatars.githubusercontent.com/u/583231?s=460&v=3",
+                                    40),
+                   "https://avatars.githubusercontent.com/u/583231?v=3&s=40");
+        assert_eq!(sized_avatar_url("https://secure.gravatar.com/avatar/205e460b479e2e5b48aec077\
+                                     10c08d50?d=https%3A%2F%2Fassets-cdn.github.com%2Fimages%2F\
+                                     gravatars%2Fgravatar-user-420.png&size=420",
+                                    40),
+                   "https://secure.gravatar.com/avatar/205e460b479e2e5b48aec07710c08d50?d=\
+                    https%3A%2F%2Fassets-cdn.github.com%2Fimages%2Fgravatars%2Fgravatar-user-\
+                    420.png&s=40");
+    }
+
+    #[test]
+    fn sized_avatar_url_keeps_unusable_input() {
+        assert_eq!(sized_avatar_url("", 40), "");
+        assert_eq!(sized_avatar_url("not a url", 40), "not a url");
+        assert_eq!(sized_avatar_url("data:image/png;base64,AAAA", 40),
+                   "data:image/png;base64,AAAA");
+        assert_eq!(sized_avatar_url("https://avatars.githubusercontent.com/u/1?v=3", 0),
+                   "https://avatars.githubusercontent.com/u/1?v=3");
+    }
+
+    /// Scopes GitHub grants, which the generated scopes are mostly drawn from so that lists
+    /// share scopes often.
+    const KNOWN_SCOPES: &'static [&'static str] = &["user", "user:email", "user:follow",
+                                                    "read:org", "write:org", "repo",
+                                                    "public_repo", "gist"];
+
+    /// Chars a scope is made of.
+    const SCOPE_CHARS: &'static str = "abcdefghijklmnopqrstuvwxyz:_";
+
+    /// Whitespace GitHub, or something in between, may put around a scope.
+    const PADDING_CHARS: &'static [char] = &[' ', '\t'];
+
+    #[derive(Clone, Debug)]
+    struct Scope(String);
+
+    impl Arbitrary for Scope {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            if g.gen_weighted_bool(4) {
+                let chars: Vec<char> = SCOPE_CHARS.chars().collect();
+                let len = g.gen_range(1, 8);
+                Scope((0..len).map(|_| *g.choose(&chars).unwrap()).collect())
+            } else {
+                Scope(g.choose(KNOWN_SCOPES).unwrap().to_string())
+            }
+        }
+
+        fn shrink(&self) -> Box<Iterator<Item = Self>> {
+            Box::new(self.0
+                .shrink()
+                .filter(|s| !s.is_empty() && s.chars().all(|c| SCOPE_CHARS.contains(c)))
+                .map(Scope))
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct Padding(String);
+
+    impl Arbitrary for Padding {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            let len = g.gen_range(0, 3);
+            Padding((0..len).map(|_| *g.choose(PADDING_CHARS).unwrap()).collect())
+        }
+
+        fn shrink(&self) -> Box<Iterator<Item = Self>> {
+            Box::new(self.0
+                .shrink()
+                .filter(|s| s.chars().all(|c| PADDING_CHARS.contains(&c)))
+                .map(Padding))
+        }
+    }
+
+    fn strings(scopes: &[Scope]) -> Vec<String> {
+        scopes.iter().map(|scope| scope.0.clone()).collect()
+    }
+
+    fn padded(scopes: &[(Scope, Padding, Padding)]) -> String {
+        scopes.iter()
+            .map(|&(ref scope, ref before, ref after)| {
+                format!("{}{}{}", before.0, scope.0, after.0)
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    #[test]
+    fn parse_scopes_drops_whitespace_and_empty_entries() {
+        assert_eq!(parse_scopes("user:email, read:org,,repo "),
+                   vec!["user:email".to_string(), "read:org".to_string(), "repo".to_string()]);
+        assert!(parse_scopes("").is_empty());
+    }
+
+    #[test]
+    fn scopes_round_trip() {
+        fn prop(scopes: Vec<Scope>) -> bool {
+            let scopes = strings(&scopes);
+            parse_scopes(&format_scopes(&scopes)) == scopes
+        }
+        quickcheck(prop as fn(Vec<Scope>) -> bool);
+    }
+
+    #[test]
+    fn has_scope_is_membership() {
+        fn prop(scopes: Vec<(Scope, Padding, Padding)>, probe: Scope) -> bool {
+            let granted: Vec<&str> = scopes.iter().map(|&(ref scope, _, _)| &scope.0[..]).collect();
+            let expected = granted.contains(&&probe.0[..]);
+            let mut reversed = scopes.clone();
+            reversed.reverse();
+            [padded(&scopes), padded(&reversed)].iter().all(|scope| {
+                let auth = AuthOk {
+                    access_token: "token".to_string(),
+                    scope: scope.clone(),
+                    token_type: "bearer".to_string(),
+                };
+                auth.has_scope(&probe.0) == expected
+            })
+        }
+        quickcheck(prop as fn(Vec<(Scope, Padding, Padding)>, Scope) -> bool);
+    }
+
+    #[test]
+    fn recorded_error_bodies_are_parsed() {
+        let err = parse_error_body(include_str!("../../tests/fixtures/recorded/error-sso.json"))
+            .unwrap();
+        assert!(err.message.starts_with("Resource protected by organization SAML"));
+        assert!(err.documentation_url.is_some());
+        assert!(err.errors.is_empty());
+        let body = r#"{"message":"Validation Failed","errors":[{"code":"missing_field"}]}"#;
+        let err = parse_error_body(body).unwrap();
+        assert_eq!(err.message, "Validation Failed");
+        assert_eq!(err.errors[0].code, "missing_field");
+        assert_eq!(err.documentation_url, None);
+    }
+
+    #[test]
+    fn error_bodies_of_unexpected_shapes_keep_what_they_can() {
+        let body = r#"{"message":7,"errors":["name is taken",3,{"code":1,"field":"name"}]}"#;
+        let err = parse_error_body(body).unwrap();
+        assert_eq!(err.message, "");
+        assert_eq!(err.errors,
+                   vec![FieldError {
+                            code: "custom".to_string(),
+                            message: Some("name is taken".to_string()),
+                            ..FieldError::default()
+                        },
+                        FieldError {
+                            field: Some("name".to_string()),
+                            ..FieldError::default()
+                        }]);
+        assert!(parse_error_body(r#"{"errors":{"code":"invalid"}}"#).unwrap().errors.is_empty());
+    }
+
+    #[test]
+    fn errors_display_on_one_line() {
+        let body = r#"{"message":"Validation\nFailed","errors":[
+            {"resource":"Repository","field":"name","code":"already_exists"},
+            {"resource":"Hook","code":"custom","message":"no\r\nnewline"}],
+            "documentation_url":"https://developer.github.com/v3/repos/#create"}"#;
+        let err = parse_error_body(body).unwrap().with_request(422, "/user/repos");
+        assert_eq!(err.to_string(),
+                   "GitHub API error 422 on /user/repos: Validation Failed (Repository.name \
+                    already_exists, Hook no  newline), see \
+                    https://developer.github.com/v3/repos/#create");
+        assert_eq!(Error::GitHubAPI(parse_error_body("{}").unwrap()).to_string(),
+                   "GitHub API error");
+    }
+
+    #[test]
+    fn error_bodies_which_are_not_objects_fail() {
+        for body in &["", "<html>", "[]", "\"message\"", "null", "{\"message\":"] {
+            match parse_error_body(body) {
+                Err(Error::JsonDecode(_)) => (),
+                other => panic!("{:?} parsed to {:?}", body, other),
+            }
+        }
+    }
+
+    #[test]
+    fn deeply_nested_bodies_fail() {
+        let nested: String = iter::repeat("[").take(100000).collect();
+        match parse_error_body(&nested) {
+            Err(Error::JsonDecode(_)) => (),
+            other => panic!("nested body parsed to {:?}", other),
+        }
+        assert!(decode_json::<Vec<Email>>(&nested).is_err());
+        // Brackets within strings don't count
+        let body = format!(r#"{{"message":"{}"}}"#, nested);
+        assert_eq!(parse_error_body(&body).unwrap().message, nested);
+    }
+
+    #[test]
+    #[ignore]
+    fn github_error_body_smoke() {
+        fuzz::smoke("github_error_body", |data| {
+            if let Ok(body) = str::from_utf8(data) {
+                let _ = parse_error_body(body);
+            }
+        });
+    }
 }
@@ -0,0 +1,197 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Payloads of version 3 of the GitLab API and their conversions into the GitHub payloads every
+//! `OAuthProvider` returns.
+//!
+//! A GitLab user becomes a `github::User` whose login is their username and whose profile URL is
+//! their page on the instance, so their account is recorded the way a GitHub user's is, under the
+//! host of the instance. Like `oauth::github`, nothing here sends a request; the `GitLabClient`
+//! lives in `oauth::gitlab_client` and is re-exported here when the `client` feature is enabled.
+
+use super::github::{AuthErr, Email, User};
+
+#[cfg(feature = "client")]
+pub use super::gitlab_client::GitLabClient;
+
+/// Path prefix of version 3 of the GitLab API
+pub const GITLAB_API_PATH: &'static str = "/api/v3";
+
+/// The owner of an access token, as returned by `GET /api/v3/user`.
+#[derive(Clone, Debug, RustcDecodable)]
+pub struct GitLabUser {
+    pub id: u64,
+    pub username: String,
+    pub name: Option<String>,
+    /// Primary email address
+    pub email: Option<String>,
+    /// `active`, or `blocked` for a user an administrator locked out
+    pub state: String,
+    pub avatar_url: Option<String>,
+    /// Profile page of the user on the instance
+    pub web_url: String,
+    pub created_at: String,
+    /// When the primary email address was confirmed, `None` if it hasn't been
+    pub confirmed_at: Option<String>,
+    pub is_admin: Option<bool>,
+    pub bio: Option<String>,
+    pub location: Option<String>,
+    pub website_url: Option<String>,
+    pub organization: Option<String>,
+}
+
+impl From<GitLabUser> for User {
+    fn from(user: GitLabUser) -> User {
+        let email = non_empty(user.email);
+        let email_verified = email.as_ref().map(|_| user.confirmed_at.is_some());
+        User {
+            login: user.username,
+            id: user.id,
+            avatar_url: user.avatar_url.unwrap_or(String::new()),
+            gravatar_id: String::new(),
+            url: String::new(),
+            html_url: user.web_url,
+            followers_url: String::new(),
+            following_url: String::new(),
+            gists_url: String::new(),
+            starred_url: String::new(),
+            subscriptions_url: String::new(),
+            organizations_url: String::new(),
+            repos_url: String::new(),
+            events_url: String::new(),
+            received_events_url: String::new(),
+            site_admin: user.is_admin.unwrap_or(false),
+            name: non_empty(user.name),
+            company: non_empty(user.organization),
+            blog: non_empty(user.website_url),
+            location: non_empty(user.location),
+            email: email,
+            email_verified: email_verified,
+            hireable: None,
+            bio: non_empty(user.bio),
+            public_repos: 0,
+            public_gists: 0,
+            followers: 0,
+            following: 0,
+            created_at: user.created_at,
+            updated_at: String::new(),
+        }
+    }
+}
+
+/// A secondary email address of the owner of an access token, as listed by
+/// `GET /api/v3/user/emails`. The primary address is only found on the user.
+#[derive(Clone, Debug, RustcDecodable)]
+pub struct GitLabEmail {
+    pub id: u64,
+    pub email: String,
+}
+
+/// Returns every email address of a user: their primary address, verified if it was confirmed,
+/// followed by their secondary addresses, which version 3 of the API doesn't say are verified and
+/// so are never taken to be.
+pub fn emails_of(user: &GitLabUser, secondary: Vec<GitLabEmail>) -> Vec<Email> {
+    let mut emails = vec![];
+    if let Some(email) = non_empty(user.email.clone()) {
+        emails.push(Email {
+            email: email,
+            primary: true,
+            verified: user.confirmed_at.is_some(),
+        });
+    }
+    emails.extend(secondary.into_iter().map(|e| {
+        Email {
+            email: e.email,
+            primary: false,
+            verified: false,
+        }
+    }));
+    emails
+}
+
+/// Response of `POST /oauth/token` to a code exchanged for an access token.
+#[derive(Clone, Debug, RustcDecodable)]
+pub struct GitLabToken {
+    pub access_token: String,
+    pub token_type: String,
+    pub refresh_token: Option<String>,
+    /// Space separated scopes granted to the token
+    pub scope: Option<String>,
+    /// Seconds since the unix epoch at which the token was issued
+    pub created_at: Option<u64>,
+}
+
+impl GitLabToken {
+    pub fn scopes(&self) -> Vec<String> {
+        self.scope.as_ref().map_or(vec![], |scope| parse_gitlab_scopes(scope))
+    }
+}
+
+/// Response of the GitLab OAuth endpoints to a request they refuse, such as a code which expired.
+#[derive(Clone, Debug, RustcDecodable)]
+pub struct GitLabTokenErr {
+    pub error: String,
+    pub error_description: Option<String>,
+}
+
+impl From<GitLabTokenErr> for AuthErr {
+    fn from(err: GitLabTokenErr) -> AuthErr {
+        AuthErr {
+            error: err.error,
+            error_description: err.error_description.unwrap_or(String::new()),
+            error_uri: String::new(),
+        }
+    }
+}
+
+/// Response of `GET /oauth/token/info` about a valid access token.
+#[derive(Clone, Debug, RustcDecodable)]
+pub struct GitLabTokenInfo {
+    /// GitLab id of the user owning the token
+    pub resource_owner_id: Option<u64>,
+    pub scopes: Vec<String>,
+    pub expires_in_seconds: Option<u64>,
+    pub application: Option<GitLabTokenApp>,
+    /// Seconds since the unix epoch at which the token was issued
+    pub created_at: u64,
+}
+
+#[derive(Clone, Debug, RustcDecodable)]
+pub struct GitLabTokenApp {
+    /// Application id of the OAuth application the token was issued to
+    pub uid: String,
+}
+
+/// Returns the scopes of a space separated list, the way GitLab grants them.
+pub fn parse_gitlab_scopes(scopes: &str) -> Vec<String> {
+    scopes.split_whitespace().map(|scope| scope.to_string()).collect()
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    match value {
+        Some(ref s) if s.trim().is_empty() => None,
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gitlab_scopes_are_space_separated() {
+        assert_eq!(parse_gitlab_scopes("api  read_user\t"), vec!["api", "read_user"]);
+        assert!(parse_gitlab_scopes(" ").is_empty());
+    }
+}
@@ -0,0 +1,333 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An identity provider for users of a GitLab instance, who log in through one of its OAuth
+//! applications.
+//!
+//! The client speaks version 3 of the GitLab API. Unlike GitHub, GitLab takes the parameters of
+//! its OAuth endpoints in a form encoded body rather than the query, and only exchanges a code
+//! along with the redirect URI it was issued for. GitLab groups aren't mapped onto organizations,
+//! so a GitLab user is a member of no organization or team, and policies granting privileges to
+//! members of one grant GitLab users nothing.
+
+use std::io::Read;
+
+use hyper::Url;
+use hyper::status::StatusCode;
+use protocol::sessionsrv;
+use rustc_serialize::Decodable;
+use url::form_urlencoded;
+
+use config;
+use error::{Error, Result};
+use http;
+use super::OAuthProvider;
+use super::github::{decode_json, AuthErr, Email, Org, Team, TeamMembership, User};
+use super::gitlab::{emails_of, GitLabEmail, GitLabToken, GitLabTokenErr, GitLabTokenInfo,
+                    GitLabUser, GITLAB_API_PATH};
+use super::health::HealthStatus;
+use super::token::{self, TokenRecord};
+
+const USER_AGENT: &'static str = "Habitat-Builder";
+
+pub struct GitLabClient {
+    /// Root URL of the instance
+    url: String,
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+    /// Seconds after which a stored access token is checked with GitLab again
+    token_validation_secs: u64,
+    api: http::ApiClient,
+}
+
+impl GitLabClient {
+    /// Create a client for the GitLab instance and OAuth application of the given configuration.
+    ///
+    /// # Errors
+    ///
+    /// * The client id, client secret, or redirect URI are missing
+    /// * The URL of the instance can't be parsed
+    pub fn new<T: config::OAuth>(config: &T) -> Result<Self> {
+        if config.oauth_client_id().is_empty() {
+            return Err(Error::RequiredConfigField("auth.client_id"));
+        }
+        if config.oauth_client_secret().is_empty() {
+            return Err(Error::RequiredConfigField("auth.client_secret"));
+        }
+        let redirect_uri = match config.oauth_redirect_uri() {
+            Some(uri) if !uri.is_empty() => uri.to_string(),
+            _ => return Err(Error::RequiredConfigField("auth.redirect_uri")),
+        };
+        let options = config::GitHubClientOptions::default();
+        let api_options = http::ApiOptions {
+            user_agent: USER_AGENT.to_string(),
+            connect_timeout_ms: options.connect_timeout_ms,
+            read_timeout_ms: options.read_timeout_ms,
+            max_retries: options.max_retries,
+            base_backoff_ms: options.base_backoff_ms,
+            trace_capacity: 0,
+        };
+        Ok(GitLabClient {
+            url: try!(config::normalize_web_url(config.oauth_url(), true)),
+            client_id: config.oauth_client_id().to_string(),
+            client_secret: config.oauth_client_secret().to_string(),
+            redirect_uri: redirect_uri,
+            token_validation_secs: options.token_validation_secs,
+            api: http::ApiClient::new(config.oauth_http(), api_options),
+        })
+    }
+
+    /// Exchange an OAuth code for an access token.
+    pub fn authenticate(&self, code: &str) -> Result<TokenRecord> {
+        let body = form_urlencoded::Serializer::new(String::new())
+            .append_pair("client_id", &self.client_id)
+            .append_pair("client_secret", &self.client_secret)
+            .append_pair("code", code)
+            .append_pair("grant_type", "authorization_code")
+            .append_pair("redirect_uri", &self.redirect_uri)
+            .finish();
+        let (status, body) = try!(self.post_form("/oauth/token", body));
+        if status.is_success() {
+            let msg: GitLabToken = try!(decode_json(&body));
+            let scopes = msg.scopes();
+            let mut record = TokenRecord::new(msg.access_token, scopes, self.client_id.clone());
+            if let Some(created_at) = msg.created_at {
+                record.issued_at = created_at;
+            }
+            Ok(record)
+        } else {
+            match decode_json::<GitLabTokenErr>(&body) {
+                Ok(err) => Err(Error::Auth(AuthErr::from(err))),
+                Err(_) => Err(Error::HTTP(status)),
+            }
+        }
+    }
+
+    pub fn user(&self, token: &str) -> Result<User> {
+        let user: GitLabUser = try!(self.api_get("/user", token));
+        Ok(User::from(user))
+    }
+
+    /// Returns every email address of the owner of the given token, see `gitlab::emails_of`.
+    pub fn emails(&self, token: &str) -> Result<Vec<Email>> {
+        let user: GitLabUser = try!(self.api_get("/user", token));
+        let secondary: Vec<GitLabEmail> = try!(self.api_get("/user/emails", token));
+        Ok(emails_of(&user, secondary))
+    }
+
+    /// Ask GitLab whether the given token is still valid for this client's OAuth application.
+    /// Returns `None` if the token has been revoked, has expired, or belongs to another
+    /// application.
+    pub fn check_token(&self, token: &str) -> Result<Option<TokenRecord>> {
+        let url = self.url_for("/oauth/token/info");
+        let mut rep = try!(self.api.get(url).accept_json().bearer(token).send());
+        if rep.status == StatusCode::Unauthorized {
+            return Ok(None);
+        }
+        let info: GitLabTokenInfo = try!(decode_body(&mut rep));
+        let client_id = info.application.map_or(self.client_id.clone(), |app| app.uid);
+        if client_id != self.client_id {
+            return Ok(None);
+        }
+        Ok(Some(TokenRecord {
+            token: token.to_string(),
+            scopes: info.scopes,
+            client_id: client_id,
+            issued_at: info.created_at,
+            last_validated_at: token::now(),
+            user_id: info.resource_owner_id,
+        }))
+    }
+
+    /// Re-check the given record with GitLab if it hasn't been validated recently, updating its
+    /// scopes and validation time.
+    ///
+    /// # Errors
+    ///
+    /// * The token was issued to a different OAuth application
+    /// * The token has been revoked or has expired
+    pub fn refresh_validation(&self, record: &mut TokenRecord) -> Result<()> {
+        if !record.is_legacy() && record.client_id != self.client_id {
+            return Err(Error::TokenClientMismatch(record.client_id.clone()));
+        }
+        if !record.is_legacy() && !record.is_stale(self.token_validation_secs) {
+            return Ok(());
+        }
+        match try!(self.check_token(&record.token)) {
+            Some(checked) => {
+                *record = checked;
+                Ok(())
+            }
+            None => Err(Error::TokenRevoked),
+        }
+    }
+
+    /// Revoke the given access token. GitLab answers a token it doesn't know about as it does one
+    /// it revoked.
+    pub fn logout(&self, token: &str) -> Result<()> {
+        let body = form_urlencoded::Serializer::new(String::new())
+            .append_pair("client_id", &self.client_id)
+            .append_pair("client_secret", &self.client_secret)
+            .append_pair("token", token)
+            .finish();
+        let (status, _) = try!(self.post_form("/oauth/revoke", body));
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(Error::HTTP(status))
+        }
+    }
+
+    /// Returns the host of the instance, which scopes the ids of its users.
+    pub fn host(&self) -> String {
+        Url::parse(&self.url)
+            .ok()
+            .and_then(|url| url.host_str().map(|h| h.to_lowercase()))
+            .unwrap_or(String::new())
+    }
+
+    fn url_for(&self, path: &str) -> Url {
+        Url::parse(&format!("{}{}", self.url, path)).unwrap()
+    }
+
+    /// Send a GET request for the given path of the API, authorized with the given token, and
+    /// decode the response.
+    fn api_get<T: Decodable>(&self, path: &str, token: &str) -> Result<T> {
+        let url = self.url_for(&format!("{}{}", GITLAB_API_PATH, path));
+        debug!("GitLab API request, url={}", url);
+        let mut rep = try!(self.api.get(url).accept_json().bearer(token).send());
+        decode_body(&mut rep)
+    }
+
+    /// Send a POST request with the given form encoded body to one of the OAuth endpoints,
+    /// returning the status and body of the response.
+    fn post_form(&self, path: &str, body: String) -> Result<(StatusCode, String)> {
+        let mut rep = try!(self.api
+            .post(self.url_for(path))
+            .accept_json()
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body.into_bytes())
+            .send());
+        let mut body = String::new();
+        try!(rep.read_to_string(&mut body));
+        Ok((rep.status, body))
+    }
+}
+
+impl OAuthProvider for GitLabClient {
+    fn authenticate(&self, code: &str) -> Result<TokenRecord> {
+        GitLabClient::authenticate(self, code)
+    }
+
+    fn client_id(&self) -> String {
+        self.client_id.clone()
+    }
+
+    fn check_token(&self, token: &str) -> Result<Option<TokenRecord>> {
+        GitLabClient::check_token(self, token)
+    }
+
+    fn refresh_validation(&self, record: &mut TokenRecord) -> Result<()> {
+        GitLabClient::refresh_validation(self, record)
+    }
+
+    fn user(&self, token: &str) -> Result<User> {
+        GitLabClient::user(self, token)
+    }
+
+    fn emails(&self, token: &str) -> Result<Vec<Email>> {
+        GitLabClient::emails(self, token)
+    }
+
+    fn orgs(&self, _token: &str) -> Result<Vec<Org>> {
+        Ok(vec![])
+    }
+
+    fn teams(&self, _token: Option<&str>, _org: &str) -> Result<Vec<Team>> {
+        Ok(vec![])
+    }
+
+    fn user_teams(&self, _token: &str) -> Result<Vec<Team>> {
+        Ok(vec![])
+    }
+
+    fn team_membership(&self,
+                       _token: &str,
+                       _team_id: u64,
+                       _login: &str)
+                       -> Result<Option<TeamMembership>> {
+        Ok(None)
+    }
+
+    fn logout(&self, token: &str) -> Result<()> {
+        GitLabClient::logout(self, token)
+    }
+
+    fn provider(&self) -> sessionsrv::OAuthProvider {
+        sessionsrv::OAuthProvider::GitLab
+    }
+
+    fn provider_host(&self) -> String {
+        self.host()
+    }
+
+    fn health(&self) -> HealthStatus {
+        HealthStatus::healthy("GitLab isn't probed by health checks")
+    }
+}
+
+/// Decodes a response of GitLab, failing with its status unless it is `200 OK`.
+fn decode_body<T: Decodable>(rep: &mut http::ApiResponse) -> Result<T> {
+    let mut body = String::new();
+    try!(rep.read_to_string(&mut body));
+    if rep.status != StatusCode::Ok {
+        return Err(Error::HTTP(rep.status));
+    }
+    decode_json(&body)
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::sessionsrv;
+
+    use super::*;
+    use config::GitLabCfg;
+    use error::Error;
+    use oauth::OAuthProvider;
+
+    fn cfg() -> GitLabCfg {
+        GitLabCfg {
+            url: "https://gitlab.example.com/".to_string(),
+            client_id: "6f1c2d9e0a8b4c7d".to_string(),
+            client_secret: "s3cr3t".to_string(),
+            redirect_uri: "https://builder.example.com/".to_string(),
+        }
+    }
+
+    #[test]
+    fn clients_are_scoped_to_their_instance() {
+        let client = GitLabClient::new(&cfg()).unwrap();
+        assert_eq!(client.provider(), sessionsrv::OAuthProvider::GitLab);
+        assert_eq!(client.provider_host(), "gitlab.example.com");
+        assert_eq!(client.url_for("/oauth/token").as_str(),
+                   "https://gitlab.example.com/oauth/token");
+        let mut missing = cfg();
+        missing.redirect_uri = String::new();
+        match GitLabClient::new(&missing) {
+            Err(Error::RequiredConfigField(field)) => assert_eq!(field, "auth.redirect_uri"),
+            _ => panic!("expected the redirect URI to be required"),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Health of an identity provider, as reported by `OAuthProvider::health` and surfaced on the
+//! gateway's status endpoint.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use rustc_serialize::json::{Json, ToJson};
+
+/// How healthy a provider is, from best to worst.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Health {
+    /// Logins can be completed
+    Healthy,
+    /// Logins can be completed but may be slow or partial
+    Degraded,
+    /// Logins can't be completed
+    Unhealthy,
+}
+
+impl Health {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Health::Healthy => "healthy",
+            Health::Degraded => "degraded",
+            Health::Unhealthy => "unhealthy",
+        }
+    }
+}
+
+/// Health of a provider and why it was judged so.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthStatus {
+    pub health: Health,
+    pub reason: String,
+}
+
+impl HealthStatus {
+    pub fn healthy<S: Into<String>>(reason: S) -> Self {
+        HealthStatus::new(Health::Healthy, reason)
+    }
+
+    pub fn degraded<S: Into<String>>(reason: S) -> Self {
+        HealthStatus::new(Health::Degraded, reason)
+    }
+
+    pub fn unhealthy<S: Into<String>>(reason: S) -> Self {
+        HealthStatus::new(Health::Unhealthy, reason)
+    }
+
+    /// Returns the less healthy of the two statuses, this one if they are as healthy.
+    pub fn worst(self, other: HealthStatus) -> HealthStatus {
+        if other.health > self.health { other } else { self }
+    }
+
+    fn new<S: Into<String>>(health: Health, reason: S) -> Self {
+        HealthStatus {
+            health: health,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl ToJson for HealthStatus {
+    fn to_json(&self) -> Json {
+        let mut m = BTreeMap::new();
+        m.insert("health".to_string(), self.health.name().to_json());
+        m.insert("reason".to_string(), self.reason.to_json());
+        Json::Object(m)
+    }
+}
+
+/// Limits the probes of a provider to one per interval, however often its health is asked for,
+/// and remembers what the last one found.
+#[derive(Debug, Default)]
+pub struct ProbeSchedule {
+    last_probe: Option<Instant>,
+    last_status: Option<HealthStatus>,
+}
+
+impl ProbeSchedule {
+    /// Returns whether a probe is due at `now`, recording that one began if it is. A probe is
+    /// due when none began within `interval` before `now`.
+    pub fn begin(&mut self, now: Instant, interval: Duration) -> bool {
+        match self.last_probe {
+            Some(at) if since(now, at) < interval => false,
+            _ => {
+                self.last_probe = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Record what the probe which began last found.
+    pub fn finish(&mut self, status: HealthStatus) {
+        self.last_status = Some(status);
+    }
+
+    /// Returns what the last probe found, or a degraded status while the first one is running.
+    pub fn last_status(&self) -> HealthStatus {
+        match self.last_status {
+            Some(ref status) => status.clone(),
+            None => HealthStatus::degraded("the first probe is still running"),
+        }
+    }
+}
+
+/// Returns the time from `then` to `now`, or zero if `then` is later, as an instant read on
+/// another thread can be.
+pub fn since(now: Instant, then: Instant) -> Duration {
+    if now > then {
+        now.duration_since(then)
+    } else {
+        Duration::from_secs(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    #[test]
+    fn probes_are_due_once_per_interval() {
+        let interval = Duration::from_secs(30);
+        let now = Instant::now();
+        let mut schedule = ProbeSchedule::default();
+        assert!(schedule.begin(now, interval));
+        assert_eq!(schedule.last_status().health, Health::Degraded);
+        schedule.finish(HealthStatus::healthy("ok"));
+        for secs in 0..30 {
+            assert!(!schedule.begin(now + Duration::from_secs(secs), interval));
+        }
+        assert_eq!(schedule.last_status(), HealthStatus::healthy("ok"));
+        assert!(schedule.begin(now + interval, interval));
+        assert!(!schedule.begin(now + Duration::from_secs(59), interval));
+    }
+
+    #[test]
+    fn worst_status_wins() {
+        let healthy = HealthStatus::healthy("a");
+        let degraded = HealthStatus::degraded("b");
+        assert_eq!(healthy.clone().worst(degraded.clone()), degraded);
+        assert_eq!(degraded.clone().worst(healthy.clone()), degraded);
+        assert_eq!(healthy.clone().worst(HealthStatus::healthy("c")), healthy);
+        assert_eq!(degraded.worst(HealthStatus::unhealthy("d")).health,
+                   Health::Unhealthy);
+    }
+
+    #[test]
+    fn later_instants_are_no_time_ago() {
+        let now = Instant::now();
+        let later = now + Duration::from_secs(5);
+        assert_eq!(since(now, later), Duration::from_secs(0));
+        assert_eq!(since(later, now), Duration::from_secs(5));
+    }
+}
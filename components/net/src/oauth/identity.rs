@@ -0,0 +1,182 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provider neutral description of who is logged in.
+//!
+//! An `Identity` carries only the fields needed to display a user, so consumers don't need to
+//! depend on the shape of a particular provider's user payload.
+//!
+//! Accounts come from session services of different ages. One which predates external ids and
+//! provenance sends accounts with only an id, a login, and maybe an email; `AccountKey` tells
+//! how to find such an account again, by login rather than by external id.
+
+use protocol::sessionsrv;
+
+use config;
+use super::github::{self, User};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Identity {
+    /// Identifier of the user's account at the session service, once they have one
+    pub account_id: Option<u64>,
+    /// Identifier of the user at the identity provider
+    pub external_id: u64,
+    /// Login of the user at the identity provider
+    pub login: String,
+    /// Full name of the user, if they set one
+    pub name: Option<String>,
+    /// Email address of the user, if one is known
+    pub email: Option<String>,
+    /// True if the identity provider verified `email`
+    pub email_verified: bool,
+    /// URL of the user's avatar, or an empty string if they have none
+    pub avatar_url: String,
+    /// Identity provider the user belongs to
+    pub provider: sessionsrv::OAuthProvider,
+    /// Host of the identity provider, such as a GitHub Enterprise appliance
+    pub provider_host: String,
+}
+
+impl Identity {
+    /// Returns the identity's avatar URL requesting an image of the given size in pixels. See
+    /// `github::sized_avatar_url`.
+    pub fn avatar_url_sized(&self, px: u32) -> String {
+        github::sized_avatar_url(&self.avatar_url, px)
+    }
+}
+
+impl<'a> From<&'a User> for Identity {
+    fn from(user: &'a User) -> Identity {
+        Identity {
+            account_id: None,
+            external_id: user.id,
+            login: user.login.clone(),
+            name: user.name.clone(),
+            email: user.email.clone(),
+            email_verified: false,
+            avatar_url: user.avatar_url_sized(config::DEFAULT_AVATAR_SIZE),
+            provider: sessionsrv::OAuthProvider::GitHub,
+            provider_host: user.provider_host(),
+        }
+    }
+}
+
+impl<'a> From<&'a sessionsrv::Account> for Identity {
+    fn from(account: &'a sessionsrv::Account) -> Identity {
+        let account_id = if account.has_id() {
+            Some(account.get_id())
+        } else {
+            None
+        };
+        let name = match account.get_display_name() {
+            "" => None,
+            name => Some(name.to_string()),
+        };
+        let email = if account.has_email() {
+            Some(account.get_email().to_string())
+        } else {
+            None
+        };
+        Identity {
+            account_id: account_id,
+            external_id: account.get_extern_id(),
+            login: account.get_name().to_string(),
+            name: name,
+            email: email,
+            email_verified: account.has_verified_email(),
+            avatar_url: account.get_avatar_url().to_string(),
+            provider: account.get_provider(),
+            provider_host: account.get_provider_host().to_string(),
+        }
+    }
+}
+
+/// How an account the session service sent is looked up again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountKey {
+    /// By the identifier of the account at its identity provider, which survives renames
+    Extern {
+        provider: sessionsrv::OAuthProvider,
+        /// Host of the identity provider, public GitHub if the account doesn't name one
+        provider_host: String,
+        extern_id: u64,
+    },
+    /// By login, for accounts sent by a session service which doesn't record external ids
+    Login(String),
+}
+
+impl AccountKey {
+    /// Returns the request for the account by login, which every session service answers, or
+    /// `None` if the account is keyed by its external id.
+    pub fn account_get(&self) -> Option<sessionsrv::AccountGet> {
+        match *self {
+            AccountKey::Login(ref login) => {
+                let mut req = sessionsrv::AccountGet::new();
+                req.set_name(login.clone());
+                Some(req)
+            }
+            AccountKey::Extern { .. } => None,
+        }
+    }
+}
+
+impl<'a> From<&'a sessionsrv::Account> for AccountKey {
+    fn from(account: &'a sessionsrv::Account) -> AccountKey {
+        if !account.has_extern_id() || account.get_extern_id() == 0 {
+            return AccountKey::Login(account.get_name().to_string());
+        }
+        let provider_host = match account.get_provider_host() {
+            "" => config::GITHUB_HOST.to_string(),
+            host => host.to_lowercase(),
+        };
+        AccountKey::Extern {
+            provider: account.get_provider(),
+            provider_host: provider_host,
+            extern_id: account.get_extern_id(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::sessionsrv;
+
+    use super::*;
+
+    #[test]
+    fn from_account() {
+        let mut account = sessionsrv::Account::new();
+        account.set_extern_id(583231);
+        account.set_name("octocat".to_string());
+        assert_eq!(Identity::from(&account),
+                   Identity {
+                       account_id: None,
+                       external_id: 583231,
+                       login: "octocat".to_string(),
+                       name: None,
+                       email: None,
+                       email_verified: false,
+                       avatar_url: "".to_string(),
+                       provider: sessionsrv::OAuthProvider::GitHub,
+                       provider_host: "".to_string(),
+                   });
+        account.set_display_name("The Octocat".to_string());
+        account.set_email("octocat@example.com".to_string());
+        account.set_id(42);
+        let identity = Identity::from(&account);
+        assert_eq!(identity.account_id, Some(42));
+        assert_eq!(identity.name, Some("The Octocat".to_string()));
+        assert_eq!(identity.email, Some("octocat@example.com".to_string()));
+    }
+}
@@ -0,0 +1,401 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoding the JSON arrays the GitHub API answers listings with, one item at a time.
+//!
+//! A `ListDecoder` turns a reader of an array into `ListItems`, an iterator of its decoded items.
+//! `BufferedList` reads the whole body and decodes it with rustc_serialize as any other body, so
+//! a listing holds its text and its tree in memory at once. `StreamingList` parses the body as
+//! it reads and only builds the tree of the item being decoded, so memory stays flat however
+//! long the listing. `decode_list` uses the streaming decoder when the `streaming-json` feature
+//! is enabled, and the buffered one otherwise.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{self, BufReader, Bytes, Read};
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::str;
+
+use rustc_serialize::Decodable;
+use rustc_serialize::json::{self, DecoderError, ErrorCode, Json, JsonEvent, Parser, ParserError,
+                            StackElement};
+
+use error::{Error, Result};
+use super::github::{decode_json, MAX_JSON_DEPTH};
+
+/// Decodes a JSON array of `T` from a reader.
+pub trait ListDecoder {
+    /// Returns the items of the array `reader` reads. An item which doesn't decode, or a body
+    /// which isn't an array, ends the items with an error.
+    fn decode_list<T, R>(&self, reader: R) -> ListItems<T>
+        where T: Decodable + 'static,
+              R: Read + 'static;
+}
+
+/// Decodes the whole body before the first item is returned, see `decode_json`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BufferedList;
+
+impl ListDecoder for BufferedList {
+    fn decode_list<T, R>(&self, mut reader: R) -> ListItems<T>
+        where T: Decodable + 'static,
+              R: Read + 'static
+    {
+        let mut body = String::new();
+        let items: Result<Vec<T>> = match reader.read_to_string(&mut body) {
+            Ok(_) => decode_json(&body),
+            Err(err) => Err(Error::from(err)),
+        };
+        match items {
+            Ok(items) => ListItems::new(items.into_iter().map(Ok)),
+            Err(err) => ListItems::new(Some(Err(err)).into_iter()),
+        }
+    }
+}
+
+/// Decodes each item as soon as it is read, holding the tree of one item at a time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StreamingList;
+
+impl ListDecoder for StreamingList {
+    fn decode_list<T, R>(&self, reader: R) -> ListItems<T>
+        where T: Decodable + 'static,
+              R: Read + 'static
+    {
+        let read_error = Rc::new(RefCell::new(None));
+        let chars = Utf8Chars {
+            bytes: BufReader::new(reader).bytes(),
+            error: read_error.clone(),
+        };
+        ListItems::new(StreamedItems {
+            parser: Parser::new(chars),
+            read_error: read_error,
+            state: StreamState::Start,
+            item: PhantomData,
+        })
+    }
+}
+
+/// Returns the items of the array `reader` reads, decoded by `StreamingList` when the
+/// `streaming-json` feature is enabled and by `BufferedList` otherwise.
+pub fn decode_list<T, R>(reader: R) -> ListItems<T>
+    where T: Decodable + 'static,
+          R: Read + 'static
+{
+    if cfg!(feature = "streaming-json") {
+        StreamingList.decode_list(reader)
+    } else {
+        BufferedList.decode_list(reader)
+    }
+}
+
+/// The items of a JSON array, decoded as they are asked for. Once an item fails, no more
+/// follow.
+pub struct ListItems<T> {
+    inner: Box<Iterator<Item = Result<T>>>,
+}
+
+impl<T> ListItems<T> {
+    fn new<I: Iterator<Item = Result<T>> + 'static>(inner: I) -> Self {
+        ListItems { inner: Box::new(inner) }
+    }
+}
+
+impl<T> Iterator for ListItems<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        self.inner.next()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StreamState {
+    /// Nothing was parsed yet
+    Start,
+    /// Within the array, before an item or its end
+    Items,
+    /// After the end of the array, or an error
+    Done,
+}
+
+struct StreamedItems<T, R: Read> {
+    parser: Parser<Utf8Chars<R>>,
+    read_error: Rc<RefCell<Option<Error>>>,
+    state: StreamState,
+    item: PhantomData<T>,
+}
+
+impl<T: Decodable, R: Read> StreamedItems<T, R> {
+    /// Returns the next event of the parser, failing with the error reading the body if it
+    /// ended there.
+    fn event(&mut self) -> Result<Option<JsonEvent>> {
+        let event = self.parser.next();
+        if let Some(err) = self.read_error.borrow_mut().take() {
+            return Err(err);
+        }
+        match event {
+            Some(JsonEvent::Error(err)) => Err(Error::from(DecoderError::ParseError(err))),
+            event => Ok(event),
+        }
+    }
+
+    fn step(&mut self) -> Result<Option<T>> {
+        if self.state == StreamState::Start {
+            match try!(self.event()) {
+                Some(JsonEvent::ArrayStart) => self.state = StreamState::Items,
+                Some(event) => return Err(expected_array(&event)),
+                None => return Err(eof()),
+            }
+        }
+        match try!(self.event()) {
+            Some(JsonEvent::ArrayEnd) => {
+                self.state = StreamState::Done;
+                // Anything but whitespace past the array is an error of the parser
+                match try!(self.event()) {
+                    None => Ok(None),
+                    Some(_) => Err(eof()),
+                }
+            }
+            Some(event) => {
+                let item = try!(self.build(event, 2));
+                Ok(Some(try!(T::decode(&mut json::Decoder::new(item)))))
+            }
+            None => Err(eof()),
+        }
+    }
+
+    /// Builds the tree of the value `event` starts, which is nested `depth` deep.
+    fn build(&mut self, event: JsonEvent, depth: usize) -> Result<Json> {
+        match event {
+            JsonEvent::ObjectStart |
+            JsonEvent::ArrayStart if depth > MAX_JSON_DEPTH => {
+                let msg = format!("JSON nested deeper than {} levels", MAX_JSON_DEPTH);
+                Err(Error::from(DecoderError::ApplicationError(msg)))
+            }
+            JsonEvent::ObjectStart => {
+                let mut members = BTreeMap::new();
+                loop {
+                    let event = match try!(self.event()) {
+                        Some(JsonEvent::ObjectEnd) => return Ok(Json::Object(members)),
+                        Some(event) => event,
+                        None => return Err(eof()),
+                    };
+                    // The parser has read the member's key by the time it starts its value
+                    let key = match self.parser.stack().top() {
+                        Some(StackElement::Key(key)) => key.to_string(),
+                        _ => return Err(eof()),
+                    };
+                    let value = try!(self.build(event, depth + 1));
+                    members.insert(key, value);
+                }
+            }
+            JsonEvent::ArrayStart => {
+                let mut values = vec![];
+                loop {
+                    match try!(self.event()) {
+                        Some(JsonEvent::ArrayEnd) => return Ok(Json::Array(values)),
+                        Some(event) => values.push(try!(self.build(event, depth + 1))),
+                        None => return Err(eof()),
+                    }
+                }
+            }
+            JsonEvent::BooleanValue(b) => Ok(Json::Boolean(b)),
+            JsonEvent::I64Value(n) => Ok(Json::I64(n)),
+            JsonEvent::U64Value(n) => Ok(Json::U64(n)),
+            JsonEvent::F64Value(n) => Ok(Json::F64(n)),
+            JsonEvent::StringValue(s) => Ok(Json::String(s)),
+            JsonEvent::NullValue => Ok(Json::Null),
+            JsonEvent::ObjectEnd |
+            JsonEvent::ArrayEnd |
+            JsonEvent::Error(_) => Err(eof()),
+        }
+    }
+}
+
+impl<T: Decodable, R: Read> Iterator for StreamedItems<T, R> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.state == StreamState::Done {
+            return None;
+        }
+        match self.step() {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => None,
+            Err(err) => {
+                self.state = StreamState::Done;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+fn expected_array(event: &JsonEvent) -> Error {
+    let found = match *event {
+        JsonEvent::ObjectStart => "Object",
+        JsonEvent::StringValue(_) => "String",
+        JsonEvent::BooleanValue(_) => "Boolean",
+        JsonEvent::NullValue => "Null",
+        _ => "Number",
+    };
+    Error::from(DecoderError::ExpectedError("Array".to_string(), found.to_string()))
+}
+
+/// The error of a body which ends, or goes on, where the parser doesn't expect it to.
+fn eof() -> Error {
+    Error::from(DecoderError::ParseError(ParserError::SyntaxError(ErrorCode::EOFWhileParsingValue,
+                                                                  0,
+                                                                  0)))
+}
+
+/// The chars of a UTF-8 reader. The parser takes chars which can't fail, so a read error or
+/// bytes which aren't UTF-8 end the chars, and the error is left for `StreamedItems` to find.
+struct Utf8Chars<R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    error: Rc<RefCell<Option<Error>>>,
+}
+
+impl<R: Read> Utf8Chars<R> {
+    fn fail(&self, err: Error) -> Option<char> {
+        *self.error.borrow_mut() = Some(err);
+        None
+    }
+}
+
+impl<R: Read> Iterator for Utf8Chars<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let first = match self.bytes.next() {
+            Some(Ok(b)) => b,
+            Some(Err(err)) => return self.fail(Error::from(err)),
+            None => return None,
+        };
+        // The number of bytes of the char is told by the high bits of its first
+        let len = if first & 0x80 == 0 {
+            return Some(first as char);
+        } else if first & 0xe0 == 0xc0 {
+            2
+        } else if first & 0xf0 == 0xe0 {
+            3
+        } else if first & 0xf8 == 0xf0 {
+            4
+        } else {
+            0
+        };
+        let mut buf = [first, 0, 0, 0];
+        for i in 1..len {
+            match self.bytes.next() {
+                Some(Ok(b)) => buf[i] = b,
+                Some(Err(err)) => return self.fail(Error::from(err)),
+                None => break,
+            }
+        }
+        match str::from_utf8(&buf[..len]).ok().and_then(|s| s.chars().next()) {
+            Some(c) if len > 0 => Some(c),
+            _ => {
+                self.fail(Error::from(io::Error::new(io::ErrorKind::InvalidData,
+                                                     "stream did not contain valid UTF-8")))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Read};
+
+    use super::*;
+    use error::{Error, Result};
+    use oauth::github::{Email, Org, MAX_JSON_DEPTH};
+
+    fn both<T: ::rustc_serialize::Decodable + 'static>(body: &str) -> Vec<Vec<Result<T>>> {
+        vec![BufferedList.decode_list(Cursor::new(body.as_bytes().to_vec())).collect(),
+             StreamingList.decode_list(Cursor::new(body.as_bytes().to_vec())).collect()]
+    }
+
+    #[test]
+    fn arrays_decode_the_same_either_way() {
+        let body = include_str!("../../tests/fixtures/recorded/user-emails.json");
+        for items in both::<Email>(body) {
+            let emails: Vec<String> = items.into_iter().map(|e| e.unwrap().email).collect();
+            assert_eq!(emails,
+                       vec!["user1@example.com",
+                            "1000001+octocat@users.noreply.github.com",
+                            "user2@example.com"]);
+        }
+        for items in both::<Org>(" [ ]\n") {
+            assert!(items.is_empty());
+        }
+    }
+
+    #[test]
+    fn items_are_streamed_before_the_body_fails() {
+        let body = r#"[{"login": "habitat-sh", "id": 1}, {"login": "chef", "id": 2}, {"login""#;
+        let items: Vec<Result<Org>> = StreamingList.decode_list(Cursor::new(body.as_bytes()
+                .to_vec()))
+            .collect();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1].as_ref().unwrap().login, "chef");
+        assert!(items[2].is_err());
+        // The buffered decoder finds out before returning anything
+        let items: Vec<Result<Org>> = BufferedList.decode_list(Cursor::new(body.as_bytes()
+                .to_vec()))
+            .collect();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].is_err());
+    }
+
+    #[test]
+    fn bodies_which_arent_arrays_of_items_fail() {
+        let mut nested = String::new();
+        for _ in 0..MAX_JSON_DEPTH + 1 {
+            nested.push('[');
+        }
+        for body in &["{\"login\": \"chef\"}",
+                      "[{\"login\": \"chef\"}]",
+                      "[{\"login\": \"chef\", \"id\": 1}] trailing",
+                      "[{\"login\": \"chef\", \"id\": 1}",
+                      "",
+                      nested.as_str()] {
+            for items in both::<Org>(body) {
+                assert!(items.last().map_or(false, |item| item.is_err()), "{:?}", body);
+            }
+        }
+    }
+
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))
+        }
+    }
+
+    #[test]
+    fn read_errors_and_invalid_utf8_fail() {
+        match StreamingList.decode_list::<Org, _>(FailingReader).next() {
+            Some(Err(Error::IO(ref err))) => assert_eq!(err.kind(), io::ErrorKind::ConnectionReset),
+            other => panic!("expected the read error, got {:?}", other.map(|r| r.is_ok())),
+        }
+        let body = b"[{\"login\": \"ch\xffef\", \"id\": 1}]".to_vec();
+        assert!(StreamingList.decode_list::<Org, _>(Cursor::new(body)).next().unwrap().is_err());
+        let body = "[{\"login\": \"ch\u{e9}f \u{1f600}\", \"id\": 1}]";
+        for mut items in both::<Org>(body) {
+            assert_eq!(items.remove(0).unwrap().login, "ch\u{e9}f \u{1f600}");
+        }
+    }
+}
@@ -0,0 +1,856 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Authorization policy derived from GitHub organization and team membership.
+//!
+//! The policy is built from the `admin_org`, `admin_team`, `builder_team`,
+//! `origin_creator_team`, and `required_org` configuration fields. A field which is absent places
+//! no restriction on who may log in and grants nobody the corresponding privilege. Teams are
+//! looked up within `admin_org`. Every OAuth application has a policy of its own, built from its
+//! own fields, see `TeamPolicies`.
+//!
+//! Independently of teams an `AdminPolicy`, built from the `admin_logins`, `admin_ids`, and
+//! `honor_site_admin` fields, makes specific users operators.
+//!
+//! Before any of that an `AccessPolicy`, built from the `allow_logins`, `deny_logins`,
+//! `allow_orgs`, and `require_org_membership` fields, decides whether a user may log in at all.
+
+use std::collections::BTreeMap;
+
+use protocol::sessionsrv;
+
+use config::{GitHubApps, GitHubOAuth, DEFAULT_GITHUB_APP};
+use error::{Error, Result};
+use super::OAuthProvider;
+use super::github::{Org, Team, User};
+
+/// Why an `AccessPolicy` refused a user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessDenied {
+    /// The user's login is in `deny_logins`
+    DeniedLogin,
+    /// The user is neither in `allow_logins` nor a member of an organization of `allow_orgs`
+    NotAllowed,
+    /// `require_org_membership` is set and the user is a member of none of `allow_orgs`
+    NotInAllowedOrg,
+}
+
+impl AccessDenied {
+    /// Returns the code reported to clients and recorded in the audit trail.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            AccessDenied::DeniedLogin => "denied_login",
+            AccessDenied::NotAllowed => "not_allowed",
+            AccessDenied::NotInAllowedOrg => "not_in_allowed_org",
+        }
+    }
+}
+
+/// Privileges granted to a user by a `TeamPolicy`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Privileges {
+    /// User is a builder administrator
+    pub admin: bool,
+    /// User is a member of the required organization, or no organization is required
+    pub member: bool,
+    /// User may run builds
+    pub builder: bool,
+    /// User may create origins
+    pub origin_creator: bool,
+}
+
+impl Privileges {
+    /// Returns the privileges of a user whose memberships couldn't be determined. Nothing is
+    /// granted, and the user is only a member if the policy doesn't require an organization.
+    pub fn none(policy: &TeamPolicy) -> Self {
+        Privileges { member: policy.required_org.is_none(), ..Privileges::default() }
+    }
+}
+
+impl From<Privileges> for sessionsrv::FeatureFlags {
+    fn from(privileges: Privileges) -> sessionsrv::FeatureFlags {
+        let mut flags = sessionsrv::FeatureFlags::empty();
+        if privileges.admin {
+            flags.insert(sessionsrv::ADMIN);
+        }
+        if privileges.member {
+            flags.insert(sessionsrv::MEMBER);
+        }
+        if privileges.builder {
+            flags.insert(sessionsrv::BUILDER);
+        }
+        if privileges.origin_creator {
+            flags.insert(sessionsrv::ORIGIN_CREATOR);
+        }
+        flags
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TeamPolicy {
+    pub admin_org: Option<String>,
+    pub admin_team: Option<String>,
+    pub builder_team: Option<String>,
+    pub origin_creator_team: Option<String>,
+    pub required_org: Option<String>,
+}
+
+impl TeamPolicy {
+    pub fn new<T: GitHubOAuth>(config: &T) -> Self {
+        TeamPolicy {
+            admin_org: config.github_admin_org().map(|o| o.to_string()),
+            admin_team: config.github_admin_team().map(|t| t.to_string()),
+            builder_team: config.github_builder_team().map(|t| t.to_string()),
+            origin_creator_team: config.github_origin_creator_team().map(|t| t.to_string()),
+            required_org: config.github_required_org().map(|o| o.to_string()),
+        }
+    }
+
+    /// True if the policy requires no membership lookups.
+    pub fn is_unrestricted(&self) -> bool {
+        self.admin_org.is_none() && self.required_org.is_none()
+    }
+
+    /// Check the policy against the provider and log a warning for every problem found. This is
+    /// run once at startup so a misconfigured policy is noticed before it silently grants nobody
+    /// administrator privileges. The teams of `admin_org` are listed with the given access token,
+    /// which needs the `read:org` scope since GitHub doesn't list teams to an application; without
+    /// one they can't be checked.
+    pub fn validate(&self, provider: &OAuthProvider, token: Option<&str>) {
+        let org = match self.admin_org {
+            Some(ref org) => org,
+            None => {
+                for (field, _) in self.teams() {
+                    warn!("GitHub {} is configured without an admin_org and will be ignored",
+                          field);
+                }
+                return;
+            }
+        };
+        let configured = self.teams();
+        if configured.is_empty() {
+            return;
+        }
+        let token = match token {
+            Some(token) => token,
+            None => {
+                warn!("unable to verify GitHub teams in organization {} without a service_token \
+                       granted read:org",
+                      org);
+                return;
+            }
+        };
+        match provider.teams(Some(token), org) {
+            Ok(teams) => {
+                for (field, team) in configured {
+                    if !teams.iter().any(|t| team_matches(&t.slug, &t.name, team)) {
+                        warn!("GitHub {} {} was not found in organization {}, nobody will be \
+                               granted its privileges",
+                              field,
+                              team,
+                              org);
+                    }
+                }
+            }
+            Err(e) => warn!("unable to verify GitHub teams in organization {}, err={}", org, e),
+        }
+    }
+
+    /// Determine the privileges of the owner of the given access token.
+    ///
+    /// At most two requests are made: one for the user's organizations and, if any team is
+    /// configured and the user belongs to `admin_org`, one for the user's teams. The latter is
+    /// skipped when the provider's cached team members answer for every configured team.
+    ///
+    /// # Errors
+    ///
+    /// * The organizations or teams of the user could not be retrieved
+    pub fn privileges<P: OAuthProvider + ?Sized>(&self,
+                                                 provider: &P,
+                                                 token: &str,
+                                                 user: &User)
+                                                 -> Result<Privileges> {
+        if self.is_unrestricted() {
+            return Ok(Privileges::none(self));
+        }
+        let orgs = try!(provider.orgs(token));
+        let member = match self.required_org {
+            Some(ref org) => is_member(&orgs, org),
+            None => true,
+        };
+        let privileges = match self.admin_org {
+            Some(ref org) if is_member(&orgs, org) => {
+                let cached: Vec<(&str, Option<bool>)> = self.teams()
+                    .into_iter()
+                    .map(|(_, team)| (team, provider.cached_team_member(org, team, user)))
+                    .collect();
+                let teams = if cached.iter().any(|&(_, member)| member.is_none()) {
+                    try!(provider.user_teams(token))
+                } else {
+                    vec![]
+                };
+                let has_team = |team: &Option<String>| {
+                    team.as_ref().map_or(false, |t| {
+                        match cached.iter().find(|&&(c, _)| c == t.as_str()) {
+                            Some(&(_, Some(member))) => member,
+                            _ => in_team(&teams, org, t),
+                        }
+                    })
+                };
+                Privileges {
+                    admin: self.admin_team.is_none() || has_team(&self.admin_team),
+                    member: member,
+                    builder: has_team(&self.builder_team),
+                    origin_creator: has_team(&self.origin_creator_team),
+                }
+            }
+            _ => Privileges { member: member, ..Privileges::default() },
+        };
+        debug!("GitHub privileges for {}, {:?}", user.login, privileges);
+        Ok(privileges)
+    }
+
+    /// Returns the configured teams along with the name of the field configuring each.
+    fn teams(&self) -> Vec<(&'static str, &str)> {
+        let fields = [("admin_team", &self.admin_team),
+                      ("builder_team", &self.builder_team),
+                      ("origin_creator_team", &self.origin_creator_team)];
+        fields.iter()
+            .filter_map(|&(field, team)| team.as_ref().map(|t| (field, t.as_str())))
+            .collect()
+    }
+}
+
+/// The `TeamPolicy` of every configured OAuth application, each built from the application's own
+/// configuration.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TeamPolicies {
+    default: TeamPolicy,
+    named: BTreeMap<String, TeamPolicy>,
+}
+
+impl TeamPolicies {
+    pub fn new<T: GitHubApps>(config: &T) -> Self {
+        TeamPolicies {
+            default: TeamPolicy::new(config),
+            named: config.github_apps()
+                .iter()
+                .map(|(name, app)| (name.clone(), TeamPolicy::new(app)))
+                .collect(),
+        }
+    }
+
+    /// Returns the policy of the default OAuth application.
+    pub fn default(&self) -> &TeamPolicy {
+        &self.default
+    }
+
+    /// Returns the policy of the OAuth application with the given name, or of the default
+    /// application if no name was given.
+    ///
+    /// # Errors
+    ///
+    /// * No OAuth application was configured with the given name
+    pub fn get(&self, name: Option<&str>) -> Result<&TeamPolicy> {
+        match name {
+            None => Ok(&self.default),
+            Some(DEFAULT_GITHUB_APP) => Ok(&self.default),
+            Some(name) => {
+                self.named.get(name).ok_or(Error::UnknownGitHubApp(name.to_string()))
+            }
+        }
+    }
+}
+
+/// Users granted the operator flag regardless of their organization and team memberships.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdminPolicy {
+    /// Logins of operators, compared case-insensitively
+    pub logins: Vec<String>,
+    /// GitHub ids of operators
+    pub ids: Vec<u64>,
+    /// Make GitHub Enterprise site administrators operators
+    pub honor_site_admin: bool,
+}
+
+impl AdminPolicy {
+    pub fn new<T: GitHubOAuth>(config: &T) -> Self {
+        AdminPolicy {
+            logins: config.github_admin_logins(),
+            ids: config.github_admin_ids(),
+            honor_site_admin: config.github_honor_site_admin(),
+        }
+    }
+
+    /// Returns the flags the policy adds to the session of the given user.
+    pub fn flags(&self, user: &User) -> sessionsrv::FeatureFlags {
+        if self.allows(&user.login, user.id, user.site_admin) {
+            debug!("GitHub user {} is an operator", user.login);
+            sessionsrv::OPERATOR
+        } else {
+            sessionsrv::FeatureFlags::empty()
+        }
+    }
+
+    fn allows(&self, login: &str, id: u64, site_admin: bool) -> bool {
+        (self.honor_site_admin && site_admin) || self.ids.iter().any(|i| *i == id) ||
+        self.logins.iter().any(|l| l.to_lowercase() == login.to_lowercase())
+    }
+}
+
+/// Users who may log in at all. A policy with every field empty lets everyone in.
+///
+/// A user whose login is in `deny_logins` is refused whatever else the policy says. Otherwise, if
+/// `allow_logins` or `allow_orgs` is set, the user must be in `allow_logins` or a member of one of
+/// `allow_orgs`. With `require_org_membership` set only members of `allow_orgs` get in, whether
+/// or not they are in `allow_logins`, and nobody does if `allow_orgs` is empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessPolicy {
+    /// Logins, compared case-insensitively, of users who may log in
+    pub allow_logins: Vec<String>,
+    /// Logins, compared case-insensitively, of users who may never log in
+    pub deny_logins: Vec<String>,
+    /// Organizations whose members may log in
+    pub allow_orgs: Vec<String>,
+    /// Only let members of `allow_orgs` in
+    pub require_org_membership: bool,
+}
+
+impl AccessPolicy {
+    pub fn new<T: GitHubOAuth>(config: &T) -> Self {
+        AccessPolicy {
+            allow_logins: config.github_allow_logins(),
+            deny_logins: config.github_deny_logins(),
+            allow_orgs: config.github_allow_orgs(),
+            require_org_membership: config.github_require_org_membership(),
+        }
+    }
+
+    /// True if the policy lets everyone in.
+    pub fn is_unrestricted(&self) -> bool {
+        self.allow_logins.is_empty() && self.deny_logins.is_empty() &&
+        self.allow_orgs.is_empty() && !self.require_org_membership
+    }
+
+    /// True if `evaluate` needs the organizations of the user.
+    pub fn needs_orgs(&self) -> bool {
+        !self.allow_orgs.is_empty()
+    }
+
+    /// Log a warning for every problem with the policy. This is run once at startup so a policy
+    /// letting nobody in is noticed before users are refused.
+    pub fn validate(&self) {
+        if self.require_org_membership && self.allow_orgs.is_empty() {
+            warn!("GitHub require_org_membership is set without any allow_orgs, nobody will be \
+                   able to log in");
+        }
+    }
+
+    /// Decide whether the user with the given login, a member of the given organizations, may
+    /// log in.
+    ///
+    /// # Errors
+    ///
+    /// * The reason the user is refused
+    pub fn evaluate(&self, login: &str, orgs: &[Org]) -> ::std::result::Result<(), AccessDenied> {
+        let login = login.to_lowercase();
+        if self.deny_logins.iter().any(|l| l.to_lowercase() == login) {
+            return Err(AccessDenied::DeniedLogin);
+        }
+        let in_allowed_org = self.allow_orgs.iter().any(|org| is_member(orgs, org));
+        if self.require_org_membership {
+            return if in_allowed_org {
+                Ok(())
+            } else {
+                Err(AccessDenied::NotInAllowedOrg)
+            };
+        }
+        if self.allow_logins.is_empty() && self.allow_orgs.is_empty() {
+            return Ok(());
+        }
+        if in_allowed_org || self.allow_logins.iter().any(|l| l.to_lowercase() == login) {
+            Ok(())
+        } else {
+            Err(AccessDenied::NotAllowed)
+        }
+    }
+}
+
+fn is_member(orgs: &[Org], org: &str) -> bool {
+    orgs.iter().any(|o| o.login.to_lowercase() == org.to_lowercase())
+}
+
+fn in_team(teams: &[Team], org: &str, team: &str) -> bool {
+    teams.iter().any(|t| {
+        team_matches(&t.slug, &t.name, team) &&
+        t.organization.as_ref().map_or(false, |o| o.login.to_lowercase() == org.to_lowercase())
+    })
+}
+
+/// True if a team with the given slug and name is the configured `team`, compared
+/// case-insensitively.
+pub fn team_matches(slug: &str, name: &str, team: &str) -> bool {
+    let team = team.to_lowercase();
+    slug.to_lowercase() == team || name.to_lowercase() == team
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    use super::{in_team, is_member, team_matches, AccessDenied, AccessPolicy, AdminPolicy,
+                Privileges, TeamPolicies, TeamPolicy};
+    use config::GitHubCfg;
+    use error::Result;
+    use oauth::OAuthProvider;
+    use oauth::fixtures::{AppsFixture, UserFixture};
+    use oauth::github::{Email, Org, Team, User};
+    use oauth::token::TokenRecord;
+    use protocol::sessionsrv;
+
+    /// Provider answering membership lookups with fixed organizations and `(org, slug)` teams,
+    /// which records the lookups made.
+    struct Directory {
+        orgs: Vec<Org>,
+        teams: Vec<(&'static str, &'static str)>,
+        lookups: Mutex<Vec<String>>,
+    }
+
+    impl Directory {
+        fn new(orgs: Vec<Org>, teams: Vec<(&'static str, &'static str)>) -> Self {
+            Directory {
+                orgs: orgs,
+                teams: teams,
+                lookups: Mutex::new(vec![]),
+            }
+        }
+
+        fn lookups(&self) -> Vec<String> {
+            self.lookups.lock().unwrap().clone()
+        }
+
+        fn listed_teams(&self) -> Vec<Team> {
+            self.teams
+                .iter()
+                .enumerate()
+                .map(|(i, &(org, slug))| {
+                    Team {
+                        id: i as u64 + 1,
+                        name: slug.to_string(),
+                        slug: slug.to_string(),
+                        organization: Some(Org {
+                            login: org.to_string(),
+                            id: 1,
+                        }),
+                    }
+                })
+                .collect()
+        }
+    }
+
+    impl OAuthProvider for Directory {
+        fn authenticate(&self, code: &str) -> Result<TokenRecord> {
+            Ok(TokenRecord::new(code.to_string(), vec![], "client".to_string()))
+        }
+
+        fn client_id(&self) -> String {
+            "client".to_string()
+        }
+
+        fn check_token(&self, _token: &str) -> Result<Option<TokenRecord>> {
+            Ok(None)
+        }
+
+        fn refresh_validation(&self, _record: &mut TokenRecord) -> Result<()> {
+            Ok(())
+        }
+
+        fn user(&self, _token: &str) -> Result<User> {
+            Ok(UserFixture::default().build())
+        }
+
+        fn emails(&self, _token: &str) -> Result<Vec<Email>> {
+            Ok(vec![])
+        }
+
+        fn orgs(&self, token: &str) -> Result<Vec<Org>> {
+            self.lookups.lock().unwrap().push(format!("orgs with {}", token));
+            Ok(self.orgs.clone())
+        }
+
+        fn teams(&self, token: Option<&str>, org: &str) -> Result<Vec<Team>> {
+            self.lookups
+                .lock()
+                .unwrap()
+                .push(format!("teams of {} with {}", org, token.unwrap_or("the app")));
+            Ok(self.listed_teams())
+        }
+
+        fn user_teams(&self, token: &str) -> Result<Vec<Team>> {
+            self.lookups.lock().unwrap().push(format!("user teams with {}", token));
+            Ok(self.listed_teams())
+        }
+
+        fn logout(&self, _token: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn access_policy(&self) -> AccessPolicy {
+            AccessPolicy::default()
+        }
+    }
+
+    /// A default application without a policy and the named applications of `apps`.
+    fn team_policy(admin_team: Option<&str>, required_org: Option<&str>) -> TeamPolicy {
+        TeamPolicy {
+            admin_org: Some("habitat-sh".to_string()),
+            admin_team: admin_team.map(|t| t.to_string()),
+            builder_team: Some("builders".to_string()),
+            origin_creator_team: None,
+            required_org: required_org.map(|o| o.to_string()),
+        }
+    }
+
+    fn orgs(logins: &[&str]) -> Vec<Org> {
+        logins.iter()
+            .enumerate()
+            .map(|(i, login)| {
+                Org {
+                    login: login.to_string(),
+                    id: i as u64 + 1,
+                }
+            })
+            .collect()
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn org_membership_ignores_case() {
+        let orgs = vec![Org {
+                            login: "habitat-sh".to_string(),
+                            id: 1,
+                        }];
+        assert!(is_member(&orgs, "Habitat-SH"));
+        assert!(!is_member(&orgs, "chef"));
+    }
+
+    #[test]
+    fn team_membership_is_scoped_to_org() {
+        let teams = vec![Team {
+                             id: 1,
+                             name: "Builders".to_string(),
+                             slug: "builders".to_string(),
+                             organization: Some(Org {
+                                 login: "habitat-sh".to_string(),
+                                 id: 1,
+                             }),
+                         }];
+        assert!(in_team(&teams, "habitat-sh", "builders"));
+        assert!(!in_team(&teams, "chef", "builders"));
+    }
+
+    #[test]
+    fn teams_without_an_org_are_not_matched() {
+        let teams = vec![Team {
+                             id: 1,
+                             name: "Builders".to_string(),
+                             slug: "builders".to_string(),
+                             organization: None,
+                         }];
+        assert!(!in_team(&teams, "habitat-sh", "builders"));
+    }
+
+    #[test]
+    fn privileges_come_from_teams_of_the_admin_org() {
+        let user = UserFixture::default().build();
+        let directory = Directory::new(orgs(&["habitat-sh"]),
+                                       vec![("habitat-sh", "builders"), ("chef", "core")]);
+        let privileges = team_policy(Some("core"), Some("habitat-sh"))
+            .privileges(&directory, "token", &user)
+            .unwrap();
+        assert_eq!(privileges,
+                   Privileges {
+                       admin: false,
+                       member: true,
+                       builder: true,
+                       origin_creator: false,
+                   });
+        assert_eq!(directory.lookups(),
+                   strings(&["orgs with token", "user teams with token"]));
+    }
+
+    #[test]
+    fn members_of_the_admin_org_are_admins_without_an_admin_team() {
+        let user = UserFixture::default().build();
+        let directory = Directory::new(orgs(&["habitat-sh"]), vec![]);
+        let privileges = team_policy(None, None).privileges(&directory, "token", &user).unwrap();
+        assert!(privileges.admin);
+        assert!(privileges.member);
+        assert!(!privileges.builder);
+    }
+
+    #[test]
+    fn teams_are_not_looked_up_outside_the_admin_org() {
+        let user = UserFixture::default().build();
+        let directory = Directory::new(orgs(&["chef"]), vec![("habitat-sh", "builders")]);
+        let privileges = team_policy(Some("core"), Some("habitat-sh"))
+            .privileges(&directory, "token", &user)
+            .unwrap();
+        assert_eq!(privileges, Privileges::default());
+        assert_eq!(directory.lookups(), strings(&["orgs with token"]));
+    }
+
+    #[test]
+    fn unrestricted_policies_look_nothing_up() {
+        let user = UserFixture::default().build();
+        let directory = Directory::new(orgs(&["habitat-sh"]), vec![]);
+        let privileges = TeamPolicy::default().privileges(&directory, "token", &user).unwrap();
+        assert_eq!(privileges, Privileges { member: true, ..Privileges::default() });
+        assert!(directory.lookups().is_empty());
+    }
+
+    #[test]
+    fn validation_lists_teams_with_the_service_token() {
+        let directory = Directory::new(vec![], vec![("habitat-sh", "builders")]);
+        let policy = team_policy(Some("core"), None);
+        policy.validate(&directory, Some("service"));
+        assert_eq!(directory.lookups(),
+                   strings(&["teams of habitat-sh with service"]));
+        // Without a token the teams can't be listed, GitHub refuses to list them to the app
+        policy.validate(&directory, None);
+        assert_eq!(directory.lookups().len(), 1);
+        // Nor is anything listed when no team is configured
+        TeamPolicy { admin_org: Some("habitat-sh".to_string()), ..TeamPolicy::default() }
+            .validate(&directory, Some("service"));
+        assert_eq!(directory.lookups().len(), 1);
+    }
+
+    #[test]
+    fn every_app_has_its_own_policy() {
+        let mut app = GitHubCfg::default();
+        app.admin_org = Some("chef".to_string());
+        app.admin_team = Some("core".to_string());
+        app.required_org = Some("chef".to_string());
+        let mut apps = BTreeMap::new();
+        apps.insert("internal".to_string(), app);
+        let policies = TeamPolicies::new(&AppsFixture {
+            default: GitHubCfg::default(),
+            apps: apps,
+        });
+        assert_eq!(policies.get(None).unwrap(), &TeamPolicy::default());
+        assert_eq!(policies.get(Some("default")).unwrap(), policies.default());
+        let internal = policies.get(Some("internal")).unwrap();
+        assert_eq!(internal.admin_org, Some("chef".to_string()));
+        assert_eq!(internal.admin_team, Some("core".to_string()));
+        assert_eq!(internal.required_org, Some("chef".to_string()));
+        assert!(policies.get(Some("missing")).is_err());
+    }
+
+    #[test]
+    fn privileges_into_flags() {
+        let privileges = Privileges {
+            admin: true,
+            member: true,
+            builder: false,
+            origin_creator: true,
+        };
+        let flags = sessionsrv::FeatureFlags::from(privileges);
+        assert_eq!(flags,
+                   sessionsrv::ADMIN | sessionsrv::MEMBER | sessionsrv::ORIGIN_CREATOR);
+    }
+
+    #[test]
+    fn no_privileges_keeps_membership_without_required_org() {
+        let mut policy = TeamPolicy::default();
+        policy.admin_org = Some("habitat-sh".to_string());
+        assert!(Privileges::none(&policy).member);
+        policy.required_org = Some("habitat-sh".to_string());
+        assert_eq!(Privileges::none(&policy), Privileges::default());
+    }
+
+    #[test]
+    fn team_matches_slug_or_name() {
+        assert!(team_matches("core-team", "Core Team", "core-team"));
+        assert!(team_matches("core-team", "Core Team", "core team"));
+        assert!(!team_matches("core-team", "Core Team", "core"));
+    }
+
+    #[test]
+    fn admin_policy_matches_login_ignoring_case_and_id_exactly() {
+        let policy = AdminPolicy {
+            logins: vec!["Octocat".to_string()],
+            ids: vec![583231],
+            honor_site_admin: false,
+        };
+        assert!(policy.allows("octocat", 1, false));
+        assert!(policy.allows("hubot", 583231, false));
+        assert!(!policy.allows("hubot", 5832310, false));
+        assert!(!policy.allows("octocat-bot", 1, false));
+        assert!(!policy.allows("hubot", 1, true));
+    }
+
+    #[test]
+    fn empty_access_policy_lets_everyone_in() {
+        let policy = AccessPolicy::default();
+        assert!(policy.is_unrestricted());
+        assert!(!policy.needs_orgs());
+        assert_eq!(policy.evaluate("octocat", &[]), Ok(()));
+        assert_eq!(policy.evaluate("octocat", &orgs(&["habitat-sh"])), Ok(()));
+    }
+
+    #[test]
+    fn access_policy_deny_takes_precedence_over_allow() {
+        let policy = AccessPolicy {
+            allow_logins: strings(&["octocat"]),
+            deny_logins: strings(&["octocat"]),
+            allow_orgs: strings(&["habitat-sh"]),
+            require_org_membership: true,
+        };
+        assert_eq!(policy.evaluate("octocat", &orgs(&["habitat-sh"])),
+                   Err(AccessDenied::DeniedLogin));
+        assert_eq!(policy.evaluate("hubot", &orgs(&["habitat-sh"])), Ok(()));
+    }
+
+    #[test]
+    fn access_policy_matches_logins_ignoring_case() {
+        let policy = AccessPolicy {
+            allow_logins: strings(&["OctoCat"]),
+            deny_logins: strings(&["HUBOT"]),
+            ..AccessPolicy::default()
+        };
+        assert_eq!(policy.evaluate("octocat", &[]), Ok(()));
+        assert_eq!(policy.evaluate("OCTOCAT", &[]), Ok(()));
+        assert_eq!(policy.evaluate("hubot", &[]), Err(AccessDenied::DeniedLogin));
+        assert_eq!(policy.evaluate("Hubot", &[]), Err(AccessDenied::DeniedLogin));
+        assert_eq!(policy.evaluate("octocat-bot", &[]), Err(AccessDenied::NotAllowed));
+    }
+
+    #[test]
+    fn access_policy_deny_list_alone_lets_others_in() {
+        let policy = AccessPolicy { deny_logins: strings(&["hubot"]), ..AccessPolicy::default() };
+        assert!(!policy.is_unrestricted());
+        assert!(!policy.needs_orgs());
+        assert_eq!(policy.evaluate("octocat", &[]), Ok(()));
+        assert_eq!(policy.evaluate("hubot", &[]), Err(AccessDenied::DeniedLogin));
+    }
+
+    #[test]
+    fn access_policy_allows_logins_or_org_members() {
+        let policy = AccessPolicy {
+            allow_logins: strings(&["octocat"]),
+            allow_orgs: strings(&["Habitat-SH"]),
+            ..AccessPolicy::default()
+        };
+        assert!(policy.needs_orgs());
+        assert_eq!(policy.evaluate("octocat", &[]), Ok(()));
+        assert_eq!(policy.evaluate("hubot", &orgs(&["chef", "habitat-sh"])), Ok(()));
+        assert_eq!(policy.evaluate("hubot", &orgs(&["chef"])),
+                   Err(AccessDenied::NotAllowed));
+        assert_eq!(policy.evaluate("hubot", &[]), Err(AccessDenied::NotAllowed));
+    }
+
+    #[test]
+    fn access_policy_requiring_membership_ignores_allowed_logins() {
+        let policy = AccessPolicy {
+            allow_logins: strings(&["octocat"]),
+            allow_orgs: strings(&["habitat-sh"]),
+            require_org_membership: true,
+            ..AccessPolicy::default()
+        };
+        assert_eq!(policy.evaluate("octocat", &[]),
+                   Err(AccessDenied::NotInAllowedOrg));
+        assert_eq!(policy.evaluate("octocat", &orgs(&["habitat-sh"])), Ok(()));
+        assert_eq!(policy.evaluate("hubot", &orgs(&["habitat-sh"])), Ok(()));
+    }
+
+    #[test]
+    fn access_policy_requiring_membership_without_orgs_refuses_everyone() {
+        let policy = AccessPolicy {
+            allow_logins: strings(&["octocat"]),
+            require_org_membership: true,
+            ..AccessPolicy::default()
+        };
+        assert!(!policy.is_unrestricted());
+        assert!(!policy.needs_orgs());
+        assert_eq!(policy.evaluate("octocat", &orgs(&["habitat-sh"])),
+                   Err(AccessDenied::NotInAllowedOrg));
+    }
+
+    #[test]
+    fn access_policy_decides_every_combination() {
+        // Every combination of where the user appears in the policy, and of whether the lists
+        // hold someone else, checked against the rules as documented on `AccessPolicy`
+        for case in 0..128u32 {
+            let bit = |n: u32| case & (1 << n) != 0;
+            let (denied, other_denied, allowed, other_allowed) = (bit(0), bit(1), bit(2), bit(3));
+            let (in_org, other_org, require) = (bit(4), bit(5), bit(6));
+            let mut policy = AccessPolicy { require_org_membership: require, ..Default::default() };
+            if denied {
+                policy.deny_logins.push("OCTOCAT".to_string());
+            }
+            if other_denied {
+                policy.deny_logins.push("hubot".to_string());
+            }
+            if allowed {
+                policy.allow_logins.push("Octocat".to_string());
+            }
+            if other_allowed {
+                policy.allow_logins.push("hubot".to_string());
+            }
+            if in_org {
+                policy.allow_orgs.push("habitat-sh".to_string());
+            }
+            if other_org {
+                policy.allow_orgs.push("chef".to_string());
+            }
+            let expected = if denied {
+                Err(AccessDenied::DeniedLogin)
+            } else if require {
+                if in_org {
+                    Ok(())
+                } else {
+                    Err(AccessDenied::NotInAllowedOrg)
+                }
+            } else if policy.allow_logins.is_empty() && policy.allow_orgs.is_empty() {
+                Ok(())
+            } else if allowed || in_org {
+                Ok(())
+            } else {
+                Err(AccessDenied::NotAllowed)
+            };
+            assert_eq!(policy.evaluate("octocat", &orgs(&["habitat-sh", "rust-lang"])),
+                       expected,
+                       "{:?}",
+                       policy);
+        }
+    }
+
+    #[test]
+    fn access_denied_codes_are_stable() {
+        assert_eq!(AccessDenied::DeniedLogin.code(), "denied_login");
+        assert_eq!(AccessDenied::NotAllowed.code(), "not_allowed");
+        assert_eq!(AccessDenied::NotInAllowedOrg.code(), "not_in_allowed_org");
+    }
+
+    #[test]
+    fn admin_policy_honors_site_admin() {
+        let policy = AdminPolicy { honor_site_admin: true, ..AdminPolicy::default() };
+        assert!(policy.allows("hubot", 1, true));
+        assert!(!policy.allows("hubot", 1, false));
+    }
+}
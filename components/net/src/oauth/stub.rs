@@ -0,0 +1,228 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An identity provider for installations which can't reach any OAuth provider.
+//!
+//! The stub provider accepts a shared secret in place of an OAuth code and always authenticates
+//! the caller as the single account described by its configuration. It must only be used on
+//! air-gapped networks where every user of the service is trusted.
+
+use config::StubAuthCfg;
+use error::{Error, Result};
+use protocol::sessionsrv;
+use super::OAuthProvider;
+use super::github::{AuthErr, Email, Org, Team, TeamMembership, User};
+use super::health::HealthStatus;
+use super::token::TokenRecord;
+
+/// Client id recorded on tokens issued by the stub provider
+pub const STUB_CLIENT_ID: &'static str = "stub";
+
+/// Host recorded on accounts created by the stub provider. Its users aren't GitHub users, so the
+/// host keeps their ids apart from GitHub's.
+pub const STUB_HOST: &'static str = "stub";
+
+pub struct StubProvider {
+    config: StubAuthCfg,
+}
+
+impl StubProvider {
+    pub fn new(config: StubAuthCfg) -> Self {
+        StubProvider { config: config }
+    }
+
+    fn verify_token(&self, token: &str) -> Result<()> {
+        if token == self.config.token {
+            Ok(())
+        } else {
+            Err(Error::Auth(auth_err("bad_token", "The access token is not valid.")))
+        }
+    }
+}
+
+impl OAuthProvider for StubProvider {
+    fn authenticate(&self, code: &str) -> Result<TokenRecord> {
+        if code == self.config.shared_secret {
+            Ok(TokenRecord::new(self.config.token.clone(), vec![], STUB_CLIENT_ID.to_string()))
+        } else {
+            Err(Error::Auth(auth_err("bad_verification_code",
+                                     "The code passed is incorrect.")))
+        }
+    }
+
+    fn client_id(&self) -> String {
+        STUB_CLIENT_ID.to_string()
+    }
+
+    fn check_token(&self, token: &str) -> Result<Option<TokenRecord>> {
+        match self.verify_token(token) {
+            Ok(()) => {
+                Ok(Some(TokenRecord::new(token.to_string(), vec![], STUB_CLIENT_ID.to_string())))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn refresh_validation(&self, record: &mut TokenRecord) -> Result<()> {
+        if record.client_id != STUB_CLIENT_ID {
+            return Err(Error::TokenClientMismatch(record.client_id.clone()));
+        }
+        match self.verify_token(&record.token) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(Error::TokenRevoked),
+        }
+    }
+
+    fn user(&self, token: &str) -> Result<User> {
+        try!(self.verify_token(token));
+        let email = if self.config.email.is_empty() {
+            None
+        } else {
+            Some(self.config.email.clone())
+        };
+        Ok(User {
+            login: self.config.login.clone(),
+            id: self.config.id,
+            avatar_url: String::new(),
+            gravatar_id: String::new(),
+            url: String::new(),
+            html_url: String::new(),
+            followers_url: String::new(),
+            following_url: String::new(),
+            gists_url: String::new(),
+            starred_url: String::new(),
+            subscriptions_url: String::new(),
+            organizations_url: String::new(),
+            repos_url: String::new(),
+            events_url: String::new(),
+            received_events_url: String::new(),
+            site_admin: true,
+            name: Some(self.config.login.clone()),
+            company: None,
+            blog: None,
+            location: None,
+            email: email,
+            email_verified: Some(true),
+            hireable: None,
+            bio: None,
+            public_repos: 0,
+            public_gists: 0,
+            followers: 0,
+            following: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+        })
+    }
+
+    fn emails(&self, token: &str) -> Result<Vec<Email>> {
+        try!(self.verify_token(token));
+        if self.config.email.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![Email {
+                    email: self.config.email.clone(),
+                    primary: true,
+                    verified: true,
+                }])
+    }
+
+    fn orgs(&self, token: &str) -> Result<Vec<Org>> {
+        try!(self.verify_token(token));
+        Ok(vec![])
+    }
+
+    fn teams(&self, _token: Option<&str>, _org: &str) -> Result<Vec<Team>> {
+        Ok(vec![])
+    }
+
+    fn user_teams(&self, token: &str) -> Result<Vec<Team>> {
+        try!(self.verify_token(token));
+        Ok(vec![])
+    }
+
+    fn team_membership(&self,
+                       token: &str,
+                       _team_id: u64,
+                       _login: &str)
+                       -> Result<Option<TeamMembership>> {
+        try!(self.verify_token(token));
+        Ok(None)
+    }
+
+    fn logout(&self, _token: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn provider(&self) -> sessionsrv::OAuthProvider {
+        sessionsrv::OAuthProvider::Stub
+    }
+
+    fn provider_host(&self) -> String {
+        STUB_HOST.to_string()
+    }
+
+    fn health(&self) -> HealthStatus {
+        HealthStatus::healthy("authentication is stubbed, GitHub isn't used")
+    }
+}
+
+fn auth_err(error: &str, description: &str) -> AuthErr {
+    AuthErr {
+        error: error.to_string(),
+        error_description: description.to_string(),
+        error_uri: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::StubAuthCfg;
+    use oauth::OAuthProvider;
+    use protocol::sessionsrv;
+
+    fn provider() -> StubProvider {
+        StubProvider::new(StubAuthCfg {
+            shared_secret: "letmein".to_string(),
+            token: "static-token".to_string(),
+            login: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            id: 1,
+        })
+    }
+
+    #[test]
+    fn authenticate_with_shared_secret() {
+        let stub = provider();
+        assert_eq!(stub.authenticate("letmein").unwrap().token, "static-token");
+        assert!(stub.authenticate("guess").is_err());
+    }
+
+    #[test]
+    fn user_from_config() {
+        let stub = provider();
+        let user = stub.user("static-token").unwrap();
+        assert_eq!(user.login, "admin");
+        assert_eq!(user.email, Some("admin@example.com".to_string()));
+        assert!(stub.user("other-token").is_err());
+    }
+
+    #[test]
+    fn identities_are_not_github_identities() {
+        let stub = provider();
+        let identity = stub.identity("static-token").unwrap();
+        assert_eq!(identity.provider, sessionsrv::OAuthProvider::Stub);
+        assert_eq!(identity.provider_host, STUB_HOST);
+    }
+}
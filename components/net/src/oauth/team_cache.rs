@@ -0,0 +1,254 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Members of GitHub teams, cached across sessions so a login doesn't have to ask GitHub which
+//! teams its user belongs to.
+//!
+//! Entries are keyed by organization and team, compared case-insensitively, and are fresh for a
+//! TTL. The first lookup of a stale entry refreshes it while every other lookup is answered with
+//! the stale members until the refresh completes, and a lookup of an entry which was never
+//! listed, or failed to be, is answered with nothing so the caller asks GitHub about its user
+//! instead.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use error::Result;
+use super::github::TeamMember;
+use super::health;
+
+/// The logins and GitHub ids of the members of a team.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Members {
+    logins: HashSet<String>,
+    ids: HashSet<u64>,
+}
+
+impl Members {
+    pub fn add(&mut self, member: &TeamMember) {
+        self.logins.insert(member.login.to_lowercase());
+        self.ids.insert(member.id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// True if the user with the given GitHub id is a member. The login, compared
+    /// case-insensitively, is only consulted for members listed without an id.
+    pub fn contains(&self, login: &str, id: u64) -> bool {
+        self.ids.contains(&id) || (id == 0 && self.logins.contains(&login.to_lowercase()))
+    }
+}
+
+impl<'a> From<&'a [TeamMember]> for Members {
+    fn from(listed: &'a [TeamMember]) -> Self {
+        let mut members = Members::default();
+        for member in listed.iter() {
+            members.add(member);
+        }
+        members
+    }
+}
+
+/// Cache of the members of teams, see the module documentation.
+#[derive(Default)]
+pub struct OrgTeamCache {
+    entries: Mutex<HashMap<(String, String), Entry>>,
+}
+
+#[derive(Default)]
+struct Entry {
+    members: Option<Arc<Members>>,
+    listed_at: Option<Instant>,
+    refreshing: bool,
+}
+
+impl OrgTeamCache {
+    /// Returns the members of a team of an organization, listing them with `list` if those
+    /// cached are older than `ttl` and no other lookup is already listing them. Returns `None`
+    /// if the members aren't known.
+    pub fn get<F>(&self, org: &str, team: &str, ttl: Duration, list: F) -> Option<Arc<Members>>
+        where F: FnOnce() -> Result<Members>
+    {
+        self.get_at(org, team, ttl, Instant::now(), list)
+    }
+
+    /// Forget the members of every team of the given organization, returning how many teams
+    /// were forgotten. A refresh in progress for one of them is dropped when it completes.
+    pub fn invalidate_org(&self, org: &str) -> usize {
+        let org = org.to_lowercase();
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|key, _| key.0 != org);
+        before - entries.len()
+    }
+
+    fn get_at<F>(&self,
+                 org: &str,
+                 team: &str,
+                 ttl: Duration,
+                 now: Instant,
+                 list: F)
+                 -> Option<Arc<Members>>
+        where F: FnOnce() -> Result<Members>
+    {
+        let key = (org.to_lowercase(), team.to_lowercase());
+        {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = entries.entry(key.clone()).or_insert_with(Entry::default);
+            let fresh = entry.listed_at.map_or(false, |at| health::since(now, at) < ttl);
+            if fresh || entry.refreshing {
+                return entry.members.clone();
+            }
+            entry.refreshing = true;
+        }
+        let listed = list();
+        let mut entries = self.entries.lock().unwrap();
+        let entry = match entries.get_mut(&key) {
+            Some(entry) => entry,
+            // Invalidated while it was being listed
+            None => return listed.ok().map(Arc::new),
+        };
+        entry.refreshing = false;
+        match listed {
+            Ok(members) => {
+                debug!("GitHub team {}/{} listed, members={}", org, team, members.len());
+                entry.members = Some(Arc::new(members));
+                entry.listed_at = Some(now);
+            }
+            Err(e) => {
+                warn!("unable to list the members of GitHub team {}/{}, err={}",
+                      org,
+                      team,
+                      e);
+            }
+        }
+        entry.members.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use error::{Error, Result};
+    use oauth::github::TeamMember;
+
+    fn ttl() -> Duration {
+        Duration::from_secs(300)
+    }
+
+    fn members(logins: &[(&str, u64)]) -> Members {
+        let listed: Vec<TeamMember> = logins.iter()
+            .map(|&(login, id)| {
+                TeamMember {
+                    login: login.to_string(),
+                    id: id,
+                }
+            })
+            .collect();
+        Members::from(&listed[..])
+    }
+
+    fn unlisted() -> Result<Members> {
+        panic!("the team was listed again")
+    }
+
+    fn failing() -> Result<Members> {
+        Err(Error::TokenRevoked)
+    }
+
+    #[test]
+    fn members_are_matched_by_id() {
+        let team = members(&[("Octocat", 1), ("hubot", 0)]);
+        assert!(team.contains("octocat", 1));
+        assert!(team.contains("renamed", 1));
+        assert!(!team.contains("octocat", 2));
+        assert!(team.contains("HUBOT", 0));
+    }
+
+    #[test]
+    fn entries_are_listed_again_once_the_ttl_expires() {
+        let cache = OrgTeamCache::default();
+        let now = Instant::now();
+        let first = cache.get_at("Habitat-Sh", "core", ttl(), now, || Ok(members(&[("a", 1)])));
+        assert_eq!(first.unwrap().len(), 1);
+        let later = now + Duration::from_secs(299);
+        let cached = cache.get_at("habitat-sh", "Core", ttl(), later, unlisted).unwrap();
+        assert!(cached.contains("a", 1));
+        let expired = now + ttl();
+        let relisted = cache.get_at("habitat-sh", "core", ttl(), expired, || {
+                Ok(members(&[("a", 1), ("b", 2)]))
+            })
+            .unwrap();
+        assert!(relisted.contains("b", 2));
+    }
+
+    #[test]
+    fn stale_members_are_served_while_a_refresh_is_in_progress() {
+        let cache = Arc::new(OrgTeamCache::default());
+        let now = Instant::now();
+        cache.get_at("habitat-sh", "core", ttl(), now, || Ok(members(&[("a", 1)])));
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let refreshing = cache.clone();
+        let refresh = thread::spawn(move || {
+            refreshing.get_at("habitat-sh", "core", ttl(), now + ttl(), move || {
+                started_tx.send(()).unwrap();
+                release_rx.recv().unwrap();
+                Ok(members(&[("b", 2)]))
+            })
+        });
+        started_rx.recv().unwrap();
+        let stale = cache.get_at("habitat-sh", "core", ttl(), now + ttl(), unlisted).unwrap();
+        assert!(stale.contains("a", 1) && !stale.contains("b", 2));
+        release_tx.send(()).unwrap();
+        assert!(refresh.join().unwrap().unwrap().contains("b", 2));
+        let fresh = cache.get_at("habitat-sh", "core", ttl(), now + ttl(), unlisted).unwrap();
+        assert!(fresh.contains("b", 2));
+    }
+
+    #[test]
+    fn failed_listings_keep_what_was_known() {
+        let cache = OrgTeamCache::default();
+        let now = Instant::now();
+        assert!(cache.get_at("habitat-sh", "core", ttl(), now, failing).is_none());
+        cache.get_at("habitat-sh", "core", ttl(), now, || Ok(members(&[("a", 1)])));
+        let stale = cache.get_at("habitat-sh", "core", ttl(), now + ttl(), failing).unwrap();
+        assert!(stale.contains("a", 1));
+    }
+
+    #[test]
+    fn invalidated_orgs_are_listed_again() {
+        let cache = OrgTeamCache::default();
+        let now = Instant::now();
+        cache.get_at("habitat-sh", "core", ttl(), now, || Ok(members(&[("a", 1)])));
+        cache.get_at("habitat-sh", "docs", ttl(), now, || Ok(members(&[("a", 1)])));
+        cache.get_at("chef", "core", ttl(), now, || Ok(members(&[("a", 1)])));
+        assert_eq!(cache.invalidate_org("Habitat-Sh"), 2);
+        let relisted = cache.get_at("habitat-sh", "core", ttl(), now, || Ok(Members::default()));
+        assert!(relisted.unwrap().is_empty());
+        assert!(cache.get_at("chef", "core", ttl(), now, unlisted).is_some());
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metadata kept alongside an OAuth access token.
+//!
+//! A `TokenRecord` is produced when a code is exchanged for a token and is persisted with the
+//! session. It records which OAuth application the token was issued to, so sessions can be
+//! invalidated after the application's credentials are rotated, and when the token was last
+//! confirmed to still be valid.
+//!
+//! Builders older than `TokenRecord` stored the bare token. Their sessions load as legacy
+//! records, see `TokenRecord::is_legacy`, which are backfilled from GitHub the first time they
+//! are validated, or all at once with `GitHubClient::migrate_tokens`.
+
+use std::fmt;
+
+use protobuf::RepeatedField;
+use protocol::sessionsrv;
+use time;
+
+#[cfg(feature = "client")]
+use error::{Error, Result};
+#[cfg(feature = "client")]
+use super::github::GitHubClient;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct TokenRecord {
+    /// The access token
+    pub token: String,
+    /// OAuth scopes granted to the token
+    pub scopes: Vec<String>,
+    /// Client id of the OAuth application the token was issued to
+    pub client_id: String,
+    /// Seconds since the unix epoch at which the token was issued
+    pub issued_at: u64,
+    /// Seconds since the unix epoch at which the token was last confirmed to be valid
+    pub last_validated_at: u64,
+    /// GitHub id of the user owning the token, if GitHub named them when the token was checked
+    pub user_id: Option<u64>,
+}
+
+impl TokenRecord {
+    /// Create a record for a token which was issued, and therefore validated, just now.
+    pub fn new(token: String, scopes: Vec<String>, client_id: String) -> Self {
+        let now = now();
+        TokenRecord {
+            token: token,
+            scopes: scopes,
+            client_id: client_id,
+            issued_at: now,
+            last_validated_at: now,
+            user_id: None,
+        }
+    }
+
+    /// Create a record for a token stored bare by an older builder, which is all that is known
+    /// of it.
+    pub fn legacy(token: String) -> Self {
+        TokenRecord {
+            token: token,
+            scopes: vec![],
+            client_id: String::new(),
+            issued_at: 0,
+            last_validated_at: 0,
+            user_id: None,
+        }
+    }
+
+    /// Create the record of a token stored bare by an older builder, with its scopes, OAuth
+    /// application, issue time, and owner backfilled from GitHub.
+    ///
+    /// # Errors
+    ///
+    /// * The token has been revoked, has expired, or was issued to another OAuth application
+    /// * GitHub couldn't be asked, see `GitHubClient::check_token`
+    #[cfg(feature = "client")]
+    pub fn from_legacy(token: &str, client: &GitHubClient) -> Result<TokenRecord> {
+        match try!(client.check_token(token)) {
+            Some(record) => Ok(record),
+            None => Err(Error::TokenRevoked),
+        }
+    }
+
+    /// True if the record was stored by a builder which kept bare tokens, so it names no OAuth
+    /// application.
+    pub fn is_legacy(&self) -> bool {
+        self.client_id.is_empty()
+    }
+
+    /// True if the token hasn't been validated within the last `window_secs` seconds.
+    pub fn is_stale(&self, window_secs: u64) -> bool {
+        self.is_stale_at(now(), window_secs)
+    }
+
+    fn is_stale_at(&self, now: u64, window_secs: u64) -> bool {
+        now.saturating_sub(self.last_validated_at) > window_secs
+    }
+}
+
+/// What GitHub reports about a valid access token when it is checked, see
+/// `GitHubClient::token_info`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenInfo {
+    /// OAuth scopes granted to the token
+    pub scopes: Vec<String>,
+    /// Client id of the OAuth application the token was issued to
+    pub client_id: String,
+    /// Seconds since the unix epoch at which the token was issued
+    pub created_at: u64,
+    /// Seconds since the unix epoch at which the token was last updated, such as when it was
+    /// granted more scopes. GitHub doesn't report when a token was last used.
+    pub updated_at: u64,
+    /// GitHub login of the user owning the token
+    pub user_login: Option<String>,
+    /// GitHub id of the user owning the token
+    pub user_id: Option<u64>,
+}
+
+impl TokenInfo {
+    /// Returns the record of the given token described by this info, validated just now.
+    pub fn into_record(self, token: String) -> TokenRecord {
+        TokenRecord {
+            token: token,
+            scopes: self.scopes,
+            client_id: self.client_id,
+            issued_at: self.created_at,
+            last_validated_at: now(),
+            user_id: self.user_id,
+        }
+    }
+}
+
+impl fmt::Debug for TokenRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TokenRecord")
+            .field("token", &"<redacted>")
+            .field("scopes", &self.scopes)
+            .field("client_id", &self.client_id)
+            .field("issued_at", &self.issued_at)
+            .field("last_validated_at", &self.last_validated_at)
+            .field("user_id", &self.user_id)
+            .finish()
+    }
+}
+
+impl From<TokenRecord> for sessionsrv::SessionCreate {
+    fn from(record: TokenRecord) -> sessionsrv::SessionCreate {
+        let mut msg = sessionsrv::SessionCreate::new();
+        msg.set_token(record.token);
+        msg.set_client_id(record.client_id);
+        msg.set_scopes(RepeatedField::from_vec(record.scopes));
+        msg.set_issued_at(record.issued_at);
+        msg.set_last_validated_at(record.last_validated_at);
+        msg
+    }
+}
+
+impl From<sessionsrv::SessionToken> for TokenRecord {
+    fn from(mut msg: sessionsrv::SessionToken) -> TokenRecord {
+        TokenRecord {
+            token: msg.take_token(),
+            scopes: msg.take_scopes().into_vec(),
+            client_id: msg.take_client_id(),
+            issued_at: msg.get_issued_at(),
+            last_validated_at: msg.get_last_validated_at(),
+            user_id: None,
+        }
+    }
+}
+
+/// The record of the token of a session the gateway looked up, see
+/// `GitHubClients::refresh_session`.
+impl<'a> From<&'a sessionsrv::Session> for TokenRecord {
+    fn from(session: &'a sessionsrv::Session) -> TokenRecord {
+        TokenRecord {
+            token: session.get_token().to_string(),
+            scopes: session.get_scopes().to_vec(),
+            client_id: session.get_client_id().to_string(),
+            issued_at: session.get_issued_at(),
+            last_validated_at: session.get_last_validated_at(),
+            user_id: None,
+        }
+    }
+}
+
+impl From<TokenRecord> for sessionsrv::SessionValidated {
+    fn from(record: TokenRecord) -> sessionsrv::SessionValidated {
+        let mut msg = sessionsrv::SessionValidated::new();
+        msg.set_token(record.token);
+        msg.set_client_id(record.client_id);
+        msg.set_scopes(RepeatedField::from_vec(record.scopes));
+        msg.set_issued_at(record.issued_at);
+        msg.set_last_validated_at(record.last_validated_at);
+        msg
+    }
+}
+
+/// Seconds since the unix epoch.
+pub fn now() -> u64 {
+    time::get_time().sec as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::RepeatedField;
+    use protocol::sessionsrv;
+
+    use super::*;
+
+    fn record() -> TokenRecord {
+        TokenRecord {
+            token: "abc123".to_string(),
+            scopes: vec!["user:email".to_string(), "read:org".to_string()],
+            client_id: "client".to_string(),
+            issued_at: 1_000,
+            last_validated_at: 2_000,
+            user_id: None,
+        }
+    }
+
+    #[test]
+    fn staleness_window() {
+        let record = record();
+        assert!(!record.is_stale_at(2_500, 600));
+        assert!(record.is_stale_at(2_601, 600));
+        assert!(!record.is_stale_at(1_500, 600));
+    }
+
+    #[test]
+    fn session_round_trip() {
+        let create = sessionsrv::SessionCreate::from(record());
+        let mut token = sessionsrv::SessionToken::new();
+        token.set_token(create.get_token().to_string());
+        token.set_client_id(create.get_client_id().to_string());
+        token.set_scopes(RepeatedField::from_vec(create.get_scopes().to_vec()));
+        token.set_issued_at(create.get_issued_at());
+        token.set_last_validated_at(create.get_last_validated_at());
+        assert_eq!(TokenRecord::from(token), record());
+    }
+
+    #[test]
+    fn looked_up_sessions_carry_their_record() {
+        let validated = sessionsrv::SessionValidated::from(record());
+        let mut session = sessionsrv::Session::new();
+        session.set_token(validated.get_token().to_string());
+        session.set_client_id(validated.get_client_id().to_string());
+        session.set_scopes(RepeatedField::from_vec(validated.get_scopes().to_vec()));
+        session.set_issued_at(validated.get_issued_at());
+        session.set_last_validated_at(validated.get_last_validated_at());
+        assert_eq!(TokenRecord::from(&session), record());
+    }
+
+    #[test]
+    fn bare_sessions_are_legacy() {
+        let mut token = sessionsrv::SessionToken::new();
+        token.set_token("abc123".to_string());
+        let record = TokenRecord::from(token);
+        assert!(record.is_legacy());
+        assert_eq!(record, TokenRecord::legacy("abc123".to_string()));
+        assert!(record.is_stale(600));
+        assert!(!self::record().is_legacy());
+    }
+
+    #[test]
+    fn debug_redacts_token() {
+        assert!(!format!("{:?}", record()).contains("abc123"));
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watches a service's configuration file and applies changes to the `[cfg.github]` section to
+//! the running clients of a `GitHubClients` registry: the section itself to the default OAuth
+//! application, and each `[cfg.github.apps.<name>]` table to the named application.
+//!
+//! The configuration file is re-read when the process receives a `SIGHUP` or when the file's
+//! modification time changes. A configuration which fails to load or validate is logged and
+//! discarded, leaving the clients with their previous configuration. The watching thread stops
+//! when the `Shutdown` it was started with is shut down.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Once, ONCE_INIT};
+use std::sync::atomic::{AtomicBool, Ordering, ATOMIC_BOOL_INIT};
+use std::time::{Duration, SystemTime};
+
+use hcore::config::ConfigFile;
+use hcore::util::shutdown::Shutdown;
+use libc;
+use toml;
+
+use config::{GitHubCfg, HttpCfg};
+use error::{Error, Result};
+use super::github::GitHubClients;
+
+const POLL_INTERVAL_MS: u64 = 1_000;
+static INIT: Once = ONCE_INIT;
+// True when a SIGHUP has been caught and not yet handled
+static RELOAD: AtomicBool = ATOMIC_BOOL_INIT;
+
+extern "C" fn handle_sighup(_: libc::c_int) {
+    RELOAD.store(true, Ordering::SeqCst);
+}
+
+/// The GitHub applications of a configuration file.
+struct WatchedCfg {
+    default: GitHubCfg,
+    apps: BTreeMap<String, GitHubCfg>,
+}
+
+impl ConfigFile for WatchedCfg {
+    type Error = Error;
+
+    fn from_toml(toml: toml::Value) -> Result<Self> {
+        let github = match toml.lookup("cfg.github") {
+            Some(github) => github,
+            None => return Err(Error::RequiredConfigField("github")),
+        };
+        let mut default = try!(GitHubCfg::from_toml(github));
+        // Like the service itself, the default application falls back to the proxy and TLS
+        // settings of the whole service
+        if github.lookup("http").is_none() {
+            if let Some(http) = toml.lookup("cfg.http") {
+                default.http = try!(HttpCfg::from_toml(http));
+            }
+        }
+        let apps = match github.lookup("apps") {
+            Some(apps) => try!(GitHubCfg::apps_from_toml(apps)),
+            None => BTreeMap::new(),
+        };
+        Ok(WatchedCfg {
+            default: default,
+            apps: apps,
+        })
+    }
+}
+
+pub struct GitHubCfgWatcher {
+    path: PathBuf,
+    clients: Arc<GitHubClients>,
+    mtime: Option<SystemTime>,
+}
+
+impl GitHubCfgWatcher {
+    pub fn new<T: Into<PathBuf>>(path: T, clients: Arc<GitHubClients>) -> Self {
+        let path = path.into();
+        let mtime = modified(&path);
+        GitHubCfgWatcher {
+            path: path,
+            clients: clients,
+            mtime: mtime,
+        }
+    }
+
+    /// Start watching the configuration file in a separate thread, until `shutdown` is shut
+    /// down.
+    ///
+    /// # Errors
+    ///
+    /// * The watching thread could not be started
+    pub fn run(mut self, shutdown: &Shutdown) -> Result<()> {
+        INIT.call_once(|| unsafe {
+            libc::signal(libc::SIGHUP, handle_sighup as libc::sighandler_t);
+        });
+        try!(shutdown.spawn("github-cfg-watcher", move |shutdown| {
+            while !shutdown.sleep(Duration::from_millis(POLL_INTERVAL_MS)) {
+                self.tick();
+            }
+        }));
+        Ok(())
+    }
+
+    /// Reload the configuration if a `SIGHUP` was received or the file changed since it was last
+    /// loaded.
+    fn tick(&mut self) {
+        let mtime = modified(&self.path);
+        let hup = RELOAD.swap(false, Ordering::SeqCst);
+        if !hup && mtime == self.mtime {
+            return;
+        }
+        self.mtime = mtime;
+        match self.reload() {
+            Ok(()) => info!("reloaded GitHub configuration from {}", self.path.display()),
+            Err(e) => {
+                error!("rejected GitHub configuration from {}, keeping previous configuration, \
+                        err={}",
+                       self.path.display(),
+                       e)
+            }
+        }
+    }
+
+    fn reload(&self) -> Result<()> {
+        let cfg = try!(WatchedCfg::from_file(&self.path));
+        self.clients.reconfigure(&cfg.default, &cfg.apps)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use hcore::util::shutdown::Shutdown;
+
+    use super::*;
+    use oauth::fixtures::AppsFixture;
+    use oauth::github::GitHubClients;
+
+    fn clients() -> Arc<GitHubClients> {
+        let apps = AppsFixture::new(("abc", "def"), &[("internal", "ghi", "jkl")]);
+        Arc::new(GitHubClients::new(&apps).unwrap())
+    }
+
+    fn cfg_file(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("habitat-net-watcher-test-{}.toml", name));
+        File::create(&path).and_then(|mut f| f.write_all(contents.as_bytes())).unwrap();
+        path
+    }
+
+    fn client_ids(clients: &GitHubClients) -> Vec<String> {
+        let mut ids = clients.client_ids();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn shutting_down_stops_the_watcher() {
+        let path = env::temp_dir().join("habitat-net-watcher-test-missing.toml");
+        let shutdown = Shutdown::new();
+        GitHubCfgWatcher::new(path, clients()).run(&shutdown).unwrap();
+        let started = Instant::now();
+        shutdown.shutdown();
+        assert!(shutdown.join(Duration::from_secs(10)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn reloading_applies_the_new_configuration() {
+        let path = cfg_file("changed",
+                            "[cfg.github]\n\
+                             client_id = \"new\"\n\
+                             client_secret = \"secret\"\n\
+                             [cfg.github.apps.internal]\n\
+                             client_id = \"newer\"\n\
+                             client_secret = \"secret\"\n");
+        let clients = clients();
+        GitHubCfgWatcher::new(path, clients.clone()).reload().unwrap();
+        assert_eq!(client_ids(&clients), vec!["new", "newer"]);
+    }
+
+    #[test]
+    fn invalid_configurations_leave_the_previous_one_in_place() {
+        let path = cfg_file("invalid", "[cfg.github]\nclient_id = \"new\"\n");
+        let clients = clients();
+        assert!(GitHubCfgWatcher::new(path, clients.clone()).reload().is_err());
+        assert_eq!(client_ids(&clients), vec!["abc", "ghi"]);
+    }
+
+    #[test]
+    fn invalid_applications_leave_only_their_configuration_in_place() {
+        let path = cfg_file("invalid-app",
+                            "[cfg.github]\n\
+                             client_id = \"new\"\n\
+                             client_secret = \"secret\"\n\
+                             [cfg.github.apps.internal]\n\
+                             client_id = \"\"\n\
+                             client_secret = \"secret\"\n");
+        let clients = clients();
+        assert!(GitHubCfgWatcher::new(path, clients.clone()).reload().is_err());
+        assert_eq!(client_ids(&clients), vec!["ghi", "new"]);
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Removing secrets from what is logged, traced, or returned in an error, with the rules of
+//! `habitat_core::util::redact`, which the sys module shares.
+
+#[cfg(feature = "client")]
+use hyper::header::Headers;
+
+pub use hcore::util::redact::{is_secret_name, redact_header, redact_text, redact_url, REDACTED};
+
+/// Returns the names and values of the headers, with the values of headers carrying
+/// credentials, and secrets in the others, redacted.
+#[cfg(feature = "client")]
+pub fn redact_headers(headers: &Headers) -> Vec<(String, String)> {
+    headers.iter()
+        .map(|header| {
+            let name = header.name().to_string();
+            let value = redact_header(&name, &header.value_string()).into_owned();
+            (name, value)
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use hyper::header::{Authorization, Bearer, Headers, UserAgent};
+
+    use super::*;
+
+    #[test]
+    fn authorization_headers_are_redacted() {
+        let mut headers = Headers::new();
+        headers.set(Authorization(Bearer { token: "secret".to_string() }));
+        headers.set(UserAgent("Habitat-Builder".to_string()));
+        headers.set_raw("If-None-Match", vec![b"\"etag\"".to_vec()]);
+        let mut redacted = redact_headers(&headers);
+        redacted.sort();
+        assert_eq!(redacted,
+                   vec![("Authorization".to_string(), REDACTED.to_string()),
+                        ("If-None-Match".to_string(), "\"etag\"".to_string()),
+                        ("User-Agent".to_string(), "Habitat-Builder".to_string())]);
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! serde implementations of the types which derive `RustcEncodable` and `RustcDecodable`, built
+//! with the `serde` feature.
+//!
+//! A value is serialized by encoding it with rustc_serialize into a `Json` tree and handing the
+//! tree to the serializer, and deserialized the other way around, so both encoders agree on the
+//! names of the fields, on how `Option`s are rendered, and on what a missing field decodes to.
+
+use rustc_serialize::{Decodable, Encodable};
+use rustc_serialize::json::{self, Json};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Implements `serde::Serialize` and `serde::Deserialize` for types which derive
+/// `RustcEncodable` and `RustcDecodable`.
+macro_rules! serde_via_rustc_serialize {
+    ($($t:ty),*) => {
+        $(
+            serialize_via_rustc_serialize!($t);
+
+            impl ::serde::Deserialize for $t {
+                fn deserialize<D>(deserializer: &mut D) -> ::std::result::Result<Self, D::Error>
+                    where D: ::serde::Deserializer
+                {
+                    ::serde_bridge::deserialize(deserializer)
+                }
+            }
+        )*
+    }
+}
+
+/// Implements `serde::Serialize` for types which only derive `RustcEncodable`.
+macro_rules! serialize_via_rustc_serialize {
+    ($($t:ty),*) => {
+        $(
+            impl ::serde::Serialize for $t {
+                fn serialize<S>(&self, serializer: &mut S) -> ::std::result::Result<(), S::Error>
+                    where S: ::serde::Serializer
+                {
+                    ::serde_bridge::serialize(self, serializer)
+                }
+            }
+        )*
+    }
+}
+
+/// Serializes a value the way rustc_serialize encodes it.
+pub fn serialize<T, S>(value: &T, serializer: &mut S) -> Result<(), S::Error>
+    where T: Encodable,
+          S: Serializer
+{
+    let custom = |e: String| <S::Error as ser::Error>::custom(e);
+    let encoded = try!(json::encode(value).map_err(|e| custom(e.to_string())));
+    let tree = try!(Json::from_str(&encoded).map_err(|e| custom(e.to_string())));
+    JsonRef(&tree).serialize(serializer)
+}
+
+/// Deserializes a value the way rustc_serialize decodes it.
+pub fn deserialize<T: Decodable, D: Deserializer>(deserializer: &mut D) -> Result<T, D::Error> {
+    let JsonValue(tree) = try!(JsonValue::deserialize(deserializer));
+    Decodable::decode(&mut json::Decoder::new(tree))
+        .map_err(|e: json::DecoderError| <D::Error as de::Error>::custom(e.to_string()))
+}
+
+/// Serializes a `Json` tree.
+struct JsonRef<'a>(&'a Json);
+
+impl<'a> Serialize for JsonRef<'a> {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        match *self.0 {
+            Json::I64(n) => serializer.serialize_i64(n),
+            Json::U64(n) => serializer.serialize_u64(n),
+            Json::F64(n) => serializer.serialize_f64(n),
+            Json::String(ref s) => serializer.serialize_str(s),
+            Json::Boolean(b) => serializer.serialize_bool(b),
+            Json::Null => serializer.serialize_none(),
+            Json::Array(ref values) => {
+                let mut state = try!(serializer.serialize_seq(Some(values.len())));
+                for value in values {
+                    try!(serializer.serialize_seq_elt(&mut state, JsonRef(value)));
+                }
+                serializer.serialize_seq_end(state)
+            }
+            Json::Object(ref object) => {
+                let mut state = try!(serializer.serialize_map(Some(object.len())));
+                for (key, value) in object {
+                    try!(serializer.serialize_map_key(&mut state, key));
+                    try!(serializer.serialize_map_value(&mut state, JsonRef(value)));
+                }
+                serializer.serialize_map_end(state)
+            }
+        }
+    }
+}
+
+/// Deserializes anything JSON can hold into a `Json` tree.
+struct JsonValue(Json);
+
+impl Deserialize for JsonValue {
+    fn deserialize<D: Deserializer>(deserializer: &mut D) -> Result<Self, D::Error> {
+        deserializer.deserialize(JsonVisitor).map(JsonValue)
+    }
+}
+
+struct JsonVisitor;
+
+impl de::Visitor for JsonVisitor {
+    type Value = Json;
+
+    fn visit_bool<E: de::Error>(&mut self, v: bool) -> Result<Json, E> {
+        Ok(Json::Boolean(v))
+    }
+
+    fn visit_i64<E: de::Error>(&mut self, v: i64) -> Result<Json, E> {
+        Ok(Json::I64(v))
+    }
+
+    fn visit_u64<E: de::Error>(&mut self, v: u64) -> Result<Json, E> {
+        Ok(Json::U64(v))
+    }
+
+    fn visit_f64<E: de::Error>(&mut self, v: f64) -> Result<Json, E> {
+        Ok(Json::F64(v))
+    }
+
+    fn visit_str<E: de::Error>(&mut self, v: &str) -> Result<Json, E> {
+        Ok(Json::String(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(&mut self, v: String) -> Result<Json, E> {
+        Ok(Json::String(v))
+    }
+
+    fn visit_unit<E: de::Error>(&mut self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_none<E: de::Error>(&mut self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_some<D: Deserializer>(&mut self, deserializer: &mut D) -> Result<Json, D::Error> {
+        JsonValue::deserialize(deserializer).map(|JsonValue(tree)| tree)
+    }
+
+    fn visit_seq<V: de::SeqVisitor>(&mut self, mut visitor: V) -> Result<Json, V::Error> {
+        let mut values = vec![];
+        while let Some(JsonValue(value)) = try!(visitor.visit()) {
+            values.push(value);
+        }
+        try!(visitor.end());
+        Ok(Json::Array(values))
+    }
+
+    fn visit_map<V: de::MapVisitor>(&mut self, mut visitor: V) -> Result<Json, V::Error> {
+        let mut object = json::Object::new();
+        while let Some((key, JsonValue(value))) = try!(visitor.visit::<String, JsonValue>()) {
+            object.insert(key, value);
+        }
+        try!(visitor.end());
+        Ok(Json::Object(object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_serialize::json::{self, Json};
+    use serde_json;
+
+    #[derive(Debug, PartialEq, RustcEncodable, RustcDecodable)]
+    struct Payload {
+        login: String,
+        id: u64,
+        score: f64,
+        bio: Option<String>,
+        tags: Vec<String>,
+    }
+
+    serde_via_rustc_serialize!(Payload);
+
+    fn payload() -> Payload {
+        Payload {
+            login: "octocat".to_string(),
+            id: 583231,
+            score: 0.5,
+            bio: None,
+            tags: vec!["a".to_string(), "b".to_string()],
+        }
+    }
+
+    #[test]
+    fn serialized_like_rustc_serialize() {
+        let serde = Json::from_str(&serde_json::to_string(&payload()).unwrap()).unwrap();
+        let rustc = Json::from_str(&json::encode(&payload()).unwrap()).unwrap();
+        assert_eq!(serde, rustc);
+    }
+
+    #[test]
+    fn deserialized_like_rustc_serialize() {
+        // A missing optional field decodes to `None`, and an unknown one is ignored
+        let body = r#"{"login":"octocat","id":583231,"score":0.5,"tags":["a","b"],"extra":1}"#;
+        let serde: Payload = serde_json::from_str(body).unwrap();
+        let rustc: Payload = json::decode(body).unwrap();
+        assert_eq!(serde, rustc);
+        assert_eq!(serde, payload());
+    }
+
+    #[test]
+    fn missing_required_fields_fail() {
+        assert!(serde_json::from_str::<Payload>(r#"{"login":"octocat"}"#).is_err());
+    }
+}
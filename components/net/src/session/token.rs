@@ -0,0 +1,627 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Internal session tokens, which let the gateway authorize a request without looking up the
+//! session or asking GitHub.
+//!
+//! A token is `hs1.<claims>.<mac>`: the claims are JSON and the MAC is an HMAC-SHA256 of
+//! `hs1.<claims>`, both base64url encoded without padding. The claims bind the account, the
+//! user's identity at their provider, a SHA-256 digest of the OAuth token the session was
+//! created with, their privilege flags, and when the token was issued and expires. Tokens are
+//! minted with the first of the configured keys and verified with any of them, so a key is
+//! rotated by putting the new one first and dropping the old one once the tokens it minted have
+//! expired.
+//!
+//! A token is only as fresh as the privileges it carries. The gateway falls back to the session
+//! service, and through it to GitHub, when a token has expired or `SessionSigner::needs_refresh`
+//! says its privileges should be evaluated again, and hands out a token renewed with what it
+//! looked up, see `SessionSigner::renew`. The tokens of an account are refused from the time
+//! it logged out on, see `SessionSigner::revoke`. Revocations are kept by the signer, so only
+//! the gateway processes which saw the logout refuse them; elsewhere they stay valid until the
+//! privileges they carry need refreshing.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use protocol::sessionsrv;
+use rustc_serialize::base64::{FromBase64, ToBase64, URL_SAFE};
+use rustc_serialize::json;
+use openssl::crypto::hash::{self as openssl_hash, Type};
+use openssl::crypto::hmac::hmac;
+
+use error::{Error, Result};
+use oauth::identity::Identity;
+use oauth::token;
+
+/// Seconds a token is valid for after it was minted
+pub const DEFAULT_SESSION_TOKEN_TTL: u64 = 60 * 60;
+
+/// Seconds after which the privileges a token carries should be evaluated again
+pub const DEFAULT_SESSION_REFRESH_AFTER: u64 = 15 * 60;
+
+/// Seconds the clocks of the hosts minting and verifying tokens may disagree by
+pub const DEFAULT_SESSION_CLOCK_SKEW: u64 = 60;
+
+/// Shortest key tokens may be signed with, in bytes
+pub const MIN_SESSION_KEY_LEN: usize = 32;
+
+/// Version prefix of every token
+const TOKEN_PREFIX: &'static str = "hs1";
+
+/// Longest token `verify` looks at. Tokens are a few hundred bytes, and anything much longer is
+/// refused before any work is done on it.
+const MAX_TOKEN_LEN: usize = 4096;
+
+/// Characters of the base64url encoding
+const BASE64URL_ALPHABET: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz\
+                                             0123456789-_";
+
+/// What a token says about the owner of a session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionClaims {
+    /// Identifier of the account at the session service, if it had one when the token was minted
+    pub account_id: Option<u64>,
+    /// Identifier of the user at the identity provider
+    pub extern_id: u64,
+    /// Host of the identity provider `extern_id` belongs to
+    pub provider_host: String,
+    /// Login of the user at the identity provider
+    pub login: String,
+    /// Digest of the OAuth token the session was created with, see `token_digest`
+    pub token_digest: String,
+    pub flags: sessionsrv::FeatureFlags,
+    /// When the token was minted, in seconds since the epoch
+    pub issued_at: u64,
+    /// When the token stops being valid, in seconds since the epoch
+    pub expires_at: u64,
+}
+
+impl SessionClaims {
+    /// Returns the session the claims describe, carrying the given OAuth token, or `None` if the
+    /// token was minted before the account existed or for another OAuth token.
+    pub fn session(&self, token: &str) -> Option<sessionsrv::Session> {
+        let id = match self.account_id {
+            Some(id) => id,
+            None => return None,
+        };
+        if token.is_empty() ||
+           !constant_time_eq(token_digest(token).as_bytes(), self.token_digest.as_bytes()) {
+            return None;
+        }
+        let mut session = sessionsrv::Session::new();
+        session.set_id(id);
+        session.set_name(self.login.clone());
+        session.set_token(token.to_string());
+        session.set_feature_flags(self.flags);
+        Some(session)
+    }
+}
+
+/// Claims as they are encoded in a token.
+#[derive(RustcEncodable, RustcDecodable)]
+struct WireClaims {
+    aid: Option<u64>,
+    sub: u64,
+    host: String,
+    login: String,
+    tdg: String,
+    flags: u32,
+    iat: u64,
+    exp: u64,
+}
+
+/// Lifetime of the tokens a `SessionSigner` mints, in seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionTokenOptions {
+    /// Seconds a token is valid for after it was minted
+    pub ttl: u64,
+    /// Seconds after which the privileges a token carries should be evaluated again
+    pub refresh_after: u64,
+    /// Seconds the clocks of the hosts minting and verifying tokens may disagree by
+    pub clock_skew: u64,
+}
+
+impl Default for SessionTokenOptions {
+    fn default() -> Self {
+        SessionTokenOptions {
+            ttl: DEFAULT_SESSION_TOKEN_TTL,
+            refresh_after: DEFAULT_SESSION_REFRESH_AFTER,
+            clock_skew: DEFAULT_SESSION_CLOCK_SKEW,
+        }
+    }
+}
+
+/// Mints and verifies session tokens. The keys are deliberately kept out of any `Debug` output.
+pub struct SessionSigner {
+    keys: Vec<Vec<u8>>,
+    options: SessionTokenOptions,
+    /// When the tokens of each account were last revoked, for as long as tokens minted before
+    /// may still verify
+    revoked: Mutex<HashMap<u64, u64>>,
+}
+
+impl SessionSigner {
+    /// Returns a signer minting tokens with the first of `keys` and verifying them with any.
+    ///
+    /// # Errors
+    ///
+    /// * No key is given
+    /// * A key is shorter than `MIN_SESSION_KEY_LEN` bytes
+    pub fn new(keys: &[String], options: SessionTokenOptions) -> Result<Self> {
+        if keys.is_empty() {
+            return Err(Error::RequiredConfigField("session.keys"));
+        }
+        if let Some(key) = keys.iter().find(|k| k.len() < MIN_SESSION_KEY_LEN) {
+            return Err(Error::SessionKeyTooShort(key.len()));
+        }
+        Ok(SessionSigner {
+            keys: keys.iter().map(|k| k.as_bytes().to_vec()).collect(),
+            options: options,
+            revoked: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn options(&self) -> &SessionTokenOptions {
+        &self.options
+    }
+
+    /// Returns a token for the given identity's session of the given OAuth token, carrying the
+    /// given privileges.
+    pub fn mint(&self,
+                identity: &Identity,
+                flags: &sessionsrv::FeatureFlags,
+                oauth_token: &str)
+                -> String {
+        self.mint_at(identity, flags, oauth_token, token::now())
+    }
+
+    /// Returns a token replacing the one the given claims came from, carrying the privileges of
+    /// the given session, which was looked up because the claims needed refreshing. Returns
+    /// `None` if the session is of another account or OAuth token than the claims.
+    pub fn renew(&self, claims: &SessionClaims, session: &sessionsrv::Session) -> Option<String> {
+        self.renew_at(claims, session, token::now())
+    }
+
+    /// Refuses every token minted for the given account so far, such as when its owner logs out.
+    pub fn revoke(&self, account_id: u64) {
+        self.revoke_at(account_id, token::now())
+    }
+
+    /// True if the given claims come from a token minted before the tokens of its account were
+    /// last revoked.
+    pub fn is_revoked(&self, claims: &SessionClaims) -> bool {
+        let id = match claims.account_id {
+            Some(id) => id,
+            None => return false,
+        };
+        match self.revoked.lock().unwrap().get(&id) {
+            Some(&revoked_at) => claims.issued_at <= revoked_at,
+            None => false,
+        }
+    }
+
+    /// Returns the claims of the given token.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidSessionToken` if the token is malformed, wasn't signed with any of the
+    ///   keys, or was issued later than the clock skew allows
+    /// * `Error::SessionTokenExpired` if the token expired longer ago than the clock skew allows
+    pub fn verify(&self, token: &str) -> Result<SessionClaims> {
+        self.verify_at(token, token::now())
+    }
+
+    /// True if the privileges the given claims carry should be evaluated again before they are
+    /// trusted, because they were fetched long enough ago or from a partial profile.
+    pub fn needs_refresh(&self, claims: &SessionClaims) -> bool {
+        self.needs_refresh_at(claims, token::now())
+    }
+
+    fn mint_at(&self,
+               identity: &Identity,
+               flags: &sessionsrv::FeatureFlags,
+               oauth_token: &str,
+               now: u64)
+               -> String {
+        self.sign(&WireClaims {
+            aid: identity.account_id,
+            sub: identity.external_id,
+            host: identity.provider_host.clone(),
+            login: identity.login.clone(),
+            tdg: token_digest(oauth_token),
+            flags: flags.bits(),
+            iat: now,
+            exp: now.saturating_add(self.options.ttl),
+        })
+    }
+
+    fn renew_at(&self,
+                claims: &SessionClaims,
+                session: &sessionsrv::Session,
+                now: u64)
+                -> Option<String> {
+        if claims.account_id != Some(session.get_id()) ||
+           claims.session(session.get_token()).is_none() {
+            return None;
+        }
+        Some(self.sign(&WireClaims {
+            aid: claims.account_id,
+            sub: claims.extern_id,
+            host: claims.provider_host.clone(),
+            login: session.get_name().to_string(),
+            tdg: claims.token_digest.clone(),
+            flags: session.get_feature_flags().bits(),
+            iat: now,
+            exp: now.saturating_add(self.options.ttl),
+        }))
+    }
+
+    /// Records the revocation, dropping those no token they refuse can outlive.
+    fn revoke_at(&self, account_id: u64, now: u64) {
+        let horizon = self.options.ttl.saturating_add(self.options.clock_skew);
+        let mut revoked = self.revoked.lock().unwrap();
+        let stale: Vec<u64> = revoked.iter()
+            .filter(|&(_, &at)| at.saturating_add(horizon) < now)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in stale {
+            revoked.remove(&id);
+        }
+        revoked.insert(account_id, now);
+    }
+
+    fn sign(&self, claims: &WireClaims) -> String {
+        let encoded = json::encode(claims).expect("session claims always encode");
+        let signed = format!("{}.{}", TOKEN_PREFIX, encoded.as_bytes().to_base64(URL_SAFE));
+        let mac = mac(&self.keys[0], &signed);
+        format!("{}.{}", signed, mac.to_base64(URL_SAFE))
+    }
+
+    fn verify_at(&self, token: &str, now: u64) -> Result<SessionClaims> {
+        if token.len() > MAX_TOKEN_LEN {
+            return Err(Error::InvalidSessionToken("too long"));
+        }
+        let (signed, mac_part) = match token.rfind('.') {
+            Some(at) => (&token[..at], &token[at + 1..]),
+            None => return Err(Error::InvalidSessionToken("malformed")),
+        };
+        let claims_part = match signed.find('.') {
+            Some(at) if &signed[..at] == TOKEN_PREFIX => &signed[at + 1..],
+            _ => return Err(Error::InvalidSessionToken("malformed")),
+        };
+        let given = try!(decode_part(mac_part));
+        if !self.keys.iter().any(|key| constant_time_eq(&mac(key, signed), &given)) {
+            return Err(Error::InvalidSessionToken("bad signature"));
+        }
+        let claims = try!(decode_part(claims_part));
+        let claims = match String::from_utf8(claims) {
+            Ok(claims) => claims,
+            Err(_) => return Err(Error::InvalidSessionToken("bad claims")),
+        };
+        let claims: WireClaims = match json::decode(&claims) {
+            Ok(claims) => claims,
+            Err(_) => return Err(Error::InvalidSessionToken("bad claims")),
+        };
+        if claims.iat > now.saturating_add(self.options.clock_skew) {
+            return Err(Error::InvalidSessionToken("issued in the future"));
+        }
+        if claims.exp.saturating_add(self.options.clock_skew) < now {
+            return Err(Error::SessionTokenExpired);
+        }
+        Ok(SessionClaims {
+            account_id: claims.aid,
+            extern_id: claims.sub,
+            provider_host: claims.host,
+            login: claims.login,
+            token_digest: claims.tdg,
+            flags: sessionsrv::FeatureFlags::from_bits_truncate(claims.flags),
+            issued_at: claims.iat,
+            expires_at: claims.exp,
+        })
+    }
+
+    fn needs_refresh_at(&self, claims: &SessionClaims, now: u64) -> bool {
+        claims.flags.contains(sessionsrv::PARTIAL_PROFILE) ||
+        claims.issued_at.saturating_add(self.options.refresh_after) <= now
+    }
+}
+
+fn mac(key: &[u8], signed: &str) -> Vec<u8> {
+    hmac(Type::SHA256, key, signed.as_bytes())
+}
+
+/// Returns the base64url encoded SHA-256 digest of an OAuth token, which a session token carries
+/// so it is only accepted along with the OAuth token it was minted for.
+fn token_digest(oauth_token: &str) -> String {
+    openssl_hash::hash(Type::SHA256, oauth_token.as_bytes()).to_base64(URL_SAFE)
+}
+
+/// Decodes one part of a token. Only the canonical, unpadded base64url encoding of the part is
+/// accepted, so every token has exactly one spelling.
+fn decode_part(part: &str) -> Result<Vec<u8>> {
+    if part.is_empty() || !part.bytes().all(|b| BASE64URL_ALPHABET.contains(&b)) {
+        return Err(Error::InvalidSessionToken("malformed"));
+    }
+    match part.from_base64() {
+        Ok(ref bytes) if bytes.to_base64(URL_SAFE) == part => Ok(bytes.clone()),
+        _ => Err(Error::InvalidSessionToken("malformed")),
+    }
+}
+
+/// Compares two MACs in a time which depends on their lengths only, so how long a comparison
+/// takes doesn't tell how much of a forged MAC was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && difference(a, b) == 0
+}
+
+/// Returns the bits in which any pair of bytes of `a` and `b` differ, looking at every pair.
+fn difference(a: &[u8], b: &[u8]) -> u8 {
+    a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y))
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::sessionsrv;
+
+    use rustc_serialize::base64::FromBase64;
+
+    use super::*;
+    use super::{constant_time_eq, difference, token_digest, MAX_TOKEN_LEN};
+    use error::{Error, Result};
+    use oauth::fixtures::{self, UserFixture};
+    use oauth::identity::Identity;
+
+    const KEY: &'static str = "0123456789abcdef0123456789abcdef";
+    const OLD_KEY: &'static str = "fedcba9876543210fedcba9876543210";
+    const NOW: u64 = 1476350400;
+    const TOKEN: &'static str = "gho_token";
+
+    fn signer(keys: &[&str]) -> SessionSigner {
+        let keys: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+        SessionSigner::new(&keys, SessionTokenOptions::default()).unwrap()
+    }
+
+    fn identity() -> Identity {
+        let mut identity = Identity::from(&UserFixture::default().build());
+        identity.account_id = Some(42);
+        identity
+    }
+
+    fn flags() -> sessionsrv::FeatureFlags {
+        sessionsrv::MEMBER | sessionsrv::BUILDER
+    }
+
+    fn is_invalid(result: Result<SessionClaims>) -> bool {
+        match result {
+            Err(Error::InvalidSessionToken(_)) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn minted_tokens_verify() {
+        let signer = signer(&[KEY]);
+        let token = signer.mint_at(&identity(), &flags(), TOKEN, NOW);
+        assert!(token.starts_with("hs1."));
+        let claims = signer.verify_at(&token, NOW).unwrap();
+        assert_eq!(claims,
+                   SessionClaims {
+                       account_id: Some(42),
+                       extern_id: fixtures::ID,
+                       provider_host: "github.com".to_string(),
+                       login: fixtures::LOGIN.to_string(),
+                       token_digest: token_digest(TOKEN),
+                       flags: flags(),
+                       issued_at: NOW,
+                       expires_at: NOW + DEFAULT_SESSION_TOKEN_TTL,
+                   });
+        let session = claims.session(TOKEN).unwrap();
+        assert_eq!(session.get_id(), 42);
+        assert_eq!(session.get_name(), fixtures::LOGIN);
+        assert_eq!(session.get_token(), TOKEN);
+        assert_eq!(session.get_feature_flags(), flags());
+    }
+
+    #[test]
+    fn claims_only_make_a_session_of_their_oauth_token() {
+        let signer = signer(&[KEY]);
+        let claims = signer.verify_at(&signer.mint_at(&identity(), &flags(), TOKEN, NOW), NOW)
+            .unwrap();
+        assert_eq!(claims.session("gho_other"), None);
+        assert_eq!(claims.session(""), None);
+        assert_eq!(claims.session(&TOKEN[1..]), None);
+    }
+
+    #[test]
+    fn revoked_tokens_are_refused_until_minted_again() {
+        let signer = signer(&[KEY]);
+        let before = signer.verify_at(&signer.mint_at(&identity(), &flags(), TOKEN, NOW), NOW)
+            .unwrap();
+        assert!(!signer.is_revoked(&before));
+        signer.revoke_at(42, NOW);
+        assert!(signer.is_revoked(&before));
+        let token = signer.mint_at(&identity(), &flags(), TOKEN, NOW + 1);
+        let after = signer.verify_at(&token, NOW + 1).unwrap();
+        assert!(!signer.is_revoked(&after));
+        let mut other = identity();
+        other.account_id = Some(7);
+        let other = signer.verify_at(&signer.mint_at(&other, &flags(), TOKEN, NOW), NOW).unwrap();
+        assert!(!signer.is_revoked(&other));
+
+        // Revocations are dropped once every token they refuse has expired
+        let horizon = DEFAULT_SESSION_TOKEN_TTL + DEFAULT_SESSION_CLOCK_SKEW;
+        signer.revoke_at(7, NOW + horizon);
+        assert!(signer.revoked.lock().unwrap().contains_key(&42));
+        signer.revoke_at(7, NOW + horizon + 1);
+        assert!(!signer.revoked.lock().unwrap().contains_key(&42));
+    }
+
+    #[test]
+    fn renewed_tokens_carry_the_looked_up_privileges() {
+        let signer = signer(&[KEY]);
+        let partial = flags() | sessionsrv::PARTIAL_PROFILE;
+        let claims = signer.verify_at(&signer.mint_at(&identity(), &partial, TOKEN, NOW), NOW)
+            .unwrap();
+        let mut session = claims.session(TOKEN).unwrap();
+        session.set_feature_flags(sessionsrv::MEMBER);
+        let later = NOW + DEFAULT_SESSION_REFRESH_AFTER;
+        let renewed = signer.renew_at(&claims, &session, later).unwrap();
+        let renewed = signer.verify_at(&renewed, later).unwrap();
+        assert_eq!(renewed.flags, sessionsrv::MEMBER);
+        assert_eq!(renewed.issued_at, later);
+        assert_eq!(renewed.expires_at, later + DEFAULT_SESSION_TOKEN_TTL);
+        assert_eq!(renewed.extern_id, claims.extern_id);
+        assert_eq!(renewed.token_digest, claims.token_digest);
+        assert!(!signer.needs_refresh_at(&renewed, later));
+
+        let mut other_token = session.clone();
+        other_token.set_token("gho_other".to_string());
+        assert_eq!(signer.renew_at(&claims, &other_token, later), None);
+        let mut other_account = session.clone();
+        other_account.set_id(7);
+        assert_eq!(signer.renew_at(&claims, &other_account, later), None);
+    }
+
+    #[test]
+    fn claims_without_account_make_no_session() {
+        let signer = signer(&[KEY]);
+        let mut identity = identity();
+        identity.account_id = None;
+        let claims = signer.verify_at(&signer.mint_at(&identity, &flags(), TOKEN, NOW), NOW)
+            .unwrap();
+        assert_eq!(claims.session(TOKEN), None);
+    }
+
+    #[test]
+    fn tokens_carry_no_email() {
+        let token = signer(&[KEY]).mint_at(&identity(), &flags(), TOKEN, NOW);
+        let claims = token.split('.').nth(1).unwrap().from_base64().unwrap();
+        assert!(!String::from_utf8(claims).unwrap().contains(fixtures::EMAIL));
+    }
+
+    #[test]
+    fn keys_must_be_long_enough() {
+        assert!(SessionSigner::new(&[], SessionTokenOptions::default()).is_err());
+        match SessionSigner::new(&[KEY.to_string(), "short".to_string()],
+                                 SessionTokenOptions::default()) {
+            Err(Error::SessionKeyTooShort(5)) => (),
+            _ => panic!("a short key was accepted"),
+        }
+    }
+
+    #[test]
+    fn rotated_keys_keep_verifying() {
+        let token = signer(&[OLD_KEY]).mint_at(&identity(), &flags(), TOKEN, NOW);
+        let rotated = signer(&[KEY, OLD_KEY]);
+        assert!(rotated.verify_at(&token, NOW).is_ok());
+        // New tokens are minted with the first key only
+        let fresh = rotated.mint_at(&identity(), &flags(), TOKEN, NOW);
+        assert!(signer(&[KEY]).verify_at(&fresh, NOW).is_ok());
+        assert!(is_invalid(signer(&[OLD_KEY]).verify_at(&fresh, NOW)));
+        // Once the old key is dropped its tokens are refused
+        assert!(is_invalid(signer(&[KEY]).verify_at(&token, NOW)));
+    }
+
+    #[test]
+    fn tampered_tokens_are_refused() {
+        let signer = signer(&[KEY]);
+        let token = signer.mint_at(&identity(), &sessionsrv::MEMBER, TOKEN, NOW);
+        let parts: Vec<&str> = token.split('.').collect();
+        let admin = signer.mint_at(&identity(), &sessionsrv::ADMIN, TOKEN, NOW);
+        let admin_parts: Vec<&str> = admin.split('.').collect();
+        let forged = vec![format!("hs1.{}.{}", admin_parts[1], parts[2]),
+                          format!("hs2.{}.{}", parts[1], parts[2]),
+                          format!("{}.{}", parts[1], parts[2]),
+                          format!("hs1.{}.{}=", parts[1], parts[2]),
+                          format!("hs1.{}.+{}", parts[1], &parts[2][1..]),
+                          format!("hs1.{}.", parts[1]),
+                          format!("hs1.{}", parts[1]),
+                          format!("{}.", token),
+                          String::new(),
+                          ".".to_string(),
+                          "hs1..".to_string()];
+        for token in forged {
+            assert!(is_invalid(signer.verify_at(&token, NOW)), "{} was accepted", token);
+        }
+        let mut flipped = token.into_bytes();
+        let last = flipped.len() - 1;
+        flipped[last] = if flipped[last] == b'A' { b'B' } else { b'A' };
+        let flipped = String::from_utf8(flipped).unwrap();
+        assert!(is_invalid(signer.verify_at(&flipped, NOW)));
+        let long: String = ::std::iter::repeat('a').take(MAX_TOKEN_LEN + 1).collect();
+        assert!(is_invalid(signer.verify_at(&long, NOW)));
+    }
+
+    #[test]
+    fn expiry_tolerates_clock_skew() {
+        let signer = signer(&[KEY]);
+        let token = signer.mint_at(&identity(), &flags(), TOKEN, NOW);
+        let expires_at = NOW + DEFAULT_SESSION_TOKEN_TTL;
+        assert!(signer.verify_at(&token, expires_at).is_ok());
+        assert!(signer.verify_at(&token, expires_at + DEFAULT_SESSION_CLOCK_SKEW).is_ok());
+        match signer.verify_at(&token, expires_at + DEFAULT_SESSION_CLOCK_SKEW + 1) {
+            Err(Error::SessionTokenExpired) => (),
+            other => panic!("expired token not refused, {:?}", other.map(|c| c.expires_at)),
+        }
+    }
+
+    #[test]
+    fn issue_time_tolerates_clock_skew() {
+        // A token minted by a host whose clock is ahead of the verifying host's
+        let signer = signer(&[KEY]);
+        let token = signer.mint_at(&identity(), &flags(), TOKEN, NOW);
+        assert!(signer.verify_at(&token, NOW - DEFAULT_SESSION_CLOCK_SKEW).is_ok());
+        assert!(is_invalid(signer.verify_at(&token, NOW - DEFAULT_SESSION_CLOCK_SKEW - 1)));
+    }
+
+    #[test]
+    fn privileges_need_refresh_after_a_while_or_when_partial() {
+        let signer = signer(&[KEY]);
+        let claims = signer.verify_at(&signer.mint_at(&identity(), &flags(), TOKEN, NOW), NOW)
+            .unwrap();
+        assert!(!signer.needs_refresh_at(&claims, NOW));
+        assert!(!signer.needs_refresh_at(&claims, NOW + DEFAULT_SESSION_REFRESH_AFTER - 1));
+        assert!(signer.needs_refresh_at(&claims, NOW + DEFAULT_SESSION_REFRESH_AFTER));
+        let partial = flags() | sessionsrv::PARTIAL_PROFILE;
+        let claims = signer.verify_at(&signer.mint_at(&identity(), &partial, TOKEN, NOW), NOW)
+            .unwrap();
+        assert!(signer.needs_refresh_at(&claims, NOW));
+    }
+
+    #[test]
+    fn constant_time_eq_compares_whole_macs() {
+        let mac = [0x5au8; 32];
+        assert!(constant_time_eq(&mac, &mac));
+        let mut first = mac;
+        first[0] ^= 1;
+        let mut last = mac;
+        last[31] ^= 1;
+        assert!(!constant_time_eq(&mac, &first));
+        assert!(!constant_time_eq(&mac, &last));
+        assert!(!constant_time_eq(&mac, &mac[..31]));
+        assert!(!constant_time_eq(&[], &mac));
+        assert!(constant_time_eq(&[], &[]));
+    }
+
+    #[test]
+    fn constant_time_eq_looks_at_every_byte() {
+        // A comparison stopping at the first difference would only see the bits of the first
+        let mut other = [0u8; 32];
+        other[0] = 0b0001;
+        other[15] = 0b0100;
+        other[31] = 0b1000;
+        assert_eq!(difference(&[0u8; 32], &other), 0b1101);
+        assert_eq!(difference(&[0xffu8; 4], &[0x0fu8; 4]), 0xf0);
+    }
+}
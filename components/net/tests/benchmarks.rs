@@ -0,0 +1,183 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Timings of the login path against the fake GitHub, to compare before and after a change.
+//!
+//! The benchmarks are ignored tests, run with
+//!
+//! ```bash
+//! $ cargo test --test benchmarks -- --ignored --nocapture
+//! ```
+//!
+//! Each prints a table of the latencies it measured. Every run makes the same number of calls
+//! after the same warm up, and the table reports percentiles rather than means, so runs on the
+//! same machine are comparable. Nothing is enforced. The client opens a connection per request
+//! and the fake GitHub closes it after answering, so there is no keep-alive to measure yet;
+//! the runs differ in whether responses are cached.
+
+#![cfg(feature = "client")]
+
+extern crate habitat_net;
+
+mod fakehub;
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use habitat_net::config::GitHubCfg;
+use habitat_net::oauth::OAuthProvider;
+use habitat_net::oauth::github::GitHubClient;
+use habitat_net::oauth::policy::TeamPolicy;
+
+use fakehub::FakeGitHub;
+
+/// Calls made before measuring, so the first connections and allocations aren't measured
+const WARMUP: usize = 20;
+
+/// Logins measured by `bench_login`
+const LOGINS: usize = 200;
+
+/// Callers of `GitHubClient::user` measured at once by `bench_concurrent_user`
+const CALLERS: usize = 32;
+
+/// Calls each caller of `bench_concurrent_user` makes
+const CALLS_PER_CALLER: usize = 25;
+
+/// Latencies measured of one operation, sorted.
+struct Timings {
+    name: String,
+    samples: Vec<Duration>,
+}
+
+impl Timings {
+    fn new(name: &str, mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        Timings {
+            name: name.to_string(),
+            samples: samples,
+        }
+    }
+
+    fn percentile(&self, percent: usize) -> Duration {
+        let at = (self.samples.len() - 1) * percent / 100;
+        self.samples[at]
+    }
+}
+
+/// Returns the latencies of `iterations` calls of `f`, after `WARMUP` calls which aren't
+/// measured.
+fn measure<F: FnMut()>(name: &str, iterations: usize, mut f: F) -> Timings {
+    for _ in 0..WARMUP {
+        f();
+    }
+    let samples = (0..iterations)
+        .map(|_| {
+            let started = Instant::now();
+            f();
+            started.elapsed()
+        })
+        .collect();
+    Timings::new(name, samples)
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000 + duration.subsec_nanos() as u64 / 1_000
+}
+
+fn print_table(rows: &[Timings]) {
+    let width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0);
+    println!("\n{:width$} {:>6} {:>9} {:>9} {:>9} {:>9} {:>9}",
+             "benchmark",
+             "n",
+             "min(us)",
+             "p50(us)",
+             "p90(us)",
+             "p99(us)",
+             "max(us)",
+             width = width);
+    for row in rows {
+        println!("{:width$} {:>6} {:>9} {:>9} {:>9} {:>9} {:>9}",
+                 row.name,
+                 row.samples.len(),
+                 micros(row.samples[0]),
+                 micros(row.percentile(50)),
+                 micros(row.percentile(90)),
+                 micros(row.percentile(99)),
+                 micros(row.samples[row.samples.len() - 1]),
+                 width = width);
+    }
+}
+
+fn cfg(hub: &FakeGitHub, cache_ttl_secs: u64) -> GitHubCfg {
+    let mut cfg = hub.cfg();
+    cfg.options.cache_ttl_secs = cache_ttl_secs;
+    cfg
+}
+
+fn client(cfg: &GitHubCfg) -> GitHubClient {
+    GitHubClient::new(cfg, cfg.options.clone()).unwrap()
+}
+
+#[test]
+#[ignore]
+fn bench_login() {
+    let hub = FakeGitHub::start();
+    let rows: Vec<Timings> = [("login", 0), ("login, cached", 300)]
+        .iter()
+        .map(|&(name, cache_ttl_secs)| {
+            let cfg = cfg(&hub, cache_ttl_secs);
+            let client = client(&cfg);
+            let policy = TeamPolicy::new(&cfg);
+            measure(name, LOGINS, || {
+                client.login(fakehub::CODE, &policy).unwrap();
+            })
+        })
+        .collect();
+    print_table(&rows);
+}
+
+#[test]
+#[ignore]
+fn bench_concurrent_user() {
+    let hub = FakeGitHub::start();
+    let rows: Vec<Timings> = [("user x32", 0), ("user x32, cached", 300)]
+        .iter()
+        .map(|&(name, cache_ttl_secs)| {
+            let client = Arc::new(client(&cfg(&hub, cache_ttl_secs)));
+            for _ in 0..WARMUP {
+                client.user(fakehub::TOKEN).unwrap();
+            }
+            let callers: Vec<_> = (0..CALLERS)
+                .map(|_| {
+                    let client = client.clone();
+                    thread::spawn(move || {
+                        (0..CALLS_PER_CALLER)
+                            .map(|_| {
+                                let started = Instant::now();
+                                client.user(fakehub::TOKEN).unwrap();
+                                started.elapsed()
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            let samples = callers.into_iter()
+                .flat_map(|caller| caller.join().unwrap())
+                .collect();
+            Timings::new(name, samples)
+        })
+        .collect();
+    print_table(&rows);
+}
@@ -0,0 +1,505 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A fake GitHub, served from a local port, for exercising a `GitHubClient` without the network.
+//!
+//! The server answers the token exchange, `/user`, `/user/emails`, and `/user/orgs` with the
+//! fixtures found in `tests/fixtures` until a test programs a different `Reply` for a route.
+//! Every request it receives is recorded so a test can assert on what the client sent.
+
+// Not every test binary uses every helper.
+#![allow(dead_code)]
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use habitat_net::config::{GitHubApps, GitHubCfg, GitHubClientOptions, GitHubOAuth, HttpCfg};
+
+/// Client id of the OAuth application `FakeGitHub::cfg` is configured with.
+pub const CLIENT_ID: &'static str = "fake-client-id";
+/// Client secret of the OAuth application `FakeGitHub::cfg` is configured with.
+pub const CLIENT_SECRET: &'static str = "fake-client-secret";
+/// OAuth code the default token exchange accepts.
+pub const CODE: &'static str = "fake-code";
+/// Access token the default token exchange issues.
+pub const TOKEN: &'static str = "fake-token";
+/// Path of the token exchange, relative to the web URL.
+pub const ACCESS_TOKEN_PATH: &'static str = "/login/oauth/access_token";
+
+const USER: &'static str = include_str!("../fixtures/github-user.json");
+const EMAILS: &'static str = include_str!("../fixtures/github-emails.json");
+const ORGS: &'static str = include_str!("../fixtures/github-orgs.json");
+
+/// A misbehaviour injected in place of a response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Close the connection without answering
+    Hangup,
+    /// Answer with bytes which aren't HTTP
+    Garbage,
+}
+
+/// The response programmed for a route.
+#[derive(Clone, Debug)]
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// Time to wait before answering
+    pub delay: Option<Duration>,
+    /// Misbehaviour to answer with instead of the response
+    pub fault: Option<Fault>,
+}
+
+impl Reply {
+    /// A JSON response with the given status and body.
+    pub fn json(status: u16, body: &str) -> Self {
+        Reply {
+            status: status,
+            headers: vec![],
+            body: body.to_string(),
+            delay: None,
+            fault: None,
+        }
+    }
+
+    /// A successful token exchange issuing `TOKEN` with the given comma separated scopes.
+    pub fn token(scope: &str) -> Self {
+        let body = format!("{{\"access_token\":\"{}\",\"scope\":\"{}\",\
+                            \"token_type\":\"bearer\"}}",
+                           TOKEN,
+                           scope);
+        Reply::json(200, &body)
+    }
+
+    /// The 403 GitHub answers with once the rate limit of the caller is exhausted. The limit
+    /// resets at the given Unix time.
+    pub fn rate_limited(reset: u64) -> Self {
+        Reply::json(403,
+                    "{\"message\":\"API rate limit exceeded for user ID 583231.\",\
+                     \"documentation_url\":\"https://developer.github.com/v3/#rate-limiting\"}")
+            .header("X-RateLimit-Limit", "5000")
+            .header("X-RateLimit-Remaining", "0")
+            .header("X-RateLimit-Reset", &reset.to_string())
+    }
+
+    /// GitHub's answer to checking a valid token of the `CLIENT_ID` application, granted the given
+    /// scopes, owned by the user with the given id.
+    pub fn token_check(token: &str, scopes: &[&str], user_id: u64) -> Self {
+        let scopes: Vec<String> = scopes.iter().map(|scope| format!("\"{}\"", scope)).collect();
+        let body = format!("{{\"token\":\"{}\",\"scopes\":[{}],\
+                            \"created_at\":\"2016-05-01T12:00:00Z\",\
+                            \"updated_at\":\"2016-06-01T12:00:00Z\",\
+                            \"app\":{{\"client_id\":\"{}\"}},\
+                            \"user\":{{\"login\":\"octocat\",\"id\":{}}}}}",
+                           token,
+                           scopes.join(","),
+                           CLIENT_ID,
+                           user_id);
+        Reply::json(200, &body)
+    }
+
+    /// The path GitHub checks a token of the `CLIENT_ID` application at.
+    pub fn token_check_path(token: &str) -> String {
+        format!("/applications/{}/tokens/{}", CLIENT_ID, token)
+    }
+
+    /// The 404 GitHub answers with for an unknown path.
+    pub fn not_found() -> Self {
+        Reply::json(404,
+                    "{\"message\":\"Not Found\",\
+                     \"documentation_url\":\"https://developer.github.com/v3\"}")
+    }
+
+    /// A reply which misbehaves as described by `fault` instead of answering.
+    pub fn fault(fault: Fault) -> Self {
+        let mut reply = Reply::json(500, "");
+        reply.fault = Some(fault);
+        reply
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+/// A request received by the server.
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: String,
+    /// Path of the request, without its query string
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    /// Returns the value of the given header, looked up case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|&&(ref n, _)| n.to_lowercase() == name.to_lowercase())
+            .map(|&(_, ref v)| v.as_str())
+    }
+
+    /// Returns the value of the given query parameter. Values are compared undecoded.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query.as_ref().and_then(|q| {
+            q.split('&')
+                .filter_map(|pair| {
+                    let mut kv = pair.splitn(2, '=');
+                    match (kv.next(), kv.next()) {
+                        (Some(k), Some(v)) if k == name => Some(v),
+                        _ => None,
+                    }
+                })
+                .next()
+        })
+    }
+
+    /// Panic unless the request carried the given header with the given value.
+    pub fn assert_header(&self, name: &str, value: &str) {
+        match self.header(name) {
+            Some(v) if v == value => (),
+            other => {
+                panic!("expected header {}: {} on {} {}, got {:?}",
+                       name,
+                       value,
+                       self.method,
+                       self.path,
+                       other)
+            }
+        }
+    }
+
+    /// Panic if the request carried the given header.
+    pub fn assert_no_header(&self, name: &str) {
+        if let Some(v) = self.header(name) {
+            panic!("unexpected header {}: {} on {} {}",
+                   name,
+                   v,
+                   self.method,
+                   self.path)
+        }
+    }
+
+    /// Panic unless the request's body contains the given text.
+    pub fn assert_body_contains(&self, text: &str) {
+        if !self.body.contains(text) {
+            panic!("expected body of {} {} to contain {:?}, got {:?}",
+                   self.method,
+                   self.path,
+                   text,
+                   self.body)
+        }
+    }
+}
+
+struct State {
+    replies: HashMap<(String, String), Reply>,
+    requests: Vec<Request>,
+}
+
+/// Configuration of a registry of OAuth applications, see `GitHubClients`, with the default
+/// application of `default` and the named ones of `apps`.
+pub struct Apps {
+    pub default: GitHubCfg,
+    pub apps: BTreeMap<String, GitHubCfg>,
+}
+
+impl Apps {
+    /// A registry of only the default application.
+    pub fn of(default: GitHubCfg) -> Self {
+        Apps {
+            default: default,
+            apps: BTreeMap::new(),
+        }
+    }
+}
+
+impl GitHubOAuth for Apps {
+    fn github_api_url(&self) -> &str {
+        &self.default.api_url
+    }
+
+    fn github_client_id(&self) -> &str {
+        &self.default.client_id
+    }
+
+    fn github_client_secret(&self) -> &str {
+        &self.default.client_secret
+    }
+
+    fn github_web_url(&self) -> &str {
+        &self.default.web_url
+    }
+
+    fn github_http(&self) -> HttpCfg {
+        self.default.http.clone()
+    }
+}
+
+impl GitHubApps for Apps {
+    fn github_apps(&self) -> &BTreeMap<String, GitHubCfg> {
+        &self.apps
+    }
+
+    fn github_client_options(&self) -> &GitHubClientOptions {
+        &self.default.options
+    }
+}
+
+/// A fake GitHub listening on a local port. The server runs until the test process exits.
+pub struct FakeGitHub {
+    addr: SocketAddr,
+    api_prefix: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeGitHub {
+    /// Start a server laid out like github.com, with the API at the root of the server.
+    pub fn start() -> Self {
+        FakeGitHub::with_api_prefix("")
+    }
+
+    /// Start a server laid out like a GitHub Enterprise appliance, with the API under `/api/v3`
+    /// and the web frontend at the root.
+    pub fn enterprise() -> Self {
+        FakeGitHub::with_api_prefix("/api/v3")
+    }
+
+    fn with_api_prefix(prefix: &str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind fake GitHub");
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(State {
+            replies: HashMap::new(),
+            requests: vec![],
+        }));
+        let hub = FakeGitHub {
+            addr: addr,
+            api_prefix: prefix.to_string(),
+            state: state.clone(),
+        };
+        hub.web("POST", ACCESS_TOKEN_PATH, Reply::token("user:email"));
+        hub.api("GET", "/user", Reply::json(200, USER));
+        hub.api("GET", "/user/emails", Reply::json(200, EMAILS));
+        hub.api("GET", "/user/orgs", Reply::json(200, ORGS));
+        thread::Builder::new()
+            .name("fake-github".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if let Ok(stream) = stream {
+                        let state = state.clone();
+                        thread::spawn(move || serve(stream, state));
+                    }
+                }
+            })
+            .unwrap();
+        hub
+    }
+
+    /// URL of the web frontend, which serves the token exchange.
+    pub fn web_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// URL of the API.
+    pub fn api_url(&self) -> String {
+        format!("{}{}", self.web_url(), self.api_prefix)
+    }
+
+    /// Returns the configuration of an OAuth application using this server, with no proxy,
+    /// short timeouts so tests of slow responses finish quickly, and no response cache so every
+    /// call reaches the server unless a test enables it.
+    pub fn cfg(&self) -> GitHubCfg {
+        let mut cfg = GitHubCfg::default();
+        cfg.api_url = self.api_url();
+        cfg.web_url = self.web_url();
+        cfg.allow_insecure_github_url = true;
+        cfg.client_id = CLIENT_ID.to_string();
+        cfg.client_secret = CLIENT_SECRET.to_string();
+        cfg.http.proxy = None;
+        cfg.options = GitHubClientOptions::default();
+        cfg.options.connect_timeout_ms = 1_000;
+        cfg.options.read_timeout_ms = 1_000;
+        cfg.options.cache_ttl_secs = 0;
+        cfg
+    }
+
+    /// Answer requests for the given API path, relative to the API prefix, with `reply`.
+    pub fn api(&self, method: &str, path: &str, reply: Reply) {
+        let path = format!("{}{}", self.api_prefix, path);
+        self.web(method, &path, reply);
+    }
+
+    /// Answer requests for the given path, relative to the root of the server, with `reply`. A
+    /// path with a query string answers only the requests with exactly that query, ahead of the
+    /// reply for its path alone.
+    pub fn web(&self, method: &str, path: &str, reply: Reply) {
+        self.state
+            .lock()
+            .unwrap()
+            .replies
+            .insert((method.to_string(), path.to_string()), reply);
+    }
+
+    /// Returns every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<Request> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Returns the paths of every request received so far, oldest first.
+    pub fn paths(&self) -> Vec<String> {
+        self.requests().into_iter().map(|r| r.path).collect()
+    }
+
+    /// Returns the most recent request for the given path, relative to the root of the server.
+    /// Panics, listing what was received instead, if there was none.
+    pub fn assert_requested(&self, method: &str, path: &str) -> Request {
+        let requests = self.requests();
+        match requests.iter().rev().find(|r| r.method == method && r.path == path) {
+            Some(request) => request.clone(),
+            None => panic!("expected {} {}, received {:?}", method, path, self.paths()),
+        }
+    }
+
+    /// Panics if a request for the given path, relative to the root of the server, was received.
+    pub fn assert_not_requested(&self, path: &str) {
+        if self.requests().iter().any(|r| r.path == path) {
+            panic!("unexpected request for {}, received {:?}", path, self.paths())
+        }
+    }
+}
+
+fn serve(stream: TcpStream, state: Arc<Mutex<State>>) {
+    let request = match read_request(&stream) {
+        Some(request) => request,
+        None => return,
+    };
+    let reply = {
+        let mut state = state.lock().unwrap();
+        let key = (request.method.clone(), request.path.clone());
+        let target = match request.query {
+            Some(ref query) => (request.method.clone(), format!("{}?{}", request.path, query)),
+            None => key.clone(),
+        };
+        let reply = state.replies
+            .get(&target)
+            .or_else(|| state.replies.get(&key))
+            .cloned()
+            .unwrap_or(Reply::not_found());
+        state.requests.push(request);
+        reply
+    };
+    if let Some(delay) = reply.delay {
+        thread::sleep(delay);
+    }
+    let mut stream = stream;
+    let _ = match reply.fault {
+        Some(Fault::Hangup) => return,
+        Some(Fault::Garbage) => stream.write_all(b"\x00\x01\x02 not http\r\n\r\n"),
+        None => write_reply(&mut stream, &reply),
+    };
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    if reader.read_line(&mut line).unwrap_or(0) == 0 {
+        return None;
+    }
+    let mut parts = line.trim_right().split(' ');
+    let method = match parts.next() {
+        Some(method) => method.to_string(),
+        None => return None,
+    };
+    let target = match parts.next() {
+        Some(target) => target.to_string(),
+        None => return None,
+    };
+    let (path, query) = match target.find('?') {
+        Some(i) => (target[..i].to_string(), Some(target[i + 1..].to_string())),
+        None => (target, None),
+    };
+    let mut headers = vec![];
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return None;
+        }
+        let line = line.trim_right();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(i) = line.find(':') {
+            headers.push((line[..i].trim().to_string(), line[i + 1..].trim().to_string()));
+        }
+    }
+    let length = headers.iter()
+        .find(|&&(ref n, _)| n.to_lowercase() == "content-length")
+        .and_then(|&(_, ref v)| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    let mut body = String::new();
+    if reader.take(length).read_to_string(&mut body).is_err() {
+        return None;
+    }
+    Some(Request {
+        method: method,
+        path: path,
+        query: query,
+        headers: headers,
+        body: body,
+    })
+}
+
+fn write_reply(stream: &mut TcpStream, reply: &Reply) -> ::std::io::Result<()> {
+    let mut head = format!("HTTP/1.1 {} {}\r\nContent-Type: application/json; \
+                            charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+                           reply.status,
+                           reason(reply.status),
+                           reply.body.len());
+    for &(ref name, ref value) in reply.headers.iter() {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    try!(stream.write_all(head.as_bytes()));
+    try!(stream.write_all(reply.body.as_bytes()));
+    stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        304 => "Not Modified",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
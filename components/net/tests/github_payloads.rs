@@ -0,0 +1,477 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replays payloads recorded from the real GitHub API through the structs they are decoded into.
+//!
+//! GitHub adds fields and turns others into `null` without notice. Every file of
+//! `fixtures/recorded` is a sanitized recording of a payload, and each is decoded here the way
+//! the client decodes it, so a change to a struct which breaks a payload GitHub actually sends
+//! fails here rather than in production.
+//!
+//! The payloads of `REFRESHED` are recorded again from the live API, replacing their ids,
+//! emails, and the login of the token's owner, with
+//!
+//! ```bash
+//! $ HAB_GITHUB_FIXTURE_TOKEN=<token> make refresh-github-fixtures
+//! ```
+//!
+//! The token needs the `user:email` and `read:org` scopes. The other recordings need a new
+//! account, an OAuth code, or a rate limited or SAML enforcing organization to reproduce, and
+//! are sanitized by hand.
+//!
+//! Built with the `serde` feature, every recording is also decoded with serde, and both encoders
+//! must render either decoding the same.
+
+extern crate habitat_net;
+#[cfg(feature = "client")]
+extern crate hyper;
+extern crate rustc_serialize;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use habitat_net::Error;
+use habitat_net::oauth::github::{is_noreply_email, is_sso_error, org_forbidden_error,
+                                 parse_error_body, primary_verified_email, AuthErr, AuthOk,
+                                 Email, FieldError, GitHubError, Org, RateLimits, Team, User};
+#[cfg(feature = "serde")]
+use rustc_serialize::{Decodable, Encodable};
+use rustc_serialize::json::{self, Json};
+
+/// Environment variable holding the token `refresh_recorded_fixtures` records payloads with
+const REFRESH_TOKEN_ENVVAR: &'static str = "HAB_GITHUB_FIXTURE_TOKEN";
+
+/// Placeholder of the values which are replaced whole, such as node ids and access tokens
+const SANITIZED: &'static str = "SANITIZED";
+
+/// Login every recorded payload is sanitized to
+const FAKE_LOGIN: &'static str = "octocat";
+
+/// First id real ids are replaced with, in the order the payloads mention them
+const FAKE_ID_BASE: u64 = 1000001;
+
+/// Every recording, by file name.
+static RECORDED: &'static [&'static str] = &["error-bad-credentials.json",
+                                             "error-not-found.json",
+                                             "error-org-scope.json",
+                                             "error-rate-limited.json",
+                                             "error-sso.json",
+                                             "error-validation.json",
+                                             "rate-limit.json",
+                                             "token-error.json",
+                                             "token.json",
+                                             "user-emails.json",
+                                             "user-minimal.json",
+                                             "user-orgs.json",
+                                             "user-teams.json",
+                                             "user.json"];
+
+/// The recordings `refresh_recorded_fixtures` records again, by file name, with the path they
+/// are requested from and whether the request is authenticated with the token.
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+static REFRESHED: &'static [(&'static str, &'static str, bool)] =
+    &[("user.json", "/user", true),
+      ("user-emails.json", "/user/emails", true),
+      ("user-orgs.json", "/user/orgs", true),
+      ("user-teams.json", "/user/teams", true),
+      ("rate-limit.json", "/rate_limit", true),
+      ("error-bad-credentials.json", "/user", false),
+      ("error-not-found.json", "/repos/habitat-sh/no-such-repository", false)];
+
+fn recorded_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("recorded")
+}
+
+fn recorded(name: &str) -> String {
+    let mut body = String::new();
+    File::open(recorded_dir().join(name))
+        .and_then(|mut file| file.read_to_string(&mut body))
+        .expect(name);
+    body
+}
+
+fn error_body(name: &str) -> GitHubError {
+    parse_error_body(&recorded(name)).expect(name)
+}
+
+#[test]
+fn every_recording_is_replayed() {
+    let mut names: Vec<String> = fs::read_dir(recorded_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, RECORDED);
+}
+
+#[test]
+fn user_decodes() {
+    let user: User = json::decode(&recorded("user.json")).unwrap();
+    assert_eq!(user.login, FAKE_LOGIN);
+    assert_eq!(user.id, FAKE_ID_BASE);
+    assert_eq!(user.name, Some("The Octocat".to_string()));
+    assert_eq!(user.location, Some("San Francisco".to_string()));
+    assert_eq!(user.email, None);
+    assert_eq!(user.email_verified, None);
+    assert_eq!(user.avatar_url_sized(64),
+               "https://avatars.githubusercontent.com/u/1000001?v=4&s=64");
+    assert!(!user.site_admin);
+}
+
+#[test]
+fn user_with_every_optional_field_null_decodes() {
+    let user: User = json::decode(&recorded("user-minimal.json")).unwrap();
+    assert_eq!(user.login, FAKE_LOGIN);
+    assert_eq!(user.name, None);
+    assert_eq!(user.blog, None);
+    assert_eq!(user.hireable, None);
+    assert_eq!(user.public_repos, 0);
+}
+
+#[test]
+fn emails_decode() {
+    let emails: Vec<Email> = json::decode(&recorded("user-emails.json")).unwrap();
+    assert_eq!(emails.len(), 3);
+    assert_eq!(primary_verified_email(&emails).unwrap().email, "user1@example.com");
+    assert!(is_noreply_email(&emails[1].email));
+    assert!(!emails[2].verified);
+}
+
+#[test]
+fn orgs_decode() {
+    let orgs: Vec<Org> = json::decode(&recorded("user-orgs.json")).unwrap();
+    let logins: Vec<&str> = orgs.iter().map(|org| org.login.as_str()).collect();
+    assert_eq!(logins, vec!["habitat-sh", "chef"]);
+    assert_eq!(orgs[1].id, FAKE_ID_BASE + 2);
+}
+
+#[test]
+fn teams_decode_with_their_organizations() {
+    // Parent teams, permissions, and counts are dropped
+    let teams: Vec<Team> = json::decode(&recorded("user-teams.json")).unwrap();
+    let slugs: Vec<&str> = teams.iter().map(|team| team.slug.as_str()).collect();
+    assert_eq!(slugs, vec!["core-maintainers", "release"]);
+    assert_eq!(teams[0].name, "Core Maintainers");
+    assert_eq!(teams[0].id, FAKE_ID_BASE + 3);
+    let orgs: Vec<&str> = teams.iter()
+        .map(|team| team.organization.as_ref().unwrap().login.as_str())
+        .collect();
+    assert_eq!(orgs, vec!["habitat-sh", "chef"]);
+    assert_eq!(teams[1].organization.as_ref().unwrap().id, FAKE_ID_BASE + 2);
+}
+
+#[test]
+fn forbidden_membership_listings_are_told_apart() {
+    match org_forbidden_error(error_body("error-org-scope.json")) {
+        Error::OrgAccessDenied(ref message) => assert!(message.contains("read:org")),
+        e => panic!("expected the org access to be denied, got {}", e),
+    }
+    for name in &["error-sso.json", "error-rate-limited.json"] {
+        match org_forbidden_error(error_body(name)) {
+            Error::GitHubAPI(_) => (),
+            e => panic!("{} is {}", name, e),
+        }
+    }
+}
+
+#[test]
+fn rate_limits_decode() {
+    // Windows the client doesn't read, such as `graphql`, are dropped
+    let limits: RateLimits = json::decode(&recorded("rate-limit.json")).unwrap();
+    assert_eq!(limits.resources.core.limit, 5000);
+    assert_eq!(limits.resources.core.remaining, 4987);
+    assert_eq!(limits.resources.core.reset, 1476400000);
+    assert_eq!(limits.resources.search.limit, 30);
+}
+
+#[test]
+fn token_exchange_decodes() {
+    let ok: AuthOk = json::decode(&recorded("token.json")).unwrap();
+    assert_eq!(ok.access_token, SANITIZED);
+    assert_eq!(ok.token_type, "bearer");
+    assert!(ok.has_scope("user:email"));
+    assert!(ok.has_scope("read:org"));
+}
+
+#[test]
+fn token_exchange_error_decodes_as_an_error_only() {
+    // The client tells the two apart by whether the body decodes as an `AuthOk`
+    let body = recorded("token-error.json");
+    assert!(json::decode::<AuthOk>(&body).is_err());
+    let err: AuthErr = json::decode(&body).unwrap();
+    assert_eq!(err.error, "bad_verification_code");
+    assert!(!err.error_description.is_empty());
+}
+
+#[test]
+fn error_bodies_decode() {
+    for name in RECORDED.iter().filter(|name| name.starts_with("error-")) {
+        let body = error_body(name);
+        assert!(body.documentation_url.is_some(), "{} has no documentation_url", name);
+        assert!(!body.message.is_empty(), "{} has no message", name);
+    }
+    assert!(is_sso_error(&Error::GitHubAPI(error_body("error-sso.json"))));
+    assert!(!is_sso_error(&Error::GitHubAPI(error_body("error-not-found.json"))));
+}
+
+#[test]
+fn error_bodies_decode_to_their_fields() {
+    let err = error_body("error-bad-credentials.json").with_request(401, "/user");
+    assert_eq!(err,
+               GitHubError {
+                   status: 401,
+                   path: "/user".to_string(),
+                   message: "Bad credentials".to_string(),
+                   documentation_url: Some("https://developer.github.com/v3".to_string()),
+                   errors: vec![],
+               });
+    assert_eq!(err.to_string(),
+               "GitHub API error 401 on /user: Bad credentials, see \
+                https://developer.github.com/v3");
+
+    let err = error_body("error-validation.json");
+    assert_eq!(err.message, "Validation Failed");
+    assert_eq!(err.errors,
+               vec![FieldError {
+                        resource: Some("Hook".to_string()),
+                        field: None,
+                        code: "custom".to_string(),
+                        message: Some("Hook already exists on this repository".to_string()),
+                    }]);
+    assert!(err.to_string().contains("(Hook Hook already exists on this repository)"));
+
+    let err = error_body("error-rate-limited.json").with_request(403, "/user/orgs");
+    assert!(err.message.contains("rate limit exceeded"));
+    assert_eq!(err.documentation_url,
+               Some("https://developer.github.com/v3/#rate-limiting".to_string()));
+    assert!(err.errors.is_empty());
+    assert!(!err.to_string().contains('\n'));
+}
+
+/// Decodes a recording with rustc_serialize and with serde, and checks that both encoders render
+/// both decodings the same.
+#[cfg(feature = "serde")]
+fn assert_interchangeable<T>(name: &str)
+    where T: Decodable + Encodable + serde::Serialize + serde::Deserialize
+{
+    let body = recorded(name);
+    let rustc: T = json::decode(&body).expect(name);
+    let serde: T = serde_json::from_str(&body).expect(name);
+    let renderings = vec![json::encode(&rustc).unwrap(),
+                          json::encode(&serde).unwrap(),
+                          serde_json::to_string(&rustc).unwrap(),
+                          serde_json::to_string(&serde).unwrap()];
+    let trees: Vec<Json> = renderings.iter().map(|r| Json::from_str(r).unwrap()).collect();
+    for (rendering, tree) in renderings.iter().zip(trees.iter()) {
+        assert!(*tree == trees[0], "{} rendered as {}", name, rendering);
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_and_rustc_serialize_are_interchangeable() {
+    assert_interchangeable::<User>("user.json");
+    assert_interchangeable::<User>("user-minimal.json");
+    assert_interchangeable::<Vec<Email>>("user-emails.json");
+    assert_interchangeable::<Vec<Org>>("user-orgs.json");
+    assert_interchangeable::<Vec<Team>>("user-teams.json");
+    assert_interchangeable::<RateLimits>("rate-limit.json");
+    assert_interchangeable::<AuthOk>("token.json");
+    assert_interchangeable::<AuthErr>("token-error.json");
+}
+
+#[test]
+fn sanitizer_replaces_ids_emails_and_the_login() {
+    let raw = r#"{
+        "avatar_url": "https://avatars.githubusercontent.com/u/4242?v=4",
+        "id": 4242,
+        "login": "jdoe",
+        "node_id": "MDQ6VXNlcjQyNDI=",
+        "orgs": [{"id": 7, "login": "jdoe-labs"}],
+        "emails": ["jane@doe.io", "4242+jdoe@users.noreply.github.com", "jane@doe.io"],
+        "url": "https://api.github.com/users/jdoe"
+    }"#;
+    let mut sanitizer = Sanitizer::new(Some("jdoe"));
+    let sanitized = sanitizer.sanitize(Json::from_str(raw).unwrap());
+    // Another account whose login starts with the owner's keeps its login
+    let expected = r#"{
+        "avatar_url": "https://avatars.githubusercontent.com/u/1000001?v=4",
+        "id": 1000001,
+        "login": "octocat",
+        "node_id": "SANITIZED",
+        "orgs": [{"id": 1000002, "login": "jdoe-labs"}],
+        "emails": ["user1@example.com", "1000001+octocat@users.noreply.github.com",
+                   "user1@example.com"],
+        "url": "https://api.github.com/users/octocat"
+    }"#;
+    assert_eq!(sanitized, Json::from_str(expected).unwrap());
+}
+
+#[cfg(feature = "client")]
+#[test]
+#[ignore]
+fn refresh_recorded_fixtures() {
+    let token = match std::env::var(REFRESH_TOKEN_ENVVAR) {
+        Ok(token) => token,
+        Err(_) => panic!("set {} to a token to record the payloads with", REFRESH_TOKEN_ENVVAR),
+    };
+    let user = Json::from_str(&live::get("/user", Some(&token))).unwrap();
+    let login = user.find("login").and_then(|login| login.as_string()).map(|l| l.to_string());
+    let mut sanitizer = Sanitizer::new(login.as_ref().map(|login| login.as_str()));
+    for &(name, path, authenticated) in REFRESHED {
+        let token = if authenticated {
+            Some(token.as_str())
+        } else {
+            None
+        };
+        let payload = Json::from_str(&live::get(path, token)).expect(path);
+        let mut file = File::create(recorded_dir().join(name)).unwrap();
+        write!(file, "{}\n", sanitizer.sanitize(payload).pretty()).unwrap();
+    }
+}
+
+#[cfg(feature = "client")]
+mod live {
+    use std::io::Read;
+
+    use habitat_net::config::HttpCfg;
+    use habitat_net::http;
+    use hyper::Url;
+    use hyper::header::{Authorization, Bearer, UserAgent};
+
+    const API_URL: &'static str = "https://api.github.com";
+
+    /// Returns the body GitHub answers a `GET` of the given path with, whatever its status.
+    pub fn get(path: &str, token: Option<&str>) -> String {
+        let url = Url::parse(&format!("{}{}", API_URL, path)).unwrap();
+        let client = http::client(&HttpCfg::default(), &url, 5000, 10000).unwrap();
+        let mut req = client.get(url).header(UserAgent("Habitat-Builder".to_string()));
+        if let Some(token) = token {
+            req = req.header(Authorization(Bearer { token: token.to_string() }));
+        }
+        let mut body = String::new();
+        req.send().unwrap().read_to_string(&mut body).unwrap();
+        body
+    }
+}
+
+/// Replaces what identifies an account in payloads. Ids become `FAKE_ID_BASE` onwards and
+/// emails `user1@example.com` onwards, both in the order they are first met, so a payload
+/// recorded again only differs where GitHub changed it. The login of the token's owner becomes
+/// `FAKE_LOGIN` wherever it appears, and noreply emails keep their form since they are built
+/// of the id and login.
+struct Sanitizer {
+    login: Option<String>,
+    ids: BTreeMap<u64, u64>,
+    emails: BTreeMap<String, String>,
+}
+
+impl Sanitizer {
+    fn new(login: Option<&str>) -> Self {
+        Sanitizer {
+            login: login.map(|login| login.to_string()),
+            ids: BTreeMap::new(),
+            emails: BTreeMap::new(),
+        }
+    }
+
+    fn sanitize(&mut self, payload: Json) -> Json {
+        // Ids are mentioned in strings, such as avatar URLs, before their own field
+        self.collect_ids(&payload);
+        self.rewrite(payload)
+    }
+
+    fn collect_ids(&mut self, payload: &Json) {
+        match *payload {
+            Json::Object(ref object) => {
+                for (key, value) in object.iter() {
+                    match (key.as_str(), value) {
+                        ("id", &Json::U64(id)) => {
+                            let next = FAKE_ID_BASE + self.ids.len() as u64;
+                            self.ids.entry(id).or_insert(next);
+                        }
+                        _ => self.collect_ids(value),
+                    }
+                }
+            }
+            Json::Array(ref array) => {
+                for value in array.iter() {
+                    self.collect_ids(value);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn rewrite(&mut self, payload: Json) -> Json {
+        match payload {
+            Json::Object(object) => {
+                let mut sanitized = BTreeMap::new();
+                for (key, value) in object.into_iter() {
+                    let value = match (key.as_str(), value) {
+                        ("id", Json::U64(id)) => Json::U64(self.ids[&id]),
+                        ("node_id", _) |
+                        ("access_token", _) => Json::String(SANITIZED.to_string()),
+                        (_, value) => self.rewrite(value),
+                    };
+                    sanitized.insert(key, value);
+                }
+                Json::Object(sanitized)
+            }
+            Json::Array(array) => Json::Array(array.into_iter().map(|v| self.rewrite(v)).collect()),
+            Json::String(s) => Json::String(self.rewrite_string(s)),
+            other => other,
+        }
+    }
+
+    fn rewrite_string(&mut self, s: String) -> String {
+        let mut s = s;
+        for (real, fake) in self.ids.iter() {
+            s = replace_word(&s, &real.to_string(), &fake.to_string(), |c| c.is_digit(10));
+        }
+        if let Some(ref login) = self.login {
+            s = replace_word(&s, login, FAKE_LOGIN, |c| c.is_alphanumeric() || c == '-');
+        }
+        let is_email = s.contains('@') && !s.contains('/') && !s.contains(' ');
+        if is_email && !is_noreply_email(&s) {
+            let next = format!("user{}@example.com", self.emails.len() + 1);
+            s = self.emails.entry(s).or_insert(next).clone();
+        }
+        s
+    }
+}
+
+/// Replaces the occurrences of `from` in `s` which aren't part of a longer word, a word being a
+/// run of the chars `in_word` accepts.
+fn replace_word<F>(s: &str, from: &str, to: &str, in_word: F) -> String
+    where F: Fn(char) -> bool
+{
+    let mut replaced = String::new();
+    let mut rest = s;
+    while let Some(at) = rest.find(from) {
+        replaced.push_str(&rest[..at]);
+        let before = replaced.chars().next_back().map_or(false, |c| in_word(c));
+        let after = rest[at + from.len()..].chars().next().map_or(false, |c| in_word(c));
+        replaced.push_str(if before || after { from } else { to });
+        rest = &rest[at + from.len()..];
+    }
+    replaced.push_str(rest);
+    replaced
+}
@@ -0,0 +1,168 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Smoke test of the GitHub client against the real GitHub, or a GitHub Enterprise, with a
+//! token and the OAuth application it was issued to:
+//!
+//! ```bash
+//! $ HAB_GH_SMOKE_TOKEN=<token> HAB_GH_SMOKE_CLIENT_ID=<id> HAB_GH_SMOKE_CLIENT_SECRET=<secret> \
+//!     make github-smoke
+//! ```
+//!
+//! `HAB_GH_SMOKE_API_URL` and `HAB_GH_SMOKE_WEB_URL` point it at an Enterprise instead. The
+//! account behind the token is whatever it is, so only what holds for every account is
+//! asserted. The summary printed leaves out emails, and everything printed, panic messages
+//! included, is redacted first. Without the token and the credentials the test does nothing.
+
+#![cfg(feature = "client")]
+
+extern crate habitat_net;
+
+use std::env;
+use std::fmt::Display;
+
+use habitat_net::oauth::github::{format_scopes, parse_scopes, GitHubClient};
+use habitat_net::redact::{redact_text, REDACTED};
+
+const TOKEN_ENVVAR: &'static str = "HAB_GH_SMOKE_TOKEN";
+const CLIENT_ID_ENVVAR: &'static str = "HAB_GH_SMOKE_CLIENT_ID";
+const CLIENT_SECRET_ENVVAR: &'static str = "HAB_GH_SMOKE_CLIENT_SECRET";
+const API_URL_ENVVAR: &'static str = "HAB_GH_SMOKE_API_URL";
+const WEB_URL_ENVVAR: &'static str = "HAB_GH_SMOKE_WEB_URL";
+
+/// The token and the credentials, which are never printed.
+struct Secrets {
+    token: String,
+    client_secret: String,
+}
+
+impl Secrets {
+    /// Returns the text with the secrets, and whatever else `redact_text` finds, redacted.
+    fn scrub(&self, text: &str) -> String {
+        let mut scrubbed = redact_text(text).into_owned();
+        for secret in [&self.token, &self.client_secret].iter() {
+            scrubbed = scrubbed.replace(secret.as_str(), REDACTED);
+        }
+        scrubbed
+    }
+
+    fn ok<T, E: Display>(&self, result: Result<T, E>, what: &str) -> T {
+        match result {
+            Ok(value) => value,
+            Err(e) => panic!("{} failed: {}", what, self.scrub(&e.to_string())),
+        }
+    }
+}
+
+/// Returns the value of every variable of `names`, or the first one which isn't set.
+fn vars(names: &[&'static str]) -> Result<Vec<String>, &'static str> {
+    let mut values = vec![];
+    for name in names.iter() {
+        match env::var(name) {
+            Ok(ref value) if !value.trim().is_empty() => values.push(value.trim().to_string()),
+            _ => return Err(*name),
+        }
+    }
+    Ok(values)
+}
+
+#[test]
+#[ignore]
+fn github_smoke() {
+    let values = match vars(&[TOKEN_ENVVAR, CLIENT_ID_ENVVAR, CLIENT_SECRET_ENVVAR]) {
+        Ok(values) => values,
+        Err(name) => {
+            println!("skipping the GitHub smoke test, {} isn't set", name);
+            return;
+        }
+    };
+    let client_id = values[1].clone();
+    let secrets = Secrets {
+        token: values[0].clone(),
+        client_secret: values[2].clone(),
+    };
+    let mut builder = GitHubClient::builder()
+        .client_id(client_id.clone())
+        .client_secret(secrets.client_secret.clone());
+    if let Ok(url) = vars(&[API_URL_ENVVAR]) {
+        builder = builder.api_url(url[0].clone());
+    }
+    if let Ok(url) = vars(&[WEB_URL_ENVVAR]) {
+        builder = builder.web_url(url[0].clone());
+    }
+    let client = secrets.ok(builder.build(), "configuring");
+    let token = secrets.token.as_str();
+
+    let user = secrets.ok(client.user(token), "user");
+    assert!(!user.login.is_empty(), "the user has no login");
+    assert!(user.id > 0, "the user's id is {}", user.id);
+
+    let emails = secrets.ok(client.emails(token), "emails");
+    assert!(emails.iter().all(|e| e.email.contains('@')), "an email has no @");
+    assert!(emails.iter().filter(|e| e.primary).count() <= 1,
+            "more than one email is primary");
+
+    let orgs = secrets.ok(client.orgs(token), "orgs");
+    for org in orgs.iter() {
+        assert!(!org.login.is_empty(), "an org has no login");
+        assert!(org.id > 0, "org {} has id {}", org.login, org.id);
+    }
+
+    let limits = secrets.ok(client.rate_limit(token), "rate_limit");
+    if let Some(ref limits) = limits {
+        for window in [&limits.resources.core, &limits.resources.search].iter() {
+            assert!(window.limit > 0, "a rate limit window allows no requests");
+            assert!(window.remaining <= window.limit,
+                    "{} of {} requests remain",
+                    window.remaining,
+                    window.limit);
+            assert!(window.reset > 0, "a rate limit window never resets");
+        }
+    }
+
+    let record = match secrets.ok(client.check_token(token), "check_token") {
+        Some(record) => record,
+        None => panic!("GitHub doesn't know the token, or it belongs to another application"),
+    };
+    assert_eq!(record.client_id, client_id);
+    assert_eq!(record.user_id, Some(user.id));
+    assert!(!record.scopes.is_empty(), "the token has no scopes");
+    for scope in record.scopes.iter() {
+        assert!(!scope.is_empty() && !scope.contains(|c: char| c.is_whitespace() || c == ','),
+                "scope {:?} doesn't parse",
+                scope);
+    }
+    assert_eq!(parse_scopes(&format_scopes(&record.scopes)), record.scopes);
+
+    let rate = match limits {
+        Some(limits) => {
+            format!("{}/{} until {}",
+                    limits.resources.core.remaining,
+                    limits.resources.core.limit,
+                    limits.resources.core.reset)
+        }
+        None => "disabled".to_string(),
+    };
+    let summary = format!("GitHub smoke test passed against {}\n  user: {} ({})\n  emails: {}, \
+                           {} primary\n  orgs: {}\n  rate limit: {}\n  scopes: {}",
+                          client.url(),
+                          user.login,
+                          user.id,
+                          emails.len(),
+                          emails.iter().filter(|e| e.primary).count(),
+                          orgs.iter().map(|org| org.login.as_str()).collect::<Vec<_>>().join(", "),
+                          rate,
+                          format_scopes(&record.scopes));
+    println!("{}", secrets.scrub(&summary));
+}
@@ -0,0 +1,147 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replays payloads recorded from version 3 of the GitLab API through the structs they are
+//! decoded into, and the GitHub payloads those are converted to.
+//!
+//! Every file of `fixtures/gitlab` is a recording of a payload of a GitLab 8 instance, sanitized
+//! by hand: ids, emails, and the username are replaced, and so are the tokens, with `SANITIZED`.
+
+extern crate habitat_builder_protocol as protocol;
+extern crate habitat_net;
+extern crate rustc_serialize;
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::PathBuf;
+
+use habitat_net::oauth::github::{primary_verified_email, AuthErr, User};
+use habitat_net::oauth::gitlab::{emails_of, GitLabEmail, GitLabToken, GitLabTokenErr,
+                                 GitLabTokenInfo, GitLabUser};
+use protocol::sessionsrv;
+use rustc_serialize::json;
+
+/// Every recording, by file name.
+static RECORDED: &'static [&'static str] = &["token-error.json",
+                                             "token-info.json",
+                                             "token.json",
+                                             "user-emails.json",
+                                             "user-minimal.json",
+                                             "user.json"];
+
+fn recorded_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join("gitlab")
+}
+
+fn recorded(name: &str) -> String {
+    let mut body = String::new();
+    File::open(recorded_dir().join(name))
+        .and_then(|mut file| file.read_to_string(&mut body))
+        .expect(name);
+    body
+}
+
+#[test]
+fn every_recording_is_replayed() {
+    let mut names: Vec<String> = fs::read_dir(recorded_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, RECORDED);
+}
+
+#[test]
+fn users_convert_to_github_users() {
+    let user: GitLabUser = json::decode(&recorded("user.json")).unwrap();
+    assert_eq!(user.state, "active");
+    let user = User::from(user);
+    assert_eq!(user.login, "jgitlab");
+    assert_eq!(user.id, 1000001);
+    assert_eq!(user.name, Some("Jean Gitlab".to_string()));
+    assert_eq!(user.email, Some("jgitlab@example.com".to_string()));
+    assert_eq!(user.email_verified, Some(true));
+    assert_eq!(user.company, Some("Example Inc.".to_string()));
+    // GitLab sends the fields a user left blank as empty strings
+    assert_eq!(user.blog, None);
+    assert_eq!(user.bio, None);
+    assert_eq!(user.provider_host(), "gitlab.example.com");
+    assert_eq!(user.avatar_url_sized(64),
+               "https://secure.gravatar.com/avatar/205e460b479e2e5b48aec07710c08d50?\
+                d=identicon&s=64");
+    assert!(!user.site_admin);
+}
+
+#[test]
+fn users_with_missing_fields_convert() {
+    let user: GitLabUser = json::decode(&recorded("user-minimal.json")).unwrap();
+    assert_eq!(user.is_admin, None);
+    let user = User::from(user);
+    assert_eq!(user.avatar_url, "");
+    assert_eq!(user.location, None);
+    assert_eq!(user.email_verified, Some(false));
+}
+
+#[test]
+fn emails_put_the_confirmed_primary_first() {
+    let user: GitLabUser = json::decode(&recorded("user.json")).unwrap();
+    let secondary: Vec<GitLabEmail> = json::decode(&recorded("user-emails.json")).unwrap();
+    let emails = emails_of(&user, secondary.clone());
+    assert_eq!(emails.len(), 3);
+    assert_eq!(primary_verified_email(&emails).unwrap().email, "jgitlab@example.com");
+    assert!(emails[1..].iter().all(|e| !e.primary && !e.verified));
+    // An unconfirmed primary address is never verified
+    let unconfirmed: GitLabUser = json::decode(&recorded("user-minimal.json")).unwrap();
+    assert!(primary_verified_email(&emails_of(&unconfirmed, secondary)).is_none());
+}
+
+#[test]
+fn token_exchange_decodes() {
+    let token: GitLabToken = json::decode(&recorded("token.json")).unwrap();
+    assert_eq!(token.access_token, "SANITIZED");
+    assert_eq!(token.token_type, "bearer");
+    assert_eq!(token.scopes(), vec!["api", "read_user"]);
+    assert_eq!(token.created_at, Some(1476432231));
+}
+
+#[test]
+fn token_exchange_error_decodes_as_an_error_only() {
+    let body = recorded("token-error.json");
+    assert!(json::decode::<GitLabToken>(&body).is_err());
+    let err = AuthErr::from(json::decode::<GitLabTokenErr>(&body).unwrap());
+    assert_eq!(err.error, "invalid_grant");
+    assert!(err.error_description.contains("redirection URI"));
+    assert_eq!(err.error_uri, "");
+}
+
+#[test]
+fn token_info_decodes() {
+    let info: GitLabTokenInfo = json::decode(&recorded("token-info.json")).unwrap();
+    assert_eq!(info.resource_owner_id, Some(1000001));
+    assert_eq!(info.scopes, vec!["api", "read_user"]);
+    assert_eq!(info.expires_in_seconds, None);
+    assert_eq!(info.application.unwrap().uid, "6f1c2d9e0a8b4c7d");
+}
+
+#[test]
+fn accounts_are_mapped_like_github_ones() {
+    let user: GitLabUser = json::decode(&recorded("user.json")).unwrap();
+    let account = sessionsrv::Account::from(User::from(user));
+    assert_eq!(account.get_name(), "jgitlab");
+    assert_eq!(account.get_extern_id(), 1000001);
+    assert_eq!(account.get_display_name(), "Jean Gitlab");
+    assert_eq!(account.get_email(), "jgitlab@example.com");
+    assert_eq!(account.get_provider_host(), "gitlab.example.com");
+    assert!(account.get_avatar_url().ends_with("&s=120"));
+}
@@ -0,0 +1,161 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Feeds a listing of several megabytes through both list decoders, checking they decode it
+//! the same and that the streaming one does so in flat memory.
+//!
+//! Memory is measured by counting what the global allocator hands out, so this binary holds a
+//! single test: another running beside it would be counted too.
+
+extern crate habitat_net;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use habitat_net::oauth::github::Org;
+use habitat_net::oauth::list::{BufferedList, ListDecoder, StreamingList};
+
+/// Organizations in the listing, which is about 7MB long
+const ITEMS: u64 = 50_000;
+
+/// Most the streaming decoder may allocate at once, whatever the length of the listing
+const STREAMING_PEAK_BOUND: usize = 256 * 1024;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, counting the bytes allocated and the most allocated at once.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns what `f` returns, and the most it allocated at once over what was already allocated.
+fn peak_of<T, F: FnOnce() -> T>(f: F) -> (T, usize) {
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+    let value = f();
+    (value, PEAK.load(Ordering::SeqCst) - before)
+}
+
+/// A reader of a JSON array of organizations, written as it is read so the listing itself
+/// takes no memory.
+struct SyntheticListing {
+    next: u64,
+    count: u64,
+    pending: Vec<u8>,
+    pos: usize,
+}
+
+impl SyntheticListing {
+    fn new(count: u64) -> Self {
+        let mut pending = Vec::with_capacity(512);
+        pending.push(b'[');
+        SyntheticListing {
+            next: 0,
+            count: count,
+            pending: pending,
+            pos: 0,
+        }
+    }
+
+    fn refill(&mut self) {
+        self.pending.clear();
+        self.pos = 0;
+        let id = self.next;
+        if id > self.count {
+            return;
+        }
+        self.next += 1;
+        if id == self.count {
+            self.pending.extend_from_slice(b"\n]\n");
+            return;
+        }
+        let separator = if id == 0 { "" } else { "," };
+        write!(self.pending,
+               "{}\n  {{\"login\": \"org-{}\", \"id\": {}, \
+                \"node_id\": \"MDEyOk9yZ2FuaXphdGlvbjE=\", \
+                \"description\": \"Organization \\u00e9 {}\", \
+                \"url\": \"https://api.github.com/orgs/org-{}\", \"public\": {}}}",
+               separator,
+               id,
+               id + 1,
+               id,
+               id,
+               id % 2 == 0)
+            .unwrap();
+    }
+}
+
+impl Read for SyntheticListing {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.pending.len() {
+            self.refill();
+        }
+        let len = buf.len().min(self.pending.len() - self.pos);
+        buf[..len].copy_from_slice(&self.pending[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+fn last_id<D: ListDecoder>(decoder: D) -> (u64, u64) {
+    decoder.decode_list::<Org, _>(SyntheticListing::new(ITEMS))
+        .fold((0, 0), |(count, _), org| (count + 1, org.unwrap().id))
+}
+
+#[test]
+fn large_listings_decode_the_same_and_stream_in_flat_memory() {
+    let listing_len = io::copy(&mut SyntheticListing::new(ITEMS), &mut io::sink()).unwrap();
+    assert!(listing_len > 5_000_000, "the listing is only {} bytes", listing_len);
+
+    let streamed = StreamingList.decode_list::<Org, _>(SyntheticListing::new(ITEMS));
+    let buffered = BufferedList.decode_list::<Org, _>(SyntheticListing::new(ITEMS));
+    let mut count = 0;
+    for (streamed, buffered) in streamed.zip(buffered) {
+        let (streamed, buffered) = (streamed.unwrap(), buffered.unwrap());
+        assert_eq!((&streamed.login, streamed.id), (&buffered.login, buffered.id));
+        count += 1;
+    }
+    assert_eq!(count, ITEMS);
+
+    let (streamed, streamed_peak) = peak_of(|| last_id(StreamingList));
+    let (buffered, buffered_peak) = peak_of(|| last_id(BufferedList));
+    assert_eq!(streamed, (ITEMS, ITEMS));
+    assert_eq!(buffered, streamed);
+    assert!(streamed_peak < STREAMING_PEAK_BOUND,
+            "streaming allocated {} bytes at once",
+            streamed_peak);
+    // Reading the listing whole takes at least its length
+    assert!(buffered_peak as u64 > listing_len,
+            "buffering allocated only {} bytes at once",
+            buffered_peak);
+}
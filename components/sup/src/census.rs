@@ -85,8 +85,10 @@ impl CensusEntry {
         CensusEntry {
             id: Uuid::new_v4(),
             member_id: member_id,
-            hostname: util::sys::hostname().unwrap_or(String::from("unknown")),
-            ip: util::sys::ip().map(|s| s.to_string()).unwrap_or("127.0.0.1".to_string()),
+            hostname: util::sys::get()
+                .map(|sys| sys.hostname.clone())
+                .unwrap_or(String::from("unknown")),
+            ip: util::sys::get().map(|sys| sys.ip.to_string()).unwrap_or("127.0.0.1".to_string()),
             suitability: 0,
             port: None,
             exposes: None,
@@ -112,6 +114,24 @@ impl CensusEntry {
         self.needs_write.is_some()
     }
 
+    /// Set the IP address we advertise.
+    pub fn ip(&mut self, ip: String) {
+        if self.ip != ip {
+            self.ip = ip;
+            self.incarnation.increment();
+            self.needs_write = Some(true);
+        }
+    }
+
+    /// Set the host name we advertise.
+    pub fn hostname(&mut self, hostname: String) {
+        if self.hostname != hostname {
+            self.hostname = hostname;
+            self.incarnation.increment();
+            self.needs_write = Some(true);
+        }
+    }
+
     /// Set our suitability number. This is an arbitrary determination of our 'suitability' to a
     /// task; most likely, being the leader in an election.
     pub fn suitability(&mut self, suitability: u64) {
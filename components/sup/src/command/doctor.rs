@@ -0,0 +1,43 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reports whether this host is fit to run services. Actually the `doctor` command.
+//!
+//! # Examples
+//!
+//! ```bash
+//! $ hab-sup doctor --url https://depot.example.com/v1/depot
+//! ```
+//!
+//! Will check the host's facts, that the depot and GitHub are reached, and the GitHub settings.
+
+use config::Config;
+use diagnostics::{self, DiagnosticsCfg};
+use error::{Error, Result};
+
+static LOGKEY: &'static str = "DR";
+
+/// Print the diagnostics of this host.
+///
+/// # Failures
+///
+/// * If any of the checks failed, after the report is printed
+pub fn run(config: &Config) -> Result<()> {
+    let report = diagnostics::run(&DiagnosticsCfg::from_env(config.url()));
+    println!("{}", report);
+    match report.failures().len() {
+        0 => Ok(()),
+        n => Err(sup_error!(Error::DiagnosticsFailed(n))),
+    }
+}
@@ -20,4 +20,5 @@
 
 pub mod start;
 pub mod configure;
+pub mod doctor;
 pub mod shell;
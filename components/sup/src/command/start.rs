@@ -63,14 +63,16 @@ use ansi_term::Colour::Yellow;
 use common::command::package::install;
 use depot_client::Client;
 use hcore::crypto::default_cache_key_path;
-use hcore::fs::{cache_artifact_path, FS_ROOT_PATH};
+use hcore::fs::{cache_artifact_path, svc_path, FS_ROOT_PATH};
 use hcore::package::PackageIdent;
+use hcore::util::sys;
 
 use {PRODUCT, VERSION};
 use error::Result;
 use config::{Config, UpdateStrategy};
 use package::Package;
 use topology::{self, Topology};
+use util;
 
 static LOGKEY: &'static str = "CS";
 
@@ -83,6 +85,9 @@ static LOGKEY: &'static str = "CS";
 /// * Fails if the `run` method for the topology fails
 /// * Fails if an unknown topology was specified on the command line
 pub fn package(config: &Config) -> Result<()> {
+    if config.doctor() {
+        util::sys::report_connectivity(config.url());
+    }
     match Package::load(config.package(), None) {
         Ok(mut package) => {
             let update_strategy = config.update_strategy();
@@ -157,6 +162,12 @@ fn start_package(package: Package, config: &Config) -> Result<()> {
     let run_path = try!(package.run_path());
     debug!("Setting the PATH to {}", run_path);
     env::set_var("PATH", &run_path);
+    util::sys::warn_restricted_mounts(&[sys::data_path(), svc_path(&package.name)]);
+    debug!("{}", sys::explain_ip_selection());
+    try!(util::sys::init(util::sys::SysOptions::default()));
+    util::sys::warn_ip_conflict();
+    util::sys::warn_loopback_resolution();
+    util::sys::log_startup_report();
     match *config.topology() {
         Topology::Standalone => topology::standalone::run(package, config),
         Topology::Leader => topology::leader::run(package, config),
@@ -34,6 +34,7 @@ static LOGKEY: &'static str = "CFG";
 /// An enum with the various CLI commands. Used to keep track of what command was called.
 pub enum Command {
     Config,
+    Doctor,
     Start,
     ShellBash,
     ShellSh,
@@ -65,6 +66,7 @@ impl FromStr for Command {
     fn from_str(s: &str) -> Result<Command, SupError> {
         match s {
             "config" => Ok(Command::Config),
+            "doctor" => Ok(Command::Doctor),
             "bash" => Ok(Command::ShellBash),
             "sh" => Ok(Command::ShellSh),
             "start" => Ok(Command::Start),
@@ -111,6 +113,7 @@ pub struct Config {
     version_number: u64,
     organization: Option<String>,
     ring: Option<String>,
+    doctor: bool,
 }
 
 impl Config {
@@ -411,6 +414,17 @@ impl Config {
     pub fn ring(&self) -> &Option<String> {
         &self.ring
     }
+
+    /// Set whether to report on the connectivity of the host at startup
+    pub fn set_doctor(&mut self, doctor: bool) -> &mut Config {
+        self.doctor = doctor;
+        self
+    }
+
+    /// Return whether to report on the connectivity of the host at startup
+    pub fn doctor(&self) -> bool {
+        self.doctor
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,699 @@
+// Copyright (c) 2016 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Everything support asks about a host the supervisor misbehaves on, in one report.
+//!
+//! `run` puts together the startup report of the host's facts, whether the host reaches the
+//! depot and GitHub, and whether the GitHub settings make sense. Each check of the report has
+//! its status, how long it took, and what to do about it when it didn't pass. Everything `run`
+//! learns about the host goes through a `Probes`, so `run_with` can be given fakes.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use hcore::env as henv;
+use hcore::util::render::{self, Render};
+use hcore::util::sys;
+use time;
+use toml;
+use url::Url;
+
+use error::Result;
+use util::sys::{startup_report, CheckStatus, StartupReport, Sys, AUTH_TOKEN_ENVVAR};
+
+/// Environment variable holding the URL of the GitHub API the depot authenticates against
+pub const GITHUB_URL_ENVVAR: &'static str = "HAB_GITHUB_URL";
+
+/// URL of the GitHub API checked when `GITHUB_URL_ENVVAR` isn't set
+pub const DEFAULT_GITHUB_URL: &'static str = "https://api.github.com";
+
+/// Host of the API of github.com, which unlike GitHub Enterprise serves it at the root
+const GITHUB_API_HOST: &'static str = "api.github.com";
+
+/// Path GitHub Enterprise serves its API under
+const GITHUB_ENTERPRISE_API_PATH: &'static str = "/api/v3";
+
+/// Milliseconds the connectivity checks get to finish, all together
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+/// What to do about a check of the startup report which didn't pass, by the name of the check.
+static STARTUP_HINTS: &'static [(&'static str, &'static str)] =
+    &[("hostname_resolution",
+       "map the hostname to the address of a network interface in /etc/hosts"),
+      ("primary_interface",
+       "pass --listen-peer with the address of the interface peers on other hosts reach"),
+      ("nofile_limit",
+       "raise the open files limit with ulimit -n, or LimitNOFILE in the systemd unit"),
+      ("tmp_mount",
+       "point TMPDIR at a directory on a writable filesystem mounted without noexec"),
+      ("disk_space", "free space on the filesystem holding /hab")];
+
+/// What `run` checks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticsCfg {
+    /// URL of the depot packages are installed from
+    pub depot_url: String,
+    /// URL of the GitHub API the depot authenticates against, or `None` if GitHub isn't used
+    pub github_url: Option<String>,
+    /// Token requests to GitHub are authenticated with
+    pub auth_token: Option<String>,
+    /// Time the connectivity checks get to finish, all together
+    pub timeout: Duration,
+}
+
+impl DiagnosticsCfg {
+    /// Returns the checks of the given depot, and of GitHub when `GITHUB_URL_ENVVAR` names its
+    /// API or `AUTH_TOKEN_ENVVAR` is set.
+    pub fn from_env(depot_url: &str) -> Self {
+        let auth_token = henv::var(AUTH_TOKEN_ENVVAR).ok();
+        let github_url = match henv::var(GITHUB_URL_ENVVAR) {
+            Ok(url) => Some(url),
+            Err(_) if auth_token.is_some() => Some(DEFAULT_GITHUB_URL.to_string()),
+            Err(_) => None,
+        };
+        DiagnosticsCfg {
+            depot_url: depot_url.to_string(),
+            github_url: github_url,
+            auth_token: auth_token,
+            timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
+        }
+    }
+}
+
+/// What the diagnostics learn about the host.
+pub trait Probes {
+    /// Returns the startup report of the host's facts.
+    fn startup_report(&self) -> Result<StartupReport>;
+
+    /// Returns whether the host reaches each of the targets, see `sys::connectivity_check`.
+    fn connectivity(&self, targets: &[sys::ConnTarget], timeout: Duration) -> Vec<sys::ConnResult>;
+}
+
+/// Probes the host the supervisor runs on.
+pub struct HostProbes;
+
+impl Probes for HostProbes {
+    fn startup_report(&self) -> Result<StartupReport> {
+        let sys = try!(Sys::from_snapshot(&sys::snapshot()));
+        Ok(startup_report(&sys))
+    }
+
+    fn connectivity(&self, targets: &[sys::ConnTarget], timeout: Duration) -> Vec<sys::ConnResult> {
+        sys::connectivity_check(targets, timeout)
+    }
+}
+
+/// What one check of the diagnostics found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Name of the check, prefixed with the part of the diagnostics it belongs to, such as
+    /// `sys.nofile_limit`
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// Time the check took, or `None` for checks which only look at facts detected before
+    pub duration: Option<Duration>,
+    /// What to do about a check which didn't pass
+    pub hint: Option<String>,
+}
+
+impl Diagnostic {
+    fn new(name: &str, status: CheckStatus, message: String) -> Self {
+        Diagnostic {
+            name: name.to_string(),
+            status: status,
+            message: message,
+            duration: None,
+            hint: None,
+        }
+    }
+}
+
+/// The facts of the host with what each check of the diagnostics found. Its text, which is also
+/// its `Display`, lines the names up so the statuses, durations, and messages form columns, and
+/// puts the hint of a check which didn't pass on the line below it. Its table has a `facts`
+/// table and a `checks` array, in the order the checks were run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticsReport {
+    /// When the diagnostics began
+    pub started_at: time::Timespec,
+    /// Time the diagnostics took, all together
+    pub duration: Duration,
+    pub facts: Vec<(&'static str, String)>,
+    pub checks: Vec<Diagnostic>,
+}
+
+impl DiagnosticsReport {
+    /// Returns the worst status of the checks.
+    pub fn status(&self) -> CheckStatus {
+        self.checks.iter().map(|check| check.status).max().unwrap_or(CheckStatus::Ok)
+    }
+
+    /// Returns the checks which failed.
+    pub fn failures(&self) -> Vec<&Diagnostic> {
+        self.checks.iter().filter(|check| check.status == CheckStatus::Fail).collect()
+    }
+}
+
+impl Render for DiagnosticsReport {
+    fn to_text(&self, width: usize) -> String {
+        let column = self.facts
+            .iter()
+            .map(|&(name, _)| name.len())
+            .chain(self.checks.iter().map(|check| check.name.len()))
+            .max()
+            .unwrap_or(0);
+        // Past the name, status, and duration columns
+        let message_column = column + 19;
+        let heading = format!("Diagnostics: {}, run at {} in {}ms",
+                              self.status().as_str(),
+                              sys::rfc3339_utc(self.started_at),
+                              render::millis(self.duration));
+        let mut lines = vec![render::wrap(&heading, 2, width)];
+        for &(name, ref value) in self.facts.iter() {
+            let line = format!("  {:column$}  {}", name, value, column = column);
+            lines.push(render::wrap(&line, column + 4, width));
+        }
+        for check in self.checks.iter() {
+            let duration = match check.duration {
+                Some(duration) => format!("{}ms", render::millis(duration)),
+                None => "-".to_string(),
+            };
+            let line = format!("  {:column$}  {:4}  {:>7}  {}",
+                               check.name,
+                               check.status.as_str(),
+                               duration,
+                               check.message,
+                               column = column);
+            lines.push(render::wrap(&line, message_column, width));
+            if let Some(ref hint) = check.hint {
+                // Lined up with the messages
+                let line = format!("{:indent$}hint: {}", "", hint, indent = message_column);
+                lines.push(render::wrap(&line, message_column + 6, width));
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn to_toml(&self) -> toml::Table {
+        let facts = self.facts
+            .iter()
+            .map(|&(name, ref value)| (name.to_string(), toml::Value::String(value.clone())))
+            .collect();
+        let checks = self.checks
+            .iter()
+            .map(|check| {
+                let mut table = toml::Table::new();
+                table.insert("name".to_string(), toml::Value::String(check.name.clone()));
+                table.insert("status".to_string(),
+                             toml::Value::String(check.status.as_str().to_string()));
+                table.insert("message".to_string(), toml::Value::String(check.message.clone()));
+                if let Some(duration) = check.duration {
+                    table.insert("duration_ms".to_string(), render::duration_ms(duration));
+                }
+                if let Some(ref hint) = check.hint {
+                    table.insert("hint".to_string(), toml::Value::String(hint.clone()));
+                }
+                toml::Value::Table(table)
+            })
+            .collect();
+        let mut table = toml::Table::new();
+        table.insert("status".to_string(),
+                     toml::Value::String(self.status().as_str().to_string()));
+        table.insert("started_at".to_string(), render::timestamp(self.started_at));
+        table.insert("duration_ms".to_string(), render::duration_ms(self.duration));
+        table.insert("facts".to_string(), toml::Value::Table(facts));
+        table.insert("checks".to_string(), toml::Value::Array(checks));
+        table
+    }
+}
+
+impl fmt::Display for DiagnosticsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_text(0))
+    }
+}
+
+/// Returns the diagnostics of the host the supervisor runs on.
+pub fn run(cfg: &DiagnosticsCfg) -> DiagnosticsReport {
+    run_with(cfg, &HostProbes)
+}
+
+/// Returns the diagnostics of the host `probes` describe.
+pub fn run_with(cfg: &DiagnosticsCfg, probes: &Probes) -> DiagnosticsReport {
+    let started = Instant::now();
+    let mut report = DiagnosticsReport {
+        started_at: time::get_time(),
+        duration: Duration::from_millis(0),
+        facts: vec![],
+        checks: vec![],
+    };
+    sys_diagnostics(probes, &mut report);
+    report.checks.extend(connectivity_diagnostics(cfg, probes));
+    let (status, message, hint) = check_github_config(cfg);
+    let mut check = Diagnostic::new("github.config", status, message);
+    check.hint = hint;
+    report.checks.push(check);
+    report.duration = started.elapsed();
+    report
+}
+
+fn sys_diagnostics(probes: &Probes, report: &mut DiagnosticsReport) {
+    let started = Instant::now();
+    let startup = match probes.startup_report() {
+        Ok(startup) => startup,
+        Err(e) => {
+            let mut check = Diagnostic::new("sys.facts", CheckStatus::Fail, e.to_string());
+            check.duration = Some(started.elapsed());
+            check.hint = Some("run with RUST_LOG=debug to see which fact wasn't detected"
+                .to_string());
+            report.checks.push(check);
+            return;
+        }
+    };
+    let mut facts = Diagnostic::new("sys.facts",
+                                    CheckStatus::Ok,
+                                    format!("{} facts detected", startup.facts.len()));
+    facts.duration = Some(started.elapsed());
+    report.checks.push(facts);
+    report.facts = startup.facts;
+    for result in startup.checks {
+        let mut check = Diagnostic::new(&format!("sys.{}", result.name),
+                                        result.status,
+                                        result.message);
+        if check.status != CheckStatus::Ok {
+            check.hint = STARTUP_HINTS.iter()
+                .find(|&&(name, _)| name == result.name)
+                .map(|&(_, hint)| hint.to_string());
+        }
+        report.checks.push(check);
+    }
+}
+
+/// Checks the host reaches the depot, and GitHub if it is used. A GitHub URL which names no
+/// target is left to `check_github_config`.
+fn connectivity_diagnostics(cfg: &DiagnosticsCfg, probes: &Probes) -> Vec<Diagnostic> {
+    let mut checks = vec![];
+    let mut names = vec![];
+    let mut targets = vec![];
+    match sys::ConnTarget::from_url(&cfg.depot_url) {
+        Ok(target) => {
+            names.push("connectivity.depot");
+            targets.push(target);
+        }
+        Err(e) => {
+            let mut check = Diagnostic::new("connectivity.depot", CheckStatus::Fail, e.to_string());
+            check.hint = Some("pass --url with the URL of the depot".to_string());
+            checks.push(check);
+        }
+    }
+    if let Some(ref url) = cfg.github_url {
+        if let Ok(target) = sys::ConnTarget::from_url(url) {
+            names.push("connectivity.github");
+            targets.push(target);
+        }
+    }
+    if targets.is_empty() {
+        return checks;
+    }
+    let started = Instant::now();
+    let results = probes.connectivity(&targets, cfg.timeout);
+    let elapsed = started.elapsed();
+    for (name, result) in names.into_iter().zip(results.iter()) {
+        let below_mtu = result.path_mtu.as_ref().map_or(false, |mtu| mtu.is_below_interface_mtu());
+        let status = if !result.is_ok() {
+            CheckStatus::Fail
+        } else if below_mtu {
+            CheckStatus::Warn
+        } else {
+            CheckStatus::Ok
+        };
+        let mut check = Diagnostic::new(name, status, result.to_string());
+        check.duration = match (result.resolve_time, result.connect_time) {
+            (None, None) => Some(elapsed),
+            (resolve, connect) => {
+                Some(resolve.unwrap_or(Duration::from_millis(0)) +
+                     connect.unwrap_or(Duration::from_millis(0)))
+            }
+        };
+        check.hint = connectivity_hint(result, below_mtu);
+        checks.push(check);
+    }
+    checks
+}
+
+fn connectivity_hint(result: &sys::ConnResult, below_mtu: bool) -> Option<String> {
+    let target = &result.target;
+    match result.error {
+        Some(sys::ConnError::Resolve(_)) |
+        Some(sys::ConnError::NoAddresses) => {
+            Some(format!("check the name servers of /etc/resolv.conf resolve {}",
+                         target.host))
+        }
+        Some(sys::ConnError::Refused) => {
+            Some(format!("check {} is the port the service listens on", target.port))
+        }
+        Some(sys::ConnError::TimedOut) |
+        Some(sys::ConnError::Unreachable) |
+        Some(sys::ConnError::DeadlineExceeded) => {
+            Some(format!("check firewalls let this host reach {}:{}, or set HTTPS_PROXY if it \
+                          must go through a proxy",
+                         target.host,
+                         target.port))
+        }
+        Some(sys::ConnError::NotTls) => {
+            Some("a proxy or captive portal may answer in place of the service; check the \
+                  scheme and port of the URL"
+                .to_string())
+        }
+        Some(sys::ConnError::Other(_)) => None,
+        None if below_mtu => {
+            Some("lower the MTU of the interface, or have the tunnel clamp the TCP MSS"
+                .to_string())
+        }
+        None => None,
+    }
+}
+
+/// Looks at the GitHub settings, and returns how it found them, why, and what to do about them.
+fn check_github_config(cfg: &DiagnosticsCfg) -> (CheckStatus, String, Option<String>) {
+    let url_hint = || {
+        Some(format!("set {} to the URL of the GitHub API, such as {}",
+                     GITHUB_URL_ENVVAR,
+                     DEFAULT_GITHUB_URL))
+    };
+    let token_hint = || {
+        Some(format!("set {} to a GitHub personal access token", AUTH_TOKEN_ENVVAR))
+    };
+    let url = match cfg.github_url {
+        Some(ref url) => url,
+        None => {
+            return (CheckStatus::Ok,
+                    format!("GitHub isn't used, {} isn't set", AUTH_TOKEN_ENVVAR),
+                    None)
+        }
+    };
+    let parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(e) => return (CheckStatus::Fail, format!("{} isn't a URL ({})", url, e), url_hint()),
+    };
+    let host = match parsed.host_str() {
+        Some(host) => host.to_lowercase(),
+        None => return (CheckStatus::Fail, format!("{} names no host", url), url_hint()),
+    };
+    if parsed.scheme() != "https" {
+        return (CheckStatus::Warn,
+                format!("{} isn't https, the token is sent in the clear", url),
+                Some("use the https URL of the GitHub API".to_string()));
+    }
+    let enterprise = parsed.path().trim_right_matches('/') == GITHUB_ENTERPRISE_API_PATH;
+    if host != GITHUB_API_HOST && !enterprise {
+        return (CheckStatus::Warn,
+                format!("{} doesn't end in {}, which GitHub Enterprise serves its API under",
+                        url,
+                        GITHUB_ENTERPRISE_API_PATH),
+                Some(format!("append {} to the URL of the GitHub Enterprise appliance",
+                             GITHUB_ENTERPRISE_API_PATH)));
+    }
+    match cfg.auth_token {
+        None => {
+            (CheckStatus::Warn,
+             format!("{} isn't set, requests to GitHub aren't authenticated",
+                     AUTH_TOKEN_ENVVAR),
+             token_hint())
+        }
+        Some(ref token) if token.trim().is_empty() => {
+            (CheckStatus::Fail, format!("{} is empty", AUTH_TOKEN_ENVVAR), token_hint())
+        }
+        Some(ref token) if token.trim() != token => {
+            (CheckStatus::Fail,
+             format!("{} has surrounding whitespace, which GitHub rejects",
+                     AUTH_TOKEN_ENVVAR),
+             Some(format!("set {} again without the surrounding whitespace",
+                          AUTH_TOKEN_ENVVAR)))
+        }
+        Some(_) => {
+            (CheckStatus::Ok,
+             format!("{} with the token of {}", url, AUTH_TOKEN_ENVVAR),
+             None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::time::Duration;
+
+    use hcore::util::render::Render;
+    use hcore::util::sys;
+    use time;
+    use toml;
+
+    use error::{Error, Result};
+    use util::sys::{CheckResult, CheckStatus, StartupReport};
+    use super::*;
+
+    static LOGKEY: &'static str = "DG";
+
+    struct FakeProbes {
+        startup: Option<StartupReport>,
+        errors: Vec<Option<sys::ConnError>>,
+        path_mtu: Option<sys::PathMtuReport>,
+        targets: RefCell<Vec<sys::ConnTarget>>,
+    }
+
+    impl FakeProbes {
+        fn new() -> Self {
+            let checks = vec![CheckResult {
+                                  name: "nofile_limit",
+                                  status: CheckStatus::Warn,
+                                  message: "the open files limit is 1024".to_string(),
+                              },
+                              CheckResult {
+                                  name: "disk_space",
+                                  status: CheckStatus::Ok,
+                                  message: "12 GB free".to_string(),
+                              }];
+            FakeProbes {
+                startup: Some(StartupReport {
+                    generated_at: time::get_time(),
+                    duration: Duration::from_millis(1),
+                    facts: vec![("hostname", "db-1".to_string())],
+                    checks: checks,
+                }),
+                errors: vec![None, None],
+                path_mtu: None,
+                targets: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    impl Probes for FakeProbes {
+        fn startup_report(&self) -> Result<StartupReport> {
+            match self.startup {
+                Some(ref startup) => Ok(startup.clone()),
+                None => Err(sup_error!(Error::CommandNotImplemented)),
+            }
+        }
+
+        fn connectivity(&self,
+                        targets: &[sys::ConnTarget],
+                        _timeout: Duration)
+                        -> Vec<sys::ConnResult> {
+            *self.targets.borrow_mut() = targets.to_vec();
+            targets.iter()
+                .zip(self.errors.iter())
+                .map(|(target, error)| {
+                    sys::ConnResult {
+                        target: target.clone(),
+                        addr: None,
+                        resolve_time: Some(Duration::from_millis(2)),
+                        connect_time: Some(Duration::from_millis(40)),
+                        error: error.clone(),
+                        path_mtu: self.path_mtu.clone(),
+                    }
+                })
+                .collect()
+        }
+    }
+
+    fn cfg() -> DiagnosticsCfg {
+        DiagnosticsCfg {
+            depot_url: "https://depot.example.com/v1/depot".to_string(),
+            github_url: Some(DEFAULT_GITHUB_URL.to_string()),
+            auth_token: Some("0123456789abcdef".to_string()),
+            timeout: Duration::from_millis(100),
+        }
+    }
+
+    fn check<'a>(report: &'a DiagnosticsReport, name: &str) -> &'a Diagnostic {
+        report.checks.iter().find(|check| check.name == name).expect(name)
+    }
+
+    #[test]
+    fn run_with_reports_every_stage() {
+        let probes = FakeProbes::new();
+        let report = run_with(&cfg(), &probes);
+        let names: Vec<&str> = report.checks.iter().map(|check| check.name.as_str()).collect();
+        assert_eq!(names,
+                   vec!["sys.facts",
+                        "sys.nofile_limit",
+                        "sys.disk_space",
+                        "connectivity.depot",
+                        "connectivity.github",
+                        "github.config"]);
+        assert_eq!(report.facts, vec![("hostname", "db-1".to_string())]);
+        assert_eq!(report.status(), CheckStatus::Warn);
+        assert!(report.failures().is_empty());
+        assert_eq!(*probes.targets.borrow(),
+                   vec![sys::ConnTarget::new("depot.example.com", 443, true),
+                        sys::ConnTarget::new("api.github.com", 443, true)]);
+        assert_eq!(check(&report, "connectivity.depot").duration,
+                   Some(Duration::from_millis(42)));
+        assert!(check(&report, "sys.nofile_limit").hint.as_ref().unwrap().contains("ulimit"));
+        assert_eq!(check(&report, "sys.disk_space").hint, None);
+    }
+
+    #[test]
+    fn run_with_fails_the_sys_facts_which_cannot_be_detected() {
+        let mut probes = FakeProbes::new();
+        probes.startup = None;
+        let report = run_with(&cfg(), &probes);
+        let facts = check(&report, "sys.facts");
+        assert_eq!(facts.status, CheckStatus::Fail);
+        assert!(facts.hint.is_some());
+        assert!(report.facts.is_empty());
+        assert_eq!(report.failures().len(), 1);
+    }
+
+    #[test]
+    fn unreached_targets_fail_with_a_hint() {
+        let mut probes = FakeProbes::new();
+        probes.errors = vec![None, Some(sys::ConnError::Resolve("no such host".to_string()))];
+        let report = run_with(&cfg(), &probes);
+        let github = check(&report, "connectivity.github");
+        assert_eq!(github.status, CheckStatus::Fail);
+        assert!(github.hint.as_ref().unwrap().contains("/etc/resolv.conf"));
+        assert_eq!(check(&report, "connectivity.depot").status, CheckStatus::Ok);
+        assert_eq!(report.status(), CheckStatus::Fail);
+    }
+
+    #[test]
+    fn small_path_mtu_warns() {
+        let mut probes = FakeProbes::new();
+        probes.path_mtu = Some(sys::PathMtuReport::Probed {
+            largest: Some(1400),
+            ceiling: 1500,
+            interface_mtu: Some(1500),
+            blackhole: true,
+        });
+        let report = run_with(&cfg(), &probes);
+        let depot = check(&report, "connectivity.depot");
+        assert_eq!(depot.status, CheckStatus::Warn);
+        assert!(depot.hint.as_ref().unwrap().contains("MSS"));
+    }
+
+    #[test]
+    fn invalid_depot_url_fails_without_probing_it() {
+        let probes = FakeProbes::new();
+        let mut cfg = cfg();
+        cfg.depot_url = "not a url".to_string();
+        let report = run_with(&cfg, &probes);
+        assert_eq!(check(&report, "connectivity.depot").status, CheckStatus::Fail);
+        assert_eq!(*probes.targets.borrow(),
+                   vec![sys::ConnTarget::new("api.github.com", 443, true)]);
+    }
+
+    #[test]
+    fn github_config_checks() {
+        let status = |github_url: Option<&str>, auth_token: Option<&str>| {
+            let mut cfg = cfg();
+            cfg.github_url = github_url.map(|url| url.to_string());
+            cfg.auth_token = auth_token.map(|token| token.to_string());
+            check_github_config(&cfg).0
+        };
+        let token = Some("0123456789abcdef");
+        assert_eq!(status(None, None), CheckStatus::Ok);
+        assert_eq!(status(Some(DEFAULT_GITHUB_URL), token), CheckStatus::Ok);
+        assert_eq!(status(Some("https://github.example.com/api/v3/"), token),
+                   CheckStatus::Ok);
+        assert_eq!(status(Some("https://github.example.com"), token),
+                   CheckStatus::Warn);
+        assert_eq!(status(Some("http://api.github.com"), token), CheckStatus::Warn);
+        assert_eq!(status(Some(DEFAULT_GITHUB_URL), None), CheckStatus::Warn);
+        assert_eq!(status(Some("not a url"), token), CheckStatus::Fail);
+        assert_eq!(status(Some(DEFAULT_GITHUB_URL), Some("  ")), CheckStatus::Fail);
+        assert_eq!(status(Some(DEFAULT_GITHUB_URL), Some("0123456789abcdef\n")),
+                   CheckStatus::Fail);
+    }
+
+    fn synthetic_report() -> DiagnosticsReport {
+        DiagnosticsReport {
+            started_at: time::Timespec::new(1476350400, 0),
+            duration: Duration::from_millis(57),
+            facts: vec![("hostname", "db-1".to_string())],
+            checks: vec![Diagnostic {
+                             name: "sys.facts".to_string(),
+                             status: CheckStatus::Ok,
+                             message: "1 facts detected".to_string(),
+                             duration: Some(Duration::from_millis(12)),
+                             hint: None,
+                         },
+                         Diagnostic {
+                             name: "github.config".to_string(),
+                             status: CheckStatus::Fail,
+                             message: "HAB_AUTH_TOKEN is empty".to_string(),
+                             duration: None,
+                             hint: Some("set it".to_string()),
+                         }],
+        }
+    }
+
+    #[test]
+    fn report_lines_up_the_checks_and_their_hints() {
+        let report = synthetic_report();
+        let lines = vec!["Diagnostics: fail, run at 2016-10-13T09:20:00Z in 57ms".to_string(),
+                         "  hostname       db-1".to_string(),
+                         "  sys.facts      ok       12ms  1 facts detected".to_string(),
+                         "  github.config  fail        -  HAB_AUTH_TOKEN is empty".to_string(),
+                         format!("  {:30}hint: set it", "")];
+        assert_eq!(report.to_string(), lines.join("\n"));
+    }
+
+    #[test]
+    fn report_text_is_pinned() {
+        let mut report = synthetic_report();
+        report.checks[1].hint = Some("export HAB_AUTH_TOKEN with a GitHub token which can read \
+                                      the members of your organization"
+            .to_string());
+        assert_eq!(format!("{}\n", report.to_text(64)),
+                   include_str!("../tests/fixtures/diagnostics-report.txt"));
+    }
+
+    #[test]
+    fn report_toml_round_trips() {
+        let table = synthetic_report().to_toml();
+        let parsed = toml::Parser::new(&toml::encode_str(&table)).parse().unwrap();
+        assert_eq!(parsed, table);
+        let value = toml::Value::Table(table);
+        assert_eq!(value.lookup("status").and_then(|v| v.as_str()), Some("fail"));
+        assert_eq!(value.lookup("started_at").and_then(|v| v.as_str()),
+                   Some("2016-10-13T09:20:00Z"));
+        assert_eq!(value.lookup("checks.0.duration_ms").and_then(|v| v.as_integer()),
+                   Some(12));
+        assert!(value.lookup("checks.1.duration_ms").is_none());
+        assert_eq!(value.lookup("checks.1.hint").and_then(|v| v.as_str()), Some("set it"));
+    }
+}
@@ -98,9 +98,11 @@ impl SupError {
 #[derive(Debug)]
 pub enum Error {
     ActorError(actor::ActorError),
+    ClockSkewFailed(String),
     CommandNotImplemented,
     DbInvalidPath,
     DepotClient(depot_client::Error),
+    DiagnosticsFailed(usize),
     ExecCommandNotFound(String),
     FileNotFound(String),
     HabitatCommon(common::Error),
@@ -116,6 +118,7 @@ pub enum Error {
     InvalidKeyParameter(String),
     InvalidPidFile,
     InvalidServiceGroupString(String),
+    InvalidSysOverride(String, String),
     Io(io::Error),
     IPFailed,
     JsonDecode(json::DecoderError),
@@ -128,11 +131,13 @@ pub enum Error {
     PackageArchiveMalformed(String),
     PackageNotFound(package::PackageIdent),
     Permissions(String),
+    PublicIpFailed(String),
     RemotePackageNotFound(package::PackageIdent),
     SignalFailed,
     SignalNotifierStarted,
     StrFromUtf8Error(str::Utf8Error),
     StringFromUtf8Error(string::FromUtf8Error),
+    SysNotInitialized,
     TomlEncode(toml::Error),
     TomlParser(Vec<toml::ParserError>),
     TryRecvError(mpsc::TryRecvError),
@@ -158,9 +163,13 @@ impl fmt::Display for SupError {
             Error::HabitatCore(ref err) => format!("{}", err),
             Error::HandlebarsTemplateFileError(ref err) => format!("{:?}", err),
             Error::HandlebarsRenderError(ref err) => format!("{}", err),
+            Error::ClockSkewFailed(ref e) => {
+                format!("Failed to measure this host's clock skew: {}", e)
+            }
             Error::CommandNotImplemented => format!("Command is not yet implemented!"),
             Error::DbInvalidPath => format!("Invalid filepath to internal datastore"),
             Error::DepotClient(ref err) => format!("{}", err),
+            Error::DiagnosticsFailed(n) => format!("{} diagnostic checks failed", n),
             Error::FileNotFound(ref e) => format!("File not found at: {}", e),
             Error::HealthCheck(ref e) => format!("Health Check failed: {}", e),
             Error::HookFailed(ref t, ref e, ref o) => {
@@ -178,6 +187,9 @@ impl fmt::Display for SupError {
             Error::InvalidServiceGroupString(ref e) => {
                 format!("Invalid service group string: {}", e)
             }
+            Error::InvalidSysOverride(ref var, ref e) => {
+                format!("Invalid sys override {}, {}", var, e)
+            }
             Error::Io(ref err) => format!("{}", err),
             Error::IPFailed => format!("Failed to discover this hosts outbound IP address"),
             Error::JsonDecode(ref e) => format!("JSON decoding error: {}", e),
@@ -201,6 +213,9 @@ impl fmt::Display for SupError {
                     format!("Cannot find a release of package: {}", pkg)
                 }
             }
+            Error::PublicIpFailed(ref e) => {
+                format!("Failed to discover this host's public IP address: {}", e)
+            }
             Error::RemotePackageNotFound(ref pkg) => {
                 if pkg.fully_qualified() {
                     format!("Cannot find package in any sources: {}", pkg)
@@ -214,6 +229,9 @@ impl fmt::Display for SupError {
             }
             Error::StrFromUtf8Error(ref e) => format!("{}", e),
             Error::StringFromUtf8Error(ref e) => format!("{}", e),
+            Error::SysNotInitialized => {
+                format!("The facts about this host were read before util::sys::init detected them")
+            }
             Error::TomlEncode(ref e) => format!("Failed to encode toml: {}", e),
             Error::TomlParser(ref errs) => {
                 format!("Failed to parse toml:\n{}", toml_parser_string(errs))
@@ -245,9 +263,11 @@ impl error::Error for SupError {
             Error::HandlebarsTemplateFileError(ref err) => err.description(),
             Error::HabitatCommon(ref err) => err.description(),
             Error::HabitatCore(ref err) => err.description(),
+            Error::ClockSkewFailed(_) => "Failed to measure the clock skew",
             Error::CommandNotImplemented => "Command is not yet implemented!",
             Error::DbInvalidPath => "A bad filepath was provided for an internal datastore",
             Error::DepotClient(ref err) => err.description(),
+            Error::DiagnosticsFailed(_) => "Diagnostic checks failed",
             Error::FileNotFound(_) => "File not found",
             Error::HealthCheck(_) => "Health Check returned an unknown status code",
             Error::HookFailed(_, _, _) => "Hook failed to run",
@@ -259,6 +279,7 @@ impl error::Error for SupError {
             Error::InvalidServiceGroupString(_) => {
                 "Service group strings must be in service.group format (example: redis.default)"
             }
+            Error::InvalidSysOverride(_, _) => "Invalid override of a sys value",
             Error::Io(ref err) => err.description(),
             Error::IPFailed => "Failed to discover the outbound IP address",
             Error::JsonDecode(_) => "JSON decoding error: {:?}",
@@ -278,11 +299,13 @@ impl error::Error for SupError {
             }
             Error::PackageNotFound(_) => "Cannot find a package",
             Error::Permissions(_) => "File system permissions error",
+            Error::PublicIpFailed(_) => "Failed to discover the public IP address",
             Error::RemotePackageNotFound(_) => "Cannot find a package in any sources",
             Error::SignalFailed => "Failed to send a signal to the child process",
             Error::SignalNotifierStarted => "Only one instance of a Signal Notifier may be running",
             Error::StrFromUtf8Error(_) => "Failed to convert a str from a &[u8] as UTF-8",
             Error::StringFromUtf8Error(_) => "Failed to convert a string from a Vec<u8> as UTF-8",
+            Error::SysNotInitialized => "Facts about this host were read before they were detected",
             Error::TomlEncode(_) => "Failed to encode toml!",
             Error::TomlParser(_) => "Failed to parse toml!",
             Error::TryRecvError(_) => "A channel failed to recieve a response",
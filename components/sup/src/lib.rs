@@ -66,6 +66,12 @@ extern crate threadpool;
 extern crate openssl;
 #[macro_use]
 extern crate lazy_static;
+#[cfg(test)]
+extern crate quickcheck;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
 #[macro_export]
 /// Creates a new SupError, embedding the current file name, line number, column, and module path.
@@ -272,6 +278,7 @@ pub mod state_machine;
 pub mod sidecar;
 pub mod health_check;
 pub mod config;
+pub mod diagnostics;
 pub mod service_config;
 pub mod census;
 pub mod gossip;
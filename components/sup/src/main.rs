@@ -177,6 +177,9 @@ fn config_from_args(subcommand: &str, sub_args: &ArgMatches) -> Result<Config> {
     if sub_args.is_present("permanent-peer") {
         config.set_gossip_permanent(true);
     }
+    if sub_args.is_present("doctor") {
+        config.set_doctor(true);
+    }
     if let Some(sg) = sub_args.value_of("service-group") {
         config.set_service_group(sg.to_string());
     }
@@ -307,7 +310,10 @@ fn main() {
         .arg(Arg::with_name("permanent-peer")
             .short("I")
             .long("permanent-peer")
-            .help("If this service is a permanent peer"));
+            .help("If this service is a permanent peer"))
+        .arg(Arg::with_name("doctor")
+            .long("doctor")
+            .help("Report whether the depot is reached before starting"));
     let sub_bash = SubCommand::with_name("bash")
         .about("Start an interactive shell (bash)")
         .aliases(&["b", "ba", "bas"]);
@@ -319,6 +325,10 @@ fn main() {
             .index(1)
             .required(true)
             .help("Name of package"));
+    let sub_doctor = SubCommand::with_name("doctor")
+        .about("Report whether this host, its connectivity, and its GitHub settings are fit to run \
+                services")
+        .arg(arg_url());
     let args = App::new(sup::PROGRAM_NAME.as_str())
         .version(VERSION)
         .setting(AppSettings::VersionlessSubcommands)
@@ -334,7 +344,8 @@ fn main() {
         .subcommand(sub_start)
         .subcommand(sub_bash)
         .subcommand(sub_sh)
-        .subcommand(sub_config);
+        .subcommand(sub_config)
+        .subcommand(sub_doctor);
     let matches = args.get_matches();
 
     debug!("clap matches {:?}", matches);
@@ -352,6 +363,7 @@ fn main() {
         Command::ShellBash => shell_bash(&config),
         Command::ShellSh => shell_sh(&config),
         Command::Config => configure(&config),
+        Command::Doctor => doctor(&config),
         Command::Start => start(&config),
     };
 
@@ -387,6 +399,12 @@ fn configure(config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Report the diagnostics of this host
+#[allow(dead_code)]
+fn doctor(config: &Config) -> Result<()> {
+    doctor::run(config)
+}
+
 /// Start a service
 #[allow(dead_code)]
 fn start(config: &Config) -> Result<()> {
@@ -19,6 +19,7 @@ use std::collections::HashMap;
 use std::env;
 use std::fs::File;
 use std::io::prelude::*;
+use std::sync::Arc;
 
 use ansi_term::Colour::Purple;
 use rustc_serialize::Encodable;
@@ -44,7 +45,7 @@ static ENV_VAR_PREFIX: &'static str = "HAB";
 
 /// The top level struct for all our configuration - this corresponds to the top level namespaces
 /// available in `config.toml`.
-#[derive(Debug, RustcEncodable)]
+#[derive(Debug)]
 pub struct ServiceConfig {
     hab: Hab,
     pkg: Pkg,
@@ -77,7 +78,7 @@ impl ServiceConfig {
         Ok(ServiceConfig {
             pkg: Pkg::new(&package.pkg_install),
             hab: Hab::new(),
-            sys: Sys::new(&config),
+            sys: try!(Sys::new(&config)),
             cfg: cfg,
             svc: Svc::new(cl),
             bind: bind,
@@ -117,6 +118,30 @@ impl ServiceConfig {
         self.needs_write = true
     }
 
+    /// Replace the `sys` data, after this host's IP address or host name changed. The
+    /// configuration is only rendered again if a key changed which isn't expected to change
+    /// from one rendering to the next, see `util::sys::requires_render`.
+    pub fn sys(&mut self, config: &Config) {
+        match Sys::new(config) {
+            Ok(sys) => {
+                let changes = match (self.sys.to_toml(), sys.to_toml()) {
+                    (Ok(toml::Value::Table(old)), Ok(toml::Value::Table(new))) => {
+                        util::sys::diff_tables(&old, &new)
+                    }
+                    _ => vec![],
+                };
+                for change in changes.iter() {
+                    debug!("{}", change);
+                }
+                if util::sys::requires_render(&changes) {
+                    self.needs_write = true;
+                }
+                self.sys = sys;
+            }
+            Err(e) => outputln!("Failed to write new sys tree: {}", e),
+        }
+    }
+
     /// Replace the `svc` data.
     pub fn svc(&mut self, cl: &CensusList) {
         self.svc = Svc::new(cl);
@@ -563,10 +588,11 @@ impl Pkg {
     }
 }
 
-#[derive(Debug, RustcEncodable)]
+/// The `[sys]` table: the facts about this host the supervisor's subsystems share, see
+/// `util::sys::Sys`, with the addresses the supervisor listens on.
+#[derive(Debug)]
 pub struct Sys {
-    pub ip: String,
-    pub hostname: String,
+    pub facts: Arc<util::sys::Sys>,
     pub gossip_ip: String,
     pub gossip_port: u16,
     pub sidecar_ip: String,
@@ -574,26 +600,15 @@ pub struct Sys {
 }
 
 impl Sys {
-    fn new(config: &Config) -> Sys {
-        let ip = match util::sys::ip() {
-            Ok(ip) => ip.to_string(),
-            Err(e) => {
-                outputln!("IP Address lookup failed; using fallback of 127.0.0.1 ({})",
-                          e);
-                String::from("127.0.0.1")
-            }
-        };
-        let hostname = match util::sys::hostname() {
-            Ok(ip) => ip,
-            Err(e) => {
-                outputln!("Hostname lookup failed; using fallback of localhost ({})",
-                          e);
-                String::from("localhost")
-            }
-        };
+    /// Returns the facts `util::sys::get` returns, which are detected with the default options if
+    /// `util::sys::init` hasn't been called, with the listen addresses of `config`.
+    fn new(config: &Config) -> Result<Sys> {
+        Ok(Sys::from_facts(config, try!(util::sys::shared())))
+    }
+
+    fn from_facts(config: &Config, facts: Arc<util::sys::Sys>) -> Sys {
         Sys {
-            ip: ip,
-            hostname: hostname,
+            facts: facts,
             gossip_ip: config.gossip_listen_ip().to_string(),
             gossip_port: config.gossip_listen_port(),
             sidecar_ip: config.http_listen_ip().to_string(),
@@ -601,11 +616,20 @@ impl Sys {
         }
     }
 
+    /// Renders the `[sys]` table of the facts, see `util::sys::Sys::to_toml`, with the listen
+    /// addresses and the overrides of the environment applied, see `util::sys::apply_overrides`.
     fn to_toml(&self) -> Result<toml::Value> {
-        let mut e = toml::Encoder::new();
-        try!(self.encode(&mut e));
-        let v = toml::Value::Table(e.toml);
-        Ok(v)
+        let mut table = self.facts.to_toml();
+        table.insert("gossip_ip".to_string(),
+                     toml::Value::String(self.gossip_ip.clone()));
+        table.insert("gossip_port".to_string(),
+                     toml::Value::Integer(self.gossip_port as i64));
+        table.insert("sidecar_ip".to_string(),
+                     toml::Value::String(self.sidecar_ip.clone()));
+        table.insert("sidecar_port".to_string(),
+                     toml::Value::Integer(self.sidecar_port as i64));
+        try!(util::sys::apply_overrides(&mut table));
+        Ok(toml::Value::Table(table))
     }
 }
 
@@ -694,30 +718,122 @@ mod test {
         let ip = toml.lookup("sys.ip").unwrap().as_str().unwrap();
         let re = Regex::new(r"\d+\.\d+\.\d+\.\d+").unwrap();
         assert!(re.is_match(&ip));
+        assert_eq!(toml.lookup("sys.cpu.count"), toml.lookup("sys.cpus"));
     }
 
     mod sys {
+        use std::sync::Arc;
+
         use config::Config;
+        use hcore::util::sys;
         use service_config::Sys;
         use regex::Regex;
+        use util;
+
+        // Detected here rather than shared, as other tests store the facts `util::sys::init`
+        // shares
+        fn detected() -> Sys {
+            let facts = util::sys::Sys::from_snapshot(&sys::snapshot()).unwrap();
+            Sys::from_facts(&Config::default(), Arc::new(facts))
+        }
 
         #[test]
         fn ip() {
-            let s = Sys::new(&Config::default());
+            let s = detected();
             let re = Regex::new(r"\d+\.\d+\.\d+\.\d+").unwrap();
-            assert!(re.is_match(&s.ip));
+            assert!(re.is_match(&s.facts.ip.to_string()));
+        }
+
+        #[test]
+        fn ip6_is_never_link_local() {
+            let s = detected();
+            if let Some(ip6) = s.facts.ip6 {
+                assert!(!ip6.to_string().starts_with("fe80:"));
+            }
+        }
+
+        #[test]
+        fn interfaces() {
+            let s = detected();
+            let toml = s.to_toml().unwrap();
+            let interfaces = toml.lookup("interfaces").unwrap().as_table().unwrap();
+            let lo = interfaces.values()
+                .find(|i| i.lookup("loopback").and_then(|l| l.as_bool()) == Some(true))
+                .unwrap();
+            let addr = lo.lookup("ipv4").unwrap().as_slice().unwrap()[0].clone();
+            assert_eq!(addr.lookup("address").unwrap().as_str(), Some("127.0.0.1"));
+            assert_eq!(addr.lookup("netmask").unwrap().as_str(), Some("255.0.0.0"));
+            assert!(toml.lookup("ip").is_some());
+        }
+
+        #[test]
+        fn process() {
+            let s = detected();
+            let toml = s.to_toml().unwrap();
+            assert!(toml.lookup("process.pid").unwrap().as_integer().unwrap() > 0);
+            assert_eq!(toml.lookup("process.user").unwrap().as_str(),
+                       Some(s.facts.process.user.as_str()));
+        }
+
+        #[test]
+        fn effective_limits() {
+            let s = detected();
+            let toml = s.to_toml().unwrap();
+            let cpus = toml.lookup("limits.effective_cpus").unwrap().as_float().unwrap();
+            assert!(cpus > 0.0 && cpus <= s.facts.cpus as f64);
+            assert!(toml.lookup("effective_cpus").is_none());
+        }
+
+        #[test]
+        fn ip_candidates() {
+            let s = detected();
+            let toml = s.to_toml().unwrap();
+            assert_eq!(toml.lookup("network.candidates.primary").unwrap().as_str(),
+                       Some(s.facts.ip.to_string().as_str()));
+            assert!(toml.lookup("network.candidates.addresses").unwrap().as_slice().is_some());
+            assert!(toml.lookup("ip_candidates").is_none());
         }
 
         #[test]
         fn hostname() {
-            let s = Sys::new(&Config::default());
+            let s = detected();
             let re = Regex::new(r"\w+").unwrap();
-            assert!(re.is_match(&s.hostname));
+            assert!(re.is_match(&s.facts.hostname));
+        }
+
+        #[test]
+        fn fqdn_starts_with_hostname() {
+            let s = detected();
+            let short = s.facts.hostname.split('.').next().unwrap();
+            assert!(s.facts.fqdn.starts_with(short));
+        }
+
+        #[test]
+        fn cpus() {
+            let s = detected();
+            let toml = s.to_toml().unwrap();
+            assert!(toml.lookup("cpus").unwrap().as_integer().unwrap() >= 1);
+        }
+
+        #[test]
+        fn mem_total_kb() {
+            let s = detected();
+            let toml = s.to_toml().unwrap();
+            assert!(toml.lookup("mem_total_kb").unwrap().as_integer().unwrap() > 0);
+        }
+
+        #[test]
+        fn listen_addresses() {
+            let s = detected();
+            let toml = s.to_toml().unwrap();
+            assert_eq!(toml.lookup("gossip_ip").unwrap().as_str(), Some(s.gossip_ip.as_str()));
+            assert_eq!(toml.lookup("sidecar_port").unwrap().as_integer(),
+                       Some(s.sidecar_port as i64));
         }
 
         #[test]
         fn to_toml() {
-            let s = Sys::new(&Config::default());
+            let s = detected();
             let toml = s.to_toml().unwrap();
             let ip = toml.lookup("ip").unwrap().as_str().unwrap();
             let re = Regex::new(r"\d+\.\d+\.\d+\.\d+").unwrap();
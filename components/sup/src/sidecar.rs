@@ -20,6 +20,7 @@
 //! * /config: Returns the current configuration of the service
 //! * /health: Returns the current health of the service
 //! * /status: Returns the current status of the service, from the supervisors point of view
+//! * /startup: Returns the startup report of the host the supervisor runs on
 
 use std::collections::HashMap;
 use std::net::SocketAddrV4;
@@ -43,6 +44,7 @@ use gossip::detector::Detector;
 use census::{CensusList, CensusEntry, CensusEntryId, Census};
 use election::{Election, ElectionList};
 use supervisor::Supervisor;
+use util;
 
 static LOGKEY: &'static str = "SI";
 const GET_HEALTH: &'static str = "/health";
@@ -51,6 +53,7 @@ const GET_STATUS: &'static str = "/status";
 const GET_GOSSIP: &'static str = "/gossip";
 const GET_CENSUS: &'static str = "/census";
 const GET_ELECTION: &'static str = "/election";
+const GET_STARTUP: &'static str = "/startup";
 
 pub type SidecarActor = wonder::actor::Actor<SidecarMessage>;
 
@@ -177,6 +180,8 @@ impl GenServer for Sidecar {
         let el = state.election_list.clone();
         router.get(GET_ELECTION, move |r: &mut Request| election(&el, r));
 
+        router.get(GET_STARTUP, startup);
+
         match Iron::new(router).http(state.listen) {
             Ok(_) => HandleResult::NoReply(None),
             Err(_) => {
@@ -281,6 +286,18 @@ fn census(census_list: &Arc<RwLock<CensusList>>, _req: &mut Request) -> IronResu
     Ok(Response::with((status::Ok, json_response)))
 }
 
+/// The /startup callback.
+///
+/// Returns the startup report of the supervisor's facts as JSON.
+///
+/// # Failures
+///
+/// * Fails if the facts haven't been detected yet.
+fn startup(_req: &mut Request) -> IronResult<Response> {
+    let sys = try!(util::sys::get());
+    Ok(Response::with((status::Ok, util::sys::startup_report(&sys).to_json())))
+}
+
 /// The /config callback.
 ///
 /// Returns the current running configuration.
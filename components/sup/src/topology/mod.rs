@@ -35,6 +35,7 @@ use std::sync::mpsc::TryRecvError;
 use std::thread;
 use std::time::Duration;
 
+use hcore::util::sys::{self, SysChange, SysWatch};
 use wonder;
 
 use state_machine::StateMachine;
@@ -52,12 +53,14 @@ use gossip::rumor::{Rumor, RumorList};
 use gossip::member::MemberList;
 use election::ElectionList;
 use time::SteadyTime;
+use util;
 use util::signals;
 use util::users as hab_users;
 use config::UpdateStrategy;
 
 static LOGKEY: &'static str = "TP";
 static MINIMUM_LOOP_TIME_MS: i64 = 200;
+static SYS_WATCH_INTERVAL_MS: u64 = 30_000;
 
 #[derive(PartialEq, Eq, Debug, RustcEncodable)]
 pub enum Topology {
@@ -120,6 +123,8 @@ pub struct Worker<'a> {
     pub pkg_updater: Option<PackageUpdaterActor>,
     /// The service supervisor
     pub supervisor: Arc<RwLock<Supervisor>>,
+    /// Notifies the main loop when this host's IP address or host name changes
+    pub sys_watch: SysWatch,
     pub return_state: Option<State>,
 }
 
@@ -191,6 +196,13 @@ impl<'a> Worker<'a> {
         let sidecar_listen = try!(SocketAddrV4::from_str(&format!("{}:{}",
                                                                   &config.http_listen_ip(),
                                                                   config.http_listen_port())));
+        let sys_watch = try!(sys::watch(Duration::from_millis(SYS_WATCH_INTERVAL_MS)));
+        // The facts shared by the supervisor's subsystems follow the snapshot the watch stores
+        sys_watch.on_change(Box::new(|_| {
+            if let Err(e) = util::sys::update(&sys::snapshot()) {
+                outputln!("Failed to update the facts about this host: {}", e);
+            }
+        }));
         Ok(Worker {
             package: pkg_lock,
             package_name: package_name,
@@ -215,6 +227,7 @@ impl<'a> Worker<'a> {
                                                    sidecar_gfl),
             supervisor: supervisor,
             pkg_updater: pkg_updater,
+            sys_watch: sys_watch,
             return_state: None,
         })
     }
@@ -292,6 +305,31 @@ fn run_internal<'a>(sm: &mut StateMachine<State, Worker<'a>, SupError>,
             try!(supervisor.check_process());
         }
 
+        // Advertise, and render into the service config, this host's new address or host name
+        loop {
+            match worker.sys_watch.try_recv() {
+                Ok(change) => {
+                    outputln!("{}", change);
+                    {
+                        let mut cl = worker.census_list.write().unwrap();
+                        match change {
+                            SysChange::Ip { new: Some(ip), .. } => cl.me_mut().ip(ip.to_string()),
+                            SysChange::Hostname { new: Some(hostname), .. } => {
+                                cl.me_mut().hostname(hostname)
+                            }
+                            _ => {}
+                        }
+                    }
+                    let mut service_config = worker.service_config.write().unwrap();
+                    service_config.sys(worker.config);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    panic!("sys watcher crashed!");
+                }
+            }
+        }
+
         let mut restart_process = false;
 
         // This section, and the following really need to be refactored:
@@ -16,12 +16,16 @@ use std::collections::BTreeMap;
 
 use toml;
 use rustc_serialize::json::Json;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
 
 pub fn toml_to_json(value: toml::Value) -> Json {
     match value {
         toml::Value::String(s) => Json::String(format!("{}", s)),
         toml::Value::Integer(i) => Json::I64(i as i64),
-        toml::Value::Float(i) => Json::F64(i as f64),
+        // JSON has no NaN or infinity, which rustc_serialize would render as invalid numbers
+        toml::Value::Float(f) if !f.is_finite() => Json::Null,
+        toml::Value::Float(f) => Json::F64(f),
         toml::Value::Boolean(b) => Json::Boolean(b),
         toml::Value::Datetime(s) => Json::String(format!("{}", s)),
         toml::Value::Array(a) => toml_vec_to_json(a),
@@ -45,3 +49,36 @@ pub fn toml_table_to_json(toml: BTreeMap<String, toml::Value>) -> Json {
     }
     Json::Object(hashmap)
 }
+
+/// Serializes a JSON tree with serde, exactly as rustc_serialize renders it.
+#[cfg(feature = "serde")]
+pub struct SerializeJson<'a>(pub &'a Json);
+
+#[cfg(feature = "serde")]
+impl<'a> Serialize for SerializeJson<'a> {
+    fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+        match *self.0 {
+            Json::I64(n) => serializer.serialize_i64(n),
+            Json::U64(n) => serializer.serialize_u64(n),
+            Json::F64(n) => serializer.serialize_f64(n),
+            Json::String(ref s) => serializer.serialize_str(s),
+            Json::Boolean(b) => serializer.serialize_bool(b),
+            Json::Null => serializer.serialize_none(),
+            Json::Array(ref values) => {
+                let mut state = try!(serializer.serialize_seq(Some(values.len())));
+                for value in values {
+                    try!(serializer.serialize_seq_elt(&mut state, SerializeJson(value)));
+                }
+                serializer.serialize_seq_end(state)
+            }
+            Json::Object(ref object) => {
+                let mut state = try!(serializer.serialize_map(Some(object.len())));
+                for (key, value) in object {
+                    try!(serializer.serialize_map_key(&mut state, key));
+                    try!(serializer.serialize_map_value(&mut state, SerializeJson(value)));
+                }
+                serializer.serialize_map_end(state)
+            }
+        }
+    }
+}